@@ -0,0 +1,105 @@
+//! Benchmarks of common commands on synthetic fixtures.
+//!
+//! Fixtures are generated with the hidden `rpdf bench --generate-only`
+//! command, and each command runs the compiled binary, like users do.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+/// Path to the compiled binary.
+const RPDF: &str = env!("CARGO_BIN_EXE_rpdf");
+
+/// Number of pages of synthetic fixtures.
+const PAGES: &str = "50";
+
+/// Numbers of annotations of synthetic fixtures.
+const ANNOTATIONS: &[&str] = &["100", "1000", "5000"];
+
+/// Run rpdf with the given arguments, panicking if it fails.
+fn rpdf<I, S>(args: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    let output = Command::new(RPDF)
+        .arg("--quiet")
+        .args(args)
+        .output()
+        .expect("Failed to run rpdf");
+    assert!(
+        output.status.success(),
+        "rpdf failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("Output is not UTF-8")
+}
+
+/// Generate synthetic fixtures in `dir`, returning their paths.
+fn fixtures(dir: &Path) -> Vec<PathBuf> {
+    let mut args = vec!["bench", "--generate-only", "--pages", PAGES, "--dir"];
+    args.push(dir.to_str().expect("Target directory is not UTF-8"));
+    for annotations in ANNOTATIONS {
+        args.extend(["--annotations", annotations]);
+    }
+    rpdf(args).lines().map(PathBuf::from).collect()
+}
+
+fn commands(c: &mut Criterion) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("bench-fixtures");
+    std::fs::create_dir_all(&dir).expect("Failed to create fixtures directory");
+    let merged = dir.join("merged.pdf");
+    let stripped = dir.join("stripped.pdf");
+
+    let mut group = c.benchmark_group("annotations");
+    group.sample_size(10);
+
+    for (fixture, annotations) in fixtures(&dir).iter().zip(ANNOTATIONS) {
+        group.bench_with_input(
+            BenchmarkId::new("stats", annotations),
+            fixture,
+            |b, fixture| {
+                b.iter(|| rpdf([Path::new("annotations"), Path::new("stats"), fixture]));
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("merge", annotations),
+            fixture,
+            |b, fixture| {
+                b.iter(|| {
+                    rpdf([
+                        Path::new("annotations"),
+                        Path::new("merge"),
+                        fixture,
+                        fixture,
+                        Path::new("--force"),
+                        Path::new("--dest"),
+                        &merged,
+                    ])
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("strip", annotations),
+            fixture,
+            |b, fixture| {
+                b.iter(|| {
+                    rpdf([
+                        Path::new("annotations"),
+                        Path::new("strip"),
+                        fixture,
+                        Path::new("--dest"),
+                        &stripped,
+                    ])
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, commands);
+criterion_main!(benches);
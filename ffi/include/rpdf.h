@@ -0,0 +1,76 @@
+#ifndef RPDF_H
+#define RPDF_H
+
+/* Generated with cbindgen from src/lib.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Merge the annotations of several copies of a document into the first one,
+ * and write the result to `output`, like `rpdf annotations merge`.
+ *
+ * Links are only kept from the first file. Returns `0` on success and `-1`
+ * on failure.
+ *
+ * # Safety
+ *
+ * `inputs` must point to `count` NUL-terminated strings, and `output` must
+ * be a NUL-terminated string.
+ */
+int rpdf_merge_annotations(const char *const *inputs, size_t count, const char *output);
+
+/**
+ * Strip the annotations of a document, except links, and write the result
+ * to `output`, like `rpdf annotations strip`.
+ *
+ * Returns `0` on success and `-1` on failure.
+ *
+ * # Safety
+ *
+ * `input` and `output` must be NUL-terminated strings.
+ */
+int rpdf_strip_annotations(const char *input, const char *output);
+
+/**
+ * Return the number of annotations of each subtype of a document, as JSON,
+ * e.g., `{"total":{"Link":2},"pages":[{"page":1,"annotations":{"Link":2}}]}`.
+ *
+ * Pages without annotations are not listed. The string must be freed with
+ * [`rpdf_string_free`]. Returns `NULL` on failure.
+ *
+ * # Safety
+ *
+ * `input` must be a NUL-terminated string.
+ */
+char *rpdf_stats_json(const char *input);
+
+/**
+ * Free a string returned by rpdf.
+ *
+ * # Safety
+ *
+ * `string` must be null or a string returned by rpdf, not freed yet.
+ */
+void rpdf_string_free(char *string);
+
+/**
+ * Return the message of the last error of the calling thread, or `NULL` if
+ * there was none.
+ *
+ * The message is owned by rpdf, and valid until another rpdf function fails
+ * on the same thread.
+ */
+const char *rpdf_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RPDF_H */
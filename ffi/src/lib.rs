@@ -0,0 +1,187 @@
+//! C bindings of rpdf, so that other programs, e.g., document servers, can
+//! merge and strip annotations, or count them, without spawning processes.
+//!
+//! Functions that return an `int` return `0` on success and `-1` on failure,
+//! and functions that return a pointer return `NULL` on failure. In both
+//! cases, [`rpdf_last_error`] describes the error.
+//!
+//! The C header is `include/rpdf.h`, which is generated with:
+//!
+//! ```bash
+//! cbindgen --config cbindgen.toml --output include/rpdf.h
+//! ```
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString, c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    ptr, slice,
+};
+
+use anyhow::{Context, Result, bail};
+use rpdf::pdf::{self, SaveOptions, annotations, merge::AnnotationMerger};
+
+/// Annotation subtypes that are neither merged nor stripped, as by default in
+/// the command-line tool.
+const EXCLUDED_SUBTYPES: &[&str] = &["Link"];
+
+thread_local! {
+    /// Message of the last error of the thread, if any.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Set the message of the last error of the thread.
+fn set_last_error(message: String) {
+    // Interior NUL bytes would truncate the message.
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run a fallible function, recording its error, and never unwinding into
+/// the caller, which is undefined behavior across the C ABI.
+fn catch<T>(f: impl FnOnce() -> Result<T>) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(error)) => {
+            set_last_error(format!("{error:#}"));
+            None
+        },
+        Err(_) => {
+            set_last_error("rpdf panicked, this is a bug".to_string());
+            None
+        },
+    }
+}
+
+/// Return the path held by a C string.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string.
+unsafe fn to_path(path: *const c_char) -> Result<PathBuf> {
+    if path.is_null() {
+        bail!("Path is null.");
+    }
+    let path = CStr::from_ptr(path)
+        .to_str()
+        .context("Path is not valid UTF-8.")?;
+    Ok(PathBuf::from(path))
+}
+
+/// Return the subtypes excluded from merging and stripping.
+fn excluded_subtypes() -> Vec<String> {
+    EXCLUDED_SUBTYPES.iter().map(ToString::to_string).collect()
+}
+
+/// Merge the annotations of several copies of a document into the first one,
+/// and write the result to `output`, like `rpdf annotations merge`.
+///
+/// Links are only kept from the first file. Returns `0` on success and `-1`
+/// on failure.
+///
+/// # Safety
+///
+/// `inputs` must point to `count` NUL-terminated strings, and `output` must
+/// be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_merge_annotations(
+    inputs: *const *const c_char,
+    count: usize,
+    output: *const c_char,
+) -> c_int {
+    let merged = catch(|| {
+        if inputs.is_null() || count < 2 {
+            bail!("At least two PDF files are needed, found {count}.");
+        }
+        let files = slice::from_raw_parts(inputs, count)
+            .iter()
+            .map(|&input| to_path(input))
+            .collect::<Result<Vec<_>>>()?;
+        let output = to_path(output)?;
+        let exclude = excluded_subtypes();
+
+        let mut merger = AnnotationMerger::new(pdf::load(&files[0], None)?);
+        for file in &files[1..] {
+            merger.add(pdf::load(file, None)?, &exclude, None);
+        }
+        let (mut document, _) = merger.finish(true);
+        pdf::save(&mut document, &output, SaveOptions::default())
+    });
+    merged.map_or(-1, |()| 0)
+}
+
+/// Strip the annotations of a document, except links, and write the result
+/// to `output`, like `rpdf annotations strip`.
+///
+/// Returns `0` on success and `-1` on failure.
+///
+/// # Safety
+///
+/// `input` and `output` must be NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_strip_annotations(
+    input: *const c_char,
+    output: *const c_char,
+) -> c_int {
+    let stripped = catch(|| {
+        let input = to_path(input)?;
+        let output = to_path(output)?;
+
+        let mut document = pdf::load(&input, None)?;
+        let ids: Vec<_> = annotations::strip_candidates(&document, &excluded_subtypes(), false)
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect();
+        annotations::delete_annotations(&mut document, &ids);
+        pdf::save(&mut document, &output, SaveOptions::default())
+    });
+    stripped.map_or(-1, |()| 0)
+}
+
+/// Return the number of annotations of each subtype of a document, as JSON,
+/// e.g., `{"total":{"Link":2},"pages":[{"page":1,"annotations":{"Link":2}}]}`.
+///
+/// Pages without annotations are not listed. The string must be freed with
+/// [`rpdf_string_free`]. Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `input` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_stats_json(input: *const c_char) -> *mut c_char {
+    let json = catch(|| {
+        let input = to_path(input)?;
+        let document = pdf::load(&input, None)?;
+
+        let json = serde_json::to_string(&annotations::stats(&document))?;
+        Ok(CString::new(json)?)
+    });
+    json.map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Free a string returned by rpdf.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by rpdf, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Return the message of the last error of the calling thread, or `NULL` if
+/// there was none.
+///
+/// The message is owned by rpdf, and valid until another rpdf function fails
+/// on the same thread.
+#[no_mangle]
+pub extern "C" fn rpdf_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |last| last.as_ptr())
+    })
+}
@@ -0,0 +1,110 @@
+use std::{
+    ffi::{CStr, CString},
+    path::Path,
+};
+
+use lopdf::Document;
+use rpdf::pdf::fixtures::Fixture;
+use rpdf_ffi::{
+    rpdf_last_error, rpdf_merge_annotations, rpdf_stats_json, rpdf_string_free,
+    rpdf_strip_annotations,
+};
+use tempfile::TempDir;
+
+/// Write a fixture with the given number of annotations, one subtype per
+/// page in turn, and return its path as a C string.
+fn fixture(dir: &Path, name: &str, annotations: usize) -> CString {
+    let path = dir.join(name);
+    Fixture {
+        pages: 2,
+        annotations,
+        subtypes: vec!["Highlight".into(), "Link".into()],
+        ..Fixture::default()
+    }
+    .build()
+    .save(&path)
+    .unwrap();
+    CString::new(path.to_str().unwrap()).unwrap()
+}
+
+/// Return the statistics of a file, as JSON.
+fn stats(path: &CString) -> serde_json::Value {
+    unsafe {
+        let json = rpdf_stats_json(path.as_ptr());
+        assert!(!json.is_null());
+        let value = serde_json::from_slice(CStr::from_ptr(json).to_bytes()).unwrap();
+        rpdf_string_free(json);
+        value
+    }
+}
+
+#[test]
+fn stats_count_annotations_per_subtype() {
+    let dir = TempDir::new().unwrap();
+    let file = fixture(dir.path(), "fixture.pdf", 4);
+
+    assert_eq!(
+        stats(&file),
+        serde_json::json!({
+            "total": { "Highlight": 2, "Link": 2 },
+            "pages": [
+                { "page": 1, "annotations": { "Highlight": 1, "Link": 1 } },
+                { "page": 2, "annotations": { "Highlight": 1, "Link": 1 } },
+            ],
+        })
+    );
+}
+
+#[test]
+fn merge_and_strip_annotations() {
+    let dir = TempDir::new().unwrap();
+    let files = [
+        fixture(dir.path(), "a.pdf", 4),
+        fixture(dir.path(), "b.pdf", 4),
+    ];
+    let inputs: Vec<_> = files.iter().map(|file| file.as_ptr()).collect();
+    let merged = CString::new(dir.path().join("merged.pdf").to_str().unwrap()).unwrap();
+    let stripped = CString::new(dir.path().join("stripped.pdf").to_str().unwrap()).unwrap();
+
+    unsafe {
+        assert_eq!(
+            rpdf_merge_annotations(inputs.as_ptr(), inputs.len(), merged.as_ptr()),
+            0
+        );
+        assert_eq!(
+            rpdf_strip_annotations(merged.as_ptr(), stripped.as_ptr()),
+            0
+        );
+    }
+
+    // Links are only kept from the first file, and never stripped.
+    assert_eq!(
+        stats(&merged)["total"],
+        serde_json::json!({ "Highlight": 4, "Link": 2 })
+    );
+    assert_eq!(stats(&stripped)["total"], serde_json::json!({ "Link": 2 }));
+    Document::load(dir.path().join("stripped.pdf")).unwrap();
+}
+
+#[test]
+fn failures_set_last_error() {
+    let missing = CString::new("missing.pdf").unwrap();
+    let output = CString::new("output.pdf").unwrap();
+
+    unsafe {
+        assert_eq!(
+            rpdf_strip_annotations(missing.as_ptr(), output.as_ptr()),
+            -1
+        );
+        let error = CStr::from_ptr(rpdf_last_error()).to_str().unwrap();
+        assert!(error.contains("missing.pdf"), "{error}");
+
+        assert_eq!(
+            rpdf_merge_annotations(&missing.as_ptr(), 1, output.as_ptr()),
+            -1
+        );
+        let error = CStr::from_ptr(rpdf_last_error()).to_str().unwrap();
+        assert_eq!(error, "At least two PDF files are needed, found 1.");
+        assert!(rpdf_stats_json(std::ptr::null()).is_null());
+    }
+}
@@ -0,0 +1,155 @@
+//! Python bindings of rpdf, so that Python tools get structured results,
+//! e.g., annotations as objects, rather than parsing the output of the
+//! command-line tool.
+//!
+//! They are built with maturin, e.g., `maturin develop -m py/Cargo.toml`,
+//! and imported as `rpdf`. Errors are raised as `RuntimeError`.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{Result, bail};
+use pyo3::prelude::*;
+use rpdf::pdf::{self, SaveOptions, annotations, merge::AnnotationMerger};
+
+/// Annotation of a page, as returned by `export`.
+#[pyclass(name = "Annotation", module = "rpdf", frozen, get_all)]
+#[derive(Clone, Debug)]
+struct PyAnnotation {
+    /// Page number, from 1.
+    page: u32,
+    subtype: String,
+    author: Option<String>,
+    /// Modification date, e.g., `2024-01-31 12:00`.
+    date: Option<String>,
+    contents: Option<String>,
+    /// Rectangle of the annotation on the page, in points.
+    rect: Option<[f32; 4]>,
+}
+
+impl From<annotations::Annotation> for PyAnnotation {
+    fn from(annotation: annotations::Annotation) -> Self {
+        Self {
+            page: annotation.page,
+            subtype: annotation.subtype,
+            author: annotation.author,
+            date: annotation.date,
+            contents: annotation.contents,
+            rect: annotation.rect,
+        }
+    }
+}
+
+#[pymethods]
+impl PyAnnotation {
+    fn __repr__(&self) -> String {
+        let author = self
+            .author
+            .as_ref()
+            .map_or_else(|| "None".to_string(), |author| format!("{author:?}"));
+        format!(
+            "Annotation(page={}, subtype={:?}, author={author})",
+            self.page, self.subtype
+        )
+    }
+}
+
+/// Merge the annotations of copies of a document into the first one, write
+/// the result to `dest`, and return the number of added annotations.
+///
+/// Annotations whose subtype is excluded, by default links, are only kept
+/// from the first file.
+#[pyfunction]
+#[pyo3(signature = (files, dest, exclude = vec!["Link".to_string()], password = None))]
+fn merge(
+    py: Python<'_>,
+    files: Vec<PathBuf>,
+    dest: PathBuf,
+    exclude: Vec<String>,
+    password: Option<String>,
+) -> Result<usize> {
+    py.allow_threads(|| {
+        if files.len() < 2 {
+            bail!("At least two PDF files are needed, found {}.", files.len());
+        }
+        let mut merger = AnnotationMerger::new(pdf::load(&files[0], password.as_deref())?);
+        for file in &files[1..] {
+            merger.add(pdf::load(file, password.as_deref())?, &exclude, None);
+        }
+        let (mut document, added) = merger.finish(true);
+        pdf::save(&mut document, &dest, SaveOptions::default())?;
+        Ok(added)
+    })
+}
+
+/// Strip the annotations of a document, write the result to `dest`, and
+/// return the number of removed annotations.
+///
+/// Annotations whose subtype is excluded, by default links, are kept. With
+/// `media`, only annotations that embed media are stripped.
+#[pyfunction]
+#[pyo3(signature = (file, dest, exclude = vec!["Link".to_string()], media = false, password = None))]
+fn strip(
+    py: Python<'_>,
+    file: PathBuf,
+    dest: PathBuf,
+    exclude: Vec<String>,
+    media: bool,
+    password: Option<String>,
+) -> Result<usize> {
+    py.allow_threads(|| {
+        let mut document = pdf::load(&file, password.as_deref())?;
+        let ids: Vec<_> = annotations::strip_candidates(&document, &exclude, media)
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect();
+        annotations::delete_annotations(&mut document, &ids);
+        pdf::save(&mut document, &dest, SaveOptions::default())?;
+        Ok(ids.len())
+    })
+}
+
+/// Return the number of annotations of each subtype, for each page in
+/// order, e.g., `[{"Highlight": 2}, {}]`.
+#[pyfunction]
+#[pyo3(signature = (file, password = None))]
+fn stats(
+    py: Python<'_>,
+    file: PathBuf,
+    password: Option<String>,
+) -> Result<Vec<BTreeMap<String, usize>>> {
+    py.allow_threads(|| {
+        let document = pdf::load_without_images(&file, password.as_deref())?;
+        Ok(annotations::subtype_counts(&document, false))
+    })
+}
+
+/// Return the annotations of a document, in page order, except those whose
+/// subtype is excluded, by default popups.
+#[pyfunction]
+#[pyo3(signature = (file, exclude = vec!["Popup".to_string()], password = None))]
+fn export(
+    py: Python<'_>,
+    file: PathBuf,
+    exclude: Vec<String>,
+    password: Option<String>,
+) -> Result<Vec<PyAnnotation>> {
+    py.allow_threads(|| {
+        let document = pdf::load_without_images(&file, password.as_deref())?;
+        Ok(annotations::read_annotations(&document, &exclude, false)
+            .into_iter()
+            .map(PyAnnotation::from)
+            .collect())
+    })
+}
+
+/// Work with the annotations of PDF files.
+#[pymodule]
+#[pyo3(name = "rpdf")]
+fn rpdf_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyAnnotation>()?;
+    module.add_function(wrap_pyfunction!(merge, module)?)?;
+    module.add_function(wrap_pyfunction!(strip, module)?)?;
+    module.add_function(wrap_pyfunction!(stats, module)?)?;
+    module.add_function(wrap_pyfunction!(export, module)?)?;
+    Ok(())
+}
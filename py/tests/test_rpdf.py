@@ -0,0 +1,45 @@
+"""Smoke tests of the Python bindings, run with pytest once the module is
+built, e.g., with `maturin develop -m py/Cargo.toml`."""
+
+from pathlib import Path
+
+import pytest
+
+import rpdf
+
+SAMPLE = Path(__file__).parents[2] / "tests" / "sample_highlighted.pdf"
+
+
+def test_export_lists_annotations():
+    annotations = rpdf.export(SAMPLE)
+    highlights = [a for a in annotations if a.subtype == "Highlight"]
+
+    assert len(highlights) == 7
+    assert [a.page for a in annotations] == sorted(a.page for a in annotations)
+    assert repr(highlights[0]).startswith('Annotation(page=1, subtype="Highlight"')
+    assert len(highlights[0].rect) == 4
+
+
+def test_stats_counts_subtypes_per_page():
+    stats = rpdf.stats(SAMPLE)
+
+    assert sum(page.get("Highlight", 0) for page in stats) == 7
+
+
+def test_strip_then_merge_restores_annotations(tmp_path):
+    stripped = tmp_path / "stripped.pdf"
+    merged = tmp_path / "merged.pdf"
+
+    assert rpdf.strip(SAMPLE, stripped) >= 7
+    assert not any(a.subtype == "Highlight" for a in rpdf.export(stripped))
+
+    assert rpdf.merge([stripped, SAMPLE], merged) >= 7
+    highlights = [a for a in rpdf.export(merged) if a.subtype == "Highlight"]
+    assert len(highlights) == 7
+
+
+def test_errors_are_raised(tmp_path):
+    with pytest.raises(RuntimeError, match="Failed to read PDF from"):
+        rpdf.export(tmp_path / "missing.pdf")
+    with pytest.raises(RuntimeError, match="At least two PDF files are needed"):
+        rpdf.merge([SAMPLE], tmp_path / "merged.pdf")
@@ -1,71 +1,239 @@
 use std::{
-    collections::{HashMap, HashSet},
-    path::PathBuf,
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result};
-use clap::{ArgAction, Args, Parser, Subcommand};
-use log::{Level::Info, debug, error, info, log_enabled, trace, warn};
+use anyhow::{Context, Result, bail};
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::{ArgValueCandidates, ArgValueCompleter};
 use lopdf::{Document, Object, ObjectId};
 use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
 use tabled::{
     builder::Builder,
     settings::{Color, Panel, Style, style::BorderColor},
 };
 use termcolor::WriteColor;
+use tracing::{Level, debug, enabled, info, trace, warn};
 
-use super::traits::Execute;
+#[cfg(feature = "convert")]
+use crate::pdf::heatmap::{self, Weight};
+use crate::pdf::{
+    annotations::{self, Annotation, AnnotationBuilder, Changes, Flag, Shape, Stamp},
+    document::DocumentExt,
+    filter::Filter,
+    layers,
+    merge::AnnotationMerger,
+    pdfstring,
+    selection::PageSelection,
+};
+
+use super::{
+    complete::{annotation_subtypes, pdf_files},
+    context::ExecutionContext,
+    naming::Naming,
+    output::{self, Overwrite, OverwritePolicy},
+    text::write_record,
+    traits::Execute,
+};
 
 /// Stats command.
 #[derive(Args, Clone, Debug)]
 struct Stats {
     /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
     file: PathBuf,
     /// Show per page statistics.
     #[clap(short, long)]
     per_page: bool,
+    /// Only count annotations of a category, e.g., form fields, or all
+    /// annotations, including pop-ups.
+    #[clap(long, value_enum, default_value_t)]
+    category: StatsCategory,
+    /// Skip hidden annotations, i.e., those with the `Hidden` or `NoView`
+    /// flag, which tools often leave for their own use.
+    #[clap(long)]
+    skip_hidden: bool,
+    /// Older revision of the file, to count the annotations of each subtype
+    /// that were added and removed since, e.g., `--compare old.pdf new.pdf`.
+    #[clap(long, value_name = "OLD", add = ArgValueCompleter::new(pdf_files()))]
+    compare: Option<PathBuf>,
 }
 
-impl Execute for Stats {
-    fn execute<W>(&self, stdout: &mut W) -> Result<()>
+/// Categories of annotations counted by stats.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum StatsCategory {
+    /// Comments and markups, e.g., notes and highlights.
+    #[default]
+    Review,
+    /// Form fields.
+    Forms,
+    /// Links.
+    Links,
+    /// Sounds, videos and 3D models.
+    Multimedia,
+    /// All annotations.
+    All,
+}
+
+impl StatsCategory {
+    /// Return whether annotations of a subtype belong to this category.
+    fn contains(self, subtype: &str) -> bool {
+        let category = match self {
+            Self::Review => annotations::Category::Review,
+            Self::Forms => annotations::Category::Forms,
+            Self::Links => annotations::Category::Links,
+            Self::Multimedia => annotations::Category::Multimedia,
+            Self::All => return true,
+        };
+        annotations::category(subtype) == Some(category)
+    }
+}
+
+/// Return the annotations of a subtype that were added and removed, e.g.,
+/// `+2 -1`, as a table cell.
+fn changes_cell(changes: Option<&Changes>, color: bool) -> String {
+    let Some(&Changes { added, removed }) = changes else {
+        return if color {
+            "0".dimmed().to_string()
+        } else {
+            "0".to_string()
+        };
+    };
+    let added = (added > 0).then(|| format!("+{added}"));
+    let removed = (removed > 0).then(|| format!("-{removed}"));
+    let (added, removed) = if color {
+        (
+            added.map(|added| added.green().to_string()),
+            removed.map(|removed| removed.red().to_string()),
+        )
+    } else {
+        (added, removed)
+    };
+    added
+        .into_iter()
+        .chain(removed)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Stats {
+    /// Print the annotations of each subtype that were added and removed
+    /// since an older revision of the file.
+    fn compare<W>(&self, ctx: &mut ExecutionContext<W>, old: &Path) -> Result<()>
     where
         W: WriteColor,
     {
-        let document = Document::load(&self.file)
-            .with_context(|| format!("Failed to read PDF from: {:?}.", self.file))?;
-
-        let mut counters = vec![];
-        let mut subtypes = HashSet::new();
-
-        for page in document.page_iter() {
-            let mut counter = HashMap::new();
-            for annotation in document
-                .get_page_annotations(page)
-                .with_context(|| format!("Failed to get page annotations for page ID {page:?}."))?
-            {
-                let subtype = annotation
-                    .get_deref(b"Subtype", &document)
-                    .and_then(Object::as_name_str)
-                    .unwrap_or("");
+        let old_document = ctx.load_document_without_images(old)?;
+        let new_document = ctx.load_document_without_images(&self.file)?;
+
+        let mut changes =
+            annotations::diff_subtype_counts(&old_document, &new_document, self.skip_hidden);
+        for page in &mut changes {
+            page.retain(|subtype, _| self.category.contains(subtype));
+        }
+        let subtypes: BTreeSet<String> = changes
+            .iter()
+            .flat_map(|page| page.keys().cloned())
+            .collect();
+
+        if subtypes.is_empty() {
+            writeln!(ctx.stdout, "{}", ctx.i18n.message("stats-no-change", &[]))?;
+            return Ok(());
+        }
+
+        let mut builder = Builder::default();
+        if self.per_page {
+            let mut header = vec![ctx.i18n.message("stats-page", &[])];
+            header.extend(subtypes.iter().cloned());
+            builder.set_header(header);
+
+            for (page, page_changes) in (1..).zip(&changes) {
+                if page_changes.is_empty() {
+                    continue;
+                }
+                let mut record = vec![format!("{page}")];
+                record.extend(
+                    subtypes.iter().map(|subtype| {
+                        changes_cell(page_changes.get(subtype), ctx.color.enabled())
+                    }),
+                );
+                builder.push_record(record);
+            }
+        } else {
+            builder.set_header(subtypes.iter().cloned());
 
-                *counter.entry(subtype).or_insert(0) += 1;
-                subtypes.insert(subtype.to_owned());
+            let mut total: HashMap<&str, Changes> = HashMap::with_capacity(subtypes.len());
+            for (subtype, page_changes) in changes.iter().flatten() {
+                let entry = total.entry(subtype.as_str()).or_default();
+                entry.added += page_changes.added;
+                entry.removed += page_changes.removed;
             }
-            counters.push(counter);
+            builder.push_record(
+                subtypes
+                    .iter()
+                    .map(|subtype| changes_cell(total.get(subtype.as_str()), ctx.color.enabled())),
+            );
         }
 
+        let mut table = builder.build();
+        table
+            .with(Panel::header(ctx.i18n.message(
+                "stats-compare-title",
+                &[
+                    ("old", old.to_str().unwrap().into()),
+                    ("new", self.file.to_str().unwrap().into()),
+                ],
+            )))
+            .with(Style::modern());
+
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+
+        writeln!(ctx.stdout, "{table}")?;
+        Ok(())
+    }
+}
+
+impl Execute for Stats {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        if let Some(old) = &self.compare {
+            return self.compare(ctx, old);
+        }
+        let document = ctx.load_document_without_images(&self.file)?;
+
+        let mut counters = annotations::subtype_counts(&document, self.skip_hidden);
+        let has_annotations = counters.iter().any(|counter| !counter.is_empty());
+        for counter in &mut counters {
+            counter.retain(|subtype, _| self.category.contains(subtype));
+        }
+        let subtypes: HashSet<String> = counters
+            .iter()
+            .flat_map(|counter| counter.keys().cloned())
+            .collect();
+
         let mut builder = Builder::default();
         let mut subtypes: Vec<_> = subtypes.iter().collect();
         subtypes.sort();
 
         if subtypes.is_empty() {
-            writeln!(stdout, "No annotation was found in the given file.")?;
+            let id = if has_annotations {
+                "stats-no-annotation-in-category"
+            } else {
+                "stats-no-annotation"
+            };
+            writeln!(ctx.stdout, "{}", ctx.i18n.message(id, &[]))?;
             return Ok(());
         }
 
         if self.per_page {
             let mut header = Vec::with_capacity(1 + subtypes.len());
-            header.push("Page no.".to_string());
+            header.push(ctx.i18n.message("stats-page", &[]));
 
             for subtype in &subtypes {
                 header.push(subtype.to_string());
@@ -82,7 +250,7 @@ impl Execute for Stats {
                 for subtype in &subtypes {
                     record.push(counter.get(subtype.as_str()).map_or_else(
                         || {
-                            if stdout.supports_color() {
+                            if ctx.color.enabled() {
                                 "0".dimmed().to_string()
                             } else {
                                 "0".to_string()
@@ -116,18 +284,38 @@ impl Execute for Stats {
 
         let mut table = builder.build();
         table
-            .with(Panel::header(format!(
-                "Annotations stats for: {}",
-                self.file.to_str().unwrap()
+            .with(Panel::header(ctx.i18n.message(
+                "stats-title",
+                &[("file", self.file.to_str().unwrap().into())],
             )))
             .with(Style::modern());
 
-        if stdout.supports_color() {
-            trace!("Stdout supports color so table will be colored");
+        if ctx.color.enabled() {
+            trace!("Color is enabled so table will be colored");
             table.with(BorderColor::filled(Color::FG_GREEN));
         }
 
-        writeln!(stdout, "{table}")?;
+        writeln!(ctx.stdout, "{table}")?;
+
+        let (media, payload) = document
+            .page_iter()
+            .flat_map(|page| annotations::page_annotation_ids(&document, page))
+            .filter(|&id| {
+                annotations::MEDIA_SUBTYPES.contains(&annotations::subtype(&document, id))
+            })
+            .fold((0, 0), |(count, bytes), id| {
+                (count + 1, bytes + annotations::payload_size(&document, id))
+            });
+        if media > 0 {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "stats-media",
+                    &[("count", media.into()), ("bytes", payload.into())]
+                )
+            )?;
+        }
 
         Ok(())
     }
@@ -137,81 +325,94 @@ impl Execute for Stats {
 #[derive(Args, Clone, Debug)]
 struct Merge {
     /// PDF filepaths (at least two files).
-    #[clap(num_args(2..), value_names = ["FILE 1", "FILE 2"], next_line_help = true, required = true)]
+    ///
+    /// Directories are replaced by the PDF files they contain, in
+    /// alphabetical order, e.g., one file per reviewer.
+    #[clap(num_args(2..), value_names = ["FILE 1", "FILE 2"], next_line_help = true, required = true, add = ArgValueCompleter::new(pdf_files()))]
     files: Vec<PathBuf>,
     /// Output file where resulting PDF is written.
-    #[clap(short, long, default_value = "merged_annotations.pdf")]
+    #[clap(short, long, default_value = "merged_annotations.pdf", value_hint = ValueHint::FilePath)]
     dest: PathBuf,
     /// Exclude a given annotation type when merging (multiple values allowed).
     ///
     /// This is especially useful to avoid duplicating links, which are
     /// categorized as "annotations" too. Excluded annotation will only be
     /// kept in <FILE 1>.
-    #[clap(short, long, default_value = "Link", action = ArgAction::Append)]
+    #[clap(short, long, default_value = "Link", action = ArgAction::Append, add = ArgValueCandidates::new(annotation_subtypes))]
     exclude: Vec<String>,
-    /// Overwrite output file if exists.
-    #[clap(short = 'f', long = "force")]
+    /// Overwrite output file if exists, like the global `--force`, kept for
+    /// scripts written before it.
+    #[clap(short = 'f', hide = true)]
     overwrite: bool,
+    /// Compress the uncompressed streams of imported annotations, e.g.,
+    /// appearance streams. `false` makes merging large files faster.
+    #[clap(long, default_value_t = true, action = ArgAction::Set, value_name = "BOOL")]
+    compress_output: bool,
+    /// Set the author of imported annotations that have none to the name of
+    /// their file, without extension, e.g., "alice" for "alice.pdf".
+    #[clap(long)]
+    tag_author_from_filename: bool,
+    /// Move annotations to the same place of the pages of <FILE 1>, as
+    /// shown, when pages differ in size or rotation, e.g., for copies that
+    /// were scanned or exported differently.
+    #[clap(long)]
+    rescale: bool,
+    /// Skip hidden annotations, i.e., those with the `Hidden` or `NoView`
+    /// flag, which tools often leave for their own use.
+    #[clap(long)]
+    skip_hidden: bool,
+    /// Only merge annotations that pass a filter, e.g.,
+    /// `author =~ "alice|bob"`, see `export --help`.
+    #[clap(long)]
+    filter: Option<Filter>,
 }
 
-/// Get mutable annotations (references) to a given page id.
-fn get_page_annotations_mut(document: &mut Document, page_id: ObjectId) -> &mut Vec<Object> {
-    match document.get_dictionary(page_id).unwrap().get(b"Annots") {
-        Ok(Object::Reference(ref id)) => {
-            trace!("This page contains a reference to a vector of annotations");
-            document
-                .get_object_mut(*id)
-                .and_then(Object::as_array_mut)
-                .unwrap()
-        },
-        Ok(Object::Array(_)) => {
-            trace!("This page contains a vector of annotations");
-            let page = document.get_dictionary_mut(page_id).unwrap();
-            page.get_mut(b"Annots")
-                .and_then(Object::as_array_mut)
-                .unwrap()
-        },
-        Err(_) => {
-            trace!(
-                "This page (ID: {:?}) does not contain any annotations, inserting an empty array.",
-                page_id
-            );
-            let page_map = document
-                .get_dictionary_mut(page_id)
-                .unwrap()
-                .as_hashmap_mut();
-            Object::as_array_mut(
-                page_map
-                    .entry(b"Annots".to_vec())
-                    .or_insert(Object::Array(vec![])),
-            )
-            .unwrap()
-        },
-        _ => unreachable!(),
+/// Replace directories by the PDF files they contain, in alphabetical order.
+fn expand_directories(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+        let mut entries: Vec<PathBuf> = fs::read_dir(path)
+            .with_context(|| format!("Failed to read directory: {path:?}"))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+            })
+            .collect();
+        entries.sort();
+        files.extend(entries);
     }
+    Ok(files)
 }
 
 impl Execute for Merge {
-    fn execute<W>(&self, stdout: &mut W) -> Result<()>
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
     where
         W: WriteColor,
     {
-        if self.dest.exists()
-            && !self.overwrite
-            && !dialoguer::Confirm::new()
-                .with_prompt(format!(
-                    "Output file {:?} already exists. Do you want to overwrite it?",
-                    self.dest
-                ))
-                .interact()
-                .unwrap_or(false)
-        {
+        if self.overwrite {
+            ctx.overwrite = Overwrite::new(OverwritePolicy::Force);
+        }
+        // Merging is slow, so users are asked before it starts.
+        if !output::resolve_destination(ctx, &self.dest)? {
             return Ok(());
         }
-        if log_enabled!(Info) {
+        let files = expand_directories(&self.files)?;
+        if files.len() < 2 {
+            bail!("At least two PDF files are needed, found {}.", files.len());
+        }
+        if enabled!(Level::INFO) {
             let msg = format!(
                 "Processing documents: {}",
-                self.files
+                files
                     .iter()
                     .enumerate()
                     .map(|(document_number, file)| format!("{:?} (#{})", file, document_number))
@@ -221,167 +422,982 @@ impl Execute for Merge {
 
             info!("{}.", msg);
         }
-        let mut main = Document::load(&self.files[0]).with_context(|| {
-            format!(
-                "Failed to read PDF from: {}",
-                self.files[0].to_str().unwrap()
-            )
-        })?;
+        let mut merger = AnnotationMerger::new(ctx.load_document(&files[0])?)
+            .rescale(self.rescale)
+            .skip_hidden(self.skip_hidden)
+            .filter(self.filter.clone());
 
-        let pages = main.get_pages();
-        debug!("Reference document contains {} pages", pages.len());
+        for (document_number, file) in (1..).zip(&files[1..]) {
+            debug!("Processing document #{document_number}");
+            let document = ctx.load_document(file)?;
+            let author = self
+                .tag_author_from_filename
+                .then(|| file.file_stem().unwrap_or_default().to_string_lossy());
+            merger.add(document, &self.exclude, author.as_deref());
+        }
 
-        // Maps page number (note object id) to annotations
-        let mut annotations_map = HashMap::new();
+        let (mut main, added) = merger.finish(self.compress_output);
+        ctx.summary.annotations_added(added);
+        ctx.save_document(&mut main, &self.dest)?;
 
-        for (document_number, file) in (1..).zip(&self.files[1..]) {
-            debug!("Processing document #{document_number}");
-            let document = Document::load(file)
-                .with_context(|| format!("Failed to read PDF from: {}", file.to_str().unwrap()))?;
-
-            for (page_number, page) in (1u32..).zip(document.page_iter()) {
-                if !pages.contains_key(&page_number) {
-                    warn!(
-                        "Reference document does not contain page number {}. Annotations from \
-                         this page will be ignored.",
-                        page_number
-                    );
-                }
-                document
-                    .get_page_annotations(page)
-                    .with_context(|| {
-                        format!("Failed to get page annotations for page ID {page:?}.")
-                    })?
-                    .into_iter()
-                    .filter(|annotation| {
-                        let subtype = annotation
-                            .get_deref(b"Subtype", &document)
-                            .and_then(Object::as_name_str)
-                            .unwrap_or("");
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "merge-success",
+                    &[
+                        ("count", files.len().into()),
+                        ("dest", format!("{:?}", self.dest.to_str().unwrap()).into()),
+                    ]
+                )
+            )?;
+        }
 
-                        return !self.exclude.iter().any(|e| subtype == e);
-                    })
-                    .for_each(|annotation| {
-                        trace!(
-                            "Found annotation on page {page_number} in document \
-                             #{document_number}, inserting it inside reference document"
-                        );
-                        let id = main.add_object(annotation.clone());
-                        annotations_map
-                            .entry(page_number)
-                            .or_insert(vec![])
-                            .push(Object::Reference(id));
-                    });
+        Ok(())
+    }
+}
+
+/// Strip command.
+#[derive(Args, Clone, Debug)]
+struct Strip {
+    /// PDF filepaths.
+    #[clap(num_args(1..), required = true, add = ArgValueCompleter::new(pdf_files()))]
+    files: Vec<PathBuf>,
+    /// Output file where resulting PDF is written, when stripping a single
+    /// file.
+    #[clap(short, long, default_value = "stripped_annotations.pdf", value_hint = ValueHint::FilePath, conflicts_with = "name_template")]
+    dest: PathBuf,
+    /// Template of the names of output files, with `{stem}` (name of the
+    /// input file), `{cmd}` and `{date}` placeholders, e.g.,
+    /// `{stem}_{cmd}_{date}.pdf`. Required to strip several files.
+    #[clap(short, long)]
+    name_template: Option<String>,
+    /// Exclude a given annotation type from stripping (multiple values
+    /// allowed).
+    #[clap(short, long, default_value = "Link", action = ArgAction::Append, add = ArgValueCandidates::new(annotation_subtypes))]
+    exclude: Vec<String>,
+    /// Ask before deleting each annotation.
+    ///
+    /// For every annotation that would be stripped, answer `y` to delete it,
+    /// `n` to keep it, `a` to delete it and all the remaining ones, or `q` to
+    /// keep it and all the remaining ones.
+    #[clap(short, long)]
+    interactive: bool,
+    /// Only strip annotations that embed media, i.e., `3D`, `RichMedia` and
+    /// `Screen` annotations, e.g., to print files with 3D models.
+    ///
+    /// Their models, videos and other assets are removed too.
+    #[clap(short, long)]
+    media: bool,
+    /// Only strip annotations that pass a filter, e.g.,
+    /// `subtype == "Ink" && page > 2`, see `export --help`.
+    #[clap(long)]
+    filter: Option<Filter>,
+}
+
+/// Maximum number of characters of an annotation's contents shown when
+/// prompting.
+const CONTENTS_PREVIEW_LEN: usize = 60;
+
+impl Strip {
+    /// Ask the user which annotations, among the candidates, to delete.
+    ///
+    /// Candidates are given as (page number, annotation id) pairs.
+    fn select_interactively(
+        &self,
+        document: &Document,
+        candidates: Vec<(u32, ObjectId)>,
+    ) -> Result<Vec<ObjectId>> {
+        let total = candidates.len();
+        let mut candidates = (1..).zip(candidates);
+        let mut delete_ids = vec![];
+
+        while let Some((index, (page_number, id))) = candidates.next() {
+            // `/Annots` may reference objects that do not exist, i.e., null
+            // ones: they are listed too, as deleting them removes the dead
+            // references.
+            let (subtype, contents) = match document.get_object(id) {
+                Err(lopdf::Error::ObjectNotFound) | Ok(Object::Null) => {
+                    ("<missing>", String::new())
+                },
+                object => {
+                    let annotation = object.and_then(Object::as_dict).with_context(|| {
+                        format!("Annotation {id:?} on page {page_number} is not a dictionary")
+                    })?;
+                    let subtype = annotation
+                        .get_deref(b"Subtype", document)
+                        .and_then(Object::as_name_str)
+                        .unwrap_or("");
+                    let contents = annotation
+                        .get_deref(b"Contents", document)
+                        .and_then(pdfstring::decode_object)
+                        .map(|contents| {
+                            let mut lines = contents.lines();
+                            let line = lines.next().unwrap_or("");
+                            let preview: String = line.chars().take(CONTENTS_PREVIEW_LEN).collect();
+
+                            if preview.len() < line.len() || lines.next().is_some() {
+                                format!(": \"{preview}...\"")
+                            } else {
+                                format!(": \"{preview}\"")
+                            }
+                        })
+                        .unwrap_or_default();
+                    (subtype, contents)
+                },
+            };
+
+            let answer: String = dialoguer::Input::new()
+                .with_prompt(format!(
+                    "[{index}/{total}] {subtype} annotation on page {page_number}{contents}. \
+                     Delete it? [y/n/a/q]"
+                ))
+                .validate_with(|input: &String| -> Result<(), &str> {
+                    match input.as_str() {
+                        "y" | "n" | "a" | "q" => Ok(()),
+                        _ => Err("Please answer one of y, n, a or q."),
+                    }
+                })
+                .interact_text()?;
+
+            match answer.as_str() {
+                "y" => delete_ids.push(id),
+                "a" => {
+                    delete_ids.push(id);
+                    delete_ids.extend(candidates.by_ref().map(|(_, (_, id))| id));
+                },
+                "q" => break,
+                _ => {},
             }
         }
 
-        info!("Updating the annotation arrays in reference document");
-        for (page_number, new_ann) in annotations_map.iter_mut() {
-            match pages.get(page_number) {
-                Some(page_id) => {
-                    debug!(
-                        "Retrieving a mutable reference to page's {page_number} annotations in \
-                         reference document"
-                    );
-                    let current_ann = get_page_annotations_mut(&mut main, *page_id);
+        Ok(delete_ids)
+    }
 
-                    current_ann.append(new_ann);
+    /// Strip the annotations of a file, and write the result to `dest`.
+    fn strip<W>(&self, ctx: &mut ExecutionContext<W>, file: &Path, dest: &Path) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(file)?;
+
+        let mut candidates = annotations::strip_candidates(&document, &self.exclude, self.media);
+        if let Some(filter) = &self.filter {
+            candidates.retain(|&(page, id)| {
+                annotations::read_annotation(&document, page, id)
+                    .is_some_and(|annotation| filter.matches(&annotation))
+            });
+        }
+
+        let delete_ids = if self.interactive {
+            self.select_interactively(&document, candidates)?
+        } else {
+            candidates.into_iter().map(|(_, id)| id).collect()
+        };
+        debug!("Deleting {} annotations", delete_ids.len());
+        ctx.summary.annotations_removed(delete_ids.len());
+
+        let (objects, bytes) = annotations::delete_annotations(&mut document, &delete_ids);
+        debug!("Deleted {objects} objects of {bytes} bytes used by deleted annotations");
+
+        ctx.save_document(&mut document, dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "strip-success",
+                    &[
+                        ("file", file.to_str().unwrap().into()),
+                        ("dest", dest.to_str().unwrap().into()),
+                    ]
+                )
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Execute for Strip {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        if self.interactive && ctx.no_input {
+            bail!("Cannot strip annotations interactively with `--no-input`.");
+        }
+
+        if self.files.len() > 1 && self.name_template.is_none() {
+            bail!(
+                "Stripping several files needs a `--name-template`, e.g., `{{stem}}_{{cmd}}.pdf`."
+            );
+        }
+        for file in &self.files {
+            let dest = match &self.name_template {
+                Some(template) => {
+                    Naming::new(ctx, file, "strip")
+                        .render(template, &[])?
+                        .into()
                 },
-                None => error!("Main document does not have page number {page_number}"),
+                None => self.dest.clone(),
+            };
+            if output::resolve_destination(ctx, &dest)? {
+                self.strip(ctx, file, &dest)?;
             }
         }
+        Ok(())
+    }
+}
 
-        main.save(&self.dest)?;
+/// Parse a point of a page, e.g., `100,700`, in points from its lower-left
+/// corner.
+fn parse_point(s: &str) -> Result<[f32; 2], String> {
+    let point = s
+        .split_once(',')
+        .and_then(|(x, y)| Some([x.trim().parse().ok()?, y.trim().parse().ok()?]));
+    point.ok_or_else(|| format!("invalid point: {s:?}, expected x,y"))
+}
 
-        writeln!(
-            stdout,
-            "Successfully merged annotations from {} files to {:?}.",
-            self.files.len(),
-            self.dest.to_str().unwrap()
-        )?;
+/// Kinds of notes.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum NoteKind {
+    /// Sticky note, i.e., an icon that opens a pop-up with the text.
+    #[default]
+    Text,
+    /// Text written directly on the page.
+    FreeText,
+}
+
+/// Size of the icon of sticky notes, in points.
+const NOTE_ICON_SIZE: f32 = 24.0;
 
+/// Add-note command.
+#[derive(Args, Clone, Debug)]
+struct AddNote {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Page number of the note, starting at 1.
+    #[clap(long, default_value_t = 1)]
+    page: u32,
+    /// Top-left corner of the note, as `x,y` in points from the lower-left
+    /// corner of the page.
+    #[clap(long, value_parser = parse_point)]
+    at: [f32; 2],
+    /// Text of the note. Line breaks are kept in free-text notes.
+    #[clap(long)]
+    text: String,
+    /// Author of the note.
+    #[clap(long)]
+    author: Option<String>,
+    /// Kind of note.
+    #[clap(long, value_enum, default_value_t)]
+    kind: NoteKind,
+    /// Font size of free-text notes.
+    #[clap(long, default_value_t = 12.0)]
+    font_size: f32,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "annotated.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for AddNote {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let pages = document.get_pages();
+        let Some(&page_id) = pages.get(&self.page) else {
+            bail!(
+                "File {:?} has {} pages, there is no page {}.",
+                self.file,
+                pages.len(),
+                self.page
+            );
+        };
+        let [x, y] = self.at;
+
+        let builder = match self.kind {
+            NoteKind::Text => {
+                AnnotationBuilder::new("Text", [x, y - NOTE_ICON_SIZE, x + NOTE_ICON_SIZE, y])
+                    .name("Comment")
+                    .open(false)
+            },
+            NoteKind::FreeText => {
+                let (appearance, width, height) =
+                    annotations::free_text_appearance(&self.text, self.font_size);
+                AnnotationBuilder::new("FreeText", [x, y - height, x + width, y])
+                    .default_appearance(&format!("/Helv {} Tf 0 g", self.font_size))
+                    .appearance(appearance)
+            },
+        };
+        let mut builder = builder.contents(&self.text).date(&ctx.date());
+        if let Some(author) = &self.author {
+            builder = builder.author(author);
+        }
+        builder.add_to_page(&mut document, page_id)?;
+        ctx.summary.annotations_added(1);
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "note-success",
+                    &[
+                        ("page", self.page.into()),
+                        ("file", format!("{:?}", self.file).into()),
+                        ("dest", format!("{:?}", self.dest).into()),
+                    ]
+                )
+            )?;
+        }
         Ok(())
     }
 }
 
-/// Get annotation ids of a given page id.
-fn get_page_annotations(document: &Document, page_id: ObjectId) -> Vec<ObjectId> {
-    let page = document.get_dictionary(page_id).unwrap();
-    let mut ids = vec![];
-
-    match page.get(b"Annots") {
-        Ok(Object::Reference(ref id)) => {
-            document
-                .get_object(*id)
-                .and_then(Object::as_array)
-                .unwrap()
-                .iter()
-                .flat_map(Object::as_reference)
-                .for_each(|id| ids.push(id))
-        },
-        Ok(Object::Array(a)) => {
-            a.iter()
-                .flat_map(Object::as_reference)
-                .for_each(|id| ids.push(id))
-        },
-        Err(_) => {},
-        _ => unreachable!(),
+/// Shape of a mark, in page coordinates.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MarkShape {
+    /// Rectangle, as `[x0, y0, x1, y1]`.
+    Rect([f32; 4]),
+    /// Polyline, as a list of `[x, y]` points.
+    Polyline(Vec<[f32; 2]>),
+}
+
+/// Mark read from a marks file, e.g.,
+/// `{"page": 1, "rect": [100, 100, 200, 150], "label": "crack"}`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Mark {
+    /// Page number, starting at 1.
+    page: u32,
+    #[serde(flatten)]
+    shape: MarkShape,
+    /// Color of the mark, in RGB, defaults to red.
+    color: Option<[f32; 3]>,
+    /// Line width of the mark, in points.
+    width: Option<f32>,
+    /// Text shown in the pop-up of the mark.
+    label: Option<String>,
+}
+
+/// Add-marks command.
+#[derive(Args, Clone, Debug)]
+struct AddMarks {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// JSON file with the list of marks, each with a `page` and either a
+    /// `rect` or a `polyline`, and optionally a `color`, `width` and `label`.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    from: PathBuf,
+    /// Default line width of marks, in points.
+    #[clap(long, default_value_t = 2.0)]
+    width: f32,
+    /// Author of the marks.
+    #[clap(long)]
+    author: Option<String>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "marked.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for AddMarks {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let json = fs::read_to_string(&self.from)
+            .with_context(|| format!("Failed to read marks from: {:?}", self.from))?;
+        let marks: Vec<Mark> = serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse marks from: {:?}", self.from))?;
+        let mut document = ctx.load_document(&self.file)?;
+        let pages = document.get_pages();
+        let date = ctx.date();
+
+        for (i, mark) in marks.iter().enumerate() {
+            let Some(&page_id) = pages.get(&mark.page) else {
+                bail!(
+                    "Mark {} is on page {}, but file {:?} has {} pages.",
+                    i + 1,
+                    mark.page,
+                    self.file,
+                    pages.len()
+                );
+            };
+            let shape = match &mark.shape {
+                MarkShape::Rect(rect) => Shape::Rectangle(*rect),
+                MarkShape::Polyline(points) if points.len() >= 2 => Shape::Polyline(points.clone()),
+                MarkShape::Polyline(_) => {
+                    bail!("Mark {} is a polyline with less than 2 points.", i + 1)
+                },
+            };
+            let mut builder = shape
+                .annotation(
+                    mark.color.unwrap_or([1.0, 0.0, 0.0]),
+                    mark.width.unwrap_or(self.width),
+                )
+                .date(&date);
+            if let Some(label) = &mark.label {
+                builder = builder.contents(label);
+            }
+            if let Some(author) = &self.author {
+                builder = builder.author(author);
+            }
+            builder.add_to_page(&mut document, page_id)?;
+            ctx.summary.annotations_added(1);
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "marks-success",
+                    &[
+                        ("count", marks.len().into()),
+                        ("file", format!("{:?}", self.file).into()),
+                        ("dest", format!("{:?}", self.dest).into()),
+                    ]
+                )
+            )?;
+        }
+        Ok(())
     }
-    ids
 }
 
-/// Strip command.
+/// Standard stamps, see [`Stamp`].
+#[derive(Clone, Copy, Debug, ValueEnum)]
+#[value(rename_all = "PascalCase")]
+enum StampName {
+    Approved,
+    AsIs,
+    Confidential,
+    Departmental,
+    Draft,
+    Experimental,
+    Expired,
+    Final,
+    ForComment,
+    ForPublicRelease,
+    NotApproved,
+    NotForPublicRelease,
+    Sold,
+    TopSecret,
+}
+
+impl From<StampName> for Stamp {
+    fn from(name: StampName) -> Self {
+        match name {
+            StampName::Approved => Self::Approved,
+            StampName::AsIs => Self::AsIs,
+            StampName::Confidential => Self::Confidential,
+            StampName::Departmental => Self::Departmental,
+            StampName::Draft => Self::Draft,
+            StampName::Experimental => Self::Experimental,
+            StampName::Expired => Self::Expired,
+            StampName::Final => Self::Final,
+            StampName::ForComment => Self::ForComment,
+            StampName::ForPublicRelease => Self::ForPublicRelease,
+            StampName::NotApproved => Self::NotApproved,
+            StampName::NotForPublicRelease => Self::NotForPublicRelease,
+            StampName::Sold => Self::Sold,
+            StampName::TopSecret => Self::TopSecret,
+        }
+    }
+}
+
+/// Position of a stamp on the page.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(super) enum StampPosition {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+    Center,
+}
+
+/// Distance between stamps and the edges of the page, in points.
+const STAMP_MARGIN: f32 = 36.0;
+
+impl StampPosition {
+    /// Return the rectangle of a stamp of the given size, on a page with the
+    /// given crop box.
+    pub(super) fn rect(self, [x0, y0, x1, y1]: [f32; 4], width: f32, height: f32) -> [f32; 4] {
+        let left = match self {
+            Self::TopLeft | Self::BottomLeft => x0 + STAMP_MARGIN,
+            Self::TopRight | Self::BottomRight => x1 - STAMP_MARGIN - width,
+            Self::TopCenter | Self::BottomCenter | Self::Center => (x0 + x1 - width) / 2.0,
+        };
+        let bottom = match self {
+            Self::TopLeft | Self::TopCenter | Self::TopRight => y1 - STAMP_MARGIN - height,
+            Self::BottomLeft | Self::BottomCenter | Self::BottomRight => y0 + STAMP_MARGIN,
+            Self::Center => (y0 + y1 - height) / 2.0,
+        };
+        [left, bottom, left + width, bottom + height]
+    }
+}
+
+/// Add-stamp command.
 #[derive(Args, Clone, Debug)]
-struct Strip {
+struct AddStamp {
     /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
     file: PathBuf,
+    /// Pages to stamp, e.g., `1` or `1,3-5`.
+    #[clap(short, long, default_value = "1")]
+    pages: PageSelection,
+    /// Name of the stamp.
+    #[clap(short, long, ignore_case = true, default_value = "Approved")]
+    name: StampName,
+    /// Position of the stamp on the page, in the unrotated page.
+    #[clap(long, default_value = "top-right")]
+    position: StampPosition,
+    /// Author of the stamp.
+    #[clap(long)]
+    author: Option<String>,
+    /// Put stamps on a layer of the given name, created if needed, so that
+    /// they can later be hidden or removed with `rpdf layers`.
+    #[clap(long, value_name = "NAME")]
+    layer: Option<String>,
     /// Output file where resulting PDF is written.
-    #[clap(short, long, default_value = "stripped_annotations.pdf")]
+    #[clap(short, long, default_value = "stamped.pdf", value_hint = ValueHint::FilePath)]
     dest: PathBuf,
-    /// Exclude a given annotation type from stripping (multiple values
+}
+
+impl Execute for AddStamp {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let pages = self.pages.select(&document);
+        if pages.is_empty() {
+            bail!(
+                "No page of {:?} matches the selection {}.",
+                self.file,
+                self.pages
+            );
+        }
+        let stamp = Stamp::from(self.name);
+        let date = ctx.date();
+        let layer_id = match &self.layer {
+            Some(name) => Some(layers::find_or_create(&mut document, name)?),
+            None => None,
+        };
+
+        for &page_id in pages.values() {
+            let Some(crop_box) = document.page_crop_box(page_id) else {
+                warn!("Skipping page {page_id:?} without a media box");
+                continue;
+            };
+            let (appearance, width, height) = stamp.appearance();
+            let mut builder =
+                AnnotationBuilder::new("Stamp", self.position.rect(crop_box, width, height))
+                    .name(stamp.name())
+                    .contents(stamp.name())
+                    .color(stamp.color())
+                    .date(&date)
+                    .appearance(appearance);
+            if let Some(author) = &self.author {
+                builder = builder.author(author);
+            }
+            if let Some(layer_id) = layer_id {
+                builder = builder.layer(layer_id);
+            }
+            builder.add_to_page(&mut document, page_id)?;
+            ctx.summary.annotations_added(1);
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "stamp-success",
+                    &[
+                        ("count", pages.len().into()),
+                        ("file", format!("{:?}", self.file).into()),
+                        ("dest", format!("{:?}", self.dest).into()),
+                    ]
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Output format of exported annotations.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum ExportFormat {
+    /// JSON document.
+    #[default]
+    Json,
+    /// Comma-separated values, one annotation per row, e.g., for
+    /// spreadsheets.
+    Csv,
+}
+
+/// Annotation, flattened for export.
+#[derive(Serialize)]
+struct AnnotationRecord<'a> {
+    file: &'a PathBuf,
+    #[serde(flatten)]
+    annotation: Annotation,
+}
+
+/// Export command.
+#[derive(Args, Clone, Debug)]
+struct Export {
+    /// PDF filepaths.
+    #[clap(required = true, add = ArgValueCompleter::new(pdf_files()))]
+    files: Vec<PathBuf>,
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t)]
+    format: ExportFormat,
+    /// Exclude a given annotation type from the export (multiple values
     /// allowed).
-    #[clap(short, long, default_value = "Link", action = ArgAction::Append)]
+    #[clap(short, long, default_value = "Popup", action = ArgAction::Append, add = ArgValueCandidates::new(annotation_subtypes))]
     exclude: Vec<String>,
+    /// Skip hidden annotations, i.e., those with the `Hidden` or `NoView`
+    /// flag, which tools often leave for their own use.
+    #[clap(long)]
+    skip_hidden: bool,
+    /// Only export annotations that pass a filter, e.g.,
+    /// `subtype == "Highlight" && author =~ "alice|bob" && page <= 10`.
+    ///
+    /// Fields are `subtype`, `author`, `contents`, `date` and `page`, and
+    /// comparisons are combined with `&&`, `||`, `!` and parentheses.
+    #[clap(long)]
+    filter: Option<Filter>,
 }
 
-impl Execute for Strip {
-    fn execute<W>(&self, stdout: &mut W) -> Result<()>
+impl Execute for Export {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
     where
         W: WriteColor,
     {
-        let mut document = Document::load(&self.file)
-            .with_context(|| format!("Failed to read PDF from: {}", self.file.to_str().unwrap()))?;
+        let mut records = vec![];
+        for file in &self.files {
+            let document = ctx.load_document_without_images(file)?;
+            let mut annotations: Vec<Annotation> =
+                annotations::read_annotations(&document, &self.exclude, self.skip_hidden)
+                    .into_iter()
+                    .filter(|annotation| {
+                        self.filter.as_ref().map_or(true, |f| f.matches(annotation))
+                    })
+                    .collect();
+            annotations::resolve_marked_text(&document, &mut annotations);
+            records.extend(
+                annotations
+                    .into_iter()
+                    .map(|annotation| AnnotationRecord { file, annotation }),
+            );
+        }
 
-        let mut delete_ids = vec![];
+        match self.format {
+            ExportFormat::Json => {
+                serde_json::to_writer_pretty(&mut ctx.stdout, &records)?;
+                writeln!(ctx.stdout)?;
+            },
+            ExportFormat::Csv => {
+                let header = [
+                    "file", "page", "subtype", "author", "date", "contents", "rect", "text",
+                ];
+                write_record(&mut ctx.stdout, &header.map(String::from), ',')?;
+                for AnnotationRecord { file, annotation } in records {
+                    let rect = annotation
+                        .rect
+                        .map(|rect| rect.map(|value| value.to_string()).join(" "))
+                        .unwrap_or_default();
+                    write_record(
+                        &mut ctx.stdout,
+                        &[
+                            file.display().to_string(),
+                            annotation.page.to_string(),
+                            annotation.subtype,
+                            annotation.author.unwrap_or_default(),
+                            annotation.date.unwrap_or_default(),
+                            annotation.contents.unwrap_or_default(),
+                            rect,
+                            annotation.text.unwrap_or_default(),
+                        ],
+                        ',',
+                    )?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Quantity that heatmap cells add up, see [`Weight`].
+#[cfg(feature = "convert")]
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum HeatmapWeight {
+    /// Number of annotations that overlap a cell.
+    #[default]
+    Count,
+    /// Area of a cell covered by annotations.
+    Area,
+}
+
+#[cfg(feature = "convert")]
+impl From<HeatmapWeight> for Weight {
+    fn from(weight: HeatmapWeight) -> Self {
+        match weight {
+            HeatmapWeight::Count => Self::Count,
+            HeatmapWeight::Area => Self::Area,
+        }
+    }
+}
+
+/// Parse the size of heatmap cells, in points.
+#[cfg(feature = "convert")]
+fn parse_cell_size(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(size) if size.is_finite() && size >= 1.0 => Ok(size),
+        Ok(_) => Err("cells must be at least 1 point wide".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Heatmap command.
+#[cfg(feature = "convert")]
+#[derive(Args, Clone, Debug)]
+struct Heatmap {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Quantity that cells add up.
+    #[clap(long, value_enum, default_value_t)]
+    weight: HeatmapWeight,
+    /// Size of cells, in points, e.g., 72 for one inch.
+    #[clap(long, default_value_t = 36.0, value_parser = parse_cell_size)]
+    cell_size: f32,
+    /// Number of pages per row of the image, which defaults to a square
+    /// layout.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    columns: Option<u32>,
+    /// Exclude a given annotation type from the heatmap (multiple values
+    /// allowed).
+    #[clap(short, long, default_value = "Popup", action = ArgAction::Append, add = ArgValueCandidates::new(annotation_subtypes))]
+    exclude: Vec<String>,
+    /// Skip hidden annotations, i.e., those with the `Hidden` or `NoView`
+    /// flag, which tools often leave for their own use.
+    #[clap(long)]
+    skip_hidden: bool,
+    /// Output file where the PNG image is written.
+    #[clap(short, long, default_value = "heatmap.png", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+#[cfg(feature = "convert")]
+impl Execute for Heatmap {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let annotations = annotations::read_annotations(&document, &self.exclude, self.skip_hidden);
+        let heatmaps =
+            heatmap::heatmaps(&document, &annotations, self.cell_size, self.weight.into());
+        let per_row = self.columns.map_or_else(
+            || (heatmaps.len() as f64).sqrt().ceil() as usize,
+            |columns| columns as usize,
+        );
+        let image = heatmap::render_png(&heatmaps, per_row)?;
+
+        output::check_destination(ctx, &self.dest)?;
+        fs::write(&self.dest, image)
+            .with_context(|| format!("Failed to write heatmap to: {:?}", self.dest))?;
+        ctx.summary.file_written(&self.dest);
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "heatmap-success",
+                    &[
+                        ("count", annotations.len().into()),
+                        ("file", self.file.to_str().unwrap().into()),
+                        ("dest", self.dest.to_str().unwrap().into()),
+                    ]
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Annotation flags, see [`Flag`].
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum FlagName {
+    Invisible,
+    Hidden,
+    Print,
+    NoZoom,
+    NoRotate,
+    NoView,
+    ReadOnly,
+    Locked,
+    ToggleNoView,
+    LockedContents,
+}
+
+impl From<FlagName> for Flag {
+    fn from(name: FlagName) -> Self {
+        match name {
+            FlagName::Invisible => Self::Invisible,
+            FlagName::Hidden => Self::Hidden,
+            FlagName::Print => Self::Print,
+            FlagName::NoZoom => Self::NoZoom,
+            FlagName::NoRotate => Self::NoRotate,
+            FlagName::NoView => Self::NoView,
+            FlagName::ReadOnly => Self::ReadOnly,
+            FlagName::Locked => Self::Locked,
+            FlagName::ToggleNoView => Self::ToggleNoView,
+            FlagName::LockedContents => Self::LockedContents,
+        }
+    }
+}
 
-        for page in document.page_iter() {
-            for id in get_page_annotations(&document, page) {
-                let subtype = document
-                    .get_dictionary(id)
-                    .unwrap()
-                    .get_deref(b"Subtype", &document)
+/// Set-flags command.
+#[derive(Args, Clone, Debug)]
+#[clap(group(ArgGroup::new("flags").required(true).multiple(true)))]
+struct SetFlags {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Pages whose annotations are updated, e.g., `1` or `1,3-5`.
+    #[clap(short, long, default_value = "all")]
+    pages: PageSelection,
+    /// Only update annotations of a given type (multiple values allowed),
+    /// e.g., `Stamp`. All annotations are updated by default.
+    #[clap(short, long, action = ArgAction::Append, add = ArgValueCandidates::new(annotation_subtypes))]
+    subtype: Vec<String>,
+    /// Hide annotations if no handler supports their type.
+    #[clap(long, group = "flags")]
+    invisible: bool,
+    /// Never show nor print annotations.
+    #[clap(long, group = "flags")]
+    hidden: bool,
+    /// Print annotations.
+    #[clap(long, group = "flags")]
+    print: bool,
+    /// Do not scale annotations with the page.
+    #[clap(long, group = "flags")]
+    no_zoom: bool,
+    /// Do not rotate annotations with the page.
+    #[clap(long, group = "flags")]
+    no_rotate: bool,
+    /// Do not show annotations, but still print them with `--print`.
+    #[clap(long, group = "flags")]
+    no_view: bool,
+    /// Do not let users interact with annotations.
+    #[clap(long, group = "flags")]
+    read_only: bool,
+    /// Do not let users move, resize or delete annotations.
+    #[clap(long, group = "flags")]
+    locked: bool,
+    /// Let viewers toggle `--no-view`, e.g., when hovering annotations.
+    #[clap(long, group = "flags")]
+    toggle_no_view: bool,
+    /// Do not let users edit the contents of annotations.
+    #[clap(long, group = "flags")]
+    locked_contents: bool,
+    /// Flags to clear, e.g., `locked,read-only` to unlock annotations.
+    #[clap(long, group = "flags", value_delimiter = ',', value_name = "FLAGS")]
+    clear: Vec<FlagName>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "flagged.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl SetFlags {
+    /// Return the flags to set.
+    fn flags(&self) -> Vec<Flag> {
+        [
+            (self.invisible, Flag::Invisible),
+            (self.hidden, Flag::Hidden),
+            (self.print, Flag::Print),
+            (self.no_zoom, Flag::NoZoom),
+            (self.no_rotate, Flag::NoRotate),
+            (self.no_view, Flag::NoView),
+            (self.read_only, Flag::ReadOnly),
+            (self.locked, Flag::Locked),
+            (self.toggle_no_view, Flag::ToggleNoView),
+            (self.locked_contents, Flag::LockedContents),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect()
+    }
+}
+
+impl Execute for SetFlags {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let pages = self.pages.select(&document);
+        if pages.is_empty() {
+            bail!(
+                "No page of {:?} matches the selection {}.",
+                self.file,
+                self.pages
+            );
+        }
+        let set = self.flags();
+        let clear: Vec<Flag> = self.clear.iter().copied().map(Flag::from).collect();
+        let mut updated = 0;
+
+        for &page_id in pages.values() {
+            for id in annotations::page_annotation_ids(&document, page_id) {
+                let Ok(annotation) = document.get_dictionary_mut(id) else {
+                    continue;
+                };
+                let subtype = annotation
+                    .get(b"Subtype")
                     .and_then(Object::as_name_str)
                     .unwrap_or("");
 
-                if !self.exclude.iter().any(|e| subtype == e) {
-                    delete_ids.push(id);
+                if (self.subtype.is_empty() || self.subtype.iter().any(|s| subtype == s))
+                    && annotations::update_flags(annotation, &set, &clear)
+                {
+                    updated += 1;
                 }
             }
         }
+        debug!("Updated the flags of {updated} annotations");
+        ctx.save_document(&mut document, &self.dest)?;
 
-        for id in delete_ids {
-            document.delete_object(id);
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "{}",
+                ctx.i18n.message(
+                    "flags-success",
+                    &[
+                        ("count", updated.into()),
+                        ("dest", format!("{:?}", self.dest).into()),
+                    ]
+                )
+            )?;
         }
-
-        document.save(&self.dest)?;
-
-        writeln!(
-            stdout,
-            "Successfully striped annotations from {} to {}",
-            self.file.to_str().unwrap(),
-            self.dest.to_str().unwrap()
-        )?;
-
         Ok(())
     }
 }
@@ -395,6 +1411,35 @@ enum AnnotationsSubcommand {
     Merge(Merge),
     /// Strip annotations from a given file.
     Strip(Strip),
+    /// Add rectangles and polylines from a JSON file, e.g., detections of
+    /// a model, as `Square` and `Ink` annotations.
+    AddMarks(AddMarks),
+    /// Add a note to a page, either as a sticky note or as free text.
+    AddNote(AddNote),
+    /// Add a standard rubber stamp, e.g., `Approved`, to pages.
+    ///
+    /// Stamps get an appearance, i.e., their label in a colored frame, so
+    /// that all viewers show them the same.
+    AddStamp(AddStamp),
+    /// Export annotations to JSON or CSV, one record per annotation.
+    ///
+    /// Records hold the file, page, subtype, author, modification date,
+    /// contents and rectangle of annotations, e.g., to triage review
+    /// comments in a spreadsheet. Highlights, underlines, strike-outs and
+    /// squiggly underlines also hold the text they mark.
+    Export(Export),
+    /// Draw a heatmap of where annotations are, as a PNG image, e.g., to
+    /// see where reviewers focused.
+    ///
+    /// Each page is drawn as a grid, whose cells are colored from white to
+    /// red by the number of annotations that overlap them, or by the area
+    /// they cover, and pages are laid out side by side in one image. Colors
+    /// are scaled to the busiest cell of the file, so that pages compare.
+    #[cfg(feature = "convert")]
+    Heatmap(Heatmap),
+    /// Set or clear flags of annotations, e.g., lock approved stamps so
+    /// that reviewers cannot move them.
+    SetFlags(SetFlags),
 }
 
 /// Work with PDF annotations.
@@ -407,14 +1452,21 @@ pub struct AnnotationsCommand {
 }
 
 impl Execute for AnnotationsCommand {
-    fn execute<W>(&self, stdout: &mut W) -> Result<()>
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
     where
         W: WriteColor,
     {
         match &self.subcommand {
-            AnnotationsSubcommand::Stats(stats) => stats.execute(stdout),
-            AnnotationsSubcommand::Merge(merge) => merge.execute(stdout),
-            AnnotationsSubcommand::Strip(strip) => strip.execute(stdout),
+            AnnotationsSubcommand::Stats(stats) => stats.execute(ctx),
+            AnnotationsSubcommand::Merge(merge) => merge.execute(ctx),
+            AnnotationsSubcommand::Strip(strip) => strip.execute(ctx),
+            AnnotationsSubcommand::Export(export) => export.execute(ctx),
+            #[cfg(feature = "convert")]
+            AnnotationsSubcommand::Heatmap(heatmap) => heatmap.execute(ctx),
+            AnnotationsSubcommand::AddMarks(add_marks) => add_marks.execute(ctx),
+            AnnotationsSubcommand::AddNote(add_note) => add_note.execute(ctx),
+            AnnotationsSubcommand::AddStamp(add_stamp) => add_stamp.execute(ctx),
+            AnnotationsSubcommand::SetFlags(set_flags) => set_flags.execute(ctx),
         }
     }
 }
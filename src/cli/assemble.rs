@@ -0,0 +1,242 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, ValueHint};
+use serde::Deserialize;
+use termcolor::WriteColor;
+
+use super::{context::ExecutionContext, traits::Execute};
+use crate::pdf::{
+    assemble::{self, Placement},
+    selection::PageSelection,
+};
+
+/// Pages of a source file, either a page number or a selection, e.g., `1`
+/// or `1,3-5`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum Pages {
+    Page(u32),
+    Selection(String),
+}
+
+/// Source file of a manifest, and the pages taken from it.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Source {
+    /// PDF filepath, relative to the manifest.
+    file: PathBuf,
+    /// Pages taken from the file, all by default.
+    pages: Option<Pages>,
+    /// Whether pages are taken in reverse order, e.g., the backs of sheets
+    /// scanned from the last one.
+    #[serde(default)]
+    reverse: bool,
+}
+
+/// Section of a manifest, made of the pages of a single file, or of several
+/// files interleaved.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestSection {
+    /// PDF filepath, relative to the manifest.
+    file: Option<PathBuf>,
+    /// Pages taken from the file, all by default.
+    pages: Option<Pages>,
+    /// Whether pages are taken in reverse order.
+    #[serde(default)]
+    reverse: bool,
+    /// Files whose pages are taken in turn, e.g., the fronts and backs of
+    /// sheets scanned separately.
+    #[serde(default)]
+    interleave: Vec<Source>,
+    /// Clockwise rotation added to the pages, in degrees.
+    #[serde(default)]
+    rotate: i64,
+    /// Title of the bookmark pointing to the first page of the section.
+    bookmark: Option<String>,
+}
+
+/// Manifest listing the sections of an assembled document.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    sections: Vec<ManifestSection>,
+}
+
+/// Assemble a document from pages of other documents, as listed in a YAML
+/// manifest.
+///
+/// Each section takes pages from a file, or from several files in turn with
+/// `interleave`, and may rotate them and add a bookmark to its first page,
+/// e.g.:
+///
+/// ```yaml
+/// sections:
+///   - file: cover.pdf
+///     pages: 1
+///     bookmark: Cover
+///   - interleave:
+///       - file: fronts.pdf
+///       - file: backs.pdf
+///         reverse: true
+///     rotate: 90
+///     bookmark: Scans
+/// ```
+///
+/// JSON manifests are also read, as JSON is a subset of YAML. File paths are
+/// relative to the manifest. The outlines, forms and article threads of the
+/// files are not kept.
+#[derive(Debug, Parser)]
+pub struct AssembleCommand {
+    /// YAML or JSON manifest filepath.
+    #[clap(value_hint = ValueHint::FilePath)]
+    manifest: PathBuf,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "assembled.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+/// Source documents of an assembly, loaded once each.
+struct Sources<'a, W> {
+    ctx: &'a ExecutionContext<W>,
+    dir: &'a Path,
+    indices: BTreeMap<PathBuf, usize>,
+    documents: Vec<lopdf::Document>,
+}
+
+impl<W: WriteColor> Sources<'_, W> {
+    /// Return the placements of the pages of a file, in order.
+    fn placements(
+        &mut self,
+        file: &Path,
+        pages: Option<&Pages>,
+        reverse: bool,
+        rotation: i64,
+    ) -> Result<Vec<Placement>> {
+        let path = self.dir.join(file);
+        let source = match self.indices.get(&path) {
+            Some(&index) => index,
+            None => {
+                self.documents.push(self.ctx.load_document(&path)?);
+                self.indices.insert(path.clone(), self.documents.len() - 1);
+                self.documents.len() - 1
+            },
+        };
+        let selection = match pages {
+            None => PageSelection::default(),
+            Some(Pages::Page(page)) => PageSelection::from_pages(&[*page]),
+            Some(Pages::Selection(selection)) => {
+                selection
+                    .parse()
+                    .map_err(anyhow::Error::msg)
+                    .with_context(|| format!("Invalid page selection for {path:?}."))?
+            },
+        };
+        let mut placements: Vec<Placement> = selection
+            .select(&self.documents[source])
+            .into_keys()
+            .map(|page| {
+                Placement {
+                    source,
+                    page,
+                    rotation,
+                }
+            })
+            .collect();
+        if placements.is_empty() {
+            bail!("No page of {path:?} matches the selection {selection}.");
+        }
+        if reverse {
+            placements.reverse();
+        }
+        Ok(placements)
+    }
+}
+
+impl Execute for AssembleCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let yaml = fs::read_to_string(&self.manifest)
+            .with_context(|| format!("Failed to read manifest from: {:?}", self.manifest))?;
+        let manifest: Manifest = serde_norway::from_str(&yaml)
+            .with_context(|| format!("Failed to parse manifest from: {:?}", self.manifest))?;
+
+        let mut sources = Sources {
+            ctx,
+            dir: self.manifest.parent().unwrap_or(Path::new("")),
+            indices: BTreeMap::new(),
+            documents: vec![],
+        };
+        let mut sections = vec![];
+        for (i, section) in manifest.sections.iter().enumerate() {
+            if section.rotate % 90 != 0 {
+                bail!(
+                    "Section {} is rotated by {} degrees, which is not a multiple of 90.",
+                    i + 1,
+                    section.rotate
+                );
+            }
+            let placements = match (&section.file, section.interleave.as_slice()) {
+                (Some(file), []) => {
+                    sources.placements(
+                        file,
+                        section.pages.as_ref(),
+                        section.reverse,
+                        section.rotate,
+                    )?
+                },
+                (None, interleaved)
+                    if !interleaved.is_empty() && section.pages.is_none() && !section.reverse =>
+                {
+                    let sequences = interleaved
+                        .iter()
+                        .map(|source| {
+                            sources.placements(
+                                &source.file,
+                                source.pages.as_ref(),
+                                source.reverse,
+                                section.rotate,
+                            )
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    assemble::interleave(sequences)
+                },
+                _ => {
+                    bail!(
+                        "Section {} must have either a `file`, or an `interleave` list whose \
+                         files have their own `pages` and `reverse`.",
+                        i + 1
+                    )
+                },
+            };
+            sections.push(assemble::Section {
+                bookmark: section.bookmark.clone(),
+                placements,
+            });
+        }
+
+        let files = sources.documents.len();
+        let mut document = assemble::assemble(sources.documents, &sections);
+        let pages: usize = sections
+            .iter()
+            .map(|section| section.placements.len())
+            .sum();
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully assembled {pages} pages from {files} files to {:?}.",
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+//! Timing of common operations, to evaluate performance-oriented changes.
+
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use clap::{ArgAction, Parser, ValueHint};
+use clap_complete::ArgValueCompleter;
+use clap_verbosity_flag::Verbosity;
+use tabled::{
+    builder::Builder,
+    settings::{Color, Style, style::BorderColor},
+};
+use termcolor::{NoColor, WriteColor};
+use tracing::info;
+
+use super::{
+    annotations::AnnotationsCommand,
+    cache::Cache,
+    color::ColorPolicy,
+    complete::pdf_files,
+    context::ExecutionContext,
+    i18n::Localizer,
+    output::{Overwrite, OverwritePolicy},
+    summary::RunSummary,
+    traits::Execute,
+};
+use crate::pdf::{self, SaveOptions, fixtures::Fixture};
+
+/// Timed phases, in order.
+const PHASES: &[&str] = &["load", "stats", "merge", "strip", "save"];
+
+/// Time common operations on fixture PDFs.
+///
+/// Each fixture is loaded, counted (`annotations stats`), merged with itself
+/// (`annotations merge`), stripped (`annotations strip`) and saved, and the
+/// median duration of each phase is reported.
+#[derive(Debug, Parser)]
+pub struct BenchCommand {
+    /// Fixture PDF filepaths. Synthetic fixtures are generated if none is
+    /// given.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    files: Vec<PathBuf>,
+    /// Number of pages of synthetic fixtures.
+    #[clap(long, default_value_t = 100)]
+    pages: u32,
+    /// Number of annotations of synthetic fixtures (multiple values allowed,
+    /// one fixture per value).
+    #[clap(long, default_values_t = [100, 1_000, 10_000], action = ArgAction::Append)]
+    annotations: Vec<usize>,
+    /// Directory where synthetic fixtures and outputs are written, defaults
+    /// to a temporary directory.
+    #[clap(long, value_hint = ValueHint::DirPath)]
+    dir: Option<PathBuf>,
+    /// Only write synthetic fixtures, and print their paths.
+    #[clap(long)]
+    generate_only: bool,
+    /// Number of runs of each phase.
+    #[clap(short = 'n', long, default_value_t = 5)]
+    iterations: usize,
+}
+
+/// Return the median duration of running `f` a number of times.
+fn median<F>(iterations: usize, mut f: F) -> Result<Duration>
+where
+    F: FnMut() -> Result<()>,
+{
+    let mut durations = Vec::with_capacity(iterations);
+
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        f()?;
+        durations.push(start.elapsed());
+    }
+    durations.sort();
+    Ok(durations[durations.len() / 2])
+}
+
+/// Format a file size in KiB or MiB.
+fn format_size(size: u64) -> String {
+    let kib = size as f64 / 1024.0;
+
+    if kib < 1024.0 {
+        format!("{kib:.1} KiB")
+    } else {
+        format!("{:.1} MiB", kib / 1024.0)
+    }
+}
+
+/// Format a duration in milliseconds.
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1e3)
+}
+
+impl BenchCommand {
+    /// Write synthetic fixtures to `dir`, returning their paths.
+    fn generate(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        self.annotations
+            .iter()
+            .map(|&annotations| {
+                let path = dir.join(format!("fixture-{}p-{annotations}a.pdf", self.pages));
+                info!("Generating fixture {path:?}");
+                let mut document = Fixture {
+                    pages: self.pages,
+                    annotations,
+                    ..Fixture::default()
+                }
+                .build();
+                pdf::save(&mut document, &path, SaveOptions::default())?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// Time each phase on a fixture.
+    fn time<W>(
+        &self,
+        file: &Path,
+        dir: &Path,
+        sink: &mut ExecutionContext<W>,
+    ) -> Result<Vec<Duration>>
+    where
+        W: WriteColor,
+    {
+        let password = sink.password.clone();
+        let merged = dir.join("merged.pdf");
+        let stripped = dir.join("stripped.pdf");
+        let saved = dir.join("saved.pdf");
+        let [file, merged, stripped] = [file, &merged, &stripped].map(Path::as_os_str);
+        let arg = OsStr::new;
+
+        Ok(vec![
+            median(self.iterations, || {
+                pdf::load(file, password.as_deref()).map(drop)
+            })?,
+            median(self.iterations, || annotations(sink, &[arg("stats"), file]))?,
+            median(self.iterations, || {
+                annotations(sink, &[arg("merge"), file, file, arg("--dest"), merged])
+            })?,
+            median(self.iterations, || {
+                annotations(sink, &[arg("strip"), file, arg("--dest"), stripped])
+            })?,
+            median(self.iterations, || {
+                let mut document = pdf::load(file, password.as_deref())?;
+                sink.save_document(&mut document, &saved)
+            })?,
+        ])
+    }
+}
+
+/// Run an `annotations` subcommand with the given arguments.
+fn annotations<W>(sink: &mut ExecutionContext<W>, args: &[&OsStr]) -> Result<()>
+where
+    W: WriteColor,
+{
+    AnnotationsCommand::try_parse_from(
+        std::iter::once(OsStr::new("annotations")).chain(args.iter().copied()),
+    )?
+    .execute(sink)
+}
+
+impl Execute for BenchCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let dir = self
+            .dir
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("rpdf-bench"));
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create directory: {dir:?}"))?;
+
+        let files = if self.files.is_empty() {
+            self.generate(&dir)?
+        } else {
+            self.files.clone()
+        };
+
+        if self.generate_only {
+            for file in &files {
+                writeln!(ctx.stdout, "{}", file.display())?;
+            }
+            return Ok(());
+        }
+
+        // Commands are run quietly, so that only timings are shown.
+        let mut sink = ExecutionContext {
+            stdout: NoColor::new(io::sink()),
+            color: ColorPolicy::Never,
+            verbosity: Verbosity::new(0, 1),
+            no_input: true,
+            password: ctx.password.clone(),
+            save_options: ctx.save_options,
+            max_memory: ctx.max_memory,
+            #[cfg(feature = "net")]
+            timeout: ctx.timeout,
+            summary: RunSummary::default(),
+            // Each iteration writes the same files again.
+            overwrite: Overwrite::new(OverwritePolicy::Force),
+            verify: ctx.verify,
+            // Each iteration must derive data from files again.
+            cache: Cache::default(),
+            // Benchmarks write new files, which are never edited in place.
+            journal: false,
+            i18n: Localizer::default(),
+        };
+
+        let mut builder = Builder::default();
+        let mut header = vec!["Fixture".to_string(), "Size".to_string()];
+        header.extend(PHASES.iter().map(ToString::to_string));
+        builder.set_header(header);
+
+        for file in &files {
+            info!("Timing {file:?}");
+            let size = fs::metadata(file)
+                .with_context(|| format!("Failed to read PDF from: {file:?}"))?
+                .len();
+            let mut record = vec![file.display().to_string(), format_size(size)];
+            record.extend(
+                self.time(file, &dir, &mut sink)?
+                    .into_iter()
+                    .map(format_duration),
+            );
+            builder.push_record(record);
+        }
+
+        let mut table = builder.build();
+        table.with(Style::modern());
+
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+
+        writeln!(ctx.stdout, "{table}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+//! On-disk cache of data derived from files, and of downloaded files.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use serde::{Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+use termcolor::WriteColor;
+use tracing::{debug, warn};
+
+use super::{context::ExecutionContext, traits::Execute};
+
+/// Directory of the data cached by this version of rpdf, within the cache
+/// directory.
+///
+/// Other versions may derive different data from the same files, e.g., as
+/// text extraction improves, so they never share cached data.
+const VERSION_DIR: &str = concat!("v", env!("CARGO_PKG_VERSION"));
+
+/// Directory of the files downloaded from URLs, within the cache directory,
+/// which all versions of rpdf share.
+const DOWNLOADS_DIR: &str = "downloads";
+
+/// On-disk cache of data derived from files, e.g., the text of pages, so
+/// that runs over unchanged files do not compute it again.
+///
+/// Data is keyed by the SHA-256 hash of the content of files, hence moved or
+/// copied files still hit the cache, and modified files never do.
+#[derive(Debug, Default)]
+pub struct Cache {
+    /// Cache directory, if any.
+    dir: Option<PathBuf>,
+    /// Whether cached data is read and written.
+    enabled: bool,
+    /// Hashes of the files seen during the run, which are only computed
+    /// once.
+    hashes: Mutex<HashMap<PathBuf, Option<String>>>,
+}
+
+impl Cache {
+    /// Return a cache stored in `dir`, or in the default cache directory.
+    #[must_use]
+    pub fn new(dir: Option<PathBuf>, enabled: bool) -> Self {
+        Self {
+            dir: dir.or_else(Self::default_dir),
+            enabled,
+            hashes: Mutex::default(),
+        }
+    }
+
+    /// Return the cache directory, if any.
+    #[must_use]
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// Return whether cached data is read and written.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Return the default cache directory, i.e., `$XDG_CACHE_HOME/rpdf` or
+    /// `~/.cache/rpdf`.
+    fn default_dir() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .map(|dir| dir.join("rpdf"))
+    }
+
+    /// Return the directory of the data derived from a file, if caching is
+    /// enabled and the file can be read.
+    fn file_dir(&self, file: &Path) -> Option<PathBuf> {
+        if !self.enabled {
+            return None;
+        }
+        let dir = self.dir.as_ref()?;
+        let mut hashes = self
+            .hashes
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let hash = hashes
+            .entry(file.to_path_buf())
+            .or_insert_with(|| {
+                let data = fs::read(file).ok()?;
+                Some(hex::encode(Sha256::digest(&data)))
+            })
+            .clone()?;
+        Some(dir.join(VERSION_DIR).join(hash))
+    }
+
+    /// Return the path where the file downloaded from a URL is cached, if
+    /// caching is enabled.
+    #[cfg(feature = "net")]
+    #[must_use]
+    pub fn download_path(&self, url: &str) -> Option<PathBuf> {
+        if !self.enabled {
+            return None;
+        }
+        let name = hex::encode(Sha256::digest(url.as_bytes()));
+        Some(
+            self.dir
+                .as_ref()?
+                .join(DOWNLOADS_DIR)
+                .join(name)
+                .with_extension("pdf"),
+        )
+    }
+
+    /// Return the data derived from a file under a key, e.g., `text-1`,
+    /// computing and caching it if it is not cached yet.
+    ///
+    /// Failing to read or write the cache is never an error, the data is
+    /// computed instead.
+    ///
+    /// # Errors
+    ///
+    /// Fails if computing the data fails.
+    pub fn get_or_compute<T, E, F>(&self, file: &Path, key: &str, compute: F) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let Some(dir) = self.file_dir(file) else {
+            return compute();
+        };
+        let path = dir.join(key).with_extension("json");
+
+        if let Some(data) = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+        {
+            debug!("Using cached {key} of {file:?}");
+            return Ok(data);
+        }
+        let data = compute()?;
+        if let Err(e) = serde_json::to_vec(&data)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Self::write_file(&path, &json))
+        {
+            warn!("Failed to cache {key} of {file:?}: {e:#}");
+        }
+        Ok(data)
+    }
+
+    /// Remove the data cached by all versions of rpdf, and return the
+    /// number of removed directories.
+    ///
+    /// Only the directories that rpdf writes are removed, so that other files
+    /// survive if the cache directory is shared, e.g., `--cache-dir .`. The
+    /// cache directory itself is removed if nothing else is left.
+    fn clear(dir: &Path) -> Result<usize> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error.into()),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if entry.file_type()?.is_dir() && is_cache_dir(name) {
+                fs::remove_dir_all(entry.path())?;
+                removed += 1;
+            }
+        }
+        // The directory is kept if anything else is in it.
+        let _ = fs::remove_dir(dir);
+        Ok(removed)
+    }
+
+    /// Write a file to the cache, through a temporary file, so that
+    /// concurrent runs never read partial data.
+    pub fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+        let dir = path.parent().context("Cached files have a directory")?;
+        fs::create_dir_all(dir)?;
+        let mut file = tempfile::Builder::new()
+            .prefix(".rpdf-")
+            .suffix(".tmp")
+            .tempfile_in(dir)?;
+        file.write_all(data)?;
+        file.flush()?;
+        file.persist(path).map_err(|error| error.error)?;
+        Ok(())
+    }
+}
+
+/// Return whether a directory within the cache directory is written by
+/// rpdf, i.e., the directory of downloads, or that of a version, e.g.,
+/// `v0.3.0`.
+fn is_cache_dir(name: &str) -> bool {
+    name == DOWNLOADS_DIR
+        || name
+            .strip_prefix('v')
+            .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheSubcommand {
+    /// Remove all cached data.
+    ///
+    /// Only the directories that rpdf writes are removed, other files in the
+    /// cache directory are kept.
+    Clear,
+}
+
+/// Manage the cache of data derived from files, e.g., the text of pages.
+///
+/// The cache is stored in `$XDG_CACHE_HOME/rpdf` or `~/.cache/rpdf`, unless
+/// `--cache-dir` is given.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct CacheCommand {
+    #[command(subcommand)]
+    subcommand: CacheSubcommand,
+}
+
+impl Execute for CacheCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match self.subcommand {
+            CacheSubcommand::Clear => {
+                let dir = ctx
+                    .cache
+                    .dir
+                    .as_ref()
+                    .context("Failed to find the cache directory, use --cache-dir.")?;
+                Cache::clear(dir)
+                    .with_context(|| format!("Failed to remove cache from: {dir:?}"))?;
+                if !ctx.quiet() {
+                    writeln!(ctx.stdout, "Successfully cleared the cache in {dir:?}.")?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
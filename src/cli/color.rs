@@ -0,0 +1,62 @@
+//! Color policy shared by all output layers.
+
+use is_terminal::IsTerminal;
+use termcolor::ColorChoice;
+
+/// Whether output should be colorized.
+///
+/// The policy is resolved once from the `--color` option, the `NO_COLOR`
+/// environment variable and whether stdout is a terminal, so that the output
+/// stream, tables and inline styling all agree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorPolicy {
+    /// Always colorize output.
+    Always,
+    /// Never colorize output.
+    Never,
+}
+
+impl ColorPolicy {
+    /// Resolve the policy from the user's color choice.
+    ///
+    /// In `auto` mode, color is disabled if `NO_COLOR` is set to a non-empty
+    /// value, see <https://no-color.org>, or if stdout is not a terminal.
+    #[must_use]
+    pub fn resolve(choice: clap::ColorChoice) -> Self {
+        Self::resolve_for(choice, &std::io::stdout())
+    }
+
+    /// Resolve the policy like [`ColorPolicy::resolve`], for another stream
+    /// than stdout, e.g., stderr where log messages are written.
+    #[must_use]
+    pub fn resolve_for<S: IsTerminal>(choice: clap::ColorChoice, stream: &S) -> Self {
+        match choice {
+            clap::ColorChoice::Always => Self::Always,
+            clap::ColorChoice::Never => Self::Never,
+            clap::ColorChoice::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+                if no_color || !stream.is_terminal() {
+                    Self::Never
+                } else {
+                    Self::Always
+                }
+            },
+        }
+    }
+
+    /// Return whether output should be colorized.
+    #[must_use]
+    pub fn enabled(self) -> bool {
+        self == Self::Always
+    }
+}
+
+impl From<ColorPolicy> for ColorChoice {
+    fn from(policy: ColorPolicy) -> Self {
+        match policy {
+            ColorPolicy::Always => ColorChoice::Always,
+            ColorPolicy::Never => ColorChoice::Never,
+        }
+    }
+}
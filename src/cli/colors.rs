@@ -0,0 +1,378 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::Document;
+use serde::Serialize;
+use tabled::{
+    builder::Builder,
+    settings::{Color, Panel, Style, style::BorderColor},
+};
+use termcolor::{ColorSpec, WriteColor};
+use tracing::warn;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::{
+    colors::{self, Family},
+    ink::{self, PageColors},
+    selection::PageSelection,
+};
+
+/// Colors that documents can be converted to.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Target {
+    /// Shades of gray.
+    Gray,
+    /// CMYK colors, keeping gray colors as is.
+    Cmyk,
+}
+
+impl From<Target> for Family {
+    fn from(target: Target) -> Self {
+        match target {
+            Target::Gray => Self::Gray,
+            Target::Cmyk => Self::Cmyk,
+        }
+    }
+}
+
+/// Convert command.
+#[derive(Args, Clone, Debug)]
+struct Convert {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Colors to convert to.
+    #[clap(long)]
+    to: Target,
+    /// ICC profile of the printing condition, e.g., `ISOcoated_v2.icc`,
+    /// embedded as the output intent of the document.
+    ///
+    /// Colors are converted with the naive formulas of the PDF
+    /// specification, and the profile only tells printers how to interpret
+    /// them. It must be a gray or CMYK profile, matching `--to`.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    icc: Option<PathBuf>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "converted.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Convert {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let target = Family::from(self.to);
+        let profile = match &self.icc {
+            Some(path) => {
+                let profile = fs::read(path)
+                    .with_context(|| format!("Failed to read ICC profile from: {path:?}"))?;
+                if Family::of_icc_profile(&profile) != Some(target) {
+                    bail!(
+                        "ICC profile {path:?} is not a {} profile.",
+                        self.to.to_possible_value().unwrap().get_name()
+                    );
+                }
+                Some((path, profile))
+            },
+            None => None,
+        };
+        let mut document = ctx.load_document(&self.file)?;
+        let report = colors::convert(&mut document, target);
+
+        if let Some((path, profile)) = profile {
+            let identifier = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy())
+                .unwrap_or_default();
+            colors::set_output_intent(&mut document, "GTS_PDFX", &identifier, profile, target)?;
+        }
+        if report.skipped > 0 {
+            warn!(
+                "{} images, shadings or content streams could not be converted, and keep their \
+                 colors.",
+                report.skipped
+            );
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Converted {} color operators, {} images and {} shadings.",
+                report.operators, report.images, report.shadings
+            )?;
+            writeln!(
+                ctx.stdout,
+                "Successfully converted colors of {:?} to {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Output format of color reports.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum ReportFormat {
+    /// Table, followed by the color and gray pages.
+    #[default]
+    Text,
+    /// JSON document.
+    Json,
+}
+
+/// Color report of a file, written as JSON.
+#[derive(Serialize)]
+struct ColorReport<'a> {
+    file: &'a PathBuf,
+    pages: &'a [PageColors],
+}
+
+/// Report command.
+#[derive(Args, Clone, Debug)]
+struct Report {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t)]
+    format: ReportFormat,
+}
+
+impl Execute for Report {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let pages = ink::page_colors(&document);
+
+        if let ReportFormat::Json = self.format {
+            let report = ColorReport {
+                file: &self.file,
+                pages: &pages,
+            };
+            serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+            writeln!(ctx.stdout)?;
+            return Ok(());
+        }
+
+        let mut builder = Builder::default();
+        builder.set_header([
+            "Page no.",
+            "Color",
+            "C %",
+            "M %",
+            "Y %",
+            "K %",
+            "Total %",
+            "Spot colors",
+        ]);
+        for page in &pages {
+            let spot_colors: Vec<String> = page
+                .spot_colors
+                .iter()
+                .map(|(name, coverage)| format!("{name} ({coverage:.1} %)"))
+                .collect();
+            builder.push_record([
+                page.page.to_string(),
+                if page.color { "yes" } else { "no" }.to_string(),
+                format!("{:.1}", page.coverage.cyan),
+                format!("{:.1}", page.coverage.magenta),
+                format!("{:.1}", page.coverage.yellow),
+                format!("{:.1}", page.coverage.black),
+                format!("{:.1}", page.coverage.total()),
+                spot_colors.join(", "),
+            ]);
+        }
+        let mut table = builder.build();
+        table
+            .with(Panel::header(format!(
+                "Color usage for: {}",
+                self.file.display()
+            )))
+            .with(Style::modern());
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+        writeln!(ctx.stdout, "{table}")?;
+
+        for (label, color) in [("Color pages", true), ("Gray pages", false)] {
+            let selection: PageSelection = pages
+                .iter()
+                .filter(|page| page.color == color)
+                .map(|page| page.page)
+                .collect();
+            if selection.is_all() {
+                writeln!(ctx.stdout, "{label}: none")?;
+            } else {
+                writeln!(ctx.stdout, "{label}: {selection}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Output-intent command.
+#[derive(Args, Clone, Debug)]
+#[clap(group(ArgGroup::new("action").required(true)))]
+struct OutputIntent {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Show the output intents of the document.
+    #[clap(long, group = "action")]
+    show: bool,
+    /// Set the output intent, embedding the given ICC profile, e.g.,
+    /// `sRGB.icc`.
+    #[clap(long, group = "action", value_name = "PROFILE", value_hint = ValueHint::FilePath)]
+    set: Option<PathBuf>,
+    /// Subtype of the output intent, e.g., `GTS_PDFA1` for PDF/A or
+    /// `GTS_PDFX` for PDF/X. Output intents of other subtypes are kept.
+    #[clap(long, default_value = "GTS_PDFA1", requires = "set")]
+    subtype: String,
+    /// Name of the output condition, e.g., `FOGRA39`. Defaults to the
+    /// description of the profile.
+    #[clap(long, requires = "set")]
+    identifier: Option<String>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "patched.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl OutputIntent {
+    /// Show the output intents of a document.
+    fn show<W>(&self, ctx: &mut ExecutionContext<W>, document: &Document) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let intents = colors::output_intents(document);
+
+        if intents.is_empty() {
+            writeln!(
+                ctx.stdout,
+                "Document {:?} has no output intents.",
+                self.file
+            )?;
+        }
+        for intent in intents {
+            ctx.stdout.set_color(ColorSpec::new().set_bold(true))?;
+            writeln!(ctx.stdout, "{}", intent.subtype)?;
+            ctx.stdout.reset()?;
+            for (label, value) in [
+                ("Identifier", &intent.identifier),
+                ("Info", &intent.info),
+                ("Registry", &intent.registry),
+            ] {
+                if let Some(value) = value {
+                    writeln!(ctx.stdout, "  {label}: {value}")?;
+                }
+            }
+            match intent.profile {
+                Some(profile) => {
+                    let family = match profile.family {
+                        Some(Family::Gray) => "gray",
+                        Some(Family::Rgb) => "RGB",
+                        Some(Family::Cmyk) => "CMYK",
+                        None => "unknown",
+                    };
+                    write!(ctx.stdout, "  Profile: {family}, {} bytes", profile.size)?;
+                    if let Some(description) = profile.description {
+                        write!(ctx.stdout, " ({description})")?;
+                    }
+                    writeln!(ctx.stdout)?;
+                },
+                None => writeln!(ctx.stdout, "  Profile: none")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Execute for OutputIntent {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let Some(path) = &self.set else {
+            return self.show(ctx, &document);
+        };
+
+        let profile =
+            fs::read(path).with_context(|| format!("Failed to read ICC profile from: {path:?}"))?;
+        let Some(family) = Family::of_icc_profile(&profile) else {
+            bail!("ICC profile {path:?} is not a gray, RGB or CMYK profile.");
+        };
+        let identifier = self
+            .identifier
+            .clone()
+            .or_else(|| colors::icc_description(&profile))
+            .or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_default();
+
+        colors::set_output_intent(&mut document, &self.subtype, &identifier, profile, family)?;
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully set {} output intent ({identifier}) of {:?} to {:?}.",
+                self.subtype, self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for colors.
+#[derive(Debug, Subcommand)]
+enum ColorSubcommand {
+    /// Convert the colors of a document to gray or CMYK colors.
+    ///
+    /// Color operators of content streams, images and shadings are
+    /// converted, e.g., for print shops that only accept gray or CMYK files.
+    /// JPEG images, mesh shadings and spot colors are left unchanged, with a
+    /// warning.
+    Convert(Convert),
+    /// Show or set the output intents of a document.
+    ///
+    /// Output intents embed the ICC profile of the device or condition a
+    /// document is intended for, as PDF/A and PDF/X require.
+    OutputIntent(OutputIntent),
+    /// Report which pages use colors, and estimate their ink coverage.
+    ///
+    /// Pages that only use shades of gray can be sent to monochrome
+    /// printers. Coverage is estimated from the area of painted shapes,
+    /// text and images, in percent of the page area, without rendering
+    /// pages, and spot colors are listed by name.
+    Report(Report),
+}
+
+/// Work with the colors of PDF files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct ColorCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: ColorSubcommand,
+}
+
+impl Execute for ColorCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            ColorSubcommand::Convert(convert) => convert.execute(ctx),
+            ColorSubcommand::OutputIntent(output_intent) => output_intent.execute(ctx),
+            ColorSubcommand::Report(report) => report.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Execution context shared by all commands.
+
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, bail};
+use clap_verbosity_flag::Verbosity;
+use lopdf::Document;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+#[cfg(feature = "net")]
+use super::net;
+use super::{
+    cache::Cache,
+    color::ColorPolicy,
+    i18n::Localizer,
+    logging,
+    output::{self, Overwrite},
+    summary::RunSummary,
+    undo,
+};
+use crate::pdf::{self, DeferredImages, journal, reproducible};
+
+/// State derived from the top-level command line, passed to every command.
+///
+/// Commands should read global options from here rather than taking them as
+/// extra arguments.
+pub struct ExecutionContext<W> {
+    /// Output stream.
+    pub stdout: W,
+    /// Whether output should be colorized.
+    pub color: ColorPolicy,
+    /// Logging verbosity.
+    pub verbosity: Verbosity,
+    /// Never prompt for user input, failing instead.
+    pub no_input: bool,
+    /// Password used to open encrypted documents.
+    pub password: Option<String>,
+    /// Options used to save PDF documents.
+    pub save_options: pdf::SaveOptions,
+    /// Maximum memory, in bytes, that a loaded document may use, if any.
+    pub max_memory: Option<u64>,
+    /// Maximum time to download a document given as a URL.
+    #[cfg(feature = "net")]
+    pub timeout: std::time::Duration,
+    /// Summary of the run, updated as documents are loaded and saved.
+    pub summary: RunSummary,
+    /// What to do with output files that already exist.
+    pub overwrite: Overwrite,
+    /// Reload saved documents to check that they were written correctly.
+    pub verify: bool,
+    /// Cache of data derived from files, e.g., the text of pages.
+    pub cache: Cache,
+    /// Write journals of files edited in place, so that edits can be undone.
+    pub journal: bool,
+    /// Translator of messages to the user's language.
+    pub i18n: Localizer,
+}
+
+/// Estimated memory used by a loaded document, per byte of its file.
+///
+/// lopdf keeps the raw data of streams, and parsed objects take about as
+/// much memory as their syntax.
+const MEMORY_PER_FILE_BYTE: u64 = 2;
+
+/// Fail if loading a file of `size` bytes would exceed a memory budget, if
+/// any, e.g., a file uploaded to the server.
+pub fn check_memory_size(max_memory: Option<u64>, path: &Path, size: u64) -> Result<()> {
+    let Some(max_memory) = max_memory else {
+        return Ok(());
+    };
+    let needed = size.saturating_mul(MEMORY_PER_FILE_BYTE);
+
+    if needed > max_memory {
+        bail!(
+            "Loading {path:?} needs about {needed} bytes of memory, more than the {max_memory} \
+             bytes allowed by --max-memory."
+        );
+    }
+    Ok(())
+}
+
+/// Return whether a file given on the command line is a URL, e.g.,
+/// `https://example.com/file.pdf`, rather than a path.
+fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.starts_with("https://") || path.starts_with("http://"))
+}
+
+/// Parse a size, e.g., `9MB`, `500KiB`, `2G` or `1024`, in bytes.
+///
+/// Single-letter units are binary, as in most command-line tools.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let factor: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        _ => {
+            return Err(format!(
+                "invalid size unit: {unit:?}, expected B, KB, MB, GB, K, M, G, KiB, MiB or GiB"
+            ));
+        },
+    };
+    match number.parse::<f64>() {
+        Ok(number) if number > 0.0 => Ok((number * factor as f64) as u64),
+        _ => Err(format!("invalid size: {s:?}")),
+    }
+}
+
+impl<W> ExecutionContext<W> {
+    /// Return whether success messages should be suppressed.
+    #[must_use]
+    pub fn quiet(&self) -> bool {
+        self.verbosity.is_silent()
+    }
+
+    /// Fail if loading a file would exceed the memory budget, rather than
+    /// being killed for running out of memory halfway through.
+    fn check_memory(&self, path: &Path) -> Result<()> {
+        if self.max_memory.is_none() {
+            return Ok(());
+        }
+        let size = fs::metadata(path)
+            .with_context(|| format!("Failed to read PDF from: {path:?}"))?
+            .len();
+        self.check_memory_size(path, size)
+    }
+
+    /// Fail if loading a file of `size` bytes would exceed the memory
+    /// budget.
+    fn check_memory_size(&self, path: &Path, size: u64) -> Result<()> {
+        check_memory_size(self.max_memory, path, size)
+    }
+
+    /// Load a PDF document, decrypting it with the user's password if needed.
+    ///
+    /// URLs are refused, as commands that load documents this way may save
+    /// them back.
+    pub fn load_document<P: AsRef<Path>>(&self, path: P) -> Result<Document> {
+        if is_url(path.as_ref()) {
+            bail!(
+                "This command cannot read PDFs from URLs, download {:?} first.",
+                path.as_ref()
+            );
+        }
+        logging::set_current_file(path.as_ref());
+        self.check_memory(path.as_ref())?;
+        self.summary.file_read(path.as_ref());
+        pdf::load(path, self.password.as_deref())
+    }
+
+    /// Load a PDF document like [`load_document`](Self::load_document), but
+    /// without the data of image streams, for read-only commands that never
+    /// look at images.
+    ///
+    /// The memory budget is checked as for other documents, as images only
+    /// make up most of the size of scanned documents.
+    ///
+    /// Documents may also be given as URLs, which are downloaded, see
+    /// [`load_url`](Self::load_url).
+    pub fn load_document_without_images<P: AsRef<Path>>(&self, path: P) -> Result<Document> {
+        logging::set_current_file(path.as_ref());
+        if is_url(path.as_ref()) {
+            return self.load_url(path.as_ref());
+        }
+        self.check_memory(path.as_ref())?;
+        self.summary.file_read(path.as_ref());
+        pdf::load_without_images(path, self.password.as_deref()).map(|(document, _)| document)
+    }
+
+    /// Load a PDF document like
+    /// [`load_document_without_images`](Self::load_document_without_images),
+    /// for read-only commands that only look at a few images, which are read
+    /// on demand.
+    ///
+    /// URLs are refused, as images are read from the file.
+    pub fn load_document_with_deferred_images<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(Document, DeferredImages)> {
+        if is_url(path.as_ref()) {
+            bail!(
+                "This command cannot read PDFs from URLs, download {:?} first.",
+                path.as_ref()
+            );
+        }
+        logging::set_current_file(path.as_ref());
+        self.check_memory(path.as_ref())?;
+        self.summary.file_read(path.as_ref());
+        pdf::load_without_images(path, self.password.as_deref())
+    }
+
+    /// Download a PDF document and load it from memory.
+    ///
+    /// Downloads are cached, and only downloaded again if the server tells
+    /// that the file changed.
+    #[cfg(feature = "net")]
+    fn load_url(&self, url: &Path) -> Result<Document> {
+        let data = net::download(&url.to_string_lossy(), &self.cache, self.timeout)?;
+        self.check_memory_size(url, data.len() as u64)?;
+        self.summary.file_downloaded(url, data.len() as u64);
+        pdf::load_mem(&data, self.password.as_deref())
+            .with_context(|| format!("Failed to read PDF from: {url:?}"))
+    }
+
+    #[cfg(not(feature = "net"))]
+    fn load_url(&self, url: &Path) -> Result<Document> {
+        bail!("Reading PDFs from URLs, like {url:?}, requires the `net` feature.");
+    }
+
+    /// Save a PDF document, reproducibly if the user asked for it.
+    ///
+    /// Existing files are only overwritten as the overwrite policy allows,
+    /// and saved files are read back if the user asked to verify them.
+    ///
+    /// Files edited in place, i.e., that were read before, get a journal
+    /// that `rpdf undo` restores them from.
+    pub fn save_document<P: AsRef<Path>>(&self, document: &mut Document, path: P) -> Result<()> {
+        let path = path.as_ref();
+        output::check_destination(self, path)?;
+        let original = if self.journal && self.summary.was_read(path) {
+            fs::read(path).ok()
+        } else {
+            None
+        };
+        pdf::save(document, path, self.save_options)?;
+        self.summary.file_written(path);
+
+        if let Some(original) = original {
+            if let Err(e) = self.write_journal(&original, document, path) {
+                warn!("Failed to write the journal of {path:?}, the edit cannot be undone: {e:#}");
+            }
+        }
+
+        if self.verify {
+            let reloaded = pdf::load(path, self.password.as_deref())
+                .with_context(|| format!("Failed to verify PDF written to: {path:?}"))?;
+            pdf::verify_round_trip(document, &reloaded)
+                .with_context(|| format!("Failed to verify PDF written to: {path:?}"))?;
+            debug!("Verified {path:?}");
+        }
+        Ok(())
+    }
+
+    /// Write the journal of a file edited in place, from its original data
+    /// and the document that was saved.
+    ///
+    /// Encrypted files are journaled as is, so that their decrypted objects
+    /// are never written in plain text.
+    fn write_journal(&self, data: &[u8], document: &Document, path: &Path) -> Result<()> {
+        let (original, encrypted) = pdf::load_mem_with_encryption(data, self.password.as_deref())?;
+        let checksum = hex::encode(Sha256::digest(fs::read(path)?));
+        let journal_path = undo::journal_path(path);
+        let mut journal = if encrypted {
+            journal::record_file(data, &checksum)
+        } else {
+            journal::record(&original, document, &checksum)
+        };
+        journal
+            .save(&journal_path)
+            .with_context(|| format!("Failed to write journal to: {journal_path:?}"))?;
+        debug!("Wrote journal of {path:?} to {journal_path:?}");
+        Ok(())
+    }
+
+    /// Return the current date, as a PDF date, or the source date if output
+    /// must be reproducible.
+    #[must_use]
+    pub fn date(&self) -> String {
+        let timestamp = self.save_options.source_date.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        });
+        reproducible::format_date(timestamp)
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Parser, Tag, TagEnd};
+use termcolor::WriteColor;
+use tracing::{debug, warn};
+
+use super::{PageSize, expand_tabs};
+use crate::{
+    cli::{context::ExecutionContext, traits::Execute},
+    pdf::{
+        fonts::StandardFont,
+        images,
+        layout::{FontId, Layout, LayoutFont, ParagraphStyle, Run},
+    },
+};
+
+/// Distance between baselines, relative to the font size.
+const LINE_HEIGHT: f32 = 1.3;
+
+/// Space left after paragraphs, relative to the body font size.
+const PARAGRAPH_SPACING: f32 = 0.6;
+
+/// Indentation of each level of lists and block quotes, in points.
+const INDENT: f32 = 18.0;
+
+/// Size of code, relative to the body font size.
+const CODE_RATIO: f32 = 0.9;
+
+/// Gray level of the background of code blocks.
+const CODE_BACKGROUND: f32 = 0.94;
+
+/// From-markdown command.
+#[derive(Args, Clone, Debug)]
+pub(super) struct FromMarkdown {
+    /// Markdown filepath.
+    #[clap(value_hint = ValueHint::FilePath)]
+    file: PathBuf,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "markdown.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+    /// Font size of body text, in points.
+    #[clap(long, default_value_t = 11.0)]
+    size: f32,
+    /// Page size.
+    #[clap(long, value_enum, default_value_t)]
+    page_size: PageSize,
+    /// Page margins, in points.
+    #[clap(long, default_value_t = 56.0)]
+    margin: f32,
+}
+
+/// Fonts used to render Markdown.
+struct Fonts {
+    regular: FontId,
+    bold: FontId,
+    italic: FontId,
+    bold_italic: FontId,
+    code: FontId,
+}
+
+/// Lays out Markdown events.
+struct Renderer<'a> {
+    layout: Layout,
+    fonts: Fonts,
+    /// Font size of body text.
+    size: f32,
+    /// Directory that relative image paths start from.
+    base_dir: &'a Path,
+    /// Inline text of the current block.
+    runs: Vec<(FontId, String)>,
+    /// Marker of the current list item, until its first line is laid out.
+    marker: Option<String>,
+    strong: usize,
+    emphasis: usize,
+    heading: Option<HeadingLevel>,
+    /// Next number of each nested list, or `None` for unordered lists.
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    /// Content of the current code block.
+    code_block: Option<String>,
+    /// Path and alternative text of the current image.
+    image: Option<(String, String)>,
+}
+
+/// Return the font size of a heading, relative to the body font size.
+fn heading_ratio(level: HeadingLevel) -> f32 {
+    match level {
+        HeadingLevel::H1 => 1.8,
+        HeadingLevel::H2 => 1.5,
+        HeadingLevel::H3 => 1.25,
+        HeadingLevel::H4 => 1.1,
+        HeadingLevel::H5 | HeadingLevel::H6 => 1.0,
+    }
+}
+
+impl<'a> Renderer<'a> {
+    fn new(mut layout: Layout, size: f32, base_dir: &'a Path) -> Self {
+        let mut add = |font| layout.add_font(LayoutFont::Standard(font));
+        let fonts = Fonts {
+            regular: add(StandardFont::Helvetica),
+            bold: add(StandardFont::HelveticaBold),
+            italic: add(StandardFont::HelveticaOblique),
+            bold_italic: add(StandardFont::HelveticaBoldOblique),
+            code: add(StandardFont::Courier),
+        };
+
+        Self {
+            layout,
+            fonts,
+            size,
+            base_dir,
+            runs: vec![],
+            marker: None,
+            strong: 0,
+            emphasis: 0,
+            heading: None,
+            lists: vec![],
+            quotes: 0,
+            code_block: None,
+            image: None,
+        }
+    }
+
+    /// Return the font of the current inline text.
+    fn font(&self) -> FontId {
+        let bold = self.strong > 0 || self.heading.is_some();
+
+        match (bold, self.emphasis > 0) {
+            (false, false) => self.fonts.regular,
+            (true, false) => self.fonts.bold,
+            (false, true) => self.fonts.italic,
+            (true, true) => self.fonts.bold_italic,
+        }
+    }
+
+    /// Return the font size of the current block.
+    fn font_size(&self) -> f32 {
+        self.heading
+            .map_or(self.size, |level| self.size * heading_ratio(level))
+    }
+
+    /// Return the indentation of the current block.
+    fn indent(&self) -> f32 {
+        (self.lists.len() + self.quotes) as f32 * INDENT
+    }
+
+    /// Append inline text to the current block.
+    fn push(&mut self, font: FontId, text: &str) {
+        match self.runs.last_mut() {
+            Some((last, last_text)) if *last == font => last_text.push_str(text),
+            _ => self.runs.push((font, text.to_string())),
+        }
+    }
+
+    /// Lay out the inline text of the current block, if any.
+    fn flush(&mut self) {
+        let runs = std::mem::take(&mut self.runs);
+
+        if runs.iter().all(|(_, text)| text.trim().is_empty()) && self.marker.is_none() {
+            return;
+        }
+
+        let size = self.font_size();
+        let marker = self.marker.take();
+        let runs: Vec<Run> = runs
+            .iter()
+            .map(|(font, text)| Run { font: *font, text })
+            .collect();
+
+        self.layout.paragraph(
+            &runs,
+            &ParagraphStyle {
+                size,
+                line_height: LINE_HEIGHT * size,
+                indent: self.indent(),
+                marker: marker.as_deref().map(|text| {
+                    Run {
+                        font: self.fonts.regular,
+                        text,
+                    }
+                }),
+                background: None,
+            },
+        );
+    }
+
+    /// Lay out the lines of a code block.
+    fn code_block(&mut self, code: &str) {
+        let size = CODE_RATIO * self.size;
+        let style = ParagraphStyle {
+            size,
+            line_height: LINE_HEIGHT * size,
+            indent: self.indent(),
+            marker: None,
+            background: Some(CODE_BACKGROUND),
+        };
+
+        for line in code.lines() {
+            let line = expand_tabs(line);
+            self.layout.paragraph(
+                &[Run {
+                    font: self.fonts.code,
+                    text: &line,
+                }],
+                &style,
+            );
+        }
+    }
+
+    /// Lay out an image, or its alternative text if it cannot be loaded.
+    fn image(&mut self, path: &str, alt: &str) {
+        let result = if path.contains("://") {
+            Err(anyhow::anyhow!("Remote images are not supported"))
+        } else {
+            let path = self.base_dir.join(path);
+            fs::read(&path)
+                .with_context(|| format!("Failed to read image from: {path:?}"))
+                .and_then(|data| images::import(&data))
+        };
+
+        match result {
+            Ok(image) => {
+                self.flush();
+                self.layout.image(image);
+            },
+            Err(e) => {
+                warn!("Skipping image {path:?}: {e:#}");
+                self.push(self.fonts.italic, &format!("[{alt}]"));
+            },
+        }
+    }
+
+    /// Lay out a Markdown event.
+    fn event(&mut self, event: Event) {
+        let spacing = PARAGRAPH_SPACING * self.size;
+
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                self.flush();
+                self.layout.space(self.size);
+                self.heading = Some(level);
+            },
+            Event::End(TagEnd::Heading(_)) => {
+                self.flush();
+                self.heading = None;
+                self.layout.space(spacing);
+            },
+            Event::End(TagEnd::Paragraph) => {
+                self.flush();
+                self.layout.space(spacing);
+            },
+            Event::Start(Tag::BlockQuote(_)) => {
+                self.flush();
+                self.quotes += 1;
+            },
+            Event::End(TagEnd::BlockQuote(_)) => {
+                self.flush();
+                self.quotes -= 1;
+            },
+            Event::Start(Tag::List(start)) => {
+                self.flush();
+                self.lists.push(start);
+            },
+            Event::End(TagEnd::List(_)) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.layout.space(spacing);
+                }
+            },
+            Event::Start(Tag::Item) => {
+                self.flush();
+                self.marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        Some(format!("{}.", *number - 1))
+                    },
+                    _ => Some("•".to_string()),
+                };
+            },
+            Event::End(TagEnd::Item) => self.flush(),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                self.flush();
+                if let CodeBlockKind::Fenced(language) = kind {
+                    debug!("Code block language: {language:?}");
+                }
+                self.code_block = Some(String::new());
+            },
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(code) = self.code_block.take() {
+                    self.code_block(&code);
+                }
+                self.layout.space(spacing);
+            },
+            Event::Start(Tag::Strong) => self.strong += 1,
+            Event::End(TagEnd::Strong) => self.strong -= 1,
+            Event::Start(Tag::Emphasis) => self.emphasis += 1,
+            Event::End(TagEnd::Emphasis) => self.emphasis -= 1,
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                self.image = Some((dest_url.to_string(), String::new()));
+            },
+            Event::End(TagEnd::Image) => {
+                if let Some((path, alt)) = self.image.take() {
+                    self.image(&path, &alt);
+                }
+            },
+            Event::Text(text) => {
+                if let Some(code) = &mut self.code_block {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = &mut self.image {
+                    alt.push_str(&text);
+                } else {
+                    self.push(self.font(), &text);
+                }
+            },
+            Event::Code(code) => self.push(self.fonts.code, &code),
+            Event::SoftBreak => self.push(self.font(), " "),
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush();
+                self.layout.rule(self.size);
+            },
+            Event::Html(html) | Event::InlineHtml(html) => {
+                debug!("Ignoring HTML: {html:?}");
+            },
+            _ => {},
+        }
+    }
+}
+
+impl Execute for FromMarkdown {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let markdown = fs::read_to_string(&self.file)
+            .with_context(|| format!("Failed to read Markdown from: {:?}", self.file))?;
+
+        if self.size <= 0.0 {
+            bail!("Font size must be positive, got {}.", self.size);
+        }
+
+        let (width, height) = self.page_size.dimensions();
+        let base_dir = self.file.parent().unwrap_or(Path::new(""));
+        let mut renderer =
+            Renderer::new(Layout::new(width, height, self.margin), self.size, base_dir);
+
+        for event in Parser::new(&markdown) {
+            renderer.event(event);
+        }
+        renderer.flush();
+
+        let mut document = renderer.layout.finish();
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully converted {:?} to {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use termcolor::WriteColor;
+
+use super::{PageSize, expand_tabs, load_font};
+use crate::{
+    cli::{context::ExecutionContext, traits::Execute},
+    pdf::layout::{Layout, ParagraphStyle, Run},
+};
+
+/// From-text command.
+#[derive(Args, Clone, Debug)]
+pub(super) struct FromText {
+    /// Text filepath.
+    #[clap(value_hint = ValueHint::FilePath)]
+    file: PathBuf,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "text.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+    /// Font, either a standard font (e.g., `Courier` or
+    /// `Helvetica-Bold`), a TrueType
+    /// font file, or the name of an installed TrueType font.
+    #[clap(long, default_value = "Courier")]
+    font: String,
+    /// Font size, in points.
+    #[clap(long, default_value_t = 10.0)]
+    size: f32,
+    /// Page size.
+    #[clap(long, value_enum, default_value_t)]
+    page_size: PageSize,
+    /// Page margins, in points.
+    #[clap(long, default_value_t = 56.0)]
+    margin: f32,
+}
+
+impl Execute for FromText {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let text = fs::read_to_string(&self.file)
+            .with_context(|| format!("Failed to read text from: {:?}", self.file))?;
+        let (width, height) = self.page_size.dimensions();
+        let mut layout = Layout::new(width, height, self.margin);
+        let font = layout.add_font(load_font(&self.font)?);
+        let line_height = 1.2 * self.size;
+
+        // Form feeds start new pages, as when printing.
+        for (i, page) in text.split('\x0C').enumerate() {
+            if i > 0 {
+                layout.page_break();
+            }
+            for line in page.lines() {
+                let line = expand_tabs(line);
+
+                if line.trim().is_empty() {
+                    layout.space(line_height);
+                } else {
+                    layout.paragraph(
+                        &[Run { font, text: &line }],
+                        &ParagraphStyle {
+                            size: self.size,
+                            line_height,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut document = layout.finish();
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully converted {:?} to {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::{Document, ObjectId};
+use rayon::prelude::*;
+use termcolor::WriteColor;
+use tracing::{debug, warn};
+
+use crate::{
+    cli::{complete::pdf_files, context::ExecutionContext, traits::Execute},
+    pdf::{
+        document::DocumentExt,
+        images::{self, PlacedImage},
+        selection::PageSelection,
+        text::{self, TextSpan},
+    },
+};
+
+/// Media box used for pages that do not define one, i.e., US Letter.
+const DEFAULT_MEDIA_BOX: [f32; 4] = [0.0, 0.0, 612.0, 792.0];
+
+/// Style sheet shared by all pages.
+const STYLE: &str = "body { margin: 0; padding: 1em; background: #777; font-family: sans-serif; }
+nav { text-align: center; margin: 0.5em; }
+nav a { color: #fff; margin: 0 1em; }
+.page { position: relative; margin: 0 auto; overflow: hidden; background: #fff; }
+.page .rotated { position: absolute; left: 0; top: 0; transform-origin: 0 0; }
+.page img { position: absolute; }
+.page span { position: absolute; white-space: pre; line-height: 1; transform-origin: 0 100%; }
+.page span.invisible { color: transparent; }";
+
+/// HTML command.
+#[derive(Args, Clone, Debug)]
+pub(super) struct Html {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Directory where HTML pages and images are written.
+    #[clap(short, long, default_value = "html", value_hint = ValueHint::DirPath)]
+    dest_dir: PathBuf,
+    /// Pages to convert, e.g., `2` or `1,3-5`.
+    #[clap(short, long, default_value = "all")]
+    pages: PageSelection,
+}
+
+/// Escape text for use in HTML content and attributes.
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Return the file name of a page.
+fn page_file_name(page: u32) -> String {
+    format!("page-{page}.html")
+}
+
+/// Write the HTML header of a document.
+fn header(html: &mut String, title: &str) {
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta \
+         charset=\"utf-8\">\n<title>{}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n",
+        escape(title)
+    );
+}
+
+impl Html {
+    /// Write an image XObject to the images directory, returning its path
+    /// relative to the destination directory, or `None` if it is not
+    /// supported.
+    fn write_image(&self, document: &Document, image: &PlacedImage) -> Result<Option<String>> {
+        let Some((format, data)) = images::export_web(document, image.stream) else {
+            warn!(
+                "Image {:?} uses an unsupported encoding, skipping it",
+                image.id
+            );
+            return Ok(None);
+        };
+        let path = format!(
+            "images/image-{}-{}.{}",
+            image.id.0,
+            image.id.1,
+            format.extension()
+        );
+
+        fs::write(self.dest_dir.join(&path), data)
+            .with_context(|| format!("Failed to write image to: {path:?}"))?;
+        Ok(Some(path))
+    }
+
+    /// Render the `i`-th converted page to HTML, with links to the previous
+    /// and next converted pages.
+    fn render_page(
+        &self,
+        document: &Document,
+        pages: &[(u32, ObjectId)],
+        i: usize,
+        placed_images: &[PlacedImage],
+        image_paths: &HashMap<ObjectId, Option<String>>,
+    ) -> Result<String> {
+        let (page, page_id) = pages[i];
+        let previous = i.checked_sub(1).map(|i| pages[i].0);
+        let next = pages.get(i + 1).map(|&(page, _)| page);
+        let [x0, y0, x1, y1] = document.page_crop_box(page_id).unwrap_or(DEFAULT_MEDIA_BOX);
+        let (width, height) = (x1 - x0, y1 - y0);
+        let page_rotation = document.page_rotation(page_id);
+        let mut html = String::new();
+
+        header(&mut html, &format!("{} - page {page}", self.file.display()));
+        html.push_str("<nav>");
+        if let Some(previous) = previous {
+            let _ = write!(
+                html,
+                "<a href=\"{}\">Previous</a>",
+                page_file_name(previous)
+            );
+        }
+        html.push_str("<a href=\"index.html\">Index</a>");
+        if let Some(next) = next {
+            let _ = write!(html, "<a href=\"{}\">Next</a>", page_file_name(next));
+        }
+        html.push_str("</nav>\n");
+        let (shown_width, shown_height) = if page_rotation % 180 == 0 {
+            (width, height)
+        } else {
+            (height, width)
+        };
+        let _ = writeln!(
+            html,
+            "<div class=\"page\" style=\"width: {shown_width:.2}pt; height: \
+             {shown_height:.2}pt;\">",
+        );
+        if page_rotation != 0 {
+            // Content is laid out unrotated, then rotated clockwise about the
+            // top-left corner, and moved back onto the page.
+            let (dx, dy) = match page_rotation {
+                90 => (height, 0.0),
+                180 => (width, height),
+                _ => (0.0, width),
+            };
+            let _ = writeln!(
+                html,
+                "<div class=\"rotated\" style=\"width: {width:.2}pt; height: {height:.2}pt; \
+                 transform: translate({dx:.2}pt, {dy:.2}pt) rotate({page_rotation}deg);\">",
+            );
+        }
+
+        for image in placed_images {
+            let Some(path) = image_paths.get(&image.id).and_then(Option::as_deref) else {
+                continue;
+            };
+
+            // Image space is the unit square, so its corners give the bounding box.
+            let corners = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+                .map(|(x, y)| image.ctm.apply(x, y));
+            let left = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min);
+            let right = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max);
+            let bottom = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min);
+            let top = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max);
+
+            let _ = writeln!(
+                html,
+                "<img src=\"{path}\" alt=\"\" style=\"left: {:.2}pt; top: {:.2}pt; width: \
+                 {:.2}pt; height: {:.2}pt;\">",
+                left - x0,
+                y1 - top,
+                right - left,
+                top - bottom
+            );
+        }
+
+        let spans = text::page_spans(document, page_id)
+            .with_context(|| format!("Failed to decode content of page {page}."))?;
+        for TextSpan {
+            text,
+            x,
+            y,
+            font_size,
+            angle,
+            render_mode,
+            ..
+        } in spans
+        {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let class = if render_mode == 3 {
+                " class=\"invisible\""
+            } else {
+                ""
+            };
+            let rotation = if angle.abs() > 0.01 {
+                format!(" transform: rotate({:.2}deg);", -angle)
+            } else {
+                String::new()
+            };
+
+            let _ = writeln!(
+                html,
+                "<span{class} style=\"left: {:.2}pt; top: {:.2}pt; font-size: \
+                 {font_size:.2}pt;{rotation}\">{}</span>",
+                x - x0,
+                y1 - y - font_size,
+                escape(&text)
+            );
+        }
+
+        if page_rotation != 0 {
+            html.push_str("</div>\n");
+        }
+        html.push_str("</div>\n</body>\n</html>\n");
+        Ok(html)
+    }
+}
+
+impl Execute for Html {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let pages = self.pages.select(&document);
+
+        if pages.is_empty() {
+            bail!(
+                "No page of {:?} matches the selection {}.",
+                self.file,
+                self.pages
+            );
+        }
+
+        fs::create_dir_all(self.dest_dir.join("images"))
+            .with_context(|| format!("Failed to create output directory: {:?}", self.dest_dir))?;
+
+        let pages: Vec<(u32, ObjectId)> = pages.into_iter().collect();
+        let placed_images = pages
+            .par_iter()
+            .map(|&(page, page_id)| {
+                images::page_images(&document, page_id)
+                    .with_context(|| format!("Failed to decode content of page {page}."))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Images drawn on several pages are only written once.
+        let unique_images: BTreeMap<ObjectId, &PlacedImage> = placed_images
+            .iter()
+            .flatten()
+            .map(|image| (image.id, image))
+            .collect();
+        let image_paths = unique_images
+            .par_iter()
+            .map(|(&id, image)| Ok((id, self.write_image(&document, image)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        pages
+            .par_iter()
+            .zip(&placed_images)
+            .enumerate()
+            .try_for_each(|(i, (&(page, _), placed_images))| {
+                debug!("Converting page {page}");
+                let html = self.render_page(&document, &pages, i, placed_images, &image_paths)?;
+                let path = self.dest_dir.join(page_file_name(page));
+
+                fs::write(&path, html).with_context(|| format!("Failed to write HTML to: {path:?}"))
+            })?;
+
+        let mut index = String::new();
+        header(&mut index, &self.file.display().to_string());
+        index.push_str("<nav>\n");
+        for &(page, _) in &pages {
+            let _ = writeln!(
+                index,
+                "<a href=\"{}\">Page {page}</a><br>",
+                page_file_name(page)
+            );
+        }
+
+        index.push_str("</nav>\n</body>\n</html>\n");
+        let path = self.dest_dir.join("index.html");
+        fs::write(&path, index).with_context(|| format!("Failed to write HTML to: {path:?}"))?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully converted {} pages to {:?}",
+                pages.len(),
+                self.dest_dir
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::ObjectId;
+use rayon::prelude::*;
+use termcolor::WriteColor;
+
+use crate::{
+    cli::{complete::pdf_files, context::ExecutionContext, output, traits::Execute},
+    pdf::{selection::PageSelection, text},
+};
+
+/// Font size ratio to the body text above which a line is a heading.
+const HEADING_RATIO: f32 = 1.15;
+
+/// Maximum number of words of a bold, body-sized line to be a heading.
+const MAX_BOLD_HEADING_WORDS: usize = 12;
+
+/// Vertical gap between two lines, relative to the font size, above which
+/// they belong to different blocks.
+const BLOCK_GAP: f32 = 1.6;
+
+/// Characters that start an unordered list item.
+const BULLETS: &[char] = &['•', '◦', '▪', '‣', '∙', '·', '-', '–', '*'];
+
+/// Markdown command.
+#[derive(Args, Clone, Debug)]
+pub(super) struct Markdown {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Output file where Markdown is written, instead of the standard output.
+    #[clap(short, long, value_hint = ValueHint::FilePath)]
+    dest: Option<PathBuf>,
+    /// Pages to convert, e.g., `2` or `1,3-5`.
+    #[clap(short, long, default_value = "all")]
+    pages: PageSelection,
+}
+
+/// A line of text with the properties used to classify it.
+struct Line {
+    page: u32,
+    y: f32,
+    x: f32,
+    font_size: f32,
+    bold: bool,
+    text: String,
+}
+
+/// A Markdown block.
+#[derive(Debug, PartialEq)]
+enum Block {
+    Heading(usize, String),
+    ListItem(String, String),
+    Paragraph(String),
+}
+
+impl Block {
+    /// Return the Markdown source of the block.
+    fn to_markdown(&self) -> String {
+        match self {
+            Self::Heading(level, text) => format!("{} {text}", "#".repeat(*level)),
+            Self::ListItem(marker, text) => format!("{marker} {text}"),
+            Self::Paragraph(text) => text.clone(),
+        }
+    }
+}
+
+/// Append a line to the text of a block, removing hyphenation.
+fn push_line(text: &mut String, line: &str) {
+    if text.ends_with('-') && !text.ends_with(" -") && line.starts_with(char::is_lowercase) {
+        text.pop();
+    } else if !text.is_empty() {
+        text.push(' ');
+    }
+    text.push_str(line);
+}
+
+/// Return the Markdown marker and the rest of a line, if it is a list item.
+fn list_item(text: &str) -> Option<(String, &str)> {
+    if let Some(rest) = text.strip_prefix(BULLETS) {
+        if rest.starts_with(char::is_whitespace) {
+            return Some(("-".to_string(), rest.trim_start()));
+        }
+    }
+
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let rest = &text[digits..];
+    if (1..=3).contains(&digits) {
+        if let Some(rest) = rest.strip_prefix(['.', ')']) {
+            if rest.starts_with(char::is_whitespace) {
+                return Some((format!("{}.", &text[..digits]), rest.trim_start()));
+            }
+        }
+    }
+    None
+}
+
+/// Return the font size used by most characters, rounded to half points.
+fn body_font_size(lines: &[Line]) -> f32 {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+
+    for line in lines {
+        *counts
+            .entry((line.font_size * 2.0).round() as u32)
+            .or_default() += line.text.chars().count();
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(size, count)| (count, size))
+        .map_or(0.0, |(size, _)| size as f32 / 2.0)
+}
+
+/// Group lines into Markdown blocks, using font sizes and weights to detect
+/// headings.
+///
+/// Paragraphs end at wide vertical gaps and before indented lines.
+fn blocks(lines: &[Line]) -> Vec<Block> {
+    let body = body_font_size(lines);
+    let mut heading_sizes: Vec<u32> = lines
+        .iter()
+        .filter(|line| line.font_size > body * HEADING_RATIO)
+        .map(|line| (line.font_size * 2.0).round() as u32)
+        .collect();
+    heading_sizes.sort_unstable_by(|a, b| b.cmp(a));
+    heading_sizes.dedup();
+
+    let heading_level = |line: &Line| {
+        if line.font_size > body * HEADING_RATIO {
+            let size = (line.font_size * 2.0).round() as u32;
+            heading_sizes
+                .iter()
+                .position(|&s| s == size)
+                .map(|i| (i + 1).min(6))
+        } else if line.bold
+            && line.text.split_whitespace().count() <= MAX_BOLD_HEADING_WORDS
+            && !line.text.ends_with(['.', ':', ','])
+        {
+            Some((heading_sizes.len() + 1).min(6))
+        } else {
+            None
+        }
+    };
+
+    let mut blocks: Vec<Block> = vec![];
+    let mut previous: Option<&Line> = None;
+    let mut item_x = f32::MAX;
+
+    for line in lines {
+        let close = previous.is_some_and(|previous| {
+            previous.page == line.page && previous.y - line.y <= BLOCK_GAP * line.font_size
+        });
+        let indented = previous.is_some_and(|previous| line.x - previous.x > line.font_size);
+
+        if let Some(level) = heading_level(line) {
+            match blocks.last_mut() {
+                Some(Block::Heading(current, text)) if close && *current == level => {
+                    push_line(text, &line.text);
+                },
+                _ => blocks.push(Block::Heading(level, line.text.clone())),
+            }
+        } else if let Some((marker, rest)) = list_item(&line.text) {
+            item_x = line.x;
+            blocks.push(Block::ListItem(marker, rest.to_string()));
+        } else {
+            match blocks.last_mut() {
+                Some(Block::ListItem(_, text)) if close && line.x > item_x => {
+                    push_line(text, &line.text);
+                },
+                Some(Block::Paragraph(text)) if close && !indented => {
+                    push_line(text, &line.text);
+                },
+                _ => blocks.push(Block::Paragraph(line.text.clone())),
+            }
+        }
+        previous = Some(line);
+    }
+
+    blocks
+}
+
+impl Execute for Markdown {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let pages = self.pages.select(&document);
+
+        if pages.is_empty() {
+            bail!(
+                "No page of {:?} matches the selection {}.",
+                self.file,
+                self.pages
+            );
+        }
+
+        let pages: Vec<(u32, ObjectId)> = pages.into_iter().collect();
+        let lines: Vec<Line> = pages
+            .par_iter()
+            .map(|&(page, page_id)| {
+                let spans = text::page_spans(&document, page_id)
+                    .with_context(|| format!("Failed to decode content of page {page}."))?;
+
+                Ok(text::reading_order(text::lines(&spans))
+                    .into_iter()
+                    .map(|line| {
+                        Line {
+                            page,
+                            y: line.y,
+                            x: line.spans.first().map_or(0.0, |span| span.x),
+                            font_size: line.font_size(),
+                            bold: line.spans.iter().all(text::TextSpan::is_bold),
+                            text: line.text().trim().to_string(),
+                        }
+                    })
+                    .collect::<Vec<_>>())
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut markdown = String::new();
+        let mut previous_item = false;
+        for block in blocks(&lines) {
+            let item = matches!(block, Block::ListItem(..));
+            if !markdown.is_empty() {
+                markdown.push_str(if item && previous_item { "\n" } else { "\n\n" });
+            }
+            markdown.push_str(&block.to_markdown());
+            previous_item = item;
+        }
+        markdown.push('\n');
+
+        match &self.dest {
+            Some(dest) => {
+                output::check_destination(ctx, dest)?;
+                fs::write(dest, markdown)
+                    .with_context(|| format!("Failed to write Markdown to: {dest:?}"))?;
+                ctx.summary.file_written(dest);
+                if !ctx.quiet() {
+                    writeln!(ctx.stdout, "Successfully wrote Markdown to {dest:?}")?;
+                }
+            },
+            None => write!(ctx.stdout, "{markdown}")?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+//! Conversion of PDF files to and from other formats.
+
+#[cfg(feature = "convert")]
+mod from_markdown;
+#[cfg(feature = "convert")]
+mod from_text;
+#[cfg(feature = "convert")]
+mod html;
+#[cfg(feature = "convert")]
+mod markdown;
+#[cfg(feature = "convert")]
+mod pdfa;
+#[cfg(feature = "convert")]
+mod svg;
+mod version;
+
+#[cfg(feature = "convert")]
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+#[cfg(feature = "convert")]
+use anyhow::{Context, bail};
+#[cfg(feature = "convert")]
+use clap::ValueEnum;
+use clap::{Parser, Subcommand};
+use termcolor::WriteColor;
+#[cfg(feature = "convert")]
+use tracing::debug;
+
+use super::{context::ExecutionContext, traits::Execute};
+#[cfg(feature = "convert")]
+use crate::pdf::{
+    fonts::{StandardFont, TrueTypeFont},
+    layout::LayoutFont,
+};
+
+/// Number of columns between tab stops.
+#[cfg(feature = "convert")]
+const TAB_WIDTH: usize = 4;
+
+/// Replace tabs with spaces, up to the next tab stop.
+#[cfg(feature = "convert")]
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::with_capacity(line.len());
+
+    for c in line.chars() {
+        if c == '\t' {
+            let column = expanded.chars().count();
+            expanded.extend(std::iter::repeat(' ').take(TAB_WIDTH - column % TAB_WIDTH));
+        } else {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+/// Size of generated pages.
+#[cfg(feature = "convert")]
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum PageSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+}
+
+#[cfg(feature = "convert")]
+impl PageSize {
+    /// Return the width and height of the page, in points.
+    fn dimensions(self) -> (f32, f32) {
+        match self {
+            Self::A4 => (595.28, 841.89),
+            Self::A5 => (419.53, 595.28),
+            Self::Letter => (612.0, 792.0),
+            Self::Legal => (612.0, 1008.0),
+        }
+    }
+}
+
+/// Return the directories where fonts are usually installed.
+#[cfg(feature = "convert")]
+fn font_directories() -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = [
+        "/usr/share/fonts",
+        "/usr/local/share/fonts",
+        "/Library/Fonts",
+        "/System/Library/Fonts",
+        "C:\\Windows\\Fonts",
+    ]
+    .into_iter()
+    .map(PathBuf::from)
+    .collect();
+
+    if let Some(home) = env::var_os("HOME") {
+        let home = PathBuf::from(home);
+        directories.push(home.join(".local/share/fonts"));
+        directories.push(home.join(".fonts"));
+        directories.push(home.join("Library/Fonts"));
+    }
+    directories
+}
+
+/// Find an installed TrueType font file whose name matches, ignoring case,
+/// spaces and a `Regular` suffix, e.g., `DejaVu Sans Mono` for
+/// `DejaVuSansMono.ttf`.
+#[cfg(feature = "convert")]
+fn find_font_file(directory: &Path, name: &str) -> Option<PathBuf> {
+    let normalize = |name: &str| -> String {
+        name.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase()
+    };
+    let name = normalize(name);
+
+    for entry in fs::read_dir(directory).ok()?.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            if let Some(path) = find_font_file(&path, &name) {
+                return Some(path);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ttf"))
+        {
+            let stem = normalize(&path.file_stem()?.to_string_lossy());
+            if stem == name || stem.strip_suffix("regular") == Some(&name) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+/// Load a font from a standard font name, a TrueType font file, or the name
+/// of an installed TrueType font.
+#[cfg(feature = "convert")]
+fn load_font(font: &str) -> Result<LayoutFont> {
+    if let Some(standard) = StandardFont::from_name(font) {
+        return Ok(LayoutFont::Standard(standard));
+    }
+
+    let path = if Path::new(font).is_file() {
+        PathBuf::from(font)
+    } else {
+        font_directories()
+            .iter()
+            .find_map(|directory| find_font_file(directory, font))
+            .with_context(|| {
+                format!(
+                    "Font {font:?} is neither a standard font, a file, nor an installed TrueType \
+                     font."
+                )
+            })?
+    };
+    debug!("Using font file {path:?}");
+
+    let data = fs::read(&path).with_context(|| format!("Failed to read font from: {path:?}"))?;
+    match TrueTypeFont::parse(data) {
+        Ok(font) => Ok(LayoutFont::TrueType(font)),
+        Err(e) => bail!("Failed to parse font from: {path:?}: {e}"),
+    }
+}
+
+/// Available subcommands for conversion.
+#[derive(Debug, Subcommand)]
+enum ConvertSubcommand {
+    /// Generate a PDF from a Markdown file.
+    #[cfg(feature = "convert")]
+    FromMarkdown(from_markdown::FromMarkdown),
+    /// Generate a paginated PDF from a plain-text file.
+    #[cfg(feature = "convert")]
+    FromText(from_text::FromText),
+    /// Convert pages to HTML, with positioned text and extracted images.
+    #[cfg(feature = "convert")]
+    Html(html::Html),
+    /// Convert text to Markdown, detecting headings, lists and paragraphs.
+    #[cfg(feature = "convert")]
+    Markdown(markdown::Markdown),
+    /// Convert a document to PDF/A, as far as it can be done automatically.
+    ///
+    /// Active content is removed, an sRGB output intent is embedded,
+    /// annotations are made printable, and XMP metadata identifying the
+    /// conformance level is written. Non-embedded fonts cannot be fixed, and
+    /// make the command fail after writing the file.
+    #[cfg(feature = "convert")]
+    Pdfa(pdfa::Pdfa),
+    /// Convert a page to an SVG vector graphic, with paths, images and text.
+    #[cfg(feature = "convert")]
+    Svg(svg::Svg),
+    /// Change the PDF version of a document, e.g., to 1.4 for old readers.
+    ///
+    /// Object streams are expanded, cross-reference streams are replaced
+    /// with a table before PDF 1.5, and features that the target version
+    /// does not support are reported, but kept.
+    Version(version::Version),
+}
+
+/// Convert PDF files to and from other formats.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct ConvertCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: ConvertSubcommand,
+}
+
+impl Execute for ConvertCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            #[cfg(feature = "convert")]
+            ConvertSubcommand::FromMarkdown(from_markdown) => from_markdown.execute(ctx),
+            #[cfg(feature = "convert")]
+            ConvertSubcommand::FromText(from_text) => from_text.execute(ctx),
+            #[cfg(feature = "convert")]
+            ConvertSubcommand::Html(html) => html.execute(ctx),
+            #[cfg(feature = "convert")]
+            ConvertSubcommand::Markdown(markdown) => markdown.execute(ctx),
+            #[cfg(feature = "convert")]
+            ConvertSubcommand::Pdfa(pdfa) => pdfa.execute(ctx),
+            #[cfg(feature = "convert")]
+            ConvertSubcommand::Svg(svg) => svg.execute(ctx),
+            ConvertSubcommand::Version(version) => version.execute(ctx),
+        }
+    }
+}
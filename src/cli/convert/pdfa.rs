@@ -0,0 +1,113 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use termcolor::WriteColor;
+use tracing::error;
+
+use crate::{
+    cli::{complete::pdf_files, context::ExecutionContext, traits::Execute},
+    pdf::{
+        colors::Family,
+        pdfa::{self, Level},
+    },
+};
+
+/// PDF/A conformance levels that documents can be converted to.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PdfaLevel {
+    /// PDF/A-1b, for viewers that only support PDF 1.4.
+    #[value(name = "1b")]
+    A1b,
+    /// PDF/A-2b, the most widely accepted level.
+    #[value(name = "2b")]
+    A2b,
+    /// PDF/A-3b, which allows embedding any file, e.g., invoice data.
+    #[value(name = "3b")]
+    A3b,
+}
+
+impl From<PdfaLevel> for Level {
+    fn from(level: PdfaLevel) -> Self {
+        match level {
+            PdfaLevel::A1b => Self::A1b,
+            PdfaLevel::A2b => Self::A2b,
+            PdfaLevel::A3b => Self::A3b,
+        }
+    }
+}
+
+/// PDF/A command.
+#[derive(Args, Clone, Debug)]
+pub(super) struct Pdfa {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// PDF/A conformance level.
+    #[clap(long, default_value = "2b")]
+    level: PdfaLevel,
+    /// RGB ICC profile embedded as the output intent, instead of a built-in
+    /// sRGB profile.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    icc: Option<PathBuf>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "pdfa.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Pdfa {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let profile = match &self.icc {
+            Some(path) => {
+                let profile = fs::read(path)
+                    .with_context(|| format!("Failed to read ICC profile from: {path:?}"))?;
+                if Family::of_icc_profile(&profile) != Some(Family::Rgb) {
+                    bail!("ICC profile {path:?} is not an RGB profile.");
+                }
+                Some(profile)
+            },
+            None => None,
+        };
+        let mut document = ctx.load_document(&self.file)?;
+        let report = pdfa::convert(&mut document, self.level.into(), profile, &ctx.date())
+            .with_context(|| format!("Failed to convert PDF to PDF/A from: {:?}", self.file))?;
+
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            for (threat, count) in &report.removed {
+                writeln!(ctx.stdout, "Removed {count} {}.", threat.description())?;
+            }
+            if report.annotations > 0 {
+                writeln!(
+                    ctx.stdout,
+                    "Made {} annotations visible and printable.",
+                    report.annotations
+                )?;
+            }
+        }
+        if !report.unembedded_fonts.is_empty() {
+            for (id, name) in &report.unembedded_fonts {
+                error!("Font {name} ({} {} R) is not embedded", id.0, id.1);
+            }
+            bail!(
+                "File {:?} is not PDF/A compliant, as {} fonts are not embedded.",
+                self.dest,
+                report.unembedded_fonts.len()
+            );
+        }
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully converted {:?} to PDF/A in {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,589 @@
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::{
+    Dictionary, Document, Object,
+    content::{Content, Operation},
+};
+use termcolor::WriteColor;
+use tracing::warn;
+
+use super::html::escape;
+use crate::{
+    cli::{complete::pdf_files, context::ExecutionContext, output, traits::Execute},
+    pdf::{
+        content::{Matrix, number},
+        document::DocumentExt,
+        images,
+        text::{self, TextSpan},
+    },
+};
+
+/// Media box used for pages that do not define one, i.e., US Letter.
+const DEFAULT_MEDIA_BOX: [f32; 4] = [0.0, 0.0, 612.0, 792.0];
+
+/// Maximum nesting depth of form XObjects, which guards against cycles in
+/// malformed documents.
+const MAX_FORM_DEPTH: usize = 16;
+
+/// SVG command.
+#[derive(Args, Clone, Debug)]
+pub(super) struct Svg {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Page to convert.
+    #[clap(short, long, default_value_t = 1)]
+    page: u32,
+    /// Output file where resulting SVG is written.
+    #[clap(short, long, default_value = "page.svg", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+/// Encode bytes in base64, for data URLs.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(
+                    ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize],
+                ));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Return an SVG color from the operands of a color operator, i.e., one gray,
+/// three RGB or four CMYK components.
+///
+/// Returns `None` for other color spaces, e.g., patterns.
+fn color(operands: &[Object]) -> Option<String> {
+    let components: Vec<f32> = operands.iter().map(number).collect::<Option<_>>()?;
+    let [r, g, b] = match components[..] {
+        [gray] => [gray; 3],
+        [r, g, b] => [r, g, b],
+        [c, m, y, k] => {
+            [
+                (1.0 - c) * (1.0 - k),
+                (1.0 - m) * (1.0 - k),
+                (1.0 - y) * (1.0 - k),
+            ]
+        },
+        _ => return None,
+    };
+    let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Some(format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b)))
+}
+
+/// Return the SVG transform attribute of a matrix.
+fn transform(matrix: &Matrix) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        matrix.a, matrix.b, matrix.c, matrix.d, matrix.e, matrix.f
+    )
+}
+
+/// Graphics state parameters that are rendered.
+#[derive(Clone, Debug)]
+struct GraphicsState {
+    ctm: Matrix,
+    fill: String,
+    stroke: String,
+    line_width: f32,
+    line_cap: i64,
+    line_join: i64,
+    miter_limit: f32,
+    dash: Option<(Vec<f32>, f32)>,
+    /// Index of the clipping path, if any.
+    clip: Option<usize>,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            ctm: Matrix::IDENTITY,
+            fill: "#000000".to_string(),
+            stroke: "#000000".to_string(),
+            line_width: 1.0,
+            line_cap: 0,
+            line_join: 0,
+            miter_limit: 10.0,
+            dash: None,
+            clip: None,
+        }
+    }
+}
+
+/// How a path is painted.
+#[derive(Clone, Copy, Debug)]
+struct Paint {
+    /// Fill the path, with the even-odd rule if `Some(true)`.
+    fill: Option<bool>,
+    stroke: bool,
+}
+
+/// Translates the paths and images of content streams to SVG elements, in
+/// default user space.
+struct Renderer<'a> {
+    document: &'a Document,
+    /// Graphic elements.
+    body: String,
+    /// Clipping paths, referred to by elements.
+    defs: String,
+    clip_count: usize,
+}
+
+impl Renderer<'_> {
+    /// Write an element, clipped by the current clipping path.
+    fn element(&mut self, state: &GraphicsState, element: &str) {
+        match state.clip {
+            Some(clip) => {
+                let _ = writeln!(self.body, "<g clip-path=\"url(#clip{clip})\">{element}</g>");
+            },
+            None => {
+                let _ = writeln!(self.body, "{element}");
+            },
+        }
+    }
+
+    /// Write a painted path.
+    fn path(&mut self, state: &GraphicsState, data: &str, paint: Paint) {
+        let mut element = format!("<path transform=\"{}\" d=\"{data}\"", transform(&state.ctm));
+
+        match paint.fill {
+            Some(even_odd) => {
+                let _ = write!(element, " fill=\"{}\"", state.fill);
+                if even_odd {
+                    element.push_str(" fill-rule=\"evenodd\"");
+                }
+            },
+            None => element.push_str(" fill=\"none\""),
+        }
+        if paint.stroke {
+            let _ = write!(
+                element,
+                " stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"{}\" stroke-linejoin=\"{}\" \
+                 stroke-miterlimit=\"{}\"",
+                state.stroke,
+                // Zero-width lines are the thinnest lines the device can draw.
+                if state.line_width > 0.0 {
+                    state.line_width
+                } else {
+                    0.1
+                },
+                ["butt", "round", "square"][state.line_cap.clamp(0, 2) as usize],
+                ["miter", "round", "bevel"][state.line_join.clamp(0, 2) as usize],
+                state.miter_limit.max(1.0)
+            );
+            if let Some((array, phase)) = &state.dash {
+                if !array.is_empty() {
+                    let array: Vec<String> = array.iter().map(f32::to_string).collect();
+                    let _ = write!(
+                        element,
+                        " stroke-dasharray=\"{}\" stroke-dashoffset=\"{phase}\"",
+                        array.join(" ")
+                    );
+                }
+            }
+        }
+        element.push_str("/>");
+        self.element(state, &element);
+    }
+
+    /// Intersect the clipping path with a path, returning the new clipping
+    /// path.
+    fn clip(&mut self, state: &GraphicsState, data: &str, even_odd: bool) -> usize {
+        let id = self.clip_count;
+        self.clip_count += 1;
+
+        let _ = write!(self.defs, "<clipPath id=\"clip{id}\"");
+        if let Some(clip) = state.clip {
+            let _ = write!(self.defs, " clip-path=\"url(#clip{clip})\"");
+        }
+        let _ = writeln!(
+            self.defs,
+            "><path transform=\"{}\" d=\"{data}\" clip-rule=\"{}\"/></clipPath>",
+            transform(&state.ctm),
+            if even_odd { "evenodd" } else { "nonzero" }
+        );
+        id
+    }
+
+    /// Write an XObject, i.e., an image or the content of a form.
+    fn xobject(
+        &mut self,
+        resources: Option<&Dictionary>,
+        name: &[u8],
+        state: &GraphicsState,
+        depth: usize,
+    ) {
+        let Some((id, stream)) = resources
+            .and_then(|resources| {
+                resources
+                    .get_deref(b"XObject", self.document)
+                    .and_then(Object::as_dict)
+                    .ok()
+            })
+            .and_then(|xobjects| xobjects.get(name).ok())
+            .and_then(|object| {
+                let id = object.as_reference().ok()?;
+                Some((id, self.document.get_object(id).ok()?.as_stream().ok()?))
+            })
+        else {
+            return;
+        };
+
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") => {
+                let Some((format, data)) = images::export_web(self.document, stream) else {
+                    warn!("Image {id:?} uses an unsupported encoding, skipping it");
+                    return;
+                };
+                // Images fill the unit square, upside down in SVG.
+                let element = format!(
+                    "<image transform=\"{} matrix(1 0 0 -1 0 1)\" width=\"1\" height=\"1\" \
+                     preserveAspectRatio=\"none\" xlink:href=\"data:{};base64,{}\"/>",
+                    transform(&state.ctm),
+                    format.mime_type(),
+                    base64(&data)
+                );
+                self.element(state, &element);
+            },
+            Ok(b"Form") if depth < MAX_FORM_DEPTH => {
+                let Ok(content) = stream
+                    .decompressed_content()
+                    .or_else(|_| Ok::<_, lopdf::Error>(stream.content.clone()))
+                    .and_then(|data| Content::decode(&data))
+                else {
+                    return;
+                };
+                let matrix = stream
+                    .dict
+                    .get(b"Matrix")
+                    .and_then(Object::as_array)
+                    .ok()
+                    .and_then(|matrix| Matrix::from_operands(matrix))
+                    .unwrap_or(Matrix::IDENTITY);
+                let form_resources = stream
+                    .dict
+                    .get_deref(b"Resources", self.document)
+                    .and_then(Object::as_dict)
+                    .ok()
+                    .or(resources);
+                let state = GraphicsState {
+                    ctm: matrix.then(&state.ctm),
+                    ..state.clone()
+                };
+
+                self.run(&content.operations, form_resources, state, depth + 1);
+            },
+            _ => {},
+        }
+    }
+
+    /// Write the paths and images of a content stream.
+    ///
+    /// Text is not written here, as it is extracted separately, and shadings
+    /// and inline images are skipped.
+    fn run(
+        &mut self,
+        operations: &[Operation],
+        resources: Option<&Dictionary>,
+        mut state: GraphicsState,
+        depth: usize,
+    ) {
+        let mut stack = vec![];
+        let mut data = String::new();
+        let mut current = (0.0, 0.0);
+        let mut pending_clip = None;
+
+        for operation in operations {
+            let operands = &operation.operands;
+            let numbers: Vec<f32> = operands.iter().filter_map(number).collect();
+
+            let paint = match operation.operator.as_str() {
+                "q" => {
+                    stack.push(state.clone());
+                    None
+                },
+                "Q" => {
+                    if let Some(saved) = stack.pop() {
+                        state = saved;
+                    }
+                    None
+                },
+                "cm" => {
+                    if let Some(matrix) = Matrix::from_operands(operands) {
+                        state.ctm = matrix.then(&state.ctm);
+                    }
+                    None
+                },
+                "w" => {
+                    state.line_width = numbers.first().copied().unwrap_or(1.0);
+                    None
+                },
+                "J" => {
+                    state.line_cap = operands
+                        .first()
+                        .and_then(|cap| cap.as_i64().ok())
+                        .unwrap_or(0);
+                    None
+                },
+                "j" => {
+                    state.line_join = operands
+                        .first()
+                        .and_then(|join| join.as_i64().ok())
+                        .unwrap_or(0);
+                    None
+                },
+                "M" => {
+                    state.miter_limit = numbers.first().copied().unwrap_or(10.0);
+                    None
+                },
+                "d" => {
+                    state.dash = match operands.as_slice() {
+                        [Object::Array(array), phase] => {
+                            Some((
+                                array.iter().filter_map(number).collect(),
+                                number(phase).unwrap_or(0.0),
+                            ))
+                        },
+                        _ => None,
+                    };
+                    None
+                },
+                "cs" => {
+                    state.fill = "#000000".to_string();
+                    None
+                },
+                "CS" => {
+                    state.stroke = "#000000".to_string();
+                    None
+                },
+                "g" | "rg" | "k" | "sc" | "scn" => {
+                    if let Some(color) = color(operands) {
+                        state.fill = color;
+                    }
+                    None
+                },
+                "G" | "RG" | "K" | "SC" | "SCN" => {
+                    if let Some(color) = color(operands) {
+                        state.stroke = color;
+                    }
+                    None
+                },
+                "m" | "l" => {
+                    if let [x, y] = numbers[..] {
+                        let command = if operation.operator == "m" { 'M' } else { 'L' };
+                        let _ = write!(data, "{command}{x} {y}");
+                        current = (x, y);
+                    }
+                    None
+                },
+                "c" | "v" | "y" => {
+                    let points = match (operation.operator.as_str(), &numbers[..]) {
+                        ("c", &[x1, y1, x2, y2, x3, y3]) => Some([x1, y1, x2, y2, x3, y3]),
+                        ("v", &[x2, y2, x3, y3]) => Some([current.0, current.1, x2, y2, x3, y3]),
+                        ("y", &[x1, y1, x3, y3]) => Some([x1, y1, x3, y3, x3, y3]),
+                        _ => None,
+                    };
+                    if let Some([x1, y1, x2, y2, x3, y3]) = points {
+                        let _ = write!(data, "C{x1} {y1} {x2} {y2} {x3} {y3}");
+                        current = (x3, y3);
+                    }
+                    None
+                },
+                "re" => {
+                    if let [x, y, width, height] = numbers[..] {
+                        let _ = write!(data, "M{x} {y}h{width}v{height}h{}Z", -width);
+                        current = (x, y);
+                    }
+                    None
+                },
+                "h" => {
+                    data.push('Z');
+                    None
+                },
+                "W" => {
+                    pending_clip = Some(false);
+                    None
+                },
+                "W*" => {
+                    pending_clip = Some(true);
+                    None
+                },
+                "S" | "s" => {
+                    if operation.operator == "s" {
+                        data.push('Z');
+                    }
+                    Some(Paint {
+                        fill: None,
+                        stroke: true,
+                    })
+                },
+                "f" | "F" | "f*" => {
+                    Some(Paint {
+                        fill: Some(operation.operator == "f*"),
+                        stroke: false,
+                    })
+                },
+                "B" | "B*" | "b" | "b*" => {
+                    if operation.operator.starts_with('b') {
+                        data.push('Z');
+                    }
+                    Some(Paint {
+                        fill: Some(operation.operator.ends_with('*')),
+                        stroke: true,
+                    })
+                },
+                "n" => {
+                    Some(Paint {
+                        fill: None,
+                        stroke: false,
+                    })
+                },
+                "Do" => {
+                    if let Some(name) = operands.first().and_then(|name| name.as_name().ok()) {
+                        self.xobject(resources, name, &state, depth);
+                    }
+                    None
+                },
+                _ => None,
+            };
+
+            if let Some(paint) = paint {
+                if !data.is_empty() {
+                    if paint.fill.is_some() || paint.stroke {
+                        self.path(&state, &data, paint);
+                    }
+                    if let Some(even_odd) = pending_clip.take() {
+                        state.clip = Some(self.clip(&state, &data, even_odd));
+                    }
+                }
+                data.clear();
+                pending_clip = None;
+            }
+        }
+    }
+}
+
+/// Return the SVG element of a text span, in page coordinates with the origin
+/// at the top-left corner of the crop box.
+fn text_element(span: &TextSpan, x0: f32, y1: f32) -> String {
+    let (x, y) = (span.x - x0, y1 - span.y);
+    let family = span.font.split(['-', ',']).next().unwrap_or_default();
+    let mut element = format!(
+        "<text x=\"{x:.2}\" y=\"{y:.2}\" font-size=\"{:.2}\" font-family=\"{}, sans-serif\"",
+        span.font_size,
+        escape(family)
+    );
+
+    if span.is_bold() {
+        element.push_str(" font-weight=\"bold\"");
+    }
+    if span.angle.abs() > 0.01 {
+        let _ = write!(
+            element,
+            " transform=\"rotate({:.2} {x:.2} {y:.2})\"",
+            -span.angle
+        );
+    }
+    // Control characters are not allowed in XML, e.g., from unmapped glyphs.
+    let text: String = span.text.chars().filter(|c| !c.is_control()).collect();
+    let _ = write!(element, ">{}</text>", escape(&text));
+    element
+}
+
+impl Execute for Svg {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let Some(&page_id) = document.get_pages().get(&self.page) else {
+            bail!("Document {:?} has no page {}.", self.file, self.page);
+        };
+        let content = document
+            .get_and_decode_page_content(page_id)
+            .with_context(|| format!("Failed to decode content of page {}.", self.page))?;
+        let spans = text::page_spans(&document, page_id)
+            .with_context(|| format!("Failed to decode content of page {}.", self.page))?;
+
+        let mut renderer = Renderer {
+            document: &document,
+            body: String::new(),
+            defs: String::new(),
+            clip_count: 0,
+        };
+        renderer.run(
+            &content.operations,
+            document.page_resources(page_id),
+            GraphicsState::default(),
+            0,
+        );
+
+        let [x0, y0, x1, y1] = document.page_crop_box(page_id).unwrap_or(DEFAULT_MEDIA_BOX);
+        let (width, height) = (x1 - x0, y1 - y0);
+        let rotation = document.page_rotation(page_id);
+        let (shown_width, shown_height) = if rotation % 180 == 0 {
+            (width, height)
+        } else {
+            (height, width)
+        };
+        // Content is laid out unrotated, then rotated clockwise about the
+        // top-left corner, and moved back onto the page.
+        let (dx, dy) = match rotation {
+            90 => (height, 0.0),
+            180 => (width, height),
+            270 => (0.0, width),
+            _ => (0.0, 0.0),
+        };
+
+        let mut svg = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" xml:space=\"preserve\" \
+             width=\"{shown_width}pt\" height=\"{shown_height}pt\" viewBox=\"0 0 {shown_width} \
+             {shown_height}\">\n<defs>\n{}</defs>\n<g transform=\"translate({dx} {dy}) \
+             rotate({rotation})\">\n<g transform=\"matrix(1 0 0 -1 {} {y1})\">\n{}</g>\n",
+            renderer.defs,
+            0.0 - x0,
+            renderer.body
+        );
+        for span in spans {
+            if span.render_mode != 3 && !span.text.trim().is_empty() {
+                svg.push_str(&text_element(&span, x0, y1));
+                svg.push('\n');
+            }
+        }
+        svg.push_str("</g>\n</svg>\n");
+
+        output::check_destination(ctx, &self.dest)?;
+        fs::write(&self.dest, svg)
+            .with_context(|| format!("Failed to write SVG to: {:?}", self.dest))?;
+        ctx.summary.file_written(&self.dest);
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully converted page {} to {:?}",
+                self.page, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
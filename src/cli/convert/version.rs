@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{
+    Args, ValueHint,
+    builder::{PossibleValuesParser, TypedValueParser},
+};
+use clap_complete::ArgValueCompleter;
+use termcolor::WriteColor;
+use tracing::warn;
+
+use crate::{
+    cli::{complete::pdf_files, context::ExecutionContext, traits::Execute},
+    pdf::version,
+};
+
+/// Version command.
+#[derive(Args, Clone, Debug)]
+pub(super) struct Version {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Target PDF version.
+    #[clap(
+        long,
+        value_parser = PossibleValuesParser::new(version::VERSIONS).try_map(|s| s.parse::<version::Version>())
+    )]
+    to: version::Version,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "converted.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Version {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let from = document.version.clone();
+        let unsupported = version::convert(&mut document, self.to);
+
+        for feature in &unsupported {
+            warn!(
+                "File {:?} uses {}, which needs PDF {} and may be ignored by PDF {} readers",
+                self.file, feature.description, feature.version, self.to
+            );
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully converted {:?} from PDF {from} to PDF {} in {:?}.",
+                self.file, self.to, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
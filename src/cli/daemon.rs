@@ -0,0 +1,306 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use lopdf::Document;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use termcolor::WriteColor;
+use tracing::debug;
+
+use super::{context::ExecutionContext, traits::Execute};
+use crate::pdf::annotations;
+#[cfg(feature = "regex")]
+use crate::pdf::text;
+
+/// Answer requests read from stdin, one JSON object per line, e.g., for
+/// editor integrations that query the same files repeatedly.
+///
+/// Requests and responses follow JSON-RPC 2.0, e.g.,
+/// `{"jsonrpc": "2.0", "id": 1, "method": "stats", "params": {"file":
+/// "a.pdf"}}`, and responses are written to stdout, one per line. Methods
+/// are:
+///
+/// - `stats`: count the annotations of `file`, per subtype;
+/// - `list`: list the annotations of `file`, except those whose subtype is in
+///   `exclude`, by default popups, and hidden ones if `skip_hidden` is `true`;
+/// - `search`: find the lines of text of `file` that match the regular
+///   expression `pattern`, with the `regex` feature;
+/// - `shutdown`: stop the daemon, as closing stdin does.
+///
+/// Recently used documents are kept in memory, and loaded again when their
+/// file changes.
+#[derive(Debug, Parser)]
+pub struct DaemonCommand {
+    /// Number of documents kept in memory.
+    #[clap(long, default_value_t = 8, value_parser = clap::value_parser!(u64).range(1..))]
+    cache_size: u64,
+}
+
+/// Request, as JSON-RPC 2.0.
+#[derive(Deserialize)]
+struct Request {
+    /// Identifier of the request, which notifications do not have.
+    ///
+    /// Requests may have a `null` identifier, unlike notifications, which
+    /// have none.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Deserialize a field that is present, even if it is `null`, which serde
+/// otherwise reads as `None`, like a missing field.
+fn deserialize_present<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Error of a request, with its JSON-RPC 2.0 code.
+#[derive(Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Code of requests that are not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// Code of requests that are not valid JSON-RPC requests, e.g., without
+/// method.
+const INVALID_REQUEST: i64 = -32600;
+/// Code of unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Code of missing or invalid parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// Code of valid requests that failed, e.g., on files that cannot be read.
+const SERVER_ERROR: i64 = -32000;
+
+impl From<anyhow::Error> for RpcError {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            code: SERVER_ERROR,
+            message: format!("{error:#}"),
+        }
+    }
+}
+
+/// Parameters of methods that only take a file.
+#[derive(Deserialize)]
+struct FileParams {
+    file: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    file: PathBuf,
+    #[serde(default = "default_list_exclude")]
+    exclude: Vec<String>,
+    #[serde(default)]
+    skip_hidden: bool,
+}
+
+fn default_list_exclude() -> Vec<String> {
+    vec!["Popup".to_string()]
+}
+
+#[cfg(feature = "regex")]
+#[derive(Deserialize)]
+struct SearchParams {
+    file: PathBuf,
+    pattern: String,
+}
+
+/// Line of text matching a search.
+#[cfg(feature = "regex")]
+#[derive(Serialize)]
+struct SearchMatch {
+    page: u32,
+    text: String,
+}
+
+/// Parse the parameters of a method.
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|error| {
+        RpcError {
+            code: INVALID_PARAMS,
+            message: error.to_string(),
+        }
+    })
+}
+
+/// Return the modification time of a file, if available.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Recently used documents, the most recently used last.
+struct DocumentCache {
+    capacity: usize,
+    entries: VecDeque<(PathBuf, Option<SystemTime>, Document)>,
+}
+
+impl DocumentCache {
+    /// Return a document, loading it if it is not cached or if its file
+    /// changed since.
+    fn get<W>(&mut self, ctx: &ExecutionContext<W>, path: &Path) -> Result<&Document> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let modified = modified(&path);
+
+        if let Some(index) = self.entries.iter().position(|(cached, ..)| *cached == path) {
+            let entry = self.entries.remove(index).unwrap();
+            if entry.1 == modified && modified.is_some() {
+                debug!("Using cached document {path:?}");
+                self.entries.push_back(entry);
+                return Ok(&self.entries.back().unwrap().2);
+            }
+        }
+
+        let document = ctx.load_document_without_images(&path)?;
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((path, modified, document));
+        Ok(&self.entries.back().unwrap().2)
+    }
+}
+
+/// Return the lines of text of a document that match a regular expression.
+#[cfg(feature = "regex")]
+fn search(document: &Document, pattern: &Regex) -> Result<Vec<SearchMatch>> {
+    let mut matches = vec![];
+    for (page, page_id) in document.get_pages() {
+        let spans = text::page_spans(document, page_id)
+            .with_context(|| format!("Failed to read the text of page {page}"))?;
+        matches.extend(
+            text::lines(&spans)
+                .iter()
+                .map(text::TextLine::text)
+                .filter(|text| pattern.is_match(text))
+                .map(|text| SearchMatch { page, text }),
+        );
+    }
+    Ok(matches)
+}
+
+impl DaemonCommand {
+    /// Answer a request, returning its result.
+    fn handle<W>(
+        cache: &mut DocumentCache,
+        ctx: &ExecutionContext<W>,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, RpcError> {
+        let result = match method {
+            "stats" => {
+                let params: FileParams = parse_params(params)?;
+                json!(annotations::stats(cache.get(ctx, &params.file)?))
+            },
+            "list" => {
+                let params: ListParams = parse_params(params)?;
+                let document = cache.get(ctx, &params.file)?;
+                let mut annotations =
+                    annotations::read_annotations(document, &params.exclude, params.skip_hidden);
+                annotations::resolve_marked_text(document, &mut annotations);
+                json!(annotations)
+            },
+            #[cfg(feature = "regex")]
+            "search" => {
+                let params: SearchParams = parse_params(params)?;
+                let pattern = Regex::new(&params.pattern).map_err(|error| {
+                    RpcError {
+                        code: INVALID_PARAMS,
+                        message: error.to_string(),
+                    }
+                })?;
+                json!(search(cache.get(ctx, &params.file)?, &pattern)?)
+            },
+            #[cfg(not(feature = "regex"))]
+            "search" => {
+                return Err(RpcError {
+                    code: METHOD_NOT_FOUND,
+                    message: "The search method requires the `regex` feature.".to_string(),
+                });
+            },
+            "shutdown" => Value::Null,
+            _ => {
+                return Err(RpcError {
+                    code: METHOD_NOT_FOUND,
+                    message: format!("Unknown method: {method:?}."),
+                });
+            },
+        };
+        Ok(result)
+    }
+}
+
+impl Execute for DaemonCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut cache = DocumentCache {
+            capacity: usize::try_from(self.cache_size).unwrap_or(usize::MAX),
+            entries: VecDeque::new(),
+        };
+
+        for line in io::stdin().lock().lines() {
+            let line = line.context("Failed to read request from stdin")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request = serde_json::from_str(&line)
+                .map_err(|error| (PARSE_ERROR, error))
+                .and_then(|value| {
+                    serde_json::from_value::<Request>(value)
+                        .map_err(|error| (INVALID_REQUEST, error))
+                });
+            let (id, result, shutdown) = match request {
+                Ok(request) => {
+                    (
+                        request.id,
+                        Self::handle(&mut cache, ctx, &request.method, request.params),
+                        request.method == "shutdown",
+                    )
+                },
+                Err((code, error)) => {
+                    (
+                        Some(Value::Null),
+                        Err(RpcError {
+                            code,
+                            message: error.to_string(),
+                        }),
+                        false,
+                    )
+                },
+            };
+
+            // Notifications, i.e., requests without identifier, get no
+            // response.
+            if let Some(id) = id {
+                let response = match result {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+                };
+                writeln!(ctx.stdout, "{response}")?;
+                ctx.stdout.flush()?;
+            }
+            if shutdown {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+//! Commands helping to develop rpdf and to report bugs.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueHint};
+use clap_complete::ArgValueCandidates;
+use termcolor::WriteColor;
+
+use super::{complete::annotation_subtypes, context::ExecutionContext, traits::Execute};
+use crate::pdf::fixtures::{DEFAULT_SUBTYPES, Fixture};
+
+/// Make-fixture command.
+#[derive(Args, Clone, Debug)]
+struct MakeFixture {
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "fixture.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+    /// Number of pages.
+    #[clap(short, long, default_value_t = 1)]
+    pages: u32,
+    /// Number of annotations, spread over the pages.
+    #[clap(short, long, default_value_t = 0)]
+    annotations: usize,
+    /// Annotation subtypes, used in turn (multiple values allowed).
+    #[clap(short, long, default_values = DEFAULT_SUBTYPES, action = ArgAction::Append, add = ArgValueCandidates::new(annotation_subtypes))]
+    subtype: Vec<String>,
+    /// Number of text form fields.
+    #[clap(long, default_value_t = 0)]
+    fields: usize,
+    /// Number of bookmarks.
+    #[clap(short, long, default_value_t = 0)]
+    bookmarks: usize,
+    /// Tag the document, with page titles as headings of its structure tree.
+    #[clap(long)]
+    tagged: bool,
+    /// Number of layers, each showing a line of text on every page.
+    #[clap(long, default_value_t = 0)]
+    layers: usize,
+}
+
+impl Execute for MakeFixture {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let fixture = Fixture {
+            pages: self.pages,
+            annotations: self.annotations,
+            subtypes: self.subtype.clone(),
+            fields: self.fields,
+            bookmarks: self.bookmarks,
+            tagged: self.tagged,
+            layers: self.layers,
+        };
+        ctx.save_document(&mut fixture.build(), &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(ctx.stdout, "Successfully wrote fixture to {:?}.", self.dest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for debug.
+#[derive(Debug, Subcommand)]
+enum DebugSubcommand {
+    /// Build a small PDF with the given pages, annotations, form fields and
+    /// bookmarks, e.g., for tests or bug reports.
+    MakeFixture(MakeFixture),
+}
+
+/// Tools for developing rpdf and reporting bugs.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct DebugCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: DebugSubcommand,
+}
+
+impl Execute for DebugCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            DebugSubcommand::MakeFixture(make_fixture) => make_fixture.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use std::{env, fs};
+#[cfg(feature = "ocr")]
+use std::{path::PathBuf, process::Command};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+#[cfg(feature = "ocr")]
+use clap::ValueHint;
+use is_terminal::IsTerminal;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+use super::{context::ExecutionContext, plugins, traits::Execute};
+
+/// Environment variables that rpdf reads, or passes to plugins.
+const ENVIRONMENT_VARIABLES: &[&str] = &[
+    "RPDF",
+    "RPDF_CACHE_DIR",
+    "RPDF_COLOR",
+    "RPDF_LANG",
+    "RPDF_MAX_MEMORY",
+    "RPDF_NO_CACHE",
+    "RPDF_NO_INPUT",
+    "RPDF_NO_JOURNAL",
+    "RPDF_PASSWORD",
+    "RPDF_PLUGIN_PROTOCOL",
+    "RPDF_TESSERACT",
+    "RPDF_TIMEOUT",
+    "RPDF_VERBOSITY",
+];
+
+/// Optional features, and whether they were enabled at build time.
+const FEATURES: &[(&str, bool)] = &[
+    ("images", cfg!(feature = "images")),
+    ("ocr", cfg!(feature = "ocr")),
+    ("convert", cfg!(feature = "convert")),
+    ("net", cfg!(feature = "net")),
+    ("server", cfg!(feature = "server")),
+    ("scripting", cfg!(feature = "scripting")),
+    ("i18n", cfg!(feature = "i18n")),
+    ("parallel", cfg!(feature = "parallel")),
+    ("qrcode", cfg!(feature = "qrcode")),
+    ("regex", cfg!(feature = "regex")),
+];
+
+/// Outcome of a check.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Status {
+    Ok,
+    Skipped,
+    Warning,
+    Error,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Skipped => "skipped",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+
+    fn color(self) -> Option<Color> {
+        match self {
+            Self::Ok => Some(Color::Green),
+            Self::Skipped => None,
+            Self::Warning => Some(Color::Yellow),
+            Self::Error => Some(Color::Red),
+        }
+    }
+}
+
+/// Result of a check, with how to fix the problem found, if any.
+#[derive(Debug)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    message: String,
+    remediation: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            message: message.into(),
+            remediation: None,
+        }
+    }
+
+    fn remediation(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+}
+
+/// Check the environment rpdf runs in, i.e., its optional features and
+/// tools, its cache directory, environment variables, locale, colors and
+/// plugins, and print how to fix the problems found.
+///
+/// Fails if any check fails, while warnings only point out what may not
+/// work as expected.
+#[derive(Debug, Parser)]
+pub struct DoctorCommand {
+    /// Path to the Tesseract executable.
+    #[cfg(feature = "ocr")]
+    #[clap(long, default_value = "tesseract", env = "RPDF_TESSERACT", value_hint = ValueHint::ExecutablePath)]
+    tesseract: PathBuf,
+}
+
+/// Return the optional features that rpdf was built with.
+fn check_features() -> Check {
+    let enabled: Vec<&str> = FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    let disabled: Vec<&str> = FEATURES
+        .iter()
+        .filter(|(_, enabled)| !enabled)
+        .map(|(name, _)| *name)
+        .collect();
+
+    if disabled.is_empty() {
+        return Check::new("features", Status::Ok, "all optional features are enabled");
+    }
+    Check::new(
+        "features",
+        Status::Ok,
+        format!(
+            "enabled: {}, disabled: {}",
+            if enabled.is_empty() {
+                "none".to_string()
+            } else {
+                enabled.join(", ")
+            },
+            disabled.join(", ")
+        ),
+    )
+    .remediation(format!(
+        "Reinstall with `cargo install rpdf --features {}` to enable the disabled ones.",
+        disabled.join(",")
+    ))
+}
+
+impl DoctorCommand {
+    /// Check that Tesseract runs, which `rpdf ocr` needs.
+    #[cfg(feature = "ocr")]
+    fn check_tesseract(&self) -> Check {
+        let remediation =
+            "Install Tesseract, see <https://tesseract-ocr.github.io/tessdoc/Installation.html>, \
+             or set RPDF_TESSERACT to the path of its executable.";
+        match Command::new(&self.tesseract).arg("--version").output() {
+            Ok(output) if output.status.success() => {
+                // Old versions print their version to standard error.
+                let version = [&output.stdout, &output.stderr]
+                    .into_iter()
+                    .find_map(|bytes| {
+                        String::from_utf8_lossy(bytes)
+                            .lines()
+                            .next()
+                            .map(|line| line.trim().to_string())
+                            .filter(|line| !line.is_empty())
+                    })
+                    .unwrap_or_else(|| "unknown version".to_string());
+                Check::new(
+                    "tesseract",
+                    Status::Ok,
+                    format!("{version}, from {:?}", self.tesseract),
+                )
+            },
+            Ok(output) => {
+                Check::new(
+                    "tesseract",
+                    Status::Error,
+                    format!("{:?} failed with {}", self.tesseract, output.status),
+                )
+                .remediation(remediation)
+            },
+            Err(e) => {
+                Check::new(
+                    "tesseract",
+                    Status::Error,
+                    format!("failed to run {:?}: {e}", self.tesseract),
+                )
+                .remediation(remediation)
+            },
+        }
+    }
+
+    /// Return that Tesseract is not checked, since `rpdf ocr` is not built.
+    #[cfg(not(feature = "ocr"))]
+    #[allow(clippy::unused_self)]
+    fn check_tesseract(&self) -> Check {
+        Check::new(
+            "tesseract",
+            Status::Skipped,
+            "the `ocr` feature, which runs Tesseract, is disabled",
+        )
+    }
+}
+
+/// Check that the cache directory can be written.
+fn check_cache<W>(ctx: &ExecutionContext<W>) -> Check {
+    if !ctx.cache.is_enabled() {
+        return Check::new("cache", Status::Skipped, "caching is disabled");
+    }
+    let Some(dir) = ctx.cache.dir() else {
+        return Check::new("cache", Status::Warning, "no cache directory was found")
+            .remediation("Set RPDF_CACHE_DIR or HOME, or use --no-cache.");
+    };
+    let writable = fs::create_dir_all(dir).and_then(|()| {
+        tempfile::Builder::new()
+            .prefix(".rpdf-")
+            .suffix(".tmp")
+            .tempfile_in(dir)
+            .map(drop)
+    });
+
+    match writable {
+        Ok(()) => Check::new("cache", Status::Ok, format!("{dir:?} is writable")),
+        Err(e) => {
+            Check::new(
+                "cache",
+                Status::Error,
+                format!("{dir:?} is not writable: {e}"),
+            )
+            .remediation(format!(
+                "Fix the permissions of {dir:?}, choose another directory with RPDF_CACHE_DIR, or \
+                 use --no-cache."
+            ))
+        },
+    }
+}
+
+/// Check that the environment variables starting with `RPDF_` are known, as
+/// misspelled ones are silently ignored.
+fn check_environment() -> Vec<Check> {
+    let mut names: Vec<String> = env::vars_os()
+        .filter_map(|(name, _)| name.into_string().ok())
+        .filter(|name| name.starts_with("RPDF_"))
+        .collect();
+    names.sort();
+    let (known, unknown): (Vec<String>, Vec<String>) = names
+        .into_iter()
+        .partition(|name| ENVIRONMENT_VARIABLES.contains(&name.as_str()));
+
+    let mut checks = vec![Check::new(
+        "environment",
+        Status::Ok,
+        if known.is_empty() {
+            "no RPDF_* variable is set".to_string()
+        } else {
+            format!("set: {}", known.join(", "))
+        },
+    )];
+    checks.extend(unknown.into_iter().map(|name| {
+        Check::new(
+            "environment",
+            Status::Warning,
+            format!("{name} is not used by rpdf"),
+        )
+        .remediation(format!(
+            "Check its spelling, known variables are {}.",
+            ENVIRONMENT_VARIABLES.join(", ")
+        ))
+    }));
+    checks
+}
+
+/// Check that the locale uses UTF-8, so that non-ASCII text, e.g., in
+/// annotations, prints correctly.
+fn check_locale() -> Check {
+    if cfg!(windows) {
+        return Check::new(
+            "locale",
+            Status::Skipped,
+            "Windows consoles do not use locale variables",
+        );
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].into_iter().find_map(|name| {
+        env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| (name, value))
+    });
+
+    match locale {
+        Some((name, value)) => {
+            let normalized = value.to_ascii_lowercase().replace('-', "");
+            if normalized.contains("utf8") {
+                Check::new("locale", Status::Ok, format!("{name}={value}"))
+            } else {
+                Check::new(
+                    "locale",
+                    Status::Warning,
+                    format!("{name}={value} does not use UTF-8"),
+                )
+                .remediation(
+                    "Non-ASCII text may not print correctly, set LANG to a UTF-8 locale, e.g., \
+                     `C.UTF-8`.",
+                )
+            }
+        },
+        None => {
+            Check::new("locale", Status::Warning, "no locale is set").remediation(
+                "Non-ASCII text may not print correctly, set LANG to a UTF-8 locale, e.g., \
+                 `C.UTF-8`.",
+            )
+        },
+    }
+}
+
+/// Return whether output is colorized, and why.
+fn check_colors<W>(ctx: &ExecutionContext<W>) -> Check {
+    if ctx.color.enabled() {
+        let dumb = env::var("TERM").is_ok_and(|term| term == "dumb");
+        if dumb {
+            return Check::new(
+                "colors",
+                Status::Warning,
+                "output is colorized, but TERM=dumb",
+            )
+            .remediation("Use --color=never if escape codes show up in the output.");
+        }
+        return Check::new("colors", Status::Ok, "output is colorized");
+    }
+    let reason = if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        "NO_COLOR is set"
+    } else if !std::io::stdout().is_terminal() {
+        "standard output is not a terminal"
+    } else {
+        "--color=never is set"
+    };
+    Check::new(
+        "colors",
+        Status::Ok,
+        format!("output is not colorized, as {reason}"),
+    )
+}
+
+/// Check that plugins are not shadowed by built-in commands, which run
+/// instead of them.
+fn check_plugins() -> Vec<Check> {
+    let installed = plugins::installed();
+    if installed.is_empty() {
+        return vec![Check::new(
+            "plugins",
+            Status::Ok,
+            "no rpdf-* executable was found in PATH",
+        )];
+    }
+    let cli = super::build_cli();
+    let builtin = |name: &str| {
+        cli.get_subcommands().any(|command| {
+            command.get_name() == name || command.get_all_aliases().any(|alias| alias == name)
+        })
+    };
+
+    installed
+        .into_iter()
+        .map(|(name, path)| {
+            if builtin(&name) {
+                Check::new(
+                    "plugins",
+                    Status::Warning,
+                    format!("{path:?} is shadowed by the built-in `{name}` command"),
+                )
+                .remediation("Rename the executable to run it.")
+            } else {
+                Check::new("plugins", Status::Ok, format!("`{name}` runs {path:?}"))
+            }
+        })
+        .collect()
+}
+
+/// Print a check, with its status in color.
+fn write_check<W>(stdout: &mut W, check: &Check) -> Result<()>
+where
+    W: WriteColor,
+{
+    stdout.set_color(ColorSpec::new().set_fg(check.status.color()).set_bold(true))?;
+    write!(stdout, "{:<8}", check.status.label())?;
+    stdout.reset()?;
+    writeln!(stdout, "{}: {}", check.name, check.message)?;
+    if let Some(remediation) = &check.remediation {
+        writeln!(stdout, "{:<8}{remediation}", "")?;
+    }
+    Ok(())
+}
+
+impl Execute for DoctorCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut checks = vec![check_features(), self.check_tesseract(), check_cache(ctx)];
+        checks.extend(check_environment());
+        checks.push(check_locale());
+        checks.push(check_colors(ctx));
+        checks.extend(check_plugins());
+
+        for check in &checks {
+            write_check(&mut ctx.stdout, check)?;
+        }
+
+        let count = |status| checks.iter().filter(|check| check.status == status).count();
+        let errors = count(Status::Error);
+        if errors > 0 {
+            bail!(
+                "Failed {errors} of {} checks, see above how to fix them.",
+                checks.len()
+            );
+        }
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "\nPassed all checks, with {} warnings.",
+                count(Status::Warning)
+            )?;
+        }
+        Ok(())
+    }
+}
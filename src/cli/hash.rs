@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use sha2::{Digest, Sha256};
+use termcolor::WriteColor;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::canonical;
+
+/// Print the SHA-256 hashes of files, in the format of `sha256sum`.
+///
+/// With `--canonical`, PDF files are hashed in a normalized form, which
+/// ignores the file identifier, the dates that change each time a file is
+/// saved, and the numbering of objects, so that logically identical files
+/// have the same hash.
+#[derive(Debug, Parser)]
+pub struct HashCommand {
+    /// Filepaths.
+    #[clap(required = true, add = ArgValueCompleter::new(pdf_files()))]
+    files: Vec<PathBuf>,
+    /// Hash a normalized form of PDF files, rather than their bytes.
+    #[clap(long)]
+    canonical: bool,
+}
+
+/// Return the SHA-256 hash of a file, as hexadecimal digits.
+fn file_hash(path: &Path) -> Result<String> {
+    let data = fs::read(path).with_context(|| format!("Failed to read file from: {path:?}"))?;
+    Ok(hex::encode(Sha256::digest(&data)))
+}
+
+/// Write the SHA-256 hashes of files to a manifest, in the format of
+/// `sha256sum`, so that `sha256sum --check` can verify them.
+pub(super) fn write_manifest(path: &Path, files: &[PathBuf]) -> Result<()> {
+    let mut manifest = String::new();
+    for file in files {
+        manifest.push_str(&format!("{}  {}\n", file_hash(file)?, file.display()));
+    }
+    fs::write(path, manifest).with_context(|| format!("Failed to write manifest to: {path:?}"))
+}
+
+impl Execute for HashCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        for file in &self.files {
+            let hash = if self.canonical {
+                let document = ctx.load_document(file)?;
+                let hash = canonical::canonical_hash(document)
+                    .with_context(|| format!("Failed to normalize PDF from: {file:?}"))?;
+                hex::encode(hash)
+            } else {
+                file_hash(file)?
+            };
+            writeln!(ctx.stdout, "{hash}  {}", file.display())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::ObjectId;
+use tabled::{
+    builder::Builder,
+    settings::{Color, Panel, Style, style::BorderColor},
+};
+use termcolor::WriteColor;
+
+use super::{complete::pdf_files, context::ExecutionContext, output, traits::Execute};
+use crate::pdf::history::{self, Revision};
+
+/// Read the revisions of a file.
+fn read_revisions(file: &Path) -> Result<(Vec<u8>, Vec<Revision>)> {
+    let data = fs::read(file).with_context(|| format!("Failed to read PDF from: {file:?}"))?;
+    let revisions = history::revisions(&data);
+
+    if revisions.is_empty() {
+        bail!("Failed to read PDF from: {file:?}, no revision could be read.");
+    }
+    Ok((data, revisions))
+}
+
+/// Format a list of object IDs, e.g., `1 0 R, 5 0 R`.
+fn format_ids(ids: &[ObjectId]) -> String {
+    ids.iter()
+        .map(|(number, generation)| format!("{number} {generation} R"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Show command.
+#[derive(Args, Clone, Debug)]
+struct Show {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Also list the objects that each revision changed.
+    #[clap(long)]
+    objects: bool,
+}
+
+impl Execute for Show {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let (_, revisions) = read_revisions(&self.file)?;
+
+        let mut builder = Builder::default();
+        builder.set_header([
+            "Revision", "Length", "Added", "Modified", "Deleted", "Signed",
+        ]);
+        for (i, revision) in revisions.iter().enumerate() {
+            builder.push_record([
+                (i + 1).to_string(),
+                revision.length.to_string(),
+                revision.added.len().to_string(),
+                revision.modified.len().to_string(),
+                revision.deleted.len().to_string(),
+                if revision.signed { "yes" } else { "" }.to_string(),
+            ]);
+        }
+        let mut table = builder.build();
+        table
+            .with(Panel::header(format!(
+                "Revisions of: {}",
+                self.file.display()
+            )))
+            .with(Style::modern());
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+        writeln!(ctx.stdout, "{table}")?;
+
+        if self.objects {
+            for (i, revision) in revisions.iter().enumerate() {
+                writeln!(ctx.stdout, "Revision {}:", i + 1)?;
+                for (label, ids) in [
+                    ("Added", &revision.added),
+                    ("Modified", &revision.modified),
+                    ("Deleted", &revision.deleted),
+                ] {
+                    if !ids.is_empty() {
+                        writeln!(ctx.stdout, "  {label}: {}", format_ids(ids))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extract command.
+#[derive(Args, Clone, Debug)]
+struct Extract {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Revision to extract, starting at 1 for the original document.
+    #[clap(long)]
+    revision: usize,
+    /// Output file where the revision is written, defaults to
+    /// `revision-<REVISION>.pdf`.
+    #[clap(short, long, value_hint = ValueHint::FilePath)]
+    dest: Option<PathBuf>,
+}
+
+impl Execute for Extract {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let (data, revisions) = read_revisions(&self.file)?;
+        let Some(revision) = self.revision.checked_sub(1).and_then(|i| revisions.get(i)) else {
+            bail!(
+                "File {:?} has {} revisions, there is no revision {}.",
+                self.file,
+                revisions.len(),
+                self.revision
+            );
+        };
+        let dest = self
+            .dest
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("revision-{}.pdf", self.revision)));
+
+        // Earlier revisions are a prefix of the file, and are written as is,
+        // so that their signatures remain valid.
+        output::check_destination(ctx, &dest)?;
+        fs::write(&dest, &data[..revision.length])
+            .with_context(|| format!("Failed to write PDF to: {dest:?}"))?;
+        ctx.summary.file_written(&dest);
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully extracted revision {} of {:?} to {dest:?}.",
+                self.revision, self.file
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for the history of documents.
+#[derive(Debug, Subcommand)]
+enum HistorySubcommand {
+    /// Extract an earlier revision of a document, as it was before later
+    /// incremental updates.
+    Extract(Extract),
+    /// List the revisions of a document, i.e., its incremental updates, and
+    /// the objects that each one changed.
+    Show(Show),
+}
+
+/// Inspect the incremental updates of documents, e.g., to audit edits made
+/// after signing.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct HistoryCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: HistorySubcommand,
+}
+
+impl Execute for HistoryCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            HistorySubcommand::Extract(extract) => extract.execute(ctx),
+            HistorySubcommand::Show(show) => show.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Translations of user-facing messages and table headers.
+//!
+//! Messages are written in [Fluent](https://projectfluent.org), one file per
+//! language in `locales`, and messages that a language lacks fall back to
+//! English.
+//!
+//! Without the `i18n` feature, messages are only in English, and their
+//! arguments are substituted without Fluent, which the English messages do
+//! not need.
+
+use std::borrow::Cow;
+#[cfg(feature = "i18n")]
+use std::env;
+
+#[cfg(feature = "i18n")]
+use clap::ValueEnum;
+#[cfg(feature = "i18n")]
+pub use fluent_bundle::FluentValue as Value;
+#[cfg(feature = "i18n")]
+use fluent_bundle::{FluentArgs, FluentResource, concurrent::FluentBundle};
+use tracing::warn;
+#[cfg(feature = "i18n")]
+use unic_langid::LanguageIdentifier;
+
+/// Languages that messages are translated to.
+#[cfg(feature = "i18n")]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Language {
+    /// English.
+    #[default]
+    En,
+    /// French.
+    Fr,
+    /// German.
+    De,
+}
+
+#[cfg(feature = "i18n")]
+impl Language {
+    /// Return the language of the locale, from the `LC_ALL`, `LC_MESSAGES`
+    /// or `LANG` environment variables, e.g., `fr_BE.UTF-8`, or English if
+    /// none is translated.
+    #[must_use]
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| {
+                let code = value.split(['_', '-', '.', '@']).next()?;
+                Self::from_str(code, true).ok()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Return the language identifier, e.g., for plural rules.
+    fn identifier(self) -> LanguageIdentifier {
+        let code = match self {
+            Self::En => "en",
+            Self::Fr => "fr",
+            Self::De => "de",
+        };
+        code.parse().expect("Language codes are valid identifiers")
+    }
+
+    /// Return the translations of the language, in Fluent syntax.
+    fn source(self) -> &'static str {
+        match self {
+            Self::En => include_str!("locales/en.ftl"),
+            Self::Fr => include_str!("locales/fr.ftl"),
+            Self::De => include_str!("locales/de.ftl"),
+        }
+    }
+
+    /// Return the bundle of the translations of the language.
+    fn bundle(self) -> FluentBundle<FluentResource> {
+        let resource = FluentResource::try_new(self.source().to_string()).unwrap_or_else(
+            |(resource, errors)| {
+                warn!("Failed to parse the translations of {self:?}: {errors:?}");
+                resource
+            },
+        );
+        let mut bundle = FluentBundle::new_concurrent(vec![self.identifier()]);
+        // Isolation marks would end up in files and terminals that do not
+        // render them.
+        bundle.set_use_isolating(false);
+        if let Err(errors) = bundle.add_resource(resource) {
+            warn!("Failed to load the translations of {self:?}: {errors:?}");
+        }
+        bundle
+    }
+}
+
+/// Translator of user-facing messages to a language.
+#[cfg(feature = "i18n")]
+pub struct Localizer {
+    /// Translations of the language.
+    bundle: FluentBundle<FluentResource>,
+    /// English translations, for messages that the language lacks.
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+#[cfg(feature = "i18n")]
+impl std::fmt::Debug for Localizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Localizer")
+            .field("locales", &self.bundle.locales)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "i18n")]
+impl Default for Localizer {
+    fn default() -> Self {
+        Self::new(Language::default())
+    }
+}
+
+#[cfg(feature = "i18n")]
+impl Localizer {
+    /// Return a translator to a language.
+    #[must_use]
+    pub fn new(language: Language) -> Self {
+        Self {
+            bundle: language.bundle(),
+            fallback: (language != Language::En).then(|| Language::En.bundle()),
+        }
+    }
+
+    /// Return the translation of a message, with its arguments, e.g.,
+    /// `&[("count", 3.into())]`.
+    ///
+    /// Messages that no language has are returned as their identifier, so
+    /// that a typo never fails a command.
+    #[must_use]
+    pub fn message(&self, id: &str, args: &[(&str, Value<'_>)]) -> String {
+        let mut fluent_args = FluentArgs::with_capacity(args.len());
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+
+        for bundle in std::iter::once(&self.bundle).chain(&self.fallback) {
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+            let mut errors = vec![];
+            let message: Cow<'_, str> =
+                bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+            if !errors.is_empty() {
+                warn!("Failed to translate message {id:?}: {errors:?}");
+            }
+            return message.into_owned();
+        }
+        warn!("Failed to find message {id:?}");
+        id.to_string()
+    }
+}
+
+/// Argument of a message, e.g., a count or a file name.
+#[cfg(not(feature = "i18n"))]
+#[derive(Clone, Debug)]
+pub struct Value<'a>(Cow<'a, str>);
+
+#[cfg(not(feature = "i18n"))]
+impl<T: std::fmt::Display> From<T> for Value<'_> {
+    fn from(value: T) -> Self {
+        Self(value.to_string().into())
+    }
+}
+
+/// Formatter of user-facing messages, in English.
+#[cfg(not(feature = "i18n"))]
+#[derive(Debug)]
+pub struct Localizer {
+    /// English messages, in Fluent syntax.
+    source: &'static str,
+}
+
+#[cfg(not(feature = "i18n"))]
+impl Default for Localizer {
+    fn default() -> Self {
+        Self {
+            source: include_str!("locales/en.ftl"),
+        }
+    }
+}
+
+#[cfg(not(feature = "i18n"))]
+impl Localizer {
+    /// Return a message, with its arguments, e.g., `&[("count", 3.into())]`.
+    ///
+    /// Messages that do not exist are returned as their identifier, so that
+    /// a typo never fails a command.
+    #[must_use]
+    pub fn message(&self, id: &str, args: &[(&str, Value<'_>)]) -> String {
+        let pattern = self
+            .source
+            .lines()
+            .find_map(|line| line.strip_prefix(id)?.strip_prefix(" = "));
+        let Some(pattern) = pattern else {
+            warn!("Failed to find message {id:?}");
+            return id.to_string();
+        };
+        args.iter()
+            .fold(pattern.to_string(), |message, (name, value)| {
+                message.replace(&format!("{{ ${name} }}"), &value.0)
+            })
+    }
+}
@@ -0,0 +1,211 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "images")]
+use anyhow::Context;
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use clap_complete::ArgValueCompleter;
+use serde::Serialize;
+use termcolor::WriteColor;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+#[cfg(feature = "images")]
+use crate::pdf::barcodes::{self, Barcode};
+use crate::pdf::{
+    geo::{self, Georeference},
+    threads::{self, Thread},
+    threats::{self, Finding, Severity},
+};
+
+/// Threats command.
+#[derive(Args, Clone, Debug)]
+struct Threats {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+/// Risk report of a file, written as JSON.
+#[derive(Serialize)]
+struct ThreatReport<'a> {
+    file: &'a PathBuf,
+    /// Highest severity among findings, or `null` if there is none.
+    risk: Option<Severity>,
+    findings: Vec<Finding>,
+}
+
+impl Execute for Threats {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let findings = threats::scan(&document);
+        let report = ThreatReport {
+            file: &self.file,
+            risk: findings.iter().map(|finding| finding.severity).max(),
+            findings,
+        };
+
+        serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+        writeln!(ctx.stdout)?;
+        Ok(())
+    }
+}
+
+/// Geo command.
+#[derive(Args, Clone, Debug)]
+struct Geo {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+/// Georeferencing report of a file, written as JSON.
+#[derive(Serialize)]
+struct GeoReport<'a> {
+    file: &'a PathBuf,
+    georeferences: Vec<Georeference>,
+}
+
+impl Execute for Geo {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let report = GeoReport {
+            file: &self.file,
+            georeferences: geo::georeferences(&document),
+        };
+
+        serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+        writeln!(ctx.stdout)?;
+        Ok(())
+    }
+}
+
+/// Threads command.
+#[derive(Args, Clone, Debug)]
+struct Threads {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+/// Article threads of a file, written as JSON.
+#[derive(Serialize)]
+struct ThreadReport<'a> {
+    file: &'a PathBuf,
+    threads: Vec<Thread>,
+}
+
+impl Execute for Threads {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let report = ThreadReport {
+            file: &self.file,
+            threads: threads::threads(&document),
+        };
+
+        serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+        writeln!(ctx.stdout)?;
+        Ok(())
+    }
+}
+
+/// Barcodes command.
+#[cfg(feature = "images")]
+#[derive(Args, Clone, Debug)]
+struct Barcodes {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+/// Barcodes of a file, written as JSON.
+#[cfg(feature = "images")]
+#[derive(Serialize)]
+struct BarcodeReport<'a> {
+    file: &'a PathBuf,
+    barcodes: Vec<Barcode>,
+}
+
+#[cfg(feature = "images")]
+impl Execute for Barcodes {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let mut report = BarcodeReport {
+            file: &self.file,
+            barcodes: vec![],
+        };
+
+        for (page, page_id) in document.get_pages() {
+            report.barcodes.extend(
+                barcodes::page_barcodes(&document, page, page_id)
+                    .with_context(|| format!("Failed to read page {page} of {:?}.", self.file))?,
+            );
+        }
+        serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+        writeln!(ctx.stdout)?;
+        Ok(())
+    }
+}
+
+/// Available subcommands for inspect.
+#[derive(Debug, Subcommand)]
+enum InspectSubcommand {
+    /// Report potentially malicious constructs as JSON, with severities.
+    ///
+    /// JavaScript, launch actions, form data exchanges, embedded executables,
+    /// links with suspicious URI schemes and abnormally deep nesting are
+    /// reported. Nothing is modified, see `sanitize` to remove them.
+    Threats(Threats),
+    /// Report the georeferencing of map pages as JSON, i.e., the bounds,
+    /// geographic points and coordinate reference system of the geospatial
+    /// measures of their viewports and images.
+    Geo(Geo),
+    /// Report the article threads as JSON, i.e., the title of each article
+    /// and the page and region of each of its beads.
+    ///
+    /// Beads whose page is not in the document, e.g., after pages were
+    /// removed by another tool, have a `null` page.
+    Threads(Threads),
+    /// Report the Code 128 barcodes of scanned pages as JSON, e.g., to route
+    /// scans by their cover sheet.
+    ///
+    /// Only image-only pages, whose image is not a JPEG, are read, as other
+    /// pages cannot be rendered. Barcodes may be horizontal or vertical.
+    #[cfg(feature = "images")]
+    Barcodes(Barcodes),
+}
+
+/// Inspect the structure of PDF files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct InspectCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: InspectSubcommand,
+}
+
+impl Execute for InspectCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            InspectSubcommand::Threats(threats) => threats.execute(ctx),
+            InspectSubcommand::Geo(geo) => geo.execute(ctx),
+            InspectSubcommand::Threads(threads) => threads.execute(ctx),
+            #[cfg(feature = "images")]
+            InspectSubcommand::Barcodes(barcodes) => barcodes.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use termcolor::WriteColor;
+
+use super::{complete::pdf_files, context::ExecutionContext, output, traits::Execute};
+use crate::pdf::facturx::{self, Profile};
+
+/// Factur-X profiles, from the least to the most detailed.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum InvoiceProfile {
+    Minimum,
+    #[value(name = "basic-wl")]
+    BasicWl,
+    Basic,
+    #[value(name = "en16931")]
+    En16931,
+    Extended,
+    #[value(name = "xrechnung")]
+    XRechnung,
+}
+
+impl From<InvoiceProfile> for Profile {
+    fn from(profile: InvoiceProfile) -> Self {
+        match profile {
+            InvoiceProfile::Minimum => Self::Minimum,
+            InvoiceProfile::BasicWl => Self::BasicWl,
+            InvoiceProfile::Basic => Self::Basic,
+            InvoiceProfile::En16931 => Self::En16931,
+            InvoiceProfile::Extended => Self::Extended,
+            InvoiceProfile::XRechnung => Self::XRechnung,
+        }
+    }
+}
+
+/// Attach command.
+#[derive(Args, Clone, Debug)]
+struct Attach {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// XML invoice, in the Cross Industry Invoice (CII) format.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    xml: PathBuf,
+    /// Factur-X profile of the XML invoice.
+    #[clap(long, ignore_case = true, default_value = "en16931")]
+    profile: InvoiceProfile,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "invoice.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Attach {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let xml = fs::read(&self.xml)
+            .with_context(|| format!("Failed to read XML invoice from: {:?}", self.xml))?;
+        let mut document = ctx.load_document(&self.file)?;
+        let profile = Profile::from(self.profile);
+
+        facturx::attach(&mut document, xml, profile, &ctx.date())
+            .with_context(|| format!("Failed to attach invoice to PDF from: {:?}", self.file))?;
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully attached {:?} as {} ({}) to {:?}.",
+                self.xml,
+                profile.file_name(),
+                profile.conformance_level(),
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Extract command.
+#[derive(Args, Clone, Debug)]
+struct Extract {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Output file where the XML invoice is written, defaults to its name in
+    /// the document, e.g., `factur-x.xml`.
+    #[clap(short, long, value_hint = ValueHint::FilePath)]
+    dest: Option<PathBuf>,
+}
+
+impl Execute for Extract {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let Some(invoice) = facturx::invoice(&document) else {
+            bail!(
+                "File {:?} has no Factur-X or ZUGFeRD invoice, i.e., none of {}.",
+                self.file,
+                facturx::INVOICE_NAMES.join(", ")
+            );
+        };
+        let dest = self
+            .dest
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(&invoice.name));
+
+        output::check_destination(ctx, &dest)?;
+        fs::write(&dest, &invoice.data)
+            .with_context(|| format!("Failed to write XML invoice to: {dest:?}"))?;
+        ctx.summary.file_written(&dest);
+
+        if !ctx.quiet() {
+            if let Some(level) = facturx::conformance_level(&document) {
+                writeln!(ctx.stdout, "Profile: {level}")?;
+            }
+            writeln!(
+                ctx.stdout,
+                "Successfully extracted {} from {:?} to {dest:?}.",
+                invoice.name, self.file
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for invoices.
+#[derive(Debug, Subcommand)]
+enum InvoiceSubcommand {
+    /// Embed an XML invoice in a PDF, making it a Factur-X or ZUGFeRD
+    /// invoice.
+    ///
+    /// The XML file is attached with the relationship and the XMP metadata
+    /// that the Factur-X specification requires. The document must also be
+    /// PDF/A-3 compliant, e.g., after `convert pdfa --level 3b`.
+    Attach(Attach),
+    /// Extract the XML invoice of a Factur-X or ZUGFeRD invoice.
+    Extract(Extract),
+}
+
+/// Work with hybrid e-invoices, i.e., Factur-X and ZUGFeRD.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct InvoiceCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: InvoiceSubcommand,
+}
+
+impl Execute for InvoiceCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            InvoiceSubcommand::Attach(attach) => attach.execute(ctx),
+            InvoiceSubcommand::Extract(extract) => extract.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::{Document, ObjectId};
+use tabled::{
+    builder::Builder,
+    settings::{Color, Panel, Style, style::BorderColor},
+};
+use termcolor::WriteColor;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::{
+    layers::{self, Layer},
+    selection::PageSelection,
+};
+
+/// Return the layers of a document with the given name, failing if there is
+/// none.
+fn named_layers(document: &Document, file: &Path, name: &str) -> Result<Vec<Layer>> {
+    let layers: Vec<Layer> = layers::layers(document)
+        .into_iter()
+        .filter(|layer| layer.name == name)
+        .collect();
+
+    if layers.is_empty() {
+        bail!("File {file:?} has no layer named {name:?}.");
+    }
+    Ok(layers)
+}
+
+/// Return the pages of a layer, e.g., `1-3,5`.
+fn pages(layer: &Layer) -> String {
+    if layer.pages.is_empty() {
+        return String::new();
+    }
+    PageSelection::from_pages(&layer.pages).to_string()
+}
+
+/// Return "on" or "off".
+fn state(visible: bool) -> &'static str {
+    if visible { "on" } else { "off" }
+}
+
+/// List command.
+#[derive(Args, Clone, Debug)]
+struct List {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+impl Execute for List {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let layers = layers::layers(&document);
+
+        if layers.is_empty() {
+            writeln!(ctx.stdout, "No layer was found in the given file.")?;
+            return Ok(());
+        }
+        let mut builder = Builder::default();
+        builder.set_header(["Name", "State", "Locked", "Intent", "Pages"]);
+        for layer in &layers {
+            builder.push_record([
+                layer.name.clone(),
+                state(layer.visible).to_string(),
+                if layer.locked { "yes" } else { "no" }.to_string(),
+                layer.intents.join(", "),
+                pages(layer),
+            ]);
+        }
+        let mut table = builder.build();
+        table
+            .with(Panel::header(format!(
+                "Layers of file: {}",
+                self.file.display()
+            )))
+            .with(Style::modern());
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+        writeln!(ctx.stdout, "{table}")?;
+        Ok(())
+    }
+}
+
+/// Show command.
+#[derive(Args, Clone, Debug)]
+struct Show {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Name of the layer.
+    #[clap(short, long)]
+    name: String,
+}
+
+impl Execute for Show {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+
+        for (i, layer) in named_layers(&document, &self.file, &self.name)?
+            .iter()
+            .enumerate()
+        {
+            if i > 0 {
+                writeln!(ctx.stdout)?;
+            }
+            let (number, generation) = layer.id;
+            writeln!(ctx.stdout, "Name: {}", layer.name)?;
+            writeln!(ctx.stdout, "Object: {number} {generation} R")?;
+            writeln!(ctx.stdout, "State: {}", state(layer.visible))?;
+            writeln!(
+                ctx.stdout,
+                "Locked: {}",
+                if layer.locked { "yes" } else { "no" }
+            )?;
+            writeln!(ctx.stdout, "Intent: {}", layer.intents.join(", "))?;
+            writeln!(ctx.stdout, "Pages: {}", pages(layer))?;
+        }
+        Ok(())
+    }
+}
+
+/// Visibility of a layer.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum State {
+    /// Shown.
+    On,
+    /// Hidden.
+    Off,
+}
+
+/// Toggle command.
+#[derive(Args, Clone, Debug)]
+struct Toggle {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Name of the layer, whose homonyms are toggled too.
+    #[clap(short, long)]
+    name: String,
+    /// Whether the layer is shown when opening the document.
+    #[clap(short, long)]
+    state: State,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "layers.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Toggle {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let ids: Vec<ObjectId> = named_layers(&document, &self.file, &self.name)?
+            .iter()
+            .map(|layer| layer.id)
+            .collect();
+        let visible = matches!(self.state, State::On);
+
+        layers::set_visible(&mut document, &ids, visible)?;
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully turned layer {:?} {} to {:?}.",
+                self.name,
+                state(visible),
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Remove command.
+#[derive(Args, Clone, Debug)]
+struct Remove {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Name of the layer, whose homonyms are removed too.
+    #[clap(short, long)]
+    name: String,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "layers.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Remove {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let ids: Vec<ObjectId> = named_layers(&document, &self.file, &self.name)?
+            .iter()
+            .map(|layer| layer.id)
+            .collect();
+
+        let removal = layers::remove(&mut document, &ids)?;
+        ctx.summary.annotations_removed(removal.annotations);
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully removed layer {:?} ({} content sections, {} XObjects, {} \
+                 annotations) to {:?}.",
+                self.name, removal.sections, removal.xobjects, removal.annotations, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for layers.
+#[derive(Debug, Subcommand)]
+enum LayersSubcommand {
+    /// List the layers of a document, with their default state and pages.
+    List(List),
+    /// Show the details of a layer.
+    Show(Show),
+    /// Show or hide a layer when opening the document.
+    ///
+    /// Users can still toggle the layer in viewers, unlike with `remove`.
+    Toggle(Toggle),
+    /// Remove a layer and its content, i.e., its marked content in pages and
+    /// forms, its XObjects and its annotations.
+    Remove(Remove),
+}
+
+/// Work with layers, i.e., optional content groups, e.g., of CAD drawings.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct LayersCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: LayersSubcommand,
+}
+
+impl Execute for LayersCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            LayersSubcommand::List(list) => list.execute(ctx),
+            LayersSubcommand::Show(show) => show.execute(ctx),
+            LayersSubcommand::Toggle(toggle) => toggle.execute(ctx),
+            LayersSubcommand::Remove(remove) => remove.execute(ctx),
+        }
+    }
+}
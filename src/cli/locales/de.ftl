@@ -0,0 +1,52 @@
+# German messages.
+
+## Annotations statistics
+
+stats-title = Anmerkungsstatistik für: { $file }
+stats-page = Seite Nr.
+stats-no-annotation = In der Datei wurde keine Anmerkung gefunden.
+stats-no-annotation-in-category = In der Datei wurde keine Anmerkung dieser Kategorie gefunden, verwenden Sie `--category all`, um alle Anmerkungen zu zählen.
+stats-media = { $count ->
+    [one] { $count } Anmerkung enthält Medien ({ $bytes } Bytes), verwenden Sie `annotations strip --media`, um sie zu entfernen.
+   *[other] { $count } Anmerkungen enthalten Medien ({ $bytes } Bytes), verwenden Sie `annotations strip --media`, um sie zu entfernen.
+}
+stats-compare-title = Änderungen der Anmerkungen von { $old } zu { $new }
+stats-no-change = Es wurde keine Anmerkung hinzugefügt oder entfernt.
+
+## Annotations commands
+
+merge-success = { $count ->
+    [one] Anmerkungen aus { $count } Datei in { $dest } zusammengeführt.
+   *[other] Anmerkungen aus { $count } Dateien in { $dest } zusammengeführt.
+}
+strip-success = Anmerkungen aus { $file } entfernt, nach { $dest } geschrieben
+note-success = Notiz zu Seite { $page } von { $file } hinzugefügt, nach { $dest } geschrieben.
+marks-success = { $count ->
+    [one] { $count } Markierung zu { $file } hinzugefügt, nach { $dest } geschrieben.
+   *[other] { $count } Markierungen zu { $file } hinzugefügt, nach { $dest } geschrieben.
+}
+stamp-success = { $count ->
+    [one] { $count } Seite von { $file } gestempelt, nach { $dest } geschrieben.
+   *[other] { $count } Seiten von { $file } gestempelt, nach { $dest } geschrieben.
+}
+flags-success = { $count ->
+    [one] Kennzeichen von { $count } Anmerkung aktualisiert, nach { $dest } geschrieben.
+   *[other] Kennzeichen von { $count } Anmerkungen aktualisiert, nach { $dest } geschrieben.
+}
+heatmap-success = { $count ->
+    [one] Heatmap von { $count } Anmerkung aus { $file } gezeichnet, nach { $dest } geschrieben.
+   *[other] Heatmap von { $count } Anmerkungen aus { $file } gezeichnet, nach { $dest } geschrieben.
+}
+
+## Output files
+
+overwrite-prompt = Die Ausgabedatei { $file } existiert bereits. Möchten Sie sie überschreiben?
+
+## Run summaries
+
+summary-files-read = Gelesene Dateien: { $count } ({ $bytes } Bytes)
+summary-files-written = Geschriebene Dateien: { $count } ({ $bytes } Bytes)
+summary-bytes-saved = Eingesparte Bytes: { $bytes }
+summary-annotations-added = Hinzugefügte Anmerkungen: { $count }
+summary-annotations-removed = Entfernte Anmerkungen: { $count }
+summary-duration = Dauer: { $seconds } s
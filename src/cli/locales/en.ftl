@@ -0,0 +1,34 @@
+# English messages, which other languages fall back to.
+
+## Annotations statistics
+
+stats-title = Annotations stats for: { $file }
+stats-page = Page no.
+stats-no-annotation = No annotation was found in the given file.
+stats-no-annotation-in-category = No annotation of this category was found in the given file, use `--category all` to count all annotations.
+stats-media = { $count } annotations embed media ({ $bytes } bytes), use `annotations strip --media` to remove them.
+stats-compare-title = Annotations changes from { $old } to { $new }
+stats-no-change = No annotation was added nor removed.
+
+## Annotations commands
+
+merge-success = Successfully merged annotations from { $count } files to { $dest }.
+strip-success = Successfully striped annotations from { $file } to { $dest }
+note-success = Successfully added a note to page { $page } of { $file } to { $dest }.
+marks-success = Successfully added { $count } marks to { $file } in { $dest }.
+stamp-success = Successfully stamped { $count } pages of { $file } to { $dest }.
+flags-success = Successfully updated the flags of { $count } annotations to { $dest }.
+heatmap-success = Successfully drew the heatmap of { $count } annotations of { $file } to { $dest }.
+
+## Output files
+
+overwrite-prompt = Output file { $file } already exists. Do you want to overwrite it?
+
+## Run summaries
+
+summary-files-read = Files read: { $count } ({ $bytes } bytes)
+summary-files-written = Files written: { $count } ({ $bytes } bytes)
+summary-bytes-saved = Bytes saved: { $bytes }
+summary-annotations-added = Annotations added: { $count }
+summary-annotations-removed = Annotations removed: { $count }
+summary-duration = Duration: { $seconds } s
@@ -0,0 +1,52 @@
+# French messages.
+
+## Annotations statistics
+
+stats-title = Statistiques des annotations de : { $file }
+stats-page = Page n°
+stats-no-annotation = Aucune annotation n'a été trouvée dans le fichier.
+stats-no-annotation-in-category = Aucune annotation de cette catégorie n'a été trouvée dans le fichier, utilisez `--category all` pour compter toutes les annotations.
+stats-media = { $count ->
+    [one] { $count } annotation contient des médias ({ $bytes } octets), utilisez `annotations strip --media` pour la supprimer.
+   *[other] { $count } annotations contiennent des médias ({ $bytes } octets), utilisez `annotations strip --media` pour les supprimer.
+}
+stats-compare-title = Modifications des annotations de { $old } à { $new }
+stats-no-change = Aucune annotation n'a été ajoutée ni supprimée.
+
+## Annotations commands
+
+merge-success = { $count ->
+    [one] Annotations de { $count } fichier fusionnées dans { $dest }.
+   *[other] Annotations de { $count } fichiers fusionnées dans { $dest }.
+}
+strip-success = Annotations supprimées de { $file } dans { $dest }
+note-success = Note ajoutée à la page { $page } de { $file } dans { $dest }.
+marks-success = { $count ->
+    [one] { $count } marque ajoutée à { $file } dans { $dest }.
+   *[other] { $count } marques ajoutées à { $file } dans { $dest }.
+}
+stamp-success = { $count ->
+    [one] { $count } page de { $file } tamponnée dans { $dest }.
+   *[other] { $count } pages de { $file } tamponnées dans { $dest }.
+}
+flags-success = { $count ->
+    [one] Indicateurs de { $count } annotation mis à jour dans { $dest }.
+   *[other] Indicateurs de { $count } annotations mis à jour dans { $dest }.
+}
+heatmap-success = { $count ->
+    [one] Carte de chaleur de { $count } annotation de { $file } dessinée dans { $dest }.
+   *[other] Carte de chaleur de { $count } annotations de { $file } dessinée dans { $dest }.
+}
+
+## Output files
+
+overwrite-prompt = Le fichier de sortie { $file } existe déjà. Voulez-vous l'écraser ?
+
+## Run summaries
+
+summary-files-read = Fichiers lus : { $count } ({ $bytes } octets)
+summary-files-written = Fichiers écrits : { $count } ({ $bytes } octets)
+summary-bytes-saved = Octets économisés : { $bytes }
+summary-annotations-added = Annotations ajoutées : { $count }
+summary-annotations-removed = Annotations supprimées : { $count }
+summary-duration = Durée : { $seconds } s
@@ -0,0 +1,94 @@
+//! Logging setup, with human-readable or JSON messages.
+//!
+//! Messages are [`tracing`] events, and those that dependencies log with the
+//! `log` crate are forwarded to the same subscriber. Spans give the context
+//! of a message, i.e., the file being processed, and the page where per-page
+//! work is done.
+
+use std::{cell::RefCell, fs::File, path::Path, sync::Mutex};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use tracing::{error_span, span::EnteredSpan};
+use tracing_subscriber::{filter::LevelFilter, fmt::writer::BoxMakeWriter};
+
+use super::color::ColorPolicy;
+
+/// Format of log messages.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable messages.
+    #[default]
+    Text,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+thread_local! {
+    /// Span of the file being processed by this thread, if any.
+    static FILE_SPAN: RefCell<Option<EnteredSpan>> = const { RefCell::new(None) };
+}
+
+/// Set the file being processed, which log messages refer to until another
+/// file is processed.
+///
+/// The span is at the error level, so that it is enabled for every message
+/// that is logged.
+pub fn set_current_file(path: &Path) {
+    FILE_SPAN.with(|span| {
+        let mut span = span.borrow_mut();
+        // Leave the span of the previous file before entering the new one.
+        span.take();
+        *span = Some(error_span!("file", path = %path.display()).entered());
+    });
+}
+
+/// Convert a level of the `log` crate, as given by the verbosity flags.
+fn level_filter(level: log::LevelFilter) -> LevelFilter {
+    match level {
+        log::LevelFilter::Off => LevelFilter::OFF,
+        log::LevelFilter::Error => LevelFilter::ERROR,
+        log::LevelFilter::Warn => LevelFilter::WARN,
+        log::LevelFilter::Info => LevelFilter::INFO,
+        log::LevelFilter::Debug => LevelFilter::DEBUG,
+        log::LevelFilter::Trace => LevelFilter::TRACE,
+    }
+}
+
+/// Install the logger, writing messages of the given level or above to
+/// standard error, or to a file.
+///
+/// Messages written to standard error are colored following the color
+/// policy, while log files and JSON messages never are.
+pub fn init(
+    level: log::LevelFilter,
+    format: LogFormat,
+    file: Option<&Path>,
+    color: ColorPolicy,
+) -> Result<()> {
+    let (writer, ansi) = match file {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create log file: {path:?}"))?;
+            (BoxMakeWriter::new(Mutex::new(file)), false)
+        },
+        None => (BoxMakeWriter::new(std::io::stderr), color.enabled()),
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level_filter(level))
+        .with_writer(writer);
+
+    match format {
+        LogFormat::Text => builder.without_time().with_ansi(ansi).init(),
+        LogFormat::Json => {
+            builder
+                .json()
+                .with_ansi(false)
+                .flatten_event(true)
+                .with_current_span(true)
+                .with_span_list(true)
+                .init();
+        },
+    }
+    Ok(())
+}
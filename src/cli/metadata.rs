@@ -0,0 +1,313 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::Object;
+use termcolor::WriteColor;
+use tracing::warn;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::{
+    info, pdfstring,
+    viewer::{self, Zoom},
+};
+
+/// Return the PDF name of a value, which is its command-line name.
+fn pdf_name<T: ValueEnum>(value: &T) -> Object {
+    let value = value.to_possible_value().expect("no value is skipped");
+    Object::Name(value.get_name().as_bytes().to_vec())
+}
+
+/// Panel shown when the document is opened.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PageMode {
+    /// No panel.
+    #[value(name = "UseNone")]
+    UseNone,
+    /// Bookmarks panel.
+    #[value(name = "UseOutlines")]
+    UseOutlines,
+    /// Thumbnails panel.
+    #[value(name = "UseThumbs")]
+    UseThumbs,
+    /// Full-screen mode, without menu bar nor window controls.
+    #[value(name = "FullScreen")]
+    FullScreen,
+    /// Layers panel.
+    #[value(name = "UseOC")]
+    UseOC,
+    /// Attachments panel.
+    #[value(name = "UseAttachments")]
+    UseAttachments,
+}
+
+/// Arrangement of pages when the document is opened.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PageLayout {
+    /// One page at a time.
+    #[value(name = "SinglePage")]
+    SinglePage,
+    /// Pages in one continuous column.
+    #[value(name = "OneColumn")]
+    OneColumn,
+    /// Pages in two continuous columns, odd pages on the left.
+    #[value(name = "TwoColumnLeft")]
+    TwoColumnLeft,
+    /// Pages in two continuous columns, odd pages on the right.
+    #[value(name = "TwoColumnRight")]
+    TwoColumnRight,
+    /// Two pages at a time, odd pages on the left.
+    #[value(name = "TwoPageLeft")]
+    TwoPageLeft,
+    /// Two pages at a time, odd pages on the right.
+    #[value(name = "TwoPageRight")]
+    TwoPageRight,
+}
+
+/// Parse a language tag, e.g., `en-GB`, as described by BCP 47.
+fn parse_language(s: &str) -> Result<String, String> {
+    let valid = s.split('-').all(|subtag| {
+        (1..=8).contains(&subtag.len()) && subtag.bytes().all(|byte| byte.is_ascii_alphanumeric())
+    });
+
+    if valid {
+        Ok(s.to_string())
+    } else {
+        Err(format!(
+            "invalid language: {s:?}, expected a language tag, e.g., en-GB"
+        ))
+    }
+}
+
+/// Lang command.
+#[derive(Args, Clone, Debug)]
+struct Lang {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Default language of the document to set, as a language tag, e.g.,
+    /// `en-GB`.
+    #[clap(long, value_name = "LANG", value_parser = parse_language)]
+    set: Option<String>,
+    /// Title of the document to set, which is also shown in the title bar
+    /// of viewers.
+    #[clap(long)]
+    title: Option<String>,
+    /// Show the document title, rather than its file name, in the title bar
+    /// of viewers.
+    #[clap(long)]
+    display_doc_title: bool,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "patched.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Lang {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let display_doc_title = self.display_doc_title || self.title.is_some();
+
+        if self.set.is_none() && !display_doc_title {
+            let document = ctx.load_document_without_images(&self.file)?;
+            let catalog = document.catalog()?;
+            let language = catalog
+                .get_deref(b"Lang", &document)
+                .and_then(pdfstring::decode_object)
+                .ok();
+            let displayed = catalog
+                .get_deref(b"ViewerPreferences", &document)
+                .and_then(Object::as_dict)
+                .and_then(|preferences| preferences.get(b"DisplayDocTitle"))
+                .and_then(Object::as_bool)
+                .unwrap_or(false);
+
+            writeln!(
+                ctx.stdout,
+                "Language: {}",
+                language.as_deref().unwrap_or("none")
+            )?;
+            writeln!(
+                ctx.stdout,
+                "Title: {}",
+                info::text(&document, b"Title").as_deref().unwrap_or("none")
+            )?;
+            writeln!(
+                ctx.stdout,
+                "Title shown by viewers: {}",
+                if displayed { "yes" } else { "no" }
+            )?;
+            return Ok(());
+        }
+
+        let mut document = ctx.load_document(&self.file)?;
+
+        if let Some(language) = &self.set {
+            document
+                .catalog_mut()?
+                .set("Lang", Object::string_literal(language.as_str()));
+        }
+        if let Some(title) = &self.title {
+            info::set(&mut document, "Title", pdfstring::encode_object(title));
+        }
+        if display_doc_title {
+            if info::text(&document, b"Title").map_or(true, |title| title.trim().is_empty()) {
+                warn!(
+                    "Document {:?} has no title, viewers will show an empty title.",
+                    self.file
+                );
+            }
+            viewer::viewer_preferences_mut(&mut document)?.set("DisplayDocTitle", true);
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully set language and title in {:?}.",
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Viewer-prefs command.
+#[derive(Args, Clone, Debug)]
+struct ViewerPrefs {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Panel shown when the document is opened.
+    #[clap(long, value_enum)]
+    page_mode: Option<PageMode>,
+    /// Arrangement of pages when the document is opened.
+    #[clap(long, value_enum)]
+    page_layout: Option<PageLayout>,
+    /// Resize the window to fit the first page shown.
+    #[clap(long)]
+    fit_window: bool,
+    /// Center the window on the screen.
+    #[clap(long)]
+    center_window: bool,
+    /// Show the document title, rather than its file name, in the title bar.
+    #[clap(long)]
+    display_doc_title: bool,
+    /// Hide the toolbars of the viewer.
+    #[clap(long)]
+    hide_toolbar: bool,
+    /// Hide the menu bar of the viewer.
+    #[clap(long)]
+    hide_menubar: bool,
+    /// Number of the page shown when the document is opened.
+    #[clap(long, value_name = "PAGE")]
+    open_page: Option<u32>,
+    /// Zoom of the page shown when the document is opened, i.e., `fit`,
+    /// `fit-width`, `fit-height` or a percentage, e.g., `150%`.
+    #[clap(long)]
+    zoom: Option<Zoom>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "patched.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for ViewerPrefs {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let flags = [
+            ("FitWindow", self.fit_window),
+            ("CenterWindow", self.center_window),
+            ("DisplayDocTitle", self.display_doc_title),
+            ("HideToolbar", self.hide_toolbar),
+            ("HideMenubar", self.hide_menubar),
+        ];
+        let open_at = self.open_page.is_some() || self.zoom.is_some();
+
+        if self.page_mode.is_none()
+            && self.page_layout.is_none()
+            && !open_at
+            && flags.iter().all(|&(_, enabled)| !enabled)
+        {
+            bail!("No viewer preference to set, see --help for the available options.");
+        }
+
+        let mut document = ctx.load_document(&self.file)?;
+
+        if open_at {
+            let page = self.open_page.unwrap_or(1);
+            let Some(&page_id) = document.get_pages().get(&page) else {
+                bail!("Document {:?} has no page {page}.", self.file);
+            };
+            viewer::set_open_action(&mut document, page_id, self.zoom.unwrap_or(Zoom::Fit))?;
+        }
+        let catalog = document.catalog_mut()?;
+        if let Some(page_mode) = &self.page_mode {
+            catalog.set("PageMode", pdf_name(page_mode));
+        }
+        if let Some(page_layout) = &self.page_layout {
+            catalog.set("PageLayout", pdf_name(page_layout));
+        }
+        if flags.iter().any(|&(_, enabled)| enabled) {
+            let preferences = viewer::viewer_preferences_mut(&mut document)?;
+            for (key, enabled) in flags {
+                if enabled {
+                    preferences.set(key, true);
+                }
+            }
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully set viewer preferences in {:?}.",
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for metadata.
+#[derive(Debug, Subcommand)]
+enum MetadataSubcommand {
+    /// Show or set the default language and the title of the document, as
+    /// required by accessibility checkers.
+    ///
+    /// Without options, the language (`/Lang`), the title and whether
+    /// viewers show it (`/DisplayDocTitle`) are shown. Setting a title also
+    /// makes viewers show it. XMP metadata, if any, is not updated.
+    Lang(Lang),
+    /// Set how viewers show the document when it is opened.
+    ///
+    /// The panel shown (`/PageMode`), the arrangement of pages
+    /// (`/PageLayout`), window options (`/ViewerPreferences`) and the page
+    /// and zoom the document opens at (`/OpenAction`) can be set. Other
+    /// preferences are kept.
+    ViewerPrefs(ViewerPrefs),
+}
+
+/// Edit document-level metadata of PDF files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct MetadataCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: MetadataSubcommand,
+}
+
+impl Execute for MetadataCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            MetadataSubcommand::Lang(lang) => lang.execute(ctx),
+            MetadataSubcommand::ViewerPrefs(viewer_prefs) => viewer_prefs.execute(ctx),
+        }
+    }
+}
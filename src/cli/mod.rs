@@ -1,14 +1,65 @@
+pub mod cache;
+pub mod color;
+pub mod context;
+pub mod i18n;
+pub mod logging;
+pub mod output;
+pub mod summary;
 pub mod traits;
 
 mod annotations;
+mod assemble;
+mod bench;
+mod colors;
+mod convert;
+mod daemon;
+mod debug;
+mod doctor;
+mod hash;
+mod history;
+mod inspect;
+mod invoice;
+mod layers;
+mod metadata;
+mod naming;
+#[cfg(feature = "net")]
+mod net;
+mod objects;
+#[cfg(feature = "ocr")]
+mod ocr;
+mod pages;
+mod plugins;
+mod portfolio;
+#[cfg(feature = "scripting")]
+mod process;
+mod sanitize;
+#[cfg(feature = "server")]
+mod serve;
+mod signatures;
+mod stamp;
+mod structure;
+mod text;
+mod undo;
+mod validate;
+
+use std::{ffi::OsString, path::PathBuf};
 
 use anyhow::Result;
-use clap::{CommandFactory, Parser, Subcommand};
-use is_terminal::IsTerminal;
-use termcolor::{ColorChoice, StandardStream};
+use clap::{CommandFactory, Parser, Subcommand, ValueHint};
+use termcolor::StandardStream;
 
+use cache::Cache;
+use color::ColorPolicy;
+use context::ExecutionContext;
+#[cfg(feature = "i18n")]
+use i18n::Language;
+use i18n::Localizer;
+use output::{Overwrite, OverwritePolicy};
+use summary::RunSummary;
 use traits::Execute;
 
+use crate::pdf::SaveOptions;
+
 #[derive(Debug, Parser)]
 #[command(
     author,
@@ -20,12 +71,126 @@ use traits::Execute;
 )]
 pub struct Cli {
     /// Specify WHEN to colorize output.
+    ///
+    /// In `auto` mode, output is only colorized if stdout is a terminal and
+    /// the `NO_COLOR` environment variable is not set.
     #[arg(short, long, value_name = "WHEN", default_value = "auto", default_missing_value = "always", num_args(0..=1), require_equals(true))]
     pub color: clap::ColorChoice,
+    /// Language of messages and table headers.
+    ///
+    /// Defaults to the language of the locale, i.e., of the `LC_ALL`,
+    /// `LC_MESSAGES` or `LANG` environment variables, or English if it is not
+    /// translated.
+    #[cfg(feature = "i18n")]
+    #[arg(long, value_enum, env = "RPDF_LANG")]
+    pub lang: Option<Language>,
     /// Subcommand.
     #[command(subcommand)]
     #[allow(missing_docs)]
     pub command: Command,
+    /// Never prompt for user input, failing instead.
+    ///
+    /// Use this in non-interactive environments (e.g., CI) to avoid hanging
+    /// on confirmation prompts.
+    #[arg(long, global = true)]
+    pub no_input: bool,
+    /// Overwrite output files that already exist, without asking.
+    #[arg(long, global = true, conflicts_with_all = ["no_clobber", "backup"])]
+    pub force: bool,
+    /// Never overwrite output files that already exist.
+    ///
+    /// Commands that write several files skip the existing ones, and other
+    /// commands fail.
+    #[arg(long, global = true, conflicts_with = "backup")]
+    pub no_clobber: bool,
+    /// Copy output files that already exist to a file with a `.bak` suffix,
+    /// e.g., `out.pdf.bak`, before overwriting them.
+    #[arg(long, global = true)]
+    pub backup: bool,
+    /// Read saved PDF files back, failing unless they parse and have the
+    /// pages and annotations that were written.
+    ///
+    /// This catches serialization issues before files are shipped, at the
+    /// cost of loading each file twice.
+    #[arg(long, global = true)]
+    pub verify: bool,
+    /// Never read nor write cached data, e.g., the text of pages.
+    #[arg(long, global = true, env = "RPDF_NO_CACHE", value_parser = clap::builder::BoolishValueParser::new())]
+    pub no_cache: bool,
+    /// Directory where data derived from files, e.g., the text of pages, is
+    /// cached, so that runs over unchanged files do not compute it again.
+    ///
+    /// Defaults to `$XDG_CACHE_HOME/rpdf` or `~/.cache/rpdf`.
+    #[arg(long, global = true, env = "RPDF_CACHE_DIR", value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub cache_dir: Option<PathBuf>,
+    /// Never write journals of files edited in place, which `rpdf undo`
+    /// restores them from.
+    ///
+    /// Journals are written next to files, e.g., `file.pdf.rpdf-journal`,
+    /// and hold the objects that the last edit changed.
+    #[arg(long, global = true, env = "RPDF_NO_JOURNAL", value_parser = clap::builder::BoolishValueParser::new())]
+    pub no_journal: bool,
+    /// Password used to open encrypted PDF files.
+    #[arg(long, global = true, env = "RPDF_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+    /// Make saved PDF files reproducible.
+    ///
+    /// Objects are renumbered in a canonical order, and the modification
+    /// date and file identifier only depend on the content, so that the
+    /// same command always writes the same bytes.
+    #[arg(long, global = true)]
+    pub deterministic: bool,
+    /// Unix timestamp used as modification date of saved PDF files.
+    ///
+    /// Setting it implies `--deterministic`, whose date otherwise defaults
+    /// to 1970-01-01.
+    #[arg(
+        long,
+        global = true,
+        env = "SOURCE_DATE_EPOCH",
+        value_name = "TIMESTAMP"
+    )]
+    pub source_date_epoch: Option<u64>,
+    /// Maximum memory that a loaded PDF file may use, e.g., `2G`.
+    ///
+    /// The memory a file needs is estimated from its size, and commands
+    /// fail before loading a file that exceeds the budget, instead of being
+    /// killed for running out of memory halfway through.
+    #[arg(long, global = true, env = "RPDF_MAX_MEMORY", value_name = "SIZE", value_parser = context::parse_size)]
+    pub max_memory: Option<u64>,
+    /// Maximum time to download a PDF file given as a URL, in seconds.
+    #[cfg(feature = "net")]
+    #[arg(
+        long,
+        global = true,
+        env = "RPDF_TIMEOUT",
+        value_name = "SECONDS",
+        default_value_t = 30
+    )]
+    pub timeout: u64,
+    /// Format of log messages.
+    ///
+    /// In `json` mode, each message is a JSON object on its own line, with
+    /// its date, level, module, message, and the spans it happened in, i.e.,
+    /// the file and page being processed.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub log_format: logging::LogFormat,
+    /// File where log messages are written, instead of standard error.
+    #[arg(long, global = true, value_hint = ValueHint::FilePath)]
+    pub log_file: Option<PathBuf>,
+    /// Print a summary of the run once the command is done, i.e., the files
+    /// read and written, the annotations added and removed, the bytes saved
+    /// and the duration.
+    #[arg(long, global = true)]
+    pub summary: bool,
+    /// File where the summary of the run is written as JSON, even if the
+    /// command fails.
+    #[arg(long, global = true, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub report: Option<PathBuf>,
+    /// File where the SHA-256 hashes of the files written are listed, in the
+    /// format of `sha256sum`, even if the command fails.
+    #[arg(long = "manifest", global = true, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub checksum_manifest: Option<PathBuf>,
     #[command(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
 }
@@ -36,36 +201,230 @@ pub struct Cli {
 pub enum Command {
     #[clap(visible_alias = "ann")]
     Annotations(annotations::AnnotationsCommand),
+    Assemble(assemble::AssembleCommand),
+    #[clap(hide = true)]
+    Bench(bench::BenchCommand),
+    Cache(cache::CacheCommand),
+    Color(colors::ColorCommand),
     Completions(complete::CompleteCommand),
+    Daemon(daemon::DaemonCommand),
+    Convert(convert::ConvertCommand),
+    #[clap(hide = true)]
+    Debug(debug::DebugCommand),
+    Doctor(doctor::DoctorCommand),
+    Hash(hash::HashCommand),
+    History(history::HistoryCommand),
+    Inspect(inspect::InspectCommand),
+    Invoice(invoice::InvoiceCommand),
+    Layers(layers::LayersCommand),
+    Manpages(manpages::ManpagesCommand),
+    Metadata(metadata::MetadataCommand),
+    #[clap(visible_alias = "obj")]
+    Objects(objects::ObjectsCommand),
+    #[cfg(feature = "ocr")]
+    Ocr(ocr::OcrCommand),
+    Pages(pages::PagesCommand),
+    Portfolio(portfolio::PortfolioCommand),
+    #[cfg(feature = "scripting")]
+    Process(process::ProcessCommand),
+    Sanitize(sanitize::SanitizeCommand),
+    #[cfg(feature = "server")]
+    Serve(serve::ServeCommand),
+    Signatures(signatures::SignaturesCommand),
+    Stamp(stamp::StampCommand),
+    Structure(structure::StructureCommand),
+    Text(text::TextCommand),
+    Undo(undo::UndoCommand),
+    Validate(validate::ValidateCommand),
+    /// Run an `rpdf-<COMMAND>` executable found in PATH, passing it the
+    /// remaining arguments.
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
 }
 
 impl Cli {
-    /// Return a standard output stream that optionally supports color.
+    /// Return the color policy resolved from the command-line options.
     #[must_use]
-    fn stdout(&self) -> StandardStream {
-        let mut choice: ColorChoice = match self.color {
-            clap::ColorChoice::Auto => ColorChoice::Auto,
-            clap::ColorChoice::Always => ColorChoice::Always,
-            clap::ColorChoice::Never => ColorChoice::Never,
-        };
+    fn color_policy(&self) -> ColorPolicy {
+        ColorPolicy::resolve(self.color)
+    }
 
-        if choice == ColorChoice::Auto && !std::io::stdout().is_terminal() {
-            choice = ColorChoice::Never;
+    /// Return the color policy of log messages, which are written to
+    /// standard error.
+    #[must_use]
+    pub fn log_color_policy(&self) -> ColorPolicy {
+        ColorPolicy::resolve_for(self.color, &std::io::stderr())
+    }
+
+    /// Return a standard output stream that follows the given color policy.
+    #[must_use]
+    fn stdout(color: ColorPolicy) -> StandardStream {
+        StandardStream::stdout(color.into())
+    }
+
+    /// Return what to do with output files that already exist.
+    #[must_use]
+    fn overwrite_policy(&self) -> OverwritePolicy {
+        if self.force {
+            OverwritePolicy::Force
+        } else if self.no_clobber {
+            OverwritePolicy::NoClobber
+        } else if self.backup {
+            OverwritePolicy::Backup
+        } else {
+            OverwritePolicy::Ask
         }
+    }
 
-        StandardStream::stdout(choice)
+    /// Return the options used to save PDF files.
+    #[must_use]
+    fn save_options(&self) -> SaveOptions {
+        let source_date = match self.source_date_epoch {
+            Some(timestamp) => Some(timestamp),
+            None if self.deterministic => Some(0),
+            None => None,
+        };
+        SaveOptions { source_date }
     }
 
     /// Execute command, possibily returning an error.
     pub fn execute(self) -> Result<()> {
-        let mut stdout = self.stdout();
+        let color = self.color_policy();
+        let save_options = self.save_options();
+        let overwrite = Overwrite::new(self.overwrite_policy());
+        let mut ctx = ExecutionContext {
+            stdout: Self::stdout(color),
+            color,
+            verbosity: self.verbose,
+            no_input: self.no_input,
+            password: self.password,
+            save_options,
+            max_memory: self.max_memory,
+            #[cfg(feature = "net")]
+            timeout: std::time::Duration::from_secs(self.timeout),
+            summary: RunSummary::default(),
+            overwrite,
+            verify: self.verify,
+            cache: Cache::new(self.cache_dir, !self.no_cache),
+            journal: !self.no_journal,
+            #[cfg(feature = "i18n")]
+            i18n: Localizer::new(self.lang.unwrap_or_else(Language::from_env)),
+            #[cfg(not(feature = "i18n"))]
+            i18n: Localizer::default(),
+        };
+        let result = Self::execute_command(self.command, &mut ctx);
+
+        let report = ctx.summary.report(result.is_ok());
+        if let Some(path) = &self.report {
+            report.write_json(path)?;
+        }
+        if let Some(path) = &self.checksum_manifest {
+            hash::write_manifest(path, &ctx.summary.written_files())?;
+        }
+        if self.summary && result.is_ok() {
+            report.write_text(&mut ctx.stdout, &ctx.i18n)?;
+        }
+        result
+    }
 
-        match self.command {
+    /// Execute a command with the given context.
+    fn execute_command(command: Command, ctx: &mut ExecutionContext<StandardStream>) -> Result<()> {
+        match command {
             Command::Annotations(cmd) => {
-                cmd.execute(&mut stdout)?;
+                cmd.execute(ctx)?;
+            },
+            Command::Assemble(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Bench(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Cache(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Color(cmd) => {
+                cmd.execute(ctx)?;
             },
             Command::Completions(cmd) => {
-                cmd.execute(&mut stdout)?;
+                cmd.execute(&mut ctx.stdout)?;
+            },
+            Command::Convert(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Daemon(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Debug(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Doctor(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Hash(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::History(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Inspect(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Invoice(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Layers(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Manpages(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Metadata(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Objects(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            #[cfg(feature = "ocr")]
+            Command::Ocr(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Pages(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Portfolio(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            #[cfg(feature = "scripting")]
+            Command::Process(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Sanitize(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            #[cfg(feature = "server")]
+            Command::Serve(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Signatures(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Stamp(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Structure(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Text(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Undo(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::Validate(cmd) => {
+                cmd.execute(ctx)?;
+            },
+            Command::External(args) => {
+                plugins::run(&args, ctx)?;
             },
         }
         Ok(())
@@ -78,14 +437,111 @@ pub fn build_cli() -> clap::Command {
     Cli::command()
 }
 
+pub(crate) mod manpages {
+    //! Man pages generation with [`clap_mangen`].
+
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use anyhow::{Context, Result};
+    use clap::{Command, Parser};
+    use clap_mangen::Man;
+    use termcolor::WriteColor;
+    use tracing::debug;
+
+    use super::{context::ExecutionContext, traits::Execute};
+
+    /// Command structure to generate man pages.
+    #[derive(Debug, Parser)]
+    #[command(about = "Generate man pages for rpdf and all its subcommands")]
+    pub struct ManpagesCommand {
+        /// Directory where man pages are written.
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+    }
+
+    /// Recursively write one man page per (visible) command to `dir`.
+    fn generate(cmd: Command, dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+        for subcommand in cmd.get_subcommands().filter(|s| !s.is_hide_set()).cloned() {
+            generate(subcommand, dir, paths)?;
+        }
+        let path = Man::new(cmd).generate_to(dir)?;
+        debug!("Wrote man page to {path:?}");
+        paths.push(path);
+        Ok(())
+    }
+
+    impl ManpagesCommand {
+        /// Generate man pages and write them to the output directory,
+        /// returning the written filepaths.
+        pub fn generate_man_pages<F>(&self, build_cli: F) -> Result<Vec<PathBuf>>
+        where
+            F: FnOnce() -> Command,
+        {
+            fs::create_dir_all(&self.dir)
+                .with_context(|| format!("Failed to create directory: {:?}", self.dir))?;
+
+            let mut cmd = build_cli().disable_help_subcommand(true);
+            cmd.build();
+
+            let mut paths = vec![];
+            generate(cmd, &self.dir, &mut paths)
+                .with_context(|| format!("Failed to write man pages to: {:?}", self.dir))?;
+            Ok(paths)
+        }
+    }
+
+    impl Execute for ManpagesCommand {
+        fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+        where
+            W: WriteColor,
+        {
+            let paths = self.generate_man_pages(super::build_cli)?;
+
+            if !ctx.quiet() {
+                writeln!(
+                    ctx.stdout,
+                    "Successfully wrote {} man pages to {:?}.",
+                    paths.len(),
+                    self.dir
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
 pub(crate) mod complete {
     //! Completion scripts generation with [`clap_complete`].
 
     use anyhow::Result;
     use clap::{Command, Parser};
-    use clap_complete::{generate, shells::Shell};
+    use clap_complete::{CompletionCandidate, PathCompleter, generate, shells::Shell};
     use std::io::Write;
 
+    use crate::pdf::annotations::SUBTYPES;
+
+    /// Complete annotation subtypes, e.g., for `--exclude`.
+    pub(crate) fn annotation_subtypes() -> Vec<CompletionCandidate> {
+        SUBTYPES
+            .iter()
+            .copied()
+            .map(CompletionCandidate::new)
+            .collect()
+    }
+
+    /// Complete paths to PDF files.
+    pub(crate) fn pdf_files() -> PathCompleter {
+        PathCompleter::file().filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+        })
+    }
+
     /// Command structure to generate complete scripts.
     #[derive(Debug, Parser)]
     #[command(
@@ -121,6 +577,10 @@ pub(crate) mod complete {
 
     pub(crate) static COMPLETIONS_HELP: &str = r"DISCUSSION:
     Enable tab completion for Bash, Fish, Zsh, or PowerShell
+    The scripts generated by this command only complete subcommands and
+    flags. For dynamic completions, e.g., of annotation subtypes or PDF
+    files, source the output of `COMPLETE=<SHELL> rpdf` instead:
+        $ echo 'source <(COMPLETE=bash rpdf)' >> ~/.bashrc
     Elvish shell completion is currently supported, but not documented below.
     The script is output on `stdout`, allowing one to re-direct the
     output to the file of their choosing. Where you place the file
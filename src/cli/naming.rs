@@ -0,0 +1,109 @@
+//! File name templates, e.g., `{stem}_{cmd}_{date}.pdf`, which give each
+//! output file of a command its own name, e.g., when processing several
+//! files at once.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use super::context::ExecutionContext;
+
+/// Value of a placeholder in a file name template.
+#[derive(Clone, Copy)]
+pub(super) enum Placeholder<'a> {
+    Text(&'a str),
+    Number(u32),
+}
+
+/// Return a file name from a template, e.g., `{index:02}_{title}.pdf`.
+///
+/// Numbers can be zero-padded to a width, e.g., `{index:03}`. Characters
+/// that are not allowed in file names are replaced in text values.
+fn render_name(template: &str, values: &[(&str, Placeholder)]) -> Result<String> {
+    let mut name = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        name.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            bail!("Unclosed placeholder in name template {template:?}.");
+        };
+        let placeholder = &rest[start + 1..start + end];
+        let (key, width) = match placeholder.split_once(':') {
+            Some((key, width)) => {
+                let width: usize = width
+                    .trim_start_matches('0')
+                    .parse()
+                    .with_context(|| format!("Invalid width in placeholder {{{placeholder}}}"))?;
+                (key, width)
+            },
+            None => (placeholder, 0),
+        };
+        match values.iter().find(|(name, _)| *name == key) {
+            Some((_, Placeholder::Number(number))) => name.push_str(&format!("{number:0width$}")),
+            Some((_, Placeholder::Text(text))) => {
+                name.extend(text.chars().map(|c| {
+                    if c.is_control()
+                        || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+                    {
+                        '_'
+                    } else {
+                        c
+                    }
+                }));
+            },
+            None => {
+                let known: Vec<_> = values
+                    .iter()
+                    .map(|(name, _)| format!("{{{name}}}"))
+                    .collect();
+                bail!(
+                    "Unknown placeholder {{{key}}} in name template, expected one of: {}.",
+                    known.join(", ")
+                );
+            },
+        }
+        rest = &rest[start + end + 1..];
+    }
+    name.push_str(rest);
+    Ok(name)
+}
+
+/// Placeholders that every template of a command has: `{stem}`, the name of
+/// the input file without extension, `{cmd}`, the name of the command, and
+/// `{date}`, the current date as `YYYY-MM-DD`.
+pub(super) struct Naming {
+    stem: String,
+    cmd: &'static str,
+    date: String,
+}
+
+impl Naming {
+    /// Return the placeholders of the output files of a command run on a
+    /// file.
+    ///
+    /// The date is the source date if output must be reproducible.
+    pub(super) fn new<W>(ctx: &ExecutionContext<W>, file: &Path, cmd: &'static str) -> Self {
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // PDF dates start with `D:YYYYMMDD`.
+        let date = ctx.date();
+        let date = format!("{}-{}-{}", &date[2..6], &date[6..8], &date[8..10]);
+
+        Self { stem, cmd, date }
+    }
+
+    /// Return a file name from a template, with the given placeholders in
+    /// addition to the common ones.
+    pub(super) fn render(&self, template: &str, values: &[(&str, Placeholder)]) -> Result<String> {
+        let mut all = vec![
+            ("stem", Placeholder::Text(&self.stem)),
+            ("cmd", Placeholder::Text(self.cmd)),
+            ("date", Placeholder::Text(&self.date)),
+        ];
+        all.extend(values.iter().copied());
+        render_name(template, &all)
+    }
+}
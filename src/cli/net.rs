@@ -0,0 +1,107 @@
+//! Downloads of PDF files given as URLs, e.g., presigned URLs of object
+//! storage, for read-only commands.
+
+use std::{fs, time::Duration};
+
+use anyhow::{Context, Result};
+use reqwest::{
+    StatusCode,
+    blocking::{Client, Response},
+    header::{self, HeaderName},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::cache::Cache;
+
+/// Validators of a cached download, sent back to the server so that it only
+/// sends the file again if it changed.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Validators {
+    /// `ETag` header of the response.
+    etag: Option<String>,
+    /// `Last-Modified` header of the response.
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Return the validators of a response.
+    fn from_response(response: &Response) -> Self {
+        let header = |name: HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        };
+        Self {
+            etag: header(header::ETAG),
+            last_modified: header(header::LAST_MODIFIED),
+        }
+    }
+}
+
+/// Return the content of the file at a URL, failing after `timeout`.
+///
+/// Downloads are cached along with their validators, and cached files are
+/// only downloaded again if the server tells that they changed. Failing to
+/// read or write the cache is never an error, the file is downloaded
+/// instead.
+///
+/// # Errors
+///
+/// Fails if the server cannot be reached in time, or if it answers with an
+/// error.
+pub fn download(url: &str, cache: &Cache, timeout: Duration) -> Result<Vec<u8>> {
+    let path = cache.download_path(url);
+    let validators_path = path.as_ref().map(|path| path.with_extension("json"));
+    let cached = path.as_ref().and_then(|path| fs::read(path).ok());
+    let validators: Validators = validators_path
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+
+    let client = Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("rpdf/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .context("Failed to create HTTP client")?;
+    let mut request = client.get(url);
+    if cached.is_some() {
+        if let Some(etag) = &validators.etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    debug!("Downloading {url:?}");
+    let response = request
+        .send()
+        .with_context(|| format!("Failed to download PDF from: {url:?}"))?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            debug!("Using cached download of {url:?}");
+            return Ok(cached);
+        }
+    }
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("Failed to download PDF from: {url:?}"))?;
+    let validators = Validators::from_response(&response);
+    let data = response
+        .bytes()
+        .with_context(|| format!("Failed to download PDF from: {url:?}"))?
+        .to_vec();
+
+    if let (Some(path), Some(validators_path)) = (path, validators_path) {
+        let written = Cache::write_file(&path, &data)
+            .and_then(|()| Cache::write_file(&validators_path, &serde_json::to_vec(&validators)?));
+        if let Err(e) = written {
+            warn!("Failed to cache download of {url:?}: {e:#}");
+        }
+    }
+    Ok(data)
+}
@@ -0,0 +1,782 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Write as _},
+    io,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use termcolor::{Color, ColorSpec, WriteColor};
+use tracing::info;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::{
+    self,
+    document::DocumentExt,
+    objects::{object_dictionary, object_types, parse_object, parse_object_id},
+    pdfstring,
+};
+
+/// Number of bytes per line of hex dumps.
+const HEX_DUMP_WIDTH: usize = 16;
+
+/// Write a name, escaping irregular characters as `#XX`.
+fn format_name(name: &[u8]) -> String {
+    let mut formatted = String::from("/");
+
+    for &byte in name {
+        if byte.is_ascii_graphic() && !b"#()<>[]{}/%".contains(&byte) {
+            formatted.push(char::from(byte));
+        } else {
+            let _ = write!(formatted, "#{byte:02X}");
+        }
+    }
+    formatted
+}
+
+/// Write a string, decoded as a text string if it is printable, and in
+/// hexadecimal otherwise.
+fn format_string(object: &Object, bytes: &[u8]) -> String {
+    match pdfstring::decode_object(object) {
+        Ok(text)
+            if !text
+                .chars()
+                .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) =>
+        {
+            let mut formatted = String::from("(");
+            for c in text.chars() {
+                match c {
+                    '(' | ')' | '\\' => {
+                        formatted.push('\\');
+                        formatted.push(c);
+                    },
+                    '\n' => formatted.push_str("\\n"),
+                    '\r' => formatted.push_str("\\r"),
+                    '\t' => formatted.push_str("\\t"),
+                    c => formatted.push(c),
+                }
+            }
+            formatted.push(')');
+            formatted
+        },
+        _ => {
+            let mut formatted = String::from("<");
+            for byte in bytes {
+                let _ = write!(formatted, "{byte:02X}");
+            }
+            formatted.push('>');
+            formatted
+        },
+    }
+}
+
+/// Return whether an object is printed on a single line.
+fn is_scalar(object: &Object) -> bool {
+    !matches!(
+        object,
+        Object::Array(_) | Object::Dictionary(_) | Object::Stream(_)
+    )
+}
+
+/// Return whether data is mostly printable text.
+fn is_text(data: &[u8]) -> bool {
+    let printable = data
+        .iter()
+        .filter(|byte| byte.is_ascii_graphic() || byte.is_ascii_whitespace())
+        .count();
+    printable * 100 >= data.len() * 95
+}
+
+/// Pretty-prints objects with syntax highlighting.
+struct Printer<'a, W> {
+    out: &'a mut W,
+    /// Decode stream contents.
+    inflate: bool,
+    /// Show stream contents as hex dumps, even if they are text.
+    hex: bool,
+}
+
+impl<W: WriteColor> Printer<'_, W> {
+    /// Write text in a color.
+    fn colored(&mut self, color: Color, text: &str) -> io::Result<()> {
+        self.out.set_color(ColorSpec::new().set_fg(Some(color)))?;
+        write!(self.out, "{text}")?;
+        self.out.reset()
+    }
+
+    /// Write a comment, dimmed.
+    fn comment(&mut self, text: &str) -> io::Result<()> {
+        self.out.set_color(ColorSpec::new().set_dimmed(true))?;
+        write!(self.out, "% {text}")?;
+        self.out.reset()
+    }
+
+    /// Write an object, whose nested lines are indented by `indent` levels.
+    fn object(&mut self, object: &Object, indent: usize) -> io::Result<()> {
+        match object {
+            Object::Null => self.colored(Color::Yellow, "null"),
+            Object::Boolean(value) => self.colored(Color::Yellow, &value.to_string()),
+            Object::Integer(value) => self.colored(Color::Cyan, &value.to_string()),
+            Object::Real(value) => self.colored(Color::Cyan, &value.to_string()),
+            Object::Name(name) => self.colored(Color::Blue, &format_name(name)),
+            Object::String(bytes, _) => self.colored(Color::Green, &format_string(object, bytes)),
+            Object::Reference((number, generation)) => {
+                self.colored(Color::Magenta, &format!("{number} {generation} R"))
+            },
+            Object::Array(array) if array.iter().all(is_scalar) => {
+                write!(self.out, "[")?;
+                for (i, item) in array.iter().enumerate() {
+                    if i > 0 {
+                        write!(self.out, " ")?;
+                    }
+                    self.object(item, indent)?;
+                }
+                write!(self.out, "]")
+            },
+            Object::Array(array) => {
+                writeln!(self.out, "[")?;
+                for item in array {
+                    write!(self.out, "{}", "  ".repeat(indent + 1))?;
+                    self.object(item, indent + 1)?;
+                    writeln!(self.out)?;
+                }
+                write!(self.out, "{}]", "  ".repeat(indent))
+            },
+            Object::Dictionary(dict) => self.dictionary(dict, indent),
+            Object::Stream(stream) => self.stream(stream, indent),
+        }
+    }
+
+    /// Write a dictionary, one entry per line.
+    fn dictionary(&mut self, dict: &Dictionary, indent: usize) -> io::Result<()> {
+        if dict.is_empty() {
+            return write!(self.out, "<< >>");
+        }
+        writeln!(self.out, "<<")?;
+        for (key, value) in dict {
+            write!(self.out, "{}", "  ".repeat(indent + 1))?;
+            self.colored(Color::Blue, &format_name(key))?;
+            write!(self.out, " ")?;
+            self.object(value, indent + 1)?;
+            writeln!(self.out)?;
+        }
+        write!(self.out, "{}>>", "  ".repeat(indent))
+    }
+
+    /// Write a stream's dictionary, followed by its content if requested.
+    fn stream(&mut self, stream: &Stream, indent: usize) -> io::Result<()> {
+        self.dictionary(&stream.dict, indent)?;
+        writeln!(self.out)?;
+        self.colored(Color::Yellow, "stream")?;
+        writeln!(self.out)?;
+
+        let filtered = !stream.filters().unwrap_or_default().is_empty();
+        let content = if !filtered {
+            Some(stream.content.clone())
+        } else if self.inflate {
+            pdf::decompress(stream).ok()
+        } else {
+            None
+        };
+
+        match content {
+            Some(content) if !self.hex && is_text(&content) => {
+                let text = String::from_utf8_lossy(&content);
+                writeln!(self.out, "{}", text.trim_end())?;
+            },
+            Some(content) => self.hex_dump(&content)?,
+            None if self.hex => self.hex_dump(&stream.content)?,
+            None => {
+                let reason = if self.inflate {
+                    "could not be decoded"
+                } else {
+                    "use --inflate to decode them"
+                };
+                self.comment(&format!("{} encoded bytes, {reason}", stream.content.len()))?;
+                writeln!(self.out)?;
+            },
+        }
+        self.colored(Color::Yellow, "endstream")
+    }
+
+    /// Write binary data as a hex dump, with offsets and ASCII columns.
+    fn hex_dump(&mut self, data: &[u8]) -> io::Result<()> {
+        for (i, chunk) in data.chunks(HEX_DUMP_WIDTH).enumerate() {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        char::from(byte)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            self.out.set_color(ColorSpec::new().set_dimmed(true))?;
+            write!(self.out, "{:08x}", i * HEX_DUMP_WIDTH)?;
+            self.out.reset()?;
+            writeln!(
+                self.out,
+                "  {:<width$}  |{ascii}|",
+                hex.join(" "),
+                width = HEX_DUMP_WIDTH * 3 - 1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Show command.
+#[derive(Args, Clone, Debug)]
+struct Show {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Object ID, e.g., `12`, `12 0` or `12 0 R`, or `trailer`.
+    id: Node,
+    /// Decode stream contents.
+    #[clap(short, long)]
+    inflate: bool,
+    /// Show stream contents as hex dumps.
+    #[clap(short = 'x', long)]
+    hex: bool,
+}
+
+impl Execute for Show {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        // Only the data of the image that is shown, if any, is read.
+        let (mut document, mut images) = ctx.load_document_with_deferred_images(&self.file)?;
+        if let Node::Object(id) = self.id {
+            images.load(&mut document, id)?;
+        }
+        let mut printer = Printer {
+            out: &mut ctx.stdout,
+            inflate: self.inflate,
+            hex: self.hex,
+        };
+
+        match self.id {
+            Node::Trailer => {
+                printer.colored(Color::Yellow, "trailer")?;
+                writeln!(printer.out)?;
+                printer.dictionary(&document.trailer, 0)?;
+            },
+            Node::Object(id) => {
+                let object = document
+                    .get_object(id)
+                    .with_context(|| format!("Failed to find object {} {} R", id.0, id.1))?;
+                printer.colored(Color::Yellow, &format!("{} {} obj", id.0, id.1))?;
+                writeln!(printer.out)?;
+                printer.object(object, 0)?;
+                writeln!(printer.out)?;
+                printer.colored(Color::Yellow, "endobj")?;
+            },
+        }
+        writeln!(printer.out)?;
+        Ok(())
+    }
+}
+
+/// Parse a name given with or without its leading slash.
+fn parse_name(s: &str) -> Result<String, String> {
+    let name = s.strip_prefix('/').unwrap_or(s);
+
+    if name.is_empty() {
+        return Err("name must not be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Find command.
+#[derive(Args, Clone, Debug)]
+struct Find {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Only list objects with this `/Type`, e.g., `/Annot`.
+    #[clap(short, long = "type", value_parser = parse_name)]
+    type_: Option<String>,
+    /// Only list objects with this `/Subtype`, e.g., `/Link`.
+    #[clap(short, long, value_parser = parse_name)]
+    subtype: Option<String>,
+}
+
+impl Execute for Find {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let mut printer = Printer {
+            out: &mut ctx.stdout,
+            inflate: false,
+            hex: false,
+        };
+        let mut count = 0;
+
+        for (&(number, generation), object) in &document.objects {
+            let (type_, subtype) = object_types(object);
+            let matches = |expected: &Option<String>, actual: Option<&[u8]>| {
+                expected
+                    .as_ref()
+                    .map_or(true, |expected| actual == Some(expected.as_bytes()))
+            };
+            if !matches(&self.type_, type_) || !matches(&self.subtype, subtype) {
+                continue;
+            }
+
+            printer.colored(Color::Magenta, &format!("{number} {generation} R"))?;
+            for name in [type_, subtype].into_iter().flatten() {
+                write!(printer.out, " ")?;
+                printer.colored(Color::Blue, &format_name(name))?;
+            }
+            writeln!(printer.out)?;
+            count += 1;
+        }
+
+        info!("Found {count} matching objects.");
+        Ok(())
+    }
+}
+
+/// Starting point of an object tree.
+#[derive(Clone, Copy, Debug)]
+enum Node {
+    Trailer,
+    Object(ObjectId),
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trailer => write!(f, "trailer"),
+            Self::Object((number, generation)) => write!(f, "{number} {generation} R"),
+        }
+    }
+}
+
+impl FromStr for Node {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("trailer") {
+            Ok(Self::Trailer)
+        } else {
+            parse_object_id(s).map(Self::Object)
+        }
+    }
+}
+
+/// Collect the references held by an object, with the path leading to them,
+/// e.g., `/Resources/Font/F1`.
+fn references(object: &Object, path: &str, references: &mut Vec<(String, ObjectId)>) {
+    match object {
+        Object::Reference(id) => references.push((path.to_string(), *id)),
+        Object::Array(array) => {
+            for (i, item) in array.iter().enumerate() {
+                self::references(item, &format!("{path}[{i}]"), references);
+            }
+        },
+        _ => {
+            if let Some(dict) = object_dictionary(object) {
+                for (key, value) in dict {
+                    self::references(value, &format!("{path}{}", format_name(key)), references);
+                }
+            }
+        },
+    }
+}
+
+/// Tree command.
+#[derive(Args, Clone, Debug)]
+struct Tree {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Object the tree starts from, e.g., `12 0`, or `trailer`.
+    #[clap(short, long, default_value = "trailer")]
+    from: Node,
+    /// Maximum depth of the tree.
+    #[clap(short, long)]
+    depth: Option<usize>,
+}
+
+impl Tree {
+    /// Write the references of an object, and recursively the references of
+    /// objects that were not visited yet.
+    fn write_children<W: WriteColor>(
+        &self,
+        printer: &mut Printer<'_, W>,
+        document: &Document,
+        object: &Object,
+        prefix: &str,
+        depth: usize,
+        visited: &mut HashSet<ObjectId>,
+    ) -> io::Result<()> {
+        let mut children = vec![];
+        references(object, "", &mut children);
+
+        for (i, (path, id)) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            write!(
+                printer.out,
+                "{prefix}{}{path} ",
+                if last { "└── " } else { "├── " }
+            )?;
+            printer.colored(Color::Magenta, &format!("{} {} R", id.0, id.1))?;
+
+            let Ok(child) = document.get_object(*id) else {
+                write!(printer.out, " ")?;
+                printer.comment("missing")?;
+                writeln!(printer.out)?;
+                continue;
+            };
+            let (type_, subtype) = object_types(child);
+            for name in [type_, subtype].into_iter().flatten() {
+                write!(printer.out, " ")?;
+                printer.colored(Color::Blue, &format_name(name))?;
+            }
+
+            if !visited.insert(*id) {
+                write!(printer.out, " ")?;
+                printer.comment("already shown")?;
+                writeln!(printer.out)?;
+                continue;
+            }
+            writeln!(printer.out)?;
+
+            if self.depth.map_or(true, |max| depth < max) {
+                let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+                self.write_children(printer, document, child, &prefix, depth + 1, visited)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Execute for Tree {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let mut printer = Printer {
+            out: &mut ctx.stdout,
+            inflate: false,
+            hex: false,
+        };
+        let mut visited = HashSet::new();
+
+        let root = match self.from {
+            Node::Trailer => {
+                printer.colored(Color::Yellow, "trailer")?;
+                Object::Dictionary(document.trailer.clone())
+            },
+            Node::Object(id) => {
+                printer.colored(Color::Magenta, &format!("{} {} R", id.0, id.1))?;
+                visited.insert(id);
+                document
+                    .get_object(id)
+                    .with_context(|| format!("Failed to find object {} {} R", id.0, id.1))?
+                    .clone()
+            },
+        };
+        writeln!(printer.out)?;
+
+        self.write_children(&mut printer, &document, &root, "", 1, &mut visited)?;
+        Ok(())
+    }
+}
+
+/// Return the resource category an operator refers to, and the index of the
+/// operand holding the resource name.
+fn resource_operand(operator: &str, operands: usize) -> Option<(&'static [u8], usize)> {
+    match operator {
+        "Tf" => Some((b"Font", 0)),
+        "Do" => Some((b"XObject", 0)),
+        "gs" => Some((b"ExtGState", 0)),
+        "cs" | "CS" => Some((b"ColorSpace", 0)),
+        "scn" | "SCN" => Some((b"Pattern", operands.checked_sub(1)?)),
+        "sh" => Some((b"Shading", 0)),
+        "BDC" | "DP" => Some((b"Properties", 1)),
+        _ => None,
+    }
+}
+
+/// Describe a resource, e.g., `/Helvetica-Bold, 5 0 R` for a font.
+fn describe_resource(document: &Document, object: &Object) -> String {
+    let (id, object) = match document.dereference(object) {
+        Ok(dereferenced) => dereferenced,
+        Err(_) => return "missing".to_string(),
+    };
+    let mut description = vec![];
+
+    if let Some(dict) = object_dictionary(object) {
+        let (type_, subtype) = object_types(object);
+        if let Ok(base_font) = dict.get(b"BaseFont").and_then(Object::as_name) {
+            description.push(format_name(base_font));
+        } else if let Some(name) = subtype.or(type_) {
+            description.push(format_name(name));
+        }
+        if let (Ok(width), Ok(height)) = (
+            dict.get(b"Width").and_then(Object::as_i64),
+            dict.get(b"Height").and_then(Object::as_i64),
+        ) {
+            description.push(format!("{width}x{height}"));
+        }
+    } else if let Ok(name) = object.as_name() {
+        description.push(format_name(name));
+    }
+    if let Some((number, generation)) = id {
+        description.push(format!("{number} {generation} R"));
+    }
+    description.join(", ")
+}
+
+/// Content command.
+#[derive(Args, Clone, Debug)]
+struct Content {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Page number.
+    #[clap(short, long)]
+    page: u32,
+}
+
+impl Execute for Content {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let page_id = *document
+            .get_pages()
+            .get(&self.page)
+            .with_context(|| format!("Page {} does not exist in {:?}.", self.page, self.file))?;
+        let operations = document
+            .get_and_decode_page_content(page_id)
+            .with_context(|| format!("Failed to decode content of page {}.", self.page))?
+            .operations;
+        let resources = document.page_resources(page_id);
+        let mut printer = Printer {
+            out: &mut ctx.stdout,
+            inflate: false,
+            hex: false,
+        };
+
+        let streams: Vec<String> = document
+            .get_page_contents(page_id)
+            .into_iter()
+            .map(|(number, generation)| format!("{number} {generation} R"))
+            .collect();
+        printer.comment(&format!(
+            "Page {} ({} {} R), content streams: {}",
+            self.page,
+            page_id.0,
+            page_id.1,
+            streams.join(", ")
+        ))?;
+        writeln!(printer.out)?;
+
+        let mut depth = 0_usize;
+        for operation in &operations {
+            if matches!(operation.operator.as_str(), "Q" | "ET" | "EMC") {
+                depth = depth.saturating_sub(1);
+            }
+
+            write!(printer.out, "{}", "  ".repeat(depth))?;
+            for operand in &operation.operands {
+                printer.object(operand, depth)?;
+                write!(printer.out, " ")?;
+            }
+            printer.colored(Color::Yellow, &operation.operator)?;
+
+            let resource = resource_operand(&operation.operator, operation.operands.len())
+                .and_then(|(category, index)| {
+                    let name = operation.operands.get(index)?.as_name().ok()?;
+                    let object = resources?
+                        .get_deref(category, &document)
+                        .and_then(Object::as_dict)
+                        .ok()?
+                        .get(name)
+                        .ok()?;
+                    Some(describe_resource(&document, object))
+                });
+            if let Some(resource) = resource {
+                write!(printer.out, "  ")?;
+                printer.comment(&resource)?;
+            }
+            writeln!(printer.out)?;
+
+            if matches!(operation.operator.as_str(), "q" | "BT" | "BDC" | "BMC") {
+                depth += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the dictionary of the trailer or of an object, for modification.
+fn node_dictionary_mut(document: &mut Document, node: Node) -> Result<&mut Dictionary> {
+    match node {
+        Node::Trailer => Ok(&mut document.trailer),
+        Node::Object(id) => {
+            match document
+                .get_object_mut(id)
+                .with_context(|| format!("Failed to find object {} {} R", id.0, id.1))?
+            {
+                Object::Dictionary(dict) => Ok(dict),
+                Object::Stream(stream) => Ok(&mut stream.dict),
+                _ => {
+                    bail!(
+                        "Object {} {} R is neither a dictionary nor a stream.",
+                        id.0,
+                        id.1
+                    )
+                },
+            }
+        },
+    }
+}
+
+/// Set command.
+#[derive(Args, Clone, Debug)]
+struct Set {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// ID of the dictionary or stream object, e.g., `12 0`, or `trailer`.
+    id: Node,
+    /// Key to set, e.g., `/Rotate`.
+    #[clap(short, long, value_parser = parse_name)]
+    key: String,
+    /// Value in PDF syntax, e.g., `90`, `/Name`, `(text)`, `[0 0 612 792]`,
+    /// `<< /S /URI >>` or `12 0 R`.
+    #[clap(long, value_parser = parse_object, allow_hyphen_values = true)]
+    value: Object,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "patched.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Set {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let dict = node_dictionary_mut(&mut document, self.id)?;
+
+        if let Some(previous) = dict.remove(self.key.as_bytes()) {
+            info!("Replacing previous value: {previous:?}");
+        }
+        dict.set(self.key.as_bytes(), self.value.clone());
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully set /{} of {} in {:?}.",
+                self.key, self.id, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Delete-key command.
+#[derive(Args, Clone, Debug)]
+struct DeleteKey {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// ID of the dictionary or stream object, e.g., `12 0`, or `trailer`.
+    id: Node,
+    /// Key to delete, e.g., `/AA`.
+    #[clap(short, long, value_parser = parse_name)]
+    key: String,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "patched.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for DeleteKey {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let dict = node_dictionary_mut(&mut document, self.id)?;
+
+        if dict.remove(self.key.as_bytes()).is_none() {
+            bail!("{} has no /{} key.", self.id, self.key);
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully deleted /{} of {} in {:?}.",
+                self.key, self.id, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for objects.
+#[derive(Debug, Subcommand)]
+enum ObjectsSubcommand {
+    /// Pretty-print an object, or the trailer.
+    Show(Show),
+    /// List objects, optionally filtered by type and subtype.
+    Find(Find),
+    /// Disassemble the content streams of a page.
+    ///
+    /// Operators are indented by graphics state, text object and marked
+    /// content nesting, and resources they use are resolved.
+    Content(Content),
+    /// Show the tree of references from the trailer or an object.
+    ///
+    /// Objects are expanded once; later references to them are marked as
+    /// already shown.
+    Tree(Tree),
+    /// Set a key of a dictionary or stream object, or of the trailer.
+    Set(Set),
+    /// Delete a key of a dictionary or stream object, or of the trailer.
+    DeleteKey(DeleteKey),
+}
+
+/// Explore the objects of PDF files, e.g., to debug broken files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct ObjectsCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: ObjectsSubcommand,
+}
+
+impl Execute for ObjectsCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            ObjectsSubcommand::Show(show) => show.execute(ctx),
+            ObjectsSubcommand::Find(find) => find.execute(ctx),
+            ObjectsSubcommand::Tree(tree) => tree.execute(ctx),
+            ObjectsSubcommand::Content(content) => content.execute(ctx),
+            ObjectsSubcommand::Set(set) => set.execute(ctx),
+            ObjectsSubcommand::DeleteKey(delete_key) => delete_key.execute(ctx),
+        }
+    }
+}
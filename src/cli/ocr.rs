@@ -0,0 +1,268 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::{
+    Document, Object, ObjectId,
+    content::{Content, Operation},
+};
+use termcolor::WriteColor;
+use tracing::{debug, error_span, info, warn};
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::{
+    content,
+    document::DocumentExt,
+    fonts::{StandardFont, win_ansi_string},
+    images,
+};
+
+/// Font used for the invisible text layer.
+const OCR_FONT: StandardFont = StandardFont::Helvetica;
+
+/// Add an invisible, searchable text layer to scanned PDFs with Tesseract.
+///
+/// Each page's largest image is sent to the `tesseract` executable, and the
+/// recognized words are drawn invisibly on top of it. Pages that already
+/// contain text are left untouched.
+#[derive(Debug, Parser)]
+pub struct OcrCommand {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "ocr.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+    /// Language(s) passed to Tesseract, e.g., `eng` or `eng+fra`.
+    #[clap(short, long, default_value = "eng")]
+    lang: String,
+    /// Path to the Tesseract executable.
+    #[clap(long, default_value = "tesseract", env = "RPDF_TESSERACT", value_hint = ValueHint::ExecutablePath)]
+    tesseract: PathBuf,
+    /// Minimum confidence (from 0 to 100) for a recognized word to be kept.
+    #[clap(long, default_value_t = 0.0)]
+    min_confidence: f32,
+}
+
+/// A recognized word, with its bounding box.
+#[derive(Debug)]
+struct Word {
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+    text: String,
+}
+
+/// Parse Tesseract's TSV output, keeping words with enough confidence.
+fn parse_tsv(tsv: &str, min_confidence: f32) -> Vec<Word> {
+    tsv.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(12, '\t').collect();
+
+            match fields.as_slice() {
+                [
+                    "5",
+                    _,
+                    _,
+                    _,
+                    _,
+                    _,
+                    left,
+                    top,
+                    width,
+                    height,
+                    confidence,
+                    text,
+                ] => {
+                    let text = text.trim();
+
+                    if text.is_empty() || confidence.parse::<f32>().ok()? < min_confidence {
+                        return None;
+                    }
+                    Some(Word {
+                        left: left.parse().ok()?,
+                        top: top.parse().ok()?,
+                        width: width.parse().ok()?,
+                        height: height.parse().ok()?,
+                        text: text.to_string(),
+                    })
+                },
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+impl OcrCommand {
+    /// Run Tesseract on an image file and return the recognized words.
+    fn recognize(&self, image: &[u8]) -> Result<Vec<Word>> {
+        let mut child = Command::new(&self.tesseract)
+            .args(["stdin", "stdout", "-l", &self.lang, "tsv"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run Tesseract from: {:?}", self.tesseract))?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let output = std::thread::scope(|scope| {
+            scope.spawn(move || stdin.write_all(image));
+            child.wait_with_output()
+        })?;
+
+        if !output.status.success() {
+            bail!(
+                "Tesseract failed with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(parse_tsv(
+            &String::from_utf8_lossy(&output.stdout),
+            self.min_confidence,
+        ))
+    }
+
+    /// Recognize the words of a page, in page coordinates, or return `None`
+    /// if the page was skipped.
+    fn recognize_page(
+        &self,
+        document: &Document,
+        page_number: u32,
+        page_id: ObjectId,
+    ) -> Result<Option<Vec<Word>>> {
+        let operations = document
+            .get_and_decode_page_content(page_id)
+            .with_context(|| format!("Failed to decode content of page {page_number}."))?
+            .operations;
+
+        if content::shows_text(&operations) {
+            info!("Page {page_number} already contains text, skipping it");
+            return Ok(None);
+        }
+
+        let largest_image = images::page_images(document, page_id)
+            .with_context(|| format!("Failed to decode content of page {page_number}."))?
+            .into_iter()
+            .max_by(|a, b| a.ctm.area_scale().total_cmp(&b.ctm.area_scale()));
+
+        let Some(images::PlacedImage { id, stream, ctm }) = largest_image else {
+            info!("Page {page_number} does not contain any image, skipping it");
+            return Ok(None);
+        };
+
+        let Some((format, image)) = images::export(document, stream) else {
+            warn!("Image on page {page_number} uses an unsupported encoding, skipping it");
+            return Ok(None);
+        };
+        debug!("Running OCR on page {page_number} ({format:?} image {id:?})");
+
+        let width = stream.dict.get(b"Width").and_then(Object::as_float)?;
+        let height = stream.dict.get(b"Height").and_then(Object::as_float)?;
+        let words = self.recognize(&image)?;
+        debug!("Recognized {} words on page {page_number}", words.len());
+
+        // Image space is the unit square, with the first row at the top.
+        let to_page = |x: f32, y: f32| ctm.apply(x / width, 1.0 - y / height);
+
+        Ok(Some(
+            words
+                .into_iter()
+                .map(|word| {
+                    let (x0, y0) = to_page(word.left, word.top);
+                    let (x1, y1) = to_page(word.left + word.width, word.top + word.height);
+
+                    Word {
+                        left: x0.min(x1),
+                        top: y0.max(y1),
+                        width: (x1 - x0).abs(),
+                        height: (y1 - y0).abs(),
+                        text: word.text,
+                    }
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Return the content drawing words, given in page coordinates, invisibly.
+fn text_layer(words: &[Word], font_name: &[u8]) -> Content {
+    let mut operations = vec![
+        Operation::new("BT", vec![]),
+        Operation::new("Tr", vec![3.into()]),
+    ];
+
+    for word in words {
+        let text_width = OCR_FONT.text_width(&word.text, word.height);
+
+        if word.height <= 0.0 || text_width <= 0.0 {
+            continue;
+        }
+
+        operations.extend([
+            Operation::new(
+                "Tf",
+                vec![Object::Name(font_name.to_vec()), word.height.into()],
+            ),
+            Operation::new("Tz", vec![(100.0 * word.width / text_width).into()]),
+            Operation::new(
+                "Tm",
+                vec![
+                    1.into(),
+                    0.into(),
+                    0.into(),
+                    1.into(),
+                    word.left.into(),
+                    (word.top - word.height).into(),
+                ],
+            ),
+            Operation::new("Tj", vec![win_ansi_string(&word.text)]),
+        ]);
+    }
+    operations.push(Operation::new("ET", vec![]));
+
+    Content { operations }
+}
+
+impl Execute for OcrCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let font_id = document.add_object(OCR_FONT.dictionary());
+        let pages = document.get_pages();
+        let mut processed = 0;
+
+        for (&page_number, &page_id) in &pages {
+            let _span = error_span!("page", number = page_number).entered();
+            if let Some(words) = self.recognize_page(&document, page_number, page_id)? {
+                let font_name = document.add_page_resource(page_id, b"Font", "RpdfOcr", font_id)?;
+
+                document.overlay_page_content(page_id, text_layer(&words, &font_name).encode()?)?;
+                processed += 1;
+            }
+        }
+
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully added a text layer to {processed} of {} pages in {:?}.",
+                pages.len(),
+                self.dest
+            )?;
+        }
+
+        Ok(())
+    }
+}
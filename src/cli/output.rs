@@ -0,0 +1,145 @@
+//! Policy for output files that already exist, shared by all commands that
+//! write files.
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use tracing::{info, warn};
+
+use super::context::ExecutionContext;
+
+/// What to do with output files that already exist.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OverwritePolicy {
+    /// Ask before overwriting, or fail with `--no-input`.
+    #[default]
+    Ask,
+    /// Overwrite without asking.
+    Force,
+    /// Never overwrite, skipping outputs that already exist.
+    NoClobber,
+    /// Copy existing files to a file with a `.bak` suffix, then write.
+    Backup,
+}
+
+/// Overwrite policy of a run, and the destinations already resolved, so that
+/// users are asked once per file.
+#[derive(Debug, Default)]
+pub struct Overwrite {
+    policy: OverwritePolicy,
+    resolved: RefCell<HashSet<PathBuf>>,
+}
+
+impl Overwrite {
+    #[must_use]
+    pub fn new(policy: OverwritePolicy) -> Self {
+        Self {
+            policy,
+            resolved: RefCell::default(),
+        }
+    }
+}
+
+/// Return the path where an existing file is backed up, e.g., `out.pdf.bak`.
+fn backup_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    dest.with_file_name(name)
+}
+
+/// Return whether a file may be written to `dest`, following the overwrite
+/// policy.
+///
+/// Commands that write several files should call this before processing
+/// each one, and skip files for which it returns `false`. Saving a document
+/// calls it too, and fails rather than skipping.
+///
+/// # Errors
+///
+/// Fails if `dest` exists and the user cannot be asked, or if it cannot be
+/// backed up.
+pub fn resolve_destination<W>(ctx: &ExecutionContext<W>, dest: &Path) -> Result<bool> {
+    let overwrite = &ctx.overwrite;
+    if overwrite.resolved.borrow().contains(dest) {
+        return Ok(true);
+    }
+    let allowed = if !dest.exists() {
+        true
+    } else {
+        match overwrite.policy {
+            OverwritePolicy::Force => true,
+            OverwritePolicy::NoClobber => {
+                warn!("Output file {dest:?} already exists, skipping it.");
+                false
+            },
+            OverwritePolicy::Backup => {
+                // Files are copied rather than renamed, so that files edited in
+                // place can still be read, and journaled.
+                let backup = backup_path(dest);
+                fs::copy(dest, &backup)
+                    .with_context(|| format!("Failed to back up {dest:?} to: {backup:?}"))?;
+                info!("Backed up {dest:?} to {backup:?}");
+                true
+            },
+            OverwritePolicy::Ask if ctx.no_input => {
+                bail!("Output file {dest:?} already exists, use `--force` to overwrite it.");
+            },
+            OverwritePolicy::Ask => {
+                dialoguer::Confirm::new()
+                    .with_prompt(
+                        ctx.i18n
+                            .message("overwrite-prompt", &[("file", format!("{dest:?}").into())]),
+                    )
+                    .interact()
+                    .unwrap_or(false)
+            },
+        }
+    };
+    if allowed {
+        overwrite.resolved.borrow_mut().insert(dest.to_path_buf());
+    }
+    Ok(allowed)
+}
+
+/// Fail unless a file may be written to `dest`, for commands that write a
+/// single file.
+///
+/// # Errors
+///
+/// Fails if `dest` exists and the policy does not allow overwriting it.
+pub fn check_destination<W>(ctx: &ExecutionContext<W>, dest: &Path) -> Result<()> {
+    if !resolve_destination(ctx, dest)? {
+        bail!("Output file {dest:?} already exists, not overwriting it.");
+    }
+    Ok(())
+}
+
+/// Write a file through a temporary file next to it, which is then renamed,
+/// like [`pdf::save`](crate::pdf::save) does, so that a failed write never
+/// leaves a truncated file. Existing files keep their permissions.
+///
+/// # Errors
+///
+/// Fails if the temporary file cannot be written or renamed.
+pub fn write_atomically(path: &Path, data: &[u8]) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".rpdf-").suffix(".tmp");
+    if let Ok(metadata) = fs::metadata(path) {
+        builder.permissions(metadata.permissions());
+    }
+    let mut file = builder.tempfile_in(dir)?;
+    file.write_all(data)?;
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|error| error.error)?;
+    Ok(())
+}
@@ -0,0 +1,904 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use serde::Serialize;
+use tabled::{
+    builder::Builder,
+    settings::{Color, Panel, Style, style::BorderColor},
+};
+use termcolor::WriteColor;
+use tracing::{debug, error_span, warn};
+
+use super::{
+    cache::Cache,
+    complete::pdf_files,
+    context::{ExecutionContext, parse_size},
+    naming::{Naming, Placeholder},
+    output,
+    traits::Execute,
+};
+#[cfg(feature = "images")]
+use crate::pdf::deskew;
+use crate::pdf::{
+    assemble::{self, Placement},
+    document::DocumentExt,
+    fingerprint, imposition, ink, outline, split, text, thumbs,
+    viewer::{self, Zoom},
+};
+
+/// Parse a number of pages per signature, i.e., a positive multiple of four.
+fn parse_signature(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(pages) if pages > 0 && pages % 4 == 0 => Ok(pages),
+        _ => {
+            Err(format!(
+                "invalid signature: {s:?}, expected a multiple of 4"
+            ))
+        },
+    }
+}
+
+/// Autorotate command.
+#[derive(Args, Clone, Debug)]
+struct Autorotate {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Fraction of the characters of a page that must share an orientation
+    /// for the page to be rotated.
+    #[clap(long, default_value_t = 0.6, value_parser = parse_fraction)]
+    min_confidence: f32,
+    /// Only report pages to rotate, without writing any file.
+    #[clap(long)]
+    dry_run: bool,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "rotated.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Autorotate {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let mut rotated = 0;
+
+        for (page, page_id) in document.get_pages() {
+            let _span = error_span!("page", number = page).entered();
+            let spans = ctx
+                .cache
+                .get_or_compute(&self.file, &format!("text-{page}"), || {
+                    text::page_spans(&document, page_id)
+                });
+            let spans = match spans {
+                Ok(spans) => spans,
+                Err(e) => {
+                    warn!("Skipping page {page}, whose content cannot be read: {e}");
+                    continue;
+                },
+            };
+            let Some((rotation, confidence)) = text::dominant_orientation(&spans) else {
+                debug!("Skipping page {page} without text");
+                continue;
+            };
+            let current = document.page_rotation(page_id);
+            if rotation == current {
+                continue;
+            }
+            if confidence < self.min_confidence {
+                warn!(
+                    "Skipping page {page}, as only {:.0}% of its text is rotated by {rotation} \
+                     degrees",
+                    100.0 * confidence
+                );
+                continue;
+            }
+            if !ctx.quiet() {
+                writeln!(
+                    ctx.stdout,
+                    "Page {page} is rotated by {rotation} degrees ({:.0}% of its text), instead \
+                     of {current}.",
+                    100.0 * confidence
+                )?;
+            }
+            document
+                .get_dictionary_mut(page_id)?
+                .set("Rotate", rotation);
+            rotated += 1;
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully rotated {rotated} pages of {:?} to {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Booklet command.
+#[derive(Args, Clone, Debug)]
+struct Booklet {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Number of pages per signature, i.e., per group of sheets folded
+    /// together, as a multiple of 4. Defaults to all pages.
+    #[clap(short, long, value_parser = parse_signature)]
+    signature: Option<u32>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "booklet.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Booklet {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        if document.get_pages().is_empty() {
+            bail!("Document {:?} has no pages.", self.file);
+        }
+        let sides = imposition::make_booklet(&mut document, self.signature)?;
+
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully wrote booklet of {} sheets to {:?}.",
+                sides / 2,
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Dedupe command.
+#[derive(Args, Clone, Debug)]
+struct Dedupe {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Only report duplicated pages, without writing any file.
+    #[clap(long)]
+    dry_run: bool,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "deduped.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Dedupe {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let mut first_pages = HashMap::new();
+        let mut duplicates = Vec::new();
+
+        let fingerprints =
+            ctx.cache
+                .get_or_compute(&self.file, "fingerprints", || -> Result<Vec<u128>> {
+                    Ok(fingerprint::page_fingerprints(&document))
+                })?;
+
+        for (page, fingerprint) in (1..).zip(fingerprints) {
+            match first_pages.entry(fingerprint) {
+                Entry::Occupied(first) => {
+                    if !ctx.quiet() {
+                        writeln!(ctx.stdout, "Page {page} duplicates page {}.", first.get())?;
+                    }
+                    duplicates.push(page);
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert(page);
+                },
+            }
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+        split::remove(&mut document, &duplicates);
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully removed {} duplicated pages of {:?} to {:?}.",
+                duplicates.len(),
+                self.file,
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Output format of page fingerprints.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum FingerprintFormat {
+    /// Table, with one row per page.
+    #[default]
+    Text,
+    /// JSON document.
+    Json,
+}
+
+/// Fingerprint of a page, written as JSON.
+#[derive(Serialize)]
+struct PageFingerprint {
+    page: u32,
+    fingerprint: String,
+    /// First page with the same fingerprint, if any.
+    duplicates: Option<u32>,
+}
+
+/// Fingerprints of the pages of a file, written as JSON.
+#[derive(Serialize)]
+struct FingerprintReport<'a> {
+    file: &'a PathBuf,
+    pages: &'a [PageFingerprint],
+}
+
+/// Fingerprint command.
+#[derive(Args, Clone, Debug)]
+struct Fingerprint {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t)]
+    format: FingerprintFormat,
+}
+
+impl Execute for Fingerprint {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let fingerprints =
+            ctx.cache
+                .get_or_compute(&self.file, "fingerprints", || -> Result<Vec<u128>> {
+                    let document = ctx.load_document_without_images(&self.file)?;
+                    Ok(fingerprint::page_fingerprints(&document))
+                })?;
+
+        let mut first_pages = HashMap::new();
+        let pages: Vec<PageFingerprint> = (1..)
+            .zip(fingerprints)
+            .map(|(page, fingerprint)| {
+                let first = *first_pages.entry(fingerprint).or_insert(page);
+                PageFingerprint {
+                    page,
+                    fingerprint: fingerprint::to_hex(fingerprint),
+                    duplicates: (first != page).then_some(first),
+                }
+            })
+            .collect();
+
+        match self.format {
+            FingerprintFormat::Text => {
+                let mut builder = Builder::default();
+                builder.set_header(["Page no.", "Fingerprint", "Duplicates"]);
+                for page in &pages {
+                    builder.push_record([
+                        page.page.to_string(),
+                        page.fingerprint.clone(),
+                        page.duplicates
+                            .map_or_else(String::new, |first| format!("page {first}")),
+                    ]);
+                }
+                let mut table = builder.build();
+                table
+                    .with(Panel::header(format!(
+                        "Page fingerprints of: {}",
+                        self.file.display()
+                    )))
+                    .with(Style::modern());
+                if ctx.color.enabled() {
+                    table.with(BorderColor::filled(Color::FG_GREEN));
+                }
+                writeln!(ctx.stdout, "{table}")?;
+            },
+            FingerprintFormat::Json => {
+                let report = FingerprintReport {
+                    file: &self.file,
+                    pages: &pages,
+                };
+                serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+                writeln!(ctx.stdout)?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Parse a maximum skew angle, in degrees.
+#[cfg(feature = "images")]
+fn parse_max_angle(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(angle) if angle > 0.0 && angle <= 45.0 => Ok(angle),
+        _ => {
+            Err(format!(
+                "invalid angle: {s:?}, expected a number of degrees between 0 and 45"
+            ))
+        },
+    }
+}
+
+/// Deskew command.
+#[cfg(feature = "images")]
+#[derive(Args, Clone, Debug)]
+struct Deskew {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Maximum skew angle that is detected, in degrees.
+    #[clap(long, default_value_t = 5.0, value_parser = parse_max_angle)]
+    max_angle: f32,
+    /// Only report skewed pages, without writing any file.
+    #[clap(long)]
+    dry_run: bool,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "deskewed.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+#[cfg(feature = "images")]
+impl Execute for Deskew {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let mut deskewed = 0;
+
+        for (page, page_id) in document.get_pages() {
+            let _span = error_span!("page", number = page).entered();
+            let (angle, center) = match deskew::page_skew(&document, page_id, self.max_angle) {
+                Ok(Some(skew)) => skew,
+                Ok(None) => {
+                    debug!("Skipping page {page}, which is not a decodable image-only page");
+                    continue;
+                },
+                Err(e) => {
+                    warn!("Skipping page {page}, whose content cannot be read: {e}");
+                    continue;
+                },
+            };
+            if angle.abs() < deskew::ANGLE_STEP {
+                continue;
+            }
+            if !ctx.quiet() {
+                writeln!(ctx.stdout, "Page {page} is skewed by {angle:.1} degrees.")?;
+            }
+            deskew::rotate_content(&mut document, page_id, -angle, center)?;
+            deskewed += 1;
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully deskewed {deskewed} pages of {:?} to {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Interleave command.
+#[derive(Args, Clone, Debug)]
+struct Interleave {
+    /// PDF filepath of the first pages, e.g., the fronts of the sheets.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    first: PathBuf,
+    /// PDF filepath of the second pages, e.g., the backs of the sheets.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    second: PathBuf,
+    /// Take the second pages in reverse order, as scanners give the backs of
+    /// a stack turned over, from the last sheet.
+    #[clap(long)]
+    reverse_second: bool,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "interleaved.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Interleave {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let sources = vec![
+            ctx.load_document(&self.first)?,
+            ctx.load_document(&self.second)?,
+        ];
+        let mut sequences: Vec<Vec<Placement>> = sources
+            .iter()
+            .enumerate()
+            .map(|(source, document)| {
+                document
+                    .get_pages()
+                    .into_keys()
+                    .map(|page| {
+                        Placement {
+                            source,
+                            page,
+                            rotation: 0,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        if sequences[0].len() != sequences[1].len() {
+            warn!(
+                "{:?} has {} pages but {:?} has {}, remaining pages are appended.",
+                self.first,
+                sequences[0].len(),
+                self.second,
+                sequences[1].len()
+            );
+        }
+        if self.reverse_second {
+            sequences[1].reverse();
+        }
+        let section = assemble::Section {
+            bookmark: None,
+            placements: assemble::interleave(sequences),
+        };
+        let mut document = assemble::assemble(sources, &[section]);
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully interleaved {} pages of {:?} and {:?} to {:?}.",
+                document.get_pages().len(),
+                self.first,
+                self.second,
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Open-at command.
+#[derive(Args, Clone, Debug)]
+struct OpenAt {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Number of the page shown when the document is opened.
+    #[clap(short, long)]
+    page: u32,
+    /// Zoom of the page, i.e., `fit`, `fit-width`, `fit-height` or a
+    /// percentage, e.g., `150%`.
+    #[clap(short, long, default_value = "fit")]
+    zoom: Zoom,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "patched.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for OpenAt {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let Some(&page_id) = document.get_pages().get(&self.page) else {
+            bail!("Document {:?} has no page {}.", self.file, self.page);
+        };
+
+        viewer::set_open_action(&mut document, page_id, self.zoom)?;
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully set {:?} to open at page {} ({}).",
+                self.dest, self.page, self.zoom
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a fraction of a page, between 0 and 1.
+fn parse_fraction(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok(fraction),
+        _ => {
+            Err(format!(
+                "invalid fraction: {s:?}, expected a number between 0 and 1"
+            ))
+        },
+    }
+}
+
+/// Remove-blank command.
+#[derive(Args, Clone, Debug)]
+struct RemoveBlank {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Fraction of a page that must be blank for the page to be removed.
+    #[clap(long, default_value_t = 0.995, value_parser = parse_fraction)]
+    threshold: f32,
+    /// Only report blank pages, without writing any file.
+    #[clap(long)]
+    dry_run: bool,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "cleaned.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for RemoveBlank {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let pages = ink::page_colors(&document);
+        let blank: Vec<u32> = pages
+            .iter()
+            .filter(|page| page.blankness() >= self.threshold)
+            .map(|page| page.page)
+            .collect();
+
+        if !ctx.quiet() {
+            for page in pages.iter().filter(|page| blank.contains(&page.page)) {
+                writeln!(
+                    ctx.stdout,
+                    "Page {} is blank ({:.1}% blank).",
+                    page.page,
+                    100.0 * page.blankness()
+                )?;
+            }
+        }
+        if blank.len() == pages.len() {
+            bail!(
+                "All pages of {:?} are blank, refusing to remove them all.",
+                self.file
+            );
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+        split::remove(&mut document, &blank);
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully removed {} blank pages of {:?} to {:?}.",
+                blank.len(),
+                self.file,
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Split command.
+#[derive(Args, Clone, Debug)]
+#[clap(group(ArgGroup::new("mode").required(true)))]
+struct Split {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Start a new part at each bookmark of the given level.
+    #[clap(long, group = "mode")]
+    by_outline: bool,
+    /// Level of the bookmarks that start parts, top-level bookmarks being at
+    /// level 1.
+    #[clap(long, default_value_t = 1, requires = "by_outline")]
+    level: usize,
+    /// Start a new part every given number of pages.
+    #[clap(long, group = "mode", value_name = "PAGES", value_parser = clap::value_parser!(u32).range(1..))]
+    chunk: Option<u32>,
+    /// Start a new part before files exceed the given size, e.g., `9MB` or
+    /// `500KiB`.
+    ///
+    /// Sizes are estimated from the objects each page uses, counting shared
+    /// resources, e.g., fonts, once per part, so parts may be slightly
+    /// larger. A page larger than the size makes a part of its own.
+    #[clap(long, group = "mode", value_parser = parse_size)]
+    max_size: Option<u64>,
+    /// Template of the names of parts, with `{stem}` (name of the input
+    /// file), `{cmd}`, `{date}`, `{index}`, `{title}` (of the bookmark),
+    /// `{start}` and `{end}` (page numbers) placeholders. Numbers can be
+    /// zero-padded, e.g., `{index:02}`.
+    #[clap(short, long, default_value = "{stem}_{index:02}.pdf")]
+    name_template: String,
+    /// Directory where parts are written.
+    #[clap(short, long, default_value = ".", value_hint = ValueHint::DirPath)]
+    dest_dir: PathBuf,
+}
+
+impl Execute for Split {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let page_count = document.get_pages().len() as u32;
+        let parts = if let Some(chunk) = self.chunk {
+            split::chunk_parts(page_count, chunk)
+        } else if let Some(max_size) = self.max_size {
+            split::size_parts(&document, max_size)
+        } else {
+            split::outline_parts(&outline::read(&document), self.level, page_count)
+        };
+
+        if parts.is_empty() && !self.by_outline {
+            bail!("Document {:?} has no pages.", self.file);
+        } else if parts.is_empty() {
+            bail!(
+                "Document {:?} has no bookmarks of level {} pointing to its pages.",
+                self.file,
+                self.level
+            );
+        }
+        let naming = Naming::new(ctx, &self.file, "split");
+        fs::create_dir_all(&self.dest_dir)
+            .with_context(|| format!("Failed to create output directory: {:?}", self.dest_dir))?;
+
+        for (index, (title, pages)) in (1..).zip(&parts) {
+            let name = naming.render(
+                &self.name_template,
+                &[
+                    ("index", Placeholder::Number(index)),
+                    ("title", Placeholder::Text(title)),
+                    ("start", Placeholder::Number(*pages.start())),
+                    ("end", Placeholder::Number(*pages.end())),
+                ],
+            )?;
+            let path = self.dest_dir.join(name);
+            if !output::resolve_destination(ctx, &path)? {
+                continue;
+            }
+            let mut part = split::extract(&document, pages.clone());
+
+            ctx.save_document(&mut part, &path)?;
+            if let Some(max_size) = self.max_size {
+                let size = fs::metadata(&path).map_or(0, |metadata| metadata.len());
+                if size > max_size {
+                    warn!("Part {path:?} is {size} bytes, more than the maximum size.");
+                }
+            }
+            if ctx.quiet() {
+                continue;
+            }
+            if title.is_empty() {
+                writeln!(
+                    ctx.stdout,
+                    "Wrote pages {}-{} to {path:?}.",
+                    pages.start(),
+                    pages.end()
+                )?;
+            } else {
+                writeln!(
+                    ctx.stdout,
+                    "Wrote pages {}-{} ({title:?}) to {path:?}.",
+                    pages.start(),
+                    pages.end()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Thumbs command.
+#[derive(Args, Clone, Debug)]
+#[clap(group(ArgGroup::new("action").required(true).args(["generate", "remove"])))]
+struct Thumbs {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Embed a thumbnail in each scanned page, replacing existing ones.
+    #[clap(long)]
+    generate: bool,
+    /// Remove the thumbnails of all pages.
+    #[clap(long)]
+    remove: bool,
+    /// Resolution of generated thumbnails, in dots per inch.
+    #[clap(long, default_value_t = 36, value_parser = clap::value_parser!(u32).range(1..=300))]
+    dpi: u32,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "thumbs.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Thumbs {
+    /// Embed thumbnails in scanned pages, and return their number.
+    #[cfg(feature = "images")]
+    fn generate(&self, cache: &Cache, document: &mut lopdf::Document) -> Result<usize> {
+        let mut generated = 0;
+
+        for (page, page_id) in document.get_pages() {
+            let _span = error_span!("page", number = page).entered();
+            let key = format!("thumbnail-{page}-{}", self.dpi);
+            let thumbnail = cache.get_or_compute(&self.file, &key, || {
+                thumbs::scan_thumbnail(document, page_id, self.dpi as f32)
+            });
+            match thumbnail {
+                Ok(Some(thumbnail)) => {
+                    thumbs::set_thumbnail(document, page_id, thumbnail)?;
+                    generated += 1;
+                },
+                Ok(None) => {
+                    warn!("Skipping page {page}, which is not a decodable image-only page");
+                },
+                Err(e) => warn!("Skipping page {page}, whose content cannot be read: {e}"),
+            }
+        }
+        // Drop replaced thumbnails.
+        document.prune_objects();
+        Ok(generated)
+    }
+
+    #[cfg(not(feature = "images"))]
+    fn generate(&self, _cache: &Cache, _document: &mut lopdf::Document) -> Result<usize> {
+        bail!("Generating thumbnails requires the `images` feature.");
+    }
+}
+
+impl Execute for Thumbs {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+
+        let message = if self.generate {
+            let generated = self.generate(&ctx.cache, &mut document)?;
+            format!("generated {generated} thumbnails")
+        } else {
+            let removed = thumbs::remove(&mut document);
+            format!("removed {removed} thumbnails")
+        };
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully {message} of {:?} to {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for pages.
+#[derive(Debug, Subcommand)]
+enum PagesSubcommand {
+    /// Rotate pages so that their text reads upright.
+    ///
+    /// The orientation of pages is that of most of their characters,
+    /// including invisible OCR text. Pages without text, e.g., scans that
+    /// were not OCRed, are left as is.
+    Autorotate(Autorotate),
+    /// Arrange pages two by two on sheets, to print a booklet.
+    ///
+    /// Pages are reordered so that printing the result in duplex, flipping
+    /// on the short edge, then folding the sheets in half, gives pages in
+    /// order. Blank pages are added as needed. Annotations and bookmarks
+    /// are dropped.
+    Booklet(Booklet),
+    /// Remove duplicated pages, e.g., from collated scan batches, keeping
+    /// the first occurrence of each page.
+    ///
+    /// Pages are duplicates if their content, resources and geometry are
+    /// the same, whatever objects they are made of. Annotations are
+    /// ignored, and pages are not rendered, so two scans of the same sheet
+    /// are not duplicates.
+    Dedupe(Dedupe),
+    /// Straighten skewed scans, e.g., from sloppy sheet feeders.
+    ///
+    /// The skew of a page is estimated from the lines of dark pixels of its
+    /// image, and its content is rotated back within the page. Only pages
+    /// made of a single upright image, without text, are deskewed, and JPEG
+    /// images cannot be decoded. Unlike `autorotate`, which fixes pages
+    /// turned by multiples of 90 degrees, this fixes small angles.
+    #[cfg(feature = "images")]
+    Deskew(Deskew),
+    /// Print a fingerprint of each page, e.g., to find duplicated pages, or
+    /// pages that changed between revisions, with external tools.
+    ///
+    /// Fingerprints are hashes of the content, resources and geometry of
+    /// pages, whatever objects they are made of, so they are stable across
+    /// rewrites of a file, and across versions of rpdf. Annotations are
+    /// ignored.
+    Fingerprint(Fingerprint),
+    /// Interleave the pages of two files, e.g., the fronts and backs of
+    /// sheets scanned in two passes by a single-sided scanner.
+    ///
+    /// Pages are taken in turn from each file, and the remaining pages of the
+    /// longer file are appended. Outlines, forms and article threads are not
+    /// kept.
+    Interleave(Interleave),
+    /// Set the page, and its zoom, that the document opens at.
+    ///
+    /// This writes an explicit destination as the document's `/OpenAction`,
+    /// replacing any previous open action.
+    OpenAt(OpenAt),
+    /// Remove blank or nearly blank pages, e.g., double-fed pages of scans.
+    ///
+    /// Blankness is estimated from the ink coverage of the content of pages,
+    /// without rendering them. Images that cannot be decoded, e.g., JPEG
+    /// scans, count as mid-tones, so scanned pages are never considered
+    /// blank. Annotations are ignored.
+    RemoveBlank(RemoveBlank),
+    /// Split a document into several files.
+    ///
+    /// With `--by-outline`, each part starts at a bookmark, e.g., one file
+    /// per chapter, and pages before the first bookmark belong to the first
+    /// part. With `--chunk` or `--max-size`, parts have a maximum number of
+    /// pages or a maximum file size, e.g., to fit email attachment limits.
+    /// Bookmarks are not kept in parts.
+    Split(Split),
+    /// Generate or remove page thumbnails, i.e., small images of pages that
+    /// some viewers show in their page panel.
+    ///
+    /// Pages are not rendered, so thumbnails are only generated for pages
+    /// made of a single upright image, without text, e.g., scans, by
+    /// downsampling their image, and JPEG images cannot be decoded. Removing
+    /// thumbnails reduces the file size.
+    Thumbs(Thumbs),
+}
+
+/// Work with the pages of PDF files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct PagesCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: PagesSubcommand,
+}
+
+impl Execute for PagesCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            PagesSubcommand::Autorotate(autorotate) => autorotate.execute(ctx),
+            PagesSubcommand::Booklet(booklet) => booklet.execute(ctx),
+            PagesSubcommand::Dedupe(dedupe) => dedupe.execute(ctx),
+            #[cfg(feature = "images")]
+            PagesSubcommand::Deskew(deskew) => deskew.execute(ctx),
+            PagesSubcommand::Fingerprint(fingerprint) => fingerprint.execute(ctx),
+            PagesSubcommand::Interleave(interleave) => interleave.execute(ctx),
+            PagesSubcommand::OpenAt(open_at) => open_at.execute(ctx),
+            PagesSubcommand::RemoveBlank(remove_blank) => remove_blank.execute(ctx),
+            PagesSubcommand::Split(split) => split.execute(ctx),
+            PagesSubcommand::Thumbs(thumbs) => thumbs.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! External subcommands, i.e., `rpdf-foo` executables on `PATH` that run as
+//! `rpdf foo`.
+//!
+//! Global options are passed to plugins as environment variables:
+//!
+//! - `RPDF_PLUGIN_PROTOCOL`: version of this protocol, currently `1`;
+//! - `RPDF`: path of the `rpdf` executable, e.g., to call other commands;
+//! - `RPDF_COLOR`: `always` or `never`, resolved from `--color`;
+//! - `RPDF_VERBOSITY`: `off`, `error`, `warn`, `info`, `debug` or `trace`;
+//! - `RPDF_NO_INPUT`: `1` if `--no-input` is set;
+//! - `RPDF_PASSWORD`: password given with `--password`, if any.
+
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result, bail};
+use tracing::debug;
+
+use super::context::ExecutionContext;
+
+/// Version of the protocol between rpdf and its plugins, bumped when the
+/// environment variables passed to plugins change incompatibly.
+const PROTOCOL_VERSION: &str = "1";
+
+/// Prefix of the executables of plugins.
+const PREFIX: &str = "rpdf-";
+
+/// Return whether a path is an executable file.
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        path.metadata()
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Return the executable of a plugin, searched for in `PATH`.
+fn find(name: &OsStr) -> Option<PathBuf> {
+    let mut file_name = OsString::from(PREFIX);
+    file_name.push(name);
+    file_name.push(env::consts::EXE_SUFFIX);
+
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&file_name))
+        .find(|path| is_executable(path))
+}
+
+/// Return the names and executables of the plugins found in `PATH`, where
+/// the first executable of each name wins, as when running them.
+pub fn installed() -> Vec<(String, PathBuf)> {
+    let mut plugins: Vec<(String, PathBuf)> = vec![];
+    let Some(path) = env::var_os("PATH") else {
+        return plugins;
+    };
+
+    for dir in env::split_paths(&path) {
+        let Ok(entries) = dir.read_dir() else {
+            continue;
+        };
+        let mut found: Vec<(String, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let name = file_name
+                    .strip_prefix(PREFIX)?
+                    .strip_suffix(env::consts::EXE_SUFFIX)?;
+                Some((name.to_string(), entry.path()))
+            })
+            .filter(|(name, path)| {
+                !name.is_empty()
+                    && is_executable(path)
+                    && !plugins.iter().any(|(other, _)| other == name)
+            })
+            .collect();
+        found.sort();
+        plugins.extend(found);
+    }
+    plugins
+}
+
+/// Run a plugin, with the arguments that follow its name, and fail if it
+/// fails.
+pub fn run<W>(args: &[OsString], ctx: &ExecutionContext<W>) -> Result<()> {
+    let Some((name, args)) = args.split_first() else {
+        bail!("A command is needed.");
+    };
+    let Some(path) = find(name) else {
+        bail!(
+            "Unknown command: {name:?}, and no `{PREFIX}{}` executable was found in PATH.",
+            name.to_string_lossy()
+        );
+    };
+    debug!("Running plugin {path:?}");
+
+    let mut command = Command::new(&path);
+    command
+        .args(args)
+        .env("RPDF_PLUGIN_PROTOCOL", PROTOCOL_VERSION)
+        .env(
+            "RPDF_COLOR",
+            if ctx.color.enabled() {
+                "always"
+            } else {
+                "never"
+            },
+        )
+        .env(
+            "RPDF_VERBOSITY",
+            ctx.verbosity
+                .log_level_filter()
+                .as_str()
+                .to_ascii_lowercase(),
+        );
+    if let Ok(exe) = env::current_exe() {
+        command.env("RPDF", exe);
+    }
+    if ctx.no_input {
+        command.env("RPDF_NO_INPUT", "1");
+    }
+    match &ctx.password {
+        Some(password) => command.env("RPDF_PASSWORD", password),
+        None => command.env_remove("RPDF_PASSWORD"),
+    };
+
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run plugin from: {path:?}"))?;
+    if !status.success() {
+        bail!("Plugin {path:?} failed with {status}.");
+    }
+    Ok(())
+}
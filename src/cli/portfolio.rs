@@ -0,0 +1,266 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use tabled::{
+    builder::Builder,
+    settings::{Color, Panel, Style, style::BorderColor},
+};
+use termcolor::WriteColor;
+use tracing::warn;
+
+use super::{complete::pdf_files, context::ExecutionContext, output, traits::Execute};
+use crate::pdf::{
+    attachments, pdfstring,
+    portfolio::{self, Field},
+    reproducible,
+};
+
+/// Columns of the list of files of a portfolio.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SchemaField {
+    /// Name of the file.
+    Name,
+    /// Modification date of the file.
+    Date,
+    /// Size of the file.
+    Size,
+}
+
+impl From<SchemaField> for Field {
+    fn from(field: SchemaField) -> Self {
+        match field {
+            SchemaField::Name => Self::Name,
+            SchemaField::Date => Self::Date,
+            SchemaField::Size => Self::Size,
+        }
+    }
+}
+
+/// Create command.
+#[derive(Args, Clone, Debug)]
+struct Create {
+    /// Files to embed in the portfolio.
+    #[clap(long, required = true, num_args = 1.., value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+    /// Columns shown by viewers, in order. Files are sorted by the first
+    /// column.
+    #[clap(long, value_delimiter = ',', default_value = "name,date,size")]
+    schema: Vec<SchemaField>,
+    /// PDF shown by viewers that do not support portfolios, instead of a
+    /// generated page.
+    #[clap(long, add = ArgValueCompleter::new(pdf_files()))]
+    cover: Option<PathBuf>,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "portfolio.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for Create {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut names = BTreeSet::new();
+        let mut files = Vec::with_capacity(self.files.len());
+
+        for path in &self.files {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .with_context(|| format!("Path {path:?} is not a file."))?;
+            if !names.insert(name.clone()) {
+                bail!("Two files are named {name:?}, but names must be unique in a portfolio.");
+            }
+            let data =
+                fs::read(path).with_context(|| format!("Failed to read file from: {path:?}"))?;
+            // Reproducible output must not depend on file dates.
+            let date = match ctx.save_options.source_date {
+                Some(_) => None,
+                None => {
+                    fs::metadata(path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                        .map(|duration| reproducible::format_date(duration.as_secs()))
+                },
+            };
+            files.push((name, data, date));
+        }
+
+        let mut document = match &self.cover {
+            Some(cover) => ctx.load_document(cover)?,
+            None => portfolio::cover(files.len()),
+        };
+        let fields: Vec<Field> = self.schema.iter().copied().map(Field::from).collect();
+        portfolio::create(&mut document, files, &fields)
+            .with_context(|| format!("Failed to create portfolio: {:?}", self.dest))?;
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully created portfolio {:?} with {} files.",
+                self.dest,
+                self.files.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// List command.
+#[derive(Args, Clone, Debug)]
+struct List {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+impl Execute for List {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let attachments = attachments::attachments(&document);
+
+        if !portfolio::is_portfolio(&document) {
+            warn!(
+                "File {:?} is not a portfolio, listing its attachments.",
+                self.file
+            );
+        }
+        let mut builder = Builder::default();
+        builder.set_header(["Name", "Size", "Modified", "Type", "Description"]);
+        for attachment in &attachments {
+            builder.push_record([
+                attachment.name.clone(),
+                attachment.data.len().to_string(),
+                attachment
+                    .date
+                    .as_deref()
+                    .map(pdfstring::display_date)
+                    .unwrap_or_default(),
+                attachment.mime_type.clone().unwrap_or_default(),
+                attachment.description.clone().unwrap_or_default(),
+            ]);
+        }
+        let mut table = builder.build();
+        table
+            .with(Panel::header(format!(
+                "Files of portfolio: {}",
+                self.file.display()
+            )))
+            .with(Style::modern());
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+        writeln!(ctx.stdout, "{table}")?;
+        Ok(())
+    }
+}
+
+/// Extract command.
+#[derive(Args, Clone, Debug)]
+struct Extract {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Names of the files to extract, defaults to all files.
+    #[clap(long, num_args = 1..)]
+    names: Vec<String>,
+    /// Directory where files are written.
+    #[clap(short, long, default_value = ".", value_hint = ValueHint::DirPath)]
+    dest_dir: PathBuf,
+}
+
+impl Execute for Extract {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document(&self.file)?;
+        let attachments: Vec<_> = attachments::attachments(&document)
+            .into_iter()
+            .filter(|attachment| self.names.is_empty() || self.names.contains(&attachment.name))
+            .collect();
+
+        for name in &self.names {
+            if !attachments
+                .iter()
+                .any(|attachment| attachment.name == *name)
+            {
+                bail!("File {:?} has no embedded file named {name:?}.", self.file);
+            }
+        }
+        fs::create_dir_all(&self.dest_dir)
+            .with_context(|| format!("Failed to create output directory: {:?}", self.dest_dir))?;
+
+        for attachment in attachments {
+            // Names come from the document, and must not write outside of
+            // the output directory.
+            let Some(name) = Path::new(&attachment.name).file_name() else {
+                warn!(
+                    "Skipping embedded file with invalid name {:?}",
+                    attachment.name
+                );
+                continue;
+            };
+            let path = self.dest_dir.join(name);
+            if !output::resolve_destination(ctx, &path)? {
+                continue;
+            }
+
+            fs::write(&path, &attachment.data)
+                .with_context(|| format!("Failed to write file to: {path:?}"))?;
+            ctx.summary.file_written(&path);
+            if !ctx.quiet() {
+                writeln!(ctx.stdout, "Extracted {:?} to {path:?}.", attachment.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for portfolios.
+#[derive(Debug, Subcommand)]
+enum PortfolioSubcommand {
+    /// Create a portfolio, i.e., a PDF collection of files of any type.
+    ///
+    /// Viewers that support portfolios list the embedded files with the
+    /// columns of the schema, and others show the cover page.
+    Create(Create),
+    /// Extract the files of a portfolio, or of any PDF with attachments.
+    Extract(Extract),
+    /// List the files of a portfolio, or of any PDF with attachments.
+    List(List),
+}
+
+/// Create portfolios, and list or extract their files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct PortfolioCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: PortfolioSubcommand,
+}
+
+impl Execute for PortfolioCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            PortfolioSubcommand::Create(create) => create.execute(ctx),
+            PortfolioSubcommand::Extract(extract) => extract.execute(ctx),
+            PortfolioSubcommand::List(list) => list.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use clap::{Parser, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::{Document, Object, ObjectId};
+use rhai::{AST, Array, CallFnOptions, Dynamic, Engine, Map, Scope};
+use termcolor::WriteColor;
+use tracing::debug;
+
+use super::{complete::pdf_files, context::ExecutionContext, output, traits::Execute};
+use crate::pdf::{
+    annotations::{self, AnnotationBuilder},
+    content::number,
+    document::DocumentExt,
+    pdfstring,
+};
+
+/// Maximum number of operations a script may run, so that endless loops
+/// fail instead of hanging.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Process annotations with a Rhai script, e.g., to implement review
+/// policies that no option covers.
+///
+/// Scripts define any of the following functions:
+///
+/// - `annotation(page)`, called for each annotation, bound to `this`. Its
+///   `page`, `subtype` and `date` can be read, and its `author`, `contents`,
+///   `rect` and `color`, in RGB, can also be changed. Returning `false` deletes
+///   the annotation.
+/// - `page(page)`, called for each page, which returns an array of annotations
+///   to add, e.g., `[#{subtype: "Text", rect: [72, 700, 96, 724], contents:
+///   "Checked"}]`.
+///
+/// Pages have a `number`, a `width`, a `height` and a `rotation`. Scripts run
+/// sandboxed, i.e., they cannot read files nor import modules.
+#[derive(Debug, Parser)]
+pub struct ProcessCommand {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Rhai script that processes annotations.
+    #[clap(short, long, value_hint = ValueHint::FilePath)]
+    script: PathBuf,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "processed.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+/// Return a sandboxed script engine.
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+        .set_max_operations(MAX_OPERATIONS)
+        // Debug builds of Rhai default to lower depths than release ones.
+        .set_max_expr_depths(64, 32)
+        .disable_symbol("eval");
+    engine
+}
+
+/// Return a number of a script, which may be an integer or a float.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn script_number(value: &Dynamic) -> Option<f32> {
+    value
+        .as_float()
+        .map(|value| value as f32)
+        .or_else(|_| value.as_int().map(|value| value as f32))
+        .ok()
+}
+
+/// Return an array of `N` numbers of a script, e.g., a rectangle.
+fn script_numbers<const N: usize>(value: &Dynamic) -> Option<[f32; N]> {
+    let values: Vec<f32> = value
+        .read_lock::<Array>()?
+        .iter()
+        .map(script_number)
+        .collect::<Option<_>>()?;
+    values.try_into().ok()
+}
+
+/// Return the numbers of a PDF array, as a script array.
+fn pdf_numbers(values: &[Object]) -> Dynamic {
+    values
+        .iter()
+        .filter_map(number)
+        .map(|value| Dynamic::from_float(value.into()))
+        .collect::<Array>()
+        .into()
+}
+
+/// Return an optional string, as a script value.
+fn optional_string(value: Option<String>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Dynamic::from)
+}
+
+/// Return the page passed to scripts.
+fn page_map(document: &Document, page_number: u32, page_id: ObjectId) -> Map {
+    let [x0, y0, x1, y1] = document.page_crop_box(page_id).unwrap_or_default();
+    let mut page = Map::new();
+    page.insert("number".into(), i64::from(page_number).into());
+    page.insert("width".into(), Dynamic::from_float((x1 - x0).into()));
+    page.insert("height".into(), Dynamic::from_float((y1 - y0).into()));
+    page.insert("rotation".into(), document.page_rotation(page_id).into());
+    page
+}
+
+/// Return an annotation as a script map, if it is a dictionary.
+fn annotation_map(document: &Document, page_number: u32, id: ObjectId) -> Option<Map> {
+    let annotation = annotations::read_annotation(document, page_number, id)?;
+    let color = document
+        .get_dictionary(id)
+        .and_then(|dict| dict.get(b"C"))
+        .and_then(Object::as_array)
+        .ok()
+        .filter(|color| color.len() == 3)
+        .map_or(Dynamic::UNIT, |color| pdf_numbers(color));
+
+    let mut map = Map::new();
+    map.insert("page".into(), i64::from(page_number).into());
+    map.insert("subtype".into(), annotation.subtype.into());
+    map.insert("author".into(), optional_string(annotation.author));
+    map.insert("contents".into(), optional_string(annotation.contents));
+    map.insert("date".into(), optional_string(annotation.date));
+    map.insert(
+        "rect".into(),
+        annotation.rect.map_or(Dynamic::UNIT, |rect| {
+            rect.iter()
+                .map(|&value| Dynamic::from_float(value.into()))
+                .collect::<Array>()
+                .into()
+        }),
+    );
+    map.insert("color".into(), color);
+    Some(map)
+}
+
+/// Return the string of an annotation field, `None` if it is unset, or fail
+/// if it is not a string.
+fn string_field(map: &Map, key: &str, page_number: u32) -> Result<Option<String>> {
+    match map.get(key) {
+        None => Ok(None),
+        Some(value) if value.is_unit() => Ok(None),
+        Some(value) => {
+            value.clone().into_string().map(Some).map_err(|type_name| {
+                anyhow!(
+                    "Invalid `{key}` of annotation on page {page_number}: expected a string, \
+                     found {type_name}"
+                )
+            })
+        },
+    }
+}
+
+/// Return the numbers of an annotation field, `None` if it is unset, or fail
+/// if it does not hold `N` numbers.
+fn numbers_field<const N: usize>(
+    map: &Map,
+    key: &str,
+    page_number: u32,
+) -> Result<Option<[f32; N]>> {
+    match map.get(key) {
+        None => Ok(None),
+        Some(value) if value.is_unit() => Ok(None),
+        Some(value) => {
+            script_numbers(value).map(Some).with_context(|| {
+                format!(
+                    "Invalid `{key}` of annotation on page {page_number}: expected an array of \
+                     {N} numbers"
+                )
+            })
+        },
+    }
+}
+
+/// Write the fields that a script changed back to an annotation, and return
+/// whether any changed.
+fn update_annotation(
+    document: &mut Document,
+    id: ObjectId,
+    before: &Map,
+    after: &Map,
+    page_number: u32,
+) -> Result<bool> {
+    let mut changed = false;
+    let annotation = document.get_dictionary_mut(id)?;
+
+    for (key, entry) in [("author", "T"), ("contents", "Contents")] {
+        let value = string_field(after, key, page_number)?;
+        if value != string_field(before, key, page_number)? {
+            match value {
+                Some(value) => annotation.set(entry, pdfstring::encode_object(&value)),
+                None => {
+                    annotation.remove(entry.as_bytes());
+                },
+            }
+            changed = true;
+        }
+    }
+    let rect = numbers_field::<4>(after, "rect", page_number)?;
+    if rect != numbers_field(before, "rect", page_number)? {
+        let rect = rect.with_context(|| {
+            format!("Invalid `rect` of annotation on page {page_number}: it cannot be removed")
+        })?;
+        annotation.set("Rect", rect.map(Object::Real).to_vec());
+        changed = true;
+    }
+    let color = numbers_field::<3>(after, "color", page_number)?;
+    if color != numbers_field(before, "color", page_number)? {
+        match color {
+            Some(color) => annotation.set("C", color.map(Object::Real).to_vec()),
+            None => {
+                annotation.remove(b"C");
+            },
+        }
+        changed = true;
+    }
+    Ok(changed)
+}
+
+/// Return the builder of an annotation that a script creates.
+fn new_annotation(value: &Dynamic, page_number: u32) -> Result<AnnotationBuilder> {
+    let map = value.read_lock::<Map>().with_context(|| {
+        format!("Invalid annotation created on page {page_number}: expected a map")
+    })?;
+    let subtype = string_field(&map, "subtype", page_number)?.with_context(|| {
+        format!("Invalid annotation created on page {page_number}: `subtype` is missing")
+    })?;
+    let rect = numbers_field(&map, "rect", page_number)?.with_context(|| {
+        format!("Invalid annotation created on page {page_number}: `rect` is missing")
+    })?;
+
+    let mut builder = AnnotationBuilder::new(&subtype, rect);
+    if let Some(contents) = string_field(&map, "contents", page_number)? {
+        builder = builder.contents(&contents);
+    }
+    if let Some(author) = string_field(&map, "author", page_number)? {
+        builder = builder.author(&author);
+    }
+    if let Some(color) = numbers_field(&map, "color", page_number)? {
+        builder = builder.color(color);
+    }
+    Ok(builder)
+}
+
+/// Counts of the changes made by a script.
+#[derive(Default)]
+struct Changes {
+    updated: usize,
+    deleted: usize,
+    created: usize,
+}
+
+impl ProcessCommand {
+    /// Run the functions of a script over the pages and annotations of a
+    /// document.
+    fn run(engine: &Engine, ast: &AST, document: &mut Document) -> Result<Changes> {
+        let has_function = |name: &str| ast.iter_functions().any(|f| f.name == name);
+        let (has_annotation, has_page) = (has_function("annotation"), has_function("page"));
+        let mut scope = Scope::new();
+        let mut changes = Changes::default();
+        let mut delete_ids = vec![];
+
+        for (page_number, page_id) in document.get_pages() {
+            let page = page_map(document, page_number, page_id);
+
+            if has_annotation {
+                for id in annotations::page_annotation_ids(document, page_id) {
+                    let Some(before) = annotation_map(document, page_number, id) else {
+                        continue;
+                    };
+                    let mut this = Dynamic::from_map(before.clone());
+                    let result: Dynamic = engine
+                        .call_fn_with_options(
+                            CallFnOptions::new()
+                                .eval_ast(false)
+                                .bind_this_ptr(&mut this),
+                            &mut scope,
+                            ast,
+                            "annotation",
+                            (page.clone(),),
+                        )
+                        .map_err(|e| {
+                            anyhow!("Failed to process annotation on page {page_number}: {e}")
+                        })?;
+
+                    if result.as_bool() == Ok(false) {
+                        delete_ids.push(id);
+                        continue;
+                    }
+                    let after = this.try_cast::<Map>().with_context(|| {
+                        format!(
+                            "Invalid annotation on page {page_number}: `this` is not a map anymore"
+                        )
+                    })?;
+                    if update_annotation(document, id, &before, &after, page_number)? {
+                        changes.updated += 1;
+                    }
+                }
+            }
+
+            if has_page {
+                let result: Dynamic = engine
+                    .call_fn_with_options(
+                        CallFnOptions::new().eval_ast(false),
+                        &mut scope,
+                        ast,
+                        "page",
+                        (page,),
+                    )
+                    .map_err(|e| anyhow!("Failed to process page {page_number}: {e}"))?;
+                if result.is_unit() {
+                    continue;
+                }
+                let created = result.try_cast::<Array>().with_context(|| {
+                    format!(
+                        "Invalid result of page {page_number}: expected an array of annotations"
+                    )
+                })?;
+                for value in &created {
+                    new_annotation(value, page_number)?.add_to_page(document, page_id)?;
+                    changes.created += 1;
+                }
+            }
+        }
+
+        changes.deleted = delete_ids.len();
+        annotations::delete_annotations(document, &delete_ids);
+        Ok(changes)
+    }
+}
+
+impl Execute for ProcessCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        if !output::resolve_destination(ctx, &self.dest)? {
+            return Ok(());
+        }
+        let source = fs::read_to_string(&self.script)
+            .with_context(|| format!("Failed to read script from: {:?}", self.script))?;
+        let engine = engine();
+        let ast = engine
+            .compile(source)
+            .map_err(|e| anyhow!("Failed to compile script from: {:?}: {e}", self.script))?;
+        let mut document = ctx.load_document(&self.file)?;
+
+        let changes = Self::run(&engine, &ast, &mut document)?;
+        debug!(
+            "Script updated {}, deleted {} and created {} annotations",
+            changes.updated, changes.deleted, changes.created
+        );
+        ctx.summary.annotations_added(changes.created);
+        ctx.summary.annotations_removed(changes.deleted);
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully updated {}, deleted {} and created {} annotations in {:?}.",
+                changes.updated, changes.deleted, changes.created, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
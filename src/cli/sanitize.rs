@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Parser, ValueHint};
+use clap_complete::ArgValueCompleter;
+use termcolor::WriteColor;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::sanitize;
+
+/// Remove active content that could harm readers of a PDF.
+///
+/// JavaScript (including document-level scripts), open actions, additional
+/// actions (`/AA`), Launch, SubmitForm and ImportData actions, and embedded
+/// files are removed. Links and other navigation actions are kept.
+#[derive(Debug, Parser)]
+pub struct SanitizeCommand {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "sanitized.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+impl Execute for SanitizeCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let report = sanitize::sanitize(&mut document);
+
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if ctx.quiet() {
+            return Ok(());
+        }
+        if report.is_empty() {
+            writeln!(ctx.stdout, "No active content was found.")?;
+        }
+        for (threat, count) in &report {
+            writeln!(ctx.stdout, "Removed {count} {}.", threat.description())?;
+        }
+        writeln!(
+            ctx.stdout,
+            "Successfully sanitized {:?} to {:?}.",
+            self.file, self.dest
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+//! HTTP server exposing a few commands, for services that process many
+//! documents.
+
+use std::{future::IntoFuture, net::SocketAddr, path::Path, sync::Arc};
+
+use anyhow::{Context, Result, bail};
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Multipart, State, multipart::MultipartError},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use clap::Parser;
+use lopdf::Document;
+use termcolor::WriteColor;
+use tracing::{debug, info};
+
+use super::{
+    context::{self, ExecutionContext, parse_size},
+    traits::Execute,
+};
+use crate::pdf::{self, SaveOptions, annotations, dedup, merge::AnnotationMerger};
+
+/// Serve an HTTP API to process documents, without spawning a process per
+/// document.
+///
+/// Documents are uploaded as `multipart/form-data`, in `file` fields, and
+/// processed documents are sent back. Endpoints are:
+///
+/// - `POST /annotations/merge`: merge the annotations of several `file`s;
+/// - `POST /annotations/strip`: strip the annotations of a `file`, except
+///   links, or those of `media` annotations if `media` is `true`;
+/// - `POST /annotations/stats`: count the annotations of a `file`, as JSON;
+/// - `POST /optimize`: deduplicate, prune and compress the objects of a `file`.
+///
+/// `exclude` fields replace the subtypes that merge and strip skip, and a
+/// `password` field opens encrypted documents. Errors are sent as plain
+/// text, and files that would exceed `--max-memory` are rejected with a 413
+/// status.
+#[derive(Debug, Parser)]
+pub struct ServeCommand {
+    /// Address to listen on.
+    #[clap(short, long, default_value = "127.0.0.1:8080")]
+    address: SocketAddr,
+    /// Maximum size of a request, e.g., `100MB`.
+    #[clap(long, default_value = "100MB", value_parser = parse_size)]
+    max_upload: u64,
+}
+
+/// State shared by all requests.
+struct ServerState {
+    /// Options used to save processed documents.
+    save_options: SaveOptions,
+    /// Memory budget of each uploaded file, see `--max-memory`.
+    max_memory: Option<u64>,
+}
+
+/// Error sent to clients, as plain text.
+struct ServerError(StatusCode, String);
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        (self.0, self.1).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ServerError {
+    fn from(error: anyhow::Error) -> Self {
+        Self(StatusCode::UNPROCESSABLE_ENTITY, format!("{error:#}"))
+    }
+}
+
+impl From<MultipartError> for ServerError {
+    fn from(error: MultipartError) -> Self {
+        Self(error.status(), error.body_text())
+    }
+}
+
+/// Fields of an uploaded form.
+#[derive(Default)]
+struct Upload {
+    files: Vec<Bytes>,
+    exclude: Vec<String>,
+    media: bool,
+    password: Option<String>,
+}
+
+impl Upload {
+    /// Read the fields of a form, rejecting unknown ones, and files that
+    /// would exceed the memory budget.
+    async fn read(mut multipart: Multipart, max_memory: Option<u64>) -> Result<Self, ServerError> {
+        let mut upload = Self::default();
+        while let Some(field) = multipart.next_field().await? {
+            match field.name() {
+                Some("file") => {
+                    let name = field.file_name().unwrap_or("file").to_string();
+                    let file = field.bytes().await?;
+                    context::check_memory_size(max_memory, Path::new(&name), file.len() as u64)
+                        .map_err(|error| {
+                            ServerError(StatusCode::PAYLOAD_TOO_LARGE, format!("{error:#}"))
+                        })?;
+                    upload.files.push(file);
+                },
+                Some("exclude") => upload.exclude.push(field.text().await?),
+                Some("media") => upload.media = field.text().await? == "true",
+                Some("password") => upload.password = Some(field.text().await?),
+                name => {
+                    return Err(ServerError(
+                        StatusCode::BAD_REQUEST,
+                        format!("Unexpected field: {:?}.", name.unwrap_or_default()),
+                    ));
+                },
+            }
+        }
+        Ok(upload)
+    }
+
+    /// Return the subtypes to skip, or the given default ones.
+    fn exclude_or(&self, default: &str) -> Vec<String> {
+        if self.exclude.is_empty() {
+            vec![default.to_string()]
+        } else {
+            self.exclude.clone()
+        }
+    }
+
+    /// Load the only uploaded document.
+    fn load_one(&self) -> Result<Document> {
+        let [file] = self.files.as_slice() else {
+            bail!(
+                "Exactly one PDF file is needed, found {}.",
+                self.files.len()
+            );
+        };
+        pdf::load_mem(file, self.password.as_deref())
+    }
+}
+
+/// Process an upload on a blocking thread, as documents take a while to
+/// process, and send the result back.
+async fn process<T, F>(
+    max_memory: Option<u64>,
+    multipart: Multipart,
+    f: F,
+) -> Result<T, ServerError>
+where
+    T: Send + 'static,
+    F: FnOnce(Upload) -> Result<T> + Send + 'static,
+{
+    let upload = Upload::read(multipart, max_memory).await?;
+    tokio::task::spawn_blocking(move || f(upload))
+        .await
+        .map_err(|error| ServerError(StatusCode::INTERNAL_SERVER_ERROR, error.to_string()))?
+        .map_err(ServerError::from)
+}
+
+/// Return a response holding a PDF file.
+fn pdf_response(pdf: Vec<u8>) -> Response {
+    ([(header::CONTENT_TYPE, "application/pdf")], pdf).into_response()
+}
+
+/// Handle `POST /annotations/merge`: merge the annotations of the uploaded
+/// files into the first one.
+async fn merge(
+    State(state): State<Arc<ServerState>>,
+    multipart: Multipart,
+) -> Result<Response, ServerError> {
+    let pdf = process(state.max_memory, multipart, move |upload| {
+        if upload.files.len() < 2 {
+            bail!(
+                "At least two PDF files are needed, found {}.",
+                upload.files.len()
+            );
+        }
+        let exclude = upload.exclude_or("Link");
+        let password = upload.password.as_deref();
+        let mut merger = AnnotationMerger::new(pdf::load_mem(&upload.files[0], password)?);
+        for file in &upload.files[1..] {
+            merger.add(pdf::load_mem(file, password)?, &exclude, None);
+        }
+        let (mut document, added) = merger.finish(true);
+        debug!("Merged {added} annotations");
+        pdf::save_to_vec(&mut document, state.save_options)
+    })
+    .await?;
+    Ok(pdf_response(pdf))
+}
+
+/// Handle `POST /annotations/strip`: strip the annotations of the uploaded
+/// file.
+async fn strip(
+    State(state): State<Arc<ServerState>>,
+    multipart: Multipart,
+) -> Result<Response, ServerError> {
+    let pdf = process(state.max_memory, multipart, move |upload| {
+        let mut document = upload.load_one()?;
+        let ids: Vec<_> =
+            annotations::strip_candidates(&document, &upload.exclude_or("Link"), upload.media)
+                .into_iter()
+                .map(|(_, id)| id)
+                .collect();
+        annotations::delete_annotations(&mut document, &ids);
+        debug!("Stripped {} annotations", ids.len());
+        pdf::save_to_vec(&mut document, state.save_options)
+    })
+    .await?;
+    Ok(pdf_response(pdf))
+}
+
+/// Handle `POST /annotations/stats`: count the annotations of the uploaded
+/// file.
+async fn stats(
+    State(state): State<Arc<ServerState>>,
+    multipart: Multipart,
+) -> Result<Json<annotations::Stats>, ServerError> {
+    let stats = process(state.max_memory, multipart, |upload| {
+        Ok(annotations::stats(&upload.load_one()?))
+    })
+    .await?;
+    Ok(Json(stats))
+}
+
+/// Handle `POST /optimize`: deduplicate, prune and compress the objects of
+/// the uploaded file.
+async fn optimize(
+    State(state): State<Arc<ServerState>>,
+    multipart: Multipart,
+) -> Result<Response, ServerError> {
+    let pdf = process(state.max_memory, multipart, move |upload| {
+        let mut document = upload.load_one()?;
+        let removed = dedup::deduplicate_objects(&mut document);
+        let pruned = document.prune_objects().len();
+        document.compress();
+        debug!("Removed {removed} duplicated objects and {pruned} unused objects");
+        pdf::save_to_vec(&mut document, state.save_options)
+    })
+    .await?;
+    Ok(pdf_response(pdf))
+}
+
+impl Execute for ServeCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let state = Arc::new(ServerState {
+            save_options: ctx.save_options,
+            max_memory: ctx.max_memory,
+        });
+        let app = Router::new()
+            .route("/annotations/merge", post(merge))
+            .route("/annotations/strip", post(strip))
+            .route("/annotations/stats", post(stats))
+            .route("/optimize", post(optimize))
+            .layer(DefaultBodyLimit::max(
+                usize::try_from(self.max_upload).unwrap_or(usize::MAX),
+            ))
+            .with_state(state);
+
+        let runtime = tokio::runtime::Runtime::new().context("Failed to start the server")?;
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind(self.address))
+            .with_context(|| format!("Failed to listen on: {}", self.address))?;
+        let address = listener.local_addr()?;
+        if !ctx.quiet() {
+            writeln!(ctx.stdout, "Listening on http://{address}")?;
+            ctx.stdout.flush()?;
+        }
+
+        runtime
+            .block_on(
+                axum::serve(listener, app)
+                    .with_graceful_shutdown(async {
+                        let _ = tokio::signal::ctrl_c().await;
+                    })
+                    .into_future(),
+            )
+            .context("Failed to serve requests")?;
+        info!("Server stopped");
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use clap_complete::ArgValueCompleter;
+use tabled::{
+    builder::Builder,
+    settings::{Color, Panel, Style, style::BorderColor},
+};
+use termcolor::WriteColor;
+use tracing::warn;
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::{
+    history, pdfstring,
+    signatures::{self, Coverage},
+};
+
+/// Show command.
+#[derive(Args, Clone, Debug)]
+struct Show {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+impl Execute for Show {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let data = fs::read(&self.file)
+            .with_context(|| format!("Failed to read PDF from: {:?}", self.file))?;
+        let document = ctx.load_document_without_images(&self.file)?;
+        let signatures = signatures::signatures(&document);
+
+        if signatures.is_empty() {
+            writeln!(ctx.stdout, "File {:?} has no signatures.", self.file)?;
+            return Ok(());
+        }
+        let revisions = history::revisions(&data);
+
+        let mut builder = Builder::default();
+        builder.set_header(["Field", "Signer", "Date", "Reason", "Covers", "Verdict"]);
+        for signature in &signatures {
+            let coverage = signatures::coverage(signature, &data, &revisions);
+            if coverage.modified_after_signing() {
+                warn!(
+                    "File {:?} was updated after signature {:?}, see `history show` for what \
+                     changed",
+                    self.file, signature.field
+                );
+            }
+            let (covers, verdict) = match coverage {
+                Coverage::Revision(revision, count) if revision == count => {
+                    (
+                        format!("revision {revision} of {count}"),
+                        "Not modified after signing".to_string(),
+                    )
+                },
+                Coverage::Revision(revision, count) => {
+                    (
+                        format!("revision {revision} of {count}"),
+                        "Modified after signing".to_string(),
+                    )
+                },
+                Coverage::Unknown => {
+                    (
+                        "unknown".to_string(),
+                        "Byte range does not end with any revision".to_string(),
+                    )
+                },
+            };
+            builder.push_record([
+                signature.field.clone(),
+                signature.signer.clone().unwrap_or_default(),
+                signature
+                    .date
+                    .as_deref()
+                    .map(pdfstring::display_date)
+                    .unwrap_or_default(),
+                signature.reason.clone().unwrap_or_default(),
+                covers,
+                verdict,
+            ]);
+        }
+        let mut table = builder.build();
+        table
+            .with(Panel::header(format!(
+                "Signatures of: {}",
+                self.file.display()
+            )))
+            .with(Style::modern());
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+        writeln!(ctx.stdout, "{table}")?;
+        Ok(())
+    }
+}
+
+/// Available subcommands for signatures.
+#[derive(Debug, Subcommand)]
+enum SignaturesSubcommand {
+    /// List signatures, the revision of the file that each one covers, and
+    /// whether the file was updated after signing.
+    ///
+    /// Signatures are not verified cryptographically.
+    Show(Show),
+}
+
+/// Inspect the digital signatures of documents.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct SignaturesCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: SignaturesSubcommand,
+}
+
+impl Execute for SignaturesCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            SignaturesSubcommand::Show(show) => show.execute(ctx),
+        }
+    }
+}
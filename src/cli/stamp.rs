@@ -0,0 +1,246 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueHint};
+use clap_complete::ArgValueCompleter;
+#[cfg(feature = "qrcode")]
+use qrcodegen::{QrCode, QrCodeEcc};
+use termcolor::WriteColor;
+
+use super::{
+    annotations::StampPosition, complete::pdf_files, context::ExecutionContext, text::write_record,
+    traits::Execute,
+};
+#[cfg(feature = "qrcode")]
+use crate::pdf::selection::PageSelection;
+use crate::pdf::{document::DocumentExt, stamp};
+
+/// Bates command.
+#[derive(Args, Clone, Debug)]
+struct Bates {
+    /// PDF filepaths, numbered in the given order.
+    #[clap(num_args(1..), required = true, add = ArgValueCompleter::new(pdf_files()))]
+    files: Vec<PathBuf>,
+    /// Text written before numbers, e.g., `ABC-`.
+    #[clap(long, default_value = "")]
+    prefix: String,
+    /// Number of the first page.
+    #[clap(long, default_value_t = 1)]
+    start: u64,
+    /// Minimum number of digits of numbers, padded with zeros.
+    #[clap(long, default_value_t = 6)]
+    digits: usize,
+    /// Position of numbers on the page, in the unrotated page.
+    #[clap(long, default_value = "bottom-right")]
+    position: StampPosition,
+    /// Font size of numbers, in points.
+    #[clap(long, default_value_t = 10.0)]
+    font_size: f32,
+    /// Directory where numbered files are written, with their original
+    /// names.
+    #[clap(short, long, default_value = "bates", value_hint = ValueHint::DirPath)]
+    dest_dir: PathBuf,
+    /// CSV file mapping each page of each file to its number. Defaults to
+    /// `index.csv` in the output directory.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    index: Option<PathBuf>,
+}
+
+impl Execute for Bates {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut names = HashSet::new();
+        for file in &self.files {
+            let Some(name) = file.file_name() else {
+                bail!("Invalid file path: {file:?}.");
+            };
+            if !names.insert(name) {
+                bail!("Several files are named {name:?}, which would overwrite each other.");
+            }
+        }
+        fs::create_dir_all(&self.dest_dir)
+            .with_context(|| format!("Failed to create output directory: {:?}", self.dest_dir))?;
+
+        let index_path = self
+            .index
+            .clone()
+            .unwrap_or_else(|| self.dest_dir.join("index.csv"));
+        let mut index = BufWriter::new(
+            File::create(&index_path)
+                .with_context(|| format!("Failed to write index to: {index_path:?}"))?,
+        );
+        write_record(
+            &mut index,
+            &["file".into(), "page".into(), "bates".into()],
+            ',',
+        )?;
+
+        let mut number = self.start;
+        for file in &self.files {
+            let mut document = ctx.load_document(file)?;
+            let font_id = stamp::add_font(&mut document);
+            let first = number;
+
+            for (page, page_id) in document.get_pages() {
+                let text = format!("{}{:0digits$}", self.prefix, number, digits = self.digits);
+                let crop_box = document
+                    .page_crop_box(page_id)
+                    .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+                let width = stamp::text_width(&text, self.font_size);
+                let [x, y, ..] = self.position.rect(crop_box, width, self.font_size);
+
+                stamp::draw_text(
+                    &mut document,
+                    page_id,
+                    font_id,
+                    &text,
+                    (x, y),
+                    self.font_size,
+                )
+                .with_context(|| format!("Failed to number page {page} of {file:?}."))?;
+                write_record(
+                    &mut index,
+                    &[file.display().to_string(), page.to_string(), text],
+                    ',',
+                )?;
+                number += 1;
+            }
+            let dest = self.dest_dir.join(file.file_name().unwrap_or_default());
+            ctx.save_document(&mut document, &dest)?;
+            if !ctx.quiet() {
+                writeln!(
+                    ctx.stdout,
+                    "Successfully numbered {} pages of {file:?} to {dest:?}.",
+                    number - first
+                )?;
+            }
+        }
+        index
+            .flush()
+            .with_context(|| format!("Failed to write index to: {index_path:?}"))?;
+        Ok(())
+    }
+}
+
+/// Qrcode command.
+#[cfg(feature = "qrcode")]
+#[derive(Args, Clone, Debug)]
+struct Qrcode {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Data of the QR code, e.g., a URL, where `{file}`, `{stem}` and
+    /// `{page}` are replaced by the file name, the file name without
+    /// extension and the page number.
+    #[clap(long, value_name = "TEMPLATE")]
+    data: String,
+    /// Pages to stamp, e.g., `1` or `1,3-5`.
+    #[clap(short, long, default_value = "1")]
+    pages: PageSelection,
+    /// Size of the QR code, including its quiet zone, in points.
+    #[clap(long, default_value_t = 80.0)]
+    size: f32,
+    /// Position of the QR code on the page, in the unrotated page.
+    #[clap(long, default_value = "top-right")]
+    position: StampPosition,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "stamped.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+#[cfg(feature = "qrcode")]
+impl Execute for Qrcode {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let pages = self.pages.select(&document);
+        if pages.is_empty() {
+            bail!(
+                "No page of {:?} matches the selection {}.",
+                self.file,
+                self.pages
+            );
+        }
+        let file_name = self.file.file_name().unwrap_or_default().to_string_lossy();
+        let stem = self.file.file_stem().unwrap_or_default().to_string_lossy();
+
+        for (&page, &page_id) in &pages {
+            let data = self
+                .data
+                .replace("{file}", &file_name)
+                .replace("{stem}", &stem)
+                .replace("{page}", &page.to_string());
+            let Ok(code) = QrCode::encode_text(&data, QrCodeEcc::Medium) else {
+                bail!("Data is too long for a QR code: {data:?}.");
+            };
+            let crop_box = document
+                .page_crop_box(page_id)
+                .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+            let [x, y, ..] = self.position.rect(crop_box, self.size, self.size);
+
+            stamp::draw_qr_code(&mut document, page_id, &code, (x, y), self.size)
+                .with_context(|| format!("Failed to stamp page {page} of {:?}.", self.file))?;
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully stamped {} pages of {:?} to {:?}.",
+                pages.len(),
+                self.file,
+                self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for stamp.
+#[derive(Debug, Subcommand)]
+enum StampSubcommand {
+    /// Number pages with Bates numbers, e.g., `ABC-001001`, for litigation.
+    ///
+    /// Numbers continue from one file to the next, and an index maps each
+    /// page of each file to its number. Numbers are drawn in the content of
+    /// pages, so they cannot be moved or removed like annotations.
+    Bates(Bates),
+    /// Stamp a QR code, e.g., linking printed documents back to a document
+    /// management system.
+    ///
+    /// The QR code is drawn with vector shapes, so it stays sharp at any
+    /// zoom or print resolution.
+    #[cfg(feature = "qrcode")]
+    Qrcode(Qrcode),
+}
+
+/// Draw stamps in the content of pages.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct StampCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: StampSubcommand,
+}
+
+impl Execute for StampCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            StampSubcommand::Bates(bates) => bates.execute(ctx),
+            #[cfg(feature = "qrcode")]
+            StampSubcommand::Qrcode(qrcode) => qrcode.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::{io, path::PathBuf};
+
+use anyhow::{Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::ArgValueCompleter;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::structure::{self, Element};
+
+/// Output format of the structure tree.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum StructureFormat {
+    /// Indented tree, one element per line.
+    #[default]
+    Text,
+    /// JSON document, with all the attributes of elements.
+    Json,
+}
+
+/// Show command.
+#[derive(Args, Clone, Debug)]
+struct Show {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t)]
+    format: StructureFormat,
+    /// Maximum depth of the tree, in text format.
+    #[clap(short, long)]
+    depth: Option<usize>,
+}
+
+/// Return the details shown after an element's type, e.g., its page and
+/// alternate description.
+fn details(element: &Element) -> Vec<String> {
+    let mut details = vec![];
+
+    if let Some(page) = element.page {
+        details.push(format!("page {page}"));
+    }
+    if !element.marked_content.is_empty() {
+        details.push(format!("{} marked contents", element.marked_content.len()));
+    }
+    if !element.objects.is_empty() {
+        details.push(format!("{} objects", element.objects.len()));
+    }
+    for (name, value) in [
+        ("title", &element.title),
+        ("lang", &element.lang),
+        ("alt", &element.alt),
+        ("actual text", &element.actual_text),
+    ] {
+        if let Some(value) = value {
+            details.push(format!("{name}: {value:?}"));
+        }
+    }
+    details
+}
+
+impl Show {
+    /// Write elements as an indented tree.
+    fn write_elements<W: WriteColor>(
+        &self,
+        out: &mut W,
+        elements: &[Element],
+        prefix: &str,
+        depth: usize,
+    ) -> io::Result<()> {
+        for (i, element) in elements.iter().enumerate() {
+            let last = i + 1 == elements.len();
+            write!(out, "{prefix}{}", if last { "└── " } else { "├── " })?;
+
+            out.set_color(ColorSpec::new().set_fg(Some(Color::Blue)))?;
+            write!(out, "{}", element.struct_type)?;
+            out.reset()?;
+            if let Some(role) = &element.role {
+                write!(out, " → {role}")?;
+            }
+            let details = details(element);
+            if !details.is_empty() {
+                write!(out, " ({})", details.join(", "))?;
+            }
+            writeln!(out)?;
+
+            if self.depth.map_or(true, |max| depth < max) {
+                let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+                self.write_elements(out, &element.children, &prefix, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Execute for Show {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let Some(tree) = structure::read(&document) else {
+            bail!(
+                "Document {:?} is not tagged, it has no structure tree.",
+                self.file
+            );
+        };
+
+        match self.format {
+            StructureFormat::Text => {
+                writeln!(ctx.stdout, "StructTreeRoot")?;
+                self.write_elements(&mut ctx.stdout, &tree.children, "", 1)?;
+            },
+            StructureFormat::Json => {
+                serde_json::to_writer_pretty(&mut ctx.stdout, &tree)?;
+                writeln!(ctx.stdout)?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for structure.
+#[derive(Debug, Subcommand)]
+enum StructureSubcommand {
+    /// Show the logical structure tree of a tagged PDF.
+    ///
+    /// Each element is shown with its structure type, the standard type it
+    /// maps to through the role map if it is a custom type, and attributes
+    /// that matter for accessibility, e.g., alternate descriptions.
+    Show(Show),
+}
+
+/// Inspect the logical structure of tagged PDF files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct StructureCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: StructureSubcommand,
+}
+
+impl Execute for StructureCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            StructureSubcommand::Show(show) => show.execute(ctx),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Summary of a run, i.e., what a command read, changed and wrote.
+//!
+//! Summaries are only shown or written on request, and never sent anywhere.
+
+use std::{
+    cell::{Cell, RefCell},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::i18n::Localizer;
+
+/// Counters updated while a command runs.
+///
+/// Counters are cells, so that they can be updated through the shared
+/// execution context, e.g., when documents are loaded.
+#[derive(Debug)]
+pub struct RunSummary {
+    start: Instant,
+    files_read: Cell<u64>,
+    bytes_read: Cell<u64>,
+    files_written: Cell<u64>,
+    bytes_written: Cell<u64>,
+    annotations_added: Cell<u64>,
+    annotations_removed: Cell<u64>,
+    /// Paths of the files read, in order.
+    read: RefCell<Vec<PathBuf>>,
+    /// Paths of the files written, in order.
+    written: RefCell<Vec<PathBuf>>,
+}
+
+impl Default for RunSummary {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            files_read: Cell::default(),
+            bytes_read: Cell::default(),
+            files_written: Cell::default(),
+            bytes_written: Cell::default(),
+            annotations_added: Cell::default(),
+            annotations_removed: Cell::default(),
+            read: RefCell::default(),
+            written: RefCell::default(),
+        }
+    }
+}
+
+/// Add to a counter.
+fn add(counter: &Cell<u64>, value: u64) {
+    counter.set(counter.get().saturating_add(value));
+}
+
+/// Return the size of a file, or zero if it cannot be read.
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+impl RunSummary {
+    /// Count a file that was read.
+    pub fn file_read(&self, path: &Path) {
+        add(&self.files_read, 1);
+        add(&self.bytes_read, file_size(path));
+        self.read.borrow_mut().push(path.to_path_buf());
+    }
+
+    /// Count a file that was downloaded from a URL, rather than read from
+    /// disk.
+    #[cfg(feature = "net")]
+    pub fn file_downloaded(&self, url: &Path, size: u64) {
+        add(&self.files_read, 1);
+        add(&self.bytes_read, size);
+        self.read.borrow_mut().push(url.to_path_buf());
+    }
+
+    /// Return whether a file was read, e.g., to tell whether it is edited in
+    /// place.
+    #[must_use]
+    pub fn was_read(&self, path: &Path) -> bool {
+        let Ok(path) = fs::canonicalize(path) else {
+            return false;
+        };
+        self.read
+            .borrow()
+            .iter()
+            .any(|read| fs::canonicalize(read).is_ok_and(|read| read == path))
+    }
+
+    /// Count a file that was written.
+    pub fn file_written(&self, path: &Path) {
+        add(&self.files_written, 1);
+        add(&self.bytes_written, file_size(path));
+        self.written.borrow_mut().push(path.to_path_buf());
+    }
+
+    /// Return the paths of the files written, in the order they were first
+    /// written.
+    #[must_use]
+    pub fn written_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = vec![];
+        for path in self.written.borrow().iter() {
+            if !files.contains(path) {
+                files.push(path.clone());
+            }
+        }
+        files
+    }
+
+    /// Count added annotations.
+    pub fn annotations_added(&self, count: usize) {
+        add(&self.annotations_added, count as u64);
+    }
+
+    /// Count removed annotations.
+    pub fn annotations_removed(&self, count: usize) {
+        add(&self.annotations_removed, count as u64);
+    }
+
+    /// Return the report of the run so far, and whether it succeeded.
+    #[must_use]
+    pub fn report(&self, success: bool) -> Report {
+        let duration = self.start.elapsed();
+        Report {
+            success,
+            files_read: self.files_read.get(),
+            bytes_read: self.bytes_read.get(),
+            files_written: self.files_written.get(),
+            bytes_written: self.bytes_written.get(),
+            bytes_saved: i128::from(self.bytes_read.get()) - i128::from(self.bytes_written.get()),
+            annotations_added: self.annotations_added.get(),
+            annotations_removed: self.annotations_removed.get(),
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            duration,
+        }
+    }
+}
+
+/// Report of a run, written as JSON by `--report`.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub success: bool,
+    pub files_read: u64,
+    pub bytes_read: u64,
+    pub files_written: u64,
+    pub bytes_written: u64,
+    /// Bytes read minus bytes written, which is negative if output files
+    /// are larger.
+    pub bytes_saved: i128,
+    pub annotations_added: u64,
+    pub annotations_removed: u64,
+    pub duration_ms: u64,
+    #[serde(skip)]
+    duration: Duration,
+}
+
+impl Report {
+    /// Write the report as text, one counter per line.
+    pub fn write_text<W: Write>(&self, out: &mut W, i18n: &Localizer) -> Result<()> {
+        let lines = [
+            (
+                "summary-files-read",
+                vec![
+                    ("count", self.files_read.into()),
+                    ("bytes", self.bytes_read.into()),
+                ],
+            ),
+            (
+                "summary-files-written",
+                vec![
+                    ("count", self.files_written.into()),
+                    ("bytes", self.bytes_written.into()),
+                ],
+            ),
+            (
+                "summary-bytes-saved",
+                // Fluent numbers are floats, which may round large values.
+                vec![("bytes", self.bytes_saved.to_string().into())],
+            ),
+            (
+                "summary-annotations-added",
+                vec![("count", self.annotations_added.into())],
+            ),
+            (
+                "summary-annotations-removed",
+                vec![("count", self.annotations_removed.into())],
+            ),
+            (
+                "summary-duration",
+                vec![(
+                    "seconds",
+                    format!("{:.3}", self.duration.as_secs_f64()).into(),
+                )],
+            ),
+        ];
+        for (id, args) in lines {
+            writeln!(out, "{}", i18n.message(id, &args))?;
+        }
+        Ok(())
+    }
+
+    /// Write the report as JSON to a file.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").with_context(|| format!("Failed to write report to: {path:?}"))
+    }
+}
@@ -0,0 +1,377 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+#[cfg(feature = "regex")]
+use clap::ValueHint;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::ArgValueCompleter;
+use lopdf::ObjectId;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use serde::Serialize;
+use tabled::{
+    builder::Builder,
+    settings::{Color, Panel, Style, style::BorderColor},
+};
+use termcolor::WriteColor;
+use tracing::info;
+#[cfg(feature = "regex")]
+use tracing::warn;
+
+#[cfg(feature = "regex")]
+use crate::pdf::replace;
+use crate::pdf::{
+    selection::PageSelection,
+    tables,
+    text::{self, Counts},
+};
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+
+/// Output format of extracted tables.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum TableFormat {
+    /// Comma-separated values.
+    #[default]
+    Csv,
+    /// Tab-separated values.
+    Tsv,
+}
+
+impl TableFormat {
+    /// Return the field delimiter.
+    fn delimiter(self) -> char {
+        match self {
+            Self::Csv => ',',
+            Self::Tsv => '\t',
+        }
+    }
+}
+
+/// Write a record, quoting fields that contain the delimiter, quotes or line
+/// breaks.
+pub(super) fn write_record<W: Write>(
+    writer: &mut W,
+    fields: &[String],
+    delimiter: char,
+) -> Result<()> {
+    let record: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    writeln!(writer, "{}", record.join(&delimiter.to_string()))?;
+    Ok(())
+}
+
+/// Tables command.
+#[derive(Args, Clone, Debug)]
+struct Tables {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Pages to extract tables from, e.g., `2` or `1,3-5`.
+    #[clap(short, long, default_value = "all")]
+    pages: PageSelection,
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t)]
+    format: TableFormat,
+}
+
+impl Execute for Tables {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let pages = self.pages.select(&document);
+
+        if pages.is_empty() {
+            bail!(
+                "No page of {:?} matches the selection {}.",
+                self.file,
+                self.pages
+            );
+        }
+
+        let pages: Vec<(u32, ObjectId)> = pages.into_iter().collect();
+        #[cfg(feature = "parallel")]
+        let page_iter = pages.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let page_iter = pages.iter();
+        let tables = page_iter
+            .map(|&(page, page_id)| {
+                tables::page_tables(&document, page_id)
+                    .with_context(|| format!("Failed to read the content of page {page}."))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut count = 0;
+        for ((page, _), tables) in pages.iter().zip(tables) {
+            for table in tables {
+                info!("Found a table of {} rows on page {page}.", table.rows.len());
+                if count > 0 {
+                    writeln!(ctx.stdout)?;
+                }
+                for row in &table.rows {
+                    write_record(&mut ctx.stdout, row, self.format.delimiter())?;
+                }
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            info!("No table was found in the selected pages.");
+        }
+        Ok(())
+    }
+}
+
+/// Output format of word counts.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum CountFormat {
+    /// Table, with one row per page and a total.
+    #[default]
+    Text,
+    /// JSON document.
+    Json,
+}
+
+/// Word counts of a page, or of all pages.
+#[derive(Serialize)]
+struct PageCounts {
+    /// Page number, or `null` for the total.
+    page: Option<u32>,
+    words: usize,
+    characters: usize,
+    /// Estimated reading time, in seconds.
+    reading_time: u64,
+}
+
+impl PageCounts {
+    fn new(page: Option<u32>, counts: Counts, words_per_minute: u32) -> Self {
+        Self {
+            page,
+            words: counts.words,
+            characters: counts.characters,
+            reading_time: (counts.words as u64 * 60).div_ceil(u64::from(words_per_minute)),
+        }
+    }
+}
+
+/// Word count report of a file, written as JSON.
+#[derive(Serialize)]
+struct CountReport<'a> {
+    file: &'a PathBuf,
+    words_per_minute: u32,
+    pages: &'a [PageCounts],
+    total: &'a PageCounts,
+}
+
+/// Return a duration in seconds as minutes and seconds, e.g., `3:05`.
+fn format_reading_time(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Wordcount command.
+#[derive(Args, Clone, Debug)]
+struct Wordcount {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Pages to count words of, e.g., `2` or `1,3-5`.
+    #[clap(short, long, default_value = "all")]
+    pages: PageSelection,
+    /// Reading speed used to estimate reading times, in words per minute.
+    #[clap(long, default_value_t = 230, value_parser = clap::value_parser!(u32).range(1..))]
+    wpm: u32,
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t)]
+    format: CountFormat,
+}
+
+impl Execute for Wordcount {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = ctx.load_document_without_images(&self.file)?;
+        let pages = self.pages.select(&document);
+
+        if pages.is_empty() {
+            bail!(
+                "No page of {:?} matches the selection {}.",
+                self.file,
+                self.pages
+            );
+        }
+
+        let pages: Vec<(u32, ObjectId)> = pages.into_iter().collect();
+        let cache = &ctx.cache;
+        #[cfg(feature = "parallel")]
+        let page_iter = pages.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let page_iter = pages.iter();
+        let counts = page_iter
+            .map(|&(page, page_id)| {
+                cache
+                    .get_or_compute(&self.file, &format!("text-{page}"), || {
+                        text::page_spans(&document, page_id)
+                    })
+                    .map(|spans| text::counts(&spans))
+                    .with_context(|| format!("Failed to read the content of page {page}."))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut total = Counts::default();
+        let pages: Vec<PageCounts> = pages
+            .iter()
+            .zip(counts)
+            .map(|(&(page, _), counts)| {
+                total += counts;
+                PageCounts::new(Some(page), counts, self.wpm)
+            })
+            .collect();
+        let total = PageCounts::new(None, total, self.wpm);
+
+        if let CountFormat::Json = self.format {
+            let report = CountReport {
+                file: &self.file,
+                words_per_minute: self.wpm,
+                pages: &pages,
+                total: &total,
+            };
+            serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+            writeln!(ctx.stdout)?;
+            return Ok(());
+        }
+
+        let mut builder = Builder::default();
+        builder.set_header(["Page no.", "Words", "Characters", "Reading time"]);
+        for counts in pages.iter().chain([&total]) {
+            builder.push_record([
+                counts
+                    .page
+                    .map_or_else(|| "Total".to_string(), |page| page.to_string()),
+                counts.words.to_string(),
+                counts.characters.to_string(),
+                format_reading_time(counts.reading_time),
+            ]);
+        }
+        let mut table = builder.build();
+        table
+            .with(Panel::header(format!(
+                "Word count for: {}",
+                self.file.display()
+            )))
+            .with(Style::modern());
+        if ctx.color.enabled() {
+            table.with(BorderColor::filled(Color::FG_GREEN));
+        }
+        writeln!(ctx.stdout, "{table}")?;
+        Ok(())
+    }
+}
+
+/// Replace command.
+#[cfg(feature = "regex")]
+#[derive(Args, Clone, Debug)]
+struct Replace {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Regular expression matching the text to replace, e.g., `ACME Corp`.
+    #[clap(long)]
+    pattern: Regex,
+    /// Replacement text, which may refer to groups of the pattern, e.g.,
+    /// `$1`.
+    #[clap(long = "with", value_name = "TEXT")]
+    replacement: String,
+    /// Output file where resulting PDF is written.
+    #[clap(short, long, default_value = "replaced.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+}
+
+#[cfg(feature = "regex")]
+impl Execute for Replace {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        let report = replace::replace(&mut document, &self.pattern, &self.replacement);
+
+        for failure in &report.failures {
+            let location = failure.page.map_or_else(
+                || "a form XObject".to_string(),
+                |page| format!("page {page}"),
+            );
+            warn!(
+                "Cannot replace text on {location}, as font {:?} cannot show: {:?}",
+                failure.font, failure.text
+            );
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully replaced {} matches in {:?} to {:?}.",
+                report.replaced, self.file, self.dest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Available subcommands for text.
+#[derive(Debug, Subcommand)]
+enum TextSubcommand {
+    /// Extract tables to CSV, using ruling lines and whitespace columns.
+    Tables(Tables),
+    /// Replace text matching a regular expression, e.g., to rebrand
+    /// documents.
+    ///
+    /// Text is matched within each text-showing operator, and the
+    /// replacement is shown with the same font, if it has glyphs for all its
+    /// characters, otherwise a warning is emitted. Text split across
+    /// operators is not matched, and following text is not moved.
+    #[cfg(feature = "regex")]
+    Replace(Replace),
+    /// Count the words and characters of pages, and estimate their reading
+    /// time.
+    ///
+    /// Only visible, horizontal text is counted, so OCR layers of scans are
+    /// not. Characters exclude whitespace.
+    Wordcount(Wordcount),
+}
+
+/// Extract text from PDF files.
+#[derive(Debug, Parser)]
+#[clap(subcommand_required = true)]
+pub struct TextCommand {
+    /// Optional subcommand.
+    #[command(subcommand)]
+    subcommand: TextSubcommand,
+}
+
+impl Execute for TextCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        match &self.subcommand {
+            TextSubcommand::Tables(tables) => tables.execute(ctx),
+            #[cfg(feature = "regex")]
+            TextSubcommand::Replace(replace) => replace.execute(ctx),
+            TextSubcommand::Wordcount(wordcount) => wordcount.execute(ctx),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use termcolor::WriteColor;
 
+use super::context::ExecutionContext;
+
 pub trait Execute {
-    fn execute<W: WriteColor>(&self, stdout: &mut W) -> Result<()>;
+    fn execute<W: WriteColor>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>;
 }
@@ -0,0 +1,84 @@
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use sha2::{Digest, Sha256};
+use termcolor::WriteColor;
+
+use super::{complete::pdf_files, context::ExecutionContext, output, traits::Execute};
+use crate::pdf::{self, journal};
+
+/// Return the path of the journal of a file, e.g., `file.pdf.rpdf-journal`.
+#[must_use]
+pub fn journal_path(file: &Path) -> PathBuf {
+    let mut path = OsString::from(file);
+    path.push(".rpdf-journal");
+    PathBuf::from(path)
+}
+
+/// Undo the last in-place edit of a file, e.g., `rpdf annotations strip
+/// --force file.pdf -d file.pdf`, from the journal written next to it.
+///
+/// Files that were changed since, by rpdf or any other tool, cannot be
+/// restored.
+#[derive(Debug, Parser)]
+pub struct UndoCommand {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+}
+
+impl Execute for UndoCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let journal_path = journal_path(&self.file);
+        if !journal_path.exists() {
+            bail!(
+                "Failed to find a journal for: {:?}, only in-place edits can be undone.",
+                self.file
+            );
+        }
+        let journal = pdf::load(&journal_path, None)
+            .with_context(|| format!("Failed to read journal from: {journal_path:?}"))?;
+
+        let data = fs::read(&self.file)
+            .with_context(|| format!("Failed to read PDF from: {:?}", self.file))?;
+        if journal::checksum(&journal).as_deref() != Some(&hex::encode(Sha256::digest(&data))) {
+            bail!(
+                "{:?} was modified since its last in-place edit, it cannot be undone.",
+                self.file
+            );
+        }
+
+        // The file is restored as it was, which no overwrite policy needs to
+        // allow, and the journal is not needed anymore.
+        if let Some(original) = journal::original_file(&journal) {
+            output::write_atomically(&self.file, original)
+                .with_context(|| format!("Failed to write PDF to: {:?}", self.file))?;
+        } else {
+            let mut document = ctx.load_document(&self.file)?;
+            journal::undo(&mut document, &journal)
+                .with_context(|| format!("Failed to read journal from: {journal_path:?}"))?;
+            pdf::save(&mut document, &self.file, ctx.save_options)?;
+        }
+        ctx.summary.file_written(&self.file);
+        fs::remove_file(&journal_path)
+            .with_context(|| format!("Failed to remove journal from: {journal_path:?}"))?;
+
+        if !ctx.quiet() {
+            writeln!(
+                ctx.stdout,
+                "Successfully undid the last edit of {:?}.",
+                self.file
+            )?;
+        }
+        Ok(())
+    }
+}
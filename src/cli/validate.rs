@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::{Parser, ValueEnum, ValueHint};
+use clap_complete::ArgValueCompleter;
+use lopdf::Document;
+use serde::Serialize;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+use super::{complete::pdf_files, context::ExecutionContext, traits::Execute};
+use crate::pdf::validate::{self, Issue};
+
+/// Set of checks a document is validated against.
+#[derive(Clone, Copy, Debug, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum Profile {
+    /// Accessibility requirements of PDF/UA-1 (partial): the document is
+    /// tagged and has a language, figures have alternate descriptions,
+    /// pages with annotations define their tab order, and there is no
+    /// untagged content.
+    #[value(name = "pdfua")]
+    PdfUa,
+}
+
+impl Profile {
+    /// Return the name of the profile, as shown to users.
+    fn name(self) -> &'static str {
+        match self {
+            Self::PdfUa => "PDF/UA",
+        }
+    }
+}
+
+/// Repair applied to documents before they are validated.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Fix {
+    /// Remove `null` entries and references to missing objects from the
+    /// `/Annots` arrays of pages, as written by some scanners.
+    Annots,
+}
+
+/// Output format of validation reports.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum ReportFormat {
+    /// One issue per line.
+    #[default]
+    Text,
+    /// JSON document.
+    Json,
+}
+
+/// Validation report of a file, written as JSON.
+#[derive(Serialize)]
+struct ValidationReport<'a> {
+    file: &'a PathBuf,
+    profile: Profile,
+    issues: &'a [Issue],
+}
+
+/// Check PDF files against a profile, e.g., accessibility requirements.
+///
+/// Each issue is reported with the rule it breaks, and the page and object
+/// at fault, if any. The command fails if there is any issue.
+///
+/// With `--fix`, the document is repaired first, and the repaired document
+/// is written to `--dest`, then validated if a profile is given.
+#[derive(Debug, Parser)]
+pub struct ValidateCommand {
+    /// PDF filepath.
+    #[clap(add = ArgValueCompleter::new(pdf_files()))]
+    file: PathBuf,
+    /// Profile to validate against.
+    #[clap(short, long, value_enum, required_unless_present = "fix")]
+    profile: Option<Profile>,
+    /// Repairs to apply, separated by commas.
+    #[clap(long, value_enum, value_delimiter = ',')]
+    fix: Vec<Fix>,
+    /// Output file where the repaired PDF is written, with `--fix`.
+    #[clap(short, long, default_value = "fixed.pdf", value_hint = ValueHint::FilePath)]
+    dest: PathBuf,
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t)]
+    format: ReportFormat,
+}
+
+impl ValidateCommand {
+    /// Apply the repairs to the document, write it to the destination, and
+    /// return it.
+    fn repair<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<Document>
+    where
+        W: WriteColor,
+    {
+        let mut document = ctx.load_document(&self.file)?;
+        // Messages would break JSON reports.
+        let verbose = !ctx.quiet() && matches!(self.format, ReportFormat::Text);
+
+        for fix in &self.fix {
+            match fix {
+                Fix::Annots => {
+                    for (page, removed) in validate::fix_annotations(&mut document) {
+                        if verbose {
+                            writeln!(
+                                ctx.stdout,
+                                "Removed {removed} invalid entries from the annotations of page \
+                                 {page}."
+                            )?;
+                        }
+                    }
+                },
+            }
+        }
+        ctx.save_document(&mut document, &self.dest)?;
+
+        if verbose {
+            writeln!(
+                ctx.stdout,
+                "Successfully repaired {:?} to {:?}.",
+                self.file, self.dest
+            )?;
+        }
+        Ok(document)
+    }
+}
+
+impl Execute for ValidateCommand {
+    fn execute<W>(&self, ctx: &mut ExecutionContext<W>) -> Result<()>
+    where
+        W: WriteColor,
+    {
+        let document = if self.fix.is_empty() {
+            ctx.load_document_without_images(&self.file)?
+        } else {
+            self.repair(ctx)?
+        };
+        let Some(profile) = self.profile else {
+            return Ok(());
+        };
+        let issues = match profile {
+            Profile::PdfUa => validate::check_pdfua(&document),
+        };
+
+        match self.format {
+            ReportFormat::Text => {
+                for issue in &issues {
+                    ctx.stdout
+                        .set_color(ColorSpec::new().set_fg(Some(Color::Red)))?;
+                    write!(ctx.stdout, "{}", issue.rule)?;
+                    ctx.stdout.reset()?;
+                    if let Some(page) = issue.page {
+                        write!(ctx.stdout, " (page {page})")?;
+                    }
+                    if let Some(object) = &issue.object {
+                        write!(ctx.stdout, " [{object}]")?;
+                    }
+                    writeln!(ctx.stdout, ": {}", issue.description)?;
+                }
+            },
+            ReportFormat::Json => {
+                let report = ValidationReport {
+                    file: &self.file,
+                    profile,
+                    issues: &issues,
+                };
+                serde_json::to_writer_pretty(&mut ctx.stdout, &report)?;
+                writeln!(ctx.stdout)?;
+            },
+        }
+
+        if !issues.is_empty() {
+            bail!(
+                "File {:?} failed {} {} checks.",
+                self.file,
+                issues.len(),
+                profile.name()
+            );
+        }
+        if !ctx.quiet() && matches!(self.format, ReportFormat::Text) {
+            writeln!(
+                ctx.stdout,
+                "File {:?} passed all {} checks.",
+                self.file,
+                profile.name()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+//! PDF utilities written in Rust.
+//!
+//! The `rpdf` binary is the main way to use them. This library exposes the
+//! helpers it is built on, e.g., for the C bindings of the `rpdf-ffi` crate.
+
+pub mod pdf;
@@ -1,18 +1,29 @@
 use clap::Parser;
-use log::error;
+use clap_complete::CompleteEnv;
+use tracing::error;
 
 mod cli;
 
 use cli::Cli;
+use rpdf::pdf;
 
 fn main() {
+    CompleteEnv::with_factory(cli::build_cli).complete();
+
     let cli = Cli::parse_from(wild::args());
 
-    pretty_env_logger::formatted_builder()
-        .filter_level(cli.verbose.log_level_filter())
-        .init();
+    if let Err(e) = cli::logging::init(
+        cli.verbose.log_level_filter(),
+        cli.log_format,
+        cli.log_file.as_deref(),
+        cli.log_color_policy(),
+    ) {
+        eprintln!("{e:#}");
+        std::process::exit(1);
+    }
 
     if let Err(e) = cli.execute() {
-        error!("{e:#}")
+        error!("{e:#}");
+        std::process::exit(1);
     }
 }
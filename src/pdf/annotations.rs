@@ -0,0 +1,986 @@
+//! Helpers to work with PDF annotations.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream,
+    content::{Content, Operation},
+    dictionary,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::{
+    content::number,
+    fonts::{self, StandardFont},
+    import::collect_references,
+    objects::object_types,
+    pdfstring,
+    text::{self, TextSpan},
+};
+
+/// Annotation flag telling that annotations are printed.
+const PRINT_FLAG: i64 = 4;
+
+/// Annotation subtypes defined by the PDF specification.
+pub const SUBTYPES: &[&str] = &[
+    "3D",
+    "Caret",
+    "Circle",
+    "FileAttachment",
+    "FreeText",
+    "Highlight",
+    "Ink",
+    "Line",
+    "Link",
+    "Movie",
+    "Polygon",
+    "PolyLine",
+    "Popup",
+    "PrinterMark",
+    "Projection",
+    "Redact",
+    "RichMedia",
+    "Screen",
+    "Sound",
+    "Square",
+    "Squiggly",
+    "Stamp",
+    "StrikeOut",
+    "Text",
+    "TrapNet",
+    "Underline",
+    "Watermark",
+    "Widget",
+];
+
+/// Subtypes of annotations that embed media, e.g., 3D models or videos,
+/// which are often much larger than the rest of the document.
+pub const MEDIA_SUBTYPES: &[&str] = &["3D", "RichMedia", "Screen"];
+
+/// Subtypes of markup annotations that mark text with quadrilaterals, whose
+/// text can be read back from the page.
+pub const TEXT_MARKUP_SUBTYPES: &[&str] = &["Highlight", "Squiggly", "StrikeOut", "Underline"];
+
+/// Categories of annotations, by purpose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    /// Comments and markups left by reviewers, e.g., notes and highlights.
+    Review,
+    /// Form fields.
+    Forms,
+    /// Links to other pages or to URIs.
+    Links,
+    /// Sounds, videos and 3D models.
+    Multimedia,
+}
+
+/// Return the category of an annotation subtype, if any.
+///
+/// Pop-ups, which only show the text of other annotations, and printing
+/// aids, e.g., printer's marks, belong to no category.
+#[must_use]
+pub fn category(subtype: &str) -> Option<Category> {
+    match subtype {
+        "Caret" | "Circle" | "FileAttachment" | "FreeText" | "Highlight" | "Ink" | "Line"
+        | "Polygon" | "PolyLine" | "Redact" | "Square" | "Squiggly" | "Stamp" | "StrikeOut"
+        | "Text" | "Underline" => Some(Category::Review),
+        "Widget" => Some(Category::Forms),
+        "Link" => Some(Category::Links),
+        "3D" | "Movie" | "RichMedia" | "Screen" | "Sound" => Some(Category::Multimedia),
+        _ => None,
+    }
+}
+
+/// Return the IDs of the annotations of a page, in order.
+#[must_use]
+pub fn page_annotation_ids(document: &Document, page_id: ObjectId) -> Vec<ObjectId> {
+    document
+        .get_dictionary(page_id)
+        .and_then(|page| page.get_deref(b"Annots", document))
+        .and_then(Object::as_array)
+        .map(|annots| {
+            annots
+                .iter()
+                .filter_map(|annot| annot.as_reference().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return the subtype of an annotation, or an empty string if it has none.
+#[must_use]
+pub fn subtype(document: &Document, id: ObjectId) -> &str {
+    document
+        .get_dictionary(id)
+        .and_then(|annotation| annotation.get_deref(b"Subtype", document))
+        .and_then(Object::as_name_str)
+        .unwrap_or("")
+}
+
+/// Return whether an annotation is never shown, i.e., whether its
+/// [`Flag::Hidden`] or [`Flag::NoView`] flag is set, as tools often leave
+/// such annotations for their own use.
+#[must_use]
+pub fn is_hidden(document: &Document, id: ObjectId) -> bool {
+    let flags = document
+        .get_dictionary(id)
+        .and_then(|annotation| annotation.get_deref(b"F", document))
+        .and_then(Object::as_i64)
+        .unwrap_or(0);
+    flags & (Flag::Hidden.bit() | Flag::NoView.bit()) != 0
+}
+
+/// Return the number of annotations of each subtype, for each page in
+/// order, except hidden ones if `skip_hidden` is set.
+#[must_use]
+pub fn subtype_counts(document: &Document, skip_hidden: bool) -> Vec<BTreeMap<String, usize>> {
+    let pages: Vec<ObjectId> = document.page_iter().collect();
+    #[cfg(feature = "parallel")]
+    let pages = pages.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let pages = pages.iter();
+    pages
+        .map(|&page_id| {
+            let mut counts = BTreeMap::new();
+            for id in page_annotation_ids(document, page_id) {
+                if document.get_dictionary(id).is_ok() && !(skip_hidden && is_hidden(document, id))
+                {
+                    *counts.entry(subtype(document, id).to_string()).or_insert(0) += 1;
+                }
+            }
+            counts
+        })
+        .collect()
+}
+
+/// Number of annotations of each subtype of a document, as reported by the
+/// bindings and servers.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Stats {
+    /// Counts over all pages.
+    pub total: BTreeMap<String, usize>,
+    /// Counts of each page with annotations, in order.
+    pub pages: Vec<PageStats>,
+}
+
+/// Number of annotations of each subtype of a page.
+#[derive(Clone, Debug, Serialize)]
+pub struct PageStats {
+    /// Page number, from 1.
+    pub page: u32,
+    pub annotations: BTreeMap<String, usize>,
+}
+
+/// Return the number of annotations of each subtype of a document, in total
+/// and for each page with annotations.
+#[must_use]
+pub fn stats(document: &Document) -> Stats {
+    let mut stats = Stats::default();
+    for (page, annotations) in (1..).zip(subtype_counts(document, false)) {
+        if annotations.is_empty() {
+            continue;
+        }
+        for (subtype, count) in &annotations {
+            *stats.total.entry(subtype.clone()).or_default() += count;
+        }
+        stats.pages.push(PageStats { page, annotations });
+    }
+    stats
+}
+
+/// Return the annotations that stripping deletes, as (page number,
+/// annotation ID) pairs, i.e., those whose subtype is not excluded, and that
+/// embed media if `media_only` is set.
+///
+/// References to objects that do not exist are returned too, with an empty
+/// subtype, so that stripping removes them from `/Annots`.
+#[must_use]
+pub fn strip_candidates(
+    document: &Document,
+    exclude: &[String],
+    media_only: bool,
+) -> Vec<(u32, ObjectId)> {
+    let mut candidates = vec![];
+
+    for (page_number, page_id) in document.get_pages() {
+        for id in page_annotation_ids(document, page_id) {
+            let subtype = subtype(document, id);
+            let is_media = MEDIA_SUBTYPES.contains(&subtype);
+            if !exclude.iter().any(|e| subtype == e) && (is_media || !media_only) {
+                candidates.push((page_number, id));
+            }
+        }
+    }
+    candidates
+}
+
+/// Annotation of a page, as read for export.
+#[derive(Clone, Debug, Serialize)]
+pub struct Annotation {
+    /// Object ID of the annotation.
+    #[serde(skip)]
+    pub id: ObjectId,
+    /// Page number.
+    pub page: u32,
+    pub subtype: String,
+    pub author: Option<String>,
+    /// Modification date, e.g., `2024-01-31 12:00`.
+    pub date: Option<String>,
+    pub contents: Option<String>,
+    /// Rectangle of the annotation on the page, in points.
+    pub rect: Option<[f32; 4]>,
+    /// Text that a text markup annotation marks, e.g., the highlighted text,
+    /// once resolved with [`resolve_marked_text`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// Return the annotations of a document, in page order, except those whose
+/// subtype is excluded, and hidden ones if `skip_hidden` is set.
+#[must_use]
+pub fn read_annotations(
+    document: &Document,
+    exclude: &[String],
+    skip_hidden: bool,
+) -> Vec<Annotation> {
+    let mut annotations = vec![];
+
+    for (page, page_id) in document.get_pages() {
+        for id in page_annotation_ids(document, page_id) {
+            let subtype = subtype(document, id);
+            if exclude.iter().any(|e| subtype == e) || (skip_hidden && is_hidden(document, id)) {
+                continue;
+            }
+            annotations.extend(read_annotation(document, page, id));
+        }
+    }
+    annotations
+}
+
+/// Return an annotation of a page, if it is a dictionary.
+#[must_use]
+pub fn read_annotation(document: &Document, page: u32, id: ObjectId) -> Option<Annotation> {
+    let annotation = document.get_dictionary(id).ok()?;
+    let text = |key: &[u8]| {
+        annotation
+            .get_deref(key, document)
+            .and_then(pdfstring::decode_object)
+            .ok()
+    };
+    let rect = annotation
+        .get_deref(b"Rect", document)
+        .and_then(Object::as_array)
+        .ok()
+        .and_then(|rect| {
+            let rect: Vec<f32> = rect.iter().filter_map(number).collect();
+            rect.try_into().ok()
+        });
+
+    Some(Annotation {
+        id,
+        page,
+        subtype: subtype(document, id).to_string(),
+        author: text(b"T"),
+        date: text(b"M").map(|date| pdfstring::display_date(&date)),
+        contents: text(b"Contents"),
+        rect,
+        text: None,
+    })
+}
+
+/// Return the quadrilaterals of a text markup annotation, as four corners
+/// each.
+fn quads(document: &Document, id: ObjectId) -> Vec<[(f32, f32); 4]> {
+    let points: Vec<f32> = document
+        .get_dictionary(id)
+        .and_then(|annotation| annotation.get_deref(b"QuadPoints", document))
+        .and_then(Object::as_array)
+        .map(|points| points.iter().filter_map(number).collect())
+        .unwrap_or_default();
+    points
+        .chunks_exact(8)
+        .map(|quad| {
+            [
+                (quad[0], quad[1]),
+                (quad[2], quad[3]),
+                (quad[4], quad[5]),
+                (quad[6], quad[7]),
+            ]
+        })
+        .collect()
+}
+
+/// Return whether a point is inside a polygon, by ray casting.
+fn contains_point(polygon: &[(f32, f32)], (x, y): (f32, f32)) -> bool {
+    let mut inside = false;
+    for (i, &(x1, y1)) in polygon.iter().enumerate() {
+        let (x2, y2) = polygon[(i + 1) % polygon.len()];
+        if (y1 > y) != (y2 > y) && x < (x2 - x1) * (y - y1) / (y2 - y1) + x1 {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Return whether a point is inside a quadrilateral.
+///
+/// The specification orders corners counterclockwise, but most tools write
+/// the top ones first, so both orders are tried.
+fn quad_contains(&[a, b, c, d]: &[(f32, f32); 4], point: (f32, f32)) -> bool {
+    contains_point(&[a, b, d, c], point) || contains_point(&[a, b, c, d], point)
+}
+
+/// Return the center of a glyph, halfway along its baseline, and a third of
+/// its font size above it.
+fn glyph_center(glyph: &TextSpan) -> (f32, f32) {
+    let (sin, cos) = glyph.angle.to_radians().sin_cos();
+    let (along, above) = (glyph.width / 2.0, glyph.font_size / 3.0);
+    (
+        glyph.x + along * cos - above * sin,
+        glyph.y + along * sin + above * cos,
+    )
+}
+
+/// Return the text of the glyphs whose center is inside any of the
+/// quadrilaterals, in content stream order, with whitespace collapsed.
+fn marked_text(glyphs: &[TextSpan], quads: &[[(f32, f32); 4]]) -> Option<String> {
+    let mut text = String::new();
+    let mut end: Option<(f32, f32)> = None;
+
+    for glyph in glyphs {
+        let center = glyph_center(glyph);
+        if !quads.iter().any(|quad| quad_contains(quad, center)) {
+            continue;
+        }
+        if let Some((x, y)) = end {
+            if (glyph.x - x).hypot(glyph.y - y) > 0.15 * glyph.font_size {
+                text.push(' ');
+            }
+        }
+        text.push_str(&glyph.text);
+        let (sin, cos) = glyph.angle.to_radians().sin_cos();
+        end = Some((glyph.x + glyph.width * cos, glyph.y + glyph.width * sin));
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Resolve the text that text markup annotations mark, e.g., the
+/// highlighted text, by intersecting their quadrilaterals with the glyphs of
+/// their page.
+///
+/// Only pages with text markup annotations are extracted, and pages whose
+/// content cannot be decoded are skipped.
+pub fn resolve_marked_text(document: &Document, annotations: &mut [Annotation]) {
+    let pages = document.get_pages();
+    let mut glyphs: BTreeMap<u32, Vec<TextSpan>> = BTreeMap::new();
+
+    for annotation in annotations {
+        if !TEXT_MARKUP_SUBTYPES.contains(&annotation.subtype.as_str()) {
+            continue;
+        }
+        let quads = quads(document, annotation.id);
+        let Some(&page_id) = pages.get(&annotation.page) else {
+            continue;
+        };
+        if quads.is_empty() {
+            continue;
+        }
+        let glyphs = glyphs
+            .entry(annotation.page)
+            .or_insert_with(|| text::page_glyphs(document, page_id).unwrap_or_default());
+        annotation.text = marked_text(glyphs, &quads);
+    }
+}
+
+/// Number of annotations of a subtype that a revision of a document added
+/// and removed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Changes {
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Return the number of annotations of each subtype that `new` added and
+/// removed compared to `old`, for each page in order, except hidden ones if
+/// `skip_hidden` is set.
+///
+/// Annotations are matched on their page, subtype, author, contents and
+/// rectangle, rounded to hundredths of points, so changing any of them
+/// counts as removing an annotation and adding another one. Modification
+/// dates are ignored, as some tools update them whenever they save files.
+#[must_use]
+pub fn diff_subtype_counts(
+    old: &Document,
+    new: &Document,
+    skip_hidden: bool,
+) -> Vec<BTreeMap<String, Changes>> {
+    let key = |annotation: Annotation| {
+        let rect = annotation
+            .rect
+            .map(|rect| rect.map(|value| format!("{value:.2}")));
+        (
+            annotation.page,
+            annotation.subtype,
+            annotation.author,
+            annotation.contents,
+            rect,
+        )
+    };
+    let mut balance: HashMap<_, isize> = HashMap::new();
+    for annotation in read_annotations(old, &[], skip_hidden) {
+        *balance.entry(key(annotation)).or_default() -= 1;
+    }
+    for annotation in read_annotations(new, &[], skip_hidden) {
+        *balance.entry(key(annotation)).or_default() += 1;
+    }
+
+    let pages = old.get_pages().len().max(new.get_pages().len());
+    let mut changes = vec![BTreeMap::<String, Changes>::new(); pages];
+    for ((page, subtype, ..), count) in balance {
+        let Some(page) = changes.get_mut(page as usize - 1) else {
+            continue;
+        };
+        let entry = page.entry(subtype).or_default();
+        if count > 0 {
+            entry.added += count.unsigned_abs();
+        } else if count < 0 {
+            entry.removed += count.unsigned_abs();
+        }
+    }
+    for page in &mut changes {
+        page.retain(|_, changes| changes.added + changes.removed > 0);
+    }
+    changes
+}
+
+/// Return the objects that annotations use, e.g., appearance streams or
+/// embedded media, without going through pages or other annotations.
+fn annotation_objects(document: &Document, ids: &[ObjectId]) -> BTreeSet<ObjectId> {
+    let mut objects = BTreeSet::new();
+    let mut pending = vec![];
+
+    for &id in ids {
+        if let Ok(annotation) = document.get_object(id) {
+            collect_references(annotation, &mut pending);
+        }
+    }
+    while let Some(id) = pending.pop() {
+        let Ok(object) = document.get_object(id) else {
+            continue;
+        };
+        let (kind, _) = object_types(object);
+        if matches!(kind, Some(b"Page" | b"Pages" | b"Annot"))
+            || ids.contains(&id)
+            || !objects.insert(id)
+        {
+            continue;
+        }
+        collect_references(object, &mut pending);
+    }
+    objects
+}
+
+/// Return the size, in bytes, of the streams that an annotation uses, e.g.,
+/// of the 3D model of a `3D` annotation.
+#[must_use]
+pub fn payload_size(document: &Document, id: ObjectId) -> u64 {
+    annotation_objects(document, &[id])
+        .into_iter()
+        .filter_map(|id| document.get_object(id).ok()?.as_stream().ok())
+        .map(|stream| stream.content.len() as u64)
+        .sum()
+}
+
+/// Delete annotations, and the objects that only they used, e.g., their
+/// appearance streams or embedded media.
+///
+/// Returns the number of other deleted objects, and the size of their
+/// streams in bytes.
+pub fn delete_annotations(document: &mut Document, ids: &[ObjectId]) -> (usize, u64) {
+    let objects = annotation_objects(document, ids);
+
+    for &id in ids {
+        document.delete_object(id);
+    }
+    let reachable: BTreeSet<ObjectId> = document.traverse_objects(|_| {}).into_iter().collect();
+    let (mut count, mut bytes) = (0, 0);
+
+    for id in objects.difference(&reachable) {
+        if let Some(object) = document.objects.remove(id) {
+            count += 1;
+            if let Object::Stream(stream) = object {
+                bytes += stream.content.len() as u64;
+            }
+        }
+    }
+    (count, bytes)
+}
+
+/// Annotation flags, stored as bits of the `/F` entry of annotations.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Flag {
+    /// Hide the annotation if no handler supports its subtype.
+    Invisible,
+    /// Never show nor print the annotation.
+    Hidden,
+    /// Print the annotation.
+    Print,
+    /// Do not scale the annotation with the page.
+    NoZoom,
+    /// Do not rotate the annotation with the page.
+    NoRotate,
+    /// Do not show the annotation, but still print it if [`Flag::Print`] is
+    /// set.
+    NoView,
+    /// Do not let users interact with the annotation.
+    ReadOnly,
+    /// Do not let users move, resize or delete the annotation.
+    Locked,
+    /// Invert [`Flag::NoView`] on some events, e.g., hovering.
+    ToggleNoView,
+    /// Do not let users edit the contents of the annotation.
+    LockedContents,
+}
+
+impl Flag {
+    /// Return the bit of the flag in the `/F` entry.
+    #[must_use]
+    pub fn bit(self) -> i64 {
+        1 << (self as u32)
+    }
+}
+
+/// Set and clear flags of an annotation, and return whether its `/F` entry
+/// changed.
+///
+/// Cleared flags win over set ones.
+pub fn update_flags(annotation: &mut Dictionary, set: &[Flag], clear: &[Flag]) -> bool {
+    let flags = annotation.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+    let set = set.iter().fold(0, |bits, flag| bits | flag.bit());
+    let clear = clear.iter().fold(0, |bits, flag| bits | flag.bit());
+    let updated = (flags | set) & !clear;
+
+    if updated != flags {
+        annotation.set("F", updated);
+    }
+    updated != flags
+}
+
+/// Builder of annotations, which are added to pages with
+/// [`add_to_page`](Self::add_to_page).
+#[derive(Clone, Debug)]
+pub struct AnnotationBuilder {
+    dict: Dictionary,
+    appearance: Option<Stream>,
+}
+
+impl AnnotationBuilder {
+    /// Start an annotation of the given subtype, covering a rectangle of the
+    /// page, as `[x0, y0, x1, y1]`. Annotations are printed by default.
+    #[must_use]
+    pub fn new(subtype: &str, rect: [f32; 4]) -> Self {
+        Self {
+            dict: dictionary! {
+                "Type" => "Annot",
+                "Subtype" => Object::Name(subtype.as_bytes().to_vec()),
+                "Rect" => rect.map(Object::Real).to_vec(),
+                "F" => PRINT_FLAG,
+            },
+            appearance: None,
+        }
+    }
+
+    /// Set the text shown by the annotation, or in its pop-up.
+    #[must_use]
+    pub fn contents(mut self, contents: &str) -> Self {
+        self.dict
+            .set("Contents", pdfstring::encode_object(contents));
+        self
+    }
+
+    /// Set the author of the annotation.
+    #[must_use]
+    pub fn author(mut self, author: &str) -> Self {
+        self.dict.set("T", pdfstring::encode_object(author));
+        self
+    }
+
+    /// Set the creation and modification dates, as a PDF date.
+    #[must_use]
+    pub fn date(mut self, date: &str) -> Self {
+        self.dict.set("M", Object::string_literal(date));
+        self.dict.set("CreationDate", Object::string_literal(date));
+        self
+    }
+
+    /// Set the name of the annotation's icon, e.g., `Approved` for stamps.
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        self.dict
+            .set("Name", Object::Name(name.as_bytes().to_vec()));
+        self
+    }
+
+    /// Set the color of the annotation, in RGB.
+    #[must_use]
+    pub fn color(mut self, color: [f32; 3]) -> Self {
+        self.dict.set("C", color.map(Object::Real).to_vec());
+        self
+    }
+
+    /// Set whether the pop-up of the annotation is initially open.
+    #[must_use]
+    pub fn open(mut self, open: bool) -> Self {
+        self.dict.set("Open", open);
+        self
+    }
+
+    /// Set the default appearance of the text of the annotation, as content
+    /// stream operators, e.g., `/Helv 12 Tf 0 g`.
+    #[must_use]
+    pub fn default_appearance(mut self, appearance: &str) -> Self {
+        self.dict.set("DA", Object::string_literal(appearance));
+        self
+    }
+
+    /// Set the width of the border of the annotation, in points.
+    #[must_use]
+    pub fn border_width(mut self, width: f32) -> Self {
+        self.dict.set("BS", dictionary! { "W" => width });
+        self
+    }
+
+    /// Put the annotation on a layer, i.e., an optional content group, so
+    /// that viewers can hide it.
+    #[must_use]
+    pub fn layer(mut self, layer_id: ObjectId) -> Self {
+        self.dict.set("OC", layer_id);
+        self
+    }
+
+    /// Set the normal appearance of the annotation, i.e., a form XObject
+    /// drawn in its rectangle.
+    #[must_use]
+    pub fn appearance(mut self, appearance: Stream) -> Self {
+        self.appearance = Some(appearance);
+        self
+    }
+
+    /// Add the annotation to a page, and return its ID.
+    pub fn add_to_page(
+        self,
+        document: &mut Document,
+        page_id: ObjectId,
+    ) -> lopdf::Result<ObjectId> {
+        let mut dict = self.dict;
+        dict.set("P", page_id);
+        if let Some(appearance) = self.appearance {
+            let appearance_id = document.add_object(appearance);
+            dict.set("AP", dictionary! { "N" => appearance_id });
+        }
+        let id = document.add_object(dict);
+
+        let annots = match document.get_dictionary(page_id)?.get(b"Annots") {
+            Ok(Object::Reference(annots_id)) => Some(*annots_id),
+            _ => None,
+        };
+        match annots {
+            Some(annots_id) => {
+                document
+                    .get_object_mut(annots_id)
+                    .and_then(Object::as_array_mut)?
+                    .push(Object::Reference(id));
+            },
+            None => {
+                let page = document.get_dictionary_mut(page_id)?;
+                match page.get_mut(b"Annots").and_then(Object::as_array_mut) {
+                    Ok(annots) => annots.push(Object::Reference(id)),
+                    Err(_) => page.set("Annots", vec![Object::Reference(id)]),
+                }
+            },
+        }
+        Ok(id)
+    }
+}
+
+/// Return the appearance of a free-text annotation, i.e., its lines of text
+/// in Helvetica with a thin frame, and its width and height.
+#[must_use]
+pub fn free_text_appearance(text: &str, size: f32) -> (Stream, f32, f32) {
+    const PADDING: f32 = 4.0;
+
+    let font = StandardFont::Helvetica;
+    let lines: Vec<&str> = text.lines().collect();
+    let leading = 1.2 * size;
+    let width = lines
+        .iter()
+        .map(|line| font.text_width(line, size))
+        .fold(0.0, f32::max)
+        + 2.0 * PADDING;
+    let height = leading * lines.len().max(1) as f32 + 2.0 * PADDING;
+
+    let mut operations = vec![
+        Operation::new("q", vec![]),
+        Operation::new("G", vec![0.into()]),
+        Operation::new(
+            "re",
+            vec![
+                0.5.into(),
+                0.5.into(),
+                (width - 1.0).into(),
+                (height - 1.0).into(),
+            ],
+        ),
+        Operation::new("S", vec![]),
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["F1".into(), size.into()]),
+        Operation::new("TL", vec![leading.into()]),
+        Operation::new("g", vec![0.into()]),
+        // The first baseline is placed below the top of the frame by about
+        // the ascent of the font.
+        Operation::new(
+            "Td",
+            vec![PADDING.into(), (height - PADDING - 0.8 * size).into()],
+        ),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            operations.push(Operation::new("T*", vec![]));
+        }
+        operations.push(Operation::new("Tj", vec![fonts::win_ansi_string(line)]));
+    }
+    operations.push(Operation::new("ET", vec![]));
+    operations.push(Operation::new("Q", vec![]));
+
+    let stream = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+            "Resources" => dictionary! {
+                "Font" => dictionary! { "F1" => font.dictionary() },
+            },
+        },
+        Content { operations }.encode().unwrap_or_default(),
+    );
+    (stream, width, height)
+}
+
+/// Shapes of markup annotations, in page coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// Rectangle, as `[x0, y0, x1, y1]`, drawn as a `Square` annotation.
+    Rectangle([f32; 4]),
+    /// Polyline, drawn as an `Ink` annotation.
+    Polyline(Vec<[f32; 2]>),
+}
+
+impl Shape {
+    /// Return the builder of an annotation drawing the shape with the given
+    /// color and line width, with its appearance.
+    #[must_use]
+    pub fn annotation(&self, color: [f32; 3], width: f32) -> AnnotationBuilder {
+        let half = width / 2.0;
+        let (subtype, rect, points, closed) = match self {
+            Self::Rectangle([x0, y0, x1, y1]) => {
+                let (x0, x1) = (x0.min(*x1), x0.max(*x1));
+                let (y0, y1) = (y0.min(*y1), y0.max(*y1));
+                // The border is drawn inside of the rectangle.
+                let points = vec![
+                    [x0 + half, y0 + half],
+                    [x1 - half, y0 + half],
+                    [x1 - half, y1 - half],
+                    [x0 + half, y1 - half],
+                ];
+                ("Square", [x0, y0, x1, y1], points, true)
+            },
+            Self::Polyline(points) => {
+                let rect = points.iter().fold(
+                    [f32::MAX, f32::MAX, f32::MIN, f32::MIN],
+                    |[x0, y0, x1, y1], &[x, y]| [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+                );
+                // The line may overflow its points by half of its width.
+                let rect = [
+                    rect[0] - half,
+                    rect[1] - half,
+                    rect[2] + half,
+                    rect[3] + half,
+                ];
+                ("Ink", rect, points.clone(), false)
+            },
+        };
+
+        let [r, g, b] = color.map(Object::Real);
+        let mut operations = vec![
+            Operation::new("q", vec![]),
+            Operation::new("RG", vec![r, g, b]),
+            Operation::new("w", vec![width.into()]),
+            Operation::new("J", vec![1.into()]),
+            Operation::new("j", vec![1.into()]),
+        ];
+        for (i, [x, y]) in points.iter().enumerate() {
+            let operator = if i == 0 { "m" } else { "l" };
+            operations.push(Operation::new(operator, vec![(*x).into(), (*y).into()]));
+        }
+        operations.push(Operation::new(if closed { "s" } else { "S" }, vec![]));
+        operations.push(Operation::new("Q", vec![]));
+        // The bounding box is the rectangle of the annotation, so that the
+        // appearance is drawn in page coordinates.
+        let appearance = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => rect.map(Object::Real).to_vec(),
+            },
+            Content { operations }.encode().unwrap_or_default(),
+        );
+
+        let mut builder = AnnotationBuilder::new(subtype, rect)
+            .color(color)
+            .border_width(width)
+            .appearance(appearance);
+        if let Self::Polyline(points) = self {
+            let path: Vec<Object> = points
+                .iter()
+                .flat_map(|&[x, y]| [Object::Real(x), Object::Real(y)])
+                .collect();
+            builder.dict.set("InkList", vec![Object::Array(path)]);
+        }
+        builder
+    }
+}
+
+/// Standard rubber stamps, which viewers know the icons of.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stamp {
+    Approved,
+    AsIs,
+    Confidential,
+    Departmental,
+    Draft,
+    Experimental,
+    Expired,
+    Final,
+    ForComment,
+    ForPublicRelease,
+    NotApproved,
+    NotForPublicRelease,
+    Sold,
+    TopSecret,
+}
+
+impl Stamp {
+    /// Return the name of the stamp's icon.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Approved => "Approved",
+            Self::AsIs => "AsIs",
+            Self::Confidential => "Confidential",
+            Self::Departmental => "Departmental",
+            Self::Draft => "Draft",
+            Self::Experimental => "Experimental",
+            Self::Expired => "Expired",
+            Self::Final => "Final",
+            Self::ForComment => "ForComment",
+            Self::ForPublicRelease => "ForPublicRelease",
+            Self::NotApproved => "NotApproved",
+            Self::NotForPublicRelease => "NotForPublicRelease",
+            Self::Sold => "Sold",
+            Self::TopSecret => "TopSecret",
+        }
+    }
+
+    /// Return the text printed on the stamp.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Approved => "APPROVED",
+            Self::AsIs => "AS IS",
+            Self::Confidential => "CONFIDENTIAL",
+            Self::Departmental => "DEPARTMENTAL",
+            Self::Draft => "DRAFT",
+            Self::Experimental => "EXPERIMENTAL",
+            Self::Expired => "EXPIRED",
+            Self::Final => "FINAL",
+            Self::ForComment => "FOR COMMENT",
+            Self::ForPublicRelease => "FOR PUBLIC RELEASE",
+            Self::NotApproved => "NOT APPROVED",
+            Self::NotForPublicRelease => "NOT FOR PUBLIC RELEASE",
+            Self::Sold => "SOLD",
+            Self::TopSecret => "TOP SECRET",
+        }
+    }
+
+    /// Return the color of the stamp, in RGB: green for stamps that clear a
+    /// document, red for those that restrict it, and blue otherwise.
+    #[must_use]
+    pub fn color(self) -> [f32; 3] {
+        match self {
+            Self::Approved | Self::Final | Self::ForPublicRelease | Self::Sold => [0.1, 0.5, 0.1],
+            Self::Confidential
+            | Self::Expired
+            | Self::NotApproved
+            | Self::NotForPublicRelease
+            | Self::TopSecret => [0.75, 0.1, 0.1],
+            _ => [0.1, 0.2, 0.6],
+        }
+    }
+
+    /// Return the appearance of the stamp, i.e., its label in a frame, and
+    /// its width and height.
+    #[must_use]
+    pub fn appearance(self) -> (Stream, f32, f32) {
+        const SIZE: f32 = 20.0;
+        const PADDING: f32 = 12.0;
+        const LINE_WIDTH: f32 = 3.0;
+
+        let font = StandardFont::HelveticaBold;
+        let label = self.label();
+        let width = font.text_width(label, SIZE) + 2.0 * PADDING;
+        let height = SIZE + PADDING;
+        let [r, g, b] = self.color().map(Object::Real);
+
+        let operations = vec![
+            Operation::new("q", vec![]),
+            Operation::new("RG", vec![r.clone(), g.clone(), b.clone()]),
+            Operation::new("w", vec![LINE_WIDTH.into()]),
+            Operation::new(
+                "re",
+                vec![
+                    (LINE_WIDTH / 2.0).into(),
+                    (LINE_WIDTH / 2.0).into(),
+                    (width - LINE_WIDTH).into(),
+                    (height - LINE_WIDTH).into(),
+                ],
+            ),
+            Operation::new("S", vec![]),
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), SIZE.into()]),
+            Operation::new("rg", vec![r, g, b]),
+            // The baseline is placed so that capitals are centered.
+            Operation::new(
+                "Td",
+                vec![PADDING.into(), ((height - 0.72 * SIZE) / 2.0).into()],
+            ),
+            Operation::new("Tj", vec![fonts::win_ansi_string(label)]),
+            Operation::new("ET", vec![]),
+            Operation::new("Q", vec![]),
+        ];
+        let stream = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font.dictionary() },
+                },
+            },
+            Content { operations }.encode().unwrap_or_default(),
+        );
+        (stream, width, height)
+    }
+}
@@ -0,0 +1,235 @@
+//! Assembly of a document from the pages of other documents, e.g., a report
+//! made of a cover, selected chapters and appendices.
+
+use std::collections::{BTreeMap, HashSet, btree_map::Entry};
+
+use lopdf::{Dictionary, Document, Object, ObjectId, dictionary};
+
+use super::{
+    dedup::Deduplicator,
+    document::DocumentExt,
+    import::{self, IdMapping},
+    pdfstring,
+    split::INHERITABLE_ATTRIBUTES,
+};
+
+/// Page of a source document, placed in the assembled document.
+#[derive(Clone, Copy, Debug)]
+pub struct Placement {
+    /// Index of the source document.
+    pub source: usize,
+    /// Page number in the source document.
+    pub page: u32,
+    /// Clockwise rotation added to the page, in degrees, a multiple of 90.
+    pub rotation: i64,
+}
+
+/// Consecutive pages of the assembled document.
+#[derive(Clone, Debug, Default)]
+pub struct Section {
+    /// Title of the bookmark pointing to the first page of the section, if
+    /// any.
+    pub bookmark: Option<String>,
+    pub placements: Vec<Placement>,
+}
+
+/// Return the placements of several sequences taken in turn, one from each
+/// sequence, e.g., the fronts and backs of sheets scanned separately.
+///
+/// Once a sequence runs out, the others go on.
+#[must_use]
+pub fn interleave(sequences: Vec<Vec<Placement>>) -> Vec<Placement> {
+    let mut queues: Vec<_> = sequences.into_iter().map(Vec::into_iter).collect();
+    let mut placements = vec![];
+
+    loop {
+        let before = placements.len();
+        placements.extend(queues.iter_mut().filter_map(Iterator::next));
+        if placements.len() == before {
+            return placements;
+        }
+    }
+}
+
+/// Move the given pages of a source document into the target document, and
+/// return the mapping of their object IDs.
+///
+/// Pages are detached from the source page tree, keeping the attributes they
+/// inherit from it. Other pages are not imported, so links to them become
+/// `null`, and neither are the outline and the article threads of the source.
+fn import_pages(target: &mut Document, mut source: Document, pages: &HashSet<u32>) -> IdMapping {
+    let all_pages = source.get_pages();
+    let mut roots = vec![];
+
+    for (number, &page_id) in &all_pages {
+        if !pages.contains(number) {
+            continue;
+        }
+        let inherited: Vec<(&[u8], Object)> = INHERITABLE_ATTRIBUTES
+            .iter()
+            .filter_map(|&key| Some((key, source.page_attribute(page_id, key)?.clone())))
+            .collect();
+        if let Ok(page) = source.get_dictionary_mut(page_id) {
+            for (key, value) in inherited {
+                if !page.has(key) {
+                    page.set(key, value);
+                }
+            }
+            page.remove(b"Parent");
+            page.remove(b"B");
+            roots.push(page_id);
+        }
+    }
+
+    // Objects missing from the source are not followed, so references to
+    // the page tree and to other pages are dropped.
+    source.objects.retain(|id, object| {
+        let kind = object
+            .as_dict()
+            .and_then(|dict| dict.get(b"Type"))
+            .and_then(Object::as_name)
+            .ok();
+        kind != Some(b"Pages") && (kind != Some(b"Page") || roots.contains(id))
+    });
+    import::import_objects(target, &mut source, &roots, IdMapping::new())
+}
+
+/// Add a flat outline with one bookmark per title, pointing to the given
+/// page, and return the ID of its root.
+fn add_outline(document: &mut Document, bookmarks: &[(String, ObjectId)]) -> ObjectId {
+    let outline_id = document.new_object_id();
+    let item_ids: Vec<ObjectId> = bookmarks.iter().map(|_| document.new_object_id()).collect();
+
+    for (i, (&id, (title, page_id))) in item_ids.iter().zip(bookmarks).enumerate() {
+        let mut item = dictionary! {
+            "Title" => pdfstring::encode_object(title),
+            "Parent" => outline_id,
+            "Dest" => vec![(*page_id).into(), "Fit".into()],
+        };
+        if let Some(&previous) = i.checked_sub(1).and_then(|i| item_ids.get(i)) {
+            item.set("Prev", previous);
+        }
+        if let Some(&next) = item_ids.get(i + 1) {
+            item.set("Next", next);
+        }
+        document.objects.insert(id, Object::Dictionary(item));
+    }
+
+    document.objects.insert(
+        outline_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Outlines",
+            "First" => item_ids[0],
+            "Last" => item_ids[item_ids.len() - 1],
+            "Count" => item_ids.len() as i64,
+        }),
+    );
+    outline_id
+}
+
+/// Assemble a document from the sections, whose pages are taken from the
+/// source documents.
+///
+/// A page placed several times is copied, sharing its content and resources,
+/// but not its annotations, which belong to a single page. The outline of
+/// the document has one bookmark per titled section, and the outlines, forms
+/// and article threads of the sources are dropped. Objects that several
+/// sources hold, e.g., fonts, are deduplicated as the sources are imported.
+#[must_use]
+pub fn assemble(sources: Vec<Document>, sections: &[Section]) -> Document {
+    let version = sources
+        .iter()
+        .map(|source| source.version.clone())
+        .max()
+        .unwrap_or_else(|| "1.7".into());
+    let mut document = Document::with_version(version);
+    let pages_id = document.new_object_id();
+
+    let mut used: BTreeMap<usize, HashSet<u32>> = BTreeMap::new();
+    for placement in sections.iter().flat_map(|section| &section.placements) {
+        used.entry(placement.source)
+            .or_default()
+            .insert(placement.page);
+    }
+    let mut mappings = BTreeMap::new();
+    let mut deduplicator = Deduplicator::default();
+    for (index, source) in sources.into_iter().enumerate() {
+        let Some(pages) = used.get(&index) else {
+            continue;
+        };
+        let page_ids = source.get_pages();
+        let first = document.max_id + 1;
+        let mapping = import_pages(&mut document, source, pages);
+        deduplicator.deduplicate(&mut document, first);
+        let imported: BTreeMap<u32, ObjectId> = page_ids
+            .into_iter()
+            .filter_map(|(page, id)| Some((page, *mapping.get(&id)?)))
+            .collect();
+        mappings.insert(index, imported);
+    }
+
+    let mut kids = vec![];
+    // Rotations of the imported pages, before sections rotate them.
+    let mut rotations = BTreeMap::new();
+    let mut bookmarks = vec![];
+    for section in sections {
+        let mut first = None;
+        for placement in &section.placements {
+            let Some(&imported_id) = mappings
+                .get(&placement.source)
+                .and_then(|pages| pages.get(&placement.page))
+            else {
+                continue;
+            };
+            let page_id = if let Entry::Vacant(entry) = rotations.entry(imported_id) {
+                entry.insert(
+                    document
+                        .get_dictionary(imported_id)
+                        .and_then(|page| page.get(b"Rotate"))
+                        .and_then(Object::as_i64)
+                        .unwrap_or(0),
+                );
+                imported_id
+            } else {
+                let mut copy = document
+                    .get_dictionary(imported_id)
+                    .cloned()
+                    .unwrap_or_else(|_| Dictionary::new());
+                copy.remove(b"Annots");
+                document.add_object(copy)
+            };
+            if let Ok(page) = document.get_dictionary_mut(page_id) {
+                page.set("Parent", pages_id);
+                if placement.rotation != 0 || page.has(b"Rotate") {
+                    let rotation = rotations[&imported_id] + placement.rotation;
+                    page.set("Rotate", rotation.rem_euclid(360));
+                }
+            }
+            first.get_or_insert(page_id);
+            kids.push(Object::Reference(page_id));
+        }
+        if let (Some(title), Some(page_id)) = (&section.bookmark, first) {
+            bookmarks.push((title.clone(), page_id));
+        }
+    }
+
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    let mut catalog = dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    };
+    if !bookmarks.is_empty() {
+        catalog.set("Outlines", add_outline(&mut document, &bookmarks));
+        catalog.set("PageMode", "UseOutlines");
+    }
+    let catalog_id = document.add_object(catalog);
+    document.trailer.set("Root", catalog_id);
+    document
+}
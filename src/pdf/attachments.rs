@@ -0,0 +1,235 @@
+//! Files embedded in documents, i.e., attachments.
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, StringFormat, dictionary};
+
+use super::pdfstring;
+
+/// Maximum depth of name trees, which guards against malformed documents.
+const MAX_DEPTH: usize = 64;
+
+/// File embedded in a document.
+#[derive(Clone, Debug)]
+pub struct Attachment {
+    /// Name of the file.
+    pub name: String,
+    pub description: Option<String>,
+    /// MIME type of the file, e.g., `text/xml`.
+    pub mime_type: Option<String>,
+    /// Modification date of the file, as a PDF date.
+    pub date: Option<String>,
+    /// Decoded content of the file.
+    pub data: Vec<u8>,
+}
+
+/// Options of [`attach`].
+#[derive(Clone, Debug, Default)]
+pub struct NewAttachment<'a> {
+    /// Name of the file.
+    pub name: &'a str,
+    pub description: Option<&'a str>,
+    /// MIME type of the file, e.g., `text/xml`.
+    pub mime_type: Option<&'a str>,
+    /// Relationship of the file to the document, e.g., `Data` or `Source`,
+    /// which makes the file associated with the document.
+    pub relationship: Option<&'a str>,
+    /// Modification date of the file, as a PDF date.
+    pub date: Option<&'a str>,
+}
+
+/// Collect the entries of a name tree, in order.
+fn name_tree_entries(
+    document: &Document,
+    node: &Dictionary,
+    entries: &mut Vec<(Vec<u8>, Object)>,
+    depth: usize,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    if let Ok(names) = node
+        .get_deref(b"Names", document)
+        .and_then(Object::as_array)
+    {
+        for pair in names.chunks_exact(2) {
+            if let Ok(key) = pair[0].as_str() {
+                entries.push((key.to_vec(), pair[1].clone()));
+            }
+        }
+    }
+    let Ok(kids) = node.get_deref(b"Kids", document).and_then(Object::as_array) else {
+        return;
+    };
+    for kid in kids {
+        if let Ok((_, Object::Dictionary(kid))) = document.dereference(kid) {
+            name_tree_entries(document, kid, entries, depth + 1);
+        }
+    }
+}
+
+/// Return the entries of the `EmbeddedFiles` name tree, i.e., names and file
+/// specifications.
+fn embedded_files(document: &Document) -> Vec<(Vec<u8>, Object)> {
+    let mut entries = vec![];
+
+    if let Ok(tree) = document
+        .catalog()
+        .and_then(|catalog| catalog.get_deref(b"Names", document))
+        .and_then(Object::as_dict)
+        .and_then(|names| names.get_deref(b"EmbeddedFiles", document))
+        .and_then(Object::as_dict)
+    {
+        name_tree_entries(document, tree, &mut entries, 0);
+    }
+    entries
+}
+
+/// Read an attachment from its file specification.
+fn read_attachment(document: &Document, key: &[u8], filespec: &Dictionary) -> Option<Attachment> {
+    let string = |key: &[u8]| {
+        filespec
+            .get_deref(key, document)
+            .and_then(Object::as_str)
+            .ok()
+            .map(pdfstring::decode)
+    };
+    let ef = filespec
+        .get_deref(b"EF", document)
+        .and_then(Object::as_dict)
+        .ok()?;
+    let stream = [b"UF".as_slice(), b"F"]
+        .iter()
+        .find_map(|key| ef.get_deref(key, document).and_then(Object::as_stream).ok())?;
+
+    Some(Attachment {
+        name: string(b"UF")
+            .or_else(|| string(b"F"))
+            .unwrap_or_else(|| pdfstring::decode(key)),
+        description: string(b"Desc"),
+        mime_type: stream
+            .dict
+            .get(b"Subtype")
+            .and_then(Object::as_name)
+            .ok()
+            .map(|name| String::from_utf8_lossy(name).into_owned()),
+        date: stream
+            .dict
+            .get_deref(b"Params", document)
+            .and_then(Object::as_dict)
+            .and_then(|params| params.get_deref(b"ModDate", document))
+            .and_then(Object::as_str)
+            .ok()
+            .map(pdfstring::decode),
+        data: stream
+            .decompressed_content()
+            .unwrap_or_else(|_| stream.content.clone()),
+    })
+}
+
+/// Return the files embedded in a document, in the order of their names.
+#[must_use]
+pub fn attachments(document: &Document) -> Vec<Attachment> {
+    embedded_files(document)
+        .iter()
+        .filter_map(|(key, filespec)| {
+            let filespec = document.dereference(filespec).ok()?.1.as_dict().ok()?;
+            read_attachment(document, key, filespec)
+        })
+        .collect()
+}
+
+/// Embed a file in a document, replacing any attachment of the same name,
+/// and return the identifier of its file specification.
+///
+/// Files with a relationship are also listed as associated files (`/AF`) of
+/// the catalog, as PDF/A-3 and PDF 2.0 require.
+pub fn attach(
+    document: &mut Document,
+    data: Vec<u8>,
+    attachment: &NewAttachment,
+) -> lopdf::Result<ObjectId> {
+    let mut params = dictionary! { "Size" => data.len() as i64 };
+    if let Some(date) = attachment.date {
+        params.set("ModDate", Object::string_literal(date));
+    }
+    let mut file = Stream::new(
+        dictionary! { "Type" => "EmbeddedFile", "Params" => params },
+        data,
+    );
+    if let Some(mime_type) = attachment.mime_type {
+        file.dict
+            .set("Subtype", Object::Name(mime_type.as_bytes().to_vec()));
+    }
+    let _ = file.compress();
+    let file_id = document.add_object(file);
+
+    let mut filespec = dictionary! {
+        "Type" => "Filespec",
+        "F" => pdfstring::encode_object(attachment.name),
+        "UF" => pdfstring::encode_object(attachment.name),
+        "EF" => dictionary! { "F" => file_id, "UF" => file_id },
+    };
+    if let Some(description) = attachment.description {
+        filespec.set("Desc", pdfstring::encode_object(description));
+    }
+    if let Some(relationship) = attachment.relationship {
+        filespec.set(
+            "AFRelationship",
+            Object::Name(relationship.as_bytes().to_vec()),
+        );
+    }
+    let filespec_id = document.add_object(filespec);
+
+    // The name tree is written again as a single node, sorted by name.
+    let key = pdfstring::encode(attachment.name);
+    let mut entries = embedded_files(document);
+    let replaced: Vec<Object> = entries
+        .iter()
+        .filter(|(name, _)| *name == key)
+        .map(|(_, filespec)| filespec.clone())
+        .collect();
+    entries.retain(|(name, _)| *name != key);
+    entries.push((key, Object::Reference(filespec_id)));
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let names: Vec<Object> = entries
+        .into_iter()
+        .flat_map(|(name, filespec)| [Object::String(name, StringFormat::Literal), filespec])
+        .collect();
+
+    let mut associated: Vec<Object> = document
+        .catalog()?
+        .get_deref(b"AF", document)
+        .and_then(Object::as_array)
+        .cloned()
+        .unwrap_or_default();
+    associated.retain(|filespec| !replaced.contains(filespec));
+    if attachment.relationship.is_some() {
+        associated.push(Object::Reference(filespec_id));
+    }
+
+    let tree_id = document.add_object(dictionary! { "Names" => names });
+    let catalog = document.catalog_mut()?;
+    if !catalog.has(b"Names") {
+        catalog.set("Names", Dictionary::new());
+    }
+    // The names dictionary is usually inline, but may be referenced.
+    match catalog.get(b"Names") {
+        Ok(Object::Reference(id)) => {
+            let id = *id;
+            document
+                .get_dictionary_mut(id)?
+                .set("EmbeddedFiles", tree_id);
+        },
+        _ => {
+            catalog
+                .get_mut(b"Names")?
+                .as_dict_mut()?
+                .set("EmbeddedFiles", tree_id);
+        },
+    }
+    if !associated.is_empty() {
+        document.catalog_mut()?.set("AF", associated);
+    }
+    // Replaced attachments and name tree nodes are no longer referenced.
+    document.prune_objects();
+    Ok(filespec_id)
+}
@@ -0,0 +1,246 @@
+//! Detection of Code 128 barcodes in scanned pages, e.g., cover sheets that
+//! route documents in intake pipelines.
+//!
+//! Rows and columns of the page image are binarized and read as sequences of
+//! bar and space widths, which are matched against the patterns of the
+//! symbology. A barcode is reported once its start, check and stop symbols
+//! are all found.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Document, ObjectId};
+use serde::Serialize;
+
+use super::images;
+
+/// Widths of the bars and spaces of Code 128 symbols, in modules, indexed by
+/// their value. The stop symbol, 106, is followed by a final bar of two
+/// modules.
+const PATTERNS: [&[u8; 6]; 107] = [
+    b"212222", b"222122", b"222221", b"121223", b"121322", b"131222", b"122213", b"122312",
+    b"132212", b"221213", b"221312", b"231212", b"112232", b"122132", b"122231", b"113222",
+    b"123122", b"123221", b"223211", b"221132", b"221231", b"213212", b"223112", b"312131",
+    b"311222", b"321122", b"321221", b"312212", b"322112", b"322211", b"212123", b"212321",
+    b"232121", b"111323", b"131123", b"131321", b"112313", b"132113", b"132311", b"211313",
+    b"231113", b"231311", b"112133", b"112331", b"132131", b"113123", b"113321", b"133121",
+    b"313121", b"211331", b"231131", b"213113", b"213311", b"213131", b"311123", b"311321",
+    b"331121", b"312113", b"312311", b"332111", b"314111", b"221411", b"431111", b"111224",
+    b"111422", b"121124", b"121421", b"141122", b"141221", b"112214", b"112412", b"122114",
+    b"122411", b"142112", b"142211", b"241211", b"221114", b"413111", b"241112", b"134111",
+    b"111242", b"121142", b"121241", b"114212", b"124112", b"124211", b"411212", b"421112",
+    b"421211", b"212141", b"214121", b"412121", b"111143", b"111341", b"131141", b"114113",
+    b"114311", b"411113", b"411311", b"113141", b"114131", b"311141", b"411131", b"211412",
+    b"211214", b"211232", b"233111",
+];
+
+/// Value of the start symbol of code set A; B and C follow.
+const START_A: usize = 103;
+
+/// Value of the stop symbol.
+const STOP: usize = 106;
+
+/// Minimum width of the quiet zone before a barcode, in modules.
+///
+/// The standard requires ten modules, but scans are often cropped close to
+/// the code.
+const QUIET_ZONE: f32 = 3.0;
+
+/// Minimum difference between the darkest and lightest pixels of a row for
+/// it to be read, so that blank rows are skipped.
+const MIN_CONTRAST: u8 = 64;
+
+/// Barcode found on a page.
+#[derive(Clone, Debug, Serialize)]
+pub struct Barcode {
+    /// Page number.
+    pub page: u32,
+    /// Symbology, e.g., `code128`.
+    pub format: &'static str,
+    /// Decoded payload.
+    pub data: String,
+}
+
+/// Code set of Code 128, which determines what data symbols encode.
+#[derive(Clone, Copy, PartialEq)]
+enum CodeSet {
+    /// Uppercase letters, digits and control characters.
+    A,
+    /// Printable ASCII characters.
+    B,
+    /// Pairs of digits.
+    C,
+}
+
+/// Return the value of the symbol made of six bars and spaces, whose widths
+/// are in pixels.
+fn symbol(widths: &[usize]) -> Option<usize> {
+    let total: usize = widths.iter().sum();
+    let module = total as f32 / 11.0;
+    let mut modules = [0u8; 6];
+
+    for (module_width, &width) in modules.iter_mut().zip(widths) {
+        let count = (width as f32 / module).round().clamp(1.0, 4.0) as u8;
+        *module_width = b'0' + count;
+    }
+    PATTERNS.iter().position(|pattern| **pattern == modules)
+}
+
+/// Return the text encoded by data symbols, after the given start symbol.
+///
+/// Function codes, e.g., `FNC1` in GS1 barcodes, are skipped.
+fn text(start: usize, values: &[usize]) -> Option<String> {
+    let mut set = match start - START_A {
+        0 => CodeSet::A,
+        1 => CodeSet::B,
+        _ => CodeSet::C,
+    };
+    let mut shift = false;
+    let mut text = String::new();
+
+    for &value in values {
+        // A shift changes between sets A and B for a single symbol.
+        let current = match (shift, set) {
+            (true, CodeSet::A) => CodeSet::B,
+            (true, CodeSet::B) => CodeSet::A,
+            _ => set,
+        };
+        shift = false;
+
+        match (current, value) {
+            (CodeSet::C, 0..=99) => text.push_str(&format!("{value:02}")),
+            (CodeSet::C, 100) => set = CodeSet::B,
+            (CodeSet::C, 101) => set = CodeSet::A,
+            (CodeSet::A, 0..=63) | (CodeSet::B, 0..=95) => text.push(char::from(32 + value as u8)),
+            (CodeSet::A, 64..=95) => text.push(char::from(value as u8 - 64)),
+            (CodeSet::A | CodeSet::B, 98) => shift = true,
+            (CodeSet::A | CodeSet::B, 99) => set = CodeSet::C,
+            (CodeSet::A, 100) => set = CodeSet::B,
+            (CodeSet::B, 101) => set = CodeSet::A,
+            (_, 96..=102) => {},
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+/// Decode the barcode whose start symbol begins at the given run, from the
+/// widths of alternating dark and light runs of pixels.
+fn decode_at(runs: &[usize], start_run: usize) -> Option<String> {
+    let start = symbol(runs.get(start_run..start_run + 6)?)?;
+    if !(START_A..STOP).contains(&start) {
+        return None;
+    }
+    let module = runs[start_run..start_run + 6].iter().sum::<usize>() as f32 / 11.0;
+    if start_run > 0 && (runs[start_run - 1] as f32) < QUIET_ZONE * module {
+        return None;
+    }
+
+    let mut values = vec![start];
+    let mut run = start_run + 6;
+    loop {
+        let value = symbol(runs.get(run..run + 6)?)?;
+        if value == STOP {
+            break;
+        }
+        values.push(value);
+        run += 6;
+    }
+    // The final bar of the stop symbol.
+    runs.get(run + 6)?;
+
+    // At least one data symbol, then the check symbol.
+    let (&check, data) = values[1..].split_last()?;
+    if data.is_empty() {
+        return None;
+    }
+    let checksum = data
+        .iter()
+        .enumerate()
+        .fold(start, |sum, (i, value)| sum + (i + 1) * value);
+    if checksum % 103 != check {
+        return None;
+    }
+    text(start, data)
+}
+
+/// Return the payloads of the barcodes found along a line of pixels.
+fn decode_line(pixels: impl Iterator<Item = u8> + Clone) -> Vec<String> {
+    let (min, max) = pixels
+        .clone()
+        .fold((u8::MAX, u8::MIN), |(min, max), pixel| {
+            (min.min(pixel), max.max(pixel))
+        });
+    if max.saturating_sub(min) < MIN_CONTRAST {
+        return vec![];
+    }
+    let threshold = min / 2 + max / 2;
+
+    // Widths of alternating runs, starting with a light run, possibly empty.
+    let mut runs = vec![0];
+    let mut dark = false;
+    for pixel in pixels {
+        if (pixel < threshold) != dark {
+            dark = !dark;
+            runs.push(0);
+        }
+        if let Some(width) = runs.last_mut() {
+            *width += 1;
+        }
+    }
+
+    let mut found = vec![];
+    let mut run = 1;
+    while run < runs.len() {
+        if let Some(data) = decode_at(&runs, run) {
+            found.push(data);
+        }
+        run += 2;
+    }
+    found
+}
+
+/// Return the Code 128 barcodes of an image-only page, e.g., a scan, read
+/// horizontally or vertically, in either direction.
+///
+/// Returns an empty list for other pages, which cannot be rendered, or if the
+/// image cannot be decoded.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+pub fn page_barcodes(
+    document: &Document,
+    page: u32,
+    page_id: ObjectId,
+) -> lopdf::Result<Vec<Barcode>> {
+    let Some(image) = images::page_scan(document, page_id)? else {
+        return Ok(vec![]);
+    };
+    let Some((width, height, pixels)) = images::luminance(document, image.stream) else {
+        return Ok(vec![]);
+    };
+    let (width, height) = (width as usize, height as usize);
+    let mut found = BTreeSet::new();
+
+    for y in 0..height {
+        let row = pixels[y * width..(y + 1) * width].iter().copied();
+        found.extend(decode_line(row.clone()));
+        found.extend(decode_line(row.rev()));
+    }
+    for x in 0..width {
+        let column = (0..height).map(|y| pixels[y * width + x]);
+        found.extend(decode_line(column.clone()));
+        found.extend(decode_line(column.rev()));
+    }
+
+    Ok(found
+        .into_iter()
+        .map(|data| {
+            Barcode {
+                page,
+                format: "code128",
+                data,
+            }
+        })
+        .collect())
+}
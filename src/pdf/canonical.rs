@@ -0,0 +1,273 @@
+//! Canonical form of objects, which is the same for equal objects whatever
+//! the order of their entries, e.g., to hash them, and canonical hash of
+//! documents, e.g., to detect duplicated outputs in archives.
+//!
+//! Every value is tagged, and names, strings and stream data are prefixed
+//! with their length, so that different objects never have the same form.
+
+use std::collections::{HashMap, HashSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use sha2::{Digest, Sha256};
+
+use super::{FILE_TRAILER_KEYS, reproducible, reproducible::fnv1a_128, xmp};
+
+/// Writer of the canonical form of objects of a document.
+pub struct Serializer<'a> {
+    document: &'a Document,
+    /// Whether references are replaced with the objects they point to,
+    /// rather than kept as is.
+    resolve_references: bool,
+    /// Whether streams are written decompressed, as streams compressed
+    /// differently hold the same data.
+    decompress_streams: bool,
+    /// Keys of dictionaries that are left out.
+    ignored_keys: &'a [&'a [u8]],
+    /// Hashes of the canonical forms of resolved objects, so that objects
+    /// shared by several others are only written once.
+    hashes: HashMap<ObjectId, u128>,
+    /// Objects being resolved, which are cycles if they are reached again.
+    resolving: HashSet<ObjectId>,
+}
+
+impl<'a> Serializer<'a> {
+    /// Start writing objects of a document, keeping references as is.
+    #[must_use]
+    pub fn new(document: &'a Document) -> Self {
+        Self {
+            document,
+            resolve_references: false,
+            decompress_streams: false,
+            ignored_keys: &[],
+            hashes: HashMap::new(),
+            resolving: HashSet::new(),
+        }
+    }
+
+    /// Replace references with the hash of the canonical form of the objects
+    /// they point to, or with `null` for missing objects.
+    #[must_use]
+    pub fn resolve_references(mut self, resolve_references: bool) -> Self {
+        self.resolve_references = resolve_references;
+        self
+    }
+
+    /// Write the decompressed data of streams, rather than their raw data.
+    #[must_use]
+    pub fn decompress_streams(mut self, decompress_streams: bool) -> Self {
+        self.decompress_streams = decompress_streams;
+        self
+    }
+
+    /// Leave out entries of dictionaries, e.g., back references.
+    #[must_use]
+    pub fn ignore_keys(mut self, ignored_keys: &'a [&'a [u8]]) -> Self {
+        self.ignored_keys = ignored_keys;
+        self
+    }
+
+    /// Return the hash of the canonical form of an object.
+    pub fn hash(&mut self, object: &Object) -> u128 {
+        let mut out = Vec::new();
+        self.write(object, &mut out);
+        fnv1a_128(&out)
+    }
+
+    /// Append the canonical form of an object.
+    pub fn write(&mut self, object: &Object, out: &mut Vec<u8>) {
+        match object {
+            Object::Null => out.push(b'n'),
+            Object::Boolean(value) => out.extend([b'b', u8::from(*value)]),
+            Object::Integer(value) => {
+                out.push(b'i');
+                out.extend_from_slice(&value.to_le_bytes());
+            },
+            Object::Real(value) => {
+                out.push(b'f');
+                out.extend_from_slice(&value.to_le_bytes());
+            },
+            Object::Name(name) => {
+                out.push(b'/');
+                write_bytes(name, out);
+            },
+            Object::String(bytes, _) => {
+                out.push(b'(');
+                write_bytes(bytes, out);
+            },
+            Object::Array(array) => {
+                out.push(b'[');
+                out.extend_from_slice(&(array.len() as u64).to_le_bytes());
+                for item in array {
+                    self.write(item, out);
+                }
+            },
+            Object::Dictionary(dict) => self.write_dictionary(dict, out),
+            Object::Stream(stream) => {
+                out.push(b's');
+                self.write_dictionary(&stream.dict, out);
+                let content = if self.decompress_streams {
+                    stream.decompressed_content().ok()
+                } else {
+                    None
+                };
+                write_bytes(content.as_deref().unwrap_or(&stream.content), out);
+            },
+            Object::Reference(id) if self.resolve_references => self.write_resolved(*id, out),
+            Object::Reference((number, generation)) => {
+                out.push(b'R');
+                out.extend_from_slice(&number.to_le_bytes());
+                out.extend_from_slice(&generation.to_le_bytes());
+            },
+        }
+    }
+
+    /// Append a dictionary, with sorted keys.
+    fn write_dictionary(&mut self, dict: &Dictionary, out: &mut Vec<u8>) {
+        let mut entries: Vec<_> = dict
+            .iter()
+            .filter(|(key, _)| !self.ignored_keys.contains(&key.as_slice()))
+            .collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+
+        out.push(b'<');
+        out.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        for (key, value) in entries {
+            write_bytes(key, out);
+            self.write(value, out);
+        }
+    }
+
+    /// Append the hash of the object a reference points to.
+    fn write_resolved(&mut self, id: ObjectId, out: &mut Vec<u8>) {
+        let Ok(object) = self.document.get_object(id) else {
+            out.push(b'n');
+            return;
+        };
+        if let Some(hash) = self.hashes.get(&id) {
+            out.push(b'h');
+            out.extend_from_slice(&hash.to_le_bytes());
+            return;
+        }
+        if !self.resolving.insert(id) {
+            // Objects reached again while they are resolved are cycles.
+            out.push(b'c');
+            return;
+        }
+        let hash = self.hash(object);
+        self.resolving.remove(&id);
+        self.hashes.insert(id, hash);
+        out.push(b'h');
+        out.extend_from_slice(&hash.to_le_bytes());
+    }
+}
+
+/// Append bytes, prefixed with their length.
+pub fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Entries of the document information dictionary that change each time a
+/// document is saved.
+const INFO_DATES: [&[u8]; 2] = [b"CreationDate", b"ModDate"];
+
+/// XMP properties that change each time a document is saved.
+const XMP_DATES: [&str; 5] = [
+    "xmp:CreateDate",
+    "xmp:ModifyDate",
+    "xmp:MetadataDate",
+    "xmpMM:DocumentID",
+    "xmpMM:InstanceID",
+];
+
+/// Remove the [`XMP_DATES`] properties of XMP metadata, which are either
+/// elements, e.g., `<xmp:ModifyDate>...</xmp:ModifyDate>`, or attributes,
+/// e.g., ` xmp:ModifyDate="..."`.
+fn remove_xmp_dates(metadata: &str) -> String {
+    let mut metadata = metadata.to_string();
+
+    for name in XMP_DATES {
+        let (open, close) = (format!("<{name}>"), format!("</{name}>"));
+        let mut from = 0;
+        while let Some(start) = metadata[from..].find(&open).map(|i| from + i) {
+            let value = start + open.len();
+            // Only elements holding text are removed.
+            match metadata[value..].find('<').map(|i| value + i) {
+                Some(end) if metadata[end..].starts_with(&close) => {
+                    metadata.replace_range(start..end + close.len(), "");
+                    from = start;
+                },
+                _ => from = value,
+            }
+        }
+
+        let attribute = format!("{name}=\"");
+        let mut from = 0;
+        while let Some(start) = metadata[from..].find(&attribute).map(|i| from + i) {
+            let value = start + attribute.len();
+            let space = metadata[..start]
+                .chars()
+                .next_back()
+                .filter(|c| c.is_whitespace());
+            match (space, metadata[value..].find('"')) {
+                (Some(space), Some(end)) => {
+                    let start = start - space.len_utf8();
+                    metadata.replace_range(start..=value + end, "");
+                    from = start;
+                },
+                _ => from = value,
+            }
+        }
+    }
+    metadata
+}
+
+/// Return the SHA-256 hash of a normalized form of a document, so that
+/// documents that only differ by what changes each time they are saved have
+/// the same hash.
+///
+/// The file identifier, the creation and modification dates, the XMP dates
+/// and identifiers, and the numbering of objects are ignored.
+///
+/// # Errors
+///
+/// Fails if the normalized document cannot be serialized.
+pub fn canonical_hash(mut document: Document) -> lopdf::Result<[u8; 32]> {
+    document.trailer.remove(b"ID");
+    for key in FILE_TRAILER_KEYS {
+        document.trailer.remove(key);
+    }
+
+    let info = match document.trailer.get_mut(b"Info") {
+        Ok(Object::Reference(id)) => {
+            let id = *id;
+            document
+                .objects
+                .get_mut(&id)
+                .and_then(|object| object.as_dict_mut().ok())
+        },
+        Ok(Object::Dictionary(info)) => Some(info),
+        _ => None,
+    };
+    let empty_info = info.is_some_and(|info| {
+        for key in INFO_DATES {
+            info.remove(key);
+        }
+        info.is_empty()
+    });
+    if empty_info {
+        document.trailer.remove(b"Info");
+    }
+
+    if let Some(metadata) = xmp::metadata(&document) {
+        let normalized = remove_xmp_dates(&metadata);
+        if normalized != metadata {
+            xmp::set_metadata(&mut document, normalized)?;
+        }
+    }
+
+    reproducible::renumber_objects(&mut document);
+    let mut bytes = vec![];
+    document.save_to(&mut bytes)?;
+    Ok(Sha256::digest(&bytes).into())
+}
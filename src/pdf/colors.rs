@@ -0,0 +1,839 @@
+//! Color spaces, and conversion of documents to gray or CMYK colors.
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream,
+    content::{Content, Operation},
+    dictionary,
+};
+use tracing::debug;
+
+use super::{content::number, document::DocumentExt, pdfstring};
+
+/// Filters of image data that cannot be decoded.
+const IMAGE_CODECS: &[&str] = &["DCTDecode", "JPXDecode", "JBIG2Decode", "CCITTFaxDecode"];
+
+/// Family of a color space, i.e., the device color space it is equivalent
+/// to, as far as conversion is concerned.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Family {
+    Gray,
+    Rgb,
+    Cmyk,
+}
+
+impl Family {
+    /// Return the number of color components.
+    #[must_use]
+    pub fn components(self) -> usize {
+        match self {
+            Self::Gray => 1,
+            Self::Rgb => 3,
+            Self::Cmyk => 4,
+        }
+    }
+
+    /// Return the name of the device color space of the family.
+    #[must_use]
+    pub fn device_name(self) -> &'static str {
+        match self {
+            Self::Gray => "DeviceGray",
+            Self::Rgb => "DeviceRGB",
+            Self::Cmyk => "DeviceCMYK",
+        }
+    }
+
+    /// Return the family of an ICC profile, read from its header.
+    #[must_use]
+    pub fn of_icc_profile(profile: &[u8]) -> Option<Self> {
+        match profile.get(16..20)? {
+            b"GRAY" => Some(Self::Gray),
+            b"RGB " => Some(Self::Rgb),
+            b"CMYK" => Some(Self::Cmyk),
+            _ => None,
+        }
+    }
+
+    /// Return whether colors of this family must be converted to be of the
+    /// given family. Gray colors are never converted, as they are printed
+    /// with black ink only.
+    fn converts_to(self, target: Self) -> bool {
+        self != target && self != Self::Gray
+    }
+}
+
+/// Return the family of a color space, or `None` for color spaces that are
+/// not equivalent to a device color space, e.g., indexed or separation
+/// color spaces.
+#[must_use]
+pub fn family(document: &Document, color_space: &Object) -> Option<Family> {
+    match document.dereference(color_space).ok()?.1 {
+        Object::Name(name) => {
+            match name.as_slice() {
+                b"DeviceGray" | b"CalGray" | b"G" => Some(Family::Gray),
+                b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(Family::Rgb),
+                b"DeviceCMYK" | b"CMYK" => Some(Family::Cmyk),
+                _ => None,
+            }
+        },
+        Object::Array(array) => {
+            match array.first().and_then(|family| family.as_name().ok())? {
+                b"ICCBased" => {
+                    let profile = document
+                        .dereference(array.get(1)?)
+                        .ok()?
+                        .1
+                        .as_stream()
+                        .ok()?;
+                    match profile.dict.get(b"N").and_then(Object::as_i64).ok()? {
+                        1 => Some(Family::Gray),
+                        3 => Some(Family::Rgb),
+                        4 => Some(Family::Cmyk),
+                        _ => None,
+                    }
+                },
+                b"CalGray" => Some(Family::Gray),
+                b"CalRGB" => Some(Family::Rgb),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Convert a color from one family to another, with the naive formulas of
+/// the PDF specification, as no color management is done.
+#[must_use]
+pub fn convert_color(components: &[f32], from: Family, to: Family) -> Vec<f32> {
+    let rgb = match (from, components) {
+        (Family::Gray, &[gray]) => [gray; 3],
+        (Family::Rgb, &[r, g, b]) => [r, g, b],
+        (Family::Cmyk, &[c, m, y, k]) => {
+            if to == Family::Gray {
+                return vec![1.0 - (0.3 * c + 0.59 * m + 0.11 * y + k).min(1.0)];
+            } else if to == Family::Cmyk {
+                return components.to_vec();
+            }
+            [
+                (1.0 - c) * (1.0 - k),
+                (1.0 - m) * (1.0 - k),
+                (1.0 - y) * (1.0 - k),
+            ]
+        },
+        _ => return components.to_vec(),
+    };
+    let [r, g, b] = rgb.map(|value| value.clamp(0.0, 1.0));
+
+    match to {
+        Family::Gray => vec![0.3 * r + 0.59 * g + 0.11 * b],
+        Family::Rgb => rgb.to_vec(),
+        Family::Cmyk => {
+            let k = 1.0 - r.max(g).max(b);
+            if k >= 1.0 {
+                return vec![0.0, 0.0, 0.0, 1.0];
+            }
+            vec![
+                (1.0 - r - k) / (1.0 - k),
+                (1.0 - g - k) / (1.0 - k),
+                (1.0 - b - k) / (1.0 - k),
+                k,
+            ]
+        },
+    }
+}
+
+/// Convert 8-bit samples from one family to another.
+fn convert_samples(samples: &[u8], from: Family, to: Family) -> Vec<u8> {
+    samples
+        .chunks_exact(from.components())
+        .flat_map(|pixel| {
+            let components: Vec<f32> = pixel
+                .iter()
+                .map(|&value| f32::from(value) / 255.0)
+                .collect();
+            convert_color(&components, from, to)
+                .into_iter()
+                .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+        })
+        .collect()
+}
+
+/// Number of objects converted, or left unchanged, by [`convert`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Report {
+    /// Color operators of content streams.
+    pub operators: usize,
+    pub images: usize,
+    pub shadings: usize,
+    /// Images, shadings and content streams that use colors of another family
+    /// but could not be converted, e.g., JPEG images.
+    pub skipped: usize,
+}
+
+/// Return the operator setting a color of a family, for filling or stroking.
+fn color_operator(family: Family, stroke: bool) -> &'static str {
+    match (family, stroke) {
+        (Family::Gray, false) => "g",
+        (Family::Gray, true) => "G",
+        (Family::Rgb, false) => "rg",
+        (Family::Rgb, true) => "RG",
+        (Family::Cmyk, false) => "k",
+        (Family::Cmyk, true) => "K",
+    }
+}
+
+/// Convert the color operators of a content stream, returning how many were
+/// converted.
+///
+/// Color spaces set with `cs` and `CS` are replaced with the device color
+/// space of the target family, and subsequent colors are converted, unless
+/// they are patterns or colors of other color spaces, e.g., spot colors.
+fn convert_operations(
+    document: &Document,
+    operations: &mut [Operation],
+    resources: Option<&Dictionary>,
+    target: Family,
+) -> usize {
+    let color_spaces = resources.and_then(|resources| {
+        resources
+            .get_deref(b"ColorSpace", document)
+            .and_then(Object::as_dict)
+            .ok()
+    });
+    // Family of the fill and stroke color spaces being converted.
+    let mut state: [Option<Family>; 2] = [None, None];
+    let mut stack = vec![];
+    let mut count = 0;
+
+    for operation in operations {
+        let stroke = operation.operator.chars().all(|c| c.is_ascii_uppercase());
+        let index = usize::from(stroke);
+        let from = match operation.operator.as_str() {
+            "q" => {
+                stack.push(state);
+                continue;
+            },
+            "Q" => {
+                state = stack.pop().unwrap_or_default();
+                continue;
+            },
+            "cs" | "CS" => {
+                let Some(name) = operation
+                    .operands
+                    .first()
+                    .and_then(|name| name.as_name().ok())
+                else {
+                    continue;
+                };
+                let color_space = color_spaces
+                    .and_then(|color_spaces| color_spaces.get(name).ok())
+                    .cloned()
+                    .unwrap_or_else(|| Object::Name(name.to_vec()));
+                state[index] =
+                    family(document, &color_space).filter(|family| family.converts_to(target));
+                if state[index].is_some() {
+                    operation.operands = vec![Object::Name(target.device_name().into())];
+                    count += 1;
+                }
+                continue;
+            },
+            "sc" | "scn" | "SC" | "SCN" => {
+                match state[index] {
+                    // Patterns are named, and their colors converted along
+                    // with shadings.
+                    Some(from)
+                        if operation
+                            .operands
+                            .iter()
+                            .all(|operand| number(operand).is_some()) =>
+                    {
+                        from
+                    },
+                    _ => continue,
+                }
+            },
+            "rg" | "RG" => Family::Rgb,
+            "k" | "K" => Family::Cmyk,
+            _ => continue,
+        };
+        if !from.converts_to(target) {
+            continue;
+        }
+        let components: Vec<f32> = operation.operands.iter().filter_map(number).collect();
+        if components.len() != from.components() {
+            continue;
+        }
+        if !operation.operator.starts_with("sc") && !operation.operator.starts_with("SC") {
+            operation.operator = color_operator(target, stroke).to_string();
+        }
+        operation.operands = convert_color(&components, from, target)
+            .into_iter()
+            .map(Object::Real)
+            .collect();
+        count += 1;
+    }
+    count
+}
+
+/// Convert the samples of an image, or the lookup table of an indexed image.
+///
+/// Returns `None` if the image does not use colors of another family, or
+/// `Some(None)` if it does but cannot be converted.
+fn convert_image(document: &Document, stream: &Stream, target: Family) -> Option<Option<Stream>> {
+    let color_space = stream.dict.get(b"ColorSpace").ok()?;
+
+    // Indexed images only need their lookup table converted.
+    if let Ok(array) = document
+        .dereference(color_space)
+        .and_then(|(_, cs)| cs.as_array())
+    {
+        if array.first().and_then(|name| name.as_name().ok()) == Some(b"Indexed") {
+            let from = family(document, array.get(1)?)?;
+            if !from.converts_to(target) {
+                return None;
+            }
+            let lookup = match document.dereference(array.get(3)?).ok()?.1 {
+                Object::String(lookup, _) => Some(lookup.clone()),
+                Object::Stream(lookup) => super::decompress(lookup).ok(),
+                _ => None,
+            };
+            let Some(lookup) = lookup else {
+                return Some(None);
+            };
+            let mut converted = stream.clone();
+            converted.dict.set(
+                "ColorSpace",
+                vec![
+                    Object::Name(b"Indexed".to_vec()),
+                    Object::Name(target.device_name().into()),
+                    array[2].clone(),
+                    Object::string_literal(convert_samples(&lookup, from, target)),
+                ],
+            );
+            return Some(Some(converted));
+        }
+    }
+
+    let from = family(document, color_space)?;
+    if !from.converts_to(target) {
+        return None;
+    }
+    let filters = stream.filters().unwrap_or_default();
+    if stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(Object::as_i64)
+        .ok()
+        != Some(8)
+        || stream.dict.has(b"Decode")
+        || filters
+            .iter()
+            .any(|filter| IMAGE_CODECS.contains(&filter.as_str()))
+    {
+        return Some(None);
+    }
+    let samples = if filters.is_empty() {
+        stream.content.clone()
+    } else {
+        match super::decompress(stream) {
+            Ok(samples) => samples,
+            Err(_) => return Some(None),
+        }
+    };
+
+    let mut converted = stream.clone();
+    converted
+        .dict
+        .set("ColorSpace", Object::Name(target.device_name().into()));
+    converted.dict.remove(b"DecodeParms");
+    converted.dict.remove(b"Filter");
+    converted.set_plain_content(convert_samples(&samples, from, target));
+    let _ = converted.compress();
+    Some(Some(converted))
+}
+
+/// Convert the colors of a function, i.e., of exponential functions and
+/// of stitching functions made of them, writing converted indirect objects
+/// to `updates`.
+fn convert_function(
+    document: &Document,
+    function: &Object,
+    from: Family,
+    target: Family,
+    updates: &mut Vec<(ObjectId, Object)>,
+) -> Option<Object> {
+    if let Ok(id) = function.as_reference() {
+        let converted = convert_function(
+            document,
+            document.get_object(id).ok()?,
+            from,
+            target,
+            updates,
+        )?;
+        updates.push((id, converted));
+        return Some(function.clone());
+    }
+    let mut dict = function.as_dict().ok()?.clone();
+
+    match dict.get(b"FunctionType").and_then(Object::as_i64).ok()? {
+        2 => {
+            for (key, default) in [(&b"C0"[..], 0.0), (&b"C1"[..], 1.0)] {
+                let color: Vec<f32> = match dict.get(key) {
+                    Ok(color) => color.as_array().ok()?.iter().filter_map(number).collect(),
+                    Err(_) => vec![default],
+                };
+                if color.len() != from.components() {
+                    return None;
+                }
+                let color = convert_color(&color, from, target);
+                dict.set(key, color.into_iter().map(Object::Real).collect::<Vec<_>>());
+            }
+        },
+        3 => {
+            let functions = dict
+                .get(b"Functions")
+                .and_then(Object::as_array)
+                .ok()?
+                .iter()
+                .map(|function| convert_function(document, function, from, target, updates))
+                .collect::<Option<Vec<_>>>()?;
+            dict.set("Functions", functions);
+        },
+        _ => return None,
+    }
+    if let Ok(range) = dict.get(b"Range").and_then(Object::as_array) {
+        if range.len() == 2 * from.components() {
+            dict.set(
+                "Range",
+                [0i64, 1]
+                    .repeat(target.components())
+                    .into_iter()
+                    .map(Object::Integer)
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+    Some(Object::Dictionary(dict))
+}
+
+/// Convert the colors of a shading whose colors are given by functions.
+///
+/// Returns `None` if the shading does not use colors of another family, or
+/// `Some(None)` if it does but cannot be converted, e.g., mesh shadings.
+fn convert_shading(
+    document: &Document,
+    shading: &Dictionary,
+    target: Family,
+    updates: &mut Vec<(ObjectId, Object)>,
+) -> Option<Option<Dictionary>> {
+    let from = family(document, shading.get(b"ColorSpace").ok()?)?;
+    if !from.converts_to(target) {
+        return None;
+    }
+    let Some(function) = shading
+        .get(b"Function")
+        .ok()
+        .and_then(|function| convert_function(document, function, from, target, updates))
+    else {
+        return Some(None);
+    };
+
+    let mut converted = shading.clone();
+    converted.set("ColorSpace", Object::Name(target.device_name().into()));
+    converted.set("Function", function);
+    if let Ok(background) = shading.get(b"Background").and_then(Object::as_array) {
+        let background: Vec<f32> = background.iter().filter_map(number).collect();
+        converted.set(
+            "Background",
+            convert_color(&background, from, target)
+                .into_iter()
+                .map(Object::Real)
+                .collect::<Vec<_>>(),
+        );
+    }
+    Some(Some(converted))
+}
+
+/// Convert the colors of a document to gray or CMYK colors, e.g., for print
+/// shops that only accept such files.
+///
+/// Color operators of pages, forms, annotation appearances and tiling
+/// patterns are converted, as well as images with 8-bit samples that are not
+/// JPEG-encoded, indexed images, and shadings defined with exponential
+/// functions. Gray colors are kept when converting to CMYK. Other objects
+/// are left unchanged and counted as skipped.
+pub fn convert(document: &mut Document, target: Family) -> Report {
+    let mut report = Report::default();
+
+    for page_id in document.get_pages().into_values() {
+        let Ok(mut content) = document.get_and_decode_page_content(page_id) else {
+            debug!("Failed to decode content of page {page_id:?}");
+            report.skipped += 1;
+            continue;
+        };
+        let count = convert_operations(
+            document,
+            &mut content.operations,
+            document.page_resources(page_id),
+            target,
+        );
+        if count == 0 {
+            continue;
+        }
+        let Ok(data) = content.encode() else {
+            report.skipped += 1;
+            continue;
+        };
+        let mut stream = Stream::new(Dictionary::new(), data);
+        let _ = stream.compress();
+        let content_id = document.add_object(stream);
+        if let Ok(page) = document.get_dictionary_mut(page_id) {
+            page.set("Contents", content_id);
+            report.operators += count;
+        }
+    }
+
+    let mut updates = vec![];
+
+    for (&id, object) in &document.objects {
+        let Object::Stream(stream) = object else {
+            if let Some(converted) = object
+                .as_dict()
+                .ok()
+                .filter(|dict| dict.has(b"ShadingType"))
+                .and_then(|dict| convert_shading(document, dict, target, &mut updates))
+            {
+                match converted {
+                    Some(shading) => {
+                        updates.push((id, Object::Dictionary(shading)));
+                        report.shadings += 1;
+                    },
+                    None => {
+                        debug!("Shading {id:?} cannot be converted");
+                        report.skipped += 1;
+                    },
+                }
+            }
+            continue;
+        };
+        let subtype = stream.dict.get(b"Subtype").and_then(Object::as_name).ok();
+
+        if subtype == Some(b"Image") {
+            match convert_image(document, stream, target) {
+                Some(Some(image)) => {
+                    updates.push((id, Object::Stream(image)));
+                    report.images += 1;
+                },
+                Some(None) => {
+                    debug!("Image {id:?} uses an unsupported encoding");
+                    report.skipped += 1;
+                },
+                None => {},
+            }
+        } else if subtype == Some(b"Form")
+            || stream
+                .dict
+                .get(b"PatternType")
+                .and_then(Object::as_i64)
+                .ok()
+                == Some(1)
+        {
+            let Ok(mut content) = stream
+                .decompressed_content()
+                .or_else(|_| Ok::<_, lopdf::Error>(stream.content.clone()))
+                .and_then(|data| Content::decode(&data))
+            else {
+                debug!("Failed to decode content of stream {id:?}");
+                report.skipped += 1;
+                continue;
+            };
+            let resources = stream
+                .dict
+                .get_deref(b"Resources", document)
+                .and_then(Object::as_dict)
+                .ok();
+            let count = convert_operations(document, &mut content.operations, resources, target);
+            if let (true, Ok(data)) = (count > 0, content.encode()) {
+                let mut converted = stream.clone();
+                converted.dict.remove(b"DecodeParms");
+                converted.dict.remove(b"Filter");
+                converted.set_plain_content(data);
+                let _ = converted.compress();
+                updates.push((id, Object::Stream(converted)));
+                report.operators += count;
+            }
+        } else if stream.dict.has(b"ShadingType") {
+            debug!("Shading {id:?} cannot be converted");
+            report.skipped += usize::from(
+                stream
+                    .dict
+                    .get(b"ColorSpace")
+                    .ok()
+                    .and_then(|color_space| family(document, color_space))
+                    .is_some_and(|family| family.converts_to(target)),
+            );
+        }
+    }
+
+    // Functions shared by several shadings are converted from the same
+    // original object, so updating them several times is harmless.
+    for (id, object) in updates {
+        document.objects.insert(id, object);
+    }
+    // Replaced page content streams are no longer referenced.
+    document.prune_objects();
+    report
+}
+
+/// Return the description of an ICC profile, from its `desc` tag.
+#[must_use]
+pub fn icc_description(profile: &[u8]) -> Option<String> {
+    let read_u32 = |offset: usize| {
+        profile
+            .get(offset..offset + 4)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    };
+    let count = read_u32(128)?;
+
+    for entry in (0..count.min(1024)).map(|i| 132 + 12 * i) {
+        if profile.get(entry..entry + 4)? != b"desc" {
+            continue;
+        }
+        let (offset, size) = (read_u32(entry + 4)?, read_u32(entry + 8)?);
+        let tag = profile.get(offset..offset.checked_add(size)?)?;
+
+        return match tag.get(..4)? {
+            // ICC v2 profiles hold an ASCII description.
+            b"desc" => {
+                let length = u32::from_be_bytes(tag.get(8..12)?.try_into().ok()?) as usize;
+                let text = tag.get(12..12 + length)?;
+                Some(
+                    String::from_utf8_lossy(text)
+                        .trim_end_matches('\0')
+                        .to_string(),
+                )
+            },
+            // ICC v4 profiles hold UTF-16 descriptions, in several languages.
+            b"mluc" => {
+                let record = tag.get(16..28)?;
+                let length = u32::from_be_bytes(record[4..8].try_into().ok()?) as usize;
+                let offset = u32::from_be_bytes(record[8..12].try_into().ok()?) as usize;
+                let units: Vec<u16> = tag
+                    .get(offset..offset + length)?
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                    .collect();
+                Some(String::from_utf16_lossy(&units))
+            },
+            _ => None,
+        };
+    }
+    None
+}
+
+/// Description of the profile returned by [`srgb_profile`].
+#[cfg(feature = "convert")]
+pub const SRGB_DESCRIPTION: &str = "sRGB IEC61966-2.1";
+
+/// Encode a number as an ICC `s15Fixed16Number`.
+#[cfg(feature = "convert")]
+fn s15_fixed16(value: f32) -> [u8; 4] {
+    ((value * 65536.0).round() as i32).to_be_bytes()
+}
+
+/// Return a minimal ICC v2 profile of the sRGB color space.
+///
+/// The profile only holds the colorants, white point and tone curves, the
+/// latter approximated with a gamma of 2.2, which is enough as the output
+/// intent of documents whose colors are not managed.
+#[cfg(feature = "convert")]
+#[must_use]
+pub fn srgb_profile() -> Vec<u8> {
+    let xyz = |x: f32, y: f32, z: f32| {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for value in [x, y, z] {
+            tag.extend_from_slice(&s15_fixed16(value));
+        }
+        tag
+    };
+    let mut description = b"desc\0\0\0\0".to_vec();
+    description.extend_from_slice(&(SRGB_DESCRIPTION.len() as u32 + 1).to_be_bytes());
+    description.extend_from_slice(SRGB_DESCRIPTION.as_bytes());
+    // Empty Unicode and ScriptCode descriptions.
+    description.extend_from_slice(&[0; 1 + 8 + 3 + 67]);
+    let mut copyright = b"text\0\0\0\0No copyright, use freely".to_vec();
+    copyright.push(0);
+    // Gamma of 2.2, as an `u8Fixed8Number`.
+    let curve = b"curv\0\0\0\0\0\0\0\x01\x02\x33".to_vec();
+
+    let tags: [(&[u8; 4], Vec<u8>); 7] = [
+        (b"desc", description),
+        (b"cprt", copyright),
+        (b"wtpt", xyz(0.9642, 1.0, 0.8249)),
+        (b"rXYZ", xyz(0.4361, 0.2225, 0.0139)),
+        (b"gXYZ", xyz(0.3851, 0.7169, 0.0971)),
+        (b"bXYZ", xyz(0.1431, 0.0606, 0.7141)),
+        (b"rTRC", curve),
+    ];
+
+    let mut header = vec![0; 128];
+    header[8..12].copy_from_slice(&[2, 0x10, 0, 0]);
+    header[12..16].copy_from_slice(b"mntr");
+    header[16..20].copy_from_slice(b"RGB ");
+    header[20..24].copy_from_slice(b"XYZ ");
+    header[36..40].copy_from_slice(b"acsp");
+    // D50 illuminant of the profile connection space.
+    for (i, value) in [0.9642, 1.0, 0.8249].into_iter().enumerate() {
+        header[68 + 4 * i..72 + 4 * i].copy_from_slice(&s15_fixed16(value));
+    }
+
+    // Green and blue tone curves share the data of the red one.
+    let count = tags.len() + 2;
+    let mut table = (count as u32).to_be_bytes().to_vec();
+    let mut data = vec![];
+    let mut entry = vec![];
+
+    for (signature, tag) in tags {
+        let offset = (128 + 4 + 12 * count + data.len()) as u32;
+        entry = [offset.to_be_bytes(), (tag.len() as u32).to_be_bytes()].concat();
+        table.extend_from_slice(signature);
+        table.extend_from_slice(&entry);
+        data.extend_from_slice(&tag);
+        // Tag data is aligned on four bytes.
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+    for signature in [b"gTRC", b"bTRC"] {
+        table.extend_from_slice(signature);
+        table.extend_from_slice(&entry);
+    }
+
+    let mut profile = [header, table, data].concat();
+    let size = profile.len() as u32;
+    profile[..4].copy_from_slice(&size.to_be_bytes());
+    profile
+}
+
+/// Output intent of a document, i.e., the device or the condition it is
+/// intended for.
+#[derive(Clone, Debug)]
+pub struct OutputIntent {
+    /// Subtype, e.g., `GTS_PDFX` for print or `GTS_PDFA1` for archiving.
+    pub subtype: String,
+    /// Name of the output condition, e.g., `FOGRA39`.
+    pub identifier: Option<String>,
+    /// Human-readable description of the output condition.
+    pub info: Option<String>,
+    /// Registry where the output condition is defined.
+    pub registry: Option<String>,
+    /// Embedded ICC profile, if any.
+    pub profile: Option<ProfileInfo>,
+}
+
+/// Description of an embedded ICC profile.
+#[derive(Clone, Debug)]
+pub struct ProfileInfo {
+    pub family: Option<Family>,
+    pub description: Option<String>,
+    /// Size of the profile, in bytes.
+    pub size: usize,
+}
+
+/// Read the output intents of a document.
+#[must_use]
+pub fn output_intents(document: &Document) -> Vec<OutputIntent> {
+    let Some(intents) = document
+        .catalog()
+        .and_then(|catalog| catalog.get_deref(b"OutputIntents", document))
+        .and_then(Object::as_array)
+        .ok()
+    else {
+        return vec![];
+    };
+    let string = |intent: &Dictionary, key: &[u8]| {
+        intent
+            .get_deref(key, document)
+            .and_then(|value| value.as_str())
+            .ok()
+            .map(pdfstring::decode)
+    };
+
+    intents
+        .iter()
+        .filter_map(|intent| document.dereference(intent).ok()?.1.as_dict().ok())
+        .map(|intent| {
+            let profile = intent
+                .get_deref(b"DestOutputProfile", document)
+                .and_then(Object::as_stream)
+                .ok()
+                .map(|stream| {
+                    let data = super::decompress(stream).unwrap_or_else(|_| stream.content.clone());
+                    ProfileInfo {
+                        family: Family::of_icc_profile(&data),
+                        description: icc_description(&data),
+                        size: data.len(),
+                    }
+                });
+            OutputIntent {
+                subtype: intent
+                    .get(b"S")
+                    .and_then(Object::as_name)
+                    .map(|name| String::from_utf8_lossy(name).into_owned())
+                    .unwrap_or_default(),
+                identifier: string(intent, b"OutputConditionIdentifier"),
+                info: string(intent, b"Info"),
+                registry: string(intent, b"RegistryName"),
+                profile,
+            }
+        })
+        .collect()
+}
+
+/// Set the output intent of a subtype, embedding the given ICC profile as
+/// the destination profile.
+///
+/// Output intents of other subtypes are kept, e.g., both `GTS_PDFX` for
+/// print and `GTS_PDFA1` for archiving, and the `identifier` names the output
+/// condition, e.g., `FOGRA39`.
+pub fn set_output_intent(
+    document: &mut Document,
+    subtype: &str,
+    identifier: &str,
+    profile: Vec<u8>,
+    family: Family,
+) -> lopdf::Result<()> {
+    let mut intents: Vec<Object> = document
+        .catalog()?
+        .get_deref(b"OutputIntents", document)
+        .and_then(Object::as_array)
+        .map(|intents| {
+            intents
+                .iter()
+                .filter(|intent| {
+                    document
+                        .dereference(intent)
+                        .and_then(|(_, intent)| intent.as_dict())
+                        .and_then(|intent| intent.get(b"S"))
+                        .and_then(Object::as_name)
+                        .map_or(true, |name| name != subtype.as_bytes())
+                })
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut profile = Stream::new(dictionary! { "N" => family.components() as i64 }, profile);
+    let _ = profile.compress();
+    let profile_id = document.add_object(profile);
+    let intent_id = document.add_object(dictionary! {
+        "Type" => "OutputIntent",
+        "S" => Object::Name(subtype.as_bytes().to_vec()),
+        "OutputConditionIdentifier" => pdfstring::encode_object(identifier),
+        "Info" => pdfstring::encode_object(identifier),
+        "DestOutputProfile" => profile_id,
+    });
+    intents.push(Object::Reference(intent_id));
+
+    document.catalog_mut()?.set("OutputIntents", intents);
+    // Replaced intents and profiles are no longer referenced.
+    document.prune_objects();
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Helpers to interpret page content streams.
+
+use lopdf::{Object, content::Operation};
+
+/// Affine transformation matrix `[a b c d e f]`, as used by the `cm` and `Tm`
+/// operators.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Matrix {
+    /// The identity matrix.
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    /// Create a new matrix from its six coefficients.
+    #[must_use]
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    /// Create a matrix from the six operands of a `cm` or `Tm` operator.
+    #[must_use]
+    pub fn from_operands(operands: &[Object]) -> Option<Self> {
+        match operands {
+            [a, b, c, d, e, f] => {
+                Some(Self::new(
+                    number(a)?,
+                    number(b)?,
+                    number(c)?,
+                    number(d)?,
+                    number(e)?,
+                    number(f)?,
+                ))
+            },
+            _ => None,
+        }
+    }
+
+    /// Return the matrix that applies `self`, then `other`.
+    #[must_use]
+    pub fn then(&self, other: &Self) -> Self {
+        Self::new(
+            self.a * other.a + self.b * other.c,
+            self.a * other.b + self.b * other.d,
+            self.c * other.a + self.d * other.c,
+            self.c * other.b + self.d * other.d,
+            self.e * other.a + self.f * other.c + other.e,
+            self.e * other.b + self.f * other.d + other.f,
+        )
+    }
+
+    /// Transform a point.
+    #[must_use]
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Return the inverse matrix, if any.
+    #[must_use]
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.a * self.d - self.b * self.c;
+        if !determinant.is_normal() {
+            return None;
+        }
+        let a = self.d / determinant;
+        let b = -self.b / determinant;
+        let c = -self.c / determinant;
+        let d = self.a / determinant;
+        Some(Self::new(
+            a,
+            b,
+            c,
+            d,
+            -(self.e * a + self.f * c),
+            -(self.e * b + self.f * d),
+        ))
+    }
+
+    /// Return the matrix without its translation.
+    #[must_use]
+    pub fn linear(&self) -> Self {
+        Self::new(self.a, self.b, self.c, self.d, 0.0, 0.0)
+    }
+
+    /// Return the area scaling factor of the matrix, i.e., the absolute value
+    /// of its determinant.
+    #[must_use]
+    pub fn area_scale(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs()
+    }
+}
+
+/// Return the number held by an integer or real object.
+#[must_use]
+pub fn number(object: &Object) -> Option<f32> {
+    object.as_float().ok()
+}
+
+/// Pair each operation with the current transformation matrix (CTM) in effect
+/// when it is executed.
+///
+/// Only `q`, `Q` and `cm` affect the CTM, and operations of nested form
+/// XObjects are not visited.
+#[must_use]
+pub fn with_ctm(operations: &[Operation]) -> Vec<(&Operation, Matrix)> {
+    let mut stack = vec![];
+    let mut ctm = Matrix::IDENTITY;
+
+    operations
+        .iter()
+        .map(|operation| {
+            match operation.operator.as_str() {
+                "q" => stack.push(ctm),
+                "Q" => ctm = stack.pop().unwrap_or(Matrix::IDENTITY),
+                "cm" => {
+                    if let Some(matrix) = Matrix::from_operands(&operation.operands) {
+                        ctm = matrix.then(&ctm);
+                    }
+                },
+                _ => {},
+            }
+            (operation, ctm)
+        })
+        .collect()
+}
+
+/// Return whether the operations show any text.
+#[cfg(feature = "ocr")]
+#[must_use]
+pub fn shows_text(operations: &[Operation]) -> bool {
+    operations
+        .iter()
+        .any(|operation| matches!(operation.operator.as_str(), "Tj" | "TJ" | "'" | "\""))
+}
@@ -0,0 +1,266 @@
+//! Deduplication of identical objects, e.g., the same font program or image
+//! imported from several documents.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use lopdf::{Document, Object, ObjectId};
+
+use super::{canonical::Serializer, objects::object_dictionary};
+
+/// Entries whose values are drawn, rather than being part of the structure
+/// of the document: content streams, resources and appearance streams.
+const DRAWING_KEYS: &[&[u8]] = &[b"Contents", b"Resources", b"AP"];
+
+/// Types of dictionaries that have an identity, and are never shared even
+/// if reached from drawing entries.
+const IDENTITY_TYPES: &[&[u8]] = &[
+    b"Catalog",
+    b"Pages",
+    b"Page",
+    b"Annot",
+    b"OCG",
+    b"OCMD",
+    b"StructTreeRoot",
+    b"StructElem",
+];
+
+/// Entries that point to dictionaries with an identity, whatever their
+/// type: optional content groups, whose `/Type` may be missing, and parents,
+/// e.g., pages or structure elements.
+const IDENTITY_KEYS: &[&[u8]] = &[b"OC", b"OCGs", b"Properties", b"Parent", b"P", b"Pg"];
+
+/// Return whether an object has an identity.
+///
+/// The `/Type` of most dictionaries is optional, so nodes of trees, e.g.,
+/// pages, annotations or structure elements, are also told apart by the
+/// parent they point to, and annotations by their rectangle.
+fn has_identity(object: &Object) -> bool {
+    object_dictionary(object).is_some_and(|dict| {
+        dict.get(b"Type")
+            .and_then(Object::as_name)
+            .is_ok_and(|kind| IDENTITY_TYPES.contains(&kind))
+            || dict.has(b"Parent")
+            || dict.has(b"P")
+            || dict.has(b"Rect")
+    })
+}
+
+/// Push the references held by an object, at any depth, except those of
+/// entries that point to dictionaries with an identity.
+fn collect_shared(object: &Object, references: &mut Vec<ObjectId>) {
+    match object {
+        Object::Reference(id) => references.push(*id),
+        Object::Array(array) => {
+            for item in array {
+                collect_shared(item, references);
+            }
+        },
+        Object::Dictionary(_) | Object::Stream(_) => {
+            let Some(dict) = object_dictionary(object) else {
+                return;
+            };
+            for (key, value) in dict.iter() {
+                if !IDENTITY_KEYS.contains(&key.as_slice()) {
+                    collect_shared(value, references);
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Push the references held by the drawing entries of the dictionaries of
+/// an object, at any depth.
+fn collect_drawn(object: &Object, references: &mut Vec<ObjectId>) {
+    match object {
+        Object::Array(array) => {
+            for item in array {
+                collect_drawn(item, references);
+            }
+        },
+        Object::Dictionary(_) | Object::Stream(_) => {
+            let Some(dict) = object_dictionary(object) else {
+                return;
+            };
+            for (key, value) in dict.iter() {
+                if DRAWING_KEYS.contains(&key.as_slice()) {
+                    collect_shared(value, references);
+                } else {
+                    collect_drawn(value, references);
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Return the objects numbered from `first` that can be shared by all the
+/// objects that refer to identical copies of them, i.e., the objects
+/// reached from drawing entries.
+///
+/// Other objects, e.g., pages or annotations, have an identity: two
+/// identical annotations on a page are two annotations.
+fn shareable_objects(document: &Document, first: u32) -> BTreeSet<ObjectId> {
+    let mut pending = vec![];
+    for (_, object) in document.objects.range((first, 0)..) {
+        collect_drawn(object, &mut pending);
+    }
+
+    let mut shareable = BTreeSet::new();
+    while let Some(id) = pending.pop() {
+        if id.0 < first {
+            continue;
+        }
+        let Some(object) = document.objects.get(&id) else {
+            continue;
+        };
+        if !has_identity(object) && shareable.insert(id) {
+            collect_shared(object, &mut pending);
+        }
+    }
+    shareable
+}
+
+/// Return whether two objects are identical, ignoring where streams were
+/// read from.
+fn is_identical(a: &Object, b: &Object) -> bool {
+    match (a, b) {
+        (Object::Stream(a), Object::Stream(b)) => a.dict == b.dict && a.content == b.content,
+        _ => a == b,
+    }
+}
+
+/// Rewrite the references held by an object, at any depth, keeping those
+/// missing from the mapping.
+fn replace_references(object: &mut Object, mapping: &BTreeMap<ObjectId, ObjectId>) {
+    match object {
+        Object::Reference(id) => {
+            if let Some(&new_id) = mapping.get(id) {
+                *id = new_id;
+            }
+        },
+        Object::Array(array) => {
+            for item in array {
+                replace_references(item, mapping);
+            }
+        },
+        Object::Dictionary(dict) => {
+            for (_, value) in dict.iter_mut() {
+                replace_references(value, mapping);
+            }
+        },
+        Object::Stream(stream) => {
+            for (_, value) in stream.dict.iter_mut() {
+                replace_references(value, mapping);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Index of the shareable objects of a document, which identical objects
+/// added later are replaced with, e.g., objects imported from other
+/// documents.
+#[derive(Default)]
+pub struct Deduplicator {
+    /// Objects that are kept, by hash of their canonical form.
+    originals: HashMap<u128, Vec<ObjectId>>,
+}
+
+impl Deduplicator {
+    /// Index the shareable objects of a document, which are all kept, even
+    /// if some of them are identical.
+    #[must_use]
+    pub fn new(document: &Document) -> Self {
+        let mut serializer = Serializer::new(document);
+        let mut originals: HashMap<u128, Vec<ObjectId>> = HashMap::new();
+        for id in shareable_objects(document, 0) {
+            let hash = serializer.hash(&document.objects[&id]);
+            originals.entry(hash).or_default().push(id);
+        }
+        Self { originals }
+    }
+
+    /// Return a mapping from each duplicated object of `objects` to an
+    /// identical indexed object, or to the first identical one of `objects`.
+    ///
+    /// Without duplicates, `objects` are indexed, as they will not change
+    /// anymore.
+    fn duplicates(
+        &mut self,
+        document: &Document,
+        objects: &BTreeSet<ObjectId>,
+    ) -> BTreeMap<ObjectId, ObjectId> {
+        let mut serializer = Serializer::new(document);
+        let mut added: HashMap<u128, Vec<ObjectId>> = HashMap::new();
+        let mut mapping = BTreeMap::new();
+
+        // Objects are visited in order, so that the first copy is kept.
+        for &id in objects {
+            let object = &document.objects[&id];
+            let hash = serializer.hash(object);
+
+            // Hashes are only compared first, as collisions are possible.
+            let original = [self.originals.get(&hash), added.get(&hash)]
+                .into_iter()
+                .flatten()
+                .flatten()
+                .find(|original| is_identical(&document.objects[original], object));
+            match original {
+                Some(&original) => {
+                    mapping.insert(id, original);
+                },
+                None => added.entry(hash).or_default().push(id),
+            }
+        }
+
+        if mapping.is_empty() {
+            for (hash, ids) in added {
+                self.originals.entry(hash).or_default().extend(ids);
+            }
+        }
+        mapping
+    }
+
+    /// Replace the duplicated shareable objects numbered from `first` with
+    /// identical objects, index the others, and return the number of
+    /// removed objects.
+    ///
+    /// Objects numbered before `first` must not refer to the others, as is
+    /// the case right after objects are imported, since they are numbered
+    /// after the existing ones.
+    ///
+    /// Deduplication is repeated until no duplicate is left, as objects that
+    /// only differ by references to duplicates become identical once those
+    /// references are replaced, e.g., fonts whose font programs are
+    /// identical.
+    pub fn deduplicate(&mut self, document: &mut Document, first: u32) -> usize {
+        let mut objects = shareable_objects(document, first);
+        let mut removed = 0;
+
+        loop {
+            let mapping = self.duplicates(document, &objects);
+            if mapping.is_empty() {
+                return removed;
+            }
+            for id in mapping.keys() {
+                document.objects.remove(id);
+                objects.remove(id);
+            }
+            for (_, object) in document.objects.range_mut((first, 0)..) {
+                replace_references(object, &mapping);
+            }
+            for (_, value) in document.trailer.iter_mut() {
+                replace_references(value, &mapping);
+            }
+            removed += mapping.len();
+        }
+    }
+}
+
+/// Replace duplicated objects drawn by pages or annotations, e.g., fonts,
+/// images or appearance streams, with a single copy, and return the number
+/// of removed objects.
+pub fn deduplicate_objects(document: &mut Document) -> usize {
+    Deduplicator::default().deduplicate(document, 0)
+}
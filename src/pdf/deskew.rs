@@ -0,0 +1,159 @@
+//! Skew detection of scanned pages, from the lines of dark pixels of their
+//! image.
+//!
+//! The skew angle is the one whose projection of dark pixels onto the
+//! vertical axis has the highest variance: text lines and table rules are
+//! then projected onto a few rows, separated by blank rows.
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream,
+    content::{Content, Operation},
+};
+
+use super::{content::Matrix, images};
+
+/// Luminance below which pixels are dark.
+const DARK: u8 = 128;
+
+/// Maximum number of pixels read along each axis, as larger images are
+/// subsampled.
+const MAX_SAMPLES: usize = 1000;
+
+/// Step between tested angles, in degrees.
+pub const ANGLE_STEP: f32 = 0.1;
+
+/// Minimum number of dark pixels for the skew to be estimated.
+const MIN_DARK_PIXELS: usize = 100;
+
+/// Return the angle, in degrees counterclockwise and at most `max_angle`
+/// in absolute value, by which the lines of an image are rotated.
+///
+/// Pixels are given row by row from the top. Returns `None` for images with
+/// too few dark pixels, e.g., blank pages.
+#[must_use]
+pub fn skew_angle(width: u32, height: u32, pixels: &[u8], max_angle: f32) -> Option<f32> {
+    let (width, height) = (width as usize, height as usize);
+    let stride = width.max(height).div_ceil(MAX_SAMPLES).max(1);
+    let dark: Vec<(f32, f32)> = (0..height)
+        .step_by(stride)
+        .flat_map(|y| (0..width).step_by(stride).map(move |x| (x, y)))
+        .filter(|&(x, y)| pixels.get(y * width + x).is_some_and(|&pixel| pixel < DARK))
+        .map(|(x, y)| ((x / stride) as f32, (y / stride) as f32))
+        .collect();
+
+    if dark.len() < MIN_DARK_PIXELS {
+        return None;
+    }
+    let (columns, rows) = (width.div_ceil(stride), height.div_ceil(stride));
+    let steps = (max_angle / ANGLE_STEP).round() as i32;
+    let mut best: Option<(u64, f32)> = None;
+
+    // Small angles come first, so that they win ties.
+    let mut angles: Vec<i32> = (-steps..=steps).collect();
+    angles.sort_by_key(|step| step.abs());
+
+    for step in angles {
+        let angle = step as f32 * ANGLE_STEP;
+        let slope = angle.to_radians().tan();
+        // Lines rotated counterclockwise go up to the right, i.e., toward
+        // the first row, so `y + slope * x` is constant along them.
+        let offset = (slope * columns as f32).min(0.0);
+        let mut bins = vec![0u64; rows + (slope.abs() * columns as f32).ceil() as usize + 1];
+
+        for &(x, y) in &dark {
+            let bin = (y + slope * x - offset).round() as usize;
+            if let Some(count) = bins.get_mut(bin) {
+                *count += 1;
+            }
+        }
+        let score = bins.iter().map(|count| count * count).sum();
+        if best.map_or(true, |(best, _)| score > best) {
+            best = Some((score, angle));
+        }
+    }
+    best.map(|(_, angle)| angle)
+}
+
+/// Return the angle, in degrees counterclockwise, by which the content of
+/// an image-only page is rotated, and the center of its image.
+///
+/// Pages with text, e.g., OCRed scans, with several images, or with an
+/// image that is not drawn upright or cannot be decoded, return `None`.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+pub fn page_skew(
+    document: &Document,
+    page_id: ObjectId,
+    max_angle: f32,
+) -> lopdf::Result<Option<(f32, (f32, f32))>> {
+    let Some(image) = images::page_scan(document, page_id)? else {
+        return Ok(None);
+    };
+    let ctm = image.ctm;
+    let Some((width, height, pixels)) = images::luminance(document, image.stream) else {
+        return Ok(None);
+    };
+    let Some(angle) = skew_angle(width, height, &pixels, max_angle) else {
+        return Ok(None);
+    };
+
+    // Pixels may not be square on the page.
+    let aspect = (ctm.d / height as f32) / (ctm.a / width as f32);
+    let angle = (angle.to_radians().tan() * aspect).atan().to_degrees();
+
+    Ok(Some((angle, ctm.apply(0.5, 0.5))))
+}
+
+/// Return the matrix that rotates by the given angle, in degrees
+/// counterclockwise, around a point.
+#[must_use]
+fn rotation(angle: f32, (x, y): (f32, f32)) -> Matrix {
+    let (sin, cos) = angle.to_radians().sin_cos();
+
+    Matrix::new(1.0, 0.0, 0.0, 1.0, -x, -y)
+        .then(&Matrix::new(cos, sin, -sin, cos, 0.0, 0.0))
+        .then(&Matrix::new(1.0, 0.0, 0.0, 1.0, x, y))
+}
+
+/// Rotate the content of a page by the given angle, in degrees
+/// counterclockwise, around a point.
+///
+/// Unlike `/Rotate`, this rotates the content within the page, whose
+/// geometry is unchanged: corners of the content may be cropped.
+///
+/// # Errors
+///
+/// Fails if the page is not a dictionary.
+pub fn rotate_content(
+    document: &mut Document,
+    page_id: ObjectId,
+    angle: f32,
+    center: (f32, f32),
+) -> lopdf::Result<()> {
+    let Matrix { a, b, c, d, e, f } = rotation(angle, center);
+    let mut transform = Content {
+        operations: vec![
+            Operation::new("q", vec![]),
+            Operation::new("cm", [a, b, c, d, e, f].map(Object::Real).to_vec()),
+        ],
+    }
+    .encode()?;
+    transform.push(b'\n');
+
+    let mut contents = document.get_page_contents(page_id);
+    let save = document.add_object(Stream::new(Dictionary::new(), transform));
+    let restore = document.add_object(Stream::new(Dictionary::new(), b"\nQ\n".to_vec()));
+    contents.insert(0, save);
+    contents.push(restore);
+
+    document.get_dictionary_mut(page_id)?.set(
+        "Contents",
+        contents
+            .into_iter()
+            .map(Object::Reference)
+            .collect::<Vec<_>>(),
+    );
+    Ok(())
+}
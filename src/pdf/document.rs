@@ -0,0 +1,239 @@
+//! Extension methods for [`lopdf::Document`].
+
+#[cfg(feature = "images")]
+use std::collections::BTreeMap;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, dictionary};
+
+/// Maximum depth of the page tree, which guards against `/Parent` cycles in
+/// malformed documents.
+const MAX_PAGE_TREE_DEPTH: usize = 256;
+
+/// Extension methods for [`Document`] shared by commands.
+pub trait DocumentExt {
+    /// Return an attribute of a page, dereferenced, possibly inherited from
+    /// the page tree.
+    ///
+    /// Only `Resources`, `MediaBox`, `CropBox` and `Rotate` are inheritable,
+    /// other attributes should be read from the page dictionary.
+    fn page_attribute(&self, page_id: ObjectId, key: &[u8]) -> Option<&Object>;
+
+    /// Return the resources dictionary of a page, possibly inherited from the
+    /// page tree.
+    fn page_resources(&self, page_id: ObjectId) -> Option<&Dictionary>;
+
+    /// Return the media box of a page, as `[x0, y0, x1, y1]`, possibly
+    /// inherited from the page tree.
+    fn page_media_box(&self, page_id: ObjectId) -> Option<[f32; 4]>;
+
+    /// Return the crop box of a page, i.e., the region shown by viewers, as
+    /// `[x0, y0, x1, y1]`, possibly inherited from the page tree, and
+    /// defaulting to the media box.
+    fn page_crop_box(&self, page_id: ObjectId) -> Option<[f32; 4]>;
+
+    /// Return the clockwise rotation of a page when shown, in degrees, i.e.,
+    /// 0, 90, 180 or 270, possibly inherited from the page tree.
+    fn page_rotation(&self, page_id: ObjectId) -> i64;
+
+    /// Copy a page as a form XObject, so that it can be drawn on other pages,
+    /// and return its ID.
+    ///
+    /// The form is clipped to the page's crop box, and keeps the page's
+    /// coordinates. Annotations and the page rotation are not copied.
+    fn page_to_form(&mut self, page_id: ObjectId) -> lopdf::Result<ObjectId>;
+
+    /// Return the XObjects available to a page, by resource name.
+    #[cfg(feature = "images")]
+    fn page_xobjects(&self, page_id: ObjectId) -> BTreeMap<Vec<u8>, ObjectId>;
+
+    /// Add a reference to an object in a category of the page's resources
+    /// (e.g., `Font` or `XObject`), under a fresh name starting with
+    /// `prefix`.
+    ///
+    /// If the page inherits its resources, they are first copied into the
+    /// page. Returns the name of the new resource.
+    fn add_page_resource(
+        &mut self,
+        page_id: ObjectId,
+        category: &[u8],
+        prefix: &str,
+        object_id: ObjectId,
+    ) -> lopdf::Result<Vec<u8>>;
+
+    /// Draw additional content on top of a page.
+    ///
+    /// Existing content is wrapped in a `q`/`Q` pair so that graphics state
+    /// changes it leaves behind do not affect the new content.
+    fn overlay_page_content(&mut self, page_id: ObjectId, content: Vec<u8>) -> lopdf::Result<()>;
+}
+
+impl DocumentExt for Document {
+    fn page_attribute(&self, page_id: ObjectId, key: &[u8]) -> Option<&Object> {
+        let mut node = self.get_dictionary(page_id).ok()?;
+
+        for _ in 0..MAX_PAGE_TREE_DEPTH {
+            if let Ok(value) = node.get_deref(key, self) {
+                return Some(value);
+            }
+            node = node
+                .get_deref(b"Parent", self)
+                .and_then(Object::as_dict)
+                .ok()?;
+        }
+        None
+    }
+
+    fn page_resources(&self, page_id: ObjectId) -> Option<&Dictionary> {
+        self.page_attribute(page_id, b"Resources")?.as_dict().ok()
+    }
+
+    fn page_media_box(&self, page_id: ObjectId) -> Option<[f32; 4]> {
+        rectangle(self.page_attribute(page_id, b"MediaBox")?)
+    }
+
+    fn page_crop_box(&self, page_id: ObjectId) -> Option<[f32; 4]> {
+        self.page_attribute(page_id, b"CropBox")
+            .and_then(rectangle)
+            .or_else(|| self.page_media_box(page_id))
+    }
+
+    fn page_rotation(&self, page_id: ObjectId) -> i64 {
+        self.page_attribute(page_id, b"Rotate")
+            .and_then(|rotate| rotate.as_i64().ok())
+            .map_or(0, |rotate| rotate.rem_euclid(360) / 90 * 90)
+    }
+
+    fn page_to_form(&mut self, page_id: ObjectId) -> lopdf::Result<ObjectId> {
+        let mut content = vec![];
+        for id in self.get_page_contents(page_id) {
+            let stream = self.get_object(id)?.as_stream()?;
+            content.extend(
+                stream
+                    .decompressed_content()
+                    .unwrap_or_else(|_| stream.content.clone()),
+            );
+            content.push(b'\n');
+        }
+        let bbox = self
+            .page_crop_box(page_id)
+            .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let mut dict = dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => bbox.into_iter().map(Object::Real).collect::<Vec<_>>(),
+        };
+        if let Some(resources) = self.page_attribute(page_id, b"Resources") {
+            dict.set("Resources", resources.clone());
+        }
+        let mut stream = Stream::new(dict, content);
+        let _ = stream.compress();
+
+        Ok(self.add_object(stream))
+    }
+
+    #[cfg(feature = "images")]
+    fn page_xobjects(&self, page_id: ObjectId) -> BTreeMap<Vec<u8>, ObjectId> {
+        self.page_resources(page_id)
+            .and_then(|resources| {
+                resources
+                    .get_deref(b"XObject", self)
+                    .and_then(Object::as_dict)
+                    .ok()
+            })
+            .map(|xobjects| {
+                xobjects
+                    .iter()
+                    .filter_map(|(name, object)| Some((name.clone(), object.as_reference().ok()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn add_page_resource(
+        &mut self,
+        page_id: ObjectId,
+        category: &[u8],
+        prefix: &str,
+        object_id: ObjectId,
+    ) -> lopdf::Result<Vec<u8>> {
+        if !self.get_dictionary(page_id)?.has(b"Resources") {
+            let inherited = self.page_resources(page_id).cloned().unwrap_or_default();
+            self.get_dictionary_mut(page_id)?
+                .set("Resources", inherited);
+        }
+
+        let resources_id = self
+            .get_dictionary(page_id)?
+            .get(b"Resources")
+            .and_then(Object::as_reference)
+            .ok();
+        let resources = match resources_id {
+            Some(id) => self.get_dictionary(id)?,
+            None => self.get_dictionary(page_id)?.get(b"Resources")?.as_dict()?,
+        };
+        let category_id = resources.get(category).and_then(Object::as_reference).ok();
+        let existing = match category_id {
+            Some(id) => Some(self.get_dictionary(id)?),
+            None => resources.get(category).and_then(Object::as_dict).ok(),
+        };
+        let name = (0..)
+            .map(|i| format!("{prefix}{i}").into_bytes())
+            .find(|name| !existing.is_some_and(|dict| dict.has(name)))
+            .unwrap();
+
+        let dict = match (category_id, resources_id) {
+            (Some(id), _) => self.get_dictionary_mut(id)?,
+            (None, Some(id)) => resources_entry(self.get_dictionary_mut(id)?, category)?,
+            (None, None) => {
+                let page = self.get_dictionary_mut(page_id)?;
+                resources_entry(page.get_mut(b"Resources")?.as_dict_mut()?, category)?
+            },
+        };
+        dict.set(name.clone(), Object::Reference(object_id));
+
+        Ok(name)
+    }
+
+    fn overlay_page_content(&mut self, page_id: ObjectId, content: Vec<u8>) -> lopdf::Result<()> {
+        let mut contents = self.get_page_contents(page_id);
+        let save = self.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+        let restore = self.add_object(Stream::new(Dictionary::new(), b"\nQ\n".to_vec()));
+        let overlay = self.add_object(Stream::new(Dictionary::new(), content));
+
+        contents.insert(0, save);
+        contents.push(restore);
+        contents.push(overlay);
+
+        self.get_dictionary_mut(page_id)?.set(
+            "Contents",
+            contents
+                .into_iter()
+                .map(Object::Reference)
+                .collect::<Vec<_>>(),
+        );
+        Ok(())
+    }
+}
+
+/// Return the sub-dictionary of a resources dictionary, creating it if needed.
+fn resources_entry<'a>(
+    resources: &'a mut Dictionary,
+    category: &[u8],
+) -> lopdf::Result<&'a mut Dictionary> {
+    if resources.get(category).and_then(Object::as_dict).is_err() {
+        resources.set(category.to_vec(), Dictionary::new());
+    }
+    resources.get_mut(category)?.as_dict_mut()
+}
+
+/// Return a rectangle, as `[x0, y0, x1, y1]` with `x0 <= x1` and `y0 <= y1`.
+fn rectangle(object: &Object) -> Option<[f32; 4]> {
+    let values: Vec<f32> = object
+        .as_array()
+        .ok()?
+        .iter()
+        .filter_map(|value| value.as_float().ok())
+        .collect();
+    let [x0, y0, x1, y1] = values.try_into().ok()?;
+    Some([x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)])
+}
@@ -0,0 +1,215 @@
+//! Hybrid e-invoices, i.e., Factur-X and ZUGFeRD, which embed the XML
+//! invoice in a PDF/A-3 document.
+
+use lopdf::Document;
+
+use super::{
+    attachments::{self, Attachment, NewAttachment},
+    xmp,
+};
+
+/// Namespace of the Factur-X XMP properties.
+const NAMESPACE: &str = "urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#";
+
+/// Names of the XML invoice in Factur-X and ZUGFeRD documents.
+pub const INVOICE_NAMES: &[&str] = &[
+    "factur-x.xml",
+    "xrechnung.xml",
+    "zugferd-invoice.xml",
+    "ZUGFeRD-invoice.xml",
+];
+
+/// Factur-X profile, i.e., how much of the invoice the XML describes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Profile {
+    Minimum,
+    BasicWl,
+    Basic,
+    En16931,
+    Extended,
+    XRechnung,
+}
+
+impl Profile {
+    /// Return the conformance level written in XMP metadata.
+    #[must_use]
+    pub fn conformance_level(self) -> &'static str {
+        match self {
+            Self::Minimum => "MINIMUM",
+            Self::BasicWl => "BASIC WL",
+            Self::Basic => "BASIC",
+            Self::En16931 => "EN 16931",
+            Self::Extended => "EXTENDED",
+            Self::XRechnung => "XRECHNUNG",
+        }
+    }
+
+    /// Return the name of the embedded XML file.
+    #[must_use]
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::XRechnung => "xrechnung.xml",
+            _ => "factur-x.xml",
+        }
+    }
+
+    /// Return the relationship of the XML file to the document.
+    ///
+    /// The XML of the `MINIMUM` and `BASIC WL` profiles is not a valid invoice
+    /// on its own, and only holds data of the PDF invoice, whereas the XML of
+    /// other profiles is an alternative representation of it.
+    #[must_use]
+    pub fn relationship(self) -> &'static str {
+        match self {
+            Self::Minimum | Self::BasicWl => "Data",
+            _ => "Alternative",
+        }
+    }
+}
+
+/// Return the `rdf:Description` elements of the Factur-X properties, and of
+/// the PDF/A extension schema that declares them.
+fn descriptions(profile: Profile) -> String {
+    let property = |name: &str, description: &str| {
+        format!(
+            "      <rdf:li \
+             rdf:parseType=\"Resource\"><pdfaProperty:name>{name}</pdfaProperty:\
+             name><pdfaProperty:valueType>Text</pdfaProperty:valueType><pdfaProperty:\
+             category>external</pdfaProperty:category><pdfaProperty:description>{description}</\
+             pdfaProperty:description></rdf:li>\n"
+        )
+    };
+    let properties = [
+        property("DocumentFileName", "The name of the embedded XML document"),
+        property("DocumentType", "The type of the hybrid document"),
+        property("Version", "The version of the XML schema"),
+        property("ConformanceLevel", "The conformance level of the XML"),
+    ]
+    .concat();
+
+    format!(
+        "  <rdf:Description rdf:about=\"\" xmlns:fx=\"{NAMESPACE}\">\n   \
+         <fx:DocumentType>INVOICE</fx:DocumentType>\n   \
+         <fx:DocumentFileName>{}</fx:DocumentFileName>\n   \
+         <fx:Version>1.0</fx:Version>\n   \
+         <fx:ConformanceLevel>{}</fx:ConformanceLevel>\n  \
+         </rdf:Description>\n  \
+         <rdf:Description rdf:about=\"\" \
+         xmlns:pdfaExtension=\"http://www.aiim.org/pdfa/ns/extension/\" \
+         xmlns:pdfaSchema=\"http://www.aiim.org/pdfa/ns/schema#\" \
+         xmlns:pdfaProperty=\"http://www.aiim.org/pdfa/ns/property#\">\n   \
+         <pdfaExtension:schemas><rdf:Bag><rdf:li rdf:parseType=\"Resource\">\n    \
+         <pdfaSchema:schema>Factur-X PDFA Extension Schema</pdfaSchema:schema>\n    \
+         <pdfaSchema:namespaceURI>{NAMESPACE}</pdfaSchema:namespaceURI>\n    \
+         <pdfaSchema:prefix>fx</pdfaSchema:prefix>\n    \
+         <pdfaSchema:property><rdf:Seq>\n{properties}    </rdf:Seq></pdfaSchema:property>\n   \
+         </rdf:li></rdf:Bag></pdfaExtension:schemas>\n  \
+         </rdf:Description>\n",
+        profile.file_name(),
+        profile.conformance_level(),
+    )
+}
+
+/// Remove the `rdf:Description` elements that declare the given namespace.
+fn remove_descriptions(xmp: &mut String, namespace: &str) {
+    const END: &str = "</rdf:Description>";
+    let mut start = 0;
+
+    while let Some(offset) = xmp[start..].find("<rdf:Description") {
+        let begin = start + offset;
+        let Some(end) = xmp[begin..].find(END).map(|end| begin + end + END.len()) else {
+            break;
+        };
+        if xmp[begin..end].contains(namespace) {
+            // Also remove the line break that follows, if any.
+            let end = end + usize::from(xmp[end..].starts_with('\n'));
+            xmp.replace_range(begin..end, "");
+            start = begin;
+        } else {
+            start = end;
+        }
+    }
+}
+
+/// Embed an XML invoice in a document, with the XMP metadata that Factur-X
+/// requires.
+///
+/// Factur-X properties are added to the existing XMP metadata, whose PDF/A
+/// part is set to 3, as only PDF/A-3 allows embedding XML files. The rest of
+/// the PDF/A requirements must be met by the document, e.g., by converting
+/// it to PDF/A-3 first.
+pub fn attach(
+    document: &mut Document,
+    xml: Vec<u8>,
+    profile: Profile,
+    date: &str,
+) -> lopdf::Result<()> {
+    attachments::attach(
+        document,
+        xml,
+        &NewAttachment {
+            name: profile.file_name(),
+            description: Some("Factur-X invoice"),
+            mime_type: Some("text/xml"),
+            relationship: Some(profile.relationship()),
+            date: Some(date),
+        },
+    )?;
+
+    let xmp = match xmp::metadata(document) {
+        Some(mut xmp) if xmp.contains("</rdf:RDF>") => {
+            remove_descriptions(&mut xmp, NAMESPACE);
+            remove_descriptions(&mut xmp, "Factur-X PDFA Extension Schema");
+            for part in ["1", "2"] {
+                xmp = xmp
+                    .replace(
+                        &format!("<pdfaid:part>{part}</pdfaid:part>"),
+                        "<pdfaid:part>3</pdfaid:part>",
+                    )
+                    .replace(&format!("pdfaid:part=\"{part}\""), "pdfaid:part=\"3\"");
+            }
+            let index = xmp.find("</rdf:RDF>").unwrap_or_default();
+            xmp.insert_str(index, &descriptions(profile));
+            xmp
+        },
+        _ => {
+            xmp::packet(&format!(
+            "  <rdf:Description rdf:about=\"\" \
+             xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n   \
+             <pdfaid:part>3</pdfaid:part>\n   \
+             <pdfaid:conformance>B</pdfaid:conformance>\n  \
+             </rdf:Description>\n{}",
+            descriptions(profile)
+        ))
+        },
+    };
+    xmp::set_metadata(document, xmp)?;
+
+    if document.version.as_str() < "1.7" {
+        document.version = "1.7".to_string();
+    }
+    Ok(())
+}
+
+/// Return the XML invoice embedded in a document, if any.
+#[must_use]
+pub fn invoice(document: &Document) -> Option<Attachment> {
+    let attachments = attachments::attachments(document);
+
+    INVOICE_NAMES.iter().find_map(|name| {
+        attachments
+            .iter()
+            .find(|attachment| attachment.name == *name)
+            .cloned()
+    })
+}
+
+/// Return the Factur-X conformance level written in XMP metadata, if any.
+#[must_use]
+pub fn conformance_level(document: &Document) -> Option<String> {
+    let xmp = xmp::metadata(document)?;
+    let start = xmp.find("<fx:ConformanceLevel>")? + "<fx:ConformanceLevel>".len();
+    let end = start + xmp[start..].find("</fx:ConformanceLevel>")?;
+
+    Some(xmp[start..end].trim().to_string())
+}
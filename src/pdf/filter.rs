@@ -0,0 +1,357 @@
+//! Filter expressions on annotations, e.g.,
+//! `subtype == "Highlight" && author =~ "alice|bob" && page <= 10`.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use super::annotations::Annotation;
+
+/// Fields of annotations that filters compare.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Field {
+    Subtype,
+    Author,
+    Contents,
+    /// Modification date, e.g., `2024-01-31 12:00`, hence dates compare as
+    /// strings.
+    Date,
+    Page,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "subtype" => Some(Self::Subtype),
+            "author" => Some(Self::Author),
+            "contents" => Some(Self::Contents),
+            "date" => Some(Self::Date),
+            "page" => Some(Self::Page),
+            _ => None,
+        }
+    }
+
+    /// Return the text of a field, or an empty string if an annotation does
+    /// not have it.
+    fn text(self, annotation: &Annotation) -> &str {
+        match self {
+            Self::Subtype => &annotation.subtype,
+            Self::Author => annotation.author.as_deref().unwrap_or(""),
+            Self::Contents => annotation.contents.as_deref().unwrap_or(""),
+            Self::Date => annotation.date.as_deref().unwrap_or(""),
+            Self::Page => "",
+        }
+    }
+}
+
+/// Comparison operators.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// Parsed filter expression.
+#[derive(Clone, Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    /// Comparison of a text field with a string.
+    Text(Field, Operator, String),
+    /// Comparison of the page number with a number.
+    Page(Operator, u32),
+    /// Match of a text field against a regular expression.
+    #[cfg(feature = "regex")]
+    Match(Field, Regex),
+}
+
+impl Expr {
+    fn eval(&self, annotation: &Annotation) -> bool {
+        match self {
+            Self::And(left, right) => left.eval(annotation) && right.eval(annotation),
+            Self::Or(left, right) => left.eval(annotation) || right.eval(annotation),
+            Self::Not(expr) => !expr.eval(annotation),
+            Self::Text(field, operator, value) => {
+                operator.holds(field.text(annotation).cmp(value.as_str()))
+            },
+            Self::Page(operator, value) => operator.holds(annotation.page.cmp(value)),
+            #[cfg(feature = "regex")]
+            Self::Match(field, regex) => regex.is_match(field.text(annotation)),
+        }
+    }
+}
+
+/// Tokens of filter expressions.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Number(u32),
+    Operator(Operator),
+    Match,
+    NotMatch,
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(name) => write!(f, "{name}"),
+            Self::String(value) => write!(f, "{value:?}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Operator(operator) => {
+                f.write_str(match operator {
+                    Operator::Eq => "==",
+                    Operator::Ne => "!=",
+                    Operator::Lt => "<",
+                    Operator::Le => "<=",
+                    Operator::Gt => ">",
+                    Operator::Ge => ">=",
+                })
+            },
+            Self::Match => f.write_str("=~"),
+            Self::NotMatch => f.write_str("!~"),
+            Self::And => f.write_str("&&"),
+            Self::Or => f.write_str("||"),
+            Self::Not => f.write_str("!"),
+            Self::Open => f.write_str("("),
+            Self::Close => f.write_str(")"),
+        }
+    }
+}
+
+/// Split an expression into tokens.
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if_eq(&expected).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' if next_is('&') => Token::And,
+            '|' if next_is('|') => Token::Or,
+            '=' if next_is('=') => Token::Operator(Operator::Eq),
+            '=' if next_is('~') => Token::Match,
+            '!' if next_is('=') => Token::Operator(Operator::Ne),
+            '!' if next_is('~') => Token::NotMatch,
+            '!' => Token::Not,
+            '<' if next_is('=') => Token::Operator(Operator::Le),
+            '<' => Token::Operator(Operator::Lt),
+            '>' if next_is('=') => Token::Operator(Operator::Ge),
+            '>' => Token::Operator(Operator::Gt),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            value.push(chars.next().ok_or("unterminated string")?);
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                Token::String(value)
+            },
+            c if c.is_ascii_digit() => {
+                let mut digits = c.to_string();
+                while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(c);
+                }
+                Token::Number(
+                    digits
+                        .parse()
+                        .map_err(|_| format!("invalid number: {digits}"))?,
+                )
+            },
+            c if c.is_ascii_alphabetic() => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                Token::Ident(name)
+            },
+            c => return Err(format!("unexpected character: {c:?}")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser of filter expressions, where `!` binds tighter
+/// than `&&`, which binds tighter than `||`.
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+    peeked: Option<Token>,
+}
+
+impl Parser {
+    fn peek(&mut self) -> Option<&Token> {
+        if self.peeked.is_none() {
+            self.peeked = self.tokens.next();
+        }
+        self.peeked.as_ref()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        self.peeked.take().or_else(|| self.tokens.next())
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    Some(token) => Err(format!("expected `)`, found `{token}`")),
+                    None => Err("expected `)`, found the end of the filter".to_string()),
+                }
+            },
+            Some(Token::Ident(name)) => {
+                let field = Field::parse(&name).ok_or_else(|| {
+                    format!(
+                        "unknown field: {name:?}, expected one of subtype, author, contents, date \
+                         or page"
+                    )
+                })?;
+                self.comparison(field)
+            },
+            Some(token) => Err(format!("expected a field, found `{token}`")),
+            None => Err("expected a field, found the end of the filter".to_string()),
+        }
+    }
+
+    fn comparison(&mut self, field: Field) -> Result<Expr, String> {
+        let operator = self.next();
+        let value = self.next();
+        match (field, operator, value) {
+            (Field::Page, Some(Token::Operator(operator)), Some(Token::Number(value))) => {
+                Ok(Expr::Page(operator, value))
+            },
+            (Field::Page, Some(Token::Operator(_)), value) => {
+                Err(format!(
+                    "expected a page number, found {}",
+                    value.map_or("the end of the filter".to_string(), |v| format!("`{v}`"))
+                ))
+            },
+            (Field::Page, operator, _) => {
+                Err(format!(
+                    "expected a comparison of the page, found {}",
+                    operator.map_or("the end of the filter".to_string(), |o| format!("`{o}`"))
+                ))
+            },
+            (_, Some(Token::Operator(operator)), Some(Token::String(value))) => {
+                Ok(Expr::Text(field, operator, value))
+            },
+            #[cfg(feature = "regex")]
+            (_, Some(token @ (Token::Match | Token::NotMatch)), Some(Token::String(pattern))) => {
+                let regex =
+                    Regex::new(&pattern).map_err(|e| format!("invalid regex {pattern:?}: {e}"))?;
+                let expr = Expr::Match(field, regex);
+                Ok(if token == Token::NotMatch {
+                    Expr::Not(Box::new(expr))
+                } else {
+                    expr
+                })
+            },
+            #[cfg(not(feature = "regex"))]
+            (_, Some(Token::Match | Token::NotMatch), Some(Token::String(_))) => {
+                Err("matching regular expressions requires the `regex` feature".to_string())
+            },
+            (_, Some(Token::Operator(_) | Token::Match | Token::NotMatch), value) => {
+                Err(format!(
+                    "expected a string, found {}",
+                    value.map_or("the end of the filter".to_string(), |v| format!("`{v}`"))
+                ))
+            },
+            (_, operator, _) => {
+                Err(format!(
+                    "expected a comparison, found {}",
+                    operator.map_or("the end of the filter".to_string(), |o| format!("`{o}`"))
+                ))
+            },
+        }
+    }
+}
+
+/// Filter on annotations, given as an expression that compares their
+/// fields, e.g., `subtype == "Highlight" && author =~ "alice|bob"`.
+///
+/// Fields are `subtype`, `author`, `contents`, `date` and `page`. Text
+/// fields are compared to strings with `==`, `!=`, `<`, `<=`, `>` and `>=`,
+/// or matched against regular expressions with `=~` and `!~`, and missing
+/// ones are empty. The page number is compared to numbers. Comparisons are
+/// combined with `&&`, `||`, `!` and parentheses.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    /// Return whether an annotation passes the filter.
+    #[must_use]
+    pub fn matches(&self, annotation: &Annotation) -> bool {
+        self.expr.eval(annotation)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?.into_iter(),
+            peeked: None,
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.next() {
+            return Err(format!("unexpected `{token}` after the end of the filter"));
+        }
+        Ok(Self { expr })
+    }
+}
@@ -0,0 +1,63 @@
+//! Fingerprints of pages, which are equal for pages that look the same,
+//! whatever the objects they are made of.
+
+use lopdf::{Document, Object, ObjectId};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::{
+    canonical::{self, Serializer},
+    document::DocumentExt,
+    reproducible::fnv1a_128,
+};
+
+/// Keys that do not change how objects look, e.g., back references, or the
+/// compression of streams, which are compared decompressed.
+const IGNORED_KEYS: &[&[u8]] = &[b"Parent", b"Length", b"Filter", b"DecodeParms"];
+
+/// Return the fingerprint of a page, from its normalized content, its
+/// resources and its geometry.
+///
+/// Annotations are ignored. Pages that look the same but are drawn
+/// differently, e.g., scans of the same sheet, have different fingerprints.
+#[must_use]
+pub fn page_fingerprint(document: &Document, page_id: ObjectId) -> u128 {
+    let mut serializer = Serializer::new(document)
+        .resolve_references(true)
+        .decompress_streams(true)
+        .ignore_keys(IGNORED_KEYS);
+    let mut out = Vec::new();
+
+    // Re-encoding the content normalizes whitespace and number formats.
+    let content = match document.get_and_decode_page_content(page_id) {
+        Ok(content) => content.encode().unwrap_or_default(),
+        Err(_) => document.get_page_content(page_id).unwrap_or_default(),
+    };
+    canonical::write_bytes(&content, &mut out);
+    for key in [b"MediaBox".as_slice(), b"CropBox", b"Rotate", b"Resources"] {
+        canonical::write_bytes(key, &mut out);
+        let value = document.page_attribute(page_id, key);
+        serializer.write(value.unwrap_or(&Object::Null), &mut out);
+    }
+    fnv1a_128(&out)
+}
+
+/// Return the fingerprints of the pages of a document, in order, e.g., to
+/// find duplicated pages, or pages that changed between revisions.
+#[must_use]
+pub fn page_fingerprints(document: &Document) -> Vec<u128> {
+    let pages: Vec<ObjectId> = document.page_iter().collect();
+    #[cfg(feature = "parallel")]
+    let pages = pages.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let pages = pages.iter();
+    pages
+        .map(|&page_id| page_fingerprint(document, page_id))
+        .collect()
+}
+
+/// Return a fingerprint as 32 lowercase hexadecimal digits.
+#[must_use]
+pub fn to_hex(fingerprint: u128) -> String {
+    format!("{fingerprint:032x}")
+}
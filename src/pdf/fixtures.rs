@@ -0,0 +1,395 @@
+//! Generation of synthetic documents, for benchmarks, tests and bug reports.
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream,
+    content::{Content, Operation},
+    dictionary,
+};
+
+use super::pdfstring;
+
+/// Size of synthetic pages, i.e., US Letter.
+const PAGE_SIZE: (f32, f32) = (612.0, 792.0);
+
+/// Default annotation subtypes of synthetic documents, used in turn.
+pub const DEFAULT_SUBTYPES: &[&str] = &["Text", "Highlight", "Square", "Ink", "Link"];
+
+/// Font resource name used by page contents and form fields.
+const FONT_NAME: &str = "Helv";
+
+/// Number of annotations stacked in a column before starting a new one.
+const ANNOTATIONS_PER_COLUMN: usize = 20;
+
+/// Custom structure type of page titles in tagged documents, mapped to `H1`.
+const TITLE_TYPE: &str = "Heading";
+
+/// Return the name of the optional content resource of the `index`-th layer.
+fn layer_resource(index: usize) -> String {
+    format!("L{}", index + 1)
+}
+
+/// Return the content stream of a synthetic page, with a title, marked as
+/// the first marked content of the page if `tagged`, followed by a line of
+/// text on each layer.
+fn page_content(page: u32, tagged: bool, layers: usize) -> Stream {
+    let mut operations = vec![
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec![FONT_NAME.into(), 24.into()]),
+        Operation::new("Td", vec![72.into(), 720.into()]),
+        Operation::new("Tj", vec![Object::string_literal(format!("Page {page}"))]),
+        Operation::new("ET", vec![]),
+    ];
+    if tagged {
+        operations.insert(
+            0,
+            Operation::new(
+                "BDC",
+                vec![TITLE_TYPE.into(), dictionary! { "MCID" => 0 }.into()],
+            ),
+        );
+        operations.push(Operation::new("EMC", vec![]));
+    }
+    for index in 0..layers {
+        let y = 690.0 - 20.0 * index as f32;
+        operations.extend([
+            Operation::new(
+                "BDC",
+                vec![
+                    "OC".into(),
+                    Object::Name(layer_resource(index).into_bytes()),
+                ],
+            ),
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec![FONT_NAME.into(), 12.into()]),
+            Operation::new("Td", vec![72.into(), y.into()]),
+            Operation::new(
+                "Tj",
+                vec![Object::string_literal(format!("Layer {}", index + 1))],
+            ),
+            Operation::new("ET", vec![]),
+            Operation::new("EMC", vec![]),
+        ]);
+    }
+    let content = Content { operations }.encode().unwrap_or_default();
+    Stream::new(dictionary! {}, content)
+}
+
+/// Return the rectangle of the `index`-th annotation of a page, placed on a
+/// grid so that annotations do not overlap.
+fn grid_rect(index: usize) -> [f32; 4] {
+    let column = (index / ANNOTATIONS_PER_COLUMN) as f32;
+    let row = (index % ANNOTATIONS_PER_COLUMN) as f32;
+    let (x0, y0) = (72.0 + 24.0 * column, 660.0 - 28.0 * row);
+    [x0, y0, x0 + 20.0, y0 + 20.0]
+}
+
+/// Return an annotation of a given subtype, with the entries its subtype
+/// requires.
+fn annotation(subtype: &str, rect: [f32; 4], number: usize, page_id: ObjectId) -> Dictionary {
+    let [x0, y0, x1, y1] = rect;
+
+    let mut annotation = dictionary! {
+        "Type" => "Annot",
+        "Subtype" => subtype,
+        "Rect" => vec![x0.into(), y0.into(), x1.into(), y1.into()],
+        "Contents" => pdfstring::encode_object(&format!("Annotation {number}")),
+        "P" => page_id,
+        "C" => vec![1.into(), 1.into(), 0.into()],
+    };
+    match subtype {
+        "Highlight" => {
+            annotation.set(
+                "QuadPoints",
+                vec![x0, y1, x1, y1, x0, y0, x1, y0]
+                    .into_iter()
+                    .map(Object::from)
+                    .collect::<Vec<_>>(),
+            );
+        },
+        "FreeText" => {
+            annotation.set(
+                "DA",
+                Object::string_literal(format!("/{FONT_NAME} 10 Tf 0 g")),
+            );
+        },
+        "Line" => {
+            annotation.set("L", vec![x0.into(), y0.into(), x1.into(), y1.into()]);
+        },
+        "Ink" => {
+            let stroke = vec![x0, y0, x1, y1, x1, y0]
+                .into_iter()
+                .map(Object::from)
+                .collect::<Vec<_>>();
+            annotation.set("InkList", vec![Object::Array(stroke)]);
+        },
+        "Link" => {
+            annotation.set("Border", vec![0.into(), 0.into(), 0.into()]);
+            annotation.set(
+                "A",
+                dictionary! {
+                    "S" => "URI",
+                    "URI" => Object::string_literal(format!("https://example.com/{number}")),
+                },
+            );
+        },
+        _ => {},
+    }
+    annotation
+}
+
+/// Description of a synthetic document.
+///
+/// Annotations and form fields are spread evenly over the pages, and
+/// bookmarks point to the pages in turn. The document only depends on the
+/// description.
+#[derive(Clone, Debug)]
+pub struct Fixture {
+    /// Number of pages, at least one.
+    pub pages: u32,
+    /// Total number of annotations, form fields excluded.
+    pub annotations: usize,
+    /// Subtypes of annotations, used in turn.
+    pub subtypes: Vec<String>,
+    /// Number of text form fields.
+    pub fields: usize,
+    /// Number of bookmarks.
+    pub bookmarks: usize,
+    /// Whether the document is tagged, with page titles as headings of its
+    /// structure tree.
+    pub tagged: bool,
+    /// Number of layers, i.e., optional content groups, each showing a line
+    /// of text on every page.
+    pub layers: usize,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self {
+            pages: 1,
+            annotations: 0,
+            subtypes: DEFAULT_SUBTYPES.iter().map(ToString::to_string).collect(),
+            fields: 0,
+            bookmarks: 0,
+            tagged: false,
+            layers: 0,
+        }
+    }
+}
+
+impl Fixture {
+    /// Build the document.
+    #[must_use]
+    pub fn build(&self) -> Document {
+        let pages = self.pages.max(1) as usize;
+        let mut document = Document::with_version("1.7");
+        let pages_id = document.new_object_id();
+        let font_id = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let page_ids: Vec<ObjectId> = (0..pages).map(|_| document.new_object_id()).collect();
+        let mut annots: Vec<Vec<Object>> = vec![vec![]; pages];
+
+        for number in 0..self.annotations {
+            if self.subtypes.is_empty() {
+                break;
+            }
+            let page = number % pages;
+            let index = annots[page].len();
+            // Subtypes are shifted on each page, so that pages do not all
+            // get the same subtypes when there are as many pages as subtypes.
+            let subtype = &self.subtypes[(index + page) % self.subtypes.len()];
+            let rect = grid_rect(index);
+            let id = document.add_object(annotation(subtype, rect, number, page_ids[page]));
+            annots[page].push(id.into());
+        }
+
+        let fields: Vec<Object> = (0..self.fields)
+            .map(|number| {
+                let page = number % pages;
+                let [x0, y0, _, y1] = grid_rect(annots[page].len());
+                let id = document.add_object(dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "Widget",
+                    "FT" => "Tx",
+                    "T" => pdfstring::encode_object(&format!("field{number}")),
+                    "V" => Object::string_literal(""),
+                    "DA" => Object::string_literal(format!("/{FONT_NAME} 0 Tf 0 g")),
+                    "Rect" => vec![x0.into(), y0.into(), (x0 + 120.0).into(), y1.into()],
+                    "F" => 4,
+                    "P" => page_ids[page],
+                });
+                annots[page].push(id.into());
+                id.into()
+            })
+            .collect();
+
+        for ((number, &page_id), annots) in (1..).zip(&page_ids).zip(annots) {
+            let content_id = document.add_object(page_content(number, self.tagged, self.layers));
+            let mut dict = dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            };
+            if !annots.is_empty() {
+                dict.set("Annots", annots);
+            }
+            if self.tagged {
+                dict.set("StructParents", i64::from(number - 1));
+                dict.set("Tabs", "S");
+            }
+            document.objects.insert(page_id, Object::Dictionary(dict));
+        }
+
+        let layer_ids: Vec<ObjectId> = (1..=self.layers)
+            .map(|number| {
+                document.add_object(dictionary! {
+                    "Type" => "OCG",
+                    "Name" => pdfstring::encode_object(&format!("Layer {number}")),
+                })
+            })
+            .collect();
+        let mut resources = dictionary! { "Font" => dictionary! { FONT_NAME => font_id } };
+        if !layer_ids.is_empty() {
+            let properties: Dictionary = layer_ids
+                .iter()
+                .enumerate()
+                .map(|(index, &id)| (layer_resource(index), Object::Reference(id)))
+                .collect();
+            resources.set("Properties", properties);
+        }
+        document.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => pages as i64,
+                "Kids" => page_ids.iter().copied().map(Object::from).collect::<Vec<_>>(),
+                "Resources" => resources.clone(),
+                "MediaBox" => vec![0.into(), 0.into(), PAGE_SIZE.0.into(), PAGE_SIZE.1.into()],
+            }),
+        );
+
+        let mut catalog = dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        };
+        if !fields.is_empty() {
+            catalog.set(
+                "AcroForm",
+                dictionary! {
+                    "Fields" => fields,
+                    "DA" => Object::string_literal(format!("/{FONT_NAME} 0 Tf 0 g")),
+                    "DR" => resources,
+                },
+            );
+        }
+        if self.bookmarks > 0 {
+            catalog.set("Outlines", self.add_outline(&mut document, &page_ids));
+            catalog.set("PageMode", "UseOutlines");
+        }
+        if !layer_ids.is_empty() {
+            let layers: Vec<Object> = layer_ids.into_iter().map(Object::from).collect();
+            catalog.set(
+                "OCProperties",
+                dictionary! {
+                    "OCGs" => layers.clone(),
+                    "D" => dictionary! { "Order" => layers.clone(), "ON" => layers },
+                },
+            );
+        }
+        if self.tagged {
+            catalog.set(
+                "StructTreeRoot",
+                add_structure_tree(&mut document, &page_ids),
+            );
+            catalog.set("MarkInfo", dictionary! { "Marked" => true });
+            catalog.set("Lang", Object::string_literal("en-US"));
+        }
+        let catalog_id = document.add_object(catalog);
+        document.trailer.set("Root", catalog_id);
+
+        document
+    }
+
+    /// Add a flat outline with one bookmark per page in turn, returning the
+    /// ID of its root.
+    fn add_outline(&self, document: &mut Document, page_ids: &[ObjectId]) -> ObjectId {
+        let outline_id = document.new_object_id();
+        let item_ids: Vec<ObjectId> = (0..self.bookmarks)
+            .map(|_| document.new_object_id())
+            .collect();
+
+        for (i, &id) in item_ids.iter().enumerate() {
+            let page = i % page_ids.len();
+            let mut item = dictionary! {
+                "Title" => pdfstring::encode_object(&format!("Bookmark {} (page {})", i + 1, page + 1)),
+                "Parent" => outline_id,
+                "Dest" => vec![page_ids[page].into(), "Fit".into()],
+            };
+            if let Some(&previous) = i.checked_sub(1).and_then(|i| item_ids.get(i)) {
+                item.set("Prev", previous);
+            }
+            if let Some(&next) = item_ids.get(i + 1) {
+                item.set("Next", next);
+            }
+            document.objects.insert(id, Object::Dictionary(item));
+        }
+
+        document.objects.insert(
+            outline_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Outlines",
+                "First" => item_ids[0],
+                "Last" => item_ids[item_ids.len() - 1],
+                "Count" => item_ids.len() as i64,
+            }),
+        );
+        outline_id
+    }
+}
+
+/// Add a structure tree with a `Document` element holding the title of each
+/// page, returning the ID of its root.
+fn add_structure_tree(document: &mut Document, page_ids: &[ObjectId]) -> ObjectId {
+    let root_id = document.new_object_id();
+    let document_id = document.new_object_id();
+    let mut nums = vec![];
+
+    let titles: Vec<Object> = (0..)
+        .zip(page_ids)
+        .map(|(i, &page_id)| {
+            let id = document.add_object(dictionary! {
+                "Type" => "StructElem",
+                "S" => TITLE_TYPE,
+                "P" => document_id,
+                "Pg" => page_id,
+                "K" => 0,
+            });
+            nums.push(Object::Integer(i));
+            nums.push(vec![Object::Reference(id)].into());
+            id.into()
+        })
+        .collect();
+
+    document.objects.insert(
+        document_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "StructElem",
+            "S" => "Document",
+            "P" => root_id,
+            "K" => titles,
+        }),
+    );
+    document.objects.insert(
+        root_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "StructTreeRoot",
+            "K" => document_id,
+            "RoleMap" => dictionary! { TITLE_TYPE => "H1" },
+            "ParentTree" => dictionary! { "Nums" => nums },
+            "ParentTreeNextKey" => page_ids.len() as i64,
+        }),
+    );
+    root_id
+}
@@ -0,0 +1,418 @@
+//! Metrics and encoding for the standard 14 fonts, and embedding of TrueType
+//! fonts.
+//!
+//! The standard fonts are available in every PDF reader and need not be
+//! embedded, which makes them convenient for generated content.
+
+use lopdf::{Dictionary, Object, dictionary};
+
+/// Widths of Helvetica glyphs for characters 32 to 126, in thousandths of the
+/// font size.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Widths of Helvetica-Bold glyphs for characters 32 to 126, in thousandths of
+/// the font size.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Width used for Helvetica glyphs outside of the ASCII range.
+const HELVETICA_DEFAULT_WIDTH: u16 = 556;
+
+/// Width of all Courier glyphs.
+#[cfg(feature = "convert")]
+const COURIER_WIDTH: u16 = 600;
+
+/// A standard font that rpdf knows the metrics of.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StandardFont {
+    Helvetica,
+    HelveticaBold,
+    #[cfg(feature = "convert")]
+    HelveticaOblique,
+    #[cfg(feature = "convert")]
+    HelveticaBoldOblique,
+    #[cfg(feature = "convert")]
+    Courier,
+}
+
+impl StandardFont {
+    /// Return the PostScript name of the font.
+    #[must_use]
+    pub fn base_font(self) -> &'static str {
+        match self {
+            Self::Helvetica => "Helvetica",
+            Self::HelveticaBold => "Helvetica-Bold",
+            #[cfg(feature = "convert")]
+            Self::HelveticaOblique => "Helvetica-Oblique",
+            #[cfg(feature = "convert")]
+            Self::HelveticaBoldOblique => "Helvetica-BoldOblique",
+            #[cfg(feature = "convert")]
+            Self::Courier => "Courier",
+        }
+    }
+
+    /// Return the standard font with the given PostScript name, ignoring
+    /// case.
+    #[cfg(feature = "convert")]
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Helvetica,
+            Self::HelveticaBold,
+            Self::HelveticaOblique,
+            Self::HelveticaBoldOblique,
+            Self::Courier,
+        ]
+        .into_iter()
+        .find(|font| font.base_font().eq_ignore_ascii_case(name))
+    }
+
+    /// Return the font dictionary, using `WinAnsiEncoding`.
+    #[must_use]
+    pub fn dictionary(self) -> Dictionary {
+        dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => self.base_font(),
+            "Encoding" => "WinAnsiEncoding",
+        }
+    }
+
+    /// Return the width of a WinAnsi-encoded byte, in thousandths of the font
+    /// size.
+    #[must_use]
+    pub fn glyph_width(self, byte: u8) -> u16 {
+        match self {
+            #[cfg(feature = "convert")]
+            Self::HelveticaOblique => Self::Helvetica.glyph_width(byte),
+            Self::Helvetica => {
+                match byte {
+                    32..=126 => HELVETICA_WIDTHS[usize::from(byte - 32)],
+                    _ => HELVETICA_DEFAULT_WIDTH,
+                }
+            },
+            #[cfg(feature = "convert")]
+            Self::HelveticaBoldOblique => Self::HelveticaBold.glyph_width(byte),
+            Self::HelveticaBold => {
+                match byte {
+                    32..=126 => HELVETICA_BOLD_WIDTHS[usize::from(byte - 32)],
+                    _ => HELVETICA_DEFAULT_WIDTH,
+                }
+            },
+            #[cfg(feature = "convert")]
+            Self::Courier => COURIER_WIDTH,
+        }
+    }
+
+    /// Return the width of a text, in unscaled text space units, when shown
+    /// with the given font size.
+    #[must_use]
+    pub fn text_width(self, text: &str, size: f32) -> f32 {
+        let units: u32 = encode_win_ansi(text)
+            .into_iter()
+            .map(|byte| u32::from(self.glyph_width(byte)))
+            .sum();
+        units as f32 * size / 1000.0
+    }
+}
+
+/// Characters of `WinAnsiEncoding` for bytes 0x80 to 0x9F, which differ from
+/// Latin-1.
+const WIN_ANSI_HIGH: [char; 32] = [
+    '€', '\u{FFFD}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{FFFD}', 'Ž',
+    '\u{FFFD}', '\u{FFFD}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{FFFD}',
+    'ž', 'Ÿ',
+];
+
+/// Decode a byte encoded with `WinAnsiEncoding`.
+#[must_use]
+pub fn decode_win_ansi(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => WIN_ANSI_HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+/// Characters of `MacRomanEncoding` for bytes 0x80 to 0xFF.
+const MAC_ROMAN_HIGH: [char; 128] = [
+    'Ä', 'Å', 'Ç', 'É', 'Ñ', 'Ö', 'Ü', 'á', 'à', 'â', 'ä', 'ã', 'å', 'ç', 'é', 'è', 'ê', 'ë', 'í',
+    'ì', 'î', 'ï', 'ñ', 'ó', 'ò', 'ô', 'ö', 'õ', 'ú', 'ù', 'û', 'ü', '†', '°', '¢', '£', '§', '•',
+    '¶', 'ß', '®', '©', '™', '´', '¨', '≠', 'Æ', 'Ø', '∞', '±', '≤', '≥', '¥', 'µ', '∂', '∑', '∏',
+    'π', '∫', 'ª', 'º', 'Ω', 'æ', 'ø', '¿', '¡', '¬', '√', 'ƒ', '≈', '∆', '«', '»', '…', '\u{A0}',
+    'À', 'Ã', 'Õ', 'Œ', 'œ', '–', '—', '“', '”', '‘', '’', '÷', '◊', 'ÿ', 'Ÿ', '⁄', '¤', '‹', '›',
+    'ﬁ', 'ﬂ', '‡', '·', '‚', '„', '‰', 'Â', 'Ê', 'Á', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', 'Ó', 'Ô',
+    '\u{F8FF}', 'Ò', 'Ú', 'Û', 'Ù', 'ı', 'ˆ', '˜', '¯', '˘', '˙', '˚', '¸', '˝', '˛', 'ˇ',
+];
+
+/// Decode a byte encoded with `MacRomanEncoding`.
+#[must_use]
+pub fn decode_mac_roman(byte: u8) -> char {
+    match byte {
+        0x80..=0xFF => MAC_ROMAN_HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+/// Return the character named by a glyph name from the Adobe Glyph List, for
+/// the most common names.
+#[must_use]
+pub fn glyph_name_to_char(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    let hex = name
+        .strip_prefix("uni")
+        .and_then(|hex| hex.get(..4))
+        .or_else(|| name.strip_prefix('u'));
+    if let Some(c) = hex
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32)
+    {
+        return Some(c);
+    }
+
+    let c = match name {
+        "space" | "nbspace" => ' ',
+        "exclam" => '!',
+        "quotedbl" => '"',
+        "numbersign" => '#',
+        "dollar" => '$',
+        "percent" => '%',
+        "ampersand" => '&',
+        "quotesingle" => '\'',
+        "parenleft" => '(',
+        "parenright" => ')',
+        "asterisk" => '*',
+        "plus" => '+',
+        "comma" => ',',
+        "hyphen" | "minus" => '-',
+        "period" => '.',
+        "slash" => '/',
+        "zero" => '0',
+        "one" => '1',
+        "two" => '2',
+        "three" => '3',
+        "four" => '4',
+        "five" => '5',
+        "six" => '6',
+        "seven" => '7',
+        "eight" => '8',
+        "nine" => '9',
+        "colon" => ':',
+        "semicolon" => ';',
+        "less" => '<',
+        "equal" => '=',
+        "greater" => '>',
+        "question" => '?',
+        "at" => '@',
+        "bracketleft" => '[',
+        "backslash" => '\\',
+        "bracketright" => ']',
+        "asciicircum" => '^',
+        "underscore" => '_',
+        "grave" => '`',
+        "braceleft" => '{',
+        "bar" => '|',
+        "braceright" => '}',
+        "asciitilde" => '~',
+        "quoteleft" => '‘',
+        "quoteright" => '’',
+        "quotedblleft" => '“',
+        "quotedblright" => '”',
+        "endash" => '–',
+        "emdash" => '—',
+        "bullet" => '•',
+        "ellipsis" => '…',
+        "Euro" => '€',
+        "degree" => '°',
+        "section" => '§',
+        "copyright" => '©',
+        "registered" => '®',
+        "agrave" => 'à',
+        "aacute" => 'á',
+        "acircumflex" => 'â',
+        "adieresis" => 'ä',
+        "ccedilla" => 'ç',
+        "egrave" => 'è',
+        "eacute" => 'é',
+        "ecircumflex" => 'ê',
+        "edieresis" => 'ë',
+        "icircumflex" => 'î',
+        "idieresis" => 'ï',
+        "ntilde" => 'ñ',
+        "ocircumflex" => 'ô',
+        "odieresis" => 'ö',
+        "ugrave" => 'ù',
+        "uacute" => 'ú',
+        "ucircumflex" => 'û',
+        "udieresis" => 'ü',
+        "germandbls" => 'ß',
+        "Agrave" => 'À',
+        "Eacute" => 'É',
+        "Egrave" => 'È',
+        "Ccedilla" => 'Ç',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// Encode a text with `WinAnsiEncoding`, replacing unsupported characters with
+/// `?`.
+#[must_use]
+pub fn encode_win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| {
+            match c {
+                '\u{20}'..='\u{7E}' | '\u{A0}'..='\u{FF}' => c as u8,
+                _ => {
+                    WIN_ANSI_HIGH
+                        .iter()
+                        .position(|&high| high == c && high != '\u{FFFD}')
+                        .map_or(b'?', |i| 0x80 + i as u8)
+                },
+            }
+        })
+        .collect()
+}
+
+/// Return a string object holding a WinAnsi-encoded text.
+#[must_use]
+pub fn win_ansi_string(text: &str) -> Object {
+    Object::string_literal(encode_win_ansi(text))
+}
+
+/// A TrueType font to embed in generated documents, used with
+/// `WinAnsiEncoding`.
+#[cfg(feature = "convert")]
+#[derive(Clone, Debug)]
+pub struct TrueTypeFont {
+    /// PostScript name of the font.
+    name: String,
+    /// Content of the font file.
+    data: Vec<u8>,
+    /// Widths of WinAnsi-encoded bytes 32 to 255, in thousandths of the font
+    /// size.
+    widths: Vec<u16>,
+    /// Font bounding box, in thousandths of the font size.
+    bbox: [i64; 4],
+    ascent: i64,
+    descent: i64,
+    cap_height: i64,
+    italic_angle: f32,
+    fixed_pitch: bool,
+}
+
+#[cfg(feature = "convert")]
+impl TrueTypeFont {
+    /// Parse a TrueType font file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file is not a valid font, or if it does not use TrueType
+    /// outlines.
+    pub fn parse(data: Vec<u8>) -> anyhow::Result<Self> {
+        let face = ttf_parser::Face::parse(&data, 0)?;
+
+        if face.tables().glyf.is_none() {
+            anyhow::bail!("Only fonts with TrueType outlines are supported");
+        }
+
+        let scale = 1000.0 / f32::from(face.units_per_em());
+        let scaled = |value: i16| (f32::from(value) * scale).round() as i64;
+        let name = face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .find_map(|name| name.to_string())
+            .unwrap_or_else(|| "Embedded".to_string())
+            .replace(' ', "");
+        let widths = (32..=u8::MAX)
+            .map(|byte| {
+                face.glyph_index(decode_win_ansi(byte))
+                    .and_then(|glyph| face.glyph_hor_advance(glyph))
+                    .map_or(0, |advance| (f32::from(advance) * scale).round() as u16)
+            })
+            .collect();
+        let bbox = face.global_bounding_box();
+
+        let font = Self {
+            name,
+            widths,
+            bbox: [
+                scaled(bbox.x_min),
+                scaled(bbox.y_min),
+                scaled(bbox.x_max),
+                scaled(bbox.y_max),
+            ],
+            ascent: scaled(face.ascender()),
+            descent: scaled(face.descender()),
+            cap_height: scaled(face.capital_height().unwrap_or(face.ascender())),
+            italic_angle: face.italic_angle(),
+            fixed_pitch: face.is_monospaced(),
+            data: vec![],
+        };
+
+        // The face borrows `data`, which can only be moved in once parsing
+        // is done.
+        Ok(Self { data, ..font })
+    }
+
+    /// Return the width of a WinAnsi-encoded byte, in thousandths of the font
+    /// size.
+    #[must_use]
+    pub fn glyph_width(&self, byte: u8) -> u16 {
+        byte.checked_sub(32)
+            .map_or(0, |i| self.widths[usize::from(i)])
+    }
+
+    /// Add the font, its descriptor and its compressed file to a document,
+    /// returning the ID of the font dictionary.
+    pub fn add_to(&self, document: &mut lopdf::Document) -> lopdf::ObjectId {
+        let mut file = lopdf::Stream::new(
+            dictionary! { "Length1" => self.data.len() as i64 },
+            self.data.clone(),
+        );
+        let _ = file.compress();
+        let file_id = document.add_object(file);
+
+        // Symbolic fonts use their built-in encoding, so only flag the font
+        // as nonsymbolic, and fixed pitch if needed.
+        let flags = 32 | i64::from(self.fixed_pitch);
+        let descriptor_id = document.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => Object::Name(self.name.clone().into_bytes()),
+            "Flags" => flags,
+            "FontBBox" => self.bbox.iter().map(|&value| value.into()).collect::<Vec<Object>>(),
+            "ItalicAngle" => self.italic_angle,
+            "Ascent" => self.ascent,
+            "Descent" => self.descent,
+            "CapHeight" => self.cap_height,
+            "StemV" => 80,
+            "FontFile2" => file_id,
+        });
+
+        document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "TrueType",
+            "BaseFont" => Object::Name(self.name.clone().into_bytes()),
+            "FirstChar" => 32,
+            "LastChar" => 255,
+            "Widths" => self.widths.iter().map(|&width| i64::from(width).into()).collect::<Vec<Object>>(),
+            "FontDescriptor" => descriptor_id,
+            "Encoding" => "WinAnsiEncoding",
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//! Georeferencing of map pages, i.e., geospatial measure dictionaries of
+//! page viewports and images.
+//!
+//! A measure maps points of a region of the page, `/LPTS`, to latitudes and
+//! longitudes, `/GPTS`, in a coordinate reference system, `/GCS`.
+
+use lopdf::{Dictionary, Document, Object};
+use serde::Serialize;
+
+use super::{content::number, document::DocumentExt, pdfstring};
+
+/// Element of a page that is georeferenced.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// Viewport of the page, listed in `/VP`.
+    Viewport,
+    /// Image drawn on the page.
+    Image,
+}
+
+/// Coordinate reference system of a measure.
+#[derive(Clone, Debug, Serialize)]
+pub struct Crs {
+    /// Kind of system, i.e., `GEOGCS` or `PROJCS`.
+    pub kind: Option<String>,
+    /// EPSG code of the system, if any.
+    pub epsg: Option<i64>,
+    /// Well-known text description of the system, if any.
+    pub wkt: Option<String>,
+}
+
+/// Georeferenced region of a page.
+#[derive(Clone, Debug, Serialize)]
+pub struct Georeference {
+    /// Page number.
+    pub page: u32,
+    pub source: Source,
+    /// Name of the viewport, or resource name of the image.
+    pub name: Option<String>,
+    /// Region of the page, as `[x0, y0, x1, y1]`, for viewports.
+    pub bbox: Option<[f32; 4]>,
+    /// Region of the unit square covered by the map, as pairs of
+    /// coordinates.
+    pub bounds: Vec<f32>,
+    /// Geographic points, as pairs of latitude and longitude.
+    pub gpts: Vec<f32>,
+    /// Points of the unit square that map to the geographic points.
+    pub lpts: Vec<f32>,
+    pub crs: Option<Crs>,
+    /// Preferred units of distance, area and angle, e.g., `M`, `SQKM` and
+    /// `DEG`.
+    pub units: Vec<String>,
+}
+
+/// Return the numbers of an array entry.
+fn numbers(document: &Document, dict: &Dictionary, key: &[u8]) -> Vec<f32> {
+    dict.get_deref(key, document)
+        .and_then(Object::as_array)
+        .map(|array| array.iter().filter_map(number).collect())
+        .unwrap_or_default()
+}
+
+/// Return the coordinate reference system of a measure.
+fn crs(document: &Document, measure: &Dictionary) -> Option<Crs> {
+    let gcs = measure
+        .get_deref(b"GCS", document)
+        .and_then(Object::as_dict)
+        .ok()?;
+
+    Some(Crs {
+        kind: gcs
+            .get(b"Type")
+            .and_then(Object::as_name_str)
+            .ok()
+            .map(ToString::to_string),
+        epsg: gcs
+            .get_deref(b"EPSG", document)
+            .and_then(Object::as_i64)
+            .ok(),
+        wkt: gcs
+            .get_deref(b"WKT", document)
+            .and_then(pdfstring::decode_object)
+            .ok(),
+    })
+}
+
+/// Return the georeference of a dictionary with a geospatial `/Measure`.
+fn georeference(
+    document: &Document,
+    page: u32,
+    source: Source,
+    name: Option<String>,
+    dict: &Dictionary,
+) -> Option<Georeference> {
+    let measure = dict
+        .get_deref(b"Measure", document)
+        .and_then(Object::as_dict)
+        .ok()?;
+    if measure.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"GEO") {
+        return None;
+    }
+    let bbox = match numbers(document, dict, b"BBox").as_slice() {
+        &[x0, y0, x1, y1] => Some([x0, y0, x1, y1]),
+        _ => None,
+    };
+
+    Some(Georeference {
+        page,
+        source,
+        name,
+        bbox,
+        bounds: numbers(document, measure, b"Bounds"),
+        gpts: numbers(document, measure, b"GPTS"),
+        lpts: numbers(document, measure, b"LPTS"),
+        crs: crs(document, measure),
+        units: measure
+            .get_deref(b"PDU", document)
+            .and_then(Object::as_array)
+            .map(|units| {
+                units
+                    .iter()
+                    .filter_map(|unit| unit.as_name_str().ok())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// Return the georeferenced viewports and images of the pages of a
+/// document.
+#[must_use]
+pub fn georeferences(document: &Document) -> Vec<Georeference> {
+    let mut georeferences = vec![];
+
+    for (page, page_id) in document.get_pages() {
+        let Ok(page_dict) = document.get_dictionary(page_id) else {
+            continue;
+        };
+        if let Ok(viewports) = page_dict
+            .get_deref(b"VP", document)
+            .and_then(Object::as_array)
+        {
+            for viewport in viewports {
+                let Ok(viewport) = document
+                    .dereference(viewport)
+                    .and_then(|(_, viewport)| viewport.as_dict())
+                else {
+                    continue;
+                };
+                let name = viewport
+                    .get_deref(b"Name", document)
+                    .and_then(pdfstring::decode_object)
+                    .ok();
+                georeferences.extend(georeference(
+                    document,
+                    page,
+                    Source::Viewport,
+                    name,
+                    viewport,
+                ));
+            }
+        }
+
+        let Some(xobjects) = document.page_resources(page_id).and_then(|resources| {
+            resources
+                .get_deref(b"XObject", document)
+                .and_then(Object::as_dict)
+                .ok()
+        }) else {
+            continue;
+        };
+        for (name, xobject) in xobjects {
+            let Ok(Object::Stream(stream)) =
+                document.dereference(xobject).map(|(_, xobject)| xobject)
+            else {
+                continue;
+            };
+            if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image") {
+                georeferences.extend(georeference(
+                    document,
+                    page,
+                    Source::Image,
+                    Some(String::from_utf8_lossy(name).into_owned()),
+                    &stream.dict,
+                ));
+            }
+        }
+    }
+    georeferences
+}
@@ -0,0 +1,218 @@
+//! Heatmaps of annotations, i.e., grids over pages whose cells are weighted
+//! by the annotations that cover them, e.g., to see where reviewers focused.
+
+use lopdf::Document;
+
+use super::{annotations::Annotation, document::DocumentExt, transform};
+
+/// Quantity that the cells of a heatmap add up.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Weight {
+    /// Number of annotations that overlap the cell.
+    #[default]
+    Count,
+    /// Fraction of the cell covered by annotations, summed over annotations,
+    /// so that large annotations weigh more than small ones.
+    Area,
+}
+
+/// Heatmap of a page, as shown, i.e., after its rotation.
+#[derive(Clone, Debug)]
+pub struct PageHeatmap {
+    /// Page number, starting at 1.
+    pub page: u32,
+    /// Number of columns of the grid.
+    pub columns: usize,
+    /// Number of rows of the grid.
+    pub rows: usize,
+    /// Weights of the cells, row by row, from the top left corner.
+    pub cells: Vec<f32>,
+}
+
+impl PageHeatmap {
+    /// Return the largest weight of the cells, or 0 if the page has none.
+    #[must_use]
+    pub fn max(&self) -> f32 {
+        self.cells.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+/// Return the number of cells of size `cell_size` that span `length`, and at
+/// least one.
+fn cell_count(length: f32, cell_size: f32) -> usize {
+    ((length / cell_size).ceil() as usize).max(1)
+}
+
+/// Return the range of the cells, out of `count`, that overlap the segment
+/// from `start` to `end`, in fractions of the page.
+fn cell_range(start: f32, end: f32, count: usize) -> std::ops::Range<usize> {
+    let first = ((start * count as f32).floor() as usize).min(count - 1);
+    let last = ((end * count as f32).ceil() as usize).clamp(first + 1, count);
+    first..last
+}
+
+/// Return the heatmaps of the pages of a document, in order, with cells of
+/// `cell_size` points, from the rectangles of `annotations`.
+///
+/// Pages without a valid crop box get a single empty cell.
+#[must_use]
+pub fn heatmaps(
+    document: &Document,
+    annotations: &[Annotation],
+    cell_size: f32,
+    weight: Weight,
+) -> Vec<PageHeatmap> {
+    document
+        .get_pages()
+        .into_iter()
+        .map(|(page, page_id)| {
+            let to_unit = transform::page_to_unit(document, page_id);
+            let (columns, rows) = match (to_unit, document.page_crop_box(page_id)) {
+                (Some(_), Some([x0, y0, x1, y1])) => {
+                    let (width, height) = if document.page_rotation(page_id) % 180 == 90 {
+                        (y1 - y0, x1 - x0)
+                    } else {
+                        (x1 - x0, y1 - y0)
+                    };
+                    (cell_count(width, cell_size), cell_count(height, cell_size))
+                },
+                _ => (1, 1),
+            };
+            let mut heatmap = PageHeatmap {
+                page,
+                columns,
+                rows,
+                cells: vec![0.0; columns * rows],
+            };
+            let Some(to_unit) = to_unit else {
+                return heatmap;
+            };
+
+            for rect in annotations
+                .iter()
+                .filter(|annotation| annotation.page == page)
+                .filter_map(|annotation| annotation.rect)
+            {
+                let corners = [
+                    to_unit.apply(rect[0], rect[1]),
+                    to_unit.apply(rect[2], rect[3]),
+                ];
+                let (u0, u1) = (
+                    corners[0].0.min(corners[1].0).clamp(0.0, 1.0),
+                    corners[0].0.max(corners[1].0).clamp(0.0, 1.0),
+                );
+                // Rows start at the top of the page.
+                let (v0, v1) = (
+                    1.0 - corners[0].1.max(corners[1].1).clamp(0.0, 1.0),
+                    1.0 - corners[0].1.min(corners[1].1).clamp(0.0, 1.0),
+                );
+
+                for row in cell_range(v0, v1, rows) {
+                    for column in cell_range(u0, u1, columns) {
+                        heatmap.cells[row * columns + column] += match weight {
+                            Weight::Count => 1.0,
+                            Weight::Area => {
+                                let overlap = |start: f32, end: f32, index: usize, count: usize| {
+                                    let cell_start = index as f32 / count as f32;
+                                    let cell_end = (index + 1) as f32 / count as f32;
+                                    (end.min(cell_end) - start.max(cell_start)).max(0.0)
+                                        * count as f32
+                                };
+                                overlap(u0, u1, column, columns) * overlap(v0, v1, row, rows)
+                            },
+                        };
+                    }
+                }
+            }
+            heatmap
+        })
+        .collect()
+}
+
+/// Size of the cells of heatmap images, in pixels.
+#[cfg(feature = "convert")]
+const CELL_PIXELS: usize = 8;
+
+/// Space around pages in heatmap images, in pixels.
+#[cfg(feature = "convert")]
+const MARGIN_PIXELS: usize = 8;
+
+/// Return the color of a cell, from white when `t` is 0, through yellow, to
+/// red when `t` is 1.
+#[cfg(feature = "convert")]
+fn ramp(t: f32) -> [u8; 3] {
+    let t = t.clamp(0.0, 1.0);
+    if t < 0.5 {
+        [255, 255, (255.0 * (1.0 - 2.0 * t)) as u8]
+    } else {
+        [255, (255.0 * (2.0 - 2.0 * t)) as u8, 0]
+    }
+}
+
+/// Render heatmaps as one PNG image, with `per_row` pages per row, in
+/// reading order.
+///
+/// Weights are scaled to the largest weight of all pages, so that pages can
+/// be compared with each other.
+///
+/// # Errors
+///
+/// Fails if the image cannot be encoded.
+#[cfg(feature = "convert")]
+pub fn render_png(heatmaps: &[PageHeatmap], per_row: usize) -> anyhow::Result<Vec<u8>> {
+    const BACKGROUND: [u8; 3] = [224, 224, 224];
+    const BORDER: [u8; 3] = [128, 128, 128];
+
+    let per_row = per_row.max(1);
+    let slot_width = heatmaps.iter().map(|h| h.columns).max().unwrap_or(1) * CELL_PIXELS + 2;
+    let slot_height = heatmaps.iter().map(|h| h.rows).max().unwrap_or(1) * CELL_PIXELS + 2;
+    let page_rows = heatmaps.len().div_ceil(per_row).max(1);
+    let width = per_row.min(heatmaps.len().max(1)) * (slot_width + MARGIN_PIXELS) + MARGIN_PIXELS;
+    let height = page_rows * (slot_height + MARGIN_PIXELS) + MARGIN_PIXELS;
+    let max = heatmaps.iter().map(PageHeatmap::max).fold(0.0, f32::max);
+
+    let mut pixels = BACKGROUND.repeat(width * height);
+    let mut fill = |x0: usize, y0: usize, w: usize, h: usize, color: [u8; 3]| {
+        for y in y0..y0 + h {
+            for x in x0..x0 + w {
+                pixels[3 * (y * width + x)..][..3].copy_from_slice(&color);
+            }
+        }
+    };
+
+    for (index, heatmap) in heatmaps.iter().enumerate() {
+        let page_width = heatmap.columns * CELL_PIXELS + 2;
+        let page_height = heatmap.rows * CELL_PIXELS + 2;
+        // Pages are centered in their slot.
+        let x0 = MARGIN_PIXELS
+            + (index % per_row) * (slot_width + MARGIN_PIXELS)
+            + (slot_width - page_width) / 2;
+        let y0 = MARGIN_PIXELS
+            + (index / per_row) * (slot_height + MARGIN_PIXELS)
+            + (slot_height - page_height) / 2;
+
+        fill(x0, y0, page_width, page_height, BORDER);
+        for row in 0..heatmap.rows {
+            for column in 0..heatmap.columns {
+                let weight = heatmap.cells[row * heatmap.columns + column];
+                let t = if max > 0.0 { weight / max } else { 0.0 };
+                fill(
+                    x0 + 1 + column * CELL_PIXELS,
+                    y0 + 1 + row * CELL_PIXELS,
+                    CELL_PIXELS,
+                    CELL_PIXELS,
+                    ramp(t),
+                );
+            }
+        }
+    }
+
+    let mut data = vec![];
+    let mut encoder = png::Encoder::new(&mut data, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(data)
+}
@@ -0,0 +1,133 @@
+//! Incremental updates, which append the changes of each revision of a
+//! document to the end of the file.
+
+use lopdf::{Document, Object, ObjectId, xref::XrefEntry};
+
+/// Revision of a document, i.e., the original file or an incremental update.
+#[derive(Clone, Debug, Default)]
+pub struct Revision {
+    /// Length of the file up to the end of the revision, in bytes.
+    pub length: usize,
+    /// Objects added by the revision.
+    pub added: Vec<ObjectId>,
+    /// Objects modified by the revision.
+    pub modified: Vec<ObjectId>,
+    /// Objects deleted by the revision.
+    pub deleted: Vec<ObjectId>,
+    /// Whether the revision added or modified a signature.
+    pub signed: bool,
+}
+
+/// Maximum distance between `startxref` and the `%%EOF` marker that follows.
+const STARTXREF_DISTANCE: usize = 32;
+
+/// Return the lengths of the file up to each `%%EOF` marker that follows a
+/// `startxref` keyword, including the end-of-line marker, if any.
+fn end_of_file_markers(data: &[u8]) -> Vec<usize> {
+    let mut lengths = Vec::new();
+
+    for (position, _) in data
+        .windows(5)
+        .enumerate()
+        .filter(|(_, window)| window == b"%%EOF")
+    {
+        let start = position.saturating_sub(STARTXREF_DISTANCE + 9);
+        if !data[start..position]
+            .windows(9)
+            .any(|window| window == b"startxref")
+        {
+            continue;
+        }
+        let mut length = position + 5;
+        if data.get(length) == Some(&b'\r') {
+            length += 1;
+        }
+        if data.get(length) == Some(&b'\n') {
+            length += 1;
+        }
+        lengths.push(length);
+    }
+    lengths
+}
+
+/// Location of an object in the file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Location {
+    /// Offset and generation of an object.
+    Offset(u32, u16),
+    /// Object stream and index of a compressed object.
+    Compressed(u32, u16),
+}
+
+/// Return the location of an object in the file, or [`None`] if it is free.
+fn location(entry: &XrefEntry) -> Option<Location> {
+    match *entry {
+        XrefEntry::Normal { offset, generation } => Some(Location::Offset(offset, generation)),
+        XrefEntry::Compressed { container, index } => Some(Location::Compressed(container, index)),
+        XrefEntry::Free | XrefEntry::UnusableFree => None,
+    }
+}
+
+/// Return the generation of an object from its cross-reference entry.
+fn generation(entry: &XrefEntry) -> u16 {
+    match *entry {
+        XrefEntry::Normal { generation, .. } => generation,
+        _ => 0,
+    }
+}
+
+/// Return whether an object is a signature dictionary.
+fn is_signature(document: &Document, id: ObjectId) -> bool {
+    document
+        .get_object(id)
+        .ok()
+        .and_then(|object| {
+            match object {
+                Object::Dictionary(dict) => Some(dict),
+                _ => None,
+            }
+        })
+        .is_some_and(|dict| dict.type_is(b"Sig"))
+}
+
+/// Return the revisions of a document, from the oldest to the latest.
+///
+/// Each revision is read from the file truncated after its `%%EOF` marker,
+/// and objects are compared with the previous revision by their location in
+/// the file. Markers after which the file cannot be read, e.g., the first
+/// one of linearized files, are skipped.
+#[must_use]
+pub fn revisions(data: &[u8]) -> Vec<Revision> {
+    let mut revisions = Vec::new();
+    let mut previous: Option<Document> = None;
+
+    for length in end_of_file_markers(data) {
+        let Ok(document) = Document::load_mem(&data[..length]) else {
+            continue;
+        };
+        let mut revision = Revision {
+            length,
+            ..Default::default()
+        };
+
+        for (&number, entry) in &document.reference_table.entries {
+            let id = (number, generation(entry));
+            let before = previous
+                .as_ref()
+                .and_then(|previous| previous.reference_table.get(number))
+                .and_then(location);
+            match (before, location(entry)) {
+                (None, Some(_)) => revision.added.push(id),
+                (Some(before), Some(after)) if before != after => revision.modified.push(id),
+                (Some(_), None) => revision.deleted.push(id),
+                _ => continue,
+            }
+            if is_signature(&document, id) {
+                revision.signed = true;
+            }
+        }
+        revisions.push(revision);
+        previous = Some(document);
+    }
+    revisions
+}
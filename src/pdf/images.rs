@@ -0,0 +1,472 @@
+//! Helpers to export image XObjects.
+
+#[cfg(feature = "convert")]
+use lopdf::{Dictionary, dictionary};
+use lopdf::{Document, Object, ObjectId, Stream};
+use tracing::debug;
+
+use super::{
+    content::{Matrix, with_ctm},
+    document::DocumentExt,
+    text,
+};
+
+/// File format an image XObject can be exported to.
+#[cfg(any(feature = "ocr", feature = "convert"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageFormat {
+    /// JPEG, copied from `DCTDecode` streams as is.
+    Jpeg,
+    /// Portable anymap (PBM, PGM or PPM), wrapping raw samples.
+    #[cfg(feature = "ocr")]
+    Pnm,
+    /// PNG, compressing raw samples.
+    #[cfg(feature = "convert")]
+    Png,
+}
+
+#[cfg(any(feature = "ocr", feature = "convert"))]
+impl ImageFormat {
+    /// Return the usual file extension of the format.
+    #[cfg(feature = "convert")]
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            #[cfg(feature = "ocr")]
+            Self::Pnm => "pnm",
+            Self::Png => "png",
+        }
+    }
+
+    /// Return the media type of the format, e.g., for data URLs.
+    #[cfg(feature = "convert")]
+    #[must_use]
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            #[cfg(feature = "ocr")]
+            Self::Pnm => "image/x-portable-anymap",
+            Self::Png => "image/png",
+        }
+    }
+}
+
+/// Uncompressed samples of an image with 1-bit gray, 8-bit gray or 8-bit RGB
+/// pixels.
+struct RawImage {
+    width: u32,
+    height: u32,
+    components: usize,
+    bits: i64,
+    /// Rows of samples, each padded to a whole number of bytes.
+    samples: Vec<u8>,
+}
+
+/// Return the number of color components of an image's color space, if it
+/// is a supported gray or RGB color space.
+fn color_components(document: &Document, stream: &Stream) -> Option<usize> {
+    let color_space = stream.dict.get_deref(b"ColorSpace", document).ok()?;
+
+    match color_space {
+        Object::Name(name) => {
+            match name.as_slice() {
+                b"DeviceGray" | b"CalGray" | b"G" => Some(1),
+                b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(3),
+                _ => None,
+            }
+        },
+        Object::Array(array) => {
+            match array.first().and_then(|family| family.as_name().ok())? {
+                b"ICCBased" => {
+                    let profile = document
+                        .dereference(array.get(1)?)
+                        .ok()?
+                        .1
+                        .as_stream()
+                        .ok()?;
+                    match profile.dict.get(b"N").and_then(Object::as_i64).ok()? {
+                        1 => Some(1),
+                        3 => Some(3),
+                        _ => None,
+                    }
+                },
+                b"CalGray" => Some(1),
+                b"CalRGB" => Some(3),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Return whether an image is JPEG-encoded, or `None` if it combines
+/// `DCTDecode` with other filters, which is not supported.
+fn is_jpeg(stream: &Stream) -> Option<bool> {
+    let filters = stream.filters().unwrap_or_default();
+
+    if !filters.iter().any(|filter| filter == "DCTDecode") {
+        return Some(false);
+    }
+    match filters.as_slice() {
+        [filter] if filter == "DCTDecode" => Some(true),
+        _ => {
+            debug!("Unsupported filter chain for JPEG image: {filters:?}");
+            None
+        },
+    }
+}
+
+/// Decode the samples of an image XObject.
+///
+/// Returns `None` for unsupported images, e.g., image masks, JBIG2, CCITT
+/// or indexed colors.
+fn decode_raw(document: &Document, stream: &Stream) -> Option<RawImage> {
+    if stream
+        .dict
+        .get(b"ImageMask")
+        .and_then(Object::as_bool)
+        .unwrap_or(false)
+    {
+        debug!("Image masks are not supported");
+        return None;
+    }
+
+    let width = stream.dict.get(b"Width").and_then(Object::as_i64).ok()?;
+    let height = stream.dict.get(b"Height").and_then(Object::as_i64).ok()?;
+    let bits = stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(Object::as_i64)
+        .ok()?;
+    let components = color_components(document, stream)?;
+
+    if !matches!((components, bits), (1, 1 | 8) | (3, 8)) {
+        debug!("Unsupported image layout: {components} components, {bits} bits");
+        return None;
+    }
+
+    let samples = if stream.filters().unwrap_or_default().is_empty() {
+        stream.content.clone()
+    } else {
+        super::decompress(stream).ok()?
+    };
+    let row_len = (components * width as usize * bits as usize).div_ceil(8);
+
+    if samples.len() < row_len * height as usize {
+        debug!("Image data is shorter than expected");
+        return None;
+    }
+
+    Some(RawImage {
+        width: u32::try_from(width).ok()?,
+        height: u32::try_from(height).ok()?,
+        components,
+        bits,
+        samples: samples[..row_len * height as usize].to_vec(),
+    })
+}
+
+/// Return the width, the height, and the 8-bit luminance of the pixels of an
+/// image XObject, row by row.
+///
+/// Returns `None` for images that [`decode_raw`] does not support, and for
+/// JPEG images.
+#[must_use]
+pub fn luminance(document: &Document, stream: &Stream) -> Option<(u32, u32, Vec<u8>)> {
+    if is_jpeg(stream)? {
+        debug!("Decoding JPEG images is not supported");
+        return None;
+    }
+
+    let image = decode_raw(document, stream)?;
+    let (width, height) = (image.width as usize, image.height as usize);
+    let pixels = match (image.components, image.bits) {
+        (1, 1) => {
+            let row_len = width.div_ceil(8);
+            image
+                .samples
+                .chunks_exact(row_len)
+                .flat_map(|row| {
+                    (0..width).map(move |x| {
+                        if row[x / 8] & (0x80 >> (x % 8)) == 0 {
+                            0
+                        } else {
+                            255
+                        }
+                    })
+                })
+                .collect()
+        },
+        (1, _) => image.samples,
+        _ => {
+            image
+                .samples
+                .chunks_exact(3)
+                .map(|rgb| {
+                    let [r, g, b] = [rgb[0], rgb[1], rgb[2]].map(u32::from);
+                    // ITU-R BT.601 luma, which always fits in a byte.
+                    ((299 * r + 587 * g + 114 * b) / 1000) as u8
+                })
+                .collect()
+        },
+    };
+    debug_assert_eq!(pixels.len(), width * height);
+
+    Some((image.width, image.height, pixels))
+}
+
+/// Export an image XObject to a standalone image file.
+///
+/// JPEG images are copied as is, and 8-bit gray or RGB images, as well as
+/// 1-bit gray images, are wrapped into a portable anymap. Returns `None` for
+/// other encodings, e.g., JBIG2, CCITT or indexed colors.
+#[cfg(feature = "ocr")]
+#[must_use]
+pub fn export(document: &Document, stream: &Stream) -> Option<(ImageFormat, Vec<u8>)> {
+    if is_jpeg(stream)? {
+        return Some((ImageFormat::Jpeg, stream.content.clone()));
+    }
+
+    let image = decode_raw(document, stream)?;
+    let (width, height) = (image.width, image.height);
+    let mut data = match (image.components, image.bits) {
+        (1, 1) => format!("P4\n{width} {height}\n"),
+        (1, _) => format!("P5\n{width} {height}\n255\n"),
+        _ => format!("P6\n{width} {height}\n255\n"),
+    }
+    .into_bytes();
+
+    if image.bits == 1 {
+        // PBM uses 1 for black, whereas DeviceGray uses 0 for black.
+        data.extend(image.samples.iter().map(|byte| !byte));
+    } else {
+        data.extend_from_slice(&image.samples);
+    }
+
+    Some((ImageFormat::Pnm, data))
+}
+
+/// Export an image XObject to a format displayed by web browsers.
+///
+/// JPEG images are copied as is, and other supported images are encoded as
+/// PNG. Returns `None` for unsupported images, like [`export`] does.
+#[cfg(feature = "convert")]
+#[must_use]
+pub fn export_web(document: &Document, stream: &Stream) -> Option<(ImageFormat, Vec<u8>)> {
+    if is_jpeg(stream)? {
+        return Some((ImageFormat::Jpeg, stream.content.clone()));
+    }
+
+    let image = decode_raw(document, stream)?;
+    let mut data = vec![];
+    let mut encoder = png::Encoder::new(&mut data, image.width, image.height);
+
+    encoder.set_color(if image.components == 1 {
+        png::ColorType::Grayscale
+    } else {
+        png::ColorType::Rgb
+    });
+    encoder.set_depth(if image.bits == 1 {
+        png::BitDepth::One
+    } else {
+        png::BitDepth::Eight
+    });
+
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(&image.samples).ok()?;
+    writer.finish().ok()?;
+
+    Some((ImageFormat::Png, data))
+}
+
+/// An image file converted to an image XObject.
+#[cfg(feature = "convert")]
+pub struct ImportedImage {
+    /// Width, in pixels.
+    pub width: u32,
+    /// Height, in pixels.
+    pub height: u32,
+    /// Image XObject.
+    pub stream: Stream,
+    /// Soft mask holding the alpha channel, if any.
+    pub mask: Option<Stream>,
+}
+
+/// Return the width, height and number of components of a JPEG image, read
+/// from its start of frame segment.
+#[cfg(feature = "convert")]
+fn jpeg_info(data: &[u8]) -> Option<(u32, u32, u8)> {
+    let mut i = 2;
+
+    while i + 4 <= data.len() {
+        if data[i] != 0xFF {
+            return None;
+        }
+        let marker = data[i + 1];
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        let len = usize::from(u16::from_be_bytes([data[i + 2], data[i + 3]]));
+
+        // SOF0 to SOF15, except DHT, JPG and DAC that share the range.
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let frame = data.get(i + 4..i + 10)?;
+            let height = u16::from_be_bytes([frame[1], frame[2]]);
+            let width = u16::from_be_bytes([frame[3], frame[4]]);
+            return Some((width.into(), height.into(), frame[5]));
+        }
+        i += 2 + len;
+    }
+    None
+}
+
+/// Return an image XObject dictionary.
+#[cfg(feature = "convert")]
+fn image_dictionary(width: u32, height: u32, color_space: &str) -> Dictionary {
+    dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => width,
+        "Height" => height,
+        "ColorSpace" => color_space,
+        "BitsPerComponent" => 8,
+    }
+}
+
+/// Convert a JPEG or PNG file to an image XObject.
+///
+/// JPEG images are embedded as is, and PNG images are decoded to 8-bit
+/// samples, their alpha channel, if any, becoming a soft mask.
+///
+/// # Errors
+///
+/// Fails if the data is neither a JPEG nor a PNG image, or cannot be decoded.
+#[cfg(feature = "convert")]
+pub fn import(data: &[u8]) -> anyhow::Result<ImportedImage> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        let (width, height, components) =
+            jpeg_info(data).ok_or_else(|| anyhow::anyhow!("Failed to read JPEG dimensions"))?;
+        let color_space = match components {
+            1 => "DeviceGray",
+            3 => "DeviceRGB",
+            4 => "DeviceCMYK",
+            _ => anyhow::bail!("Unsupported number of JPEG components: {components}"),
+        };
+        let mut dict = image_dictionary(width, height, color_space);
+        dict.set("Filter", "DCTDecode");
+
+        return Ok(ImportedImage {
+            width,
+            height,
+            stream: Stream::new(dict, data.to_vec()),
+            mask: None,
+        });
+    }
+
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let pixels = &buffer[..info.buffer_size()];
+    let (width, height) = (info.width, info.height);
+
+    let (color_space, components, alpha) = match info.color_type {
+        png::ColorType::Grayscale => ("DeviceGray", 1, false),
+        png::ColorType::GrayscaleAlpha => ("DeviceGray", 1, true),
+        png::ColorType::Rgb => ("DeviceRGB", 3, false),
+        png::ColorType::Rgba => ("DeviceRGB", 3, true),
+        png::ColorType::Indexed => anyhow::bail!("Indexed PNG images should have been expanded"),
+    };
+
+    let (samples, mask) = if alpha {
+        let pixel_len = components + 1;
+        let mut samples = Vec::with_capacity(pixels.len() / pixel_len * components);
+        let mut alphas = Vec::with_capacity(pixels.len() / pixel_len);
+
+        for pixel in pixels.chunks_exact(pixel_len) {
+            samples.extend_from_slice(&pixel[..components]);
+            alphas.push(pixel[components]);
+        }
+        let mut mask = Stream::new(image_dictionary(width, height, "DeviceGray"), alphas);
+        let _ = mask.compress();
+        (samples, Some(mask))
+    } else {
+        (pixels.to_vec(), None)
+    };
+
+    let mut stream = Stream::new(image_dictionary(width, height, color_space), samples);
+    let _ = stream.compress();
+
+    Ok(ImportedImage {
+        width,
+        height,
+        stream,
+        mask,
+    })
+}
+
+/// An image XObject drawn on a page.
+pub struct PlacedImage<'a> {
+    /// Object ID of the image XObject.
+    #[cfg_attr(not(feature = "convert"), allow(dead_code))]
+    pub id: ObjectId,
+    /// Image XObject.
+    pub stream: &'a Stream,
+    /// Transformation from the unit square to page coordinates.
+    pub ctm: Matrix,
+}
+
+/// Return the image XObjects drawn by a page's content stream, in drawing
+/// order.
+///
+/// Images drawn by nested form XObjects or inline images are not returned.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+pub fn page_images(document: &Document, page_id: ObjectId) -> lopdf::Result<Vec<PlacedImage<'_>>> {
+    let operations = document.get_and_decode_page_content(page_id)?.operations;
+    let xobjects = document.page_xobjects(page_id);
+
+    Ok(with_ctm(&operations)
+        .into_iter()
+        .filter(|(operation, _)| operation.operator == "Do")
+        .filter_map(|(operation, ctm)| {
+            let name = operation.operands.first()?.as_name().ok()?;
+            let id = *xobjects.get(name)?;
+            let stream = document.get_object(id).ok()?.as_stream().ok()?;
+            let subtype = stream.dict.get(b"Subtype").and_then(Object::as_name).ok()?;
+
+            (subtype == b"Image").then_some(PlacedImage { id, stream, ctm })
+        })
+        .collect())
+}
+
+/// Return the image of an image-only page, e.g., a scan, if it is drawn
+/// upright.
+///
+/// Pages with text, e.g., OCRed scans, or with several images return
+/// `None`.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+pub fn page_scan(document: &Document, page_id: ObjectId) -> lopdf::Result<Option<PlacedImage<'_>>> {
+    if !text::page_spans(document, page_id)?.is_empty() {
+        return Ok(None);
+    }
+    let mut images = page_images(document, page_id)?;
+    if images.len() != 1 {
+        return Ok(None);
+    }
+    let image = images.remove(0);
+    let ctm = image.ctm;
+
+    if ctm.b != 0.0 || ctm.c != 0.0 || ctm.a <= 0.0 || ctm.d <= 0.0 {
+        return Ok(None);
+    }
+    Ok(Some(image))
+}
@@ -0,0 +1,111 @@
+//! Transfer of objects between documents.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Document, Object, ObjectId};
+
+/// Mapping from object IDs of a source document to object IDs of a target
+/// document.
+pub type IdMapping = BTreeMap<ObjectId, ObjectId>;
+
+/// Push the references held by an object, at any depth.
+pub fn collect_references(object: &Object, references: &mut Vec<ObjectId>) {
+    match object {
+        Object::Reference(id) => references.push(*id),
+        Object::Array(array) => {
+            for item in array {
+                collect_references(item, references);
+            }
+        },
+        Object::Dictionary(dict) => {
+            for (_, value) in dict.iter() {
+                collect_references(value, references);
+            }
+        },
+        Object::Stream(stream) => {
+            for (_, value) in stream.dict.iter() {
+                collect_references(value, references);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Rewrite the references held by an object, at any depth.
+///
+/// References missing from the mapping are dangling in the source document,
+/// and become `null`, as the specification mandates for them.
+pub fn remap_references(object: &mut Object, mapping: &IdMapping) {
+    match object {
+        Object::Reference(id) => {
+            *object = match mapping.get(id) {
+                Some(&id) => Object::Reference(id),
+                None => Object::Null,
+            };
+        },
+        Object::Array(array) => {
+            for item in array {
+                remap_references(item, mapping);
+            }
+        },
+        Object::Dictionary(dict) => {
+            for (_, value) in dict.iter_mut() {
+                remap_references(value, mapping);
+            }
+        },
+        Object::Stream(stream) => {
+            for (_, value) in stream.dict.iter_mut() {
+                remap_references(value, mapping);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Move the objects reachable from `roots` out of `source` and into `target`,
+/// under new object IDs.
+///
+/// References to objects found in `mapping` are rewritten to the mapped IDs
+/// and not followed, e.g., to attach annotations to the matching pages of
+/// `target` instead of importing the pages of `source`. Other references are
+/// followed, and the objects they point to are moved too.
+///
+/// Objects are moved rather than cloned, so `source` is left incomplete and
+/// should be discarded afterwards. Returns the mapping of all imported
+/// objects, including the given one.
+pub fn import_objects(
+    target: &mut Document,
+    source: &mut Document,
+    roots: &[ObjectId],
+    mut mapping: IdMapping,
+) -> IdMapping {
+    let mut pending = roots.to_vec();
+    let mut moved = vec![];
+
+    while let Some(id) = pending.pop() {
+        if mapping.contains_key(&id) {
+            continue;
+        }
+        let Some(object) = source.objects.remove(&id) else {
+            continue;
+        };
+        // Objects already moved are no longer in `source`, hence visited
+        // once.
+        collect_references(&object, &mut pending);
+        moved.push((id, object));
+    }
+
+    // IDs are allocated in one go, once all objects are known.
+    let first = target.max_id + 1;
+    target.max_id += u32::try_from(moved.len()).unwrap_or(u32::MAX);
+    for ((id, _), number) in moved.iter().zip(first..) {
+        mapping.insert(*id, (number, 0));
+    }
+    target
+        .objects
+        .extend(moved.into_iter().map(|(id, mut object)| {
+            remap_references(&mut object, &mapping);
+            (mapping[&id], object)
+        }));
+    mapping
+}
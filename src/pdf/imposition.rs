@@ -0,0 +1,111 @@
+//! Imposition, i.e., arranging pages on printer sheets.
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, dictionary};
+
+use super::document::DocumentExt;
+
+/// Return the pages on each side of the sheets of a booklet, as
+/// `[left, right]` page numbers, `None` being a blank page.
+///
+/// Pages are split in signatures, i.e., groups of sheets folded together,
+/// of `signature` pages, or of all pages if `None`. The last signature is
+/// padded with blank pages to a multiple of four pages. Sides alternate
+/// between the front and the back of each sheet, so that printing them in
+/// duplex, flipping on the short edge, then folding the stack of sheets,
+/// gives pages in order.
+#[must_use]
+pub fn booklet_order(pages: u32, signature: Option<u32>) -> Vec<[Option<u32>; 2]> {
+    let signature = signature
+        .unwrap_or_else(|| pages.next_multiple_of(4))
+        .max(4);
+    let page = |number: u32| (number <= pages).then_some(number);
+    let mut sides = vec![];
+    let mut first = 1;
+
+    while first <= pages {
+        let size = signature.min((pages - first + 1).next_multiple_of(4));
+        let last = first + size - 1;
+
+        for i in 0..size / 4 {
+            sides.push([page(last - 2 * i), page(first + 2 * i)]);
+            sides.push([page(first + 2 * i + 1), page(last - 2 * i - 1)]);
+        }
+        first += size;
+    }
+    sides
+}
+
+/// Replace the pages of a document with booklet sheets, two pages side by
+/// side, ordered with [`booklet_order`]. Returns the number of sheet sides.
+///
+/// Each half of a sheet is as large as the largest page, and pages are
+/// centered in it. Annotations, bookmarks and the open action are dropped,
+/// as they refer to pages that no longer exist.
+pub fn make_booklet(document: &mut Document, signature: Option<u32>) -> lopdf::Result<usize> {
+    let pages: Vec<ObjectId> = document.get_pages().into_values().collect();
+    let boxes: Vec<[f32; 4]> = pages
+        .iter()
+        .map(|&id| {
+            document
+                .page_crop_box(id)
+                .unwrap_or([0.0, 0.0, 612.0, 792.0])
+        })
+        .collect();
+    let (width, height) = boxes.iter().fold((0.0f32, 0.0f32), |(width, height), b| {
+        (width.max(b[2] - b[0]), height.max(b[3] - b[1]))
+    });
+    let forms = pages
+        .iter()
+        .map(|&id| document.page_to_form(id))
+        .collect::<lopdf::Result<Vec<_>>>()?;
+
+    let pages_id = document.new_object_id();
+    let sides = booklet_order(pages.len() as u32, signature);
+    let mut kids = vec![];
+
+    for side in &sides {
+        let mut content = String::new();
+        let mut xobjects = Dictionary::new();
+
+        for (half, number) in side.iter().enumerate() {
+            let Some(number) = *number else {
+                continue;
+            };
+            let index = number as usize - 1;
+            let [x0, y0, x1, y1] = boxes[index];
+            let x = half as f32 * width + (width - (x1 - x0)) / 2.0 - x0;
+            let y = (height - (y1 - y0)) / 2.0 - y0;
+            let name = format!("Page{number}");
+
+            content += &format!("q 1 0 0 1 {x} {y} cm /{name} Do Q\n");
+            xobjects.set(name, forms[index]);
+        }
+        let mut stream = Stream::new(Dictionary::new(), content.into_bytes());
+        let _ = stream.compress();
+        let content_id = document.add_object(stream);
+
+        kids.push(Object::Reference(document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), Object::Real(2.0 * width), Object::Real(height)],
+            "Resources" => dictionary! { "XObject" => xobjects },
+            "Contents" => content_id,
+        })));
+    }
+
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    let catalog = document.catalog_mut()?;
+    catalog.set("Pages", pages_id);
+    catalog.remove(b"Outlines");
+    catalog.remove(b"OpenAction");
+    document.prune_objects();
+
+    Ok(sides.len())
+}
@@ -0,0 +1,47 @@
+//! Document information dictionary, which holds the title, author and dates
+//! of a document.
+
+use lopdf::{Dictionary, Document, Object};
+
+use super::pdfstring;
+
+/// Return the document information dictionary, if any.
+#[must_use]
+pub fn info(document: &Document) -> Option<&Dictionary> {
+    document
+        .trailer
+        .get_deref(b"Info", document)
+        .and_then(Object::as_dict)
+        .ok()
+}
+
+/// Return a text entry of the document information dictionary, if any.
+#[must_use]
+pub fn text(document: &Document, key: &[u8]) -> Option<String> {
+    info(document)?
+        .get_deref(key, document)
+        .and_then(pdfstring::decode_object)
+        .ok()
+}
+
+/// Set an entry of the document information dictionary, creating the
+/// dictionary if needed.
+pub fn set(document: &mut Document, key: &str, value: Object) {
+    let info = document
+        .trailer
+        .get(b"Info")
+        .and_then(Object::as_reference)
+        .ok()
+        .and_then(|id| document.objects.get_mut(&id))
+        .and_then(|object| object.as_dict_mut().ok());
+
+    match info {
+        Some(info) => info.set(key, value),
+        None => {
+            let mut info = Dictionary::new();
+            info.set(key, value);
+            let id = document.add_object(info);
+            document.trailer.set("Info", id);
+        },
+    }
+}
@@ -0,0 +1,853 @@
+//! Color usage and ink coverage of pages.
+//!
+//! Coverage is estimated from the area of painted shapes, without rendering
+//! pages: overlapping shapes are counted several times, text is assumed to
+//! cover a fixed fraction of its lines, and images that cannot be decoded,
+//! e.g., JPEG images, are assumed to be mid-tones.
+
+use std::collections::{BTreeMap, HashMap};
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream,
+    content::{Content, Operation},
+};
+use serde::Serialize;
+
+use super::{
+    colors::{Family, convert_color, family},
+    content::{Matrix, number},
+    document::DocumentExt,
+};
+
+/// Maximum nesting depth of form XObjects, which guards against cycles in
+/// malformed documents.
+const MAX_FORM_DEPTH: usize = 16;
+
+/// Difference between the RGB components of a color above which it is not
+/// considered a shade of gray.
+const NEUTRAL_TOLERANCE: f32 = 0.02;
+
+/// Fraction of the area of text lines covered by glyphs.
+const TEXT_DENSITY: f32 = 0.25;
+
+/// Average width of glyphs, relative to the font size.
+const GLYPH_WIDTH: f32 = 0.5;
+
+/// Filters of image data that cannot be decoded.
+const IMAGE_CODECS: &[&str] = &["DCTDecode", "JPXDecode", "JBIG2Decode", "CCITTFaxDecode"];
+
+/// Estimated coverage of process inks, in percent of the page area.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct Coverage {
+    pub cyan: f32,
+    pub magenta: f32,
+    pub yellow: f32,
+    pub black: f32,
+}
+
+impl Coverage {
+    /// Return the total coverage of all inks.
+    #[must_use]
+    pub fn total(&self) -> f32 {
+        self.cyan + self.magenta + self.yellow + self.black
+    }
+}
+
+/// Color usage of a page.
+#[derive(Clone, Debug, Serialize)]
+pub struct PageColors {
+    pub page: u32,
+    /// Whether the page uses colors other than shades of gray.
+    pub color: bool,
+    pub coverage: Coverage,
+    /// Estimated coverage of spot colors, by name, in percent of the page
+    /// area.
+    pub spot_colors: BTreeMap<String, f32>,
+}
+
+impl PageColors {
+    /// Return the estimated fraction of the page left blank, from 0 to 1,
+    /// counting all inks, including spot colors.
+    #[must_use]
+    pub fn blankness(&self) -> f32 {
+        let ink = self.coverage.total() + self.spot_colors.values().sum::<f32>();
+        1.0 - (ink / 100.0).min(1.0)
+    }
+}
+
+/// Color space of the current color.
+#[derive(Clone, Debug)]
+enum Space {
+    Process(Family),
+    /// Separation or DeviceN color space, with the names of its colorants.
+    Spot(Vec<String>),
+    /// Indexed color space, with the family of its base and its lookup
+    /// table.
+    Indexed(Family, Vec<u8>),
+    Pattern,
+    /// Other color spaces, e.g., Lab.
+    Unknown,
+}
+
+/// Color used to paint shapes.
+#[derive(Clone, Debug)]
+enum Paint {
+    Process(Family, Vec<f32>),
+    /// Tints of spot colorants.
+    Spot(Vec<(String, f32)>),
+    /// Pattern, by resource name.
+    Pattern(Vec<u8>),
+    Unknown,
+}
+
+impl Paint {
+    /// Return the initial color of a color space.
+    fn initial(space: &Space) -> Self {
+        match space {
+            Space::Process(Family::Cmyk) => Self::Process(Family::Cmyk, vec![0.0, 0.0, 0.0, 1.0]),
+            Space::Process(family) => Self::Process(*family, vec![0.0; family.components()]),
+            Space::Spot(names) => {
+                Self::Spot(names.iter().map(|name| (name.clone(), 1.0)).collect())
+            },
+            Space::Indexed(family, lookup) => Self::indexed(*family, lookup, 0),
+            Space::Pattern | Space::Unknown => Self::Unknown,
+        }
+    }
+
+    /// Return a color of an indexed color space.
+    fn indexed(family: Family, lookup: &[u8], index: usize) -> Self {
+        let components = family.components();
+        match lookup.get(index * components..(index + 1) * components) {
+            Some(color) => {
+                Self::Process(
+                    family,
+                    color
+                        .iter()
+                        .map(|&value| f32::from(value) / 255.0)
+                        .collect(),
+                )
+            },
+            None => Self::Unknown,
+        }
+    }
+
+    /// Return the color set by a `sc` or `scn` operator in a color space.
+    fn from_operands(space: &Space, operands: &[Object]) -> Self {
+        let components: Vec<f32> = operands.iter().filter_map(number).collect();
+        match space {
+            Space::Process(family) if components.len() == family.components() => {
+                Self::Process(*family, components)
+            },
+            Space::Spot(names) if components.len() == names.len() => {
+                Self::Spot(names.iter().cloned().zip(components).collect())
+            },
+            Space::Indexed(family, lookup) if components.len() == 1 => {
+                Self::indexed(*family, lookup, components[0].max(0.0) as usize)
+            },
+            Space::Pattern => {
+                match operands.last().and_then(|name| name.as_name().ok()) {
+                    Some(name) => Self::Pattern(name.to_vec()),
+                    None => Self::Unknown,
+                }
+            },
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Return whether a color is a shade of gray.
+fn is_neutral(components: &[f32], family: Family) -> bool {
+    let rgb = convert_color(components, family, Family::Rgb);
+    let max = rgb.iter().copied().fold(f32::MIN, f32::max);
+    let min = rgb.iter().copied().fold(f32::MAX, f32::min);
+    max - min <= NEUTRAL_TOLERANCE
+}
+
+/// Return the color space a `cs` or `CS` operand stands for.
+fn color_space(document: &Document, resources: Option<&Dictionary>, name: &[u8]) -> Space {
+    let object = resources
+        .and_then(|resources| {
+            resources
+                .get_deref(b"ColorSpace", document)
+                .and_then(Object::as_dict)
+                .ok()
+        })
+        .and_then(|color_spaces| color_spaces.get(name).ok())
+        .cloned()
+        .unwrap_or_else(|| Object::Name(name.to_vec()));
+    resolve_space(document, &object)
+}
+
+/// Return the color space described by an object.
+fn resolve_space(document: &Document, object: &Object) -> Space {
+    if let Some(family) = family(document, object) {
+        return Space::Process(family);
+    }
+    let Ok((_, object)) = document.dereference(object) else {
+        return Space::Unknown;
+    };
+    let name = |object: &Object| {
+        document
+            .dereference(object)
+            .ok()
+            .and_then(|(_, name)| name.as_name().ok())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+    };
+    match object {
+        Object::Name(name) if name == b"Pattern" => Space::Pattern,
+        Object::Array(array) => {
+            match array.first().and_then(|family| family.as_name().ok()) {
+                Some(b"Pattern") => Space::Pattern,
+                Some(b"Separation") => {
+                    array
+                        .get(1)
+                        .and_then(name)
+                        .map_or(Space::Unknown, |name| Space::Spot(vec![name]))
+                },
+                Some(b"DeviceN") => {
+                    array
+                        .get(1)
+                        .and_then(|names| document.dereference(names).ok()?.1.as_array().ok())
+                        .and_then(|names| names.iter().map(name).collect::<Option<Vec<_>>>())
+                        .map_or(Space::Unknown, Space::Spot)
+                },
+                Some(b"Indexed") => {
+                    let base = array.get(1).and_then(|base| family(document, base));
+                    let lookup = array.get(3).and_then(|lookup| {
+                        match document.dereference(lookup).ok()?.1 {
+                            Object::String(lookup, _) => Some(lookup.clone()),
+                            Object::Stream(lookup) => super::decompress(lookup).ok(),
+                            _ => None,
+                        }
+                    });
+                    match (base, lookup) {
+                        (Some(base), Some(lookup)) => Space::Indexed(base, lookup),
+                        _ => Space::Unknown,
+                    }
+                },
+                _ => Space::Unknown,
+            }
+        },
+        _ => Space::Unknown,
+    }
+}
+
+/// Return the colors at the ends of a function, for exponential functions
+/// and stitching functions made of them.
+fn function_colors(document: &Document, function: &Object, depth: usize) -> Option<Vec<Vec<f32>>> {
+    let function = document.dereference(function).ok()?.1.as_dict().ok()?;
+
+    match function
+        .get(b"FunctionType")
+        .and_then(Object::as_i64)
+        .ok()?
+    {
+        2 => {
+            let color = |key: &[u8], default: f32| {
+                function.get(key).map_or_else(
+                    |_| vec![default],
+                    |color| {
+                        color
+                            .as_array()
+                            .map(|color| color.iter().filter_map(number).collect())
+                            .unwrap_or_default()
+                    },
+                )
+            };
+            Some(vec![color(b"C0", 0.0), color(b"C1", 1.0)])
+        },
+        3 if depth < MAX_FORM_DEPTH => {
+            let functions = function.get(b"Functions").and_then(Object::as_array).ok()?;
+            let mut colors = vec![];
+            for function in functions {
+                colors.extend(function_colors(document, function, depth + 1)?);
+            }
+            Some(colors)
+        },
+        _ => None,
+    }
+}
+
+/// Return the colors a shading goes through, as far as they can be known.
+fn shading_paints(document: &Document, shading: &Object) -> Vec<Paint> {
+    let Some(shading) = document.dereference(shading).ok().and_then(|(_, shading)| {
+        match shading {
+            Object::Dictionary(dict) => Some(dict),
+            Object::Stream(stream) => Some(&stream.dict),
+            _ => None,
+        }
+    }) else {
+        return vec![Paint::Unknown];
+    };
+    let Some(family) = shading
+        .get(b"ColorSpace")
+        .ok()
+        .and_then(|color_space| family(document, color_space))
+    else {
+        return vec![Paint::Unknown];
+    };
+    let colors = shading
+        .get(b"Function")
+        .ok()
+        .and_then(|function| function_colors(document, function, 0))
+        .filter(|colors| {
+            colors
+                .iter()
+                .all(|color| color.len() == family.components())
+        });
+
+    match colors {
+        Some(colors) => {
+            colors
+                .into_iter()
+                .map(|color| Paint::Process(family, color))
+                .collect()
+        },
+        // Gray shadings are neutral whatever their colors.
+        None if family == Family::Gray => vec![Paint::Process(Family::Gray, vec![0.5])],
+        None => vec![Paint::Unknown],
+    }
+}
+
+/// Return whether an image uses colors other than shades of gray, and the
+/// average coverage of each process ink, from `0` to `1`.
+fn image_ink(document: &Document, stream: &Stream) -> (bool, [f32; 4]) {
+    let space = stream
+        .dict
+        .get(b"ColorSpace")
+        .map_or(Space::Unknown, |color_space| {
+            resolve_space(document, color_space)
+        });
+    let family = match &space {
+        Space::Process(family) | Space::Indexed(family, _) => *family,
+        _ => return (true, [0.0; 4]),
+    };
+    let filters = stream.filters().unwrap_or_default();
+    let samples = if stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(Object::as_i64)
+        .ok()
+        != Some(8)
+        || filters
+            .iter()
+            .any(|filter| IMAGE_CODECS.contains(&filter.as_str()))
+    {
+        None
+    } else if filters.is_empty() {
+        Some(stream.content.clone())
+    } else {
+        super::decompress(stream).ok()
+    };
+    let Some(samples) = samples else {
+        // Assume a mid-tone, and colors unless the image is gray.
+        let ink = convert_color(&vec![0.5; family.components()], family, Family::Cmyk);
+        return (family != Family::Gray, [ink[0], ink[1], ink[2], ink[3]]);
+    };
+
+    let mut color = false;
+    let mut total = [0.0; 4];
+    let mut count = 0;
+    let mut add = |components: &[f32]| {
+        color |= !is_neutral(components, family);
+        let ink = convert_color(components, family, Family::Cmyk);
+        for (total, ink) in total.iter_mut().zip(ink) {
+            *total += ink;
+        }
+        count += 1;
+    };
+    match &space {
+        Space::Indexed(_, lookup) => {
+            for &index in &samples {
+                if let Paint::Process(_, components) =
+                    Paint::indexed(family, lookup, usize::from(index))
+                {
+                    add(&components);
+                }
+            }
+        },
+        _ => {
+            for pixel in samples.chunks_exact(family.components()) {
+                let components: Vec<f32> = pixel
+                    .iter()
+                    .map(|&value| f32::from(value) / 255.0)
+                    .collect();
+                add(&components);
+            }
+        },
+    }
+    (color, total.map(|total| total / count.max(1) as f32))
+}
+
+/// Return the area of a polygon, with the shoelace formula.
+fn polygon_area(points: &[(f32, f32)]) -> f32 {
+    let doubled: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(&(x0, y0), &(x1, y1))| x0 * y1 - x1 * y0)
+        .sum();
+    doubled.abs() / 2.0
+}
+
+/// Return the length of the lines joining points.
+fn polyline_length(points: &[(f32, f32)]) -> f32 {
+    points
+        .windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1))
+        .sum()
+}
+
+/// Add a point to a path, starting a new subpath if `new`.
+fn add_point(subpaths: &mut Vec<Vec<(f32, f32)>>, ctm: &Matrix, (x, y): (f32, f32), new: bool) {
+    let point = ctm.apply(x, y);
+    match subpaths.last_mut() {
+        Some(subpath) if !new => subpath.push(point),
+        _ => subpaths.push(vec![point]),
+    }
+}
+
+/// Graphics state parameters that matter to ink coverage.
+#[derive(Clone, Debug)]
+struct GraphicsState {
+    ctm: Matrix,
+    fill_space: Space,
+    stroke_space: Space,
+    fill: Paint,
+    stroke: Paint,
+    line_width: f32,
+    /// Area of the clipping path, in default user space, if any.
+    clip_area: Option<f32>,
+    font_size: f32,
+    render_mode: i64,
+}
+
+/// Accumulates the color usage of a page.
+struct Scanner<'a> {
+    document: &'a Document,
+    page_area: f32,
+    color: bool,
+    ink: [f32; 4],
+    spots: BTreeMap<String, f32>,
+    /// Images already decoded, as they are often shown on several pages.
+    images: &'a mut HashMap<ObjectId, (bool, [f32; 4])>,
+}
+
+impl Scanner<'_> {
+    /// Add ink painted over an area, in default user space.
+    fn paint(&mut self, paint: &Paint, area: f32, resources: Option<&Dictionary>) {
+        let area = area.min(self.page_area);
+
+        match paint {
+            Paint::Process(family, components) => {
+                self.color |= !is_neutral(components, *family);
+                let ink = convert_color(components, *family, Family::Cmyk);
+                for (total, ink) in self.ink.iter_mut().zip(ink) {
+                    *total += ink * area;
+                }
+            },
+            Paint::Spot(tints) => {
+                for (name, tint) in tints {
+                    let channel = match name.as_str() {
+                        "None" => continue,
+                        "All" => {
+                            for total in &mut self.ink {
+                                *total += tint * area;
+                            }
+                            continue;
+                        },
+                        "Cyan" => 0,
+                        "Magenta" => 1,
+                        "Yellow" => 2,
+                        "Black" => 3,
+                        _ => {
+                            self.color = true;
+                            *self.spots.entry(name.clone()).or_default() += tint * area;
+                            continue;
+                        },
+                    };
+                    self.color |= channel < 3 && *tint > 0.0;
+                    self.ink[channel] += tint * area;
+                }
+            },
+            Paint::Pattern(name) => {
+                let pattern = resources
+                    .and_then(|resources| {
+                        resources
+                            .get_deref(b"Pattern", self.document)
+                            .and_then(Object::as_dict)
+                            .ok()
+                    })
+                    .and_then(|patterns| patterns.get_deref(name, self.document).ok());
+                let shading = pattern
+                    .and_then(|pattern| pattern.as_dict().ok())
+                    .filter(|pattern| {
+                        pattern.get(b"PatternType").and_then(Object::as_i64).ok() == Some(2)
+                    })
+                    .and_then(|pattern| pattern.get(b"Shading").ok());
+                match shading {
+                    Some(shading) => self.shade(shading, area, resources),
+                    // Tiling patterns are assumed to use colors.
+                    None => self.color = true,
+                }
+            },
+            Paint::Unknown => self.color = true,
+        }
+    }
+
+    /// Add the ink of a shading painted over an area.
+    fn shade(&mut self, shading: &Object, area: f32, resources: Option<&Dictionary>) {
+        let paints = shading_paints(self.document, shading);
+        let share = area / paints.len() as f32;
+        for paint in &paints {
+            self.paint(paint, share, resources);
+        }
+    }
+
+    /// Add the ink of an XObject, i.e., an image or the content of a form.
+    fn xobject(
+        &mut self,
+        resources: Option<&Dictionary>,
+        name: &[u8],
+        state: &GraphicsState,
+        depth: usize,
+    ) {
+        let Some((id, stream)) = resources
+            .and_then(|resources| {
+                resources
+                    .get_deref(b"XObject", self.document)
+                    .and_then(Object::as_dict)
+                    .ok()
+            })
+            .and_then(|xobjects| xobjects.get(name).ok())
+            .and_then(|object| {
+                let id = object.as_reference().ok()?;
+                Some((id, self.document.get_object(id).ok()?.as_stream().ok()?))
+            })
+        else {
+            return;
+        };
+        let area = state.ctm.area_scale();
+
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") => {
+                if stream
+                    .dict
+                    .get(b"ImageMask")
+                    .and_then(Object::as_bool)
+                    .unwrap_or(false)
+                {
+                    // Masks are painted with the fill color, assumed to cover
+                    // half of the image.
+                    self.paint(&state.fill.clone(), area / 2.0, resources);
+                    return;
+                }
+                let document = self.document;
+                let (color, ink) = *self
+                    .images
+                    .entry(id)
+                    .or_insert_with(|| image_ink(document, stream));
+                let area = area.min(self.page_area);
+                self.color |= color;
+                for (total, ink) in self.ink.iter_mut().zip(ink) {
+                    *total += ink * area;
+                }
+            },
+            Ok(b"Form") if depth < MAX_FORM_DEPTH => {
+                let Ok(content) = stream
+                    .decompressed_content()
+                    .or_else(|_| Ok::<_, lopdf::Error>(stream.content.clone()))
+                    .and_then(|data| Content::decode(&data))
+                else {
+                    return;
+                };
+                let matrix = stream
+                    .dict
+                    .get(b"Matrix")
+                    .and_then(Object::as_array)
+                    .ok()
+                    .and_then(|matrix| Matrix::from_operands(matrix))
+                    .unwrap_or(Matrix::IDENTITY);
+                let form_resources = stream
+                    .dict
+                    .get_deref(b"Resources", self.document)
+                    .and_then(Object::as_dict)
+                    .ok()
+                    .or(resources);
+                let state = GraphicsState {
+                    ctm: matrix.then(&state.ctm),
+                    ..state.clone()
+                };
+
+                self.run(&content.operations, form_resources, state, depth + 1);
+            },
+            _ => {},
+        }
+    }
+
+    /// Add the ink of the operations of a content stream.
+    fn run(
+        &mut self,
+        operations: &[Operation],
+        resources: Option<&Dictionary>,
+        mut state: GraphicsState,
+        depth: usize,
+    ) {
+        let mut stack = vec![];
+        // Subpaths of the current path, in default user space.
+        let mut subpaths: Vec<Vec<(f32, f32)>> = vec![];
+        let mut clip = false;
+        let mut text_matrix = Matrix::IDENTITY;
+
+        for operation in operations {
+            let operands = &operation.operands;
+            let numbers: Vec<f32> = operands.iter().filter_map(number).collect();
+            let (fill, stroke) = match operation.operator.as_str() {
+                "q" => {
+                    stack.push(state.clone());
+                    continue;
+                },
+                "Q" => {
+                    if let Some(saved) = stack.pop() {
+                        state = saved;
+                    }
+                    continue;
+                },
+                "cm" => {
+                    if let Some(matrix) = Matrix::from_operands(operands) {
+                        state.ctm = matrix.then(&state.ctm);
+                    }
+                    continue;
+                },
+                "w" => {
+                    state.line_width = numbers.first().copied().unwrap_or(1.0);
+                    continue;
+                },
+                "cs" | "CS" => {
+                    let Some(name) = operands.first().and_then(|name| name.as_name().ok()) else {
+                        continue;
+                    };
+                    let space = color_space(self.document, resources, name);
+                    if operation.operator == "cs" {
+                        state.fill = Paint::initial(&space);
+                        state.fill_space = space;
+                    } else {
+                        state.stroke = Paint::initial(&space);
+                        state.stroke_space = space;
+                    }
+                    continue;
+                },
+                "sc" | "scn" => {
+                    state.fill = Paint::from_operands(&state.fill_space, operands);
+                    continue;
+                },
+                "SC" | "SCN" => {
+                    state.stroke = Paint::from_operands(&state.stroke_space, operands);
+                    continue;
+                },
+                "g" | "rg" | "k" | "G" | "RG" | "K" => {
+                    let family = match operation.operator.to_ascii_lowercase().as_str() {
+                        "g" => Family::Gray,
+                        "rg" => Family::Rgb,
+                        _ => Family::Cmyk,
+                    };
+                    let space = Space::Process(family);
+                    let paint = Paint::from_operands(&space, operands);
+                    if operation.operator.chars().all(|c| c.is_ascii_lowercase()) {
+                        (state.fill, state.fill_space) = (paint, space);
+                    } else {
+                        (state.stroke, state.stroke_space) = (paint, space);
+                    }
+                    continue;
+                },
+                "m" | "l" => {
+                    if let [x, y] = numbers[..] {
+                        add_point(&mut subpaths, &state.ctm, (x, y), operation.operator == "m");
+                    }
+                    continue;
+                },
+                "c" | "v" | "y" => {
+                    // Curves are approximated by their control points.
+                    for pair in numbers.chunks_exact(2) {
+                        add_point(&mut subpaths, &state.ctm, (pair[0], pair[1]), false);
+                    }
+                    continue;
+                },
+                "re" => {
+                    if let [x, y, width, height] = numbers[..] {
+                        add_point(&mut subpaths, &state.ctm, (x, y), true);
+                        add_point(&mut subpaths, &state.ctm, (x + width, y), false);
+                        add_point(&mut subpaths, &state.ctm, (x + width, y + height), false);
+                        add_point(&mut subpaths, &state.ctm, (x, y + height), false);
+                        add_point(&mut subpaths, &state.ctm, (x, y), false);
+                    }
+                    continue;
+                },
+                "W" | "W*" => {
+                    clip = true;
+                    continue;
+                },
+                "S" | "s" => (false, true),
+                "f" | "F" | "f*" => (true, false),
+                "B" | "B*" | "b" | "b*" => (true, true),
+                "n" => (false, false),
+                "sh" => {
+                    if let Some(shading) = operands
+                        .first()
+                        .and_then(|name| name.as_name().ok())
+                        .and_then(|name| {
+                            resources?
+                                .get_deref(b"Shading", self.document)
+                                .and_then(Object::as_dict)
+                                .ok()?
+                                .get(name)
+                                .ok()
+                        })
+                    {
+                        let area = state.clip_area.unwrap_or(self.page_area);
+                        self.shade(shading, area, resources);
+                    }
+                    continue;
+                },
+                "Do" => {
+                    if let Some(name) = operands.first().and_then(|name| name.as_name().ok()) {
+                        self.xobject(resources, name, &state, depth);
+                    }
+                    continue;
+                },
+                "BT" => {
+                    text_matrix = Matrix::IDENTITY;
+                    continue;
+                },
+                "Tm" => {
+                    text_matrix = Matrix::from_operands(operands).unwrap_or(Matrix::IDENTITY);
+                    continue;
+                },
+                "Tf" => {
+                    state.font_size = numbers.first().copied().unwrap_or(0.0);
+                    continue;
+                },
+                "Tr" => {
+                    state.render_mode = operands
+                        .first()
+                        .and_then(|mode| mode.as_i64().ok())
+                        .unwrap_or(0);
+                    continue;
+                },
+                "Tj" | "'" | "\"" | "TJ" => {
+                    let glyphs: usize = operands
+                        .iter()
+                        .flat_map(|operand| {
+                            match operand {
+                                Object::Array(array) => array.iter().collect(),
+                                operand => vec![operand],
+                            }
+                        })
+                        .filter_map(|operand| operand.as_str().ok())
+                        .map(<[u8]>::len)
+                        .sum();
+                    let matrix = text_matrix.then(&state.ctm);
+                    let scale = matrix.area_scale();
+                    let area = glyphs as f32
+                        * GLYPH_WIDTH
+                        * state.font_size.powi(2)
+                        * scale
+                        * TEXT_DENSITY;
+                    match state.render_mode {
+                        3 | 7 => {},
+                        1 | 5 => self.paint(&state.stroke.clone(), area, resources),
+                        _ => self.paint(&state.fill.clone(), area, resources),
+                    }
+                    continue;
+                },
+                _ => continue,
+            };
+
+            let area: f32 = subpaths.iter().map(|subpath| polygon_area(subpath)).sum();
+            if fill {
+                self.paint(&state.fill.clone(), area, resources);
+            }
+            if stroke {
+                let scale = state.ctm.area_scale().sqrt();
+                let length: f32 = subpaths
+                    .iter()
+                    .map(|subpath| polyline_length(subpath))
+                    .sum();
+                self.paint(
+                    &state.stroke.clone(),
+                    length * state.line_width * scale,
+                    resources,
+                );
+            }
+            if std::mem::take(&mut clip) {
+                state.clip_area = Some(
+                    state
+                        .clip_area
+                        .map_or(area, |clip_area| clip_area.min(area)),
+                );
+            }
+            subpaths.clear();
+        }
+    }
+}
+
+/// Analyze the colors that the pages of a document use, and estimate their
+/// ink coverage.
+#[must_use]
+pub fn page_colors(document: &Document) -> Vec<PageColors> {
+    let mut images = HashMap::new();
+
+    document
+        .get_pages()
+        .into_iter()
+        .map(|(page, page_id)| {
+            let [x0, y0, x1, y1] = document
+                .page_crop_box(page_id)
+                .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+            let page_area = ((x1 - x0) * (y1 - y0)).max(1.0);
+            let mut scanner = Scanner {
+                document,
+                page_area,
+                color: false,
+                ink: [0.0; 4],
+                spots: BTreeMap::new(),
+                images: &mut images,
+            };
+            if let Ok(content) = document.get_and_decode_page_content(page_id) {
+                let state = GraphicsState {
+                    ctm: Matrix::IDENTITY,
+                    fill_space: Space::Process(Family::Gray),
+                    stroke_space: Space::Process(Family::Gray),
+                    fill: Paint::Process(Family::Gray, vec![0.0]),
+                    stroke: Paint::Process(Family::Gray, vec![0.0]),
+                    line_width: 1.0,
+                    clip_area: None,
+                    font_size: 0.0,
+                    render_mode: 0,
+                };
+                scanner.run(
+                    &content.operations,
+                    document.page_resources(page_id),
+                    state,
+                    0,
+                );
+            }
+
+            let percent = |area: f32| (100.0 * area / page_area).min(100.0);
+            PageColors {
+                page,
+                color: scanner.color,
+                coverage: Coverage {
+                    cyan: percent(scanner.ink[0]),
+                    magenta: percent(scanner.ink[1]),
+                    yellow: percent(scanner.ink[2]),
+                    black: percent(scanner.ink[3]),
+                },
+                spot_colors: scanner
+                    .spots
+                    .into_iter()
+                    .map(|(name, area)| (name, percent(area)))
+                    .collect(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,134 @@
+//! Journals of in-place edits, which hold the objects and trailer that an
+//! edit changed, so that it can be undone.
+//!
+//! Journals are PDF files themselves, whose objects are the original
+//! versions of the objects that the edit modified or deleted, with their
+//! original IDs. Their trailer holds the original trailer, the objects that
+//! the edit added, and the checksum of the edited file.
+//!
+//! Journals of encrypted files hold the whole original file instead, as the
+//! objects of a document are decrypted when it is read, and would be
+//! written in plain text.
+
+use anyhow::{Result, bail};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+
+/// Trailer key of the original trailer.
+const TRAILER_KEY: &str = "RpdfTrailer";
+/// Trailer key of the objects added by the edit.
+const ADDED_KEY: &str = "RpdfAdded";
+/// Trailer key of the SHA-256 hash of the edited file, in hexadecimal.
+const CHECKSUM_KEY: &str = "RpdfChecksum";
+/// Trailer key of the original file, for encrypted files.
+const FILE_KEY: &str = "RpdfFile";
+
+/// Return the checksum of an edited file as a trailer value.
+fn checksum_object(checksum: &str) -> Object {
+    Object::String(checksum.as_bytes().to_vec(), StringFormat::Literal)
+}
+
+/// Return the journal of an edit, from the document before and after it.
+///
+/// `checksum` is the hash of the edited file, see [`checksum`].
+#[must_use]
+pub fn record(original: &Document, edited: &Document, checksum: &str) -> Document {
+    let mut journal = Document::with_version(original.version.clone());
+
+    for (&id, object) in &original.objects {
+        if edited.objects.get(&id) != Some(object) {
+            journal.objects.insert(id, object.clone());
+        }
+    }
+    let added: Vec<Object> = edited
+        .objects
+        .keys()
+        .filter(|id| !original.objects.contains_key(id))
+        .map(|&id| Object::Reference(id))
+        .collect();
+
+    journal.max_id = original.max_id.max(edited.max_id);
+    journal.trailer = Dictionary::new();
+    journal.trailer.set(TRAILER_KEY, original.trailer.clone());
+    journal.trailer.set(ADDED_KEY, added);
+    journal.trailer.set(CHECKSUM_KEY, checksum_object(checksum));
+    journal
+}
+
+/// Return the journal of an edit of an encrypted file, from the data of the
+/// file before the edit, which it holds as is.
+///
+/// `checksum` is the hash of the edited file, see [`checksum`].
+#[must_use]
+pub fn record_file(original: &[u8], checksum: &str) -> Document {
+    let mut journal = Document::with_version("1.7");
+    let file_id = journal.add_object(Stream::new(Dictionary::new(), original.to_vec()));
+
+    journal.trailer = Dictionary::new();
+    journal.trailer.set(FILE_KEY, file_id);
+    journal.trailer.set(CHECKSUM_KEY, checksum_object(checksum));
+    journal
+}
+
+/// Return the data of the file before the edit of a journal, if it was
+/// recorded with [`record_file`].
+#[must_use]
+pub fn original_file(journal: &Document) -> Option<&[u8]> {
+    let id = journal
+        .trailer
+        .get(FILE_KEY.as_bytes())
+        .and_then(Object::as_reference)
+        .ok()?;
+    let stream = journal.get_object(id).and_then(Object::as_stream).ok()?;
+    Some(&stream.content)
+}
+
+/// Return the hash of the edited file that a journal was recorded for, if
+/// any.
+#[must_use]
+pub fn checksum(journal: &Document) -> Option<String> {
+    journal
+        .trailer
+        .get(CHECKSUM_KEY.as_bytes())
+        .and_then(Object::as_str)
+        .ok()
+        .map(|checksum| String::from_utf8_lossy(checksum).into_owned())
+}
+
+/// Restore the document as it was before the edit of a journal.
+///
+/// Journals of encrypted files hold the original file rather than objects,
+/// see [`original_file`].
+///
+/// # Errors
+///
+/// Fails if the journal is not one.
+pub fn undo(document: &mut Document, journal: &Document) -> Result<()> {
+    let (Ok(trailer), Ok(added)) = (
+        journal
+            .trailer
+            .get(TRAILER_KEY.as_bytes())
+            .and_then(Object::as_dict),
+        journal
+            .trailer
+            .get(ADDED_KEY.as_bytes())
+            .and_then(Object::as_array),
+    ) else {
+        bail!("Not a journal of rpdf.");
+    };
+
+    for id in added.iter().filter_map(|id| id.as_reference().ok()) {
+        document.objects.remove(&id);
+    }
+    for (&id, object) in &journal.objects {
+        document.objects.insert(id, object.clone());
+    }
+    document.trailer = trailer.clone();
+    document.version.clone_from(&journal.version);
+    document.max_id = document
+        .objects
+        .keys()
+        .map(|&(number, _): &ObjectId| number)
+        .max()
+        .unwrap_or(0);
+    Ok(())
+}
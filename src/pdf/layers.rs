@@ -0,0 +1,529 @@
+//! Optional content groups, i.e., layers that viewers can show or hide, e.g.,
+//! the layers of CAD drawings.
+//!
+//! Layers are listed in `/OCProperties` of the catalog, whose default
+//! configuration `/D` tells which layers are shown when opening the document.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, content::Content, dictionary};
+
+use super::{document::DocumentExt, objects::object_dictionary, pdfstring};
+
+/// Layer of a document.
+#[derive(Clone, Debug)]
+pub struct Layer {
+    /// ID of the optional content group.
+    pub id: ObjectId,
+    /// Name shown by viewers.
+    pub name: String,
+    /// Whether the layer is shown when opening the document.
+    pub visible: bool,
+    /// Whether viewers prevent users from changing the visibility of the
+    /// layer.
+    pub locked: bool,
+    /// Intents of the layer, e.g., `View` or `Design`.
+    pub intents: Vec<String>,
+    /// Numbers of the pages whose content or annotations belong to the layer.
+    pub pages: Vec<u32>,
+}
+
+/// Number of objects removed with layers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Removal {
+    /// Marked content sections removed from pages and forms.
+    pub sections: usize,
+    /// XObjects, e.g., images, replaced by empty forms.
+    pub xobjects: usize,
+    /// Annotations removed from pages.
+    pub annotations: usize,
+}
+
+/// Return the optional content properties of the document, if any.
+fn properties(document: &Document) -> Option<&Dictionary> {
+    document
+        .catalog()
+        .ok()?
+        .get_deref(b"OCProperties", document)
+        .and_then(Object::as_dict)
+        .ok()
+}
+
+/// Return the default configuration of layers, if any.
+fn default_config(document: &Document) -> Option<&Dictionary> {
+    properties(document)?
+        .get_deref(b"D", document)
+        .and_then(Object::as_dict)
+        .ok()
+}
+
+/// Return the optional content properties of the document, mutably.
+fn properties_mut(document: &mut Document) -> lopdf::Result<&mut Dictionary> {
+    match document.catalog()?.get(b"OCProperties")? {
+        &Object::Reference(id) => document.get_dictionary_mut(id),
+        _ => {
+            document
+                .catalog_mut()?
+                .get_mut(b"OCProperties")
+                .and_then(Object::as_dict_mut)
+        },
+    }
+}
+
+/// Return the default configuration of layers, mutably.
+fn default_config_mut(document: &mut Document) -> lopdf::Result<&mut Dictionary> {
+    match properties_mut(document)?.get(b"D")? {
+        &Object::Reference(id) => document.get_dictionary_mut(id),
+        _ => {
+            properties_mut(document)?
+                .get_mut(b"D")
+                .and_then(Object::as_dict_mut)
+        },
+    }
+}
+
+/// Return the IDs referenced by an array entry of a dictionary.
+fn references(document: &Document, dict: &Dictionary, key: &[u8]) -> Vec<ObjectId> {
+    dict.get_deref(key, document)
+        .and_then(Object::as_array)
+        .map(|array| {
+            array
+                .iter()
+                .filter_map(|item| item.as_reference().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return whether an optional content object, i.e., a group or a membership
+/// dictionary, only depends on the given layers.
+fn targets_layers(document: &Document, object: &Object, ids: &BTreeSet<ObjectId>) -> bool {
+    if let Ok(id) = object.as_reference() {
+        if ids.contains(&id) {
+            return true;
+        }
+    }
+    let Ok(dict) = document
+        .dereference(object)
+        .and_then(|(_, object)| object.as_dict())
+    else {
+        return false;
+    };
+    if dict.get(b"Type").and_then(Object::as_name).ok() != Some(b"OCMD") {
+        return false;
+    }
+    match dict.get(b"OCGs") {
+        Ok(Object::Reference(id)) if ids.contains(id) => true,
+        Ok(Object::Array(groups)) => {
+            !groups.is_empty()
+                && groups
+                    .iter()
+                    .all(|group| group.as_reference().is_ok_and(|id| ids.contains(&id)))
+        },
+        _ => false,
+    }
+}
+
+/// Return the names of the properties of a resources dictionary that refer
+/// to the given layers.
+fn property_names(
+    document: &Document,
+    resources: Option<&Dictionary>,
+    ids: &BTreeSet<ObjectId>,
+) -> BTreeSet<Vec<u8>> {
+    resources
+        .and_then(|resources| {
+            resources
+                .get_deref(b"Properties", document)
+                .and_then(Object::as_dict)
+                .ok()
+        })
+        .map(|properties| {
+            properties
+                .iter()
+                .filter(|(_, value)| targets_layers(document, value, ids))
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return the layers of a document, in the order of `/OCGs`.
+#[must_use]
+pub fn layers(document: &Document) -> Vec<Layer> {
+    let Some(properties) = properties(document) else {
+        return vec![];
+    };
+    let config = default_config(document);
+    let config_references =
+        |key: &[u8]| config.map_or(vec![], |config| references(document, config, key));
+    let (on, off, locked) = (
+        config_references(b"ON"),
+        config_references(b"OFF"),
+        config_references(b"Locked"),
+    );
+    let hidden_by_default = config
+        .and_then(|config| config.get(b"BaseState").and_then(Object::as_name).ok())
+        == Some(b"OFF");
+    let pages = document.get_pages();
+
+    references(document, properties, b"OCGs")
+        .into_iter()
+        .filter_map(|id| {
+            let group = document.get_dictionary(id).ok()?;
+            let name = group
+                .get_deref(b"Name", document)
+                .and_then(pdfstring::decode_object)
+                .unwrap_or_default();
+            let intents = match group.get_deref(b"Intent", document) {
+                Ok(Object::Name(intent)) => vec![String::from_utf8_lossy(intent).into_owned()],
+                Ok(Object::Array(intents)) => {
+                    intents
+                        .iter()
+                        .filter_map(|intent| intent.as_name_str().ok())
+                        .map(ToString::to_string)
+                        .collect()
+                },
+                _ => vec!["View".to_string()],
+            };
+            let ids = BTreeSet::from([id]);
+            let used_by = |page_id: ObjectId| {
+                !property_names(document, document.page_resources(page_id), &ids).is_empty()
+                    || page_annotations(document, page_id)
+                        .into_iter()
+                        .any(|annotation| {
+                            document
+                                .get_dictionary(annotation)
+                                .and_then(|annotation| annotation.get(b"OC"))
+                                .is_ok_and(|oc| targets_layers(document, oc, &ids))
+                        })
+            };
+
+            Some(Layer {
+                id,
+                name,
+                visible: if off.contains(&id) {
+                    false
+                } else {
+                    on.contains(&id) || !hidden_by_default
+                },
+                locked: locked.contains(&id),
+                intents,
+                pages: pages
+                    .iter()
+                    .filter(|&(_, &page_id)| used_by(page_id))
+                    .map(|(&number, _)| number)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Return the first layer with the given name, creating it if needed.
+///
+/// New layers are shown by default, and listed last by viewers.
+///
+/// # Errors
+///
+/// Fails if the document has no catalog.
+pub fn find_or_create(document: &mut Document, name: &str) -> lopdf::Result<ObjectId> {
+    if let Some(layer) = layers(document)
+        .into_iter()
+        .find(|layer| layer.name == name)
+    {
+        return Ok(layer.id);
+    }
+    let id = document.add_object(dictionary! {
+        "Type" => "OCG",
+        "Name" => pdfstring::encode_object(name),
+    });
+
+    if properties(document).is_none() {
+        document.catalog_mut()?.set(
+            "OCProperties",
+            dictionary! { "OCGs" => Vec::<Object>::new(), "D" => Dictionary::new() },
+        );
+    }
+    let properties = properties_mut(document)?;
+    if properties.get(b"OCGs").and_then(Object::as_array).is_err() {
+        properties.set("OCGs", Vec::<Object>::new());
+    }
+    properties
+        .get_mut(b"OCGs")
+        .and_then(Object::as_array_mut)?
+        .push(id.into());
+
+    if default_config(document).is_none() {
+        properties_mut(document)?.set("D", Dictionary::new());
+    }
+    let config = default_config_mut(document)?;
+    for key in ["Order", "ON"] {
+        if config
+            .get(key.as_bytes())
+            .and_then(Object::as_array)
+            .is_err()
+        {
+            config.set(key, Vec::<Object>::new());
+        }
+        config
+            .get_mut(key.as_bytes())
+            .and_then(Object::as_array_mut)?
+            .push(id.into());
+    }
+    Ok(id)
+}
+
+/// Remove the given IDs from an array, at any depth, e.g., from `/Order`.
+fn remove_from_array(object: &mut Object, ids: &BTreeSet<ObjectId>) {
+    if let Object::Array(array) = object {
+        array.retain(|item| item.as_reference().map_or(true, |id| !ids.contains(&id)));
+        for item in array {
+            remove_from_array(item, ids);
+        }
+    }
+}
+
+/// Show or hide layers when opening the document.
+///
+/// # Errors
+///
+/// Fails if the document has no optional content properties.
+pub fn set_visible(document: &mut Document, ids: &[ObjectId], visible: bool) -> lopdf::Result<()> {
+    let set: BTreeSet<ObjectId> = ids.iter().copied().collect();
+    let config = default_config_mut(document)?;
+
+    for key in ["ON", "OFF"] {
+        if let Ok(array) = config.get_mut(key.as_bytes()) {
+            remove_from_array(array, &set);
+        }
+    }
+    let key = if visible { "ON" } else { "OFF" };
+    if config
+        .get(key.as_bytes())
+        .and_then(Object::as_array)
+        .is_err()
+    {
+        config.set(key, Vec::<Object>::new());
+    }
+    config
+        .get_mut(key.as_bytes())
+        .and_then(Object::as_array_mut)?
+        .extend(ids.iter().copied().map(Object::Reference));
+    Ok(())
+}
+
+/// Return the annotations of a page.
+fn page_annotations(document: &Document, page_id: ObjectId) -> Vec<ObjectId> {
+    document
+        .get_dictionary(page_id)
+        .and_then(|page| page.get_deref(b"Annots", document))
+        .and_then(Object::as_array)
+        .map(|annots| {
+            annots
+                .iter()
+                .filter_map(|annot| annot.as_reference().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Remove the marked content sections of the given property names, e.g.,
+/// `/OC /L1 BDC ... EMC`, and return the number of removed sections.
+fn remove_marked_content(content: &mut Content, names: &BTreeSet<Vec<u8>>) -> usize {
+    let mut removed = 0;
+    // Depth of nested marked content within the removed section, if any.
+    let mut depth = 0;
+
+    content.operations.retain(|operation| {
+        if depth > 0 {
+            match operation.operator.as_str() {
+                "BMC" | "BDC" => depth += 1,
+                "EMC" => depth -= 1,
+                _ => {},
+            }
+            return false;
+        }
+        let is_layer = operation.operator == "BDC"
+            && matches!(
+                operation.operands.as_slice(),
+                [Object::Name(tag), Object::Name(name)] if tag == b"OC" && names.contains(name)
+            );
+        if is_layer {
+            depth = 1;
+            removed += 1;
+        }
+        !is_layer
+    });
+    removed
+}
+
+/// Remove layers and everything that belongs to them, i.e., their marked
+/// content in pages and forms, their XObjects and their annotations.
+///
+/// XObjects are replaced by empty forms, as other content may still draw
+/// them.
+///
+/// # Errors
+///
+/// Fails if the document has no optional content properties.
+pub fn remove(document: &mut Document, ids: &[ObjectId]) -> lopdf::Result<Removal> {
+    let ids: BTreeSet<ObjectId> = ids.iter().copied().collect();
+    let mut removal = Removal::default();
+
+    for page_id in document.get_pages().into_values() {
+        let names = property_names(document, document.page_resources(page_id), &ids);
+        if !names.is_empty() {
+            let mut content = document.get_and_decode_page_content(page_id)?;
+            let removed = remove_marked_content(&mut content, &names);
+            if removed > 0 {
+                let content_id =
+                    document.add_object(Stream::new(Dictionary::new(), content.encode()?));
+                document
+                    .get_dictionary_mut(page_id)?
+                    .set("Contents", content_id);
+                removal.sections += removed;
+            }
+        }
+
+        let removed: BTreeSet<ObjectId> = page_annotations(document, page_id)
+            .into_iter()
+            .filter(|&annotation| {
+                document
+                    .get_dictionary(annotation)
+                    .and_then(|annotation| annotation.get(b"OC"))
+                    .is_ok_and(|oc| targets_layers(document, oc, &ids))
+            })
+            .collect();
+        if !removed.is_empty() {
+            let page = document.get_dictionary(page_id)?;
+            let annots = match page.get(b"Annots") {
+                Ok(&Object::Reference(id)) => document.get_object_mut(id)?,
+                _ => document.get_dictionary_mut(page_id)?.get_mut(b"Annots")?,
+            };
+            remove_from_array(annots, &removed);
+            for id in &removed {
+                document.objects.remove(id);
+            }
+            removal.annotations += removed.len();
+        }
+    }
+
+    let mut updates = vec![];
+    for (&id, object) in &document.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        if stream
+            .dict
+            .get(b"OC")
+            .is_ok_and(|oc| targets_layers(document, oc, &ids))
+        {
+            let empty = dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+            };
+            updates.push((id, Stream::new(empty, vec![])));
+            removal.xobjects += 1;
+            continue;
+        }
+        if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Form") {
+            continue;
+        }
+        let resources = stream
+            .dict
+            .get_deref(b"Resources", document)
+            .and_then(Object::as_dict)
+            .ok();
+        let names = property_names(document, resources, &ids);
+        if names.is_empty() {
+            continue;
+        }
+        let Ok(mut content) = stream
+            .decompressed_content()
+            .or_else(|_| Ok(stream.content.clone()))
+            .and_then(|data: Vec<u8>| Content::decode(&data))
+        else {
+            continue;
+        };
+        let removed = remove_marked_content(&mut content, &names);
+        if removed > 0 {
+            let mut dict = stream.dict.clone();
+            dict.remove(b"Filter");
+            dict.remove(b"DecodeParms");
+            updates.push((id, Stream::new(dict, content.encode()?)));
+            removal.sections += removed;
+        }
+    }
+    for (id, stream) in updates {
+        document.objects.insert(id, Object::Stream(stream));
+    }
+
+    // Resources must not refer to removed layers.
+    let targets: Vec<(ObjectId, Vec<Vec<u8>>)> = document
+        .objects
+        .iter()
+        .filter_map(|(&id, object)| {
+            let dict = object_dictionary(object)?;
+            let resources = match dict.get(b"Resources") {
+                Ok(Object::Dictionary(resources)) => resources,
+                _ => dict,
+            };
+            let names = property_names(document, Some(resources), &ids);
+            (!names.is_empty()).then(|| (id, names.into_iter().collect()))
+        })
+        .collect();
+    for (id, names) in targets {
+        let Some(dict) = document.objects.get_mut(&id).and_then(|object| {
+            match object {
+                Object::Dictionary(dict) => Some(dict),
+                Object::Stream(stream) => Some(&mut stream.dict),
+                _ => None,
+            }
+        }) else {
+            continue;
+        };
+        let resources = match dict.get_mut(b"Resources") {
+            Ok(Object::Dictionary(resources)) => resources,
+            _ => dict,
+        };
+        if let Ok(properties) = resources
+            .get_mut(b"Properties")
+            .and_then(Object::as_dict_mut)
+        {
+            for name in names {
+                properties.remove(&name);
+            }
+        }
+    }
+
+    // Configurations, i.e., `/D` and `/Configs`, must not refer to removed
+    // layers either.
+    let properties = properties_mut(document)?;
+    if let Ok(groups) = properties.get_mut(b"OCGs") {
+        remove_from_array(groups, &ids);
+    }
+    if let Ok(configs) = properties
+        .get_mut(b"Configs")
+        .and_then(Object::as_array_mut)
+    {
+        for config in configs
+            .iter_mut()
+            .filter_map(|config| config.as_dict_mut().ok())
+        {
+            for (_, value) in config.iter_mut() {
+                remove_from_array(value, &ids);
+            }
+        }
+    }
+    if let Ok(config) = default_config_mut(document) {
+        for (_, value) in config.iter_mut() {
+            remove_from_array(value, &ids);
+        }
+    }
+    for id in &ids {
+        document.objects.remove(id);
+    }
+    document.prune_objects();
+    Ok(removal)
+}
@@ -0,0 +1,412 @@
+//! A minimal layout engine to generate paginated documents.
+//!
+//! Blocks (paragraphs, images and rules) are laid out from top to bottom,
+//! wrapping lines at word boundaries and starting new pages when the bottom
+//! margin is reached.
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream,
+    content::{Content, Operation},
+    dictionary,
+};
+
+use super::{
+    fonts::{StandardFont, TrueTypeFont, encode_win_ansi, win_ansi_string},
+    images::ImportedImage,
+};
+
+/// Size of an image pixel, in points, i.e., images are shown at 96 DPI unless
+/// they do not fit.
+const PIXEL_SIZE: f32 = 0.75;
+
+/// Padding around the background of paragraphs, in points.
+const BACKGROUND_PADDING: f32 = 4.0;
+
+/// Gap between a list marker and the text, in points.
+const MARKER_GAP: f32 = 6.0;
+
+/// A font used to lay out text.
+#[derive(Clone, Debug)]
+pub enum LayoutFont {
+    /// A standard font, which is not embedded.
+    Standard(StandardFont),
+    /// A TrueType font, embedded in the document.
+    TrueType(TrueTypeFont),
+}
+
+impl LayoutFont {
+    /// Return the width of a WinAnsi-encoded byte, in thousandths of the font
+    /// size.
+    fn glyph_width(&self, byte: u8) -> u16 {
+        match self {
+            Self::Standard(font) => font.glyph_width(byte),
+            Self::TrueType(font) => font.glyph_width(byte),
+        }
+    }
+
+    /// Return the width of a text, in unscaled text space units, when shown
+    /// with the given font size.
+    #[must_use]
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = encode_win_ansi(text)
+            .into_iter()
+            .map(|byte| u32::from(self.glyph_width(byte)))
+            .sum();
+        units as f32 * size / 1000.0
+    }
+
+    /// Add the font to a document, returning the ID of its dictionary.
+    fn add_to(&self, document: &mut Document) -> ObjectId {
+        match self {
+            Self::Standard(font) => document.add_object(font.dictionary()),
+            Self::TrueType(font) => font.add_to(document),
+        }
+    }
+}
+
+/// Identifier of a font added to a [`Layout`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FontId(usize);
+
+impl FontId {
+    /// Return the resource name of the font.
+    fn name(self) -> Vec<u8> {
+        format!("F{}", self.0 + 1).into_bytes()
+    }
+}
+
+/// A piece of text shown with a single font.
+#[derive(Clone, Copy, Debug)]
+pub struct Run<'a> {
+    pub font: FontId,
+    pub text: &'a str,
+}
+
+/// Properties of a laid out paragraph.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParagraphStyle<'a> {
+    /// Font size, in points.
+    pub size: f32,
+    /// Distance between baselines, in points.
+    pub line_height: f32,
+    /// Left indentation, in points.
+    pub indent: f32,
+    /// Text shown before the first line, in the indentation, e.g., a list
+    /// bullet.
+    pub marker: Option<Run<'a>>,
+    /// Gray level of the background, if it is filled.
+    pub background: Option<f32>,
+}
+
+/// Lays out text on pages of a new document.
+pub struct Layout {
+    fonts: Vec<LayoutFont>,
+    images: Vec<ImportedImage>,
+    /// Operations of the finished pages.
+    pages: Vec<Vec<Operation>>,
+    /// Operations of the current page.
+    operations: Vec<Operation>,
+    width: f32,
+    height: f32,
+    margin: f32,
+    /// Vertical position of the top of the next line.
+    cursor: f32,
+}
+
+impl Layout {
+    /// Create a layout for pages of the given size and margin, in points.
+    #[must_use]
+    pub fn new(width: f32, height: f32, margin: f32) -> Self {
+        Self {
+            fonts: vec![],
+            images: vec![],
+            pages: vec![],
+            operations: vec![],
+            width,
+            height,
+            margin,
+            cursor: height - margin,
+        }
+    }
+
+    /// Add a font that runs can use.
+    pub fn add_font(&mut self, font: LayoutFont) -> FontId {
+        self.fonts.push(font);
+        FontId(self.fonts.len() - 1)
+    }
+
+    /// Return the width available for text between the margins.
+    #[must_use]
+    pub fn content_width(&self) -> f32 {
+        self.width - 2.0 * self.margin
+    }
+
+    /// Start a new page, unless the current one is still empty.
+    pub fn page_break(&mut self) {
+        if !self.operations.is_empty() {
+            self.pages.push(std::mem::take(&mut self.operations));
+        }
+        self.cursor = self.height - self.margin;
+    }
+
+    /// Leave vertical space, which is dropped at the top of a page.
+    pub fn space(&mut self, height: f32) {
+        if !self.operations.is_empty() {
+            self.cursor -= height;
+        }
+    }
+
+    /// Start a new page if less than the given height is left on the current
+    /// one.
+    fn reserve(&mut self, height: f32) {
+        if self.cursor - height < self.margin {
+            self.page_break();
+        }
+    }
+
+    /// Show text at a given position.
+    fn show_text(&mut self, x: f32, y: f32, size: f32, runs: &[(FontId, String)]) {
+        self.operations.push(Operation::new("BT", vec![]));
+        self.operations
+            .push(Operation::new("Td", vec![x.into(), y.into()]));
+        for (font, text) in runs {
+            self.operations.push(Operation::new(
+                "Tf",
+                vec![Object::Name(font.name()), size.into()],
+            ));
+            self.operations
+                .push(Operation::new("Tj", vec![win_ansi_string(text)]));
+        }
+        self.operations.push(Operation::new("ET", vec![]));
+    }
+
+    /// Lay out a paragraph, wrapping its runs to the content width minus the
+    /// indentation.
+    pub fn paragraph(&mut self, runs: &[Run], style: &ParagraphStyle) {
+        let ParagraphStyle {
+            size,
+            line_height,
+            indent,
+            marker,
+            background,
+        } = *style;
+        let x = self.margin + indent;
+        let mut marker = marker;
+
+        for line in self.wrap(runs, size, self.content_width() - indent) {
+            self.reserve(line_height);
+            let baseline = self.cursor - size;
+
+            if let Some(gray) = background {
+                let width = self.content_width() - indent + 2.0 * BACKGROUND_PADDING;
+                self.operations.extend([
+                    Operation::new("q", vec![]),
+                    Operation::new("g", vec![gray.into()]),
+                    Operation::new(
+                        "re",
+                        vec![
+                            (x - BACKGROUND_PADDING).into(),
+                            (self.cursor - line_height).into(),
+                            width.into(),
+                            line_height.into(),
+                        ],
+                    ),
+                    Operation::new("f", vec![]),
+                    Operation::new("Q", vec![]),
+                ]);
+            }
+            if let Some(Run { font, text }) = marker.take() {
+                let width = self.fonts[font.0].text_width(text, size);
+                self.show_text(
+                    x - width - MARKER_GAP,
+                    baseline,
+                    size,
+                    &[(font, text.to_string())],
+                );
+            }
+            self.show_text(x, baseline, size, &line);
+            self.cursor -= line_height;
+        }
+    }
+
+    /// Lay out an image, centered and scaled down to fit in the page if
+    /// needed.
+    pub fn image(&mut self, image: ImportedImage) {
+        let max_height = self.height - 2.0 * self.margin;
+        let mut width = image.width as f32 * PIXEL_SIZE;
+        let mut height = image.height as f32 * PIXEL_SIZE;
+        let scale = (self.content_width() / width)
+            .min(max_height / height)
+            .min(1.0);
+        width *= scale;
+        height *= scale;
+
+        self.reserve(height);
+        let name = format!("Im{}", self.images.len() + 1).into_bytes();
+        self.operations.extend([
+            Operation::new("q", vec![]),
+            Operation::new(
+                "cm",
+                vec![
+                    width.into(),
+                    0.into(),
+                    0.into(),
+                    height.into(),
+                    ((self.width - width) / 2.0).into(),
+                    (self.cursor - height).into(),
+                ],
+            ),
+            Operation::new("Do", vec![Object::Name(name)]),
+            Operation::new("Q", vec![]),
+        ]);
+        self.images.push(image);
+        self.cursor -= height;
+    }
+
+    /// Lay out a horizontal rule across the content width, in the middle of
+    /// the given height.
+    pub fn rule(&mut self, height: f32) {
+        self.reserve(height);
+        let y = self.cursor - height / 2.0;
+
+        self.operations.extend([
+            Operation::new("q", vec![]),
+            Operation::new("w", vec![0.5.into()]),
+            Operation::new("G", vec![0.6.into()]),
+            Operation::new("m", vec![self.margin.into(), y.into()]),
+            Operation::new("l", vec![(self.width - self.margin).into(), y.into()]),
+            Operation::new("S", vec![]),
+            Operation::new("Q", vec![]),
+        ]);
+        self.cursor -= height;
+    }
+
+    /// Split runs into lines that fit in the given width.
+    ///
+    /// Words longer than the width are broken between characters, and spaces
+    /// at the end of lines, or at the start of wrapped lines, are dropped.
+    fn wrap(&self, runs: &[Run], size: f32, width: f32) -> Vec<Vec<(FontId, String)>> {
+        let mut lines: Vec<Vec<(FontId, String)>> = vec![vec![]];
+        let mut line_width = 0.0;
+
+        let push = |lines: &mut Vec<Vec<(FontId, String)>>, font: FontId, text: &str| {
+            let line = lines.last_mut().unwrap();
+            match line.last_mut() {
+                Some((last, last_text)) if *last == font => last_text.push_str(text),
+                _ => line.push((font, text.to_string())),
+            }
+        };
+
+        for run in runs {
+            let font = &self.fonts[run.font.0];
+
+            for word in run.text.split_inclusive(' ') {
+                let word_width = font.text_width(word.trim_end(), size);
+
+                if line_width > 0.0 && line_width + word_width > width {
+                    lines.push(vec![]);
+                    line_width = 0.0;
+                    if word.trim().is_empty() {
+                        continue;
+                    }
+                }
+
+                if word_width > width {
+                    for c in word.chars().map(String::from) {
+                        let c_width = font.text_width(&c, size);
+                        if line_width > 0.0 && line_width + c_width > width {
+                            lines.push(vec![]);
+                            line_width = 0.0;
+                        }
+                        push(&mut lines, run.font, &c);
+                        line_width += c_width;
+                    }
+                    continue;
+                }
+
+                push(&mut lines, run.font, word);
+                line_width += font.text_width(word, size);
+            }
+        }
+
+        for line in &mut lines {
+            if let Some((_, text)) = line.last_mut() {
+                text.truncate(text.trim_end().len());
+            }
+        }
+        lines
+    }
+
+    /// Finish the layout and return the generated document.
+    #[must_use]
+    pub fn finish(mut self) -> Document {
+        self.page_break();
+
+        let mut document = Document::with_version("1.7");
+        let pages_id = document.new_object_id();
+
+        let mut fonts = Dictionary::new();
+        for (i, font) in self.fonts.iter().enumerate() {
+            fonts.set(FontId(i).name(), font.add_to(&mut document));
+        }
+        let mut resources = dictionary! { "Font" => fonts };
+
+        if !self.images.is_empty() {
+            let mut xobjects = Dictionary::new();
+            for (i, image) in self.images.into_iter().enumerate() {
+                let mut stream = image.stream;
+                if let Some(mask) = image.mask {
+                    stream.dict.set("SMask", document.add_object(mask));
+                }
+                xobjects.set(format!("Im{}", i + 1), document.add_object(stream));
+            }
+            resources.set("XObject", xobjects);
+        }
+        let resources_id = document.add_object(resources);
+
+        // Documents always have at least one page, even if there is no text.
+        if self.pages.is_empty() {
+            self.pages.push(vec![]);
+        }
+        let kids: Vec<Object> = self
+            .pages
+            .into_iter()
+            .map(|operations| {
+                let content = Content { operations }.encode().unwrap_or_default();
+                let mut stream = Stream::new(Dictionary::new(), content);
+                let _ = stream.compress();
+                let content_id = document.add_object(stream);
+
+                document
+                    .add_object(dictionary! {
+                        "Type" => "Page",
+                        "Parent" => pages_id,
+                        "Contents" => content_id,
+                    })
+                    .into()
+            })
+            .collect();
+
+        document.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => kids.len() as i64,
+                "Kids" => kids,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), self.width.into(), self.height.into()],
+            }),
+        );
+        let catalog_id = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        let info_id = document.add_object(dictionary! {
+            "Producer" => Object::string_literal(concat!("rpdf ", env!("CARGO_PKG_VERSION"))),
+        });
+        document.trailer.set("Root", catalog_id);
+        document.trailer.set("Info", info_id);
+
+        document
+    }
+}
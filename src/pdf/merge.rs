@@ -0,0 +1,234 @@
+//! Merging of the annotations of several copies of a document, e.g., one per
+//! reviewer, into one of them.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Document, Object, ObjectId};
+use tracing::{debug, error_span, trace, warn};
+
+use super::{
+    annotations::{is_hidden, page_annotation_ids, read_annotation, subtype},
+    dedup::Deduplicator,
+    filter::Filter,
+    import::{self, IdMapping},
+    pdfstring, transform,
+};
+
+/// Return the annotation array of a page, creating it if needed.
+fn page_annotations_mut(document: &mut Document, page_id: ObjectId) -> Option<&mut Vec<Object>> {
+    let annots = document.get_dictionary(page_id).ok()?.get(b"Annots");
+    if let Ok(&Object::Reference(id)) = annots {
+        trace!("This page contains a reference to a vector of annotations");
+        return document
+            .get_object_mut(id)
+            .and_then(Object::as_array_mut)
+            .ok();
+    }
+    let page = document.get_dictionary_mut(page_id).ok()?;
+    if !matches!(page.get(b"Annots"), Ok(Object::Array(_))) {
+        trace!(
+            "This page (ID: {page_id:?}) does not contain any annotations, inserting an empty \
+             array."
+        );
+        page.set("Annots", Object::Array(vec![]));
+    }
+    page.get_mut(b"Annots").and_then(Object::as_array_mut).ok()
+}
+
+/// Annotations imported into a reference document, which are added to its
+/// pages once all copies are imported.
+pub struct AnnotationMerger {
+    document: Document,
+    pages: BTreeMap<u32, ObjectId>,
+    /// Imported objects are numbered after the objects of the reference
+    /// document.
+    first_imported: u32,
+    /// References to imported annotations, by page number.
+    imported: BTreeMap<u32, Vec<Object>>,
+    /// Whether annotations are moved to the same place of reference pages
+    /// that differ in size or rotation.
+    rescale: bool,
+    /// Whether hidden annotations are left out.
+    skip_hidden: bool,
+    /// Filter that imported annotations must pass, if any.
+    filter: Option<Filter>,
+    /// Shareable objects of the merged document, which identical imported
+    /// objects are replaced with.
+    deduplicator: Deduplicator,
+}
+
+impl AnnotationMerger {
+    /// Start merging annotations into a reference document.
+    #[must_use]
+    pub fn new(document: Document) -> Self {
+        let pages = document.get_pages();
+        debug!("Reference document contains {} pages", pages.len());
+        let first_imported = document.max_id + 1;
+        let deduplicator = Deduplicator::new(&document);
+
+        Self {
+            document,
+            pages,
+            first_imported,
+            imported: BTreeMap::new(),
+            rescale: false,
+            skip_hidden: false,
+            filter: None,
+            deduplicator,
+        }
+    }
+
+    /// Move imported annotations to the same place of the reference pages,
+    /// as shown, when pages differ in size or rotation, e.g., for copies that
+    /// were scanned or exported differently.
+    #[must_use]
+    pub fn rescale(mut self, rescale: bool) -> Self {
+        self.rescale = rescale;
+        self
+    }
+
+    /// Leave out imported annotations that are never shown, see
+    /// [`is_hidden`].
+    #[must_use]
+    pub fn skip_hidden(mut self, skip_hidden: bool) -> Self {
+        self.skip_hidden = skip_hidden;
+        self
+    }
+
+    /// Only import annotations that pass a filter.
+    #[must_use]
+    pub fn filter(mut self, filter: Option<Filter>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Import the annotations of a copy of the reference document, except
+    /// those whose subtype is excluded, and return their number.
+    ///
+    /// Annotations go to the page with the same number, and those of pages
+    /// that the reference document lacks are ignored. Annotations without an
+    /// author get `author`, if any. Imported objects that the merged document
+    /// already holds, e.g., fonts, are deduplicated.
+    pub fn add(
+        &mut self,
+        mut document: Document,
+        exclude: &[String],
+        author: Option<&str>,
+    ) -> usize {
+        let document_pages = document.get_pages();
+
+        // Annotations point to their page, which is mapped to the reference
+        // document's page rather than imported.
+        let page_mapping: IdMapping = document_pages
+            .iter()
+            .filter_map(|(page_number, page)| Some((*page, *self.pages.get(page_number)?)))
+            .collect();
+
+        let mut imported = vec![];
+        let mut transforms = vec![];
+        for (&page_number, &page) in &document_pages {
+            let _span = error_span!("page", number = page_number).entered();
+            if !self.pages.contains_key(&page_number) {
+                warn!(
+                    "Reference document does not contain page number {page_number}. Annotations \
+                     from this page will be ignored."
+                );
+                continue;
+            }
+            let transform = if self.rescale {
+                transform::page_to_page(&document, page, &self.document, self.pages[&page_number])
+                    .filter(|matrix| !transform::is_identity(matrix))
+            } else {
+                None
+            };
+            for id in page_annotation_ids(&document, page) {
+                let subtype = subtype(&document, id);
+                if self.skip_hidden && is_hidden(&document, id) {
+                    trace!("Skipping hidden annotation on page {page_number}");
+                } else if !exclude.iter().any(|e| subtype == e)
+                    && self.filter.as_ref().map_or(true, |filter| {
+                        read_annotation(&document, page_number, id)
+                            .is_some_and(|annotation| filter.matches(&annotation))
+                    })
+                {
+                    trace!("Found annotation on page {page_number}, importing it");
+                    imported.push((page_number, id));
+                    if let Some(matrix) = transform {
+                        transforms.push((id, matrix));
+                    }
+                }
+            }
+        }
+        if !transforms.is_empty() {
+            debug!(
+                "Rescaling {} annotations to the reference pages",
+                transforms.len()
+            );
+            transform::transform_annotations(&mut document, &transforms);
+        }
+
+        if let Some(author) = author {
+            for &(_, id) in &imported {
+                if let Ok(annotation) = document.get_dictionary_mut(id) {
+                    if !annotation.has(b"T") {
+                        annotation.set("T", pdfstring::encode_object(author));
+                    }
+                }
+            }
+        }
+
+        let roots: Vec<ObjectId> = imported.iter().map(|&(_, id)| id).collect();
+        let first = self.document.max_id + 1;
+        let mapping =
+            import::import_objects(&mut self.document, &mut document, &roots, page_mapping);
+
+        // Annotations of each copy bring their own copy of fonts and
+        // appearance streams, which are replaced as they are imported so
+        // that the merged document does not grow with the number of copies.
+        let removed = self.deduplicator.deduplicate(&mut self.document, first);
+        debug!("Removed {removed} duplicated objects of imported annotations");
+        let mut count = 0;
+        for (page_number, id) in imported {
+            if let Some(&id) = mapping.get(&id) {
+                self.imported
+                    .entry(page_number)
+                    .or_default()
+                    .push(Object::Reference(id));
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Add the imported annotations to the pages of the reference document,
+    /// and return it with the number of added annotations.
+    ///
+    /// With `compress`, the uncompressed streams of imported annotations,
+    /// e.g., appearance streams, are compressed.
+    #[must_use]
+    pub fn finish(mut self, compress: bool) -> (Document, usize) {
+        debug!("Updating the annotation arrays in reference document");
+        let mut added = 0;
+        for (page_number, mut annotations) in std::mem::take(&mut self.imported) {
+            let Some(&page_id) = self.pages.get(&page_number) else {
+                continue;
+            };
+            if let Some(current) = page_annotations_mut(&mut self.document, page_id) {
+                added += annotations.len();
+                current.append(&mut annotations);
+            }
+        }
+
+        if compress {
+            for (_, object) in self.document.objects.range_mut((self.first_imported, 0)..) {
+                if let Object::Stream(stream) = object {
+                    if stream.allows_compression {
+                        let _ = stream.compress();
+                    }
+                }
+            }
+        }
+
+        (self.document, added)
+    }
+}
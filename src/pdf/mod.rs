@@ -0,0 +1,506 @@
+//! Shared helpers to work with PDF documents.
+
+pub mod annotations;
+pub mod assemble;
+pub mod attachments;
+#[cfg(feature = "images")]
+pub mod barcodes;
+pub mod canonical;
+pub mod colors;
+pub mod content;
+pub mod dedup;
+#[cfg(feature = "images")]
+pub mod deskew;
+pub mod document;
+pub mod facturx;
+pub mod filter;
+pub mod fingerprint;
+pub mod fixtures;
+pub mod fonts;
+pub mod geo;
+pub mod heatmap;
+pub mod history;
+#[cfg(feature = "images")]
+pub mod images;
+pub mod import;
+pub mod imposition;
+pub mod info;
+pub mod ink;
+pub mod journal;
+pub mod layers;
+#[cfg(feature = "convert")]
+pub mod layout;
+pub mod merge;
+pub mod objects;
+pub mod outline;
+#[cfg(feature = "convert")]
+pub mod pdfa;
+pub mod pdfstring;
+pub mod portfolio;
+#[cfg(feature = "regex")]
+pub mod replace;
+pub mod reproducible;
+pub mod sanitize;
+pub mod selection;
+pub mod signatures;
+pub mod split;
+pub mod stamp;
+pub mod structure;
+pub mod tables;
+pub mod text;
+pub mod threads;
+pub mod threats;
+pub mod thumbs;
+pub mod transform;
+pub mod validate;
+pub mod version;
+pub mod viewer;
+pub mod xmp;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    mem,
+    path::Path,
+};
+
+use anyhow::{Context, Result, bail};
+#[cfg(not(target_arch = "wasm32"))]
+use lopdf::encryption;
+use lopdf::{Document, Object, ObjectId, ObjectStream, Reader, Stream};
+#[cfg(not(target_arch = "wasm32"))]
+use memmap2::Mmap;
+use tracing::{debug, debug_span, warn};
+
+/// Return the reference notation of an object ID, e.g., `12 0 R`.
+pub fn reference(id: ObjectId) -> String {
+    format!("{} {} R", id.0, id.1)
+}
+
+/// Load a PDF document from a file, decrypting it if needed.
+///
+/// Encrypted documents are decrypted with the given password, or with the
+/// empty password if none is given, which opens documents that only restrict
+/// permissions. Objects stored in object streams are read after decryption,
+/// as lopdf cannot read encrypted object streams.
+///
+/// Files are not available in WebAssembly, where [`load_mem`] is used instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load<P: AsRef<Path>>(path: P, password: Option<&str>) -> Result<Document> {
+    let path = path.as_ref();
+    let buffer = fs::read(path).with_context(|| format!("Failed to read PDF from: {path:?}"))?;
+    load_with(path, &buffer, password, defer_object_streams).map(|(document, _)| document)
+}
+
+/// Load a PDF document like [`load`], but without the data of image streams,
+/// which is read on demand with the returned [`DeferredImages`].
+///
+/// Images make up most of the size of scanned documents, so this greatly
+/// reduces memory usage for commands that never read them, or only a few of
+/// them. The file is mapped in memory rather than read, and the data of
+/// images is dropped as soon as each image is parsed. Image streams are kept
+/// with an empty content until they are loaded, hence documents loaded this
+/// way must never be saved.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_without_images<P: AsRef<Path>>(
+    path: P,
+    password: Option<&str>,
+) -> Result<(Document, DeferredImages)> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("Failed to read PDF from: {path:?}"))?;
+    // SAFETY: the file must not be truncated while it is mapped, which rpdf
+    // never does, as files are saved to a temporary file that is then
+    // renamed.
+    let map = unsafe { Mmap::map(&file) }
+        .with_context(|| format!("Failed to read PDF from: {path:?}"))?;
+    let (document, key) = load_with(path, &map, password, skip_image_data)?;
+
+    let mut objects = Document::new();
+    objects.reference_table = document.reference_table.clone();
+    Ok((document, DeferredImages { map, objects, key }))
+}
+
+/// Data of the image streams of a document loaded by [`load_without_images`],
+/// read from the file on demand.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DeferredImages {
+    /// File the document was read from, mapped in memory.
+    map: Mmap,
+    /// Document holding only the cross-reference table of the file, to find
+    /// objects in the file.
+    objects: Document,
+    /// Encryption key of encrypted documents, to decrypt the data of images.
+    key: Option<Vec<u8>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DeferredImages {
+    /// Read the data of an image stream into the document, if it was left
+    /// out. Other objects are left as is.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the image cannot be read from the file, or decrypted.
+    pub fn load(&mut self, document: &mut Document, id: ObjectId) -> Result<()> {
+        let Some(Object::Stream(stream)) = document.objects.get(&id) else {
+            return Ok(());
+        };
+        if !is_image(stream) || !stream.content.is_empty() {
+            return Ok(());
+        }
+        let length = stream
+            .dict
+            .get_deref(b"Length", document)
+            .and_then(Object::as_i64)
+            .ok();
+
+        let reader = Reader {
+            buffer: &self.map,
+            document: mem::take(&mut self.objects),
+        };
+        let object = reader.get_object(id, &mut HashSet::new());
+        self.objects = reader.document;
+        let mut object = object.with_context(|| format!("Failed to read image {id:?}"))?;
+
+        // lopdf leaves the data of streams whose length is stored in an
+        // object stream, which it cannot read from the file alone.
+        if let Object::Stream(image) = &mut object {
+            if let (Some(start), Some(length), true) =
+                (image.start_position, length, image.content.is_empty())
+            {
+                let data = usize::try_from(length)
+                    .ok()
+                    .and_then(|length| self.map.get(start..start.checked_add(length)?))
+                    .with_context(|| format!("Failed to read image {id:?}"))?;
+                image.set_content(data.to_vec());
+            }
+        }
+        let content = match &self.key {
+            Some(key) => {
+                encryption::decrypt_object(key, id, &object)
+                    .map_err(lopdf::Error::from)
+                    .with_context(|| format!("Failed to decrypt image {id:?}"))?
+            },
+            None => object.as_stream().map(|image| image.content.clone())?,
+        };
+        if let Ok(stream) = document.get_object_mut(id).and_then(Object::as_stream_mut) {
+            stream.set_content(content);
+        }
+        Ok(())
+    }
+}
+
+/// Function applied to each object while a document is read, as expected by
+/// [`Reader::read`].
+///
+/// Objects are modified in place and the returned object is ignored, as
+/// lopdf does for top-level objects, and as [`expand_object_streams`] does
+/// for objects of object streams. Returning `None` drops the object.
+type LoadFilter = fn(ObjectId, &mut Object) -> Option<(ObjectId, Object)>;
+
+/// Type given to object streams while a document is read, so that lopdf keeps
+/// them as regular streams.
+const DEFERRED_OBJECT_STREAM: &[u8] = b"DeferredObjStm";
+
+/// Keep object streams as regular streams, for [`expand_object_streams`].
+///
+/// lopdf reads object streams before the document is decrypted, and silently
+/// drops encrypted ones along with all the objects they hold.
+fn defer_object_streams(id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+    if let Object::Stream(stream) = object {
+        if stream.dict.type_is(b"ObjStm") {
+            stream
+                .dict
+                .set("Type", Object::Name(DEFERRED_OBJECT_STREAM.to_vec()));
+        }
+    }
+    Some((id, Object::Null))
+}
+
+/// Return whether a stream is an image.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_image(stream: &Stream) -> bool {
+    stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image")
+}
+
+/// Drop the data of an image stream.
+#[cfg(not(target_arch = "wasm32"))]
+fn skip_image_data(id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+    if let Object::Stream(stream) = object {
+        if is_image(stream) {
+            stream.content = Vec::new();
+            // Otherwise, lopdf reads empty streams again from the file.
+            stream.start_position = None;
+        }
+    }
+    defer_object_streams(id, object)
+}
+
+/// Move the objects of deferred object streams into the document, and remove
+/// the object streams.
+///
+/// Like lopdf does, objects already read from the cross-reference table are
+/// never replaced.
+fn expand_object_streams(document: &mut Document, filter: LoadFilter) {
+    let ids: Vec<ObjectId> = document
+        .objects
+        .iter()
+        .filter(|(_, object)| {
+            object
+                .as_stream()
+                .is_ok_and(|stream| stream.dict.type_is(DEFERRED_OBJECT_STREAM))
+        })
+        .map(|(&id, _)| id)
+        .collect();
+
+    for id in ids {
+        let Some(Object::Stream(mut stream)) = document.objects.remove(&id) else {
+            continue;
+        };
+        match ObjectStream::new(&mut stream) {
+            Ok(object_stream) => {
+                debug!(
+                    "Read {} objects from object stream {id:?}",
+                    object_stream.objects.len()
+                );
+                for (id, mut object) in object_stream.objects {
+                    if filter(id, &mut object).is_some() {
+                        document.objects.entry(id).or_insert(object);
+                    }
+                }
+            },
+            Err(e) => warn!("Failed to read object stream {id:?}: {e}"),
+        }
+    }
+}
+
+/// Load a PDF document from the data of a file, and return it with its
+/// encryption key if it was encrypted.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_with(
+    path: &Path,
+    buffer: &[u8],
+    password: Option<&str>,
+    filter: LoadFilter,
+) -> Result<(Document, Option<Vec<u8>>)> {
+    let _span = debug_span!("load").entered();
+    let mut document = Reader {
+        buffer,
+        document: Document::new(),
+    }
+    .read(Some(filter))
+    .with_context(|| format!("Failed to read PDF from: {path:?}"))?;
+
+    let mut key = None;
+    if document.is_encrypted() {
+        debug!("Document {path:?} is encrypted, decrypting it");
+        // Decryption removes the encryption dictionary, and fails below if
+        // the password is wrong.
+        key = encryption::get_encryption_key(&document, password.unwrap_or(""), true).ok();
+    }
+    finish_loading(&mut document, password, filter).with_context(|| {
+        format!("Failed to decrypt PDF from: {path:?}, is the password correct?")
+    })?;
+
+    Ok((document, key))
+}
+
+/// Load a PDF document from a byte buffer, like [`load`] does from a file.
+///
+/// This is how documents are loaded where there is no filesystem, e.g., in
+/// WebAssembly.
+pub fn load_mem(buffer: &[u8], password: Option<&str>) -> Result<Document> {
+    load_mem_with_encryption(buffer, password).map(|(document, _)| document)
+}
+
+/// Load a PDF document from a byte buffer like [`load_mem`], and return it
+/// with whether it was encrypted, which decrypted documents no longer tell.
+pub fn load_mem_with_encryption(buffer: &[u8], password: Option<&str>) -> Result<(Document, bool)> {
+    let _span = debug_span!("load").entered();
+    let mut document = Reader {
+        buffer,
+        document: Document::new(),
+    }
+    .read(Some(defer_object_streams))
+    .context("Failed to read PDF from memory")?;
+    let encrypted = document.is_encrypted();
+
+    finish_loading(&mut document, password, defer_object_streams)
+        .context("Failed to decrypt PDF from memory, is the password correct?")?;
+
+    Ok((document, encrypted))
+}
+
+/// Decrypt a document that was just read, if needed, and read its object
+/// streams.
+fn finish_loading(
+    document: &mut Document,
+    password: Option<&str>,
+    filter: LoadFilter,
+) -> lopdf::Result<()> {
+    if document.is_encrypted() {
+        document.decrypt(password.unwrap_or(""))?;
+    }
+    expand_object_streams(document, filter);
+    Ok(())
+}
+
+/// Trailer entries that describe the file a document was read from, rather
+/// than the document, and are wrong in any other file.
+///
+/// lopdf writes the cross-reference stream entries again when needed, but
+/// keeps stale ones, e.g., a `/Prev` offset that points to nowhere in the
+/// saved file and makes it unreadable.
+const FILE_TRAILER_KEYS: &[&[u8]] = &[
+    b"Prev",
+    b"XRefStm",
+    b"Type",
+    b"W",
+    b"Index",
+    b"Length",
+    b"Filter",
+    b"DecodeParms",
+];
+
+/// Options of [`save`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SaveOptions {
+    /// Unix timestamp used as modification date, making output reproducible
+    /// if set.
+    pub source_date: Option<u64>,
+}
+
+/// Save a PDF document to a file.
+///
+/// With a [`SaveOptions::source_date`], output only depends on the document
+/// and on that date: objects are renumbered in the order they are reached,
+/// the modification date is fixed, and the file identifier is derived from
+/// the content (see [`reproducible`]).
+///
+/// The document is written to a temporary file next to `path`, which is then
+/// renamed, so that a failed or interrupted write never leaves a truncated
+/// file at `path`, nor alters a file that was already there.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save<P: AsRef<Path>>(document: &mut Document, path: P, options: SaveOptions) -> Result<()> {
+    let path = path.as_ref();
+    let _span = debug_span!("save", path = %path.display()).entered();
+
+    if let Some(timestamp) = options.source_date {
+        debug!("Making {path:?} reproducible, with date {timestamp}");
+    }
+    prepare_save(document, options).with_context(|| format!("Failed to write PDF to: {path:?}"))?;
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".rpdf-").suffix(".tmp");
+    // Temporary files are only readable by their owner: new files get the
+    // usual permissions instead, and existing files keep theirs.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o666));
+    }
+    if let Ok(metadata) = fs::metadata(path) {
+        builder.permissions(metadata.permissions());
+    }
+
+    let mut file = builder
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to write PDF to: {path:?}"))?;
+    let mut writer = BufWriter::new(file.as_file_mut());
+    document
+        .save_to(&mut writer)
+        .and_then(|()| writer.flush())
+        .with_context(|| format!("Failed to write PDF to: {path:?}"))?;
+    drop(writer);
+    file.as_file()
+        .sync_all()
+        .with_context(|| format!("Failed to write PDF to: {path:?}"))?;
+    // The error holds the temporary file, which is removed when dropped.
+    file.persist(path)
+        .map_err(|error| error.error)
+        .with_context(|| format!("Failed to write PDF to: {path:?}"))?;
+    Ok(())
+}
+
+/// Save a PDF document to a byte buffer, like [`save`] does to a file.
+pub fn save_to_vec(document: &mut Document, options: SaveOptions) -> Result<Vec<u8>> {
+    let _span = debug_span!("save").entered();
+    prepare_save(document, options).context("Failed to write PDF to memory")?;
+    let mut buffer = Vec::new();
+    document
+        .save_to(&mut buffer)
+        .context("Failed to write PDF to memory")?;
+    Ok(buffer)
+}
+
+/// Remove the trailer entries of the file a document was read from, and make
+/// it reproducible if asked to.
+fn prepare_save(document: &mut Document, options: SaveOptions) -> Result<()> {
+    for key in FILE_TRAILER_KEYS {
+        document.trailer.remove(key);
+    }
+    if let Some(timestamp) = options.source_date {
+        reproducible::make_reproducible(document, timestamp)?;
+    }
+    Ok(())
+}
+
+/// Return the number of annotations of each page of a document, in order.
+fn annotation_counts(document: &Document) -> Vec<usize> {
+    document
+        .get_pages()
+        .into_values()
+        .map(|page_id| {
+            document
+                .get_page_annotations(page_id)
+                .map_or(0, |annotations| annotations.len())
+        })
+        .collect()
+}
+
+/// Check that a document reloaded from the file it was saved to has the same
+/// pages and annotations as the saved one, catching serialization issues.
+///
+/// # Errors
+///
+/// Fails with the first difference found.
+pub fn verify_round_trip(saved: &Document, reloaded: &Document) -> Result<()> {
+    let expected = annotation_counts(saved);
+    let actual = annotation_counts(reloaded);
+
+    if expected.len() != actual.len() {
+        bail!(
+            "The file has {} pages instead of {}.",
+            actual.len(),
+            expected.len()
+        );
+    }
+    for (page, (expected, actual)) in expected.iter().zip(&actual).enumerate() {
+        if expected != actual {
+            bail!(
+                "Page {} has {actual} annotations instead of {expected}.",
+                page + 1
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Return the decoded content of a stream.
+///
+/// Unlike [`Stream::decompressed_content`], this also decodes image streams,
+/// which lopdf refuses to decompress.
+pub fn decompress(stream: &Stream) -> lopdf::Result<Vec<u8>> {
+    if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image") {
+        let mut stream = stream.clone();
+        stream.dict.remove(b"Subtype");
+        return stream.decompressed_content();
+    }
+    stream.decompressed_content()
+}
@@ -0,0 +1,325 @@
+//! Helpers to address, describe and parse individual objects.
+
+use lopdf::{Dictionary, Object, ObjectId, StringFormat};
+
+use super::pdfstring;
+
+/// Parse an object ID, written as `12`, `12 0` or `12 0 R`.
+///
+/// # Errors
+///
+/// Fails if the numbers are missing or invalid.
+pub fn parse_object_id(s: &str) -> Result<ObjectId, String> {
+    let mut parts = s.split_whitespace();
+    let number = parts
+        .next()
+        .and_then(|number| number.parse::<u32>().ok())
+        .ok_or_else(|| format!("invalid object number in {s:?}"))?;
+    let generation = match parts.next() {
+        Some(generation) => {
+            generation
+                .parse::<u16>()
+                .map_err(|_| format!("invalid generation number in {s:?}"))?
+        },
+        None => 0,
+    };
+
+    match parts.next() {
+        None | Some("R") if parts.next().is_none() => Ok((number, generation)),
+        _ => Err(format!("expected `N`, `N G` or `N G R`, got {s:?}")),
+    }
+}
+
+/// Return the dictionary of an object, or of a stream.
+#[must_use]
+pub fn object_dictionary(object: &Object) -> Option<&Dictionary> {
+    match object {
+        Object::Dictionary(dict) => Some(dict),
+        Object::Stream(stream) => Some(&stream.dict),
+        _ => None,
+    }
+}
+
+/// Return the `/Type` and `/Subtype` names of an object, if any.
+#[must_use]
+pub fn object_types(object: &Object) -> (Option<&[u8]>, Option<&[u8]>) {
+    let Some(dict) = object_dictionary(object) else {
+        return (None, None);
+    };
+    (
+        dict.get(b"Type").and_then(Object::as_name).ok(),
+        dict.get(b"Subtype").and_then(Object::as_name).ok(),
+    )
+}
+
+/// Parser for the PDF syntax of a single object, e.g., `<< /S /URI >>`.
+struct ObjectParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+/// Return whether a byte is a PDF delimiter.
+fn is_delimiter(byte: u8) -> bool {
+    b"()<>[]{}/%".contains(&byte)
+}
+
+/// Return whether a byte ends a name, number or keyword.
+fn is_regular(byte: u8) -> bool {
+    !byte.is_ascii_whitespace() && !is_delimiter(byte)
+}
+
+impl ObjectParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Read bytes while they are regular characters.
+    fn token(&mut self) -> &[u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(is_regular) {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn object(&mut self) -> Result<Object, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => Err("unexpected end of input".to_string()),
+            Some(b'/') => {
+                self.pos += 1;
+                self.name().map(Object::Name)
+            },
+            Some(b'(') => self.literal_string(),
+            Some(b'<') if self.input.get(self.pos + 1) == Some(&b'<') => self.dictionary(),
+            Some(b'<') => self.hex_string(),
+            Some(b'[') => self.array(),
+            Some(byte) if byte.is_ascii_digit() || b"+-.".contains(&byte) => self.number(),
+            Some(_) => {
+                match self.token() {
+                    b"true" => Ok(Object::Boolean(true)),
+                    b"false" => Ok(Object::Boolean(false)),
+                    b"null" => Ok(Object::Null),
+                    token => {
+                        Err(format!(
+                            "unexpected {:?}",
+                            String::from_utf8_lossy(if token.is_empty() {
+                                &self.input[self.pos..=self.pos]
+                            } else {
+                                token
+                            })
+                        ))
+                    },
+                }
+            },
+        }
+    }
+
+    /// Parse a name, after its slash, decoding `#XX` escapes.
+    fn name(&mut self) -> Result<Vec<u8>, String> {
+        let token = self.token().to_vec();
+        let mut name = Vec::with_capacity(token.len());
+        let mut bytes = token.iter();
+
+        while let Some(&byte) = bytes.next() {
+            if byte == b'#' {
+                let hex: Vec<u8> = bytes.by_ref().take(2).copied().collect();
+                let code = std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .filter(|_| hex.len() == 2)
+                    .ok_or_else(|| "invalid `#` escape in name".to_string())?;
+                name.push(code);
+            } else {
+                name.push(byte);
+            }
+        }
+        Ok(name)
+    }
+
+    /// Parse a number, or a reference if it is followed by a generation
+    /// number and `R`.
+    fn number(&mut self) -> Result<Object, String> {
+        let token = String::from_utf8_lossy(self.token()).into_owned();
+
+        if let Ok(number) = token.parse::<i64>() {
+            let start = self.pos;
+            self.skip_whitespace();
+            let generation = String::from_utf8_lossy(self.token()).into_owned();
+            self.skip_whitespace();
+
+            if let (Ok(number), Ok(generation), b"R") = (
+                u32::try_from(number),
+                generation.parse::<u16>(),
+                self.token(),
+            ) {
+                return Ok(Object::Reference((number, generation)));
+            }
+            self.pos = start;
+            return Ok(Object::Integer(number));
+        }
+        token
+            .parse::<f32>()
+            .map(Object::Real)
+            .map_err(|_| format!("invalid number {token:?}"))
+    }
+
+    /// Parse a literal string, with its escapes and balanced parentheses.
+    ///
+    /// Non-ASCII text is encoded in UTF-16, as for text strings.
+    fn literal_string(&mut self) -> Result<Object, String> {
+        self.pos += 1;
+        let mut text = String::new();
+        let mut depth = 0;
+        let rest = std::str::from_utf8(&self.input[self.pos..])
+            .map_err(|_| "invalid UTF-8 in string".to_string())?;
+        let mut chars = rest.char_indices();
+
+        loop {
+            let Some((i, c)) = chars.next() else {
+                return Err("unterminated string".to_string());
+            };
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => {
+                    self.pos += i + 1;
+                    break;
+                },
+                ')' => depth -= 1,
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{C}',
+                        Some(c @ '0'..='7') => {
+                            let mut code = c.to_digit(8).unwrap();
+                            for _ in 0..2 {
+                                match chars.clone().next() {
+                                    Some((_, c @ '0'..='7')) => {
+                                        code = code * 8 + c.to_digit(8).unwrap();
+                                        chars.next();
+                                    },
+                                    _ => break,
+                                }
+                            }
+                            char::from_u32(code & 0xFF).unwrap()
+                        },
+                        Some(c) => c,
+                        None => return Err("unterminated string".to_string()),
+                    };
+                    text.push(escaped);
+                    continue;
+                },
+                _ => {},
+            }
+            text.push(c);
+        }
+
+        // Characters below U+0100 are kept as bytes, so that escapes write
+        // arbitrary bytes, and other text is encoded as a text string.
+        if text.chars().all(|c| u32::from(c) < 0x100) {
+            Ok(Object::string_literal(
+                text.chars().map(|c| c as u8).collect::<Vec<u8>>(),
+            ))
+        } else {
+            Ok(pdfstring::encode_object(&text))
+        }
+    }
+
+    /// Parse a hexadecimal string.
+    fn hex_string(&mut self) -> Result<Object, String> {
+        self.pos += 1;
+        let end = self.input[self.pos..]
+            .iter()
+            .position(|&byte| byte == b'>')
+            .ok_or_else(|| "unterminated hexadecimal string".to_string())?;
+        let mut digits: Vec<u8> = self.input[self.pos..self.pos + end]
+            .iter()
+            .copied()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        self.pos += end + 1;
+
+        if digits.len() % 2 == 1 {
+            digits.push(b'0');
+        }
+        let bytes = digits
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or_else(|| "invalid hexadecimal string".to_string())
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+        Ok(Object::String(bytes, StringFormat::Hexadecimal))
+    }
+
+    fn array(&mut self) -> Result<Object, String> {
+        self.pos += 1;
+        let mut array = vec![];
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                return Ok(Object::Array(array));
+            }
+            array.push(self.object()?);
+        }
+    }
+
+    fn dictionary(&mut self) -> Result<Object, String> {
+        self.pos += 2;
+        let mut dict = Dictionary::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'>') if self.input.get(self.pos + 1) == Some(&b'>') => {
+                    self.pos += 2;
+                    return Ok(Object::Dictionary(dict));
+                },
+                Some(b'/') => {
+                    self.pos += 1;
+                    let key = self.name()?;
+                    dict.set(key, self.object()?);
+                },
+                Some(_) => return Err("expected a name as dictionary key".to_string()),
+                None => return Err("unterminated dictionary".to_string()),
+            }
+        }
+    }
+}
+
+/// Parse a single object written in PDF syntax, e.g., `90`, `/Name`,
+/// `(text)`, `[0 0 612 792]`, `<< /S /URI >>` or `12 0 R`.
+///
+/// # Errors
+///
+/// Fails if the syntax is invalid, or if there is more than one object.
+pub fn parse_object(s: &str) -> Result<Object, String> {
+    let mut parser = ObjectParser {
+        input: s.as_bytes(),
+        pos: 0,
+    };
+    let object = parser.object()?;
+
+    parser.skip_whitespace();
+    if parser.pos < parser.input.len() {
+        return Err(format!(
+            "unexpected {:?} after object",
+            String::from_utf8_lossy(&parser.input[parser.pos..])
+        ));
+    }
+    Ok(object)
+}
@@ -0,0 +1,165 @@
+//! Document outline, i.e., bookmarks, and the destinations they point to.
+
+use std::collections::{BTreeMap, HashSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use super::pdfstring;
+
+/// Maximum depth of outlines and name trees, which guards against malformed
+/// documents.
+const MAX_DEPTH: usize = 64;
+
+/// Entry of the document outline.
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+    pub title: String,
+    /// Depth of the entry, top-level entries being at level 1.
+    pub level: usize,
+    /// Number of the page the entry points to, if it points to a page of the
+    /// document.
+    pub page: Option<u32>,
+}
+
+/// Look a key up in a name tree.
+fn name_tree_get<'a>(
+    document: &'a Document,
+    node: &'a Dictionary,
+    key: &[u8],
+    depth: usize,
+) -> Option<&'a Object> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    if let Ok(names) = node
+        .get_deref(b"Names", document)
+        .and_then(Object::as_array)
+    {
+        for pair in names.chunks_exact(2) {
+            if pair[0].as_str().ok() == Some(key) {
+                return document.dereference(&pair[1]).ok().map(|(_, value)| value);
+            }
+        }
+    }
+    node.get_deref(b"Kids", document)
+        .and_then(Object::as_array)
+        .ok()?
+        .iter()
+        .filter_map(|kid| document.dereference(kid).ok()?.1.as_dict().ok())
+        .find_map(|kid| name_tree_get(document, kid, key, depth + 1))
+}
+
+/// Return the explicit destination a named destination stands for.
+fn named_destination<'a>(document: &'a Document, name: &[u8]) -> Option<&'a Object> {
+    let catalog = document.catalog().ok()?;
+
+    // PDF 1.1 named destinations are names, and later ones are strings in a
+    // name tree.
+    let destination = catalog
+        .get_deref(b"Dests", document)
+        .and_then(Object::as_dict)
+        .and_then(|dests| dests.get_deref(name, document))
+        .ok()
+        .or_else(|| {
+            let tree = catalog
+                .get_deref(b"Names", document)
+                .and_then(Object::as_dict)
+                .and_then(|names| names.get_deref(b"Dests", document))
+                .and_then(Object::as_dict)
+                .ok()?;
+            name_tree_get(document, tree, name, 0)
+        })?;
+
+    // Destinations can also be wrapped in a dictionary.
+    match destination {
+        Object::Dictionary(dict) => dict.get_deref(b"D", document).ok(),
+        destination => Some(destination),
+    }
+}
+
+/// Return the number of the page a destination points to, if it points to
+/// a page of the document.
+///
+/// Destinations are either explicit, i.e., arrays starting with the page,
+/// or named.
+#[must_use]
+pub fn destination_page(
+    document: &Document,
+    destination: &Object,
+    page_numbers: &BTreeMap<ObjectId, u32>,
+) -> Option<u32> {
+    let destination = match document.dereference(destination).ok()?.1 {
+        Object::Name(name) | Object::String(name, _) => named_destination(document, name)?,
+        destination => destination,
+    };
+    let page = destination.as_array().ok()?.first()?.as_reference().ok()?;
+    page_numbers.get(&page).copied()
+}
+
+/// Return the number of the page an outline item points to, through its
+/// destination or a go-to action.
+fn item_page(
+    document: &Document,
+    item: &Dictionary,
+    page_numbers: &BTreeMap<ObjectId, u32>,
+) -> Option<u32> {
+    let destination = item.get(b"Dest").ok().or_else(|| {
+        let action = item
+            .get_deref(b"A", document)
+            .and_then(Object::as_dict)
+            .ok()?;
+        (action.get(b"S").and_then(Object::as_name).ok() == Some(b"GoTo"))
+            .then(|| action.get(b"D").ok())
+            .flatten()
+    })?;
+    destination_page(document, destination, page_numbers)
+}
+
+/// Read the bookmarks of a document, in outline order, i.e., depth-first.
+#[must_use]
+pub fn read(document: &Document) -> Vec<Bookmark> {
+    let mut bookmarks = vec![];
+    let Some(outlines) = document
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get_deref(b"Outlines", document).ok())
+        .and_then(|outlines| outlines.as_dict().ok())
+    else {
+        return bookmarks;
+    };
+    let page_numbers = document
+        .get_pages()
+        .into_iter()
+        .map(|(number, id)| (id, number))
+        .collect();
+    let mut visited = HashSet::new();
+    // Stack of the next item to read at each level.
+    let mut stack = vec![outlines.get(b"First").and_then(Object::as_reference).ok()];
+
+    while let Some(next) = stack.last_mut() {
+        let Some(id) = next.take() else {
+            stack.pop();
+            continue;
+        };
+        let Ok(item) = document.get_dictionary(id) else {
+            continue;
+        };
+        if !visited.insert(id) {
+            continue;
+        }
+        *next = item.get(b"Next").and_then(Object::as_reference).ok();
+
+        bookmarks.push(Bookmark {
+            title: item
+                .get_deref(b"Title", document)
+                .map(|title| pdfstring::decode(title.as_str().unwrap_or_default()))
+                .unwrap_or_default(),
+            level: stack.len(),
+            page: item_page(document, item, &page_numbers),
+        });
+        if stack.len() < MAX_DEPTH {
+            stack.push(item.get(b"First").and_then(Object::as_reference).ok());
+        }
+    }
+    bookmarks
+}
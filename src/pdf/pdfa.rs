@@ -0,0 +1,304 @@
+//! Best-effort conversion of documents to PDF/A, for long-term archiving.
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use super::{
+    colors::{self, Family},
+    pdfstring, reproducible, sanitize,
+    version::{self, Version},
+    xmp,
+};
+
+/// Annotation flags that hide annotations, i.e., `Invisible`, `Hidden`,
+/// `NoView` and `ToggleNoView`, which PDF/A forbids.
+const HIDDEN_FLAGS: i64 = 1 | 2 | 32 | 256;
+
+/// Annotation flag telling that annotations are printed, which PDF/A
+/// requires.
+const PRINT_FLAG: i64 = 4;
+
+/// Conformance level of PDF/A.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Level {
+    /// PDF/A-1b, based on PDF 1.4.
+    A1b,
+    /// PDF/A-2b, based on PDF 1.7.
+    A2b,
+    /// PDF/A-3b, like PDF/A-2b, but allowing any embedded file.
+    A3b,
+}
+
+impl Level {
+    /// Return the part of the PDF/A standard.
+    #[must_use]
+    pub fn part(self) -> u8 {
+        match self {
+            Self::A1b => 1,
+            Self::A2b => 2,
+            Self::A3b => 3,
+        }
+    }
+}
+
+/// Outcome of a conversion to PDF/A.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Active content that was removed.
+    pub removed: sanitize::Report,
+    /// Number of annotations that were made visible and printable.
+    pub annotations: usize,
+    /// Fonts that are not embedded, and their names, which PDF/A forbids.
+    pub unembedded_fonts: Vec<(ObjectId, String)>,
+}
+
+/// Return whether a font dictionary, or its descendant font, has an embedded
+/// font program.
+fn is_embedded(document: &Document, font: &Dictionary) -> bool {
+    match font.get(b"Subtype").and_then(Object::as_name) {
+        // Glyphs of Type 3 fonts are content streams of the document.
+        Ok(b"Type3") => return true,
+        Ok(b"Type0") => {
+            return font
+                .get_deref(b"DescendantFonts", document)
+                .and_then(Object::as_array)
+                .ok()
+                .and_then(|fonts| fonts.first())
+                .and_then(|descendant| document.dereference(descendant).ok())
+                .and_then(|(_, descendant)| descendant.as_dict().ok())
+                .is_some_and(|descendant| is_embedded(document, descendant));
+        },
+        _ => {},
+    }
+    font.get_deref(b"FontDescriptor", document)
+        .and_then(Object::as_dict)
+        .is_ok_and(|descriptor| {
+            [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"]
+                .iter()
+                .any(|key| descriptor.has(key))
+        })
+}
+
+/// Return the fonts of a document that are not embedded, with their names.
+#[must_use]
+pub fn unembedded_fonts(document: &Document) -> Vec<(ObjectId, String)> {
+    document
+        .objects
+        .iter()
+        .filter_map(|(&id, object)| Some((id, object.as_dict().ok()?)))
+        .filter(|(_, dict)| dict.type_is(b"Font"))
+        // Descendant fonts are checked with their parent font.
+        .filter(|(_, dict)| {
+            !matches!(
+                dict.get(b"Subtype").and_then(Object::as_name),
+                Ok(b"CIDFontType0" | b"CIDFontType2")
+            )
+        })
+        .filter(|(_, dict)| !is_embedded(document, dict))
+        .map(|(id, dict)| {
+            let name = dict
+                .get(b"BaseFont")
+                .and_then(Object::as_name)
+                .map(|name| String::from_utf8_lossy(name).into_owned())
+                .unwrap_or_default();
+            (id, name)
+        })
+        .collect()
+}
+
+/// Make annotations visible and printable, except pop-ups, which are
+/// displayed by their parent annotation.
+fn fix_annotation_flags(document: &mut Document) -> usize {
+    let mut count = 0;
+
+    for object in document.objects.values_mut() {
+        let Ok(dict) = object.as_dict_mut() else {
+            continue;
+        };
+        let is_annotation = dict.has(b"Rect")
+            && dict.has(b"Subtype")
+            && (dict.type_is(b"Annot") || !dict.has(b"Type"));
+        if !is_annotation {
+            continue;
+        }
+        if dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Popup") {
+            continue;
+        }
+        let flags = dict.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+        let fixed = (flags & !HIDDEN_FLAGS) | PRINT_FLAG;
+
+        if fixed != flags {
+            dict.set("F", fixed);
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Convert a PDF date, e.g., `D:20240131120000+01'00'`, to an XMP date, e.g.,
+/// `2024-01-31T12:00:00+01:00`.
+fn xmp_date(date: &str) -> Option<String> {
+    let date = date.strip_prefix("D:").unwrap_or(date);
+    let digits = |range: std::ops::Range<usize>| {
+        date.get(range)
+            .filter(|part| part.bytes().all(|b| b.is_ascii_digit()))
+    };
+    let year = digits(0..4)?;
+    let mut xmp = year.to_string();
+
+    for (range, separator) in [(4..6, '-'), (6..8, '-'), (8..10, 'T'), (10..12, ':')] {
+        match digits(range) {
+            Some(part) => {
+                xmp.push(separator);
+                xmp.push_str(part);
+            },
+            None if separator == ':' => return None,
+            None => return Some(xmp),
+        }
+    }
+    xmp.push(':');
+    xmp.push_str(digits(12..14).unwrap_or("00"));
+
+    let offset = date.get(14..).unwrap_or_default();
+    match offset.chars().next() {
+        Some('Z') | None => xmp.push('Z'),
+        Some(sign @ ('+' | '-')) => {
+            let hours = offset.get(1..3)?;
+            let minutes = offset.get(4..6).unwrap_or("00");
+            xmp.push_str(&format!("{sign}{hours}:{minutes}"));
+        },
+        _ => return None,
+    }
+    Some(xmp)
+}
+
+/// Escape text for XML.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Write the XMP metadata stream of the catalog, with the PDF/A
+/// identification and the entries of the document information dictionary.
+fn set_xmp_metadata(document: &mut Document, level: Level) -> lopdf::Result<()> {
+    let info = document
+        .trailer
+        .get_deref(b"Info", document)
+        .and_then(Object::as_dict)
+        .ok();
+    let entry = |key: &[u8]| {
+        info.and_then(|info| info.get_deref(key, document).ok())
+            .and_then(|value| value.as_str().ok())
+            .map(pdfstring::decode)
+            .filter(|value| !value.is_empty())
+    };
+
+    let mut properties = String::new();
+    let mut property = |name: &str, value: String| {
+        properties.push_str(&format!("   <{name}>{value}</{name}>\n"));
+    };
+    let alternative = |value: &str| {
+        format!(
+            "<rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt>",
+            escape_xml(value)
+        )
+    };
+
+    property("pdfaid:part", level.part().to_string());
+    property("pdfaid:conformance", "B".to_string());
+    if let Some(title) = entry(b"Title") {
+        property("dc:title", alternative(&title));
+    }
+    if let Some(author) = entry(b"Author") {
+        property(
+            "dc:creator",
+            format!(
+                "<rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq>",
+                escape_xml(&author)
+            ),
+        );
+    }
+    if let Some(subject) = entry(b"Subject") {
+        property("dc:description", alternative(&subject));
+    }
+    if let Some(keywords) = entry(b"Keywords") {
+        property("pdf:Keywords", escape_xml(&keywords));
+    }
+    if let Some(producer) = entry(b"Producer") {
+        property("pdf:Producer", escape_xml(&producer));
+    }
+    if let Some(creator) = entry(b"Creator") {
+        property("xmp:CreatorTool", escape_xml(&creator));
+    }
+    for (key, name) in [
+        (b"CreationDate".as_slice(), "xmp:CreateDate"),
+        (b"ModDate", "xmp:ModifyDate"),
+    ] {
+        if let Some(date) = entry(key).as_deref().and_then(xmp_date) {
+            property(name, date);
+        }
+    }
+
+    xmp::set_metadata(
+        document,
+        xmp::packet(&format!(
+            "  <rdf:Description rdf:about=\"\" \
+             xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\" \
+             xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+             xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\" \
+             xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\">\n\
+             {properties}  </rdf:Description>\n"
+        )),
+    )
+}
+
+/// Convert a document to PDF/A, as far as it can be done mechanically.
+///
+/// Active content is removed (keeping embedded files for PDF/A-3), the sRGB
+/// profile, or the given one, is embedded as the `GTS_PDFA1` output intent,
+/// annotations are made printable, and XMP metadata identifying the level
+/// is written, with the given modification date. Documents are decrypted
+/// when loaded, and saved without encryption.
+///
+/// Non-embedded fonts cannot be fixed here, and are listed in the report,
+/// and neither are transparency in PDF/A-1 nor device colors other than RGB.
+pub fn convert(
+    document: &mut Document,
+    level: Level,
+    profile: Option<Vec<u8>>,
+    date: &str,
+) -> lopdf::Result<Report> {
+    document.trailer.remove(b"Encrypt");
+    let removed = sanitize::sanitize_with(document, level == Level::A3b);
+    let annotations = fix_annotation_flags(document);
+
+    let profile = profile.unwrap_or_else(colors::srgb_profile);
+    let family = Family::of_icc_profile(&profile).unwrap_or(Family::Rgb);
+    let identifier =
+        colors::icc_description(&profile).unwrap_or_else(|| colors::SRGB_DESCRIPTION.to_string());
+    colors::set_output_intent(document, "GTS_PDFA1", &identifier, profile, family)?;
+
+    reproducible::set_modification_date(document, date);
+    set_xmp_metadata(document, level)?;
+
+    if level == Level::A1b {
+        // PDF/A-1 forbids cross-reference and object streams.
+        version::convert(document, Version::new(1, 4));
+    } else if document
+        .version
+        .parse::<Version>()
+        .is_ok_and(|version| version > Version::new(1, 7))
+    {
+        document.version = "1.7".to_string();
+    }
+    if !document.trailer.has(b"ID") {
+        reproducible::set_identifier(document)?;
+    }
+
+    Ok(Report {
+        removed,
+        annotations,
+        unembedded_fonts: unembedded_fonts(document),
+    })
+}
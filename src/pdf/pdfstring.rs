@@ -0,0 +1,116 @@
+//! Encoding and decoding of PDF text strings, e.g., annotation contents and
+//! authors, bookmark titles or document metadata.
+//!
+//! Text strings are either UTF-16BE or UTF-8 encoded, prefixed with a byte
+//! order mark, or PDFDocEncoding encoded, a single-byte encoding that mostly
+//! matches Latin-1.
+
+use lopdf::Object;
+
+/// Byte order mark of UTF-16BE text strings.
+const UTF16_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Byte order mark of UTF-8 text strings, only allowed since PDF 2.0.
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Characters of PDFDocEncoding bytes `0x18` to `0x1F`, i.e., diacritics.
+const PDF_DOC_DIACRITICS: [char; 8] = [
+    '\u{02D8}', '\u{02C7}', '\u{02C6}', '\u{02D9}', '\u{02DD}', '\u{02DB}', '\u{02DA}', '\u{02DC}',
+];
+
+/// Characters of PDFDocEncoding bytes `0x80` to `0xA0`, where it differs
+/// from Latin-1. Byte `0x9F` is undefined.
+#[rustfmt::skip]
+const PDF_DOC_PUNCTUATION: [char; 33] = [
+    '\u{2022}', '\u{2020}', '\u{2021}', '\u{2026}', '\u{2014}', '\u{2013}', '\u{0192}', '\u{2044}',
+    '\u{2039}', '\u{203A}', '\u{2212}', '\u{2030}', '\u{201E}', '\u{201C}', '\u{201D}', '\u{2018}',
+    '\u{2019}', '\u{201A}', '\u{2122}', '\u{FB01}', '\u{FB02}', '\u{0141}', '\u{0152}', '\u{0160}',
+    '\u{0178}', '\u{017D}', '\u{0131}', '\u{0142}', '\u{0153}', '\u{0161}', '\u{017E}',
+    char::REPLACEMENT_CHARACTER, '\u{20AC}',
+];
+
+/// Decode a PDFDocEncoding byte, undefined bytes being replaced with
+/// U+FFFD.
+fn decode_pdf_doc(byte: u8) -> char {
+    match byte {
+        0x18..=0x1F => PDF_DOC_DIACRITICS[usize::from(byte - 0x18)],
+        0x80..=0xA0 => PDF_DOC_PUNCTUATION[usize::from(byte - 0x80)],
+        0x7F | 0xAD => char::REPLACEMENT_CHARACTER,
+        _ => char::from(byte),
+    }
+}
+
+/// Encode a character in PDFDocEncoding, if it is part of it.
+fn encode_pdf_doc(c: char) -> Option<u8> {
+    match u32::from(c) {
+        code @ (0x09 | 0x0A | 0x0D | 0x20..=0x7E | 0xA1..=0xAC | 0xAE..=0xFF) => {
+            u8::try_from(code).ok()
+        },
+        _ if c == char::REPLACEMENT_CHARACTER => None,
+        _ => {
+            let position = |table: &[char]| table.iter().position(|&other| other == c);
+            position(&PDF_DOC_DIACRITICS)
+                .map(|i| 0x18 + i as u8)
+                .or_else(|| position(&PDF_DOC_PUNCTUATION).map(|i| 0x80 + i as u8))
+        },
+    }
+}
+
+/// Decode the bytes of a text string, lossily.
+pub fn decode(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(UTF16_BOM) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        String::from_utf8_lossy(rest).into_owned()
+    } else {
+        bytes.iter().copied().map(decode_pdf_doc).collect()
+    }
+}
+
+/// Decode a string object as a text string, lossily.
+pub fn decode_object(object: &Object) -> lopdf::Result<String> {
+    object.as_str().map(decode)
+}
+
+/// Encode text as the bytes of a text string.
+///
+/// PDFDocEncoding is used if it can represent the text, which is the most
+/// compatible with old readers, and UTF-16BE otherwise, e.g., for Japanese.
+pub fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(encode_pdf_doc)
+        .collect::<Option<Vec<u8>>>()
+        .unwrap_or_else(|| {
+            UTF16_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect()
+        })
+}
+
+/// Return a string object holding text, see [`encode`].
+pub fn encode_object(text: &str) -> Object {
+    Object::string_literal(encode(text))
+}
+
+/// Format a PDF date as a readable date, e.g., `2024-01-31 12:00`.
+#[must_use]
+pub fn display_date(date: &str) -> String {
+    let digits = date.strip_prefix("D:").unwrap_or(date);
+
+    match (digits.get(0..4), digits.get(4..6), digits.get(6..8)) {
+        (Some(year), Some(month), Some(day)) => {
+            let time = match (digits.get(8..10), digits.get(10..12)) {
+                (Some(hour), Some(minute)) => format!(" {hour}:{minute}"),
+                _ => String::new(),
+            };
+            format!("{year}-{month}-{day}{time}")
+        },
+        _ => date.to_string(),
+    }
+}
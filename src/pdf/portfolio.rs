@@ -0,0 +1,193 @@
+//! Portfolios, i.e., PDF collections, which present their embedded files
+//! rather than their pages.
+
+use lopdf::{
+    Document, Object, Stream,
+    content::{Content, Operation},
+    dictionary,
+};
+
+use super::{
+    attachments::{self, NewAttachment},
+    pdfstring,
+};
+
+/// Column of the list of files of a portfolio.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Field {
+    /// Name of the file.
+    Name,
+    /// Modification date of the file.
+    Date,
+    /// Size of the file.
+    Size,
+}
+
+impl Field {
+    /// Return the key of the field in the collection schema.
+    fn key(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Date => "date",
+            Self::Size => "size",
+        }
+    }
+
+    /// Return the subtype of the field, i.e., the entry of file
+    /// specifications it shows.
+    fn subtype(self) -> &'static str {
+        match self {
+            Self::Name => "F",
+            Self::Date => "ModDate",
+            Self::Size => "Size",
+        }
+    }
+
+    /// Return the name of the column.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Date => "Modified",
+            Self::Size => "Size",
+        }
+    }
+}
+
+/// Return a document with a single page, shown by viewers that do not
+/// support portfolios.
+#[must_use]
+pub fn cover(file_count: usize) -> Document {
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+    let text = format!(
+        "This portfolio holds {file_count} files. Open it with a viewer that supports PDF \
+         portfolios, or extract them."
+    );
+    let content = Content {
+        operations: vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 12.into()]),
+            Operation::new("Td", vec![72.into(), 760.into()]),
+            Operation::new("Tj", vec![Object::string_literal(text)]),
+            Operation::new("ET", vec![]),
+        ],
+    };
+    let content_id = document.add_object(Stream::new(
+        dictionary! {},
+        content.encode().unwrap_or_default(),
+    ));
+    let page_id = document.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        "Contents" => content_id,
+        "Resources" => dictionary! {
+            "Font" => dictionary! {
+                "F1" => dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                },
+            },
+        },
+    });
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+    document
+}
+
+/// Make a document a portfolio, embedding the given files and listing them
+/// with the given fields.
+///
+/// Files are given as names, contents and modification dates.
+pub fn create(
+    document: &mut Document,
+    files: Vec<(String, Vec<u8>, Option<String>)>,
+    fields: &[Field],
+) -> lopdf::Result<()> {
+    for (name, data, date) in files {
+        attachments::attach(
+            document,
+            data,
+            &NewAttachment {
+                name: &name,
+                mime_type: Some(mime_type(&name)),
+                date: date.as_deref(),
+                ..Default::default()
+            },
+        )?;
+    }
+
+    let mut schema = dictionary! { "Type" => "CollectionSchema" };
+    for (order, field) in fields.iter().enumerate() {
+        schema.set(
+            field.key(),
+            dictionary! {
+                "Type" => "CollectionField",
+                "Subtype" => field.subtype(),
+                "N" => pdfstring::encode_object(field.label()),
+                "O" => order as i64,
+            },
+        );
+    }
+    let mut collection = dictionary! {
+        "Type" => "Collection",
+        "Schema" => schema,
+        // Details view, i.e., files are listed with the fields of the schema.
+        "View" => "D",
+    };
+    if let Some(field) = fields.first() {
+        collection.set("Sort", dictionary! { "S" => field.key(), "A" => true });
+    }
+    document.catalog_mut()?.set("Collection", collection);
+
+    if document.version.as_str() < "1.7" {
+        document.version = "1.7".to_string();
+    }
+    Ok(())
+}
+
+/// Return whether a document is a portfolio.
+#[must_use]
+pub fn is_portfolio(document: &Document) -> bool {
+    document
+        .catalog()
+        .is_ok_and(|catalog| catalog.has(b"Collection"))
+}
+
+/// Return the MIME type of a file, guessed from its extension.
+fn mime_type(name: &str) -> &'static str {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "xml" => "text/xml",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "zip" => "application/zip",
+        "doc" => "application/msword",
+        "xls" => "application/vnd.ms-excel",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        _ => "application/octet-stream",
+    }
+}
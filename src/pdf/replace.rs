@@ -0,0 +1,368 @@
+//! Replacement of text in content streams, e.g., to rebrand documents.
+//!
+//! Text is matched within each text-showing operator, decoded with the font
+//! it is shown with, and the replacement is encoded back with the same font.
+//! Text split across operators, e.g., by a change of font or position, is
+//! never matched, and the replacement is not reflowed: following text on the
+//! same line keeps its position.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    ops::Range,
+    rc::Rc,
+};
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream, StringFormat,
+    content::{Content, Operation},
+};
+use regex::{Captures, Regex};
+use tracing::{debug, error_span};
+
+use super::{
+    content::number,
+    document::DocumentExt,
+    text::{Font, SPACE_ADJUSTMENT},
+};
+
+/// Match whose replacement cannot be shown with the font of the text.
+#[derive(Clone, Debug)]
+pub struct Failure {
+    /// Page number, or `None` for text in form XObjects, e.g., shared
+    /// headers.
+    pub page: Option<u32>,
+    /// Name of the font.
+    pub font: String,
+    /// Text that the operator would show after replacement.
+    pub text: String,
+}
+
+/// Outcome of [`replace`].
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// Replaced matches.
+    pub replaced: usize,
+    /// Matches that were left unchanged.
+    pub failures: Vec<Failure>,
+}
+
+/// Rewriter of the text-showing operators of content streams.
+struct Rewriter<'a> {
+    document: &'a Document,
+    pattern: &'a Regex,
+    replacement: &'a str,
+    fonts: HashMap<ObjectId, Rc<Font>>,
+}
+
+impl Rewriter<'_> {
+    /// Return the font of the given resource name, reading it on first use.
+    fn font(&mut self, resources: Option<&Dictionary>, name: &[u8]) -> Option<Rc<Font>> {
+        let object = resources?
+            .get_deref(b"Font", self.document)
+            .and_then(Object::as_dict)
+            .and_then(|fonts| fonts.get(name))
+            .ok()?;
+
+        match object {
+            Object::Reference(id) => {
+                if let Some(font) = self.fonts.get(id) {
+                    return Some(font.clone());
+                }
+                let font = Rc::new(Font::new(
+                    self.document,
+                    self.document.get_dictionary(*id).ok()?,
+                ));
+                self.fonts.insert(*id, font.clone());
+                Some(font)
+            },
+            Object::Dictionary(dictionary) => Some(Rc::new(Font::new(self.document, dictionary))),
+            _ => None,
+        }
+    }
+
+    /// Replace matches in the text shown by operations, and return the
+    /// number of replaced matches.
+    fn run(
+        &mut self,
+        operations: &mut [Operation],
+        resources: Option<&Dictionary>,
+        page: Option<u32>,
+        report: &mut Report,
+    ) -> usize {
+        let mut font: Option<Rc<Font>> = None;
+        let mut stack = vec![];
+        let mut replaced = 0;
+
+        for operation in operations {
+            match operation.operator.as_str() {
+                "q" => stack.push(font.clone()),
+                "Q" => font = stack.pop().flatten(),
+                "Tf" => {
+                    font = operation
+                        .operands
+                        .first()
+                        .and_then(|name| name.as_name().ok())
+                        .and_then(|name| self.font(resources, name));
+                },
+                "Tj" | "'" | "\"" => {
+                    let Some(font) = &font else {
+                        continue;
+                    };
+                    let Some(Object::String(bytes, format)) = operation.operands.last() else {
+                        continue;
+                    };
+                    let text = decode(font, bytes);
+                    let matches = self.pattern.find_iter(&text).count();
+                    if matches == 0 {
+                        continue;
+                    }
+                    let new_text = self.pattern.replace_all(&text, self.replacement);
+                    let Some(bytes) = font.encode(&new_text) else {
+                        report.failures.push(Failure {
+                            page,
+                            font: font.name.clone(),
+                            text: new_text.into_owned(),
+                        });
+                        continue;
+                    };
+                    let format = *format;
+                    if let Some(last) = operation.operands.last_mut() {
+                        *last = Object::String(bytes, format);
+                    }
+                    replaced += matches;
+                },
+                "TJ" => {
+                    let Some(font) = &font else {
+                        continue;
+                    };
+                    let Some(Object::Array(items)) = operation.operands.first() else {
+                        continue;
+                    };
+                    let (text, ranges) = shown_items(font, items);
+                    if !self.pattern.is_match(&text) {
+                        continue;
+                    }
+                    match self.replace_items(font, items, &text, &ranges) {
+                        Some((new_items, matches)) => {
+                            operation.operands = vec![Object::Array(new_items)];
+                            replaced += matches;
+                        },
+                        None => {
+                            report.failures.push(Failure {
+                                page,
+                                font: font.name.clone(),
+                                text: self
+                                    .pattern
+                                    .replace_all(&text, self.replacement)
+                                    .into_owned(),
+                            });
+                        },
+                    }
+                },
+                _ => {},
+            }
+        }
+        report.replaced += replaced;
+        replaced
+    }
+
+    /// Replace matches in the items of a `TJ` array, given the text they show
+    /// and the range of the text of each item, and return the new items with
+    /// the number of replaced matches, or `None` if the font cannot show a
+    /// replacement.
+    ///
+    /// Items that no match overlaps are kept as is, with their adjustments.
+    /// The items that a match overlaps are replaced with a single string,
+    /// where spaces shown by adjustments become actual spaces, and kerning is
+    /// lost with the text it applied to.
+    fn replace_items(
+        &self,
+        font: &Font,
+        items: &[Object],
+        text: &str,
+        ranges: &[Range<usize>],
+    ) -> Option<(Vec<Object>, usize)> {
+        // Runs of items that matches overlap, with the matches.
+        let mut runs: Vec<(Range<usize>, Vec<Captures>)> = vec![];
+        for captures in self.pattern.captures_iter(text) {
+            let found = captures.get(0)?;
+            // Empty matches belong to the item whose text they are in.
+            let (start, end) = (found.start(), found.end().max(found.start() + 1));
+            let mut overlapped = ranges.iter().enumerate().filter(|(_, range)| {
+                if range.is_empty() {
+                    start < range.start && range.start < end
+                } else {
+                    range.start < end && start < range.end
+                }
+            });
+            let Some((first, _)) = overlapped.next() else {
+                continue;
+            };
+            let last = overlapped.next_back().map_or(first, |(last, _)| last);
+
+            match runs.last_mut() {
+                Some((run, matches)) if first < run.end => {
+                    run.end = run.end.max(last + 1);
+                    matches.push(captures);
+                },
+                _ => runs.push((first..last + 1, vec![captures])),
+            }
+        }
+
+        let mut new_items = vec![];
+        let mut next = 0;
+        let mut replaced = 0;
+        for (run, matches) in runs {
+            new_items.extend_from_slice(&items[next..run.start]);
+            let mut position = ranges[run.start].start;
+            let mut new_text = String::new();
+            for captures in &matches {
+                let found = captures.get(0)?;
+                new_text.push_str(&text[position..found.start()]);
+                captures.expand(self.replacement, &mut new_text);
+                position = found.end();
+            }
+            new_text.push_str(&text[position..ranges[run.end - 1].end]);
+
+            let format = items[run.clone()]
+                .iter()
+                .find_map(|item| {
+                    match item {
+                        Object::String(_, format) => Some(*format),
+                        _ => None,
+                    }
+                })
+                .unwrap_or(StringFormat::Literal);
+            new_items.push(Object::String(font.encode(&new_text)?, format));
+            replaced += matches.len();
+            next = run.end;
+        }
+        new_items.extend_from_slice(&items[next..]);
+        Some((new_items, replaced))
+    }
+}
+
+/// Return the text of a string shown with a font.
+fn decode(font: &Font, bytes: &[u8]) -> String {
+    font.codes(bytes).map(|code| font.text(code)).collect()
+}
+
+/// Return the text shown by the items of a `TJ` array, and the range of the
+/// text of each item.
+///
+/// Like text extraction does, a space is inserted where an adjustment moves
+/// the next string far enough, and other adjustments, i.e., kerning, show no
+/// text.
+fn shown_items(font: &Font, items: &[Object]) -> (String, Vec<Range<usize>>) {
+    let mut text = String::new();
+    let mut ranges = Vec::with_capacity(items.len());
+
+    for item in items {
+        let start = text.len();
+        match item {
+            Object::String(bytes, _) => text.push_str(&decode(font, bytes)),
+            item => {
+                if number(item).is_some_and(|adjustment| -adjustment >= SPACE_ADJUSTMENT)
+                    && !text.is_empty()
+                    && !text.ends_with(' ')
+                {
+                    text.push(' ');
+                }
+            },
+        }
+        ranges.push(start..text.len());
+    }
+    (text, ranges)
+}
+
+/// Replace the matches of a pattern in the text of a document, e.g.,
+/// `ACME Corp` with `NewCo Ltd`.
+///
+/// The replacement may refer to groups of the pattern, e.g., `$1`. Text of
+/// pages and form XObjects is replaced, and matches whose replacement has
+/// characters missing from the font are left unchanged and reported as
+/// failures.
+pub fn replace(document: &mut Document, pattern: &Regex, replacement: &str) -> Report {
+    let mut report = Report::default();
+    let mut updates = vec![];
+    let mut rewriter = Rewriter {
+        document,
+        pattern,
+        replacement,
+        fonts: HashMap::new(),
+    };
+
+    for (page, page_id) in document.get_pages() {
+        let _span = error_span!("page", number = page).entered();
+        let Ok(mut content) = document.get_and_decode_page_content(page_id) else {
+            debug!("Failed to decode content of page {page}");
+            continue;
+        };
+        let replaced = rewriter.run(
+            &mut content.operations,
+            document.page_resources(page_id),
+            Some(page),
+            &mut report,
+        );
+        if replaced > 0 {
+            if let Ok(data) = content.encode() {
+                updates.push((page_id, data));
+            }
+        }
+    }
+
+    let mut forms = vec![];
+    for (&id, object) in &document.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Form") {
+            continue;
+        }
+        let Ok(mut content) = stream
+            .decompressed_content()
+            .or_else(|_| Ok::<_, lopdf::Error>(stream.content.clone()))
+            .and_then(|data| Content::decode(&data))
+        else {
+            debug!("Failed to decode content of stream {id:?}");
+            continue;
+        };
+        let resources = stream
+            .dict
+            .get_deref(b"Resources", document)
+            .and_then(Object::as_dict)
+            .ok();
+        let replaced = rewriter.run(&mut content.operations, resources, None, &mut report);
+        if let (true, Ok(data)) = (replaced > 0, content.encode()) {
+            let mut form = stream.clone();
+            form.set_plain_content(data);
+            let _ = form.compress();
+            forms.push((id, Object::Stream(form)));
+        }
+    }
+
+    let mut replaced_streams = BTreeSet::new();
+    for (page_id, data) in updates {
+        replaced_streams.extend(document.get_page_contents(page_id));
+        let mut stream = Stream::new(Dictionary::new(), data);
+        let _ = stream.compress();
+        let content_id = document.add_object(stream);
+        if let Ok(page) = document.get_dictionary_mut(page_id) {
+            page.set("Contents", content_id);
+        }
+    }
+    for (id, form) in forms {
+        document.objects.insert(id, form);
+    }
+    // Replaced page content streams are removed, unless other pages still
+    // draw them.
+    for page_id in document.page_iter() {
+        for id in document.get_page_contents(page_id) {
+            replaced_streams.remove(&id);
+        }
+    }
+    for id in replaced_streams {
+        document.objects.remove(&id);
+    }
+    report
+}
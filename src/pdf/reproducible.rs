@@ -0,0 +1,144 @@
+//! Reproducible output, so that saving the same document twice gives the
+//! same bytes.
+
+use std::collections::VecDeque;
+
+use lopdf::{Document, Object, StringFormat};
+
+use super::{
+    import::{IdMapping, collect_references, remap_references},
+    info,
+};
+
+/// Format a Unix timestamp as a PDF date, in UTC.
+pub fn format_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days(timestamp / 86_400);
+    let seconds = timestamp % 86_400;
+
+    format!(
+        "D:{year:04}{month:02}{day:02}{:02}{:02}{:02}Z",
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+/// Convert a number of days since 1970-01-01 to a `(year, month, day)` date
+/// of the proleptic Gregorian calendar.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = era * 400 + yoe + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Set the modification date of the document information dictionary,
+/// creating the dictionary if needed.
+pub fn set_modification_date(document: &mut Document, date: &str) {
+    info::set(document, "ModDate", Object::string_literal(date));
+}
+
+/// Renumber objects in the order they are reached from the trailer,
+/// breadth-first, dropping unreachable objects.
+///
+/// Object numbers then only depend on the structure of the document, and
+/// not on the file it was read from or on the order objects were added in.
+pub fn renumber_objects(document: &mut Document) {
+    let mut queue = VecDeque::new();
+    let mut references = vec![];
+    let mut mapping = IdMapping::new();
+    let mut order = vec![];
+
+    for (_, value) in document.trailer.iter() {
+        collect_references(value, &mut references);
+    }
+    queue.extend(references.drain(..));
+
+    while let Some(id) = queue.pop_front() {
+        if mapping.contains_key(&id) {
+            continue;
+        }
+        let Some(object) = document.objects.get(&id) else {
+            continue;
+        };
+        collect_references(object, &mut references);
+        queue.extend(references.drain(..));
+
+        let number = u32::try_from(order.len() + 1).unwrap_or(u32::MAX);
+        mapping.insert(id, (number, 0));
+        order.push(id);
+    }
+
+    let mut objects = std::mem::take(&mut document.objects);
+
+    for id in order {
+        if let Some(mut object) = objects.remove(&id) {
+            remap_references(&mut object, &mapping);
+            document.objects.insert(mapping[&id], object);
+        }
+    }
+    let mut trailer = Object::Dictionary(std::mem::take(&mut document.trailer));
+    remap_references(&mut trailer, &mapping);
+    if let Object::Dictionary(trailer) = trailer {
+        document.trailer = trailer;
+    }
+    document.max_id = u32::try_from(mapping.len()).unwrap_or(u32::MAX);
+}
+
+/// Return the 128-bit FNV-1a hash of some bytes.
+///
+/// Unlike [`std::hash::DefaultHasher`], its output is guaranteed not to
+/// change across Rust versions.
+#[must_use]
+pub fn fnv1a_128(bytes: &[u8]) -> u128 {
+    const OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+    const PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+    bytes.iter().fold(OFFSET, |hash, &byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Set the file identifier of the trailer to a hash of the saved document.
+///
+/// The first part of an existing identifier is kept, as it identifies the
+/// original file, and only the second part, which identifies the revision,
+/// is replaced. This requires saving the document once in memory.
+pub fn set_identifier(document: &mut Document) -> lopdf::Result<()> {
+    let first = match document.trailer.get(b"ID").and_then(Object::as_array) {
+        Ok(id) => id.first().cloned(),
+        Err(_) => None,
+    };
+    document.trailer.remove(b"ID");
+
+    // Saving reserves an object number for the cross-reference stream, which
+    // is reserved again when the document is actually saved.
+    let max_id = document.max_id;
+    let mut bytes = vec![];
+    document.save_to(&mut bytes)?;
+    document.max_id = max_id;
+
+    let hash = Object::String(
+        fnv1a_128(&bytes).to_be_bytes().to_vec(),
+        StringFormat::Hexadecimal,
+    );
+    let first = first.unwrap_or_else(|| hash.clone());
+    document.trailer.set("ID", vec![first, hash]);
+    Ok(())
+}
+
+/// Make the document only depend on its content and on the given timestamp.
+pub fn make_reproducible(document: &mut Document, timestamp: u64) -> lopdf::Result<()> {
+    set_modification_date(document, &format_date(timestamp));
+    renumber_objects(document);
+    set_identifier(document)
+}
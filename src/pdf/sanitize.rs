@@ -0,0 +1,207 @@
+//! Removal of active content, i.e., scripts, actions that reach outside of
+//! the document, and embedded files.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+/// A kind of active content.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Threat {
+    /// A JavaScript action, or a document-level script.
+    JavaScript,
+    /// An action run when the document is opened.
+    OpenAction,
+    /// Actions triggered by events, e.g., when a page is opened or a form
+    /// field changes (`/AA` entries).
+    AdditionalActions,
+    /// An action launching an application or opening a file.
+    Launch,
+    /// An action sending form data to a URL.
+    SubmitForm,
+    /// An action importing form data from a file.
+    ImportData,
+    /// A file embedded in the document.
+    EmbeddedFile,
+}
+
+impl Threat {
+    /// Return a human-readable description of the threat, in the plural.
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::JavaScript => "JavaScript actions",
+            Self::OpenAction => "open actions",
+            Self::AdditionalActions => "additional actions",
+            Self::Launch => "launch actions",
+            Self::SubmitForm => "submit-form actions",
+            Self::ImportData => "import-data actions",
+            Self::EmbeddedFile => "embedded files",
+        }
+    }
+}
+
+/// Number of removed occurrences of each threat.
+pub type Report = BTreeMap<Threat, usize>;
+
+/// Return the threat posed by an action dictionary, if any.
+fn action_threat(dict: &Dictionary) -> Option<Threat> {
+    if dict.has(b"JS") {
+        return Some(Threat::JavaScript);
+    }
+    match dict.get(b"S").and_then(Object::as_name).ok()? {
+        b"JavaScript" => Some(Threat::JavaScript),
+        b"Launch" => Some(Threat::Launch),
+        b"SubmitForm" => Some(Threat::SubmitForm),
+        b"ImportData" => Some(Threat::ImportData),
+        _ => None,
+    }
+}
+
+/// Return whether an action, inline or referenced, is one of the removed ones.
+fn is_removed_action(object: &Object, removed: &BTreeSet<ObjectId>) -> bool {
+    match object {
+        Object::Reference(id) => removed.contains(id),
+        Object::Dictionary(dict) => action_threat(dict).is_some(),
+        _ => false,
+    }
+}
+
+/// Remove active content from a dictionary and its descendants.
+///
+/// Actions are removed from `/A` and `/Next` entries, `/AA` entries are
+/// removed altogether, and embedded file streams are detached from their
+/// file specifications.
+fn sanitize_dictionary(
+    dict: &mut Dictionary,
+    removed: &BTreeSet<ObjectId>,
+    keep_files: bool,
+    report: &mut Report,
+) {
+    if dict.remove(b"AA").is_some() {
+        *report.entry(Threat::AdditionalActions).or_default() += 1;
+    }
+    if !keep_files && dict.remove(b"EF").is_some() {
+        *report.entry(Threat::EmbeddedFile).or_default() += 1;
+    }
+
+    for key in [b"A".as_slice(), b"Next"] {
+        match dict.get_mut(key) {
+            Ok(Object::Array(actions)) => {
+                actions.retain(|action| {
+                    if let Object::Dictionary(action) = action {
+                        if let Some(threat) = action_threat(action) {
+                            *report.entry(threat).or_default() += 1;
+                        }
+                    }
+                    !is_removed_action(action, removed)
+                });
+            },
+            Ok(action) if is_removed_action(action, removed) => {
+                if let Object::Dictionary(action) = action {
+                    if let Some(threat) = action_threat(action) {
+                        *report.entry(threat).or_default() += 1;
+                    }
+                }
+                dict.remove(key);
+            },
+            _ => {},
+        }
+    }
+
+    for (_, value) in dict.iter_mut() {
+        sanitize_object(value, removed, keep_files, report);
+    }
+}
+
+/// Remove active content from an object and its descendants.
+fn sanitize_object(
+    object: &mut Object,
+    removed: &BTreeSet<ObjectId>,
+    keep_files: bool,
+    report: &mut Report,
+) {
+    match object {
+        Object::Dictionary(dict) => sanitize_dictionary(dict, removed, keep_files, report),
+        Object::Stream(stream) => {
+            sanitize_dictionary(&mut stream.dict, removed, keep_files, report);
+        },
+        Object::Array(array) => {
+            for item in array {
+                sanitize_object(item, removed, keep_files, report);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Remove JavaScript, open actions, additional actions, actions that launch
+/// applications or exchange form data, and embedded files from a document.
+///
+/// Objects that are no longer referenced are pruned. Returns how many
+/// occurrences of each threat were removed.
+pub fn sanitize(document: &mut Document) -> Report {
+    sanitize_with(document, false)
+}
+
+/// Remove active content from a document like [`sanitize`], but keep
+/// embedded files if `keep_files`, e.g., for PDF/A-3 documents, which may
+/// embed any file.
+pub fn sanitize_with(document: &mut Document, keep_files: bool) -> Report {
+    let mut report = Report::new();
+
+    let removed: BTreeSet<ObjectId> = document
+        .objects
+        .iter()
+        .filter_map(|(id, object)| {
+            let threat = action_threat(object.as_dict().ok()?)?;
+            *report.entry(threat).or_default() += 1;
+            Some(*id)
+        })
+        .collect();
+
+    if let Ok(catalog) = document.catalog_mut() {
+        if catalog.remove(b"OpenAction").is_some() {
+            *report.entry(Threat::OpenAction).or_default() += 1;
+        }
+    }
+
+    let names_id = document
+        .catalog()
+        .and_then(|catalog| catalog.get(b"Names"))
+        .and_then(Object::as_reference)
+        .ok();
+    let names = match names_id {
+        Some(id) => document.get_dictionary_mut(id).ok(),
+        None => {
+            document
+                .catalog_mut()
+                .and_then(|catalog| catalog.get_mut(b"Names"))
+                .and_then(Object::as_dict_mut)
+                .ok()
+        },
+    };
+    if let Some(names) = names {
+        // Scripts and files in these name trees are counted with their
+        // actions and file specifications.
+        names.remove(b"JavaScript");
+        if !keep_files {
+            names.remove(b"EmbeddedFiles");
+        }
+    }
+
+    for object in document.objects.values_mut() {
+        sanitize_object(object, &removed, keep_files, &mut report);
+    }
+    for id in &removed {
+        document.objects.remove(id);
+    }
+    document.prune_objects();
+
+    // The document is written as a whole, so previous revisions, which may
+    // still hold the removed content, must not be reachable.
+    document.trailer.remove(b"Prev");
+    document.trailer.remove(b"XRefStm");
+
+    report
+}
@@ -0,0 +1,137 @@
+//! Page selections, e.g., `1,3-5,8-`.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use lopdf::{Document, ObjectId};
+
+/// A set of page numbers, given as a comma-separated list of page numbers and
+/// inclusive ranges, where either bound of a range may be omitted.
+///
+/// `all` selects every page.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PageSelection {
+    /// Inclusive ranges, `None` meaning the first or the last page.
+    ranges: Vec<(Option<u32>, Option<u32>)>,
+}
+
+impl PageSelection {
+    /// Return whether the selection contains every page.
+    #[must_use]
+    pub fn is_all(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Return whether a page number is selected.
+    #[must_use]
+    pub fn contains(&self, page: u32) -> bool {
+        self.is_all()
+            || self.ranges.iter().any(|&(start, end)| {
+                start.map_or(true, |start| start <= page) && end.map_or(true, |end| page <= end)
+            })
+    }
+
+    /// Return the selection of the given page numbers, given in increasing
+    /// order, with consecutive pages merged into ranges.
+    ///
+    /// An empty list gives the selection of all pages.
+    #[must_use]
+    pub fn from_pages(pages: &[u32]) -> Self {
+        let mut ranges: Vec<(Option<u32>, Option<u32>)> = vec![];
+
+        for &page in pages {
+            match ranges.last_mut() {
+                Some((_, Some(end))) if page == *end + 1 => *end = page,
+                _ => ranges.push((Some(page), Some(page))),
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Return the selected pages of a document, by page number.
+    #[must_use]
+    pub fn select(&self, document: &Document) -> BTreeMap<u32, ObjectId> {
+        document
+            .get_pages()
+            .into_iter()
+            .filter(|&(page, _)| self.contains(page))
+            .collect()
+    }
+}
+
+impl FromStr for PageSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("all") {
+            return Ok(Self::default());
+        }
+
+        let parse = |bound: &str| -> Result<Option<u32>, String> {
+            let bound = bound.trim();
+            if bound.is_empty() {
+                return Ok(None);
+            }
+            match bound.parse() {
+                Ok(0) | Err(_) => Err(format!("invalid page number: {bound:?}")),
+                Ok(page) => Ok(Some(page)),
+            }
+        };
+
+        let ranges = s
+            .split(',')
+            .map(|part| {
+                match part.split_once('-') {
+                    Some((start, end)) => Ok((parse(start)?, parse(end)?)),
+                    None => {
+                        let page = parse(part)?.ok_or("empty page number")?;
+                        Ok((Some(page), Some(page)))
+                    },
+                }
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self { ranges })
+    }
+}
+
+/// Collect page numbers, in ascending order, into a selection of ranges of
+/// consecutive pages.
+impl FromIterator<u32> for PageSelection {
+    fn from_iter<I: IntoIterator<Item = u32>>(pages: I) -> Self {
+        let mut ranges: Vec<(Option<u32>, Option<u32>)> = vec![];
+
+        for page in pages {
+            match ranges.last_mut() {
+                Some((_, Some(end))) if *end + 1 == page => *end = page,
+                _ => ranges.push((Some(page), Some(page))),
+            }
+        }
+        Self { ranges }
+    }
+}
+
+impl fmt::Display for PageSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_all() {
+            return write!(f, "all");
+        }
+        for (i, &(start, end)) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match (start, end) {
+                (Some(start), Some(end)) if start == end => write!(f, "{start}")?,
+                (start, end) => {
+                    if let Some(start) = start {
+                        write!(f, "{start}")?;
+                    }
+                    write!(f, "-")?;
+                    if let Some(end) = end {
+                        write!(f, "{end}")?;
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! Digital signatures, and the revisions of the document they cover.
+//!
+//! Signatures are not verified cryptographically: this module only tells
+//! which bytes of the file a signature covers, and whether the file was
+//! updated after signing.
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use super::{history::Revision, pdfstring};
+
+/// Maximum depth of the field tree, guarding against reference cycles.
+const MAX_FIELD_DEPTH: usize = 32;
+
+/// Signature of a document.
+#[derive(Clone, Debug, Default)]
+pub struct Signature {
+    /// Fully qualified name of the signature field.
+    pub field: String,
+    /// Name of the signer, if any.
+    pub signer: Option<String>,
+    /// Signing date, as a PDF date, if any.
+    pub date: Option<String>,
+    /// Reason for signing, if any.
+    pub reason: Option<String>,
+    /// Byte ranges of the file covered by the signature, as offsets and
+    /// lengths.
+    pub byte_range: Vec<(usize, usize)>,
+}
+
+impl Signature {
+    /// Return the length of the file when it was signed, i.e., the end of
+    /// the last byte range.
+    #[must_use]
+    pub fn signed_length(&self) -> Option<usize> {
+        self.byte_range
+            .last()
+            .map(|(offset, length)| offset + length)
+    }
+}
+
+/// Coverage of a file by a signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Coverage {
+    /// The signature covers the given revision, starting at 1, out of the
+    /// given number of revisions.
+    Revision(usize, usize),
+    /// The byte ranges of the signature do not end with any revision.
+    Unknown,
+}
+
+impl Coverage {
+    /// Return whether the file was updated after signing.
+    #[must_use]
+    pub fn modified_after_signing(self) -> bool {
+        match self {
+            Self::Revision(revision, count) => revision < count,
+            Self::Unknown => true,
+        }
+    }
+}
+
+/// Return a text entry of a dictionary, if any.
+fn text(dict: &Dictionary, key: &[u8]) -> Option<String> {
+    dict.get(key).and_then(pdfstring::decode_object).ok()
+}
+
+/// Read a signature dictionary.
+fn read_signature(field: String, dict: &Dictionary) -> Signature {
+    let numbers: Vec<usize> = dict
+        .get(b"ByteRange")
+        .and_then(Object::as_array)
+        .map(|range| {
+            range
+                .iter()
+                .filter_map(|number| number.as_i64().ok())
+                .filter_map(|number| usize::try_from(number).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Signature {
+        field,
+        signer: text(dict, b"Name"),
+        date: dict
+            .get(b"M")
+            .and_then(Object::as_str)
+            .ok()
+            .map(|date| String::from_utf8_lossy(date).into_owned()),
+        reason: text(dict, b"Reason"),
+        byte_range: numbers
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect(),
+    }
+}
+
+/// Collect the signatures of a field and of its kids.
+fn collect_signatures(
+    document: &Document,
+    id: ObjectId,
+    parent: &str,
+    depth: usize,
+    signatures: &mut Vec<Signature>,
+) {
+    let Ok(field) = document.get_dictionary(id) else {
+        return;
+    };
+    let name = match text(field, b"T") {
+        Some(name) if parent.is_empty() => name,
+        Some(name) => format!("{parent}.{name}"),
+        None => parent.to_string(),
+    };
+
+    if field.get(b"FT").and_then(Object::as_name).ok() == Some(b"Sig") {
+        if let Ok(value) = field.get_deref(b"V", document).and_then(Object::as_dict) {
+            signatures.push(read_signature(name.clone(), value));
+        }
+    }
+    if depth >= MAX_FIELD_DEPTH {
+        return;
+    }
+    if let Ok(kids) = field
+        .get_deref(b"Kids", document)
+        .and_then(Object::as_array)
+    {
+        for kid in kids.iter().filter_map(|kid| kid.as_reference().ok()) {
+            collect_signatures(document, kid, &name, depth + 1, signatures);
+        }
+    }
+}
+
+/// Return the signed signature fields of a document, in the order of its
+/// form.
+#[must_use]
+pub fn signatures(document: &Document) -> Vec<Signature> {
+    let mut signatures = Vec::new();
+    let fields = document
+        .catalog()
+        .and_then(|catalog| catalog.get_deref(b"AcroForm", document))
+        .and_then(Object::as_dict)
+        .and_then(|form| form.get_deref(b"Fields", document))
+        .and_then(Object::as_array);
+
+    if let Ok(fields) = fields {
+        for id in fields.iter().filter_map(|field| field.as_reference().ok()) {
+            collect_signatures(document, id, "", 0, &mut signatures);
+        }
+    }
+    signatures
+}
+
+/// Return which revision of a file a signature covers.
+///
+/// Signers may or may not include the end-of-line marker after `%%EOF`, so
+/// both are accepted.
+#[must_use]
+pub fn coverage(signature: &Signature, data: &[u8], revisions: &[Revision]) -> Coverage {
+    let Some(signed_length) = signature.signed_length() else {
+        return Coverage::Unknown;
+    };
+
+    revisions
+        .iter()
+        .position(|revision| {
+            let end = data
+                .get(..revision.length)
+                .and_then(|data| data.iter().rposition(|byte| !byte.is_ascii_whitespace()))
+                .map_or(revision.length, |last| last + 1);
+            (end..=revision.length).contains(&signed_length)
+        })
+        .map_or(Coverage::Unknown, |i| {
+            Coverage::Revision(i + 1, revisions.len())
+        })
+}
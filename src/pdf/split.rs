@@ -0,0 +1,295 @@
+//! Splitting documents into parts, i.e., extracting ranges of pages.
+
+use std::{collections::HashSet, ops::RangeInclusive};
+
+use lopdf::{Document, Object, ObjectId, dictionary};
+
+use super::{document::DocumentExt, import::collect_references, outline::Bookmark, threads};
+
+/// Estimated size of the header, catalog, page tree and trailer of a file,
+/// in bytes.
+const BASE_SIZE: u64 = 1_024;
+
+/// Estimated size of the cross-reference entry of an object, and of its
+/// `obj`/`endobj` keywords, in bytes.
+const OBJECT_OVERHEAD: u64 = 40;
+
+/// Page attributes that pages can inherit from the page tree.
+pub(super) const INHERITABLE_ATTRIBUTES: &[&[u8]] =
+    &[b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Return a copy of a document with only the given pages.
+///
+/// Objects that only the other pages use are dropped, and references to
+/// the other pages, e.g., from links, are left dangling, which readers treat
+/// as `null`. The outline is dropped, as it refers to the whole document,
+/// and article threads only keep their beads on the given pages.
+#[must_use]
+pub fn extract(document: &Document, pages: RangeInclusive<u32>) -> Document {
+    let mut part = document.clone();
+    let all_pages = part.get_pages();
+    let pages_id = part.new_object_id();
+    let mut kept = HashSet::new();
+
+    for (number, &page_id) in &all_pages {
+        if !pages.contains(number) {
+            continue;
+        }
+        // Attributes inherited from the page tree are lost with it.
+        let inherited: Vec<(&[u8], Object)> = INHERITABLE_ATTRIBUTES
+            .iter()
+            .filter_map(|&key| Some((key, part.page_attribute(page_id, key)?.clone())))
+            .collect();
+        if let Ok(page) = part.get_dictionary_mut(page_id) {
+            for (key, value) in inherited {
+                if !page.has(key) {
+                    page.set(key, value);
+                }
+            }
+            page.set("Parent", pages_id);
+            kept.insert(page_id);
+        }
+    }
+
+    // Remove the old page tree and the other pages, which the new tree no
+    // longer references.
+    let old_nodes: Vec<_> = part
+        .objects
+        .iter()
+        .filter(|(id, object)| {
+            let kind = object
+                .as_dict()
+                .and_then(|dict| dict.get(b"Type"))
+                .and_then(Object::as_name)
+                .ok();
+            kind == Some(b"Pages") || (kind == Some(b"Page") && !kept.contains(*id))
+        })
+        .map(|(&id, _)| id)
+        .collect();
+    for id in old_nodes {
+        part.objects.remove(&id);
+    }
+
+    let kids: Vec<Object> = all_pages
+        .values()
+        .filter(|id| kept.contains(*id))
+        .map(|&id| Object::Reference(id))
+        .collect();
+    part.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    if let Ok(catalog) = part.catalog_mut() {
+        catalog.set("Pages", pages_id);
+        catalog.remove(b"Outlines");
+        if catalog.get(b"PageMode").and_then(Object::as_name).ok() == Some(b"UseOutlines") {
+            catalog.remove(b"PageMode");
+        }
+    }
+    threads::repair(&mut part);
+    part.prune_objects();
+    part
+}
+
+/// Remove pages from a document, by page number, and the objects that only
+/// they use.
+///
+/// References to removed pages, e.g., from links or bookmarks, are removed
+/// too, and so are the beads of article threads on removed pages.
+pub fn remove(document: &mut Document, pages: &[u32]) {
+    document.delete_pages(pages);
+    threads::repair(document);
+    document.prune_objects();
+}
+
+/// Return the parts of a document delimited by its bookmarks of a given
+/// level, as `(title, pages)` pairs.
+///
+/// Each part runs from the page of its bookmark to the page before the next
+/// one. Pages before the first bookmark are part of the first part, and
+/// bookmarks that point to the same page as the next one are ignored.
+#[must_use]
+pub fn outline_parts(
+    bookmarks: &[Bookmark],
+    level: usize,
+    page_count: u32,
+) -> Vec<(String, RangeInclusive<u32>)> {
+    let mut starts: Vec<(String, u32)> = bookmarks
+        .iter()
+        .filter(|bookmark| bookmark.level == level)
+        .filter_map(|bookmark| Some((bookmark.title.clone(), bookmark.page?)))
+        .collect();
+    starts.sort_by_key(|&(_, page)| page);
+    starts.dedup_by(|next, previous| {
+        if next.1 == previous.1 {
+            previous.0 = std::mem::take(&mut next.0);
+            true
+        } else {
+            false
+        }
+    });
+
+    let ends: Vec<u32> = starts
+        .iter()
+        .skip(1)
+        .map(|&(_, page)| page - 1)
+        .chain([page_count])
+        .collect();
+    starts
+        .into_iter()
+        .zip(ends)
+        .enumerate()
+        .map(|(i, ((title, start), end))| (title, if i == 0 { 1 } else { start }..=end))
+        .collect()
+}
+
+/// Return parts of at most `chunk` pages each.
+#[must_use]
+pub fn chunk_parts(page_count: u32, chunk: u32) -> Vec<(String, RangeInclusive<u32>)> {
+    (1..=page_count)
+        .step_by(chunk.max(1) as usize)
+        .map(|start| {
+            (
+                String::new(),
+                start..=start.saturating_add(chunk - 1).min(page_count),
+            )
+        })
+        .collect()
+}
+
+/// Return an estimate of the size of an object once written, in bytes.
+fn estimated_size(object: &Object) -> u64 {
+    match object {
+        Object::Array(array) => {
+            2 + array
+                .iter()
+                .map(|item| 1 + estimated_size(item))
+                .sum::<u64>()
+        },
+        Object::Dictionary(dict) => {
+            4 + dict
+                .iter()
+                .map(|(key, value)| 2 + key.len() as u64 + estimated_size(value))
+                .sum::<u64>()
+        },
+        Object::Stream(stream) => {
+            estimated_size(&Object::Dictionary(stream.dict.clone()))
+                + stream.content.len() as u64
+                + 18
+        },
+        Object::Name(name) => 1 + name.len() as u64,
+        Object::String(string, _) => 2 + string.len() as u64,
+        _ => 8,
+    }
+}
+
+/// Return the objects reachable from the given references, without going
+/// through pages or the page tree.
+fn reachable_objects(document: &Document, mut queue: Vec<ObjectId>) -> HashSet<ObjectId> {
+    let mut objects = HashSet::new();
+
+    while let Some(id) = queue.pop() {
+        let Ok(object) = document.get_object(id) else {
+            continue;
+        };
+        let kind = object
+            .as_dict()
+            .and_then(|dict| dict.get(b"Type"))
+            .and_then(Object::as_name)
+            .ok();
+        if matches!(kind, Some(b"Page" | b"Pages")) || !objects.insert(id) {
+            continue;
+        }
+        collect_references(object, &mut queue);
+    }
+    objects
+}
+
+/// Return the objects that a page uses, e.g., its content streams, fonts and
+/// images, excluding other pages and the page tree.
+fn page_objects(document: &Document, page_id: ObjectId) -> HashSet<ObjectId> {
+    let mut queue = vec![];
+
+    if let Ok(page) = document.get_dictionary(page_id) {
+        for (key, value) in page.iter() {
+            if key != b"Parent" {
+                collect_references(value, &mut queue);
+            }
+        }
+    }
+    if let Some(resources) = document.page_attribute(page_id, b"Resources") {
+        collect_references(resources, &mut queue);
+    }
+    let mut objects = reachable_objects(document, queue);
+    objects.insert(page_id);
+    objects
+}
+
+/// Return the objects that every part keeps, e.g., the document information
+/// dictionary, metadata or form fields, excluding the outline.
+fn document_objects(document: &Document) -> HashSet<ObjectId> {
+    let mut queue = vec![];
+
+    for (key, value) in document.trailer.iter() {
+        if key != b"Root" {
+            collect_references(value, &mut queue);
+        }
+    }
+    if let Ok(catalog) = document.catalog() {
+        for (key, value) in catalog.iter() {
+            if key != b"Outlines" {
+                collect_references(value, &mut queue);
+            }
+        }
+    }
+    reachable_objects(document, queue)
+}
+
+/// Return an estimate of the size of some objects once written, in bytes.
+fn objects_size<'a>(document: &Document, ids: impl Iterator<Item = &'a ObjectId>) -> u64 {
+    ids.filter_map(|id| document.get_object(*id).ok())
+        .map(|object| estimated_size(object) + OBJECT_OVERHEAD)
+        .sum()
+}
+
+/// Return parts whose files should not exceed `max_size` bytes, as
+/// estimated from the size of the objects their pages use.
+///
+/// Objects shared by pages, e.g., fonts, are only counted once per part, and
+/// objects of the document itself, e.g., form fields, are counted in every
+/// part. A page that alone exceeds the size makes a part of its own.
+#[must_use]
+pub fn size_parts(document: &Document, max_size: u64) -> Vec<(String, RangeInclusive<u32>)> {
+    let shared = document_objects(document);
+    let base_size = BASE_SIZE + objects_size(document, shared.iter());
+    let mut parts = vec![];
+    let mut objects = shared.clone();
+    let mut size = base_size;
+    let mut start = 1;
+
+    for (number, page_id) in document.get_pages() {
+        let used = page_objects(document, page_id);
+        let added_size = objects_size(document, used.difference(&objects));
+
+        if number > start && size + added_size > max_size {
+            parts.push((String::new(), start..=number - 1));
+            start = number;
+            // Objects shared with the previous part are counted again.
+            objects.clone_from(&shared);
+            size = base_size + objects_size(document, used.difference(&objects));
+            objects.extend(used);
+        } else {
+            size += added_size;
+            objects.extend(used);
+        }
+    }
+    let page_count = document.get_pages().len() as u32;
+    if start <= page_count {
+        parts.push((String::new(), start..=page_count));
+    }
+    parts
+}
@@ -0,0 +1,112 @@
+//! Stamps drawn in the content of pages, e.g., Bates numbers or QR codes.
+//!
+//! Unlike stamp annotations, these stamps are part of the page, so viewers
+//! neither let users move or delete them, nor hide them when printing.
+
+use lopdf::{
+    Document, Object, ObjectId,
+    content::{Content, Operation},
+};
+#[cfg(feature = "qrcode")]
+use qrcodegen::QrCode;
+
+use super::{
+    document::DocumentExt,
+    fonts::{StandardFont, win_ansi_string},
+};
+
+/// Font of text stamps.
+pub const FONT: StandardFont = StandardFont::Helvetica;
+
+/// Add the font of text stamps to a document, and return its ID, so that
+/// pages share it.
+pub fn add_font(document: &mut Document) -> ObjectId {
+    document.add_object(FONT.dictionary())
+}
+
+/// Return the width of a text stamp, in points.
+#[must_use]
+pub fn text_width(text: &str, size: f32) -> f32 {
+    FONT.text_width(text, size)
+}
+
+/// Draw a line of black text on top of a page, with its baseline starting
+/// at `(x, y)`.
+///
+/// The font, added with [`add_font`], is added to the page's resources.
+pub fn draw_text(
+    document: &mut Document,
+    page_id: ObjectId,
+    font_id: ObjectId,
+    text: &str,
+    (x, y): (f32, f32),
+    size: f32,
+) -> lopdf::Result<()> {
+    let name = document.add_page_resource(page_id, b"Font", "Stamp", font_id)?;
+    let content = Content {
+        operations: vec![
+            Operation::new("BT", vec![]),
+            Operation::new("g", vec![0.into()]),
+            Operation::new("Tf", vec![Object::Name(name), size.into()]),
+            Operation::new("Td", vec![x.into(), y.into()]),
+            Operation::new("Tj", vec![win_ansi_string(text)]),
+            Operation::new("ET", vec![]),
+        ],
+    };
+    document.overlay_page_content(page_id, content.encode()?)
+}
+
+/// Width of the quiet zone around QR codes, in modules, as required by the
+/// standard for readers to find codes.
+#[cfg(feature = "qrcode")]
+const QUIET_ZONE: i32 = 4;
+
+/// Draw a QR code on top of a page, with its bottom-left corner at `(x, y)`,
+/// as a square of the given size, in points.
+///
+/// Modules are drawn as vector rectangles, on a white square that includes
+/// the quiet zone, so that codes can be read on any background.
+#[cfg(feature = "qrcode")]
+pub fn draw_qr_code(
+    document: &mut Document,
+    page_id: ObjectId,
+    code: &QrCode,
+    (x, y): (f32, f32),
+    size: f32,
+) -> lopdf::Result<()> {
+    let modules = code.size();
+    let module = size / (modules + 2 * QUIET_ZONE) as f32;
+    let mut operations = vec![
+        Operation::new("q", vec![]),
+        Operation::new("g", vec![1.into()]),
+        Operation::new("re", vec![x.into(), y.into(), size.into(), size.into()]),
+        Operation::new("f", vec![]),
+        Operation::new("g", vec![0.into()]),
+    ];
+
+    // Runs of dark modules of each row are drawn as a single rectangle.
+    for row in 0..modules {
+        let bottom = y + (modules - 1 - row + QUIET_ZONE) as f32 * module;
+        let mut column = 0;
+        while column < modules {
+            if !code.get_module(column, row) {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < modules && code.get_module(column, row) {
+                column += 1;
+            }
+            let left = x + (start + QUIET_ZONE) as f32 * module;
+            let width = (column - start) as f32 * module;
+            operations.push(Operation::new(
+                "re",
+                vec![left.into(), bottom.into(), width.into(), module.into()],
+            ));
+        }
+    }
+    operations.push(Operation::new("f", vec![]));
+    operations.push(Operation::new("Q", vec![]));
+
+    document.overlay_page_content(page_id, Content { operations }.encode()?)
+}
@@ -0,0 +1,295 @@
+//! Logical structure of tagged PDF documents, i.e., the structure tree that
+//! assistive technologies read.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::Serialize;
+
+use super::{pdfstring, reference};
+
+/// Standard structure types, from PDF 1.7 and PDF 2.0.
+pub const STANDARD_TYPES: &[&str] = &[
+    "Document",
+    "DocumentFragment",
+    "Part",
+    "Art",
+    "Sect",
+    "Div",
+    "Aside",
+    "BlockQuote",
+    "Caption",
+    "TOC",
+    "TOCI",
+    "Index",
+    "NonStruct",
+    "Private",
+    "Title",
+    "P",
+    "H",
+    "H1",
+    "H2",
+    "H3",
+    "H4",
+    "H5",
+    "H6",
+    "L",
+    "LI",
+    "Lbl",
+    "LBody",
+    "Table",
+    "TR",
+    "TH",
+    "TD",
+    "THead",
+    "TBody",
+    "TFoot",
+    "Span",
+    "Quote",
+    "Note",
+    "FENote",
+    "Reference",
+    "BibEntry",
+    "Code",
+    "Link",
+    "Annot",
+    "Ruby",
+    "RB",
+    "RT",
+    "RP",
+    "Warichu",
+    "WT",
+    "WP",
+    "Sub",
+    "Em",
+    "Strong",
+    "Figure",
+    "Formula",
+    "Form",
+    "Artifact",
+];
+
+/// Maximum depth of the structure tree, which guards against malformed
+/// documents.
+const MAX_DEPTH: usize = 256;
+
+/// Structure element, i.e., a node of the structure tree.
+#[derive(Debug, Serialize)]
+pub struct Element {
+    /// Structure type, as written in the document, e.g., `P` or a custom
+    /// type.
+    #[serde(rename = "type")]
+    pub struct_type: String,
+    /// Standard type that the structure type maps to through the role map,
+    /// or `null` if it does not map to one.
+    pub role: Option<String>,
+    /// Object holding the element, e.g., `12 0 R`, or `null` if it is
+    /// inline.
+    pub object: Option<String>,
+    /// Number of the page the element's content is on, if given.
+    pub page: Option<u32>,
+    /// Title of the element.
+    pub title: Option<String>,
+    /// Language of the element, e.g., `fr-FR`.
+    pub lang: Option<String>,
+    /// Alternate description, e.g., of a figure.
+    pub alt: Option<String>,
+    /// Replacement text of the element's content.
+    pub actual_text: Option<String>,
+    /// Marked-content identifiers of the element's content in page content
+    /// streams.
+    pub marked_content: Vec<i64>,
+    /// Objects referred to by the element, e.g., annotations.
+    pub objects: Vec<String>,
+    pub children: Vec<Element>,
+}
+
+/// Structure tree of a document.
+#[derive(Debug, Serialize)]
+pub struct StructureTree {
+    /// Mapping from custom structure types to other structure types.
+    pub role_map: BTreeMap<String, String>,
+    pub children: Vec<Element>,
+}
+
+/// Reads the structure tree of a document.
+struct Reader<'a> {
+    document: &'a Document,
+    role_map: BTreeMap<String, String>,
+    page_numbers: HashMap<ObjectId, u32>,
+    visited: HashSet<ObjectId>,
+}
+
+impl Reader<'_> {
+    /// Return the standard type a structure type maps to, following the role
+    /// map.
+    fn role(&self, struct_type: &str) -> Option<String> {
+        let mut current = struct_type;
+
+        for _ in 0..=self.role_map.len() {
+            match self.role_map.get(current) {
+                Some(mapped) if STANDARD_TYPES.contains(&mapped.as_str()) => {
+                    return Some(mapped.clone());
+                },
+                Some(mapped) => current = mapped,
+                None => return None,
+            }
+        }
+        None
+    }
+
+    /// Return a text string entry of a dictionary.
+    fn text(&self, dict: &Dictionary, key: &[u8]) -> Option<String> {
+        dict.get_deref(key, self.document)
+            .and_then(pdfstring::decode_object)
+            .ok()
+    }
+
+    /// Read the kids of a structure element or of the structure tree root,
+    /// adding marked contents and object references to `parent`, if any.
+    fn kids(
+        &mut self,
+        kids: &Object,
+        mut parent: Option<&mut Element>,
+        depth: usize,
+    ) -> Vec<Element> {
+        // Arrays of kids are sometimes indirect.
+        let kids = match kids {
+            Object::Reference(id) => {
+                match self.document.get_object(*id) {
+                    Ok(array @ Object::Array(_)) => array,
+                    _ => kids,
+                }
+            },
+            kids => kids,
+        };
+        let items = match kids {
+            Object::Array(array) => array.as_slice(),
+            kid => std::slice::from_ref(kid),
+        };
+        let mut children = vec![];
+
+        for item in items {
+            let (id, object) = match item {
+                Object::Reference(id) => {
+                    match self.document.get_object(*id) {
+                        Ok(object) => (Some(*id), object),
+                        Err(_) => continue,
+                    }
+                },
+                object => (None, object),
+            };
+            match object {
+                Object::Integer(mcid) => {
+                    if let Some(parent) = parent.as_deref_mut() {
+                        parent.marked_content.push(*mcid);
+                    }
+                },
+                Object::Dictionary(dict) if dict.has(b"S") => {
+                    if let Some(element) = self.element(dict, id, depth) {
+                        children.push(element);
+                    }
+                },
+                Object::Dictionary(dict) => {
+                    let Some(parent) = parent.as_deref_mut() else {
+                        continue;
+                    };
+                    if let Ok(mcid) = dict.get(b"MCID").and_then(Object::as_i64) {
+                        parent.marked_content.push(mcid);
+                    } else if let Ok(id) = dict.get(b"Obj").and_then(Object::as_reference) {
+                        parent.objects.push(reference(id));
+                    }
+                },
+                _ => {},
+            }
+        }
+        children
+    }
+
+    /// Read a structure element and its descendants.
+    fn element(
+        &mut self,
+        dict: &Dictionary,
+        id: Option<ObjectId>,
+        depth: usize,
+    ) -> Option<Element> {
+        if depth > MAX_DEPTH || id.is_some_and(|id| !self.visited.insert(id)) {
+            return None;
+        }
+        let struct_type = dict
+            .get(b"S")
+            .and_then(Object::as_name)
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .ok()?;
+
+        let mut element = Element {
+            role: self.role(&struct_type),
+            struct_type,
+            object: id.map(reference),
+            page: dict
+                .get(b"Pg")
+                .and_then(Object::as_reference)
+                .ok()
+                .and_then(|page_id| self.page_numbers.get(&page_id).copied()),
+            title: self.text(dict, b"T"),
+            lang: self.text(dict, b"Lang"),
+            alt: self.text(dict, b"Alt"),
+            actual_text: self.text(dict, b"ActualText"),
+            marked_content: vec![],
+            objects: vec![],
+            children: vec![],
+        };
+        if let Ok(kids) = dict.get(b"K") {
+            element.children = self.kids(kids, Some(&mut element), depth + 1);
+        }
+        Some(element)
+    }
+}
+
+/// Read the structure tree of a document, or return `None` if it has none,
+/// i.e., it is not tagged.
+pub fn read(document: &Document) -> Option<StructureTree> {
+    let root = document
+        .catalog()
+        .ok()?
+        .get_deref(b"StructTreeRoot", document)
+        .and_then(Object::as_dict)
+        .ok()?;
+
+    let role_map = root
+        .get_deref(b"RoleMap", document)
+        .and_then(Object::as_dict)
+        .map(|role_map| {
+            role_map
+                .iter()
+                .filter_map(|(name, mapped)| {
+                    Some((
+                        String::from_utf8_lossy(name).into_owned(),
+                        String::from_utf8_lossy(mapped.as_name().ok()?).into_owned(),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let page_numbers = document
+        .get_pages()
+        .into_iter()
+        .map(|(number, id)| (id, number))
+        .collect();
+
+    let mut reader = Reader {
+        document,
+        role_map,
+        page_numbers,
+        visited: HashSet::new(),
+    };
+    let children = match root.get(b"K") {
+        Ok(kids) => reader.kids(kids, None, 0),
+        Err(_) => vec![],
+    };
+
+    Some(StructureTree {
+        role_map: reader.role_map,
+        children,
+    })
+}
@@ -0,0 +1,361 @@
+//! Table detection over positioned text.
+//!
+//! Tables drawn with ruling lines are split into columns (and rows, when
+//! horizontal rules are present) along those lines. Other tables are found as
+//! runs of lines with several cells, whose columns are the whitespace gaps
+//! shared by all lines.
+
+use lopdf::{Document, ObjectId};
+
+use super::{
+    content::{number, with_ctm},
+    text::{self, TextLine},
+};
+
+/// Tolerance when comparing positions, in points.
+const TOLERANCE: f32 = 2.0;
+
+/// Minimum length of a path segment to be considered a ruling line, in points.
+const MIN_RULING_LENGTH: f32 = 5.0;
+
+/// Gap between spans of a line, relative to the font size, above which they
+/// belong to different cells.
+const CELL_GAP: f32 = 0.8;
+
+/// Mean length of the cells of a table without ruling lines, in characters,
+/// above which it is considered to be prose laid out in columns.
+const MAX_MEAN_CELL_LENGTH: usize = 30;
+
+/// A table of text cells.
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    /// Cells, row by row, all rows having the same number of cells.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// An axis-aligned ruling line, `position` being the constant coordinate.
+#[derive(Clone, Copy, Debug)]
+struct Ruling {
+    position: f32,
+    start: f32,
+    end: f32,
+}
+
+/// A horizontal run of text within a line.
+#[derive(Clone, Debug)]
+struct Cell {
+    start: f32,
+    end: f32,
+    text: String,
+}
+
+/// Detect the tables of a page, from top to bottom.
+///
+/// Only tables with at least two rows and two non-empty columns are returned.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+pub fn page_tables(document: &Document, page_id: ObjectId) -> lopdf::Result<Vec<Table>> {
+    let lines = text::lines(&text::page_spans(document, page_id)?);
+    let (vertical, horizontal) = rulings(document, page_id)?;
+    let mut used = vec![false; lines.len()];
+    let mut tables: Vec<(f32, Table)> = vec![];
+
+    for grid in clusters(vertical) {
+        let top = grid
+            .iter()
+            .map(|ruling| ruling.end)
+            .fold(f32::MIN, f32::max);
+        let bottom = grid
+            .iter()
+            .map(|ruling| ruling.start)
+            .fold(f32::MAX, f32::min);
+        let columns = dedup(grid.iter().map(|ruling| ruling.position).collect());
+        if columns.len() < 2 {
+            continue;
+        }
+        let (left, right) = (columns[0], columns[columns.len() - 1]);
+        let inside = |line: &TextLine| {
+            line.y >= bottom - TOLERANCE
+                && line.y <= top + TOLERANCE
+                && line
+                    .spans
+                    .iter()
+                    .any(|span| span.x >= left - TOLERANCE && span.end() <= right + TOLERANCE)
+        };
+        let indices: Vec<usize> = (0..lines.len())
+            .filter(|&i| !used[i] && inside(&lines[i]))
+            .collect();
+        if indices.is_empty() {
+            continue;
+        }
+
+        let mut bands = dedup(
+            horizontal
+                .iter()
+                .filter(|ruling| {
+                    ruling.position >= bottom - TOLERANCE
+                        && ruling.position <= top + TOLERANCE
+                        && ruling.start <= left + TOLERANCE
+                        && ruling.end >= right - TOLERANCE
+                })
+                .map(|ruling| ruling.position)
+                .collect(),
+        );
+        bands.reverse();
+
+        let mut rows: Vec<(Option<usize>, Vec<String>)> = vec![];
+        for &i in &indices {
+            used[i] = true;
+            let band = (bands.len() >= 2)
+                .then(|| bands.iter().position(|&y| y < lines[i].y))
+                .flatten();
+            let mut row = vec![String::new(); columns.len() - 1];
+
+            for cell in cells(&lines[i]) {
+                let center = (cell.start + cell.end) / 2.0;
+                let column = columns
+                    .windows(2)
+                    .position(|pair| center >= pair[0] && center < pair[1])
+                    .unwrap_or(if center < left { 0 } else { row.len() - 1 });
+                append(&mut row[column], &cell.text);
+            }
+
+            match rows.last_mut() {
+                Some((last_band, last)) if band.is_some() && *last_band == band => {
+                    for (cell, text) in last.iter_mut().zip(row) {
+                        append(cell, &text);
+                    }
+                },
+                _ => rows.push((band, row)),
+            }
+        }
+
+        tables.push((
+            lines[indices[0]].y,
+            Table {
+                rows: rows.into_iter().map(|(_, row)| row).collect(),
+            },
+        ));
+    }
+
+    let mut block: Vec<Vec<Cell>> = vec![];
+    let mut block_top = 0.0;
+    for (i, line) in lines.iter().enumerate() {
+        let line_cells = if used[i] { vec![] } else { cells(line) };
+        if line_cells.len() >= 2 {
+            if block.is_empty() {
+                block_top = line.y;
+            }
+            block.push(line_cells);
+        } else {
+            if let Some(table) = whitespace_table(&block) {
+                tables.push((block_top, table));
+            }
+            block.clear();
+        }
+    }
+    if let Some(table) = whitespace_table(&block) {
+        tables.push((block_top, table));
+    }
+
+    tables.sort_by(|a, b| b.0.total_cmp(&a.0));
+    Ok(tables
+        .into_iter()
+        .map(|(_, table)| remove_empty_columns(table))
+        .filter(|table| table.rows.len() >= 2 && table.rows[0].len() >= 2)
+        .collect())
+}
+
+/// Build a table whose columns are separated by whitespace shared by all
+/// lines.
+///
+/// Returns `None` if there are too few lines, or if the cells look like prose.
+fn whitespace_table(block: &[Vec<Cell>]) -> Option<Table> {
+    let cells = block.iter().flatten();
+    let length: usize = cells.clone().map(|cell| cell.text.chars().count()).sum();
+    if block.len() < 2 || length > MAX_MEAN_CELL_LENGTH * cells.count() {
+        return None;
+    }
+
+    let mut intervals: Vec<(f32, f32)> = block
+        .iter()
+        .flatten()
+        .map(|cell| (cell.start, cell.end))
+        .collect();
+    intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut columns: Vec<(f32, f32)> = vec![];
+    for (start, end) in intervals {
+        match columns.last_mut() {
+            Some(column) if start <= column.1 + TOLERANCE => column.1 = column.1.max(end),
+            _ => columns.push((start, end)),
+        }
+    }
+
+    let rows = block
+        .iter()
+        .map(|line| {
+            let mut row = vec![String::new(); columns.len()];
+            for cell in line {
+                let column = columns
+                    .iter()
+                    .position(|&(start, end)| cell.start >= start - TOLERANCE && cell.start <= end)
+                    .unwrap_or(0);
+                append(&mut row[column], &cell.text);
+            }
+            row
+        })
+        .collect();
+
+    Some(Table { rows })
+}
+
+/// Split a line into cells separated by wide gaps.
+fn cells(line: &TextLine) -> Vec<Cell> {
+    let mut cells: Vec<Cell> = vec![];
+
+    for span in &line.spans {
+        match cells.last_mut() {
+            Some(cell) if span.x - cell.end < CELL_GAP * span.font_size => {
+                if span.x - cell.end > 0.15 * span.font_size && !cell.text.ends_with(' ') {
+                    cell.text.push(' ');
+                }
+                cell.text.push_str(&span.text);
+                cell.end = cell.end.max(span.end());
+            },
+            _ => {
+                cells.push(Cell {
+                    start: span.x,
+                    end: span.end(),
+                    text: span.text.clone(),
+                });
+            },
+        }
+    }
+
+    for cell in &mut cells {
+        cell.text = cell.text.trim().to_string();
+    }
+    cells
+}
+
+/// Append text to a cell, separated by a space.
+fn append(cell: &mut String, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if !cell.is_empty() {
+        cell.push(' ');
+    }
+    cell.push_str(text);
+}
+
+/// Remove the columns that are empty in every row.
+fn remove_empty_columns(table: Table) -> Table {
+    let width = table.rows.first().map_or(0, Vec::len);
+    let keep: Vec<bool> = (0..width)
+        .map(|column| table.rows.iter().any(|row| !row[column].is_empty()))
+        .collect();
+
+    Table {
+        rows: table
+            .rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(&keep)
+                    .filter_map(|(cell, &keep)| keep.then_some(cell))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+/// Sort positions and merge the ones closer than the tolerance.
+fn dedup(mut positions: Vec<f32>) -> Vec<f32> {
+    positions.sort_by(f32::total_cmp);
+    positions.dedup_by(|a, b| (*a - *b).abs() <= TOLERANCE);
+    positions
+}
+
+/// Group vertical rulings whose extents overlap.
+fn clusters(mut vertical: Vec<Ruling>) -> Vec<Vec<Ruling>> {
+    vertical.sort_by(|a, b| b.end.total_cmp(&a.end));
+    let mut clusters: Vec<(f32, Vec<Ruling>)> = vec![];
+
+    for ruling in vertical {
+        match clusters.last_mut() {
+            Some((bottom, cluster)) if ruling.end >= *bottom - TOLERANCE => {
+                *bottom = bottom.min(ruling.start);
+                cluster.push(ruling);
+            },
+            _ => clusters.push((ruling.start, vec![ruling])),
+        }
+    }
+    clusters.into_iter().map(|(_, cluster)| cluster).collect()
+}
+
+/// Return the vertical and horizontal ruling lines stroked or filled by a
+/// page.
+fn rulings(document: &Document, page_id: ObjectId) -> lopdf::Result<(Vec<Ruling>, Vec<Ruling>)> {
+    let content = document.get_and_decode_page_content(page_id)?;
+    let mut vertical = vec![];
+    let mut horizontal = vec![];
+    let mut path: Vec<((f32, f32), (f32, f32))> = vec![];
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+
+    for (operation, ctm) in with_ctm(&content.operations) {
+        let operands: Vec<f32> = operation.operands.iter().filter_map(number).collect();
+
+        match (operation.operator.as_str(), operands.as_slice()) {
+            ("m", &[x, y]) => {
+                current = ctm.apply(x, y);
+                start = current;
+            },
+            ("l", &[x, y]) => {
+                let point = ctm.apply(x, y);
+                path.push((current, point));
+                current = point;
+            },
+            ("h", _) => {
+                path.push((current, start));
+                current = start;
+            },
+            ("re", &[x, y, width, height]) => {
+                let corners = [
+                    ctm.apply(x, y),
+                    ctm.apply(x + width, y),
+                    ctm.apply(x + width, y + height),
+                    ctm.apply(x, y + height),
+                ];
+                for i in 0..4 {
+                    path.push((corners[i], corners[(i + 1) % 4]));
+                }
+            },
+            ("S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*", _) => {
+                for ((x0, y0), (x1, y1)) in path.drain(..) {
+                    if (x0 - x1).abs() <= TOLERANCE && (y0 - y1).abs() >= MIN_RULING_LENGTH {
+                        vertical.push(Ruling {
+                            position: (x0 + x1) / 2.0,
+                            start: y0.min(y1),
+                            end: y0.max(y1),
+                        });
+                    } else if (y0 - y1).abs() <= TOLERANCE && (x0 - x1).abs() >= MIN_RULING_LENGTH {
+                        horizontal.push(Ruling {
+                            position: (y0 + y1) / 2.0,
+                            start: x0.min(x1),
+                            end: x0.max(x1),
+                        });
+                    }
+                }
+            },
+            ("n", _) => path.clear(),
+            _ => {},
+        }
+    }
+
+    Ok((vertical, horizontal))
+}
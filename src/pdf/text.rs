@@ -0,0 +1,983 @@
+//! Positioned text extraction from page content streams.
+//!
+//! Text is decoded with the font's `/ToUnicode` map when present, falling back
+//! to its `/Encoding` differences and `WinAnsiEncoding`. Positions are
+//! computed from glyph widths, so they are only as accurate as the font
+//! metrics found in the document.
+
+use std::{collections::HashMap, rc::Rc};
+
+use lopdf::{
+    Dictionary, Document, Object, ObjectId,
+    content::{Content, Operation},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    content::{Matrix, number},
+    document::DocumentExt,
+    fonts::{StandardFont, decode_mac_roman, decode_win_ansi, glyph_name_to_char},
+};
+
+/// Maximum nesting depth of form XObjects whose content is extracted.
+const MAX_FORM_DEPTH: usize = 8;
+
+/// Adjustment of a `TJ` array, in thousandths of the font size, above which
+/// a space is inserted between the surrounding strings.
+pub(super) const SPACE_ADJUSTMENT: f32 = 200.0;
+
+/// Adjustment of a `TJ` array, in thousandths of the font size, above which
+/// the surrounding strings are split into separate spans.
+const SPLIT_ADJUSTMENT: f32 = 1500.0;
+
+/// A run of text shown with the same font, in page coordinates.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TextSpan {
+    /// Decoded text.
+    pub text: String,
+    /// Horizontal position of the start of the baseline.
+    pub x: f32,
+    /// Vertical position of the baseline.
+    pub y: f32,
+    /// Length of the span along its baseline.
+    pub width: f32,
+    /// Effective font size, after scaling by the text and graphics matrices.
+    pub font_size: f32,
+    /// Name of the font, without its subset prefix.
+    #[cfg(feature = "convert")]
+    pub font: String,
+    /// Angle of the baseline, in degrees counterclockwise.
+    pub angle: f32,
+    /// Text rendering mode, `3` meaning invisible text.
+    pub render_mode: i64,
+}
+
+impl TextSpan {
+    /// Return the horizontal position of the end of the baseline.
+    #[must_use]
+    pub fn end(&self) -> f32 {
+        self.x + self.width
+    }
+
+    /// Return whether the font name suggests a bold face.
+    #[cfg(feature = "convert")]
+    #[must_use]
+    pub fn is_bold(&self) -> bool {
+        ["Bold", "Black", "Heavy", "Semibold"]
+            .iter()
+            .any(|weight| self.font.contains(weight))
+    }
+}
+
+/// A line of text, made of spans sharing the same baseline.
+#[derive(Clone, Debug)]
+pub struct TextLine {
+    /// Vertical position of the baseline.
+    pub y: f32,
+    /// Spans, from left to right.
+    pub spans: Vec<TextSpan>,
+}
+
+impl TextLine {
+    /// Return the largest font size of the line.
+    #[must_use]
+    pub fn font_size(&self) -> f32 {
+        self.spans
+            .iter()
+            .map(|span| span.font_size)
+            .fold(0.0, f32::max)
+    }
+
+    /// Return the text of the line, inserting spaces where spans are apart.
+    #[must_use]
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        let mut end: Option<f32> = None;
+
+        for span in &self.spans {
+            if let Some(end) = end {
+                if span.x - end > 0.15 * span.font_size
+                    && !text.ends_with(' ')
+                    && !span.text.starts_with(' ')
+                {
+                    text.push(' ');
+                }
+            }
+            text.push_str(&span.text);
+            end = Some(span.end());
+        }
+        text
+    }
+}
+
+/// Numbers of words and characters of some text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    /// Words, i.e., runs of characters between whitespace.
+    pub words: usize,
+    /// Characters, excluding whitespace.
+    pub characters: usize,
+}
+
+impl std::ops::AddAssign for Counts {
+    fn add_assign(&mut self, other: Self) {
+        self.words += other.words;
+        self.characters += other.characters;
+    }
+}
+
+/// Count the words and characters of the visible text of spans, grouped into
+/// lines like [`lines`] does.
+///
+/// Spans are joined into lines first, so that words split across spans are
+/// counted once.
+#[must_use]
+pub fn counts(spans: &[TextSpan]) -> Counts {
+    let mut counts = Counts::default();
+
+    for line in lines(spans) {
+        let text = line.text();
+        counts.words += text.split_whitespace().count();
+        counts.characters += text.chars().filter(|c| !c.is_whitespace()).count();
+    }
+    counts
+}
+
+/// Extract the text spans shown by a page, in content stream order.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+pub fn page_spans(document: &Document, page_id: ObjectId) -> lopdf::Result<Vec<TextSpan>> {
+    extract(document, page_id, false)
+}
+
+/// Extract the glyphs shown by a page, in content stream order, as spans of
+/// a single character code each, e.g., to find the text under highlights.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+pub fn page_glyphs(document: &Document, page_id: ObjectId) -> lopdf::Result<Vec<TextSpan>> {
+    extract(document, page_id, true)
+}
+
+/// Extract the spans shown by a page, with one span per glyph if `glyphs`
+/// is set.
+fn extract(document: &Document, page_id: ObjectId, glyphs: bool) -> lopdf::Result<Vec<TextSpan>> {
+    let content = document.get_and_decode_page_content(page_id)?;
+    let mut extractor = Extractor {
+        document,
+        fonts: HashMap::new(),
+        spans: vec![],
+        glyphs,
+    };
+
+    extractor.run(
+        &content.operations,
+        document.page_resources(page_id),
+        Matrix::IDENTITY,
+        0,
+    );
+    Ok(extractor.spans)
+}
+
+/// Return the dominant orientation of text, i.e., the angle of the baseline
+/// of most characters, rounded to 0, 90, 180 or 270 degrees
+/// counterclockwise, with the fraction of characters that have it.
+///
+/// Invisible text is counted, as it is often an OCR layer that follows the
+/// orientation of scanned text.
+#[must_use]
+pub fn dominant_orientation(spans: &[TextSpan]) -> Option<(i64, f32)> {
+    let mut counts = [0usize; 4];
+
+    for span in spans {
+        let quadrant = (span.angle / 90.0).round().rem_euclid(4.0) as usize;
+        counts[quadrant % 4] += span.text.chars().filter(|c| !c.is_whitespace()).count();
+    }
+    let total: usize = counts.iter().sum();
+    let (quadrant, count) = counts
+        .into_iter()
+        .enumerate()
+        .max_by_key(|&(_, count)| count)?;
+
+    if total == 0 {
+        return None;
+    }
+    Some((quadrant as i64 * 90, count as f32 / total as f32))
+}
+
+/// Group spans into lines, from top to bottom.
+///
+/// Invisible, rotated and whitespace-only spans are ignored.
+#[must_use]
+pub fn lines(spans: &[TextSpan]) -> Vec<TextLine> {
+    let mut spans: Vec<&TextSpan> = spans
+        .iter()
+        .filter(|span| {
+            span.render_mode != 3 && span.angle.abs() < 1.0 && !span.text.trim().is_empty()
+        })
+        .collect();
+    spans.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let mut lines: Vec<TextLine> = vec![];
+
+    for span in spans {
+        match lines.last_mut() {
+            Some(line) if (line.y - span.y).abs() <= 0.4 * span.font_size.min(line.font_size()) => {
+                line.spans.push(span.clone());
+            },
+            _ => {
+                lines.push(TextLine {
+                    y: span.y,
+                    spans: vec![span.clone()],
+                });
+            },
+        }
+    }
+
+    for line in &mut lines {
+        line.spans.sort_by(|a, b| a.x.total_cmp(&b.x));
+    }
+    lines
+}
+
+/// Reorder lines of a page for reading, splitting the lines of two-column
+/// layouts into their columns.
+///
+/// Columns are separated by a vertical gutter that almost no line crosses.
+/// Lines crossing the gutter, like titles, are kept whole, and the columns
+/// between them are read left first.
+#[cfg(feature = "convert")]
+#[must_use]
+pub fn reading_order(lines: Vec<TextLine>) -> Vec<TextLine> {
+    let Some(gutter) = find_gutter(&lines) else {
+        return lines;
+    };
+    let mut ordered = vec![];
+    let mut left = vec![];
+    let mut right = vec![];
+
+    for line in lines {
+        if line
+            .spans
+            .iter()
+            .any(|span| span.x < gutter && span.end() > gutter)
+        {
+            ordered.append(&mut left);
+            ordered.append(&mut right);
+            ordered.push(line);
+            continue;
+        }
+        let (left_spans, right_spans): (Vec<_>, Vec<_>) = line
+            .spans
+            .into_iter()
+            .partition(|span| span.end() <= gutter);
+
+        for (spans, column) in [(left_spans, &mut left), (right_spans, &mut right)] {
+            if !spans.is_empty() {
+                column.push(TextLine { y: line.y, spans });
+            }
+        }
+    }
+    ordered.append(&mut left);
+    ordered.append(&mut right);
+    ordered
+}
+
+/// Return the horizontal position of the gutter between two columns, if any.
+#[cfg(feature = "convert")]
+fn find_gutter(lines: &[TextLine]) -> Option<f32> {
+    let segments: Vec<(f32, f32)> = lines
+        .iter()
+        .flat_map(|line| line.spans.iter().map(|span| (span.x, span.end())))
+        .collect();
+
+    let left = segments.iter().map(|s| s.0).fold(f32::MAX, f32::min);
+    let right = segments.iter().map(|s| s.1).fold(f32::MIN, f32::max);
+    if segments.len() < 10 || right <= left {
+        return None;
+    }
+
+    // Find the least crossed position in the middle of the text, preferring
+    // the center of the widest empty band.
+    let crossing = |x: f32| segments.iter().filter(|s| s.0 < x && x < s.1).count();
+    let candidates: Vec<(f32, usize)> = (0..=40)
+        .map(|i| {
+            let x = left + (right - left) * (0.3 + 0.01 * i as f32);
+            (x, crossing(x))
+        })
+        .collect();
+    let min = candidates.iter().map(|c| c.1).min()?;
+    let band: Vec<f32> = candidates
+        .iter()
+        .filter(|c| c.1 == min)
+        .map(|c| c.0)
+        .collect();
+    let gutter = band[band.len() / 2];
+
+    let left_count = segments.iter().filter(|s| s.1 <= gutter).count();
+    let right_count = segments.iter().filter(|s| s.0 >= gutter).count();
+    (min * 10 <= segments.len()
+        && left_count * 5 >= segments.len()
+        && right_count * 5 >= segments.len())
+    .then_some(gutter)
+}
+
+/// Font information needed to decode and measure shown strings.
+pub(super) struct Font {
+    /// Name of the font, without its subset prefix.
+    pub(super) name: String,
+    /// Whether character codes are two bytes long, as in composite fonts.
+    two_byte: bool,
+    /// Text of each character code.
+    unicode: HashMap<u32, String>,
+    /// Widths of glyphs by character code, in thousandths of the font size.
+    widths: HashMap<u32, f32>,
+    /// Width of glyphs missing from `widths`.
+    default_width: f32,
+    /// Standard font providing widths when the font has none.
+    standard: Option<StandardFont>,
+    /// Whether the font program is embedded, and hence possibly a subset.
+    #[cfg(feature = "regex")]
+    embedded: bool,
+}
+
+impl Font {
+    /// Read a font dictionary.
+    pub(super) fn new(document: &Document, font: &Dictionary) -> Self {
+        let name = font
+            .get(b"BaseFont")
+            .and_then(Object::as_name_str)
+            .unwrap_or_default();
+        let name = match name.split_once('+') {
+            Some((prefix, rest)) if prefix.len() == 6 => rest,
+            _ => name,
+        }
+        .to_string();
+        let two_byte = font.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0");
+        #[cfg(feature = "regex")]
+        let embedded = font
+            .get_deref(b"FontDescriptor", document)
+            .and_then(Object::as_dict)
+            .is_ok_and(|descriptor| {
+                [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"]
+                    .iter()
+                    .any(|key| descriptor.has(key))
+            });
+
+        let mut this = Self {
+            standard: (name.starts_with("Helvetica") || name.starts_with("Arial"))
+                .then_some(StandardFont::Helvetica),
+            name,
+            two_byte,
+            unicode: HashMap::new(),
+            widths: HashMap::new(),
+            default_width: if two_byte { 1000.0 } else { 500.0 },
+            #[cfg(feature = "regex")]
+            embedded,
+        };
+
+        if two_byte {
+            this.read_composite_widths(document, font);
+        } else {
+            this.read_simple_widths(document, font);
+            this.read_simple_encoding(document, font);
+        }
+        if let Ok(stream) = font
+            .get_deref(b"ToUnicode", document)
+            .and_then(Object::as_stream)
+        {
+            if let Ok(data) = stream.decompressed_content() {
+                this.read_to_unicode(&data);
+            }
+        }
+        this
+    }
+
+    /// Read `/FirstChar` and `/Widths` of a simple font.
+    fn read_simple_widths(&mut self, document: &Document, font: &Dictionary) {
+        if self.name.starts_with("Courier") {
+            self.default_width = 600.0;
+        }
+        if let Some(width) = font
+            .get_deref(b"FontDescriptor", document)
+            .and_then(Object::as_dict)
+            .and_then(|descriptor| descriptor.get(b"MissingWidth"))
+            .ok()
+            .and_then(number)
+        {
+            self.default_width = width;
+        }
+
+        let first = font.get(b"FirstChar").and_then(Object::as_i64).unwrap_or(0);
+        if let Ok(widths) = font
+            .get_deref(b"Widths", document)
+            .and_then(Object::as_array)
+        {
+            for (code, width) in (first..).zip(widths) {
+                if let (Ok(code), Some(width)) =
+                    (u32::try_from(code), deref_number(document, width))
+                {
+                    self.widths.insert(code, width);
+                }
+            }
+        }
+    }
+
+    /// Read `/DW` and `/W` of the descendant font of a composite font.
+    fn read_composite_widths(&mut self, document: &Document, font: &Dictionary) {
+        let Some(descendant) = font
+            .get_deref(b"DescendantFonts", document)
+            .and_then(Object::as_array)
+            .ok()
+            .and_then(|fonts| fonts.first())
+            .and_then(|font| document.dereference(font).ok())
+            .and_then(|(_, font)| font.as_dict().ok())
+        else {
+            return;
+        };
+
+        if let Some(width) = descendant.get(b"DW").ok().and_then(number) {
+            self.default_width = width;
+        }
+        let Ok(widths) = descendant
+            .get_deref(b"W", document)
+            .and_then(Object::as_array)
+        else {
+            return;
+        };
+
+        let mut entries = widths.iter();
+        while let Some(first) = entries
+            .next()
+            .and_then(|first| deref_number(document, first))
+        {
+            let first = first as u32;
+            match entries.next().map(|entry| document.dereference(entry)) {
+                Some(Ok((_, Object::Array(list)))) => {
+                    for (code, width) in (first..).zip(list) {
+                        if let Some(width) = deref_number(document, width) {
+                            self.widths.insert(code, width);
+                        }
+                    }
+                },
+                Some(Ok((_, last))) => {
+                    let (Some(last), Some(width)) = (
+                        number(last),
+                        entries
+                            .next()
+                            .and_then(|width| deref_number(document, width)),
+                    ) else {
+                        return;
+                    };
+                    for code in first..=last as u32 {
+                        self.widths.insert(code, width);
+                    }
+                },
+                _ => return,
+            }
+        }
+    }
+
+    /// Read the base encoding and differences of a simple font.
+    fn read_simple_encoding(&mut self, document: &Document, font: &Dictionary) {
+        let encoding = font.get_deref(b"Encoding", document).ok();
+        let base_encoding = match encoding {
+            Some(Object::Dictionary(encoding)) => encoding.get(b"BaseEncoding").ok(),
+            encoding => encoding,
+        }
+        .and_then(|name| name.as_name().ok());
+        let decode = match base_encoding {
+            Some(b"MacRomanEncoding") => decode_mac_roman,
+            _ => decode_win_ansi,
+        };
+        for byte in 0..=u8::MAX {
+            self.unicode
+                .insert(u32::from(byte), decode(byte).to_string());
+        }
+
+        let Some(differences) = encoding
+            .and_then(|encoding| encoding.as_dict().ok())
+            .and_then(|encoding| encoding.get_deref(b"Differences", document).ok())
+            .and_then(|differences| differences.as_array().ok())
+        else {
+            return;
+        };
+
+        let mut code = 0;
+        for entry in differences {
+            match entry {
+                Object::Integer(start) => code = u32::try_from(*start).unwrap_or(0),
+                Object::Name(name) => {
+                    if let Some(c) = std::str::from_utf8(name).ok().and_then(glyph_name_to_char) {
+                        self.unicode.insert(code, c.to_string());
+                    }
+                    code += 1;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Read the `bfchar` and `bfrange` mappings of a `/ToUnicode` CMap.
+    ///
+    /// CMaps use the same syntax as content streams, so the content parser
+    /// reports each mapping block as the operands of its closing keyword.
+    fn read_to_unicode(&mut self, data: &[u8]) {
+        let Ok(cmap) = Content::decode(data) else {
+            return;
+        };
+
+        for operation in &cmap.operations {
+            match operation.operator.as_str() {
+                "endcodespacerange" => {
+                    if let Some(Object::String(low, _)) = operation.operands.first() {
+                        self.two_byte = low.len() == 2;
+                    }
+                },
+                "endbfchar" => {
+                    for pair in operation.operands.chunks_exact(2) {
+                        if let [Object::String(code, _), Object::String(text, _)] = pair {
+                            self.unicode.insert(code_value(code), utf16_text(text));
+                        }
+                    }
+                },
+                "endbfrange" => {
+                    for triple in operation.operands.chunks_exact(3) {
+                        let [Object::String(low, _), Object::String(high, _), target] = triple
+                        else {
+                            continue;
+                        };
+                        let (low, high) = (code_value(low), code_value(high));
+
+                        match target {
+                            Object::String(text, _) => {
+                                let mut units = utf16_units(text);
+                                for code in low..=high.min(low + 0xFFFF) {
+                                    self.unicode.insert(code, String::from_utf16_lossy(&units));
+                                    if let Some(last) = units.last_mut() {
+                                        *last = last.wrapping_add(1);
+                                    }
+                                }
+                            },
+                            Object::Array(texts) => {
+                                for (code, text) in (low..=high).zip(texts) {
+                                    if let Object::String(text, _) = text {
+                                        self.unicode.insert(code, utf16_text(text));
+                                    }
+                                }
+                            },
+                            _ => {},
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Split a shown string into character codes.
+    pub(super) fn codes<'a>(&self, bytes: &'a [u8]) -> impl Iterator<Item = u32> + 'a {
+        let size = if self.two_byte { 2 } else { 1 };
+        bytes.chunks(size).map(code_value)
+    }
+
+    /// Return the text of a character code.
+    pub(super) fn text(&self, code: u32) -> String {
+        match self.unicode.get(&code) {
+            Some(text) => text.clone(),
+            None if self.two_byte => '\u{FFFD}'.to_string(),
+            None => decode_win_ansi(code as u8).to_string(),
+        }
+    }
+
+    /// Encode text as character codes of the font, or return `None` if a
+    /// character has no glyph in the font.
+    ///
+    /// Characters are encoded with the lowest code that decodes to them.
+    /// Embedded simple fonts are often subsets, so their codes without a
+    /// width are assumed to have no glyph.
+    #[cfg(feature = "regex")]
+    pub(super) fn encode(&self, text: &str) -> Option<Vec<u8>> {
+        let mut codes: HashMap<&str, u32> = HashMap::new();
+        for (&code, text) in &self.unicode {
+            if self.embedded
+                && !self.two_byte
+                && !self.widths.get(&code).is_some_and(|&width| width > 0.0)
+            {
+                continue;
+            }
+            codes
+                .entry(text)
+                .and_modify(|lowest| *lowest = (*lowest).min(code))
+                .or_insert(code);
+        }
+
+        let mut bytes = vec![];
+        let mut buffer = [0; 4];
+        for c in text.chars() {
+            let code = *codes.get(&*c.encode_utf8(&mut buffer))?;
+            if self.two_byte {
+                bytes.extend_from_slice(&u16::try_from(code).ok()?.to_be_bytes());
+            } else {
+                bytes.push(u8::try_from(code).ok()?);
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Return the width of a character code, in thousandths of the font size.
+    fn width(&self, code: u32) -> f32 {
+        match (self.widths.get(&code), self.standard) {
+            (Some(&width), _) => width,
+            (None, Some(standard)) if !self.two_byte => f32::from(standard.glyph_width(code as u8)),
+            (None, _) => self.default_width,
+        }
+    }
+}
+
+/// Return a number, following a reference if needed.
+fn deref_number(document: &Document, object: &Object) -> Option<f32> {
+    document
+        .dereference(object)
+        .ok()
+        .and_then(|(_, object)| number(object))
+}
+
+/// Return the big-endian value of a character code.
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | u32::from(byte))
+}
+
+/// Return the UTF-16BE code units of a string.
+fn utf16_units(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)]))
+        .collect()
+}
+
+/// Decode a UTF-16BE string.
+fn utf16_text(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(bytes))
+}
+
+/// Graphics and text state parameters that matter for text extraction.
+#[derive(Clone)]
+struct State {
+    ctm: Matrix,
+    font: Option<Rc<Font>>,
+    font_size: f32,
+    char_spacing: f32,
+    word_spacing: f32,
+    horizontal_scaling: f32,
+    leading: f32,
+    rise: f32,
+    render_mode: i64,
+}
+
+/// Interpreter of content streams that collects text spans.
+struct Extractor<'a> {
+    document: &'a Document,
+    fonts: HashMap<ObjectId, Rc<Font>>,
+    spans: Vec<TextSpan>,
+    /// Record one span per glyph, rather than per shown string.
+    glyphs: bool,
+}
+
+impl Extractor<'_> {
+    /// Interpret operations with the given resources and initial CTM.
+    fn run(
+        &mut self,
+        operations: &[Operation],
+        resources: Option<&Dictionary>,
+        ctm: Matrix,
+        depth: usize,
+    ) {
+        let mut stack = vec![];
+        let mut state = State {
+            ctm,
+            font: None,
+            font_size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scaling: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+            render_mode: 0,
+        };
+        let mut text_matrix = Matrix::IDENTITY;
+        let mut line_matrix = Matrix::IDENTITY;
+
+        for operation in operations {
+            let operands = &operation.operands;
+            let first = operands.first().and_then(number).unwrap_or(0.0);
+
+            match operation.operator.as_str() {
+                "q" => stack.push(state.clone()),
+                "Q" => {
+                    if let Some(saved) = stack.pop() {
+                        state = saved;
+                    }
+                },
+                "cm" => {
+                    if let Some(matrix) = Matrix::from_operands(operands) {
+                        state.ctm = matrix.then(&state.ctm);
+                    }
+                },
+                "BT" => {
+                    text_matrix = Matrix::IDENTITY;
+                    line_matrix = Matrix::IDENTITY;
+                },
+                "Tc" => state.char_spacing = first,
+                "Tw" => state.word_spacing = first,
+                "Tz" => state.horizontal_scaling = first / 100.0,
+                "TL" => state.leading = first,
+                "Ts" => state.rise = first,
+                "Tr" => state.render_mode = first as i64,
+                "Tf" => {
+                    state.font = operands
+                        .first()
+                        .and_then(|name| name.as_name().ok())
+                        .and_then(|name| self.font(resources, name));
+                    state.font_size = operands.get(1).and_then(number).unwrap_or(0.0);
+                },
+                "Td" | "TD" => {
+                    let ty = operands.get(1).and_then(number).unwrap_or(0.0);
+                    if operation.operator == "TD" {
+                        state.leading = -ty;
+                    }
+                    line_matrix = Matrix::new(1.0, 0.0, 0.0, 1.0, first, ty).then(&line_matrix);
+                    text_matrix = line_matrix;
+                },
+                "Tm" => {
+                    if let Some(matrix) = Matrix::from_operands(operands) {
+                        line_matrix = matrix;
+                        text_matrix = matrix;
+                    }
+                },
+                "T*" => {
+                    line_matrix =
+                        Matrix::new(1.0, 0.0, 0.0, 1.0, 0.0, -state.leading).then(&line_matrix);
+                    text_matrix = line_matrix;
+                },
+                "Tj" | "'" | "\"" => {
+                    if operation.operator != "Tj" {
+                        if operation.operator == "\"" {
+                            state.word_spacing = first;
+                            state.char_spacing = operands.get(1).and_then(number).unwrap_or(0.0);
+                        }
+                        line_matrix =
+                            Matrix::new(1.0, 0.0, 0.0, 1.0, 0.0, -state.leading).then(&line_matrix);
+                        text_matrix = line_matrix;
+                    }
+                    if let Some(Object::String(bytes, _)) = operands.last() {
+                        let mut span = self.start_span(&state, &text_matrix);
+                        self.show(&state, &mut text_matrix, bytes, &mut span);
+                        self.finish_span(&state, &text_matrix, span);
+                    }
+                },
+                "TJ" => {
+                    let Some(Object::Array(items)) = operands.first() else {
+                        continue;
+                    };
+                    let mut span = self.start_span(&state, &text_matrix);
+
+                    for item in items {
+                        match item {
+                            Object::String(bytes, _) => {
+                                self.show(&state, &mut text_matrix, bytes, &mut span);
+                            },
+                            item => {
+                                let Some(adjustment) = number(item) else {
+                                    continue;
+                                };
+                                if -adjustment >= SPLIT_ADJUSTMENT {
+                                    self.finish_span(&state, &text_matrix, span);
+                                    span = PendingSpan::default();
+                                } else if -adjustment >= SPACE_ADJUSTMENT
+                                    && !span.text.is_empty()
+                                    && !span.text.ends_with(' ')
+                                {
+                                    span.text.push(' ');
+                                }
+                                let tx = -adjustment / 1000.0
+                                    * state.font_size
+                                    * state.horizontal_scaling;
+                                text_matrix =
+                                    Matrix::new(1.0, 0.0, 0.0, 1.0, tx, 0.0).then(&text_matrix);
+                                if span.text.is_empty() {
+                                    span = self.start_span(&state, &text_matrix);
+                                }
+                            },
+                        }
+                    }
+                    self.finish_span(&state, &text_matrix, span);
+                },
+                "Do" if depth < MAX_FORM_DEPTH => {
+                    if let Some(name) = operands.first().and_then(|name| name.as_name().ok()) {
+                        self.form(resources, name, state.ctm, depth);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Return the font of the given resource name, reading it on first use.
+    fn font(&mut self, resources: Option<&Dictionary>, name: &[u8]) -> Option<Rc<Font>> {
+        let object = resources?
+            .get_deref(b"Font", self.document)
+            .and_then(Object::as_dict)
+            .and_then(|fonts| fonts.get(name))
+            .ok()?;
+
+        match object {
+            Object::Reference(id) => {
+                if let Some(font) = self.fonts.get(id) {
+                    return Some(font.clone());
+                }
+                let font = Rc::new(Font::new(
+                    self.document,
+                    self.document.get_dictionary(*id).ok()?,
+                ));
+                self.fonts.insert(*id, font.clone());
+                Some(font)
+            },
+            Object::Dictionary(dictionary) => Some(Rc::new(Font::new(self.document, dictionary))),
+            _ => None,
+        }
+    }
+
+    /// Extract the text of a form XObject.
+    fn form(&mut self, resources: Option<&Dictionary>, name: &[u8], ctm: Matrix, depth: usize) {
+        let Some(stream) = resources
+            .and_then(|resources| {
+                resources
+                    .get_deref(b"XObject", self.document)
+                    .and_then(Object::as_dict)
+                    .ok()
+            })
+            .and_then(|xobjects| xobjects.get_deref(name, self.document).ok())
+            .and_then(|object| object.as_stream().ok())
+        else {
+            return;
+        };
+        if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Form") {
+            return;
+        }
+        let Some(content) = stream
+            .decompressed_content()
+            .ok()
+            .and_then(|data| Content::decode(&data).ok())
+        else {
+            return;
+        };
+
+        let matrix = stream
+            .dict
+            .get(b"Matrix")
+            .and_then(Object::as_array)
+            .ok()
+            .and_then(|matrix| Matrix::from_operands(matrix))
+            .unwrap_or(Matrix::IDENTITY);
+        let form_resources = stream
+            .dict
+            .get_deref(b"Resources", self.document)
+            .and_then(Object::as_dict)
+            .ok()
+            .or(resources);
+
+        self.run(
+            &content.operations,
+            form_resources,
+            matrix.then(&ctm),
+            depth + 1,
+        );
+    }
+
+    /// Start a span at the current text position.
+    fn start_span(&self, state: &State, text_matrix: &Matrix) -> PendingSpan {
+        let matrix = text_matrix.then(&state.ctm);
+        let (x, y) = matrix.apply(0.0, state.rise);
+
+        PendingSpan {
+            text: String::new(),
+            x,
+            y,
+            font_size: state.font_size * matrix.c.hypot(matrix.d),
+            angle: matrix.b.atan2(matrix.a).to_degrees(),
+        }
+    }
+
+    /// Decode a shown string and advance the text matrix past it.
+    fn show(
+        &mut self,
+        state: &State,
+        text_matrix: &mut Matrix,
+        bytes: &[u8],
+        span: &mut PendingSpan,
+    ) {
+        let Some(font) = &state.font else {
+            return;
+        };
+
+        for code in font.codes(bytes) {
+            let glyph = self.glyphs.then(|| self.start_span(state, text_matrix));
+            span.text.push_str(&font.text(code));
+
+            let mut tx = font.width(code) / 1000.0 * state.font_size + state.char_spacing;
+            if !font.two_byte && code == 32 {
+                tx += state.word_spacing;
+            }
+            *text_matrix = Matrix::new(1.0, 0.0, 0.0, 1.0, tx * state.horizontal_scaling, 0.0)
+                .then(text_matrix);
+
+            if let Some(mut glyph) = glyph {
+                glyph.text = font.text(code);
+                self.finish_span(state, text_matrix, glyph);
+            }
+        }
+        if self.glyphs {
+            span.text.clear();
+        }
+    }
+
+    /// Record a span, ending at the current text position.
+    fn finish_span(&mut self, state: &State, text_matrix: &Matrix, span: PendingSpan) {
+        if span.text.is_empty() {
+            return;
+        }
+        let (x, y) = text_matrix.then(&state.ctm).apply(0.0, state.rise);
+
+        self.spans.push(TextSpan {
+            text: span.text,
+            x: span.x,
+            y: span.y,
+            width: (x - span.x).hypot(y - span.y),
+            font_size: span.font_size,
+            #[cfg(feature = "convert")]
+            font: state
+                .font
+                .as_ref()
+                .map(|font| font.name.clone())
+                .unwrap_or_default(),
+            angle: span.angle,
+            render_mode: state.render_mode,
+        });
+    }
+}
+
+/// A span whose end is not known yet.
+#[derive(Default)]
+struct PendingSpan {
+    text: String,
+    x: f32,
+    y: f32,
+    font_size: f32,
+    angle: f32,
+}
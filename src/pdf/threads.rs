@@ -0,0 +1,205 @@
+//! Article threads, i.e., chains of beads that let readers follow an
+//! article across columns and pages, as found in magazines.
+//!
+//! A thread, listed in the catalog's `/Threads`, points to its first bead
+//! with `/F`. Beads form a circular list, with `/N` and `/V` pointing to the
+//! next and previous beads, and each bead points to its page with `/P` and is
+//! listed in the page's `/B`.
+
+use std::collections::HashSet;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::Serialize;
+
+use super::{content::number, pdfstring};
+
+/// Bead of a thread, i.e., a region of a page.
+#[derive(Clone, Debug, Serialize)]
+pub struct Bead {
+    /// Page number, or `None` if the bead points to no page of the document.
+    pub page: Option<u32>,
+    /// Region of the page, as `[x0, y0, x1, y1]`.
+    pub rect: Option<[f32; 4]>,
+}
+
+/// Article thread.
+#[derive(Clone, Debug, Serialize)]
+pub struct Thread {
+    /// Title of the article, from the thread information dictionary.
+    pub title: Option<String>,
+    pub beads: Vec<Bead>,
+}
+
+/// Beads and threads removed by [`repair`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Repair {
+    pub beads: usize,
+    pub threads: usize,
+}
+
+/// Return the object IDs of the threads of a document.
+fn thread_ids(document: &Document) -> Vec<ObjectId> {
+    document
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get_deref(b"Threads", document).ok())
+        .and_then(|threads| threads.as_array().ok())
+        .map(|threads| {
+            threads
+                .iter()
+                .filter_map(|thread| thread.as_reference().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return the object IDs of the beads of a thread, in order, following `/N`
+/// from `/F` until the chain loops or breaks.
+fn bead_ids(document: &Document, thread_id: ObjectId) -> Vec<ObjectId> {
+    let mut beads = vec![];
+    let mut seen = HashSet::new();
+    let mut next = document
+        .get_dictionary(thread_id)
+        .and_then(|thread| thread.get(b"F"))
+        .and_then(Object::as_reference)
+        .ok();
+
+    while let Some(id) = next {
+        if !seen.insert(id) {
+            break;
+        }
+        let Ok(bead) = document.get_dictionary(id) else {
+            break;
+        };
+        beads.push(id);
+        next = bead.get(b"N").and_then(Object::as_reference).ok();
+    }
+    beads
+}
+
+/// Return the page that a bead points to, if it is a page of the document.
+fn bead_page(bead: &Dictionary, pages: &[(u32, ObjectId)]) -> Option<u32> {
+    let page_id = bead.get(b"P").and_then(Object::as_reference).ok()?;
+    pages
+        .iter()
+        .find(|&&(_, id)| id == page_id)
+        .map(|&(page, _)| page)
+}
+
+/// Return the article threads of a document.
+#[must_use]
+pub fn threads(document: &Document) -> Vec<Thread> {
+    let pages: Vec<(u32, ObjectId)> = document.get_pages().into_iter().collect();
+
+    thread_ids(document)
+        .into_iter()
+        .filter_map(|thread_id| {
+            let thread = document.get_dictionary(thread_id).ok()?;
+            let title = thread
+                .get_deref(b"I", document)
+                .and_then(Object::as_dict)
+                .and_then(|info| info.get_deref(b"Title", document))
+                .and_then(pdfstring::decode_object)
+                .ok();
+            let beads = bead_ids(document, thread_id)
+                .into_iter()
+                .filter_map(|id| document.get_dictionary(id).ok())
+                .map(|bead| {
+                    let rect = bead
+                        .get_deref(b"R", document)
+                        .and_then(Object::as_array)
+                        .ok()
+                        .and_then(|rect| {
+                            match rect.iter().filter_map(number).collect::<Vec<_>>()[..] {
+                                [x0, y0, x1, y1] => Some([x0, y0, x1, y1]),
+                                _ => None,
+                            }
+                        });
+                    Bead {
+                        page: bead_page(bead, &pages),
+                        rect,
+                    }
+                })
+                .collect();
+            Some(Thread { title, beads })
+        })
+        .collect()
+}
+
+/// Remove the beads that point to no page of the document, e.g., after pages
+/// were removed, and link the remaining beads of each thread again.
+///
+/// Threads left without beads are removed from the catalog. Removed beads and
+/// threads stay in the document until objects are pruned.
+pub fn repair(document: &mut Document) -> Repair {
+    let pages: Vec<(u32, ObjectId)> = document.get_pages().into_iter().collect();
+    let thread_ids = thread_ids(document);
+    let mut repair = Repair::default();
+    let mut kept_threads = vec![];
+
+    for thread_id in thread_ids {
+        let beads = bead_ids(document, thread_id);
+        let kept: Vec<ObjectId> = beads
+            .iter()
+            .copied()
+            .filter(|&id| {
+                document
+                    .get_dictionary(id)
+                    .is_ok_and(|bead| bead_page(bead, &pages).is_some())
+            })
+            .collect();
+        repair.beads += beads.len() - kept.len();
+
+        if kept.is_empty() {
+            repair.threads += 1;
+            continue;
+        }
+        kept_threads.push(Object::Reference(thread_id));
+        if kept.len() == beads.len() {
+            continue;
+        }
+        if let Ok(thread) = document.get_dictionary_mut(thread_id) {
+            thread.set("F", kept[0]);
+        }
+        for (i, &id) in kept.iter().enumerate() {
+            let next = kept[(i + 1) % kept.len()];
+            let previous = kept[(i + kept.len() - 1) % kept.len()];
+            if let Ok(bead) = document.get_dictionary_mut(id) {
+                bead.set("T", thread_id);
+                bead.set("N", next);
+                bead.set("V", previous);
+            }
+        }
+    }
+    if repair.beads == 0 && repair.threads == 0 {
+        return repair;
+    }
+
+    // Pages may still list beads removed from their thread.
+    let kept_beads: HashSet<ObjectId> = kept_threads
+        .iter()
+        .filter_map(|thread| thread.as_reference().ok())
+        .flat_map(|thread_id| bead_ids(document, thread_id))
+        .collect();
+    for &(_, page_id) in &pages {
+        if let Ok(page) = document.get_dictionary_mut(page_id) {
+            if let Ok(Object::Array(beads)) = page.get_mut(b"B") {
+                beads.retain(|bead| {
+                    bead.as_reference()
+                        .is_ok_and(|bead| kept_beads.contains(&bead))
+                });
+                if beads.is_empty() {
+                    page.remove(b"B");
+                }
+            }
+        }
+    }
+    if let Ok(catalog) = document.catalog_mut() {
+        if kept_threads.is_empty() {
+            catalog.remove(b"Threads");
+        } else {
+            catalog.set("Threads", kept_threads);
+        }
+    }
+    repair
+}
@@ -0,0 +1,355 @@
+//! Detection of potentially malicious constructs, for triage.
+//!
+//! Unlike [`sanitize`](super::sanitize), nothing is modified here: findings
+//! are only reported, with a severity.
+
+use std::cmp::Reverse;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::Serialize;
+
+use super::{pdfstring, reference};
+
+/// Inline nesting depth of arrays and dictionaries above which an object is
+/// reported, as legitimate files rarely nest more than a few levels.
+const MAX_NESTING_DEPTH: usize = 32;
+
+/// File extensions of embedded files that can be run directly.
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "app", "bat", "cmd", "com", "cpl", "dll", "exe", "hta", "jar", "js", "lnk", "msi", "ps1",
+    "scr", "sh", "vbs", "wsf",
+];
+
+/// Magic numbers of executable formats: PE, ELF and Mach-O.
+const EXECUTABLE_MAGICS: &[&[u8]] = &[
+    b"MZ",
+    b"\x7FELF",
+    b"\xFE\xED\xFA\xCE",
+    b"\xFE\xED\xFA\xCF",
+    b"\xCE\xFA\xED\xFE",
+    b"\xCF\xFA\xED\xFE",
+];
+
+/// URI schemes that run code when followed.
+const SCRIPT_SCHEMES: &[&str] = &["javascript", "vbscript", "data"];
+
+/// URI schemes that access local or network files, possibly leaking
+/// credentials.
+const FILE_SCHEMES: &[&str] = &["file", "smb"];
+
+/// URI schemes that are expected in links.
+const SAFE_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// How dangerous a finding is.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+/// A kind of potentially malicious construct.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingKind {
+    #[serde(rename = "javascript")]
+    JavaScript,
+    OpenAction,
+    AdditionalActions,
+    LaunchAction,
+    SubmitForm,
+    ImportData,
+    EmbeddedFile,
+    EmbeddedExecutable,
+    SuspiciousUri,
+    DeepNesting,
+}
+
+/// A potentially malicious construct found in a document.
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub severity: Severity,
+    /// Object holding the construct, e.g., `12 0 R`.
+    pub object: Option<String>,
+    pub description: String,
+}
+
+/// Return a string object as text.
+fn text(object: &Object) -> Option<String> {
+    pdfstring::decode_object(object).ok()
+}
+
+/// Return the inline nesting depth of an object.
+fn nesting_depth(object: &Object) -> usize {
+    match object {
+        Object::Array(array) => 1 + array.iter().map(nesting_depth).max().unwrap_or(0),
+        Object::Dictionary(dict) => {
+            1 + dict
+                .iter()
+                .map(|(_, value)| nesting_depth(value))
+                .max()
+                .unwrap_or(0)
+        },
+        Object::Stream(stream) => {
+            stream
+                .dict
+                .iter()
+                .map(|(_, value)| nesting_depth(value))
+                .max()
+                .unwrap_or(0)
+                + 1
+        },
+        _ => 0,
+    }
+}
+
+/// Collects findings while walking a document.
+struct Scanner<'a> {
+    document: &'a Document,
+    findings: Vec<Finding>,
+}
+
+impl Scanner<'_> {
+    fn report(
+        &mut self,
+        kind: FindingKind,
+        severity: Severity,
+        id: Option<ObjectId>,
+        description: String,
+    ) {
+        self.findings.push(Finding {
+            kind,
+            severity,
+            object: id.map(reference),
+            description,
+        });
+    }
+
+    /// Check an action dictionary.
+    fn action(&mut self, dict: &Dictionary, id: Option<ObjectId>) {
+        if dict.has(b"JS") {
+            let script = dict
+                .get_deref(b"JS", self.document)
+                .ok()
+                .and_then(|js| {
+                    text(js).or_else(|| {
+                        js.as_stream()
+                            .ok()?
+                            .decompressed_content()
+                            .ok()
+                            .map(|content| String::from_utf8_lossy(&content).into_owned())
+                    })
+                })
+                .unwrap_or_default();
+            let preview: String = script.chars().take(80).collect();
+            self.report(
+                FindingKind::JavaScript,
+                Severity::High,
+                id,
+                format!("JavaScript action: {preview:?}"),
+            );
+            return;
+        }
+
+        match dict.get(b"S").and_then(Object::as_name).unwrap_or_default() {
+            b"Launch" => {
+                let target = dict
+                    .get_deref(b"F", self.document)
+                    .ok()
+                    .and_then(|file| {
+                        text(file).or_else(|| {
+                            file.as_dict()
+                                .ok()
+                                .and_then(|spec| text(spec.get(b"F").ok()?))
+                        })
+                    })
+                    .or_else(|| {
+                        let windows = dict.get(b"Win").and_then(Object::as_dict).ok()?;
+                        text(windows.get(b"F").ok()?)
+                    })
+                    .unwrap_or_default();
+                self.report(
+                    FindingKind::LaunchAction,
+                    Severity::High,
+                    id,
+                    format!("Launch action running {target:?}"),
+                );
+            },
+            b"SubmitForm" => {
+                self.report(
+                    FindingKind::SubmitForm,
+                    Severity::Medium,
+                    id,
+                    "Action sending form data".to_string(),
+                );
+            },
+            b"ImportData" => {
+                self.report(
+                    FindingKind::ImportData,
+                    Severity::Medium,
+                    id,
+                    "Action importing form data from a file".to_string(),
+                );
+            },
+            b"URI" => {
+                if let Some(uri) = dict.get(b"URI").ok().and_then(text) {
+                    self.uri(&uri, id);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Check the scheme of a URI action.
+    fn uri(&mut self, uri: &str, id: Option<ObjectId>) {
+        let scheme = uri
+            .split_once(':')
+            .map(|(scheme, _)| scheme.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        let severity = if uri.starts_with("\\\\")
+            || FILE_SCHEMES.contains(&scheme.as_str())
+            || SCRIPT_SCHEMES.contains(&scheme.as_str())
+        {
+            Severity::High
+        } else if scheme.is_empty() || SAFE_SCHEMES.contains(&scheme.as_str()) {
+            return;
+        } else {
+            Severity::Medium
+        };
+        self.report(
+            FindingKind::SuspiciousUri,
+            severity,
+            id,
+            format!("Link to {uri:?}"),
+        );
+    }
+
+    /// Check a file specification with an embedded file.
+    fn embedded_file(&mut self, spec: &Dictionary, id: Option<ObjectId>) {
+        let name = spec
+            .get(b"UF")
+            .or_else(|_| spec.get(b"F"))
+            .ok()
+            .and_then(text)
+            .unwrap_or_default();
+        let extension = name
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase())
+            .unwrap_or_default();
+        let content = spec
+            .get_deref(b"EF", self.document)
+            .and_then(Object::as_dict)
+            .and_then(|files| files.get_deref(b"F", self.document))
+            .and_then(Object::as_stream)
+            .ok()
+            .map(|stream| {
+                stream
+                    .decompressed_content()
+                    .unwrap_or_else(|_| stream.content.clone())
+            })
+            .unwrap_or_default();
+
+        if EXECUTABLE_EXTENSIONS.contains(&extension.as_str())
+            || EXECUTABLE_MAGICS
+                .iter()
+                .any(|magic| content.starts_with(magic))
+        {
+            self.report(
+                FindingKind::EmbeddedExecutable,
+                Severity::High,
+                id,
+                format!("Embedded executable {name:?}"),
+            );
+        } else {
+            self.report(
+                FindingKind::EmbeddedFile,
+                Severity::Low,
+                id,
+                format!("Embedded file {name:?}"),
+            );
+        }
+    }
+
+    /// Check a dictionary and the dictionaries it contains inline.
+    fn dictionary(&mut self, dict: &Dictionary, id: Option<ObjectId>) {
+        if dict.has(b"S") || dict.has(b"JS") {
+            self.action(dict, id);
+        }
+        if dict.has(b"EF") {
+            self.embedded_file(dict, id);
+        }
+        if dict.has(b"AA") {
+            self.report(
+                FindingKind::AdditionalActions,
+                Severity::Medium,
+                id,
+                "Actions triggered by viewer events".to_string(),
+            );
+        }
+        for (_, value) in dict.iter() {
+            self.object(value, id);
+        }
+    }
+
+    /// Check an object and the objects it contains inline.
+    fn object(&mut self, object: &Object, id: Option<ObjectId>) {
+        match object {
+            Object::Dictionary(dict) => self.dictionary(dict, id),
+            Object::Stream(stream) => self.dictionary(&stream.dict, id),
+            Object::Array(array) => {
+                for item in array {
+                    self.object(item, id);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Scan a document for potentially malicious constructs: JavaScript, launch
+/// actions, form data exchanges, embedded (executable) files, links with
+/// suspicious URI schemes and abnormally deep nesting.
+#[must_use]
+pub fn scan(document: &Document) -> Vec<Finding> {
+    let mut scanner = Scanner {
+        document,
+        findings: vec![],
+    };
+
+    if let Ok(catalog) = document.catalog() {
+        if catalog.has(b"OpenAction") {
+            let id = catalog
+                .get(b"OpenAction")
+                .and_then(Object::as_reference)
+                .ok();
+            scanner.report(
+                FindingKind::OpenAction,
+                Severity::Medium,
+                id,
+                "Action run when the document is opened".to_string(),
+            );
+        }
+    }
+
+    for (&id, object) in &document.objects {
+        let depth = nesting_depth(object);
+        if depth > MAX_NESTING_DEPTH {
+            scanner.report(
+                FindingKind::DeepNesting,
+                Severity::Medium,
+                Some(id),
+                format!("Objects nested {depth} levels deep"),
+            );
+            continue;
+        }
+        scanner.object(object, Some(id));
+    }
+
+    scanner
+        .findings
+        .sort_by_key(|finding| Reverse(finding.severity));
+    scanner.findings
+}
@@ -0,0 +1,126 @@
+//! Page thumbnails, i.e., small images of pages embedded as `/Thumb`, which
+//! some viewers show in their page panel instead of rendering pages.
+
+use lopdf::Document;
+#[cfg(feature = "images")]
+use lopdf::{ObjectId, Stream, dictionary};
+
+#[cfg(feature = "images")]
+use super::{document::DocumentExt, images};
+
+/// Remove the thumbnails of all pages, and return the number of removed
+/// thumbnails.
+pub fn remove(document: &mut Document) -> usize {
+    let mut removed = 0;
+
+    for page_id in document.get_pages().into_values() {
+        if let Ok(page) = document.get_dictionary_mut(page_id) {
+            if page.remove(b"Thumb").is_some() {
+                removed += 1;
+            }
+        }
+    }
+    if removed > 0 {
+        document.prune_objects();
+    }
+    removed
+}
+
+/// Return the gray thumbnail of an image-only page, e.g., a scan, at the
+/// given resolution, as its width, height and pixels, row by row from the
+/// top.
+///
+/// Pixels of the thumbnail average the pixels of the image they cover, and
+/// parts of the page that the image does not cover are white. Returns `None`
+/// for other pages, which cannot be rendered, or if the image cannot be
+/// decoded.
+///
+/// # Errors
+///
+/// Fails if the page content cannot be decoded.
+#[cfg(feature = "images")]
+pub fn scan_thumbnail(
+    document: &Document,
+    page_id: ObjectId,
+    dpi: f32,
+) -> lopdf::Result<Option<(u32, u32, Vec<u8>)>> {
+    let Some([x0, y0, x1, y1]) = document.page_crop_box(page_id) else {
+        return Ok(None);
+    };
+    let Some(image) = images::page_scan(document, page_id)? else {
+        return Ok(None);
+    };
+    let Some((width, height, pixels)) = images::luminance(document, image.stream) else {
+        return Ok(None);
+    };
+    let scale = dpi / 72.0;
+    let thumb_width = ((x1 - x0).abs() * scale).round().max(1.0) as u32;
+    let thumb_height = ((y1 - y0).abs() * scale).round().max(1.0) as u32;
+    let ctm = image.ctm;
+
+    // Return the range of image pixels covered by an interval of the unit
+    // square, along an axis of `size` pixels.
+    let span = |start: f32, end: f32, size: u32| {
+        let (start, end) = (start.min(end).max(0.0), start.max(end).min(1.0));
+        if start >= end {
+            return 0..0;
+        }
+        let first = (start * size as f32).floor() as u32;
+        let last = ((end * size as f32).ceil() as u32).min(size);
+        first..last.max(first + 1).min(size)
+    };
+    let mut thumb = Vec::with_capacity((thumb_width * thumb_height) as usize);
+
+    for row in 0..thumb_height {
+        // Rows go from the top of the page.
+        let top = y1.max(y0) - row as f32 / scale;
+        let bottom = top - 1.0 / scale;
+        // Image rows go from the top of the unit square, too.
+        let rows = span(
+            1.0 - (top - ctm.f) / ctm.d,
+            1.0 - (bottom - ctm.f) / ctm.d,
+            height,
+        );
+
+        for column in 0..thumb_width {
+            let left = x0.min(x1) + column as f32 / scale;
+            let right = left + 1.0 / scale;
+            let columns = span((left - ctm.e) / ctm.a, (right - ctm.e) / ctm.a, width);
+
+            let (mut sum, mut count) = (0u64, 0u64);
+            for y in rows.clone() {
+                for x in columns.clone() {
+                    sum += u64::from(pixels[(y * width + x) as usize]);
+                    count += 1;
+                }
+            }
+            thumb.push(sum.checked_div(count).map_or(255, |mean| mean as u8));
+        }
+    }
+    Ok(Some((thumb_width, thumb_height, thumb)))
+}
+
+/// Set the thumbnail of a page from gray pixels, row by row from the top.
+///
+/// A previous thumbnail of the page is replaced, but not removed from the
+/// document.
+#[cfg(feature = "images")]
+pub fn set_thumbnail(
+    document: &mut Document,
+    page_id: ObjectId,
+    (width, height, pixels): (u32, u32, Vec<u8>),
+) -> lopdf::Result<()> {
+    let mut stream = Stream::new(
+        dictionary! {
+            "Width" => i64::from(width),
+            "Height" => i64::from(height),
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+        },
+        pixels,
+    );
+    let _ = stream.compress();
+    let thumb_id = document.add_object(stream);
+    document.get_dictionary_mut(page_id)?.set("Thumb", thumb_id);
+    Ok(())
+}
@@ -0,0 +1,196 @@
+//! Transformations of annotations between pages, e.g., to move annotations
+//! to a copy of their page that has another size or rotation.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use super::{
+    content::{Matrix, number},
+    document::DocumentExt,
+};
+
+/// Entries of annotations that hold points, as flat arrays of coordinates.
+const POINT_KEYS: &[&[u8]] = &[b"QuadPoints", b"Vertices", b"L", b"CL"];
+
+/// Return the matrix that maps the default user space of a page to the unit
+/// square as the page is shown, i.e., after its crop box is rotated, with
+/// `(0, 0)` at the bottom left and `(1, 1)` at the top right.
+#[must_use]
+pub fn page_to_unit(document: &Document, page_id: ObjectId) -> Option<Matrix> {
+    let [x0, y0, x1, y1] = document.page_crop_box(page_id)?;
+    let (width, height) = (x1 - x0, y1 - y0);
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+    let normalize = Matrix::new(
+        1.0 / width,
+        0.0,
+        0.0,
+        1.0 / height,
+        -x0 / width,
+        -y0 / height,
+    );
+    // Pages are rotated clockwise.
+    let rotate = match document.page_rotation(page_id) {
+        90 => Matrix::new(0.0, -1.0, 1.0, 0.0, 0.0, 1.0),
+        180 => Matrix::new(-1.0, 0.0, 0.0, -1.0, 1.0, 1.0),
+        270 => Matrix::new(0.0, 1.0, -1.0, 0.0, 1.0, 0.0),
+        _ => Matrix::IDENTITY,
+    };
+    Some(normalize.then(&rotate))
+}
+
+/// Return the matrix that maps points of a page to the points shown at the
+/// same place of another page, accounting for their sizes and rotations.
+#[must_use]
+pub fn page_to_page(
+    from: &Document,
+    from_page: ObjectId,
+    to: &Document,
+    to_page: ObjectId,
+) -> Option<Matrix> {
+    Some(page_to_unit(from, from_page)?.then(&page_to_unit(to, to_page)?.inverse()?))
+}
+
+/// Return whether a matrix leaves points where they are, up to a fraction
+/// of a point.
+#[must_use]
+pub fn is_identity(matrix: &Matrix) -> bool {
+    let Matrix { a, b, c, d, e, f } = *matrix;
+    [a - 1.0, b, c, d - 1.0]
+        .iter()
+        .all(|value| value.abs() < 1e-4)
+        && e.abs() < 0.01
+        && f.abs() < 0.01
+}
+
+/// Transform the pairs of coordinates of an array in place.
+fn transform_points(values: &mut [Object], matrix: &Matrix) {
+    for pair in values.chunks_exact_mut(2) {
+        if let (Some(x), Some(y)) = (number(&pair[0]), number(&pair[1])) {
+            let (x, y) = matrix.apply(x, y);
+            pair[0] = Object::Real(x);
+            pair[1] = Object::Real(y);
+        }
+    }
+}
+
+/// Transform the rectangle of an annotation, which becomes the bounding box
+/// of its transformed corners.
+fn transform_rect(annotation: &mut Dictionary, matrix: &Matrix) {
+    let Some([x0, y0, x1, y1]) = annotation
+        .get(b"Rect")
+        .and_then(Object::as_array)
+        .ok()
+        .and_then(|rect| {
+            let values: Vec<f32> = rect.iter().filter_map(number).collect();
+            <[f32; 4]>::try_from(values).ok()
+        })
+    else {
+        return;
+    };
+    let corners = [(x0, y0), (x0, y1), (x1, y0), (x1, y1)].map(|(x, y)| matrix.apply(x, y));
+    let xs = corners.map(|(x, _)| x);
+    let ys = corners.map(|(_, y)| y);
+    let min = |values: [f32; 4]| values.into_iter().fold(f32::INFINITY, f32::min);
+    let max = |values: [f32; 4]| values.into_iter().fold(f32::NEG_INFINITY, f32::max);
+    annotation.set(
+        "Rect",
+        vec![min(xs), min(ys), max(xs), max(ys)]
+            .into_iter()
+            .map(Object::Real)
+            .collect::<Vec<_>>(),
+    );
+}
+
+/// Return the appearance streams of an annotation, for all its states.
+fn appearance_streams(document: &Document, id: ObjectId) -> Vec<ObjectId> {
+    let Ok(appearances) = document
+        .get_dictionary(id)
+        .and_then(|annotation| annotation.get_deref(b"AP", document))
+        .and_then(Object::as_dict)
+    else {
+        return vec![];
+    };
+    let mut streams = vec![];
+    for (_, appearance) in appearances {
+        match appearance {
+            Object::Reference(id) => {
+                match document.get_object(*id) {
+                    Ok(Object::Stream(_)) => streams.push(*id),
+                    Ok(Object::Dictionary(states)) => {
+                        streams.extend(
+                            states
+                                .iter()
+                                .filter_map(|(_, state)| state.as_reference().ok()),
+                        );
+                    },
+                    _ => {},
+                }
+            },
+            Object::Dictionary(states) => {
+                streams.extend(
+                    states
+                        .iter()
+                        .filter_map(|(_, state)| state.as_reference().ok()),
+                );
+            },
+            _ => {},
+        }
+    }
+    streams
+}
+
+/// Transform the geometry of annotations, i.e., their rectangle, points, ink
+/// strokes and appearance streams.
+///
+/// Appearance streams are rotated and flipped along, but only once, even if
+/// several annotations share them.
+pub fn transform_annotations(document: &mut Document, annotations: &[(ObjectId, Matrix)]) {
+    let mut transformed_streams = BTreeSet::new();
+
+    for &(id, matrix) in annotations {
+        for stream_id in appearance_streams(document, id) {
+            if !transformed_streams.insert(stream_id) {
+                continue;
+            }
+            if let Ok(Object::Stream(stream)) = document.get_object_mut(stream_id) {
+                let current = stream
+                    .dict
+                    .get(b"Matrix")
+                    .and_then(Object::as_array)
+                    .ok()
+                    .and_then(|operands| Matrix::from_operands(operands))
+                    .unwrap_or(Matrix::IDENTITY);
+                // Viewers scale and move appearances to fit the rectangle,
+                // but never rotate them.
+                let Matrix { a, b, c, d, e, f } = current.then(&matrix.linear());
+                stream.dict.set(
+                    "Matrix",
+                    [a, b, c, d, e, f]
+                        .into_iter()
+                        .map(Object::Real)
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+
+        let Ok(annotation) = document.get_dictionary_mut(id) else {
+            continue;
+        };
+        transform_rect(annotation, &matrix);
+        for key in POINT_KEYS {
+            if let Ok(Object::Array(values)) = annotation.get_mut(key) {
+                transform_points(values, &matrix);
+            }
+        }
+        if let Ok(Object::Array(strokes)) = annotation.get_mut(b"InkList") {
+            for stroke in strokes {
+                if let Object::Array(values) = stroke {
+                    transform_points(values, &matrix);
+                }
+            }
+        }
+    }
+}
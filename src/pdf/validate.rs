@@ -0,0 +1,285 @@
+//! Validation of documents against profiles, e.g., the accessibility
+//! requirements of PDF/UA.
+//!
+//! Checks are partial: passing them does not make a document conform to a
+//! profile, but failing them means it does not.
+
+use std::fmt;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::Serialize;
+
+use super::{
+    document::DocumentExt,
+    pdfstring, reference,
+    structure::{self, Element},
+};
+
+/// Operators that paint on the page, and must therefore be tagged as real
+/// content or marked as artifacts in tagged documents.
+const PAINTING_OPERATORS: &[&str] = &[
+    "Tj", "TJ", "'", "\"", "Do", "sh", "BI", "f", "F", "f*", "B", "B*", "b", "b*", "S", "s",
+];
+
+/// A rule that a document fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    NotTagged,
+    MissingLanguage,
+    FigureWithoutAlt,
+    MissingTabOrder,
+    UntaggedContent,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotTagged => "not-tagged",
+            Self::MissingLanguage => "missing-language",
+            Self::FigureWithoutAlt => "figure-without-alt",
+            Self::MissingTabOrder => "missing-tab-order",
+            Self::UntaggedContent => "untagged-content",
+        })
+    }
+}
+
+/// A place where a document fails a rule.
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub rule: Rule,
+    /// Page number, if the issue is local to a page.
+    pub page: Option<u32>,
+    /// Object at fault, e.g., `12 0 R`.
+    pub object: Option<String>,
+    pub description: String,
+}
+
+/// Return whether the document declares itself as tagged, with a structure
+/// tree.
+fn is_tagged(catalog: &Dictionary, document: &Document) -> bool {
+    let marked = catalog
+        .get_deref(b"MarkInfo", document)
+        .and_then(Object::as_dict)
+        .and_then(|info| info.get(b"Marked"))
+        .and_then(Object::as_bool)
+        .unwrap_or(false);
+
+    marked && catalog.has(b"StructTreeRoot")
+}
+
+/// Report figures without alternate description or replacement text.
+fn check_figures(elements: &[Element], issues: &mut Vec<Issue>) {
+    for element in elements {
+        let standard_type = element.role.as_deref().unwrap_or(&element.struct_type);
+
+        if standard_type == "Figure" && element.alt.is_none() && element.actual_text.is_none() {
+            issues.push(Issue {
+                rule: Rule::FigureWithoutAlt,
+                page: element.page,
+                object: element.object.clone(),
+                description: format!(
+                    "{} element has no alternate description",
+                    element.struct_type
+                ),
+            });
+        }
+        check_figures(&element.children, issues);
+    }
+}
+
+/// Return whether a marked-content sequence, opened by `BMC` or `BDC`, tags
+/// real content, with a marked-content identifier, or marks an artifact.
+fn is_tagged_sequence(operands: &[Object], properties: Option<&Dictionary>) -> bool {
+    if operands.first().and_then(|tag| tag.as_name().ok()) == Some(b"Artifact") {
+        return true;
+    }
+    let dict = match operands.get(1) {
+        Some(Object::Dictionary(dict)) => Some(dict),
+        Some(Object::Name(name)) => {
+            properties
+                .and_then(|properties| properties.get(name).ok())
+                .and_then(|dict| dict.as_dict().ok())
+        },
+        _ => None,
+    };
+    dict.is_some_and(|dict| dict.has(b"MCID"))
+}
+
+/// Return the number of painting operations of a page that are neither
+/// tagged nor marked as artifacts.
+///
+/// Operations of nested form XObjects are not visited, but drawing an
+/// untagged form XObject counts as one untagged operation.
+fn untagged_operations(document: &Document, page_id: ObjectId) -> lopdf::Result<usize> {
+    let content = document.get_and_decode_page_content(page_id)?;
+    let properties = document.page_resources(page_id).and_then(|resources| {
+        resources
+            .get_deref(b"Properties", document)
+            .and_then(Object::as_dict)
+            .ok()
+    });
+    // Whether each open marked-content sequence is within tagged content.
+    let mut stack: Vec<bool> = vec![];
+    let mut count = 0;
+
+    for operation in &content.operations {
+        let tagged = stack.last().copied().unwrap_or(false);
+
+        match operation.operator.as_str() {
+            "BMC" | "BDC" => {
+                stack.push(tagged || is_tagged_sequence(&operation.operands, properties));
+            },
+            "EMC" => {
+                stack.pop();
+            },
+            operator if !tagged && PAINTING_OPERATORS.contains(&operator) => count += 1,
+            _ => {},
+        }
+    }
+    Ok(count)
+}
+
+/// Check a document against the accessibility requirements of PDF/UA-1: the
+/// document is tagged and has a language, figures have alternate
+/// descriptions, pages with annotations define their tab order, and all
+/// page content is either tagged or marked as artifacts.
+#[must_use]
+pub fn check_pdfua(document: &Document) -> Vec<Issue> {
+    let mut issues = vec![];
+    let Ok(catalog) = document.catalog() else {
+        return issues;
+    };
+    let pages = document.get_pages();
+
+    let tree = if is_tagged(catalog, document) {
+        structure::read(document)
+    } else {
+        None
+    };
+    if tree.is_none() {
+        issues.push(Issue {
+            rule: Rule::NotTagged,
+            page: None,
+            object: None,
+            description: "Document is not tagged, it has no structure tree or is not marked"
+                .to_string(),
+        });
+    }
+
+    let language = catalog
+        .get_deref(b"Lang", document)
+        .and_then(pdfstring::decode_object)
+        .unwrap_or_default();
+    if language.trim().is_empty() {
+        issues.push(Issue {
+            rule: Rule::MissingLanguage,
+            page: None,
+            object: None,
+            description: "Document has no default language".to_string(),
+        });
+    }
+
+    if let Some(tree) = &tree {
+        check_figures(&tree.children, &mut issues);
+    }
+
+    for (&number, &page_id) in &pages {
+        let Ok(page) = document.get_dictionary(page_id) else {
+            continue;
+        };
+        let has_annotations = page
+            .get_deref(b"Annots", document)
+            .and_then(Object::as_array)
+            .is_ok_and(|annots| !annots.is_empty());
+        let tab_order = page.get(b"Tabs").and_then(Object::as_name).ok();
+
+        if has_annotations && tab_order != Some(b"S") {
+            issues.push(Issue {
+                rule: Rule::MissingTabOrder,
+                page: Some(number),
+                object: Some(reference(page_id)),
+                description: "Page has annotations but its tab order does not follow the structure"
+                    .to_string(),
+            });
+        }
+
+        if tree.is_some() {
+            let count = untagged_operations(document, page_id).unwrap_or(0);
+            if count > 0 {
+                issues.push(Issue {
+                    rule: Rule::UntaggedContent,
+                    page: Some(number),
+                    object: Some(reference(page_id)),
+                    description: format!(
+                        "{count} painting operations are neither tagged nor artifacts"
+                    ),
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Return whether an entry of an `/Annots` array is an annotation, i.e., a
+/// dictionary or a reference to an existing dictionary.
+fn is_annotation(document: &Document, entry: &Object) -> bool {
+    match entry {
+        Object::Dictionary(_) => true,
+        Object::Reference(id) => document.get_dictionary(*id).is_ok(),
+        _ => false,
+    }
+}
+
+/// Remove the entries of the `/Annots` arrays of pages that are not
+/// annotations, e.g., `null` or references to missing objects, and return
+/// the number of removed entries of each page.
+///
+/// Pages left without annotations lose their `/Annots` entry.
+pub fn fix_annotations(document: &mut Document) -> Vec<(u32, usize)> {
+    let mut fixed = vec![];
+
+    for (number, page_id) in document.get_pages() {
+        let Ok(annots) = document
+            .get_dictionary(page_id)
+            .and_then(|page| page.get(b"Annots"))
+        else {
+            continue;
+        };
+        // Arrays of annotations may be shared by reference.
+        let array_id = annots.as_reference().ok();
+        let Ok(entries) = document
+            .dereference(annots)
+            .and_then(|(_, annots)| annots.as_array())
+        else {
+            continue;
+        };
+        let kept: Vec<Object> = entries
+            .iter()
+            .filter(|entry| is_annotation(document, entry))
+            .cloned()
+            .collect();
+        let removed = entries.len() - kept.len();
+
+        if removed == 0 {
+            continue;
+        }
+        match array_id {
+            Some(id) if !kept.is_empty() => {
+                document.objects.insert(id, Object::Array(kept));
+            },
+            _ => {
+                let Ok(page) = document.get_dictionary_mut(page_id) else {
+                    continue;
+                };
+                if kept.is_empty() {
+                    page.remove(b"Annots");
+                } else {
+                    page.set("Annots", kept);
+                }
+            },
+        }
+        fixed.push((number, removed));
+    }
+    fixed
+}
@@ -0,0 +1,166 @@
+//! PDF versions, and the features that documents need a given version for.
+
+use std::{fmt, str::FromStr};
+
+use lopdf::{Dictionary, Document, Object, xref::XrefType};
+
+use super::objects::object_dictionary;
+
+/// Versions that documents can be converted to.
+pub const VERSIONS: &[&str] = &["1.3", "1.4", "1.5", "1.6", "1.7", "2.0"];
+
+/// PDF version, e.g., `1.7`, ordered by major, then minor number.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl Version {
+    #[must_use]
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid PDF version: {s:?}, expected e.g. 1.7");
+        let (major, minor) = s.trim().split_once('.').ok_or_else(invalid)?;
+        Ok(Self::new(
+            major.parse().map_err(|_| invalid())?,
+            minor.parse().map_err(|_| invalid())?,
+        ))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Feature of a document, and the version that introduced it.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Feature {
+    /// Version that introduced the feature.
+    pub version: Version,
+    /// Description of the feature.
+    pub description: &'static str,
+}
+
+impl Feature {
+    const fn new(major: u8, minor: u8, description: &'static str) -> Self {
+        Self {
+            version: Version::new(major, minor),
+            description,
+        }
+    }
+}
+
+/// Return whether any filter of a stream is the given one.
+fn has_filter(dict: &Dictionary, filter: &[u8]) -> bool {
+    match dict.get(b"Filter") {
+        Ok(Object::Name(name)) => name == filter,
+        Ok(Object::Array(filters)) => {
+            filters
+                .iter()
+                .any(|name| name.as_name().is_ok_and(|name| name == filter))
+        },
+        _ => false,
+    }
+}
+
+/// Return the features a dictionary needs, if any.
+fn dictionary_features(dict: &Dictionary) -> Vec<Feature> {
+    let mut features = Vec::new();
+    let subtype = dict.get(b"Subtype").and_then(Object::as_name).ok();
+    let blend_mode = dict.get(b"BM").and_then(Object::as_name).ok();
+    let alpha = [b"CA".as_slice(), b"ca"].into_iter().any(|key| {
+        dict.get(key)
+            .and_then(Object::as_float)
+            .is_ok_and(|alpha| alpha < 1.0)
+    });
+
+    let soft_mask = dict
+        .get(b"SMask")
+        .is_ok_and(|mask| !matches!(mask, Object::Name(name) if name == b"None"));
+
+    if soft_mask
+        || blend_mode.is_some_and(|mode| mode != b"Normal" && mode != b"Compatible")
+        || alpha
+    {
+        features.push(Feature::new(1, 4, "transparency"));
+    }
+    if has_filter(dict, b"JBIG2Decode") {
+        features.push(Feature::new(1, 4, "JBIG2 images"));
+    }
+    if has_filter(dict, b"JPXDecode") {
+        features.push(Feature::new(1, 5, "JPEG 2000 images"));
+    }
+    if dict.has(b"XFA") {
+        features.push(Feature::new(1, 5, "XFA forms"));
+    }
+    if subtype == Some(b"3D") {
+        features.push(Feature::new(1, 6, "3D annotations"));
+    }
+    if dict.has(b"AF") {
+        features.push(Feature::new(2, 0, "associated files"));
+    }
+    features
+}
+
+/// Return the features of a document, sorted by version, without
+/// duplicates.
+///
+/// Object and cross-reference streams are not listed, as documents are
+/// always written without object streams, and with a cross-reference
+/// table when needed.
+#[must_use]
+pub fn features(document: &Document) -> Vec<Feature> {
+    let mut features: Vec<Feature> = document
+        .objects
+        .values()
+        .filter_map(object_dictionary)
+        .flat_map(dictionary_features)
+        .collect();
+
+    if let Ok(catalog) = document.catalog() {
+        if catalog.has(b"Metadata") {
+            features.push(Feature::new(1, 4, "XMP metadata"));
+        }
+        if catalog.has(b"OCProperties") {
+            features.push(Feature::new(1, 5, "optional content (layers)"));
+        }
+        if catalog.has(b"Collection") {
+            features.push(Feature::new(1, 7, "portfolios"));
+        }
+    }
+    features.sort_unstable();
+    features.dedup();
+    features
+}
+
+/// Set the version of a document, and return the features it has that the
+/// version does not support.
+///
+/// Features are kept, as removing them would lose content, but readers of
+/// the target version may ignore them. Cross-reference streams are replaced
+/// with a table before PDF 1.5.
+pub fn convert(document: &mut Document, version: Version) -> Vec<Feature> {
+    document.version = version.to_string();
+    if version < Version::new(1, 5) {
+        document.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    }
+    // The version of the catalog overrides the header if it is higher.
+    if let Ok(catalog) = document.catalog_mut() {
+        catalog.remove(b"Version");
+    }
+
+    features(document)
+        .into_iter()
+        .filter(|feature| feature.version > version)
+        .collect()
+}
@@ -0,0 +1,119 @@
+//! Initial view of documents, i.e., how viewers show them when they are
+//! opened: panels, page layout, window options and first page shown.
+
+use std::{fmt, str::FromStr};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+/// Magnification of the page a document opens at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zoom {
+    /// Whole page in the window.
+    Fit,
+    /// Page width in the window.
+    FitWidth,
+    /// Page height in the window.
+    FitHeight,
+    /// Zoom factor, e.g., `1.5` for 150%.
+    Factor(f32),
+}
+
+impl FromStr for Zoom {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "fit" => Ok(Self::Fit),
+            "fit-width" => Ok(Self::FitWidth),
+            "fit-height" => Ok(Self::FitHeight),
+            percent => {
+                match percent.trim_end_matches('%').parse::<f32>() {
+                    Ok(percent) if percent.is_finite() && percent > 0.0 => {
+                        Ok(Self::Factor(percent / 100.0))
+                    },
+                    _ => {
+                        Err(format!(
+                            "invalid zoom: {s:?}, expected fit, fit-width, fit-height or a \
+                             percentage, e.g., 150%"
+                        ))
+                    },
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for Zoom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fit => f.write_str("fit"),
+            Self::FitWidth => f.write_str("fit-width"),
+            Self::FitHeight => f.write_str("fit-height"),
+            Self::Factor(factor) => write!(f, "{}%", factor * 100.0),
+        }
+    }
+}
+
+/// Return an explicit destination showing a page with the given zoom.
+///
+/// Coordinates are left `null`, so that viewers keep the top (or left) of
+/// the page in view.
+#[must_use]
+pub fn destination(page_id: ObjectId, zoom: Zoom) -> Object {
+    let page = Object::Reference(page_id);
+
+    Object::Array(match zoom {
+        Zoom::Fit => vec![page, "Fit".into()],
+        Zoom::FitWidth => vec![page, "FitH".into(), Object::Null],
+        Zoom::FitHeight => vec![page, "FitV".into(), Object::Null],
+        Zoom::Factor(factor) => {
+            vec![
+                page,
+                "XYZ".into(),
+                Object::Null,
+                Object::Null,
+                Object::Real(factor),
+            ]
+        },
+    })
+}
+
+/// Set the action run when the document is opened to showing a page with
+/// the given zoom, replacing any previous open action.
+pub fn set_open_action(
+    document: &mut Document,
+    page_id: ObjectId,
+    zoom: Zoom,
+) -> lopdf::Result<()> {
+    document
+        .catalog_mut()?
+        .set("OpenAction", destination(page_id, zoom));
+    Ok(())
+}
+
+/// Return the viewer preferences dictionary of a document, creating it if
+/// needed.
+pub fn viewer_preferences_mut(document: &mut Document) -> lopdf::Result<&mut Dictionary> {
+    let existing = document
+        .catalog()?
+        .get(b"ViewerPreferences")
+        .and_then(Object::as_reference)
+        .ok();
+
+    if let Some(id) = existing {
+        if document.get_dictionary(id).is_ok() {
+            return document.get_dictionary_mut(id);
+        }
+    }
+    let catalog = document.catalog_mut()?;
+    if catalog
+        .get(b"ViewerPreferences")
+        .and_then(Object::as_dict)
+        .is_err()
+    {
+        catalog.set("ViewerPreferences", Dictionary::new());
+    }
+    catalog
+        .get_mut(b"ViewerPreferences")
+        .and_then(Object::as_dict_mut)
+}
@@ -0,0 +1,59 @@
+//! XMP metadata streams, which hold document metadata as RDF/XML.
+
+use lopdf::{Document, Object, Stream, dictionary};
+
+/// Wrap `rdf:Description` elements in an XMP packet.
+#[must_use]
+pub fn packet(descriptions: &str) -> String {
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n \
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+         {descriptions} \
+         </rdf:RDF>\n\
+         </x:xmpmeta>\n\
+         <?xpacket end=\"w\"?>"
+    )
+}
+
+/// Return the XMP metadata of the catalog, if any.
+#[must_use]
+pub fn metadata(document: &Document) -> Option<String> {
+    let stream = document
+        .catalog()
+        .and_then(|catalog| catalog.get_deref(b"Metadata", document))
+        .and_then(Object::as_stream)
+        .ok()?;
+    let data = stream
+        .decompressed_content()
+        .unwrap_or_else(|_| stream.content.clone());
+
+    String::from_utf8(data).ok()
+}
+
+/// Set the XMP metadata of the catalog, replacing the existing stream if
+/// any.
+pub fn set_metadata(document: &mut Document, xmp: String) -> lopdf::Result<()> {
+    // Metadata is left uncompressed, so that tools that do not parse PDF
+    // files can still read it.
+    let mut stream = Stream::new(
+        dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+        xmp.into_bytes(),
+    );
+    stream.allows_compression = false;
+
+    match document
+        .catalog()?
+        .get(b"Metadata")
+        .and_then(Object::as_reference)
+    {
+        Ok(id) => {
+            document.objects.insert(id, Object::Stream(stream));
+        },
+        Err(_) => {
+            let id = document.add_object(stream);
+            document.catalog_mut()?.set("Metadata", id);
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,1161 @@
+mod common;
+
+use common::{make_fixture, pdf_snapshot, rpdf, sample, write_object_stream_pdf};
+use insta::assert_snapshot;
+use predicates::{prelude::*, str::contains};
+use tempfile::TempDir;
+
+/// Objects of a one-page document with two annotations, as Acrobat writes
+/// them, i.e., with an indirect array of annotations.
+const ACROBAT_OBJECTS: &[&str] = &[
+    "<</Type/Catalog/Pages 2 0 R>>",
+    "<</Type/Pages/Kids[3 0 R]/Count 1/MediaBox[0 0 612 792]>>",
+    "<</Type/Page/Parent 2 0 R/Annots 4 0 R>>",
+    "[5 0 R 6 0 R]",
+    "<</Type/Annot/Subtype/Text/Rect[72 700 92 720]/Contents(Comment)/P 3 0 R>>",
+    "<</Type/Annot/Subtype/Highlight/Rect[72 600 300 620]/QuadPoints[72 620 300 620 72 600 300 \
+     600]/P 3 0 R>>",
+];
+
+#[test]
+fn stats_counts_annotations_by_subtype() {
+    let output = rpdf()
+        .current_dir(sample(""))
+        .args(["annotations", "stats", "sample_highlighted.pdf"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn stats_per_page() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "3", "--annotations", "8"],
+    );
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "stats", "--per-page", "fixture.pdf"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn stats_counts_selected_category() {
+    let args = ["annotations", "stats", "sample_highlighted.pdf"];
+
+    rpdf()
+        .current_dir(sample(""))
+        .args(args)
+        .args(["--category", "links"])
+        .assert()
+        .success()
+        .stdout(contains("Link").and(contains("Highlight").not()));
+    rpdf()
+        .current_dir(sample(""))
+        .args(args)
+        .args(["--category", "all"])
+        .assert()
+        .success()
+        .stdout(contains("Link").and(contains("Highlight")));
+    rpdf()
+        .current_dir(sample(""))
+        .args(args)
+        .args(["--category", "forms"])
+        .assert()
+        .success()
+        .stdout(contains("No annotation of this category was found"));
+}
+
+#[test]
+fn stats_without_annotations() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+
+    rpdf()
+        .args(["annotations", "stats"])
+        .arg(fixture)
+        .assert()
+        .success()
+        .stdout("No annotation was found in the given file.\n");
+}
+
+#[test]
+fn stats_compares_revisions() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(
+        dir.path(),
+        "old.pdf",
+        &["--pages", "2", "--annotations", "3"],
+    );
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "--quiet",
+            "annotations",
+            "strip",
+            "old.pdf",
+            "-d",
+            "stripped.pdf",
+        ])
+        .args(["--filter", r#"subtype == "Highlight" && page == 2"#])
+        .assert()
+        .success();
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "--quiet",
+            "annotations",
+            "add-note",
+            "stripped.pdf",
+            "-d",
+            "new.pdf",
+        ])
+        .args(["--page", "2", "--at", "100,100", "--text", "Done"])
+        .assert()
+        .success();
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args([
+            "annotations",
+            "stats",
+            "--per-page",
+            "--compare",
+            "old.pdf",
+            "new.pdf",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "stats", "--compare", "old.pdf", "old.pdf"])
+        .assert()
+        .success()
+        .stdout("No annotation was added nor removed.\n");
+}
+
+#[cfg(feature = "i18n")]
+#[test]
+fn stats_in_selected_language() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "2", "--annotations", "3"],
+    );
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args([
+            "--lang",
+            "fr",
+            "annotations",
+            "stats",
+            "--per-page",
+            "fixture.pdf",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+
+    let fixture = make_fixture(dir.path(), "empty.pdf", &[]);
+    rpdf()
+        .env("LANG", "de_DE.UTF-8")
+        .args(["annotations", "stats"])
+        .arg(fixture)
+        .assert()
+        .success()
+        .stdout("In der Datei wurde keine Anmerkung gefunden.\n");
+}
+
+#[test]
+fn merge_imports_annotations_on_matching_pages() {
+    let dir = TempDir::new().unwrap();
+    let first = make_fixture(
+        dir.path(),
+        "first.pdf",
+        &["--pages", "2", "--annotations", "2", "--subtype", "Text"],
+    );
+    let second = make_fixture(
+        dir.path(),
+        "second.pdf",
+        &[
+            "--pages",
+            "2",
+            "--annotations",
+            "3",
+            "--subtype",
+            "Ink",
+            "--subtype",
+            "Link",
+        ],
+    );
+    let merged = dir.path().join("merged.pdf");
+
+    rpdf()
+        .args(["--quiet", "annotations", "merge"])
+        .args([&first, &second])
+        .arg("--dest")
+        .arg(&merged)
+        .assert()
+        .success();
+
+    assert_snapshot!(pdf_snapshot(&merged));
+}
+
+#[test]
+fn merge_does_not_overwrite_without_input() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+
+    rpdf()
+        .args(["annotations", "merge"])
+        .args([&fixture, &fixture])
+        .arg("--dest")
+        .arg(&fixture)
+        .assert()
+        .failure()
+        .stderr(contains("already exists, use `--force` to overwrite it."));
+}
+
+#[test]
+fn merge_still_takes_short_force_flag() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+    let merged = dir.path().join("merged.pdf");
+    std::fs::write(&merged, "existing").unwrap();
+
+    rpdf()
+        .args(["--quiet", "annotations", "merge", "-f"])
+        .args([&fixture, &fixture])
+        .arg("--dest")
+        .arg(&merged)
+        .assert()
+        .success();
+
+    assert!(std::fs::read(&merged).unwrap().starts_with(b"%PDF-"));
+}
+
+#[test]
+fn merge_leaves_no_partial_file_on_failure() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+    // Renaming the written file over a directory fails once it is complete.
+    let dest = dir.path().join("merged.pdf");
+    std::fs::create_dir(&dest).unwrap();
+    std::fs::write(dest.join("keep"), "").unwrap();
+
+    rpdf()
+        .args(["--force", "annotations", "merge"])
+        .args([&fixture, &fixture])
+        .arg("--dest")
+        .arg(&dest)
+        .assert()
+        .failure()
+        .stderr(contains("Failed to write PDF to:"));
+
+    let mut names: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["fixture.pdf", "merged.pdf"]);
+    assert!(dest.join("keep").exists());
+}
+
+#[test]
+fn strip_and_merge_verify_written_files() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "2"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--verify", "-vvv", "annotations", "merge"])
+        .args([&fixture, &fixture])
+        .assert()
+        .success()
+        .stderr(contains("Verified \"merged_annotations.pdf\""));
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "--verify",
+            "--quiet",
+            "annotations",
+            "strip",
+            "merged_annotations.pdf",
+        ])
+        .args(["--dest", "stripped.pdf"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn strip_follows_overwrite_policy() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "a.pdf", &["--annotations", "2"]);
+    make_fixture(dir.path(), "b.pdf", &["--annotations", "2"]);
+    std::fs::write(dir.path().join("a_strip.pdf"), "existing").unwrap();
+    let strip = |policy: &str| {
+        rpdf()
+            .current_dir(dir.path())
+            .args([policy, "annotations", "strip", "a.pdf", "b.pdf"])
+            .args(["--name-template", "{stem}_{cmd}.pdf"])
+            .assert()
+    };
+
+    strip("--no-clobber")
+        .success()
+        .stdout("Successfully striped annotations from b.pdf to b_strip.pdf\n");
+    assert_eq!(
+        std::fs::read(dir.path().join("a_strip.pdf")).unwrap(),
+        b"existing"
+    );
+
+    strip("--backup").success();
+    for name in ["a_strip.pdf.bak", "b_strip.pdf.bak"] {
+        assert!(dir.path().join(name).exists());
+    }
+    assert_eq!(
+        std::fs::read(dir.path().join("a_strip.pdf.bak")).unwrap(),
+        b"existing"
+    );
+}
+
+#[test]
+fn strip_keeps_links_by_default() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &[
+            "--annotations",
+            "4",
+            "--subtype",
+            "Square",
+            "--subtype",
+            "Link",
+        ],
+    );
+    let stripped = dir.path().join("stripped.pdf");
+
+    rpdf()
+        .args(["--quiet", "annotations", "strip"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&stripped)
+        .assert()
+        .success();
+
+    assert_snapshot!(pdf_snapshot(&stripped));
+}
+
+#[test]
+fn merge_is_reproducible_in_deterministic_mode() {
+    let dir = TempDir::new().unwrap();
+    let outputs = ["first.pdf", "second.pdf"].map(|name| {
+        let dest = dir.path().join(name);
+        rpdf()
+            .current_dir(sample(""))
+            .args(["--quiet", "--deterministic", "annotations", "merge"])
+            .args(["sample.pdf", "sample_highlighted.pdf", "--dest"])
+            .arg(&dest)
+            .assert()
+            .success();
+        std::fs::read(dest).unwrap()
+    });
+
+    assert_eq!(outputs[0], outputs[1]);
+
+    let document = lopdf::Document::load_mem(&outputs[0]).unwrap();
+    let info = document
+        .trailer
+        .get(b"Info")
+        .and_then(lopdf::Object::as_reference)
+        .and_then(|id| document.get_dictionary(id))
+        .unwrap();
+    assert_eq!(
+        info.get(b"ModDate")
+            .and_then(lopdf::Object::as_str)
+            .unwrap(),
+        b"D:19700101000000Z"
+    );
+}
+
+#[test]
+fn strip_names_outputs_of_several_files() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "a.pdf", &["--annotations", "2"]);
+    make_fixture(dir.path(), "b.pdf", &["--annotations", "2"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "strip", "a.pdf", "b.pdf"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Stripping several files needs a `--name-template`",
+        ));
+
+    rpdf()
+        .current_dir(dir.path())
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .args(["annotations", "strip", "a.pdf", "b.pdf"])
+        .args(["--name-template", "{stem}_{cmd}_{date}.pdf"])
+        .assert()
+        .success()
+        .stdout(
+            "Successfully striped annotations from a.pdf to a_strip_2023-11-14.pdf\nSuccessfully \
+             striped annotations from b.pdf to b_strip_2023-11-14.pdf\n",
+        );
+}
+
+#[test]
+fn strip_uses_source_date_epoch() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "2"]);
+    let stripped = dir.path().join("stripped.pdf");
+
+    rpdf()
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .args(["--quiet", "annotations", "strip"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&stripped)
+        .assert()
+        .success();
+
+    let document = lopdf::Document::load(&stripped).unwrap();
+    let info = document
+        .trailer
+        .get(b"Info")
+        .and_then(lopdf::Object::as_reference)
+        .and_then(|id| document.get_dictionary(id))
+        .unwrap();
+    assert_eq!(
+        info.get(b"ModDate")
+            .and_then(lopdf::Object::as_str)
+            .unwrap(),
+        b"D:20231114221320Z"
+    );
+}
+
+#[test]
+fn stats_reads_object_streams() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("objstm.pdf"), ACROBAT_OBJECTS, None);
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "stats", "objstm.pdf"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn stats_reads_encrypted_object_streams() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("encrypted.pdf"), ACROBAT_OBJECTS, Some(""));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "stats", "encrypted.pdf"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn stats_requires_user_password() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("encrypted.pdf");
+    write_object_stream_pdf(&path, ACROBAT_OBJECTS, Some("secret"));
+
+    rpdf()
+        .args(["annotations", "stats"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(contains("is the password correct?"));
+
+    rpdf()
+        .args(["--password", "secret", "annotations", "stats"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(contains("Highlight"));
+}
+
+#[test]
+fn merge_from_encrypted_object_streams() {
+    let dir = TempDir::new().unwrap();
+    let reference = dir.path().join("reference.pdf");
+    let encrypted = dir.path().join("encrypted.pdf");
+    let merged = dir.path().join("merged.pdf");
+    write_object_stream_pdf(&reference, ACROBAT_OBJECTS, None);
+    write_object_stream_pdf(&encrypted, ACROBAT_OBJECTS, Some(""));
+
+    rpdf()
+        .args(["--quiet", "annotations", "merge"])
+        .args([&reference, &encrypted])
+        .arg("--dest")
+        .arg(&merged)
+        .assert()
+        .success();
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "stats", "merged.pdf"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn export_resolves_highlighted_text() {
+    let output = rpdf()
+        .current_dir(sample(""))
+        .args([
+            "annotations",
+            "export",
+            "sample_highlighted.pdf",
+            "--format",
+            "csv",
+        ])
+        .args(["--filter", r#"subtype == "Highlight""#])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn export_annotations_to_csv() {
+    let output = rpdf()
+        .current_dir(sample(""))
+        .args([
+            "annotations",
+            "export",
+            "sample_popup.pdf",
+            "--format",
+            "csv",
+        ])
+        .args(["--exclude", "Link", "--exclude", "Popup"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn add_stamp_to_selected_pages() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &["--pages", "3"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "add-stamp", "fixture.pdf", "--pages", "1,3"])
+        .args(["--name", "confidential", "--position", "center"])
+        .assert()
+        .success()
+        .stdout(contains("Successfully stamped 2 pages"));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "stats", "stamped.pdf", "--per-page"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn add_note_as_free_text() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &["--pages", "2"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--source-date-epoch", "1700000000"])
+        .args(["annotations", "add-note", "fixture.pdf", "--page", "2"])
+        .args([
+            "--at",
+            "100,700",
+            "--text",
+            "Check figure",
+            "--author",
+            "bot",
+        ])
+        .args(["--kind", "free-text"])
+        .assert()
+        .success()
+        .stdout(contains("Successfully added a note to page 2"));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "export", "annotated.pdf", "--format", "csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "add-note", "fixture.pdf", "--page", "3"])
+        .args(["--at", "100,700", "--text", "Missing page"])
+        .assert()
+        .failure()
+        .stderr(contains("there is no page 3"));
+}
+
+#[test]
+fn add_marks_from_json() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &["--pages", "2"]);
+    std::fs::write(
+        dir.path().join("marks.json"),
+        r#"[
+            {"page": 1, "rect": [100, 100, 200, 150], "label": "crack"},
+            {"page": 2, "polyline": [[50, 50], [80, 90], [120, 60]], "color": [0, 0, 1]}
+        ]"#,
+    )
+    .unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "add-marks", "fixture.pdf", "--from", "marks.json"])
+        .assert()
+        .success()
+        .stdout(contains("Successfully added 2 marks"));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "stats", "marked.pdf", "--per-page"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn merge_deduplicates_imported_objects() {
+    let dir = TempDir::new().unwrap();
+    let count_streams = |files: &[&str]| {
+        let dest = dir.path().join("merged.pdf");
+        rpdf()
+            .current_dir(sample(""))
+            .args(["--quiet", "annotations", "merge", "--force"])
+            .args(files)
+            .arg("--dest")
+            .arg(&dest)
+            .assert()
+            .success();
+        lopdf::Document::load(dest)
+            .unwrap()
+            .objects
+            .values()
+            .filter(|object| object.as_stream().is_ok())
+            .count()
+    };
+
+    // Annotations of the same file bring identical appearance streams.
+    assert_eq!(
+        count_streams(&["sample.pdf", "sample_ios_native.pdf"]),
+        count_streams(&[
+            "sample.pdf",
+            "sample_ios_native.pdf",
+            "sample_ios_native.pdf"
+        ])
+    );
+}
+
+#[test]
+fn merge_without_compressing_output() {
+    let dir = TempDir::new().unwrap();
+    let sizes = ["true", "false"].map(|compress| {
+        let dest = dir.path().join(format!("merged-{compress}.pdf"));
+        rpdf()
+            .current_dir(sample(""))
+            .args(["--quiet", "annotations", "merge"])
+            .args([
+                "sample.pdf",
+                "sample_acrobat.pdf",
+                "--compress-output",
+                compress,
+            ])
+            .arg("--dest")
+            .arg(&dest)
+            .assert()
+            .success();
+        lopdf::Document::load(&dest).unwrap();
+        std::fs::metadata(dest).unwrap().len()
+    });
+
+    assert!(sizes[0] <= sizes[1]);
+}
+
+#[test]
+fn merge_fails_beyond_max_memory() {
+    let dir = TempDir::new().unwrap();
+
+    rpdf()
+        .current_dir(sample(""))
+        .args(["annotations", "merge", "--max-memory", "1K"])
+        .args(["sample.pdf", "sample_acrobat.pdf", "--dest"])
+        .arg(dir.path().join("merged.pdf"))
+        .assert()
+        .failure()
+        .stderr(contains("allowed by --max-memory"));
+}
+
+#[test]
+fn stats_fails_beyond_max_memory() {
+    rpdf()
+        .current_dir(sample(""))
+        .args(["annotations", "stats", "--max-memory", "1K", "sample.pdf"])
+        .assert()
+        .failure()
+        .stderr(contains("allowed by --max-memory"));
+}
+
+#[test]
+fn strip_writes_report() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "4"]);
+    let stripped = dir.path().join("stripped.pdf");
+    let report = dir.path().join("report.json");
+
+    rpdf()
+        .args(["--quiet", "annotations", "strip"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&stripped)
+        .arg("--report")
+        .arg(&report)
+        .assert()
+        .success();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(report).unwrap()).unwrap();
+    assert_eq!(report["success"], true);
+    assert_eq!(report["files_read"], 1);
+    assert_eq!(report["files_written"], 1);
+    assert_eq!(report["annotations_removed"], 4);
+    assert_eq!(report["annotations_added"], 0);
+}
+
+#[test]
+fn summary_is_printed_after_command() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "2"]);
+
+    rpdf()
+        .args(["annotations", "strip", "--summary"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(dir.path().join("stripped.pdf"))
+        .assert()
+        .success()
+        .stdout(contains("Annotations removed: 2"));
+}
+
+#[test]
+fn merge_directory_tags_author_from_filename() {
+    let dir = TempDir::new().unwrap();
+    let reviews = dir.path().join("reviews");
+    std::fs::create_dir(&reviews).unwrap();
+    let reference = make_fixture(dir.path(), "reference.pdf", &["--pages", "2"]);
+    for reviewer in ["alice.pdf", "bob.pdf"] {
+        make_fixture(
+            &reviews,
+            reviewer,
+            &["--pages", "2", "--annotations", "1", "--subtype", "Text"],
+        );
+    }
+    let merged = dir.path().join("merged.pdf");
+
+    rpdf()
+        .args([
+            "--quiet",
+            "annotations",
+            "merge",
+            "--tag-author-from-filename",
+        ])
+        .arg(&reference)
+        .arg(&reviews)
+        .arg("--dest")
+        .arg(&merged)
+        .assert()
+        .success();
+
+    let output = rpdf()
+        .args(["annotations", "export", "--format", "csv"])
+        .arg(&merged)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert!(csv.contains(",alice,"), "{csv}");
+    assert!(csv.contains(",bob,"), "{csv}");
+}
+
+#[test]
+fn merge_rescales_annotations_to_rotated_pages() {
+    let dir = TempDir::new().unwrap();
+    let reference = make_fixture(dir.path(), "reference.pdf", &[]);
+    let mut document = lopdf::Document::load(&reference).unwrap();
+    let page_id = document.page_iter().next().unwrap();
+    document
+        .get_dictionary_mut(page_id)
+        .unwrap()
+        .set("Rotate", 90);
+    document.save(&reference).unwrap();
+    let review = make_fixture(
+        dir.path(),
+        "review.pdf",
+        &["--annotations", "1", "--subtype", "Square"],
+    );
+    let merged = dir.path().join("merged.pdf");
+
+    rpdf()
+        .args(["--quiet", "annotations", "merge", "--rescale"])
+        .args([&reference, &review])
+        .arg("--dest")
+        .arg(&merged)
+        .assert()
+        .success();
+
+    let document = lopdf::Document::load(&merged).unwrap();
+    let page_id = document.page_iter().next().unwrap();
+    let annotations = document.get_page_annotations(page_id).unwrap();
+    let rect: Vec<f32> = annotations[0]
+        .get(b"Rect")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|value| value.as_float().unwrap())
+        .collect();
+    // The annotation is still shown at the top left of the page, which is
+    // now on the right of the unrotated page.
+    for (value, expected) in rect.iter().zip([86.5, 93.2, 102.0, 119.1]) {
+        assert!((value - expected).abs() < 0.1, "{rect:?}");
+    }
+}
+
+#[test]
+fn skip_hidden_leaves_out_hidden_annotations() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--annotations", "2", "--subtype", "Text"],
+    );
+    let mut document = lopdf::Document::load(&fixture).unwrap();
+    let page_id = document.page_iter().next().unwrap();
+    let hidden = rpdf::pdf::annotations::page_annotation_ids(&document, page_id)[1];
+    document.get_dictionary_mut(hidden).unwrap().set("F", 2);
+    document.save(&fixture).unwrap();
+
+    let count = |args: &[&str]| {
+        let output = rpdf()
+            .args(["annotations", "export", "--format", "csv"])
+            .args(args)
+            .arg(&fixture)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap().lines().count() - 1
+    };
+    assert_eq!(count(&[]), 2);
+    assert_eq!(count(&["--skip-hidden"]), 1);
+
+    let reference = make_fixture(dir.path(), "reference.pdf", &[]);
+    let merged = dir.path().join("merged.pdf");
+    rpdf()
+        .args(["--quiet", "annotations", "merge", "--skip-hidden"])
+        .args([&reference, &fixture])
+        .arg("--dest")
+        .arg(&merged)
+        .assert()
+        .success();
+    rpdf()
+        .args(["annotations", "stats"])
+        .arg(&merged)
+        .assert()
+        .success()
+        .stdout(contains("│ 1 "));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn export_filters_annotations() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "2", "--annotations", "6"],
+    );
+
+    let output = rpdf()
+        .args(["annotations", "export", "--format", "csv", "--filter"])
+        .arg(r#"subtype == "Text" || (page >= 2 && !(subtype =~ "^(Ink|Square)$"))"#)
+        .arg(&fixture)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let subtypes: Vec<_> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').take(3).collect::<Vec<_>>().join(","))
+        .collect();
+    assert_eq!(
+        subtypes,
+        [
+            format!("{},1,Text", fixture.display()),
+            format!("{},2,Highlight", fixture.display()),
+        ]
+    );
+
+    rpdf()
+        .args(["annotations", "export", "--filter", "page <= \"2\""])
+        .arg(&fixture)
+        .assert()
+        .failure()
+        .stderr(contains("expected a page number, found `\"2\"`"));
+}
+
+#[test]
+fn set_flags_locks_selected_annotations() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &[
+            "--annotations",
+            "2",
+            "--subtype",
+            "Stamp",
+            "--subtype",
+            "Text",
+        ],
+    );
+    let flagged = dir.path().join("flagged.pdf");
+
+    rpdf()
+        .args(["annotations", "set-flags", "--subtype", "Stamp"])
+        .args(["--locked", "--no-view", "--clear", "print"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&flagged)
+        .assert()
+        .success()
+        .stdout(contains("updated the flags of 1 annotations"));
+
+    assert_snapshot!(pdf_snapshot(&flagged));
+}
+
+/// Add a `3D` annotation with a model of `size` bytes to the first page.
+fn add_3d_annotation(path: &std::path::Path, size: usize) {
+    use lopdf::{Object, Stream, dictionary};
+
+    let mut document = lopdf::Document::load(path).unwrap();
+    let page_id = document.page_iter().next().unwrap();
+    let model = document.add_object(Stream::new(
+        dictionary! { "Type" => "3D", "Subtype" => "U3D" },
+        vec![b'x'; size],
+    ));
+    let annotation = document.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "3D",
+        "Rect" => vec![100.into(), 100.into(), 300.into(), 300.into()],
+        "3DD" => model,
+        "P" => page_id,
+    });
+    let page = document.get_dictionary_mut(page_id).unwrap();
+    match page.get_mut(b"Annots").and_then(Object::as_array_mut) {
+        Ok(annots) => annots.push(annotation.into()),
+        Err(_) => page.set("Annots", vec![annotation.into()]),
+    }
+    document.save(path).unwrap();
+}
+
+#[test]
+fn strip_media_removes_embedded_models() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--annotations", "2", "--subtype", "Square"],
+    );
+    add_3d_annotation(&fixture, 10_000);
+    let stripped = dir.path().join("stripped.pdf");
+
+    rpdf()
+        .args(["annotations", "stats"])
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(contains("1 annotations embed media (10000 bytes)"));
+
+    rpdf()
+        .args(["--quiet", "annotations", "strip", "--media"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&stripped)
+        .assert()
+        .success();
+
+    assert!(std::fs::metadata(&stripped).unwrap().len() < 10_000);
+    rpdf()
+        .args(["annotations", "stats"])
+        .arg(&stripped)
+        .assert()
+        .success()
+        .stdout(contains("Square"))
+        .stdout(contains("embed media").not());
+}
+
+/// Append a reference to an object that does not exist to the `/Annots` of
+/// the first page.
+fn add_dead_annotation(path: &std::path::Path) {
+    use lopdf::Object;
+
+    let mut document = lopdf::Document::load(path).unwrap();
+    let page_id = document.page_iter().next().unwrap();
+    // Saving numbers the cross-reference stream after the last object.
+    let dead = (document.max_id + 100, 0);
+    document
+        .get_dictionary_mut(page_id)
+        .and_then(|page| page.get_mut(b"Annots"))
+        .and_then(Object::as_array_mut)
+        .unwrap()
+        .push(dead.into());
+    document.save(path).unwrap();
+}
+
+/// Return the number of entries of the `/Annots` of the first page.
+fn first_page_annotation_count(path: &std::path::Path) -> usize {
+    let document = lopdf::Document::load(path).unwrap();
+    let page_id = document.page_iter().next().unwrap();
+    rpdf::pdf::annotations::page_annotation_ids(&document, page_id).len()
+}
+
+#[test]
+fn strip_removes_dead_references() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--annotations", "2", "--subtype", "Square"],
+    );
+    add_dead_annotation(&fixture);
+    let stripped = dir.path().join("stripped.pdf");
+
+    rpdf()
+        .args(["--quiet", "annotations", "strip"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&stripped)
+        .assert()
+        .success();
+
+    assert_eq!(first_page_annotation_count(&stripped), 0);
+}
+
+/// Interactive prompts need a terminal, which `script` provides.
+#[cfg(target_os = "linux")]
+#[test]
+fn strip_interactively_lists_dead_references() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--annotations", "2", "--subtype", "Square"],
+    );
+    add_dead_annotation(&fixture);
+
+    assert_cmd::Command::new("script")
+        .current_dir(dir.path())
+        .env("RPDF_NO_CACHE", "1")
+        .env("LANG", "C.UTF-8")
+        .arg("-qec")
+        .arg(format!(
+            "{} annotations strip --interactive fixture.pdf --dest stripped.pdf",
+            env!("CARGO_BIN_EXE_rpdf")
+        ))
+        .arg("/dev/null")
+        .write_stdin("n\nn\ny\n")
+        .assert()
+        .success()
+        .stdout(contains("[3/3] <missing> annotation on page 1. Delete it?"));
+
+    assert_eq!(
+        first_page_annotation_count(&dir.path().join("stripped.pdf")),
+        2
+    );
+}
+
+#[cfg(feature = "convert")]
+#[test]
+fn heatmap_stitches_pages_into_one_image() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(
+        dir.path(),
+        "review.pdf",
+        &["--pages", "4", "--annotations", "6"],
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "heatmap", "review.pdf", "--columns", "2"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Successfully drew the heatmap of 6 annotations of review.pdf to heatmap.png.",
+        ));
+    let image = std::fs::read(dir.path().join("heatmap.png")).unwrap();
+    assert_eq!(image[..8], *b"\x89PNG\r\n\x1a\n");
+    // Letter pages are 17 by 22 cells of 8 pixels, with a border, laid out
+    // two by two with margins of 8 pixels.
+    assert_eq!(image[16..20], 300u32.to_be_bytes());
+    assert_eq!(image[20..24], 380u32.to_be_bytes());
+}
+
+#[cfg(not(feature = "convert"))]
+#[test]
+fn heatmap_requires_convert_feature() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "review.pdf", &["--annotations", "1"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "heatmap", "review.pdf"])
+        .assert()
+        .failure()
+        .stderr(contains("unrecognized subcommand 'heatmap'"));
+    assert!(!dir.path().join("heatmap.png").exists());
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use std::fs;
+
+use common::{make_fixture, rpdf};
+use lopdf::{Document, Object};
+use tempfile::TempDir;
+
+/// Manifest with a cover, rotated pages of the same file, one of them placed
+/// twice, and scans of the fronts and backs of sheets, interleaved.
+const MANIFEST: &str = r#"{
+    "sections": [
+        {"file": "report.pdf", "pages": 1, "bookmark": "Cover"},
+        {"file": "report.pdf", "pages": "2-3", "rotate": 90},
+        {"file": "report.pdf", "pages": 2},
+        {
+            "interleave": [
+                {"file": "front/scan.pdf"},
+                {"file": "back/scan.pdf", "reverse": true}
+            ],
+            "bookmark": "Scans"
+        }
+    ]
+}"#;
+
+/// Return the text shown by a page, and its rotation.
+fn describe_page(document: &Document, page_id: lopdf::ObjectId) -> String {
+    let content = String::from_utf8(document.get_page_content(page_id).unwrap()).unwrap();
+    let text: Vec<&str> = content
+        .split('(')
+        .skip(1)
+        .filter_map(|text| text.split_once(") Tj").map(|(text, _)| text))
+        .collect();
+    let rotation = document
+        .get_dictionary(page_id)
+        .and_then(|page| page.get(b"Rotate"))
+        .and_then(Object::as_i64)
+        .unwrap_or(0);
+    format!("{} @{rotation}", text.join(" "))
+}
+
+#[test]
+fn assemble_follows_manifest() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "report.pdf", &["--pages", "3"]);
+    make_fixture(dir.path(), "scan.pdf", &["--pages", "2"]);
+    // Fronts and backs are told apart by their Bates numbers.
+    for (prefix, dest_dir) in [("F-", "front"), ("B-", "back")] {
+        rpdf()
+            .current_dir(dir.path())
+            .args([
+                "--quiet", "stamp", "bates", "scan.pdf", "--prefix", prefix, "--digits", "1", "-d",
+                dest_dir,
+            ])
+            .assert()
+            .success();
+    }
+    fs::write(dir.path().join("manifest.json"), MANIFEST).unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["assemble", "manifest.json"])
+        .assert()
+        .success()
+        .stdout("Successfully assembled 8 pages from 3 files to \"assembled.pdf\".\n");
+
+    let assembled = Document::load(dir.path().join("assembled.pdf")).unwrap();
+    let pages: Vec<String> = assembled
+        .get_pages()
+        .into_values()
+        .map(|page_id| describe_page(&assembled, page_id))
+        .collect();
+    assert_eq!(
+        pages,
+        [
+            "Page 1 @0",
+            "Page 2 @90",
+            "Page 3 @90",
+            "Page 2 @0",
+            "Page 1 F-1 @0",
+            "Page 2 B-2 @0",
+            "Page 2 F-2 @0",
+            "Page 1 B-1 @0",
+        ]
+    );
+
+    let outline = assembled
+        .catalog()
+        .and_then(|catalog| catalog.get(b"Outlines"))
+        .and_then(Object::as_reference)
+        .and_then(|id| assembled.get_dictionary(id))
+        .unwrap();
+    assert_eq!(outline.get(b"Count").and_then(Object::as_i64).unwrap(), 2);
+}
+
+#[test]
+fn assemble_rejects_empty_selection() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "report.pdf", &["--pages", "2"]);
+    fs::write(
+        dir.path().join("manifest.json"),
+        r#"{"sections": [{"file": "report.pdf", "pages": "5-"}]}"#,
+    )
+    .unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["assemble", "manifest.json"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "No page of \"report.pdf\" matches the selection 5-.",
+        ));
+}
+
+#[test]
+fn assemble_reads_yaml_manifest() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "report.pdf", &["--pages", "3"]);
+    fs::write(
+        dir.path().join("manifest.yaml"),
+        "\
+# Cover, then the other pages, rotated.
+sections:
+  - file: report.pdf
+    pages: 1
+    bookmark: Cover
+  - file: report.pdf
+    pages: 2-3
+    reverse: true
+    rotate: 90
+",
+    )
+    .unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "assemble", "manifest.yaml"])
+        .assert()
+        .success();
+
+    let assembled = Document::load(dir.path().join("assembled.pdf")).unwrap();
+    let pages: Vec<String> = assembled
+        .get_pages()
+        .into_values()
+        .map(|page_id| describe_page(&assembled, page_id))
+        .collect();
+    assert_eq!(pages, ["Page 1 @0", "Page 3 @90", "Page 2 @90"]);
+}
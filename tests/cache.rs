@@ -0,0 +1,92 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{make_fixture, rpdf};
+use tempfile::TempDir;
+
+/// Return the files of a directory and its subdirectories.
+fn files(dir: &Path) -> Vec<std::path::PathBuf> {
+    let mut files = vec![];
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                files.extend(self::files(&path));
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Return the output of `text wordcount` on a file, as JSON.
+fn wordcount(dir: &Path, cache: &Path, no_cache: bool) -> String {
+    let output = rpdf()
+        .current_dir(dir)
+        .env("RPDF_NO_CACHE", if no_cache { "1" } else { "0" })
+        .arg("--cache-dir")
+        .arg(cache)
+        .args(["text", "wordcount", "fixture.pdf", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn cache_holds_text_of_pages() {
+    let dir = TempDir::new().unwrap();
+    let cache = dir.path().join("cache");
+    make_fixture(dir.path(), "fixture.pdf", &["--pages", "2"]);
+
+    wordcount(dir.path(), &cache, true);
+    assert!(files(&cache).is_empty());
+
+    let expected = wordcount(dir.path(), &cache, false);
+    let mut cached = files(&cache);
+    cached.sort();
+    let names: Vec<_> = cached
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(names, ["text-1.json", "text-2.json"]);
+
+    // Cached text is used instead of the text of the page.
+    fs::write(&cached[0], "[]").unwrap();
+    assert_ne!(wordcount(dir.path(), &cache, false), expected);
+    assert_eq!(wordcount(dir.path(), &cache, true), expected);
+
+    rpdf()
+        .current_dir(dir.path())
+        .arg("--cache-dir")
+        .arg(&cache)
+        .args(["--quiet", "cache", "clear"])
+        .assert()
+        .success();
+    assert!(!cache.exists());
+    assert_eq!(wordcount(dir.path(), &cache, false), expected);
+}
+
+#[test]
+fn cache_clear_keeps_foreign_files() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &[]);
+    wordcount(dir.path(), dir.path(), false);
+    fs::create_dir(dir.path().join("notes")).unwrap();
+    fs::write(dir.path().join("notes").join("todo.txt"), "keep me").unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--cache-dir", ".", "--quiet", "cache", "clear"])
+        .assert()
+        .success();
+    assert!(dir.path().join("fixture.pdf").exists());
+    assert!(dir.path().join("notes").join("todo.txt").exists());
+    let names: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(names.len(), 2, "{names:?}");
+}
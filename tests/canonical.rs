@@ -0,0 +1,63 @@
+use lopdf::{Document, Object, ObjectId};
+use rpdf::pdf::canonical::Serializer;
+
+/// Return an array of literal strings.
+fn strings(items: &[&str]) -> Object {
+    Object::Array(
+        items
+            .iter()
+            .map(|&item| Object::string_literal(item))
+            .collect(),
+    )
+}
+
+#[test]
+fn strings_and_names_are_delimited_by_their_length() {
+    let document = Document::with_version("1.7");
+    let mut serializer = Serializer::new(&document);
+
+    assert_ne!(
+        serializer.hash(&strings(&["ab", "c"])),
+        serializer.hash(&strings(&["a", "bc"]))
+    );
+    assert_ne!(
+        serializer.hash(&Object::Array(vec!["ab".into(), "c".into()])),
+        serializer.hash(&Object::Array(vec!["a".into(), "bc".into()]))
+    );
+    assert_eq!(
+        serializer.hash(&strings(&["ab", "c"])),
+        serializer.hash(&strings(&["ab", "c"]))
+    );
+}
+
+#[test]
+fn shared_objects_are_resolved_once() {
+    // Each array points twice to the next one, so that resolving the first
+    // one without memoization would visit 2^64 arrays.
+    let mut document = Document::with_version("1.7");
+    let mut next: ObjectId = document.add_object(Object::Null);
+    for _ in 0..64 {
+        next = document.add_object(vec![Object::Reference(next), Object::Reference(next)]);
+    }
+    let mut serializer = Serializer::new(&document).resolve_references(true);
+
+    assert_ne!(
+        serializer.hash(&Object::Reference(next)),
+        serializer.hash(&Object::Null)
+    );
+}
+
+#[test]
+fn cycles_are_resolved() {
+    let mut document = Document::with_version("1.7");
+    let id = document.new_object_id();
+    document
+        .objects
+        .insert(id, Object::Array(vec![Object::Reference(id)]));
+    let mut serializer = Serializer::new(&document).resolve_references(true);
+
+    assert_eq!(
+        serializer.hash(&Object::Reference(id)),
+        serializer.hash(&Object::Reference(id))
+    );
+}
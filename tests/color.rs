@@ -0,0 +1,232 @@
+mod common;
+
+use std::path::Path;
+
+use common::{make_fixture, pdf_snapshot, rpdf};
+use insta::assert_snapshot;
+use lopdf::{Document, Object, Stream, dictionary};
+use predicates::{prelude::*, str::contains};
+use tempfile::TempDir;
+
+/// Write a page with RGB fill and stroke colors, an RGB color space
+/// resource, an RGB image and an axial RGB shading.
+fn write_rgb_pdf(path: &Path) {
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+
+    let mut image = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => 2,
+            "Height" => 1,
+            "ColorSpace" => "DeviceRGB",
+            "BitsPerComponent" => 8,
+        },
+        vec![255, 0, 0, 0, 0, 255],
+    );
+    image.compress().unwrap();
+    let image_id = document.add_object(image);
+    let shading_id = document.add_object(dictionary! {
+        "ShadingType" => 2,
+        "ColorSpace" => "DeviceRGB",
+        "Coords" => vec![0.into(), 0.into(), 100.into(), 0.into()],
+        "Function" => dictionary! {
+            "FunctionType" => 2,
+            "Domain" => vec![0.into(), 1.into()],
+            "C0" => vec![1.into(), 0.into(), 0.into()],
+            "C1" => vec![0.into(), 0.into(), 1.into()],
+            "N" => 1,
+        },
+    });
+    let content_id = document.add_object(Stream::new(
+        dictionary! {},
+        b"1 0 0 rg 0 0 100 100 re f /CS0 cs 0 0 1 sc 0 1 0 RG 10 10 m 20 20 l S \
+          q 100 0 0 50 0 0 cm /Im0 Do Q /Sh0 sh"
+            .to_vec(),
+    ));
+    let page_id = document.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 200.into(), 200.into()],
+        "Contents" => content_id,
+        "Resources" => dictionary! {
+            "ColorSpace" => dictionary! { "CS0" => "DeviceRGB" },
+            "XObject" => dictionary! { "Im0" => image_id },
+            "Shading" => dictionary! { "Sh0" => shading_id },
+        },
+    });
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+    document.save(path).unwrap();
+}
+
+/// Return a fake ICC profile of the given color space, with only a
+/// description tag.
+fn icc_profile(color_space: &[u8; 4], description: &str) -> Vec<u8> {
+    let mut profile = vec![0; 128];
+    profile[16..20].copy_from_slice(color_space);
+    let mut tag = b"desc\0\0\0\0".to_vec();
+    tag.extend_from_slice(&(description.len() as u32 + 1).to_be_bytes());
+    tag.extend_from_slice(description.as_bytes());
+    tag.push(0);
+
+    profile.extend_from_slice(&1u32.to_be_bytes());
+    profile.extend_from_slice(b"desc");
+    profile.extend_from_slice(&144u32.to_be_bytes());
+    profile.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+    profile.extend(tag);
+    profile
+}
+
+#[test]
+fn convert_to_gray() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("rgb.pdf");
+    let output = dir.path().join("gray.pdf");
+    write_rgb_pdf(&input);
+
+    rpdf()
+        .args(["color", "convert", "--to", "gray"])
+        .arg(&input)
+        .arg("--dest")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(contains(
+            "Converted 4 color operators, 1 images and 1 shadings.",
+        ));
+
+    assert_snapshot!(pdf_snapshot(&output));
+}
+
+#[test]
+fn convert_to_cmyk_with_profile() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("rgb.pdf");
+    let output = dir.path().join("cmyk.pdf");
+    write_rgb_pdf(&input);
+    std::fs::write(
+        dir.path().join("gray.icc"),
+        icc_profile(b"GRAY", "Gray Gamma 2.2"),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("coated.icc"),
+        icc_profile(b"CMYK", "Coated FOGRA39"),
+    )
+    .unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "color", "convert", "rgb.pdf", "--to", "cmyk", "--icc", "gray.icc",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("is not a cmyk profile"));
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "color", "convert", "rgb.pdf", "--to", "cmyk"])
+        .args(["--icc", "coated.icc", "--dest", "cmyk.pdf"])
+        .assert()
+        .success();
+
+    let document = Document::load(&output).unwrap();
+    let intents = document
+        .catalog()
+        .unwrap()
+        .get(b"OutputIntents")
+        .unwrap()
+        .as_array()
+        .unwrap();
+    let intent = document
+        .get_dictionary(intents[0].as_reference().unwrap())
+        .unwrap();
+    assert_eq!(intent.get(b"S").unwrap().as_name().unwrap(), b"GTS_PDFX");
+
+    let page_id = document.page_iter().next().unwrap();
+    let content = document.get_and_decode_page_content(page_id).unwrap();
+    let operators: Vec<&str> = content
+        .operations
+        .iter()
+        .map(|operation| operation.operator.as_str())
+        .collect();
+    assert!(operators.contains(&"k") && operators.contains(&"K"));
+    assert!(!operators.contains(&"rg") && !operators.contains(&"RG"));
+}
+
+#[test]
+fn report_color_pages() {
+    let dir = TempDir::new().unwrap();
+    write_rgb_pdf(&dir.path().join("rgb.pdf"));
+    make_fixture(dir.path(), "gray.pdf", &["--pages", "2"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["color", "report", "gray.pdf"])
+        .assert()
+        .success()
+        .stdout(contains("Color pages: none").and(contains("Gray pages: 1-2")));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["color", "report", "rgb.pdf", "--format", "json"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let page = &report["pages"][0];
+
+    assert_eq!(page["color"], true);
+    // The red square alone covers a quarter of the page.
+    assert!(page["coverage"]["magenta"].as_f64().unwrap() > 25.0);
+}
+
+#[test]
+fn output_intent_set_and_show() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &[]);
+    std::fs::write(
+        dir.path().join("sRGB.icc"),
+        icc_profile(b"RGB ", "sRGB IEC61966-2.1"),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("coated.icc"),
+        icc_profile(b"CMYK", "Coated FOGRA39"),
+    )
+    .unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "color", "output-intent", "fixture.pdf"])
+        .args(["--set", "sRGB.icc", "--dest", "archive.pdf"])
+        .assert()
+        .success();
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "color", "output-intent", "archive.pdf"])
+        .args(["--set", "coated.icc", "--subtype", "GTS_PDFX"])
+        .args(["--identifier", "FOGRA39", "--dest", "print.pdf"])
+        .assert()
+        .success();
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["color", "output-intent", "print.pdf", "--show"])
+        .output()
+        .unwrap();
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
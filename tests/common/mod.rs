@@ -0,0 +1,270 @@
+//! Helpers shared by integration tests.
+//!
+//! Output PDFs are compared with golden files (insta snapshots) after being
+//! normalized, so that dates, file identifiers and the producer version do
+//! not make snapshots change on every run.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use lopdf::{Dictionary, Document, Object, Stream, dictionary};
+use md5::{Digest, Md5};
+
+/// Date that replaces all dates in normalized PDFs.
+const NORMALIZED_DATE: &str = "D:20000101000000Z";
+
+/// Entries holding dates, which change on every run.
+const DATE_KEYS: &[&[u8]] = &[b"CreationDate", b"ModDate", b"M"];
+
+/// Padding of passwords, from the standard security handler.
+const PASSWORD_PADDING: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01, 0x08,
+    0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53, 0x69, 0x7A,
+];
+
+/// File identifier of handwritten PDFs.
+const FILE_ID: &[u8; 16] = b"rpdf test file 1";
+
+/// Permissions of encrypted PDFs, i.e., all of them.
+const PERMISSIONS: i32 = -4;
+
+/// Return a command running rpdf, never prompting for input, nor caching
+/// data in the user's cache directory, with messages in English.
+pub fn rpdf() -> Command {
+    let mut command = Command::cargo_bin("rpdf").expect("Failed to find rpdf binary");
+    command
+        .arg("--no-input")
+        .env_remove("RPDF_PASSWORD")
+        .env_remove("SOURCE_DATE_EPOCH")
+        .env_remove("RPDF_CACHE_DIR")
+        .env_remove("RPDF_NO_JOURNAL")
+        .env_remove("RPDF_LANG")
+        .env_remove("LC_ALL")
+        .env_remove("LC_MESSAGES")
+        .env("LANG", "C.UTF-8")
+        .env("RPDF_NO_CACHE", "1");
+    command
+}
+
+/// Return the path to a sample file shipped in the `tests` directory.
+pub fn sample(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name)
+}
+
+/// Build a fixture with `rpdf debug make-fixture`, passing it the given
+/// arguments, and return its path.
+pub fn make_fixture(dir: &Path, name: &str, args: &[&str]) -> PathBuf {
+    let path = dir.join(name);
+    rpdf()
+        .args(["--quiet", "debug", "make-fixture", "--dest"])
+        .arg(&path)
+        .args(args)
+        .assert()
+        .success();
+    path
+}
+
+/// Replace nondeterministic values of a dictionary, at any depth.
+fn normalize_dictionary(dict: &mut Dictionary) {
+    for (key, value) in dict.iter_mut() {
+        if DATE_KEYS.contains(&key.as_slice()) && value.as_str().is_ok() {
+            *value = Object::string_literal(NORMALIZED_DATE);
+        } else if key == b"Producer" {
+            *value = Object::string_literal("rpdf");
+        } else {
+            normalize_object(value);
+        }
+    }
+}
+
+/// Replace nondeterministic values of an object, at any depth.
+fn normalize_object(object: &mut Object) {
+    match object {
+        Object::Dictionary(dict) => normalize_dictionary(dict),
+        Object::Stream(stream) => normalize_dictionary(&mut stream.dict),
+        Object::Array(array) => array.iter_mut().for_each(normalize_object),
+        _ => {},
+    }
+}
+
+/// Normalize a PDF so that it can be compared byte-for-byte.
+///
+/// Streams are decompressed, dates are replaced with a fixed date, the
+/// producer is replaced with `rpdf`, the file identifier (`/ID`) is removed,
+/// and the document is written again, with objects in ascending order.
+pub fn normalize_pdf(bytes: &[u8]) -> Vec<u8> {
+    let mut document = Document::load_mem(bytes).expect("Failed to parse PDF");
+    document.decompress();
+    document.trailer.remove(b"ID");
+    for object in document.objects.values_mut() {
+        normalize_object(object);
+    }
+
+    let mut normalized = vec![];
+    document
+        .save_to(&mut normalized)
+        .expect("Failed to write PDF");
+    normalized
+}
+
+/// Return a normalized PDF as text, suitable for snapshots.
+///
+/// Bytes that are not printable ASCII are escaped, so that the snapshot
+/// still holds the exact bytes.
+pub fn pdf_snapshot(path: &Path) -> String {
+    let bytes = std::fs::read(path).expect("Failed to read PDF");
+    normalize_pdf(&bytes)
+        .into_iter()
+        .map(|byte| {
+            match byte {
+                b'\n' | b' '..=b'~' if byte != b'\\' => char::from(byte).to_string(),
+                _ => std::ascii::escape_default(byte).to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Encrypt or decrypt data with RC4.
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state: Vec<u8> = (0..=255).collect();
+    let mut j = 0u8;
+
+    for i in 0..256 {
+        j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+        state.swap(i, usize::from(j));
+    }
+
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|&byte| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(state[usize::from(i)]);
+            state.swap(usize::from(i), usize::from(j));
+            byte ^ state[usize::from(state[usize::from(i)].wrapping_add(state[usize::from(j)]))]
+        })
+        .collect()
+}
+
+/// Pad or truncate a password to 32 bytes.
+fn pad_password(password: &str) -> Vec<u8> {
+    password.bytes().chain(PASSWORD_PADDING).take(32).collect()
+}
+
+/// Return the encryption dictionary, in PDF syntax, and the file key of
+/// 40-bit RC4 encryption (revision 2 of the standard security handler).
+fn rc4_encryption(user_password: &str) -> (String, Vec<u8>) {
+    let owner_key = &Md5::digest(pad_password("owner"))[..5];
+    let owner = rc4(owner_key, &pad_password(user_password));
+
+    let mut hasher = Md5::new();
+    hasher.update(pad_password(user_password));
+    hasher.update(&owner);
+    hasher.update(PERMISSIONS.to_le_bytes());
+    hasher.update(FILE_ID);
+    let key = hasher.finalize()[..5].to_vec();
+    let user = rc4(&key, &PASSWORD_PADDING);
+
+    let dict = format!(
+        "<</Filter/Standard/V 1/R 2/O<{}>/U<{}>/P {PERMISSIONS}>>",
+        hex(&owner),
+        hex(&user)
+    );
+    (dict, key)
+}
+
+/// Write a PDF as Acrobat does, with all `objects` stored in a compressed
+/// object stream, and encrypted with the given user password if any.
+///
+/// Objects are written in PDF syntax and numbered from 1, and the first one
+/// is the catalog.
+pub fn write_object_stream_pdf(path: &Path, objects: &[&str], user_password: Option<&str>) {
+    let count = objects.len() as u32;
+    let (stream_number, xref_number, encrypt_number) = (count + 1, count + 2, count + 3);
+    let encryption = user_password.map(rc4_encryption);
+
+    let mut header = String::new();
+    let mut body = String::new();
+    for (number, object) in (1..).zip(objects) {
+        header += &format!("{number} {} ", body.len());
+        body += object;
+        body.push('\n');
+    }
+    let first = header.len() + 1;
+    let mut stream = Stream::new(dictionary! {}, format!("{header}\n{body}").into_bytes());
+    stream.compress().expect("Failed to compress object stream");
+    let filter = if stream.dict.has(b"Filter") {
+        "/Filter/FlateDecode"
+    } else {
+        ""
+    };
+    let mut content = stream.content;
+
+    if let Some((_, key)) = &encryption {
+        let mut object_key = key.clone();
+        object_key.extend_from_slice(&stream_number.to_le_bytes()[..3]);
+        object_key.extend_from_slice(&[0, 0]);
+        content = rc4(&Md5::digest(object_key)[..10], &content);
+    }
+
+    let mut file = b"%PDF-1.5\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = vec![];
+
+    offsets.push((stream_number, file.len()));
+    file.extend(
+        format!(
+            "{stream_number} 0 obj\n<</Type/ObjStm/N {count}/First {first}/Length \
+             {}{filter}>>\nstream\n",
+            content.len()
+        )
+        .bytes(),
+    );
+    file.extend(&content);
+    file.extend(b"\nendstream\nendobj\n");
+
+    let mut trailer = format!("/Root 1 0 R/ID[<{hex}><{hex}>]", hex = hex(FILE_ID));
+    if let Some((dict, _)) = &encryption {
+        offsets.push((encrypt_number, file.len()));
+        file.extend(format!("{encrypt_number} 0 obj\n{dict}\nendobj\n").bytes());
+        trailer += &format!("/Encrypt {encrypt_number} 0 R");
+    }
+
+    let xref_offset = file.len();
+    offsets.push((xref_number, xref_offset));
+    let size = offsets.iter().map(|&(number, _)| number).max().unwrap() + 1;
+    let mut xref = vec![0u8; 7 * size as usize];
+
+    for number in 0..size {
+        let entry = &mut xref[7 * number as usize..][..7];
+        if let Some(&(_, offset)) = offsets.iter().find(|&&(n, _)| n == number) {
+            entry[0] = 1;
+            entry[1..5].copy_from_slice(&(offset as u32).to_be_bytes());
+        } else if (1..=count).contains(&number) {
+            entry[0] = 2;
+            entry[1..5].copy_from_slice(&stream_number.to_be_bytes());
+            entry[5..7].copy_from_slice(&((number - 1) as u16).to_be_bytes());
+        } else {
+            entry[5..7].copy_from_slice(&u16::MAX.to_be_bytes());
+        }
+    }
+
+    file.extend(
+        format!(
+            "{xref_number} 0 obj\n<</Type/XRef/Size {size}/W[1 4 2]{trailer}/Length {}>>\nstream\n",
+            xref.len()
+        )
+        .bytes(),
+    );
+    file.extend(&xref);
+    file.extend(format!("\nendstream\nendobj\nstartxref\n{xref_offset}\n%%EOF\n").bytes());
+
+    std::fs::write(path, file).expect("Failed to write PDF");
+}
+
+/// Return bytes as a hexadecimal string.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
@@ -0,0 +1,267 @@
+mod common;
+
+#[cfg(feature = "convert")]
+use common::make_fixture;
+use common::{rpdf, write_object_stream_pdf};
+use lopdf::Document;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+/// Objects of a document with layers, which need PDF 1.5.
+const LAYERS_OBJECTS: &[&str] = &[
+    "<</Type/Catalog/Pages 2 0 R/OCProperties<</OCGs[4 0 R]/D<</ON[4 0 R]>>>>>>",
+    "<</Type/Pages/Kids[3 0 R]/Count 1/MediaBox[0 0 612 792]>>",
+    "<</Type/Page/Parent 2 0 R>>",
+    "<</Type/OCG/Name(Notes)>>",
+];
+
+/// Return whether `needle` occurs in `haystack`.
+fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[test]
+fn convert_version_rewrites_header_and_cross_reference_table() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("layers.pdf"), LAYERS_OBJECTS, None);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["-v", "convert", "version", "layers.pdf", "--to", "1.4"])
+        .args(["--dest", "layers-1.4.pdf"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Successfully converted \"layers.pdf\" from PDF 1.5 to PDF 1.4",
+        ))
+        .stderr(contains(
+            "uses optional content (layers), which needs PDF 1.5 and may be ignored by PDF 1.4 \
+             readers",
+        ));
+
+    let data = std::fs::read(dir.path().join("layers-1.4.pdf")).unwrap();
+    assert!(data.starts_with(b"%PDF-1.4\n"));
+    assert!(contains_bytes(&data, b"\nxref\n"));
+    assert!(!contains_bytes(&data, b"/XRef"));
+    assert!(!contains_bytes(&data, b"/ObjStm"));
+
+    let document = Document::load_mem(&data).unwrap();
+    assert_eq!(document.version, "1.4");
+    assert_eq!(document.get_pages().len(), 1);
+    assert!(document.catalog().unwrap().has(b"OCProperties"));
+}
+
+#[test]
+fn convert_version_to_newer_version_does_not_warn() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("layers.pdf"), LAYERS_OBJECTS, None);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["-v", "convert", "version", "layers.pdf", "--to", "2.0"])
+        .args(["--dest", "layers-2.0.pdf"])
+        .assert()
+        .success()
+        .stderr("");
+
+    let data = std::fs::read(dir.path().join("layers-2.0.pdf")).unwrap();
+    assert!(data.starts_with(b"%PDF-2.0\n"));
+    assert_eq!(Document::load_mem(&data).unwrap().version, "2.0");
+}
+
+#[test]
+fn convert_version_rejects_unknown_versions() {
+    rpdf()
+        .args(["convert", "version", "layers.pdf", "--to", "1.10"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid value '1.10'"));
+}
+
+#[cfg(feature = "convert")]
+#[test]
+fn convert_svg_writes_one_page() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &["--pages", "3"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "convert", "svg", "fixture.pdf", "--page", "2"])
+        .args(["--dest", "page.svg"])
+        .assert()
+        .success();
+
+    let svg = std::fs::read_to_string(dir.path().join("page.svg")).unwrap();
+    let root = svg
+        .strip_prefix("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")
+        .unwrap();
+    assert!(root.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" "));
+    assert!(root.ends_with("</svg>\n"));
+    assert_eq!(root.matches("<svg ").count(), 1);
+    assert!(root.contains(" width=\"612pt\" height=\"792pt\" viewBox=\"0 0 612 792\""));
+    assert!(root.contains(">Page 2</text>"));
+    assert!(!root.contains(">Page 1</text>"));
+    assert!(!root.contains(">Page 3</text>"));
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["convert", "svg", "fixture.pdf", "--page", "4"])
+        .assert()
+        .failure()
+        .stderr(contains("Document \"fixture.pdf\" has no page 4."));
+}
+
+#[cfg(feature = "convert")]
+#[test]
+fn convert_svg_swaps_view_box_of_rotated_pages() {
+    let dir = TempDir::new().unwrap();
+    let path = make_fixture(dir.path(), "fixture.pdf", &[]);
+    let mut document = Document::load(&path).unwrap();
+    let page_id = document.page_iter().next().unwrap();
+    document
+        .get_dictionary_mut(page_id)
+        .unwrap()
+        .set("Rotate", 90);
+    document.save(&path).unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "--quiet",
+            "convert",
+            "svg",
+            "fixture.pdf",
+            "--dest",
+            "page.svg",
+        ])
+        .assert()
+        .success();
+
+    let svg = std::fs::read_to_string(dir.path().join("page.svg")).unwrap();
+    assert!(svg.contains(" width=\"792pt\" height=\"612pt\" viewBox=\"0 0 792 612\""));
+    assert!(svg.contains("<g transform=\"translate(792 0) rotate(90)\">"));
+}
+
+#[cfg(feature = "convert")]
+#[test]
+fn convert_pdfa_writes_metadata_and_output_intent() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("layers.pdf"), LAYERS_OBJECTS, None);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["convert", "pdfa", "layers.pdf", "--level", "3b"])
+        .args(["--dest", "pdfa.pdf"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Successfully converted \"layers.pdf\" to PDF/A in \"pdfa.pdf\".",
+        ));
+
+    let document = Document::load(dir.path().join("pdfa.pdf")).unwrap();
+    let metadata = rpdf::pdf::xmp::metadata(&document).unwrap();
+    assert!(metadata.contains("<pdfaid:part>3</pdfaid:part>"));
+    assert!(metadata.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+
+    let catalog = document.catalog().unwrap();
+    let intents = catalog.get(b"OutputIntents").unwrap().as_array().unwrap();
+    assert_eq!(intents.len(), 1);
+    let intent = document
+        .get_dictionary(intents[0].as_reference().unwrap())
+        .unwrap();
+    assert_eq!(intent.get(b"S").unwrap().as_name().unwrap(), b"GTS_PDFA1");
+    let profile = document
+        .get_object(
+            intent
+                .get(b"DestOutputProfile")
+                .unwrap()
+                .as_reference()
+                .unwrap(),
+        )
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    assert_eq!(profile.dict.get(b"N").unwrap().as_i64().unwrap(), 3);
+}
+
+#[cfg(feature = "convert")]
+#[test]
+fn convert_pdfa_fails_on_fonts_that_are_not_embedded() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &[]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["convert", "pdfa", "fixture.pdf", "--dest", "pdfa.pdf"])
+        .assert()
+        .failure()
+        .stderr(contains("Font Helvetica (2 0 R) is not embedded"))
+        .stderr(contains(
+            "File \"pdfa.pdf\" is not PDF/A compliant, as 1 fonts are not embedded.",
+        ));
+    assert!(dir.path().join("pdfa.pdf").exists());
+}
+
+#[cfg(feature = "convert")]
+#[test]
+fn convert_pdfa_1b_sets_version_and_cross_reference_table() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("layers.pdf"), LAYERS_OBJECTS, None);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "convert", "pdfa", "layers.pdf", "--level", "1b"])
+        .args(["--dest", "pdfa.pdf"])
+        .assert()
+        .success();
+
+    let data = std::fs::read(dir.path().join("pdfa.pdf")).unwrap();
+    assert!(data.starts_with(b"%PDF-1.4\n"));
+    assert!(contains_bytes(&data, b"\nxref\n"));
+    assert!(!contains_bytes(&data, b"/XRef"));
+    assert!(!contains_bytes(&data, b"/ObjStm"));
+
+    let document = Document::load_mem(&data).unwrap();
+    let metadata = rpdf::pdf::xmp::metadata(&document).unwrap();
+    assert!(metadata.contains("<pdfaid:part>1</pdfaid:part>"));
+}
+
+#[cfg(feature = "convert")]
+#[test]
+fn convert_pdfa_drops_encryption_only_with_the_password() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(
+        &dir.path().join("secret.pdf"),
+        LAYERS_OBJECTS,
+        Some("secret"),
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["convert", "pdfa", "secret.pdf", "--dest", "pdfa.pdf"])
+        .assert()
+        .failure()
+        .stderr(contains("is the password correct?"));
+    assert!(!dir.path().join("pdfa.pdf").exists());
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "--quiet",
+            "--password",
+            "secret",
+            "convert",
+            "pdfa",
+            "secret.pdf",
+        ])
+        .args(["--dest", "pdfa.pdf"])
+        .assert()
+        .success();
+
+    let document = Document::load(dir.path().join("pdfa.pdf")).unwrap();
+    assert!(!document.is_encrypted());
+    assert!(!document.trailer.has(b"Encrypt"));
+    assert!(document.catalog().unwrap().has(b"OutputIntents"));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::rpdf;
+
+#[cfg(feature = "regex")]
+#[test]
+fn daemon_answers_requests_until_shutdown() {
+    use common::make_fixture;
+    use insta::assert_snapshot;
+    use tempfile::TempDir;
+
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &["--annotations", "3"]);
+    let requests = [
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "stats", "params": {"file": "fixture.pdf"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "list", "params": {"file": "fixture.pdf", "exclude": ["Text"]}}"#,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "search", "params": {"file": "fixture.pdf", "pattern": "^Page \\d$"}}"#,
+        r#"{"jsonrpc": "2.0", "method": "stats", "params": {"file": "fixture.pdf"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 4, "method": "stats", "params": {"file": "missing.pdf"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 5, "method": "search", "params": {"file": "fixture.pdf"}}"#,
+        r#"{"jsonrpc": "2.0", "id": 6, "method": "unknown"}"#,
+        "not JSON",
+        r#"{"jsonrpc": "2.0", "id": 7, "method": "shutdown"}"#,
+        r#"{"jsonrpc": "2.0", "id": 8, "method": "stats", "params": {"file": "fixture.pdf"}}"#,
+    ];
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .arg("daemon")
+        .write_stdin(requests.join("\n"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn daemon_answers_requests_with_null_identifier() {
+    let requests = [
+        r#"{"jsonrpc": "2.0", "id": null, "method": "unknown"}"#,
+        r#"{"jsonrpc": "2.0", "method": "unknown"}"#,
+    ];
+
+    rpdf()
+        .arg("daemon")
+        .write_stdin(requests.join("\n"))
+        .assert()
+        .success()
+        .stdout(concat!(
+            r#"{"error":{"code":-32601,"message":"Unknown method: \"unknown\"."},"id":null,"#,
+            r#""jsonrpc":"2.0"}"#,
+            "\n"
+        ));
+}
+
+#[cfg(not(feature = "regex"))]
+#[test]
+fn daemon_search_requires_regex_feature() {
+    let request = r#"{"jsonrpc": "2.0", "id": 1, "method": "search", "params": {}}"#;
+
+    rpdf()
+        .arg("daemon")
+        .write_stdin(request)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            r#"{"code":-32601,"message":"The search method requires the `regex` feature."}"#,
+        ));
+}
@@ -0,0 +1,91 @@
+use lopdf::{Document, Object, ObjectId, dictionary};
+use rpdf::pdf::dedup::{self, Deduplicator};
+
+/// Add a standard font.
+fn add_font(document: &mut Document) -> ObjectId {
+    document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    })
+}
+
+/// Add a page drawn with the given resources, which is not part of a page
+/// tree, as only resources matter here.
+fn add_page(document: &mut Document, resources: lopdf::Dictionary) -> ObjectId {
+    document.add_object(dictionary! {
+        "Type" => "Page",
+        "Resources" => resources,
+    })
+}
+
+#[test]
+fn optional_content_groups_without_type_are_not_merged() {
+    let mut document = Document::with_version("1.7");
+    let fonts = [add_font(&mut document), add_font(&mut document)];
+    let groups = [(); 2].map(|()| {
+        document.add_object(dictionary! {
+            "Name" => Object::string_literal("Notes"),
+        })
+    });
+    let forms = groups.map(|group| {
+        document.add_object(lopdf::Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+                "OC" => group,
+            },
+            b"0 0 10 10 re f".to_vec(),
+        ))
+    });
+    add_page(
+        &mut document,
+        dictionary! {
+            "Font" => dictionary! { "F1" => fonts[0], "F2" => fonts[1] },
+            "Properties" => dictionary! { "MC0" => groups[0], "MC1" => groups[1] },
+            "XObject" => dictionary! { "X1" => forms[0], "X2" => forms[1] },
+        },
+    );
+
+    assert_eq!(dedup::deduplicate_objects(&mut document), 1);
+    assert!(document.objects.contains_key(&fonts[0]));
+    assert!(!document.objects.contains_key(&fonts[1]));
+    for id in groups.into_iter().chain(forms) {
+        assert!(document.objects.contains_key(&id));
+    }
+}
+
+#[test]
+fn only_objects_added_after_indexing_are_replaced() {
+    let mut document = Document::with_version("1.7");
+    let fonts = [add_font(&mut document), add_font(&mut document)];
+    add_page(
+        &mut document,
+        dictionary! { "Font" => dictionary! { "F1" => fonts[0], "F2" => fonts[1] } },
+    );
+    let mut deduplicator = Deduplicator::new(&document);
+
+    // Objects of an imported page, numbered after the existing ones.
+    let first = document.max_id + 1;
+    let imported = add_font(&mut document);
+    let page_id = add_page(
+        &mut document,
+        dictionary! { "Font" => dictionary! { "F1" => imported } },
+    );
+
+    assert_eq!(deduplicator.deduplicate(&mut document, first), 1);
+    assert!(document.objects.contains_key(&fonts[0]));
+    assert!(document.objects.contains_key(&fonts[1]));
+    assert!(!document.objects.contains_key(&imported));
+    let font = document
+        .get_dictionary(page_id)
+        .and_then(|page| page.get_deref(b"Resources", &document))
+        .and_then(Object::as_dict)
+        .and_then(|resources| resources.get(b"Font"))
+        .and_then(Object::as_dict)
+        .and_then(|fonts| fonts.get(b"F1"))
+        .and_then(Object::as_reference)
+        .unwrap();
+    assert_eq!(font, fonts[0]);
+}
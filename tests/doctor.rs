@@ -0,0 +1,71 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use common::rpdf;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+/// Write an executable shell script.
+fn write_script(path: &Path, script: &str) {
+    fs::write(path, script).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn doctor_reports_environment() {
+    let dir = TempDir::new().unwrap();
+    let tesseract = dir.path().join("tesseract");
+    write_script(&tesseract, "#!/bin/sh\necho 'tesseract 5.3.0'\n");
+    write_script(&dir.path().join("rpdf-text"), "#!/bin/sh\n");
+    write_script(&dir.path().join("rpdf-hello"), "#!/bin/sh\n");
+
+    let assert = rpdf()
+        .env("PATH", dir.path())
+        .env("LANG", "C.UTF-8")
+        .env_remove("LC_ALL")
+        .env_remove("LC_CTYPE")
+        .env("RPDF_TESSERACT", &tesseract)
+        .env("RPDF_CAHCE_DIR", "typo")
+        .env("RPDF_NO_CACHE", "0")
+        .arg("--cache-dir")
+        .arg(dir.path().join("cache"))
+        .args(["--color=never", "doctor"])
+        .assert()
+        .success()
+        .stdout(contains("is writable"))
+        .stdout(contains(
+            "warning environment: RPDF_CAHCE_DIR is not used by rpdf",
+        ))
+        .stdout(contains("ok      locale: LANG=C.UTF-8"))
+        .stdout(contains("ok      plugins: `hello` runs"))
+        .stdout(contains("is shadowed by the built-in `text` command"))
+        .stdout(contains("Passed all checks, with 2 warnings."));
+
+    if cfg!(feature = "ocr") {
+        assert.stdout(contains("ok      tesseract: tesseract 5.3.0"));
+    } else {
+        assert.stdout(contains("skipped tesseract"));
+    }
+}
+
+#[test]
+fn doctor_fails_on_unwritable_cache() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("file");
+    fs::write(&file, "").unwrap();
+
+    rpdf()
+        .env("RPDF_NO_CACHE", "0")
+        .env("RPDF_TESSERACT", "true")
+        .arg("--cache-dir")
+        .arg(file.join("cache"))
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(contains("is not writable"))
+        .stdout(contains("use --no-cache."))
+        .stderr(contains("see above how to fix them."));
+}
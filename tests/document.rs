@@ -0,0 +1,127 @@
+use lopdf::{Document, Object, ObjectId, dictionary};
+use rpdf::pdf::document::DocumentExt;
+
+/// ID of the root node of [`page_tree`].
+const ROOT_ID: ObjectId = (1, 0);
+/// ID of the intermediate node of [`page_tree`].
+const NODE_ID: ObjectId = (2, 0);
+
+/// Build a document with a page under an intermediate `/Pages` node, with
+/// the given attributes on the root node and on the page, and return it with
+/// the ID of the page.
+fn page_tree(root: lopdf::Dictionary, page: lopdf::Dictionary) -> (Document, ObjectId) {
+    let mut document = Document::with_version("1.7");
+    let (root_id, node_id) = (ROOT_ID, NODE_ID);
+    let page_id = (3, 0);
+    document.max_id = 3;
+
+    let mut root_node = dictionary! {
+        "Type" => "Pages",
+        "Kids" => vec![Object::Reference(node_id)],
+        "Count" => 1,
+    };
+    for (key, value) in root {
+        root_node.set(key, value);
+    }
+    document.objects.insert(root_id, root_node.into());
+    document.objects.insert(
+        node_id,
+        dictionary! {
+            "Type" => "Pages",
+            "Parent" => root_id,
+            "Kids" => vec![Object::Reference(page_id)],
+            "Count" => 1,
+        }
+        .into(),
+    );
+    let mut page_node = dictionary! {
+        "Type" => "Page",
+        "Parent" => node_id,
+    };
+    for (key, value) in page {
+        page_node.set(key, value);
+    }
+    document.objects.insert(page_id, page_node.into());
+    (document, page_id)
+}
+
+/// Attributes of the root node of [`page_tree`].
+fn inherited_attributes() -> lopdf::Dictionary {
+    dictionary! {
+        "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        "Resources" => dictionary! {
+            "Font" => dictionary! {
+                "F1" => dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                },
+            },
+        },
+        "Rotate" => 90,
+    }
+}
+
+#[test]
+fn page_attributes_are_inherited_from_pages_nodes() {
+    let (document, page_id) = page_tree(inherited_attributes(), dictionary! {});
+
+    assert_eq!(
+        document.page_media_box(page_id),
+        Some([0.0, 0.0, 612.0, 792.0])
+    );
+    assert_eq!(
+        document.page_crop_box(page_id),
+        Some([0.0, 0.0, 612.0, 792.0])
+    );
+    assert!(document.page_resources(page_id).unwrap().has(b"Font"));
+    assert_eq!(document.page_rotation(page_id), 90);
+}
+
+#[test]
+fn page_attributes_override_inherited_ones() {
+    let (document, page_id) = page_tree(
+        inherited_attributes(),
+        dictionary! {
+            "MediaBox" => vec![0.into(), 0.into(), 100.into(), 200.into()],
+            "Resources" => dictionary! {},
+            "Rotate" => 180,
+        },
+    );
+
+    assert_eq!(
+        document.page_media_box(page_id),
+        Some([0.0, 0.0, 100.0, 200.0])
+    );
+    assert!(!document.page_resources(page_id).unwrap().has(b"Font"));
+    assert_eq!(document.page_rotation(page_id), 180);
+}
+
+#[test]
+fn page_attributes_are_dereferenced() {
+    let (mut document, page_id) = page_tree(dictionary! {}, dictionary! {});
+    let media_box = document.add_object(vec![0.into(), 0.into(), 300.into(), 400.into()]);
+    document
+        .get_dictionary_mut(ROOT_ID)
+        .unwrap()
+        .set("MediaBox", media_box);
+
+    assert_eq!(
+        document.page_media_box(page_id),
+        Some([0.0, 0.0, 300.0, 400.0])
+    );
+}
+
+#[test]
+fn page_attributes_stop_at_parent_cycles() {
+    let (mut document, page_id) = page_tree(dictionary! {}, dictionary! {});
+    // The root node becomes its own ancestor, through the intermediate node.
+    document
+        .get_dictionary_mut(ROOT_ID)
+        .unwrap()
+        .set("Parent", NODE_ID);
+
+    assert_eq!(document.page_attribute(page_id, b"MediaBox"), None);
+    assert_eq!(document.page_media_box(page_id), None);
+    assert_eq!(document.page_rotation(page_id), 0);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use tempfile::TempDir;
+
+/// Return the hash printed by `rpdf hash` for a file.
+fn hash(dir: &std::path::Path, file: &str, canonical: bool) -> String {
+    let mut cmd = rpdf();
+    cmd.current_dir(dir).args(["hash", file]);
+    if canonical {
+        cmd.arg("--canonical");
+    }
+    let output = cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn hash_is_sha256() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("abc.txt"), "abc").unwrap();
+
+    assert_eq!(
+        hash(dir.path(), "abc.txt", false),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  abc.txt\n"
+    );
+}
+
+#[test]
+fn canonical_hash_ignores_dates_and_identifier() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &["--annotations", "2"]);
+    for (dest, date) in [("first.pdf", "1700000000"), ("second.pdf", "1800000000")] {
+        rpdf()
+            .current_dir(dir.path())
+            .args(["--quiet", "--source-date-epoch", date])
+            .args(["annotations", "strip", "fixture.pdf", "--dest", dest])
+            .assert()
+            .success();
+    }
+
+    assert_ne!(
+        hash(dir.path(), "first.pdf", false)[..64],
+        hash(dir.path(), "second.pdf", false)[..64]
+    );
+    assert_eq!(
+        hash(dir.path(), "first.pdf", true)[..64],
+        hash(dir.path(), "second.pdf", true)[..64]
+    );
+    assert_ne!(
+        hash(dir.path(), "first.pdf", true)[..64],
+        hash(dir.path(), "fixture.pdf", true)[..64]
+    );
+}
+
+#[test]
+fn canonical_hash_ignores_xmp_dates() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+    let document = lopdf::Document::load(&fixture).unwrap();
+    let with_metadata = |name: &str, date: &str, title: &str| {
+        let mut document = document.clone();
+        let xmp = format!(
+            "<rdf:Description xmp:CreateDate=\"{date}\" \
+             xmpMM:InstanceID=\"uuid:{date}\"><xmp:ModifyDate>{date}</xmp:ModifyDate><dc:\
+             title>{title}</dc:title></rdf:Description>"
+        );
+        rpdf::pdf::xmp::set_metadata(&mut document, xmp).unwrap();
+        document.save(dir.path().join(name)).unwrap();
+    };
+    with_metadata("first.pdf", "2024-01-01", "Report");
+    with_metadata("second.pdf", "2025-06-30", "Report");
+    with_metadata("renamed.pdf", "2024-01-01", "Minutes");
+
+    assert_eq!(
+        hash(dir.path(), "first.pdf", true)[..64],
+        hash(dir.path(), "second.pdf", true)[..64]
+    );
+    assert_ne!(
+        hash(dir.path(), "first.pdf", true)[..64],
+        hash(dir.path(), "renamed.pdf", true)[..64]
+    );
+}
+
+#[test]
+fn manifest_lists_written_files() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "a.pdf", &["--annotations", "2"]);
+    make_fixture(dir.path(), "b.pdf", &["--annotations", "2"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "--quiet",
+            "--manifest",
+            "sha256.txt",
+            "annotations",
+            "strip",
+        ])
+        .args(["a.pdf", "b.pdf", "-n", "{stem}_clean.pdf"])
+        .assert()
+        .success();
+
+    let manifest = std::fs::read_to_string(dir.path().join("sha256.txt")).unwrap();
+    assert_eq!(
+        manifest,
+        hash(dir.path(), "a_clean.pdf", false) + &hash(dir.path(), "b_clean.pdf", false)
+    );
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use insta::assert_snapshot;
+use lopdf::{IncrementalDocument, Object};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn show_and_extract_revisions() {
+    let dir = TempDir::new().unwrap();
+    let original = make_fixture(dir.path(), "original.pdf", &["--pages", "2"]);
+
+    // Append an incremental update that changes the language of the document.
+    let mut document = IncrementalDocument::load(&original).unwrap();
+    let catalog_id = document
+        .get_prev_documents()
+        .trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .unwrap();
+    document
+        .opt_clone_object_to_new_document(catalog_id)
+        .unwrap();
+    document
+        .new_document
+        .get_dictionary_mut(catalog_id)
+        .unwrap()
+        .set("Lang", Object::string_literal("en"));
+    document.save(dir.path().join("updated.pdf")).unwrap();
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["history", "show", "updated.pdf", "--objects"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&format!("Modified: {} {} R", catalog_id.0, catalog_id.1)));
+    assert_snapshot!(stdout.lines().take(8).collect::<Vec<_>>().join("\n"));
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["history", "extract", "updated.pdf", "--revision", "1"])
+        .assert()
+        .success()
+        .stdout(contains("Successfully extracted revision 1"));
+    // The update starts on a new line, which belongs to the first revision.
+    assert_eq!(
+        std::fs::read(dir.path().join("revision-1.pdf"))
+            .unwrap()
+            .trim_ascii_end(),
+        std::fs::read(&original).unwrap()
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["history", "extract", "updated.pdf", "--revision", "3"])
+        .assert()
+        .failure()
+        .stderr(contains("has 2 revisions, there is no revision 3"));
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::{rpdf, write_object_stream_pdf};
+use insta::assert_snapshot;
+use tempfile::TempDir;
+
+/// Map sheet with a georeferenced viewport, in WGS 84.
+const MAP_OBJECTS: &[&str] = &[
+    "<</Type/Catalog/Pages 2 0 R>>",
+    "<</Type/Pages/Kids[3 0 R]/Count 1>>",
+    "<</Type/Page/Parent 2 0 R/MediaBox[0 0 842 595]/VP[<</Type/Viewport/Name(Map frame)/BBox[36 \
+     36 806 559]/Measure 4 0 R>>]>>",
+    "<</Type/Measure/Subtype/GEO/Bounds[0 0 0 1 1 1 1 0]/GPTS[50.8 4.3 50.9 4.3 50.9 4.4 50.8 \
+     4.4]/LPTS[0 0 0 1 1 1 1 0]/GCS 5 0 R/PDU[/M/SQM/DEG]>>",
+    "<</Type/GEOGCS/EPSG 4326>>",
+];
+
+#[test]
+fn geo_reports_georeferenced_viewports() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("map.pdf"), MAP_OBJECTS, None);
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["inspect", "geo", "map.pdf"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+/// Magazine with an article continued from two columns of page 1 to page 2.
+const THREAD_OBJECTS: &[&str] = &[
+    "<</Type/Catalog/Pages 2 0 R/Threads[5 0 R]>>",
+    "<</Type/Pages/Kids[3 0 R 4 0 R]/Count 2/MediaBox[0 0 612 792]>>",
+    "<</Type/Page/Parent 2 0 R/B[6 0 R 7 0 R]>>",
+    "<</Type/Page/Parent 2 0 R/B[8 0 R]>>",
+    "<</Type/Thread/F 6 0 R/I<</Title(Cover story)>>>>",
+    "<</Type/Bead/T 5 0 R/N 7 0 R/V 8 0 R/P 3 0 R/R[36 36 300 756]>>",
+    "<</Type/Bead/T 5 0 R/N 8 0 R/V 6 0 R/P 3 0 R/R[312 36 576 756]>>",
+    "<</Type/Bead/T 5 0 R/N 6 0 R/V 7 0 R/P 4 0 R/R[36 396 576 756]>>",
+];
+
+#[test]
+fn threads_reports_article_beads() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(&dir.path().join("magazine.pdf"), THREAD_OBJECTS, None);
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["inspect", "threads", "magazine.pdf"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+/// Widths of the bars and spaces of the Code 128 symbols of `SCAN-0042`, in
+/// modules, from the start symbol to the stop symbol.
+#[cfg(feature = "images")]
+const COVER_SHEET_CODE: &str =
+    "211214 213113 131321 111323 113321 122132 123122 123122 221231 223211 142112 2331112";
+
+/// Write a scanned cover sheet with the barcode drawn horizontally on page 1,
+/// and vertically on page 2.
+#[cfg(feature = "images")]
+fn write_cover_sheet(path: &std::path::Path) {
+    use lopdf::{Document, Object, Stream, dictionary};
+
+    // Two pixels per module, and a quiet zone of ten modules.
+    let mut line = vec![255u8; 20];
+    for (i, width) in COVER_SHEET_CODE
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .enumerate()
+    {
+        let pixel = if i % 2 == 0 { 0 } else { 255 };
+        line.extend(std::iter::repeat(pixel).take(2 * usize::from(width - b'0')));
+    }
+    line.extend([255; 20]);
+    let (length, thickness) = (line.len(), 40);
+
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+    let images = [
+        (length, thickness, line.repeat(thickness)),
+        (
+            thickness,
+            length,
+            line.iter().flat_map(|&pixel| [pixel; 40]).collect(),
+        ),
+    ];
+    let kids: Vec<Object> = images
+        .into_iter()
+        .map(|(width, height, pixels)| {
+            let image_id = document.add_object(Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Image",
+                    "Width" => width as i64,
+                    "Height" => height as i64,
+                    "ColorSpace" => "DeviceGray",
+                    "BitsPerComponent" => 8,
+                },
+                pixels,
+            ));
+            let content = format!("q {width} 0 0 {height} 0 0 cm /Im1 Do Q");
+            let content_id = document.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+            document
+                .add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "MediaBox" => vec![0.into(), 0.into(), (width as i64).into(), (height as i64).into()],
+                    "Resources" => dictionary! {
+                        "XObject" => dictionary! { "Im1" => image_id },
+                    },
+                })
+                .into()
+        })
+        .collect();
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+    document.save(path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "images")]
+fn barcodes_are_decoded_from_scans() {
+    let dir = TempDir::new().unwrap();
+    write_cover_sheet(&dir.path().join("cover.pdf"));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["inspect", "barcodes", "cover.pdf"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use lopdf::{Document, Object};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+const XML: &str = "<?xml version=\"1.0\"?><rsm:CrossIndustryInvoice/>";
+
+#[test]
+fn invoice_attach_and_extract() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "fixture.pdf", &[]);
+    std::fs::write(dir.path().join("invoice.xml"), XML).unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["invoice", "attach", "fixture.pdf", "--xml", "invoice.xml"])
+        .args(["--profile", "EN16931", "--dest", "hybrid.pdf"])
+        .assert()
+        .success()
+        .stdout(contains("as factur-x.xml (EN 16931)"));
+
+    let document = Document::load(dir.path().join("hybrid.pdf")).unwrap();
+    let catalog = document.catalog().unwrap();
+    let associated = catalog.get(b"AF").unwrap().as_array().unwrap();
+    let filespec = document
+        .get_dictionary(associated[0].as_reference().unwrap())
+        .unwrap();
+    assert_eq!(
+        filespec
+            .get(b"AFRelationship")
+            .and_then(Object::as_name)
+            .unwrap(),
+        b"Alternative"
+    );
+    let metadata = document
+        .get_object(catalog.get(b"Metadata").unwrap().as_reference().unwrap())
+        .and_then(Object::as_stream)
+        .unwrap();
+    let xmp = String::from_utf8_lossy(&metadata.content);
+    assert!(xmp.contains("<pdfaid:part>3</pdfaid:part>"));
+    assert!(xmp.contains("<fx:ConformanceLevel>EN 16931</fx:ConformanceLevel>"));
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "invoice",
+            "extract",
+            "hybrid.pdf",
+            "--dest",
+            "extracted.xml",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Profile: EN 16931"));
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("extracted.xml")).unwrap(),
+        XML
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["invoice", "extract", "fixture.pdf"])
+        .assert()
+        .failure()
+        .stderr(contains("has no Factur-X or ZUGFeRD invoice"));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{make_fixture, pdf_snapshot, rpdf};
+use insta::assert_snapshot;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn list_layers() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(
+        dir.path(),
+        "drawing.pdf",
+        &["--pages", "3", "--layers", "2"],
+    );
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["layers", "list", "drawing.pdf"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn toggle_hides_layer_by_default() {
+    let dir = TempDir::new().unwrap();
+    let drawing = make_fixture(dir.path(), "drawing.pdf", &["--layers", "2"]);
+    let toggled = dir.path().join("toggled.pdf");
+
+    rpdf()
+        .args([
+            "--quiet", "layers", "toggle", "--name", "Layer 2", "--state", "off",
+        ])
+        .arg(&drawing)
+        .arg("--dest")
+        .arg(&toggled)
+        .assert()
+        .success();
+
+    rpdf()
+        .args(["layers", "show", "--name", "Layer 2"])
+        .arg(&toggled)
+        .assert()
+        .success()
+        .stdout(contains("State: off"));
+    rpdf()
+        .args(["layers", "show", "--name", "Layer 1"])
+        .arg(&toggled)
+        .assert()
+        .success()
+        .stdout(contains("State: on"));
+}
+
+#[test]
+fn remove_layer_and_its_content() {
+    let dir = TempDir::new().unwrap();
+    let drawing = make_fixture(dir.path(), "drawing.pdf", &["--layers", "2"]);
+    let removed = dir.path().join("removed.pdf");
+
+    rpdf()
+        .args(["layers", "remove", "--name", "Layer 1"])
+        .arg(&drawing)
+        .arg("--dest")
+        .arg(&removed)
+        .assert()
+        .success()
+        .stdout(contains("1 content sections"));
+
+    assert_snapshot!(pdf_snapshot(&removed));
+}
+
+#[test]
+fn show_unknown_layer() {
+    let dir = TempDir::new().unwrap();
+    let drawing = make_fixture(dir.path(), "drawing.pdf", &["--layers", "1"]);
+
+    rpdf()
+        .args(["layers", "show", "--name", "Watermark"])
+        .arg(&drawing)
+        .assert()
+        .failure()
+        .stderr(contains("has no layer named \"Watermark\""));
+}
+
+#[test]
+fn stamps_on_their_own_layer() {
+    let dir = TempDir::new().unwrap();
+    let drawing = make_fixture(dir.path(), "drawing.pdf", &["--pages", "2"]);
+    let stamped = dir.path().join("stamped.pdf");
+
+    rpdf()
+        .args(["--quiet", "annotations", "add-stamp", "--pages", "all"])
+        .args(["--name", "Draft", "--layer", "Draft watermark"])
+        .arg(&drawing)
+        .arg("--dest")
+        .arg(&stamped)
+        .assert()
+        .success();
+
+    rpdf()
+        .args(["layers", "show", "--name", "Draft watermark"])
+        .arg(&stamped)
+        .assert()
+        .success()
+        .stdout(contains("State: on\n"))
+        .stdout(contains("Pages: 1-2\n"));
+
+    rpdf()
+        .args(["layers", "remove", "--name", "Draft watermark"])
+        .arg(&stamped)
+        .arg("--dest")
+        .arg(dir.path().join("clean.pdf"))
+        .assert()
+        .success()
+        .stdout(contains("2 annotations"));
+}
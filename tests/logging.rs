@@ -0,0 +1,109 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use tempfile::TempDir;
+
+/// Parse JSON log messages, one per line.
+fn json_records(stderr: Vec<u8>) -> Vec<serde_json::Value> {
+    String::from_utf8(stderr)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn json_logs_refer_to_file() {
+    let output = rpdf()
+        .args([
+            "--log-format",
+            "json",
+            "annotations",
+            "stats",
+            "missing.pdf",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let records = json_records(output.stderr);
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record["level"], "ERROR");
+    assert_eq!(record["span"]["name"], "file");
+    assert_eq!(record["span"]["path"], "missing.pdf");
+    assert!(
+        record["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to read PDF from")
+    );
+}
+
+#[test]
+fn json_logs_refer_to_page() {
+    let dir = TempDir::new().unwrap();
+    let reference = make_fixture(dir.path(), "reference.pdf", &[]);
+    let review = make_fixture(dir.path(), "review.pdf", &["--pages", "2"]);
+
+    let output = rpdf()
+        .args(["-v", "--log-format", "json", "annotations", "merge"])
+        .args([&reference, &review])
+        .arg("--dest")
+        .arg(dir.path().join("merged.pdf"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let records = json_records(output.stderr);
+    let record = records
+        .iter()
+        .find(|record| record["level"] == "WARN")
+        .unwrap();
+    assert!(
+        record["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Reference document does not contain page number 2")
+    );
+    assert_eq!(record["span"]["name"], "page");
+    assert_eq!(record["span"]["number"], 2);
+    assert_eq!(record["spans"][0]["name"], "file");
+    assert_eq!(
+        record["spans"][0]["path"],
+        review.display().to_string().as_str()
+    );
+}
+
+#[test]
+fn logs_are_written_to_file() {
+    let dir = TempDir::new().unwrap();
+    let log_file = dir.path().join("rpdf.log");
+
+    rpdf()
+        .args(["annotations", "stats", "missing.pdf", "--log-file"])
+        .arg(&log_file)
+        .assert()
+        .failure()
+        .stderr("");
+
+    let logs = std::fs::read_to_string(log_file).unwrap();
+    assert!(logs.contains("Failed to read PDF from"));
+    assert!(logs.contains("file{path=missing.pdf}"));
+}
+
+#[test]
+fn logs_follow_color_policy() {
+    let stderr = |args: &[&str]| {
+        let output = rpdf()
+            .args(args)
+            .args(["annotations", "stats", "missing.pdf"])
+            .output()
+            .unwrap();
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    assert!(stderr(&["--color=always"]).contains('\x1b'));
+    assert!(!stderr(&["--color=never"]).contains('\x1b'));
+    assert!(!stderr(&["--color=always", "--log-format", "json"]).contains('\x1b'));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::{make_fixture, pdf_snapshot, rpdf};
+use insta::assert_snapshot;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn viewer_prefs_edits_catalog() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "3", "--bookmarks", "1"],
+    );
+    let patched = dir.path().join("patched.pdf");
+
+    rpdf()
+        .args(["--quiet", "metadata", "viewer-prefs"])
+        .arg(&fixture)
+        .args([
+            "--page-mode",
+            "UseOutlines",
+            "--page-layout",
+            "TwoPageLeft",
+            "--fit-window",
+            "--open-page",
+            "2",
+            "--zoom",
+            "150%",
+            "--dest",
+        ])
+        .arg(&patched)
+        .assert()
+        .success();
+
+    assert_snapshot!(pdf_snapshot(&patched));
+}
+
+#[test]
+fn viewer_prefs_rejects_missing_page() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+
+    rpdf()
+        .args(["metadata", "viewer-prefs"])
+        .arg(&fixture)
+        .args(["--open-page", "2"])
+        .assert()
+        .failure()
+        .stderr(contains("has no page 2"));
+}
+
+#[test]
+fn lang_sets_language_and_title() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+    let patched = dir.path().join("patched.pdf");
+
+    rpdf()
+        .args(["--quiet", "metadata", "lang"])
+        .arg(&fixture)
+        .args(["--set", "en-GB", "--title", "Annual report", "--dest"])
+        .arg(&patched)
+        .assert()
+        .success();
+
+    rpdf()
+        .args(["metadata", "lang"])
+        .arg(&patched)
+        .assert()
+        .success()
+        .stdout(contains("Language: en-GB"))
+        .stdout(contains("Title: Annual report"))
+        .stdout(contains("Title shown by viewers: yes"));
+}
+
+#[test]
+fn lang_rejects_invalid_language() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+
+    rpdf()
+        .args(["metadata", "lang"])
+        .arg(&fixture)
+        .args(["--set", "en_GB"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid language"));
+}
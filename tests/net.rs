@@ -0,0 +1,104 @@
+mod common;
+
+use common::rpdf;
+use predicates::str::contains;
+
+#[cfg(feature = "net")]
+mod server {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    /// Entity tag of the served file.
+    const ETAG: &str = "\"v1\"";
+
+    /// Serve a file over HTTP on a local port, answering `304 Not Modified`
+    /// to requests that already have it, and return its URL and the number
+    /// of times it was sent in full.
+    pub fn serve(data: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/review.pdf", listener.local_addr().unwrap());
+        let downloads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&downloads);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut cached = false;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    cached |= line.to_ascii_lowercase() == format!("if-none-match: {ETAG}\r\n");
+                    line.clear();
+                }
+                if cached {
+                    write!(
+                        stream,
+                        "HTTP/1.1 304 Not Modified\r\nETag: {ETAG}\r\nConnection: close\r\n\r\n"
+                    )
+                    .unwrap();
+                } else {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nETag: {ETAG}\r\nContent-Length: {}\r\nConnection: \
+                         close\r\n\r\n",
+                        data.len()
+                    )
+                    .unwrap();
+                    stream.write_all(&data).unwrap();
+                }
+            }
+        });
+        (url, downloads)
+    }
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn read_only_commands_download_urls_once() {
+    use std::sync::atomic::Ordering;
+
+    use common::make_fixture;
+    use tempfile::TempDir;
+
+    let dir = TempDir::new().unwrap();
+    let path = make_fixture(dir.path(), "review.pdf", &["--annotations", "3"]);
+    let (url, downloads) = server::serve(std::fs::read(path).unwrap());
+
+    for _ in 0..2 {
+        rpdf()
+            .env_remove("RPDF_NO_CACHE")
+            .env("RPDF_CACHE_DIR", dir.path().join("cache"))
+            .env("NO_PROXY", "127.0.0.1")
+            .args(["annotations", "stats", &url])
+            .assert()
+            .success()
+            .stdout(contains(format!("Annotations stats for: {url}")));
+    }
+    assert_eq!(downloads.load(Ordering::SeqCst), 1);
+
+    rpdf()
+        .current_dir(dir.path())
+        .env("NO_PROXY", "127.0.0.1")
+        .args(["annotations", "strip", &url])
+        .assert()
+        .failure()
+        .stderr(contains("This command cannot read PDFs from URLs"));
+}
+
+#[cfg(not(feature = "net"))]
+#[test]
+fn urls_require_net_feature() {
+    rpdf()
+        .args(["annotations", "stats", "https://example.com/review.pdf"])
+        .assert()
+        .failure()
+        .stderr(contains("requires the `net` feature"));
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use insta::assert_snapshot;
+use lopdf::{Document, Object, Stream, dictionary};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn show_prints_objects_in_pdf_syntax() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "1"]);
+
+    let output = rpdf()
+        .args(["objects", "show"])
+        .arg(&fixture)
+        .arg("trailer")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn show_reads_data_of_shown_image() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("scan.pdf");
+    let mut document = Document::with_version("1.7");
+    // The length of the image is an object, which is read from the file too.
+    let length_id = document.add_object(4);
+    let mut image = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => 2,
+            "Height" => 2,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+        },
+        vec![0xde, 0xad, 0xbe, 0xef],
+    );
+    image.dict.set("Length", Object::Reference(length_id));
+    let image_id = document.add_object(image);
+    let catalog_id = document.add_object(dictionary! { "Type" => "Catalog" });
+    document.trailer.set("Root", catalog_id);
+    document.save(&path).unwrap();
+
+    rpdf()
+        .args(["objects", "show", "--hex"])
+        .arg(&path)
+        .arg(image_id.0.to_string())
+        .assert()
+        .success()
+        .stdout(contains("de ad be ef"));
+}
+
+#[test]
+fn find_lists_objects_by_type() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "2", "--annotations", "3", "--fields", "1"],
+    );
+
+    let output = rpdf()
+        .args(["objects", "find", "--type", "/Annot"])
+        .arg(&fixture)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn set_and_delete_key() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+    let patched = dir.path().join("patched.pdf");
+
+    rpdf()
+        .args(["--quiet", "objects", "set"])
+        .arg(&fixture)
+        .args([
+            "trailer",
+            "--key",
+            "/Foo",
+            "--value",
+            "[1 (two) /Three]",
+            "--dest",
+        ])
+        .arg(&patched)
+        .assert()
+        .success();
+    rpdf()
+        .args(["objects", "show"])
+        .arg(&patched)
+        .arg("trailer")
+        .assert()
+        .success()
+        .stdout(contains("/Foo [1 (two) /Three]"));
+
+    rpdf()
+        .args(["--quiet", "--force", "objects", "delete-key"])
+        .arg(&patched)
+        .args(["trailer", "--key", "/Foo", "--dest"])
+        .arg(&patched)
+        .assert()
+        .success();
+    rpdf()
+        .args(["objects", "delete-key"])
+        .arg(&patched)
+        .args(["trailer", "--key", "/Foo", "--dest"])
+        .arg(&patched)
+        .assert()
+        .failure()
+        .stderr(contains("trailer has no /Foo key."));
+}
+
+#[test]
+fn set_and_show_unicode_text_strings() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "1"]);
+    let patched = dir.path().join("patched.pdf");
+
+    for (key, value, source) in [
+        ("/Contents", "(Très bien, 日本語のコメント)", &fixture),
+        ("/T", "(Hélène — “relu”)", &patched),
+    ] {
+        rpdf()
+            .args(["--quiet", "--force", "objects", "set"])
+            .arg(source)
+            .args(["4 0", "--key", key, "--value", value, "--dest"])
+            .arg(&patched)
+            .assert()
+            .success();
+    }
+
+    rpdf()
+        .args(["objects", "show"])
+        .arg(&patched)
+        .arg("4 0")
+        .assert()
+        .success()
+        .stdout(contains("/Contents (Très bien, 日本語のコメント)"))
+        .stdout(contains("/T (Hélène — “relu”)"));
+
+    // Text that PDFDocEncoding can represent is stored in a single byte per
+    // character, for old readers.
+    let document = lopdf::Document::load(&patched).unwrap();
+    let annotation = document.get_dictionary((4, 0)).unwrap();
+    assert_eq!(
+        annotation
+            .get(b"T")
+            .and_then(lopdf::Object::as_str)
+            .unwrap(),
+        b"H\xE9l\xE8ne \x84 \x8Drelu\x8E"
+    );
+    assert!(
+        annotation
+            .get(b"Contents")
+            .and_then(lopdf::Object::as_str)
+            .unwrap()
+            .starts_with(b"\xFE\xFF")
+    );
+}
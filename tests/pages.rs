@@ -0,0 +1,732 @@
+mod common;
+
+use common::{make_fixture, pdf_snapshot, rpdf};
+use insta::assert_snapshot;
+use lopdf::{Document, Object, Stream, dictionary};
+use tempfile::TempDir;
+
+#[test]
+fn open_at_sets_open_action() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--pages", "3"]);
+    let patched = dir.path().join("patched.pdf");
+
+    rpdf()
+        .args(["--quiet", "pages", "open-at"])
+        .arg(&fixture)
+        .args(["--page", "3", "--zoom", "fit-width", "--dest"])
+        .arg(&patched)
+        .assert()
+        .success();
+
+    assert_snapshot!(pdf_snapshot(&patched));
+}
+
+#[test]
+fn booklet_orders_pages_by_signature() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--pages", "10"]);
+    let booklet = dir.path().join("booklet.pdf");
+
+    rpdf()
+        .args(["--quiet", "pages", "booklet", "--signature", "8"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&booklet)
+        .assert()
+        .success();
+
+    // Pages are drawn as form XObjects named after their original number.
+    let sides: Vec<String> = (1..=6)
+        .map(|page| {
+            let output = rpdf()
+                .args(["objects", "content", "--page", &page.to_string()])
+                .arg(&booklet)
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout)
+                .unwrap()
+                .split_whitespace()
+                .filter(|word| word.starts_with("/Page"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+
+    assert_eq!(
+        sides,
+        [
+            "/Page8 /Page1",
+            "/Page2 /Page7",
+            "/Page6 /Page3",
+            "/Page4 /Page5",
+            "/Page9",
+            "/Page10"
+        ]
+    );
+}
+
+#[test]
+fn split_by_outline_names_parts_after_bookmarks() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "7", "--bookmarks", "3"],
+    );
+    let parts = dir.path().join("parts");
+
+    rpdf()
+        .args(["--quiet", "pages", "split", "--by-outline", "--level", "1"])
+        .args(["--name-template", "{index:02}_{title}.pdf", "--dest-dir"])
+        .arg(&parts)
+        .arg(&fixture)
+        .assert()
+        .success();
+
+    for (name, pages) in [
+        ("01_Bookmark 1 (page 1).pdf", 1),
+        ("02_Bookmark 2 (page 2).pdf", 1),
+        ("03_Bookmark 3 (page 3).pdf", 5),
+    ] {
+        let part = Document::load(parts.join(name)).unwrap();
+        assert_eq!(part.get_pages().len(), pages, "{name}");
+    }
+}
+
+#[test]
+fn split_by_chunk_and_size() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--pages", "7"]);
+
+    for (args, expected) in [
+        (["--chunk", "3"], &[3, 3, 1][..]),
+        (["--max-size", "1GB"], &[7]),
+        (["--max-size", "1B"], &[1; 7]),
+    ] {
+        let parts = dir.path().join(args.join(""));
+
+        rpdf()
+            .args(["--quiet", "pages", "split"])
+            .args(args)
+            .arg("--dest-dir")
+            .arg(&parts)
+            .arg(&fixture)
+            .assert()
+            .success();
+
+        let pages: Vec<usize> = (1..=expected.len())
+            .map(|index| {
+                let part = Document::load(parts.join(format!("fixture_{index:02}.pdf"))).unwrap();
+                part.get_pages().len()
+            })
+            .collect();
+        assert_eq!(pages, expected, "{args:?}");
+        assert!(
+            !parts
+                .join(format!("fixture_{:02}.pdf", expected.len() + 1))
+                .exists()
+        );
+    }
+}
+
+/// Write a document with one page per content stream.
+fn write_pages(path: &std::path::Path, contents: &[&str]) {
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+    let kids: Vec<Object> = contents
+        .iter()
+        .map(|content| {
+            let content_id =
+                document.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+            document
+                .add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                })
+                .into()
+        })
+        .collect();
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            "Resources" => dictionary! {
+                "Font" => dictionary! {
+                    "F1" => dictionary! {
+                        "Type" => "Font",
+                        "Subtype" => "Type1",
+                        "BaseFont" => "Helvetica",
+                    },
+                },
+            },
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+    document.save(path).unwrap();
+}
+
+#[test]
+fn remove_blank_pages() {
+    let dir = TempDir::new().unwrap();
+    write_pages(
+        &dir.path().join("scan.pdf"),
+        &[
+            "0 g 72 72 468 648 re f",
+            "",
+            "1 g 0 0 612 792 re f 0 g 300 400 1 1 re f",
+            "0.5 g 72 72 200 200 re f",
+        ],
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "remove-blank", "scan.pdf", "--dry-run"])
+        .assert()
+        .success()
+        .stdout("Page 2 is blank (100.0% blank).\nPage 3 is blank (100.0% blank).\n");
+    assert!(!dir.path().join("cleaned.pdf").exists());
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "pages", "remove-blank", "scan.pdf"])
+        .assert()
+        .success();
+    let cleaned = Document::load(dir.path().join("cleaned.pdf")).unwrap();
+    assert_eq!(cleaned.get_pages().len(), 2);
+}
+
+#[test]
+fn interleave_merges_single_sided_scans() {
+    let dir = TempDir::new().unwrap();
+    write_pages(
+        &dir.path().join("fronts.pdf"),
+        &["% front 1", "% front 2", "% front 3"],
+    );
+    write_pages(
+        &dir.path().join("backs.pdf"),
+        &["% back 3", "% back 2", "% back 1"],
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "pages",
+            "interleave",
+            "fronts.pdf",
+            "backs.pdf",
+            "--reverse-second",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "Successfully interleaved 6 pages of \"fronts.pdf\" and \"backs.pdf\" to \
+             \"interleaved.pdf\".\n",
+        );
+    let interleaved = Document::load(dir.path().join("interleaved.pdf")).unwrap();
+    let contents: Vec<String> = interleaved
+        .get_pages()
+        .into_values()
+        .map(|page_id| String::from_utf8(interleaved.get_page_content(page_id).unwrap()).unwrap())
+        .collect();
+    assert_eq!(
+        contents,
+        [
+            "% front 1",
+            "% back 1",
+            "% front 2",
+            "% back 2",
+            "% front 3",
+            "% back 3"
+        ]
+    );
+    // Attributes inherited from the page trees are kept.
+    let page_id = interleaved.get_pages()[&2];
+    assert!(
+        interleaved
+            .get_dictionary(page_id)
+            .unwrap()
+            .has(b"MediaBox")
+    );
+}
+
+#[test]
+fn dedupe_removes_duplicated_pages() {
+    let dir = TempDir::new().unwrap();
+    write_pages(
+        &dir.path().join("batch.pdf"),
+        &[
+            "0 g 72 72 200 200 re f",
+            "0.5 g 72 72 200 200 re f",
+            // Same content as the first page, written differently.
+            "0  g\n72 72 200 200 re\nf",
+            "0.5 g 72 72 200 200 re f",
+        ],
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "dedupe", "batch.pdf"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "Page 3 duplicates page 1.\nPage 4 duplicates page 2.\n",
+        ));
+    let deduped = Document::load(dir.path().join("deduped.pdf")).unwrap();
+    assert_eq!(deduped.get_pages().len(), 2);
+}
+
+#[test]
+fn fingerprint_lists_page_hashes() {
+    let dir = TempDir::new().unwrap();
+    write_pages(
+        &dir.path().join("batch.pdf"),
+        &[
+            "0 g 72 72 200 200 re f",
+            "0.5 g 72 72 200 200 re f",
+            "0  g\n72 72 200 200 re\nf",
+        ],
+    );
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "fingerprint", "batch.pdf", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let pages = report["pages"].as_array().unwrap();
+    assert_eq!(pages.len(), 3);
+    assert_eq!(pages[0]["fingerprint"], pages[2]["fingerprint"]);
+    assert_ne!(pages[0]["fingerprint"], pages[1]["fingerprint"]);
+    assert_eq!(pages[2]["duplicates"], 1);
+    assert_eq!(pages[0]["fingerprint"].as_str().unwrap().len(), 32);
+
+    // Fingerprints are stable across rewrites of the file.
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "dedupe", "batch.pdf"])
+        .assert()
+        .success();
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "fingerprint", "deduped.pdf"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            pages[1]["fingerprint"].as_str().unwrap(),
+        ));
+}
+
+#[test]
+fn autorotate_pages_by_text_orientation() {
+    let dir = TempDir::new().unwrap();
+    write_pages(
+        &dir.path().join("scan.pdf"),
+        &[
+            "BT /F1 12 Tf 72 720 Td (Upright text) Tj ET",
+            // Text running upward, i.e., a page scanned sideways.
+            "BT /F1 12 Tf 0 1 -1 0 300 100 Tm (Sideways text) Tj ET",
+            "0 g 72 72 200 200 re f",
+        ],
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "autorotate", "scan.pdf"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "Page 2 is rotated by 90 degrees (100% of its text), instead of 0.\n",
+        ));
+    let rotated = Document::load(dir.path().join("rotated.pdf")).unwrap();
+    let rotations: Vec<i64> = rotated
+        .get_pages()
+        .values()
+        .map(|&id| {
+            rotated
+                .get_dictionary(id)
+                .unwrap()
+                .get(b"Rotate")
+                .and_then(Object::as_i64)
+                .unwrap_or(0)
+        })
+        .collect();
+    assert_eq!(rotations, [0, 90, 0]);
+}
+
+/// Write a document with a scanned page, i.e., a gray image covering the
+/// page, half black and half white, then a page of text.
+fn write_scan(path: &std::path::Path) {
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+    // Top half black, bottom half white.
+    let pixels: Vec<u8> = (0..64).map(|i| if i < 32 { 0 } else { 255 }).collect();
+    let image_id = document.add_object(Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => 8,
+            "Height" => 8,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+        },
+        pixels,
+    ));
+    let contents = [
+        "q 144 0 0 144 0 0 cm /Im1 Do Q",
+        "BT /F1 12 Tf 72 72 Td (Text) Tj ET",
+    ];
+    let kids: Vec<Object> = contents
+        .iter()
+        .map(|content| {
+            let content_id =
+                document.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+            document
+                .add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                })
+                .into()
+        })
+        .collect();
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "MediaBox" => vec![0.into(), 0.into(), 144.into(), 144.into()],
+            "Resources" => dictionary! {
+                "XObject" => dictionary! { "Im1" => image_id },
+                "Font" => dictionary! {
+                    "F1" => dictionary! {
+                        "Type" => "Font",
+                        "Subtype" => "Type1",
+                        "BaseFont" => "Helvetica",
+                    },
+                },
+            },
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+    document.save(path).unwrap();
+}
+
+/// Return the thumbnails of the pages of a document.
+fn thumbnails(document: &Document) -> Vec<Option<&Stream>> {
+    document
+        .get_pages()
+        .values()
+        .map(|&id| {
+            let page = document.get_dictionary(id).unwrap();
+            page.get(b"Thumb")
+                .and_then(Object::as_reference)
+                .ok()
+                .map(|thumb_id| document.get_object(thumb_id).unwrap().as_stream().unwrap())
+        })
+        .collect()
+}
+
+#[cfg(feature = "images")]
+#[test]
+fn thumbs_are_generated_for_scanned_pages() {
+    let dir = TempDir::new().unwrap();
+    write_scan(&dir.path().join("scan.pdf"));
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "thumbs", "scan.pdf", "--generate", "--dpi", "4"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "Successfully generated 1 thumbnails of",
+        ));
+    let document = Document::load(dir.path().join("thumbs.pdf")).unwrap();
+    let thumbnails = thumbnails(&document);
+    assert!(thumbnails[1].is_none());
+    let thumb = thumbnails[0].unwrap();
+    assert_eq!(thumb.dict.get(b"Width").unwrap().as_i64().unwrap(), 8);
+    assert_eq!(thumb.dict.get(b"Height").unwrap().as_i64().unwrap(), 8);
+    let pixels = thumb.decompressed_content().unwrap();
+    assert_eq!(pixels[..32], [0; 32]);
+    assert_eq!(pixels[32..], [255; 32]);
+}
+
+#[test]
+fn thumbs_are_removed() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("scan.pdf");
+    write_scan(&path);
+    let mut document = Document::load(&path).unwrap();
+    for page_id in document.get_pages().into_values() {
+        let thumb_id = document.add_object(Stream::new(
+            dictionary! {
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![255],
+        ));
+        document
+            .get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Thumb", thumb_id);
+    }
+    document.save(&path).unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "thumbs", "scan.pdf", "--remove"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "Successfully removed 2 thumbnails of",
+        ));
+    let document = Document::load(dir.path().join("thumbs.pdf")).unwrap();
+    assert!(thumbnails(&document).iter().all(Option::is_none));
+    assert_eq!(
+        document.objects.len(),
+        Document::load(&path).unwrap().objects.len() - 2
+    );
+}
+
+/// Write a document with one scanned page per angle, i.e., a gray image of
+/// horizontal lines, rotated counterclockwise by that angle in degrees.
+#[cfg(feature = "images")]
+fn write_skewed_scans(path: &std::path::Path, angles: &[f32]) {
+    const SIZE: usize = 400;
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+    let mut kids = vec![];
+
+    for (i, angle) in angles.iter().enumerate() {
+        let slope = angle.to_radians().tan();
+        let mut pixels = vec![255u8; SIZE * SIZE];
+        for line in (20..SIZE - 20).step_by(10) {
+            for x in 0..SIZE {
+                // Rows go down, so lines rotated counterclockwise go up.
+                let y = line as f32 - slope * (x as f32 - SIZE as f32 / 2.0);
+                if let Some(pixel) = pixels.get_mut(y.round() as usize * SIZE + x) {
+                    *pixel = 0;
+                }
+            }
+        }
+        let image_id = document.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => SIZE as i64,
+                "Height" => SIZE as i64,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            pixels,
+        ));
+        let name = format!("Im{i}");
+        let content_id = document.add_object(Stream::new(
+            dictionary! {},
+            format!("q 144 0 0 144 0 0 cm /{name} Do Q").into_bytes(),
+        ));
+        kids.push(
+            document
+                .add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "Resources" => dictionary! {
+                        "XObject" => dictionary! { name => image_id },
+                    },
+                })
+                .into(),
+        );
+    }
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "MediaBox" => vec![0.into(), 0.into(), 144.into(), 144.into()],
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+    document.save(path).unwrap();
+}
+
+#[cfg(feature = "images")]
+#[test]
+fn deskew_rotates_content_of_skewed_pages_only() {
+    use lopdf::content::Content;
+
+    let dir = TempDir::new().unwrap();
+    write_skewed_scans(&dir.path().join("scan.pdf"), &[2.0, 0.0]);
+    let original = Document::load(dir.path().join("scan.pdf")).unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "deskew", "scan.pdf"])
+        .assert()
+        .success()
+        .stdout(
+            "Page 1 is skewed by 2.0 degrees.\nSuccessfully deskewed 1 pages of \"scan.pdf\" to \
+             \"deskewed.pdf\".\n",
+        );
+
+    let document = Document::load(dir.path().join("deskewed.pdf")).unwrap();
+    let pages = document.get_pages();
+
+    // The skewed page is rotated back clockwise about the center of its image,
+    // within the page, whose geometry is unchanged.
+    let skewed = pages[&1];
+    assert!(!document.get_dictionary(skewed).unwrap().has(b"Rotate"));
+    let contents = document.get_page_contents(skewed);
+    assert_eq!(contents.len(), 3);
+    let transform = document
+        .get_object(contents[0])
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    let operations = Content::decode(&transform.content).unwrap().operations;
+    assert_eq!(operations[0].operator, "q");
+    assert_eq!(operations[1].operator, "cm");
+    let matrix: Vec<f32> = operations[1]
+        .operands
+        .iter()
+        .map(|operand| operand.as_float().unwrap())
+        .collect();
+    let (sin, cos) = (-2.0f32).to_radians().sin_cos();
+    let center = 72.0;
+    let expected = [
+        cos,
+        sin,
+        -sin,
+        cos,
+        center - cos * center + sin * center,
+        center - sin * center - cos * center,
+    ];
+    for (value, expected) in matrix.iter().zip(expected) {
+        assert!(
+            (value - expected).abs() < 1e-3,
+            "{matrix:?} != {expected:?}"
+        );
+    }
+    let restore = document
+        .get_object(contents[2])
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    assert_eq!(restore.content.trim_ascii(), b"Q");
+
+    // The straight page is left unchanged.
+    let straight = pages[&2];
+    assert_eq!(
+        document.get_page_content(straight).unwrap(),
+        original.get_page_content(original.get_pages()[&2]).unwrap()
+    );
+    assert_eq!(document.get_page_contents(straight).len(), 1);
+    assert!(!document.get_dictionary(straight).unwrap().has(b"Rotate"));
+}
+
+#[cfg(feature = "images")]
+#[test]
+fn deskew_dry_run_writes_nothing() {
+    let dir = TempDir::new().unwrap();
+    write_skewed_scans(&dir.path().join("scan.pdf"), &[0.0, -3.0]);
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["pages", "deskew", "scan.pdf", "--dry-run"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let angle: f32 = stdout
+        .strip_prefix("Page 2 is skewed by ")
+        .and_then(|rest| rest.strip_suffix(" degrees.\n"))
+        .unwrap()
+        .parse()
+        .unwrap();
+    // Angles are detected in steps of 0.1 degrees.
+    assert!((angle + 3.0).abs() <= 0.1 + f32::EPSILON, "{angle}");
+    assert!(!dir.path().join("deskewed.pdf").exists());
+}
+
+#[test]
+fn remove_blank_relinks_article_threads() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("magazine.pdf");
+    write_pages(
+        &path,
+        &["0 g 72 72 200 200 re f", "", "0 g 72 72 200 200 re f"],
+    );
+    // One article across the three pages, with one bead per page.
+    let mut document = Document::load(&path).unwrap();
+    let page_ids: Vec<_> = document.get_pages().into_values().collect();
+    let thread_id = document.new_object_id();
+    let bead_ids: Vec<_> = page_ids.iter().map(|_| document.new_object_id()).collect();
+    for (i, (&bead_id, &page_id)) in bead_ids.iter().zip(&page_ids).enumerate() {
+        document.objects.insert(
+            bead_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Bead",
+                "T" => thread_id,
+                "N" => bead_ids[(i + 1) % 3],
+                "V" => bead_ids[(i + 2) % 3],
+                "P" => page_id,
+                "R" => vec![72.into(), 72.into(), 272.into(), 272.into()],
+            }),
+        );
+        document
+            .get_dictionary_mut(page_id)
+            .unwrap()
+            .set("B", vec![bead_id.into()]);
+    }
+    document.objects.insert(
+        thread_id,
+        Object::Dictionary(dictionary! { "Type" => "Thread", "F" => bead_ids[0] }),
+    );
+    document
+        .catalog_mut()
+        .unwrap()
+        .set("Threads", vec![thread_id.into()]);
+    document.save(&path).unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "pages", "remove-blank", "magazine.pdf"])
+        .assert()
+        .success();
+    let cleaned = Document::load(dir.path().join("cleaned.pdf")).unwrap();
+    let first = cleaned.get_dictionary(bead_ids[0]).unwrap();
+    let last = cleaned.get_dictionary(bead_ids[2]).unwrap();
+    assert_eq!(
+        first.get(b"N").unwrap().as_reference().unwrap(),
+        bead_ids[2]
+    );
+    assert_eq!(
+        first.get(b"V").unwrap().as_reference().unwrap(),
+        bead_ids[2]
+    );
+    assert_eq!(last.get(b"N").unwrap().as_reference().unwrap(), bead_ids[0]);
+    assert!(cleaned.get_object(bead_ids[1]).is_err());
+}
@@ -0,0 +1,51 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, os::unix::fs::PermissionsExt};
+
+use common::rpdf;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn external_subcommand_runs_plugin() {
+    let dir = TempDir::new().unwrap();
+    let plugin = dir.path().join("rpdf-hello");
+    fs::write(
+        &plugin,
+        "#!/bin/sh\necho \"$@ protocol=$RPDF_PLUGIN_PROTOCOL color=$RPDF_COLOR \
+         verbosity=$RPDF_VERBOSITY no_input=$RPDF_NO_INPUT password=$RPDF_PASSWORD\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&plugin, fs::Permissions::from_mode(0o755)).unwrap();
+
+    rpdf()
+        .env("PATH", dir.path())
+        .args([
+            "--color=never",
+            "-v",
+            "--password",
+            "secret",
+            "hello",
+            "a",
+            "--b",
+        ])
+        .assert()
+        .success()
+        .stdout("a --b protocol=1 color=never verbosity=warn no_input=1 password=secret\n");
+}
+
+#[test]
+fn external_subcommand_requires_plugin() {
+    let dir = TempDir::new().unwrap();
+
+    rpdf()
+        .env("PATH", dir.path())
+        .arg("hello")
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Unknown command: \"hello\", and no `rpdf-hello` executable was found in PATH.",
+        ));
+}
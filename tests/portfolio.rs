@@ -0,0 +1,51 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use insta::assert_snapshot;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn portfolio_create_list_and_extract() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "contract.pdf", &[]);
+    std::fs::write(dir.path().join("budget.csv"), "item,amount\nlaptop,1200\n").unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--source-date-epoch", "0", "portfolio", "create"])
+        .args([
+            "--files",
+            "contract.pdf",
+            "budget.csv",
+            "--schema",
+            "name,size",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("with 2 files"));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["portfolio", "list", "portfolio.pdf"])
+        .output()
+        .unwrap();
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "portfolio",
+            "extract",
+            "portfolio.pdf",
+            "--names",
+            "budget.csv",
+        ])
+        .args(["--dest-dir", "out"])
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("out/budget.csv")).unwrap(),
+        "item,amount\nlaptop,1200\n"
+    );
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "scripting")]
+
+mod common;
+
+use common::{make_fixture, rpdf};
+use insta::assert_snapshot;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn process_updates_deletes_and_creates_annotations() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "2", "--annotations", "4"],
+    );
+    let script = dir.path().join("policy.rhai");
+    std::fs::write(
+        &script,
+        r#"
+        fn annotation(page) {
+            if this.subtype == "Square" {
+                return false;
+            }
+            this.author = "bot";
+            this.contents += " on page " + page.number;
+            this.rect[0] = 10;
+        }
+
+        fn page(page) {
+            if page.number == 2 {
+                [#{ subtype: "Text", rect: [0, 0, page.width / 2, 24], contents: "Checked" }]
+            }
+        }
+        "#,
+    )
+    .unwrap();
+    let processed = dir.path().join("processed.pdf");
+
+    rpdf()
+        .arg("process")
+        .arg(&fixture)
+        .arg("--script")
+        .arg(&script)
+        .arg("--dest")
+        .arg(&processed)
+        .assert()
+        .success()
+        .stdout(contains(
+            "Successfully updated 3, deleted 1 and created 1 annotations",
+        ));
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["annotations", "export", "--format", "csv", "processed.pdf"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn process_reports_script_errors() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "1"]);
+    let script = dir.path().join("invalid.rhai");
+    std::fs::write(&script, "fn annotation(page) { this.rect = \"wide\"; }").unwrap();
+
+    rpdf()
+        .arg("process")
+        .arg(&fixture)
+        .arg("--script")
+        .arg(&script)
+        .arg("--dest")
+        .arg(dir.path().join("processed.pdf"))
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Invalid `rect` of annotation on page 1: expected an array of 4 numbers",
+        ));
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn sanitize_keeps_harmless_files() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--annotations", "2", "--subtype", "Link"],
+    );
+
+    rpdf()
+        .arg("sanitize")
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(dir.path().join("sanitized.pdf"))
+        .assert()
+        .success()
+        .stdout(contains("No active content was found."));
+}
+
+#[test]
+fn inspect_threats_reports_no_risk() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "5"]);
+
+    let output = rpdf()
+        .args(["inspect", "threats"])
+        .arg(&fixture)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["risk"], serde_json::Value::Null);
+    assert_eq!(report["findings"], serde_json::json!([]));
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    process::{Child, Command, Stdio},
+};
+
+use common::make_fixture;
+use lopdf::Document;
+use tempfile::TempDir;
+
+/// Server running on a free port, killed when dropped.
+struct Server {
+    child: Child,
+    address: String,
+}
+
+impl Server {
+    fn start() -> Self {
+        Self::start_with(&[])
+    }
+
+    /// Start a server with the given global options.
+    fn start_with(options: &[&str]) -> Self {
+        let mut child = Command::new(assert_cmd::cargo::cargo_bin("rpdf"))
+            .arg("--no-input")
+            .args(options)
+            .args(["serve", "--address", "127.0.0.1:0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line
+            .trim()
+            .strip_prefix("Listening on http://")
+            .unwrap()
+            .to_string();
+        Self { child, address }
+    }
+
+    /// Post a form with the given fields, and return the status code and
+    /// body of the response.
+    fn post(&self, path: &str, fields: &[(&str, &[u8])]) -> (u16, Vec<u8>) {
+        let boundary = "rpdf-test-boundary";
+        let mut body = vec![];
+        for (name, value) in fields {
+            write!(
+                body,
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"; \
+                 filename=\"{name}\"\r\n\r\n"
+            )
+            .unwrap();
+            body.extend_from_slice(value);
+            body.extend_from_slice(b"\r\n");
+        }
+        write!(body, "--{boundary}--\r\n").unwrap();
+
+        let mut stream = TcpStream::connect(&self.address).unwrap();
+        write!(
+            stream,
+            "POST {path} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: \
+             multipart/form-data; boundary={boundary}\r\nContent-Length: {}\r\n\r\n",
+            self.address,
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(&body).unwrap();
+
+        let mut response = vec![];
+        stream.read_to_end(&mut response).unwrap();
+        let split = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .unwrap();
+        let head = String::from_utf8_lossy(&response[..split]).to_string();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        let mut body = response[split + 4..].to_vec();
+        if head
+            .to_ascii_lowercase()
+            .contains("transfer-encoding: chunked")
+        {
+            body = dechunk(&body);
+        }
+        (status, body)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Decode a body sent with chunked transfer encoding.
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut decoded = vec![];
+    loop {
+        let end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .unwrap();
+        let size = usize::from_str_radix(std::str::from_utf8(&body[..end]).unwrap(), 16).unwrap();
+        if size == 0 {
+            return decoded;
+        }
+        decoded.extend_from_slice(&body[end + 2..end + 2 + size]);
+        body = &body[end + 4 + size..];
+    }
+}
+
+#[test]
+fn serve_strips_and_counts_annotations() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "4"]);
+    let fixture = std::fs::read(fixture).unwrap();
+    let server = Server::start();
+
+    let (status, stats) = server.post("/annotations/stats", &[("file", &fixture)]);
+    assert_eq!(status, 200);
+    let stats: serde_json::Value = serde_json::from_slice(&stats).unwrap();
+    assert_eq!(
+        stats["total"],
+        serde_json::json!({ "Highlight": 1, "Ink": 1, "Square": 1, "Text": 1 })
+    );
+
+    let (status, stripped) = server.post("/annotations/strip", &[("file", &fixture)]);
+    assert_eq!(status, 200);
+    let document = Document::load_mem(&stripped).unwrap();
+    for page in document.get_pages().into_values() {
+        assert!(
+            document
+                .get_page_annotations(page)
+                .unwrap_or_default()
+                .is_empty()
+        );
+    }
+
+    let (status, optimized) = server.post("/optimize", &[("file", &fixture)]);
+    assert_eq!(status, 200);
+    Document::load_mem(&optimized).unwrap();
+}
+
+#[test]
+fn serve_reports_errors() {
+    let server = Server::start();
+
+    let (status, message) = server.post("/annotations/strip", &[("file", b"not a PDF")]);
+    assert_eq!(status, 422);
+    assert_eq!(
+        String::from_utf8(message).unwrap(),
+        "Failed to read PDF from memory: Invalid file header"
+    );
+
+    let (status, message) = server.post("/annotations/merge", &[("other", b"")]);
+    assert_eq!(status, 400);
+    assert_eq!(
+        String::from_utf8(message).unwrap(),
+        "Unexpected field: \"other\"."
+    );
+}
+
+#[test]
+fn serve_rejects_files_beyond_max_memory() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+    let fixture = std::fs::read(fixture).unwrap();
+    let server = Server::start_with(&["--max-memory", "1KB"]);
+
+    let (status, message) = server.post("/annotations/stats", &[("file", &fixture)]);
+    assert_eq!(status, 413);
+    assert!(
+        String::from_utf8(message)
+            .unwrap()
+            .starts_with("Loading \"file\" needs about")
+    );
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use insta::assert_snapshot;
+use lopdf::{Document, IncrementalDocument, Object, dictionary};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+/// Sign a document with a fake signature whose byte range covers the whole
+/// file, as it is written.
+fn write_signed(document: &mut Document, path: &std::path::Path) {
+    let signature_id = document.add_object(dictionary! {
+        "Type" => "Sig",
+        "Filter" => "Adobe.PPKLite",
+        "Name" => Object::string_literal("Jane Doe"),
+        "Reason" => Object::string_literal("Approval"),
+        "Contents" => Object::String(vec![0; 16], lopdf::StringFormat::Hexadecimal),
+    });
+    let field_id = document.add_object(dictionary! {
+        "FT" => "Sig",
+        "T" => Object::string_literal("Signature1"),
+        "V" => signature_id,
+    });
+    document.catalog_mut().unwrap().set(
+        "AcroForm",
+        dictionary! { "Fields" => vec![field_id.into()] },
+    );
+
+    // The byte range holds the length of the file, which depends on the
+    // byte range, until the number of digits is stable.
+    let mut length = 0;
+    loop {
+        document.get_dictionary_mut(signature_id).unwrap().set(
+            "ByteRange",
+            vec![0.into(), length.into(), length.into(), 0.into()],
+        );
+        document.save(path).unwrap();
+        let written = std::fs::metadata(path).unwrap().len() as i64;
+        if written == length {
+            break;
+        }
+        length = written;
+    }
+}
+
+#[test]
+fn show_signature_coverage() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+    let signed = dir.path().join("signed.pdf");
+    write_signed(&mut Document::load(&fixture).unwrap(), &signed);
+
+    rpdf()
+        .args(["signatures", "show"])
+        .arg(&signed)
+        .assert()
+        .success()
+        .stdout(contains("revision 1 of 1"))
+        .stdout(contains("Not modified after signing"));
+
+    // Append an incremental update after signing.
+    let mut document = IncrementalDocument::load(&signed).unwrap();
+    let catalog_id = document
+        .get_prev_documents()
+        .trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .unwrap();
+    document
+        .opt_clone_object_to_new_document(catalog_id)
+        .unwrap();
+    document
+        .new_document
+        .get_dictionary_mut(catalog_id)
+        .unwrap()
+        .set("Lang", Object::string_literal("en"));
+    document.save(dir.path().join("updated.pdf")).unwrap();
+
+    let output = rpdf()
+        .current_dir(dir.path())
+        .args(["signatures", "show", "updated.pdf"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["signatures", "show", "fixture.pdf"])
+        .assert()
+        .success()
+        .stdout(contains("has no signatures"));
+}
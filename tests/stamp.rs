@@ -0,0 +1,89 @@
+mod common;
+
+use std::fs;
+
+use common::{make_fixture, rpdf};
+use lopdf::Document;
+use tempfile::TempDir;
+
+#[test]
+fn bates_numbers_continue_across_files() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "a.pdf", &["--pages", "2"]);
+    make_fixture(dir.path(), "b.pdf", &["--pages", "3"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "stamp", "bates", "a.pdf", "b.pdf", "--prefix", "ABC-", "--start", "1001",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "Successfully numbered 2 pages of \"a.pdf\" to \"bates/a.pdf\".\nSuccessfully \
+             numbered 3 pages of \"b.pdf\" to \"bates/b.pdf\".\n",
+        );
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("bates/index.csv")).unwrap(),
+        "file,page,bates\na.pdf,1,ABC-001001\na.pdf,2,ABC-001002\nb.pdf,1,ABC-001003\nb.pdf,2,\
+         ABC-001004\nb.pdf,3,ABC-001005\n"
+    );
+    let numbered = Document::load(dir.path().join("bates/b.pdf")).unwrap();
+    let page_id = numbered.get_pages()[&3];
+    let content = String::from_utf8(numbered.get_page_content(page_id).unwrap()).unwrap();
+    assert!(content.contains("(ABC-001005) Tj"));
+}
+
+#[cfg(feature = "qrcode")]
+#[test]
+fn qrcode_is_drawn_with_vector_modules() {
+    let dir = TempDir::new().unwrap();
+    make_fixture(dir.path(), "report.pdf", &["--pages", "2"]);
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "stamp",
+            "qrcode",
+            "report.pdf",
+            "--data",
+            "https://example.com/{file}?page={page}",
+            "--size",
+            "80",
+            "--position",
+            "bottom-left",
+        ])
+        .assert()
+        .success()
+        .stdout("Successfully stamped 1 pages of \"report.pdf\" to \"stamped.pdf\".\n");
+
+    let stamped = Document::load(dir.path().join("stamped.pdf")).unwrap();
+    let pages = stamped.get_pages();
+    let content = stamped.get_and_decode_page_content(pages[&1]).unwrap();
+    let rectangles: Vec<Vec<f32>> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "re")
+        .map(|operation| {
+            operation
+                .operands
+                .iter()
+                .map(|operand| operand.as_float().unwrap())
+                .collect()
+        })
+        .collect();
+    // White background, with its quiet zone, at the bottom-left margin.
+    assert_eq!(rectangles[0], [36.0, 36.0, 80.0, 80.0]);
+    // First row of modules starts with the top-left finder pattern, i.e.,
+    // 7 dark modules after a quiet zone of 4 modules.
+    let [left, _, width, height] = rectangles[1][..] else {
+        panic!("Invalid rectangle: {:?}", rectangles[1]);
+    };
+    assert!(((left - 36.0) / height - 4.0).abs() < 1e-3);
+    assert!((width / height - 7.0).abs() < 1e-3);
+    assert!(rectangles.len() > 50);
+
+    let page_id = pages[&2];
+    assert_eq!(stamped.get_page_contents(page_id).len(), 1);
+}
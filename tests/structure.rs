@@ -0,0 +1,42 @@
+mod common;
+
+use common::{make_fixture, rpdf};
+use insta::assert_snapshot;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn show_prints_structure_tree_with_roles() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--pages", "2", "--tagged"]);
+
+    let output = rpdf()
+        .args(["structure", "show"])
+        .arg(&fixture)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+
+    rpdf()
+        .args(["structure", "show", "--format", "json"])
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(contains(r#""Heading": "H1""#))
+        .stdout(contains(r#""role": "H1""#));
+}
+
+#[test]
+fn show_fails_on_untagged_files() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &[]);
+
+    rpdf()
+        .args(["structure", "show"])
+        .arg(&fixture)
+        .assert()
+        .failure()
+        .stderr(contains("is not tagged"));
+}
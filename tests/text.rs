@@ -0,0 +1,209 @@
+mod common;
+
+use common::{rpdf, sample};
+use insta::assert_snapshot;
+#[cfg(feature = "regex")]
+use lopdf::{Document, Object, Stream, dictionary};
+#[cfg(feature = "regex")]
+use predicates::str::contains;
+#[cfg(feature = "regex")]
+use tempfile::TempDir;
+
+#[test]
+fn wordcount_reports_pages_and_total() {
+    let output = rpdf()
+        .current_dir(sample(""))
+        .args([
+            "text",
+            "wordcount",
+            "sample.pdf",
+            "--pages",
+            "1-2",
+            "--wpm",
+            "250",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_snapshot!(String::from_utf8(output.stdout).unwrap());
+}
+
+/// Write a one-page document showing the given content with a standard
+/// font, `/F1`, and an embedded subset font, `/F2`, that only has glyphs
+/// for `A` to `E`.
+#[cfg(feature = "regex")]
+fn write_page(path: &std::path::Path, content: &str) {
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+    let content_id = document.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+    let font_file_id = document.add_object(Stream::new(dictionary! {}, vec![]));
+    let page_id = document.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        "Resources" => dictionary! {
+            "Font" => dictionary! {
+                "F1" => dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                },
+                "F2" => dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "TrueType",
+                    "BaseFont" => "ABCDEF+Logo",
+                    "FirstChar" => 65,
+                    "LastChar" => 69,
+                    "Widths" => vec![600.into(); 5],
+                    "FontDescriptor" => dictionary! {
+                        "Type" => "FontDescriptor",
+                        "FontName" => "ABCDEF+Logo",
+                        "FontFile2" => font_file_id,
+                    },
+                },
+            },
+        },
+    });
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => 1,
+            "Kids" => vec![page_id.into()],
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+    document.save(path).unwrap();
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn replace_rewrites_matching_text() {
+    let dir = TempDir::new().unwrap();
+    write_page(
+        &dir.path().join("letter.pdf"),
+        "BT /F1 12 Tf 72 720 Td (Copyright ACME Corp 2020) Tj 0 -14 Td [(ACME) -250 (Corp)] TJ \
+         /F2 12 Tf 0 -14 Td (ACE) Tj ET",
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args([
+            "-v",
+            "text",
+            "replace",
+            "letter.pdf",
+            "--pattern",
+            "ACME Corp|A(C)E",
+            "--with",
+            "NewCo Ltd$1",
+        ])
+        .assert()
+        .success()
+        .stdout("Successfully replaced 2 matches in \"letter.pdf\" to \"replaced.pdf\".\n")
+        .stderr(contains("font \"Logo\" cannot show: \"NewCo LtdC\""));
+
+    let replaced = Document::load(dir.path().join("replaced.pdf")).unwrap();
+    let page_id = replaced.page_iter().next().unwrap();
+    let content = String::from_utf8(replaced.get_page_content(page_id).unwrap()).unwrap();
+    assert!(content.contains("(Copyright NewCo Ltd 2020) Tj"));
+    assert!(content.contains("[(NewCo Ltd)] TJ"));
+    assert!(content.contains("(ACE) Tj"));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn replace_keeps_kerning_of_other_text() {
+    let dir = TempDir::new().unwrap();
+    write_page(
+        &dir.path().join("letter.pdf"),
+        "BT /F1 12 Tf 72 720 Td [(Cop) 20 (yright) -250 (ACME) -250 (Corp) 30 (s)] TJ ET",
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "text", "replace", "letter.pdf"])
+        .args(["--pattern", "ACME Corp", "--with", "NewCo Ltd"])
+        .assert()
+        .success();
+
+    let replaced = Document::load(dir.path().join("replaced.pdf")).unwrap();
+    let page_id = replaced.page_iter().next().unwrap();
+    let content = String::from_utf8(replaced.get_page_content(page_id).unwrap()).unwrap();
+    assert!(content.contains("[(Cop) 20(yright) -250(NewCo Ltd) 30(s)] TJ"));
+
+    // The original content stream is removed, rather than left unused.
+    assert!(!replaced.objects.values().any(|object| {
+        object
+            .as_stream()
+            .is_ok_and(|stream| stream.content.windows(4).any(|window| window == b"ACME"))
+    }));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn replace_keeps_content_streams_of_other_pages() {
+    let dir = TempDir::new().unwrap();
+    let mut document = Document::with_version("1.7");
+    let pages_id = document.new_object_id();
+    let header_id = document.add_object(Stream::new(
+        dictionary! {},
+        b"BT /F1 12 Tf 72 750 Td (Header) Tj ET".to_vec(),
+    ));
+    let body_id = document.add_object(Stream::new(
+        dictionary! {},
+        b"BT /F1 12 Tf 72 720 Td (ACME Corp) Tj ET".to_vec(),
+    ));
+    let kids: Vec<Object> = [
+        vec![header_id.into(), body_id.into()],
+        vec![header_id.into()],
+    ]
+    .into_iter()
+    .map(|contents| {
+        document
+            .add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => contents,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                "Resources" => dictionary! {
+                    "Font" => dictionary! {
+                        "F1" => dictionary! {
+                            "Type" => "Font",
+                            "Subtype" => "Type1",
+                            "BaseFont" => "Helvetica",
+                        },
+                    },
+                },
+            })
+            .into()
+    })
+    .collect();
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! { "Type" => "Pages", "Count" => 2, "Kids" => kids }),
+    );
+    let catalog_id = document.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    document.trailer.set("Root", catalog_id);
+    document.save(dir.path().join("letter.pdf")).unwrap();
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "text", "replace", "letter.pdf"])
+        .args(["--pattern", "ACME", "--with", "NewCo"])
+        .assert()
+        .success();
+
+    // Only the first page is rewritten, and the second one still draws the
+    // stream they shared.
+    let replaced = Document::load(dir.path().join("replaced.pdf")).unwrap();
+    let pages: Vec<_> = replaced.page_iter().collect();
+    assert_eq!(replaced.get_page_contents(pages[1]), [header_id]);
+    assert!(replaced.get_object(header_id).is_ok());
+    assert!(replaced.get_object(body_id).is_err());
+}
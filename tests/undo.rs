@@ -0,0 +1,144 @@
+mod common;
+
+use common::{make_fixture, rpdf, write_object_stream_pdf};
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn undo_restores_file_edited_in_place() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "2", "--annotations", "4"],
+    );
+    let journal = dir.path().join("fixture.pdf.rpdf-journal");
+    let stats = || {
+        let output = rpdf()
+            .args(["annotations", "stats", "--per-page"])
+            .arg(&fixture)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let before = stats();
+
+    rpdf()
+        .args(["--quiet", "--force", "annotations", "strip"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&fixture)
+        .assert()
+        .success();
+    assert!(journal.exists());
+    assert!(stats().contains("No annotation was found"));
+
+    rpdf()
+        .arg("undo")
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(contains("Successfully undid the last edit"));
+    assert_eq!(stats(), before);
+    assert!(!journal.exists());
+}
+
+#[test]
+fn undo_restores_file_edited_in_place_with_backup() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "2"]);
+    let journal = dir.path().join("fixture.pdf.rpdf-journal");
+    let stats = || {
+        let output = rpdf()
+            .args(["annotations", "stats"])
+            .arg(&fixture)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let before = stats();
+
+    // The file is backed up before it is written, which must not prevent
+    // writing its journal.
+    rpdf()
+        .args(["--quiet", "--backup", "annotations", "strip"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&fixture)
+        .assert()
+        .success();
+    assert!(dir.path().join("fixture.pdf.bak").exists());
+    assert!(journal.exists());
+
+    rpdf().arg("undo").arg(&fixture).assert().success();
+    assert_eq!(stats(), before);
+    assert!(!journal.exists());
+}
+
+#[test]
+fn undo_refuses_files_modified_since() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(dir.path(), "fixture.pdf", &["--annotations", "2"]);
+
+    rpdf()
+        .args(["--quiet", "--force", "annotations", "strip"])
+        .arg(&fixture)
+        .arg("--dest")
+        .arg(&fixture)
+        .assert()
+        .success();
+    make_fixture(dir.path(), "other.pdf", &[]);
+    std::fs::copy(dir.path().join("other.pdf"), &fixture).unwrap();
+
+    rpdf()
+        .arg("undo")
+        .arg(&fixture)
+        .assert()
+        .failure()
+        .stderr(contains("was modified since its last in-place edit"));
+
+    rpdf()
+        .arg("undo")
+        .arg(dir.path().join("other.pdf"))
+        .assert()
+        .failure()
+        .stderr(contains("only in-place edits can be undone"));
+}
+
+#[test]
+fn undo_restores_encrypted_file_from_encrypted_copy() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("secret.pdf");
+    write_object_stream_pdf(
+        &path,
+        &[
+            "<</Type/Catalog/Pages 2 0 R>>",
+            "<</Type/Pages/Kids[3 0 R]/Count 1/MediaBox[0 0 612 792]>>",
+            "<</Type/Page/Parent 2 0 R/Annots[4 0 R]>>",
+            "<</Type/Annot/Subtype/Text/Rect[0 0 10 10]/Contents(Confidential)>>",
+        ],
+        Some("secret"),
+    );
+    let original = std::fs::read(&path).unwrap();
+
+    rpdf()
+        .args(["--quiet", "--force", "--password", "secret"])
+        .args(["annotations", "strip", "secret.pdf", "--dest", "secret.pdf"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    let journal = std::fs::read(dir.path().join("secret.pdf.rpdf-journal")).unwrap();
+    assert!(!journal.windows(12).any(|window| window == b"Confidential"));
+    assert!(
+        journal
+            .windows(original.len())
+            .any(|window| window == original)
+    );
+
+    rpdf()
+        .current_dir(dir.path())
+        .args(["--quiet", "undo", "secret.pdf"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+}
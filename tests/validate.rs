@@ -0,0 +1,94 @@
+mod common;
+
+use common::{make_fixture, pdf_snapshot, rpdf, write_object_stream_pdf};
+use insta::assert_snapshot;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+/// Tagged document whose figure, of a custom type, has no alternate
+/// description, and whose page has a link but no tab order.
+const UNDESCRIBED_FIGURE_OBJECTS: &[&str] = &[
+    "<</Type/Catalog/Pages 2 0 R/StructTreeRoot 5 0 R/MarkInfo<</Marked true>>/Lang(en)>>",
+    "<</Type/Pages/Kids[3 0 R]/Count 1/MediaBox[0 0 612 792]>>",
+    "<</Type/Page/Parent 2 0 R/Annots[4 0 R]>>",
+    "<</Type/Annot/Subtype/Link/Rect[72 700 92 720]/P 3 0 R>>",
+    "<</Type/StructTreeRoot/K 6 0 R/RoleMap<</Logo/Figure>>>>",
+    "<</Type/StructElem/S/Document/P 5 0 R/K[7 0 R 8 0 R]>>",
+    "<</Type/StructElem/S/Logo/P 6 0 R/Pg 3 0 R/K 0>>",
+    "<</Type/StructElem/S/Figure/P 6 0 R/Pg 3 0 R/K 1/Alt(Chart)>>",
+];
+
+#[test]
+fn pdfua_passes_tagged_files() {
+    let dir = TempDir::new().unwrap();
+    let fixture = make_fixture(
+        dir.path(),
+        "fixture.pdf",
+        &["--pages", "2", "--annotations", "2", "--tagged"],
+    );
+
+    rpdf()
+        .args(["validate", "--profile", "pdfua"])
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(contains("passed all PDF/UA checks"));
+}
+
+#[test]
+fn pdfua_reports_issues() {
+    let dir = TempDir::new().unwrap();
+    write_object_stream_pdf(
+        &dir.path().join("figure.pdf"),
+        UNDESCRIBED_FIGURE_OBJECTS,
+        None,
+    );
+    make_fixture(dir.path(), "untagged.pdf", &["--annotations", "1"]);
+
+    for name in ["figure", "untagged"] {
+        let output = rpdf()
+            .current_dir(dir.path())
+            .args(["validate", "--profile", "pdfua"])
+            .arg(format!("{name}.pdf"))
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        assert_snapshot!(name, String::from_utf8(output.stdout).unwrap());
+    }
+}
+
+#[test]
+fn fix_annots_removes_invalid_entries() {
+    let dir = TempDir::new().unwrap();
+    let broken = dir.path().join("broken.pdf");
+    let fixed = dir.path().join("fixed.pdf");
+    write_object_stream_pdf(
+        &broken,
+        &[
+            "<</Type/Catalog/Pages 2 0 R>>",
+            "<</Type/Pages/Kids[3 0 R 5 0 R]/Count 2/MediaBox[0 0 612 792]>>",
+            "<</Type/Page/Parent 2 0 R/Annots[null 4 0 R 42 0 R]>>",
+            "<</Type/Annot/Subtype/Link/Rect[72 700 92 720]/P 3 0 R>>",
+            "<</Type/Page/Parent 2 0 R/Annots 6 0 R>>",
+            "[null]",
+        ],
+        None,
+    );
+
+    rpdf()
+        .args(["validate", "--fix", "annots"])
+        .arg(&broken)
+        .arg("--dest")
+        .arg(&fixed)
+        .assert()
+        .success()
+        .stdout(contains(
+            "Removed 2 invalid entries from the annotations of page 1.",
+        ))
+        .stdout(contains(
+            "Removed 1 invalid entries from the annotations of page 2.",
+        ));
+
+    assert_snapshot!(pdf_snapshot(&fixed));
+}
@@ -0,0 +1,48 @@
+//! WebAssembly bindings of rpdf, so that web apps can merge annotations in the
+//! browser, without uploading documents.
+//!
+//! There is no filesystem in the browser, so documents are passed as byte
+//! buffers. The package is built with:
+//!
+//! ```bash
+//! wasm-pack build wasm --target web
+//! ```
+
+use anyhow::{Result, bail};
+use js_sys::Uint8Array;
+use rpdf::pdf::{self, SaveOptions, merge::AnnotationMerger};
+use wasm_bindgen::prelude::*;
+
+/// Annotation subtypes that are only kept from the first document, as by
+/// default in the command-line tool.
+const EXCLUDED_SUBTYPES: &[&str] = &["Link"];
+
+/// Merge the annotations of several copies of a document into the first one,
+/// and return the merged document.
+///
+/// # Errors
+///
+/// Fails if fewer than two documents are given, or if one cannot be read.
+pub fn merge<B: AsRef<[u8]>>(files: &[B]) -> Result<Vec<u8>> {
+    if files.len() < 2 {
+        bail!("At least two PDF files are needed, found {}.", files.len());
+    }
+    let exclude: Vec<String> = EXCLUDED_SUBTYPES.iter().map(ToString::to_string).collect();
+
+    let mut merger = AnnotationMerger::new(pdf::load_mem(files[0].as_ref(), None)?);
+    for file in &files[1..] {
+        merger.add(pdf::load_mem(file.as_ref(), None)?, &exclude, None);
+    }
+    let (mut document, _) = merger.finish(true);
+    pdf::save_to_vec(&mut document, SaveOptions::default())
+}
+
+/// Merge the annotations of several copies of a document into the first one,
+/// like `rpdf annotations merge`, and return the merged document.
+///
+/// Links are only kept from the first document. Errors are thrown as `Error`.
+#[wasm_bindgen]
+pub fn merge_annotations(files: Vec<Uint8Array>) -> Result<Vec<u8>, JsError> {
+    let files: Vec<Vec<u8>> = files.iter().map(Uint8Array::to_vec).collect();
+    merge(&files).map_err(|error| JsError::new(&format!("{error:#}")))
+}
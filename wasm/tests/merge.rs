@@ -0,0 +1,46 @@
+use lopdf::Document;
+use rpdf::pdf::{self, SaveOptions, annotations, fixtures::Fixture};
+use rpdf_wasm::merge;
+
+/// Return a fixture with four annotations, alternating highlights and links
+/// over two pages.
+fn fixture() -> Vec<u8> {
+    let mut document = Fixture {
+        pages: 2,
+        annotations: 4,
+        subtypes: vec!["Highlight".into(), "Link".into()],
+        ..Fixture::default()
+    }
+    .build();
+    pdf::save_to_vec(&mut document, SaveOptions::default()).unwrap()
+}
+
+/// Return the number of annotations of a subtype in a document.
+fn count(document: &Document, subtype: &str) -> usize {
+    annotations::subtype_counts(document, false)
+        .iter()
+        .filter_map(|counts| counts.get(subtype))
+        .sum()
+}
+
+#[test]
+fn merge_byte_buffers() {
+    let merged = merge(&[fixture(), fixture(), fixture()]).unwrap();
+    let document = pdf::load_mem(&merged, None).unwrap();
+
+    // Links are only kept from the first document.
+    assert_eq!(count(&document, "Highlight"), 6);
+    assert_eq!(count(&document, "Link"), 2);
+}
+
+#[test]
+fn merge_needs_two_documents() {
+    let error = merge(&[fixture()]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "At least two PDF files are needed, found 1."
+    );
+
+    let error = merge(&[fixture(), b"not a PDF".to_vec()]).unwrap_err();
+    assert_eq!(error.to_string(), "Failed to read PDF from memory");
+}